@@ -1,9 +1,18 @@
 extern crate gcc;
 
 
+use std::env;
 use std::process::Command;
 
 fn main() {
+    // simdcomp is only built with the `simd` feature, on x86 targets.
+    // Otherwise, tantivy falls back to its scalar bitpacking.
+    let target = env::var("TARGET").unwrap_or(String::new());
+    let is_x86 = target.starts_with("x86_64") || target.starts_with("i686") || target.starts_with("i586");
+    if env::var("CARGO_FEATURE_SIMD").is_err() || !is_x86 {
+        return;
+    }
+
     Command::new("make")
         .current_dir("cpp/simdcomp")
         .output()
@@ -55,7 +55,7 @@ fn run_example(index_path: &Path) -> tantivy::Result<()> {
     // to retrieve the body after the search.
     schema_builder.add_text_field("body", TEXT);
     
-    let schema = schema_builder.build(); 
+    let schema = try!(schema_builder.build());
 
 
 
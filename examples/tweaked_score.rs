@@ -0,0 +1,102 @@
+extern crate tantivy;
+extern crate tempdir;
+
+use std::path::Path;
+use tempdir::TempDir;
+use tantivy::Index;
+use tantivy::DocId;
+use tantivy::Score;
+use tantivy::U32FastFieldReader;
+use tantivy::schema::*;
+use tantivy::collector::TweakedScoreTopCollector;
+use tantivy::query::QueryParser;
+use tantivy::query::Query;
+
+fn main() {
+    // Let's create a temporary directory for the
+    // sake of this example
+    if let Ok(dir) = TempDir::new("tantivy_tweaked_score_example_dir") {
+        run_example(dir.path()).unwrap();
+        dir.close().unwrap();
+    }
+}
+
+
+fn run_example(index_path: &Path) -> tantivy::Result<()> {
+
+    // # Defining the schema
+    //
+    // Our documents have a title, which we want to search
+    // and to display, and a popularity, like a number
+    // of views or of sales.
+    let mut schema_builder = SchemaBuilder::default();
+    schema_builder.add_text_field("title", TEXT | STORED);
+
+    // The popularity is not searched, but it is read
+    // for each of the documents matching the query,
+    // so it has to be a fast field.
+    //
+    // Fast fields are stored in a column-oriented fashion,
+    // so that the value of a document can be accessed
+    // very quickly given its doc id.
+    //
+    // We also store it, to display it with the results.
+    schema_builder.add_u32_field("popularity", U32Options::default().set_fast().set_stored());
+
+    let schema = try!(schema_builder.build());
+    let title = schema.get_field("title").unwrap();
+    let popularity = schema.get_field("popularity").unwrap();
+
+
+    // # Indexing documents
+    let index = try!(Index::create(index_path, schema.clone()));
+    let mut index_writer = try!(index.writer(50_000_000));
+    let books = vec!(
+        ("The Old Man and the Sea", 1_200),
+        ("Sea", 3),
+        ("The Sea of Monsters", 85_000),
+        ("The Sea Wolf", 400),
+    );
+    for (book_title, book_popularity) in books {
+        let mut doc = Document::default();
+        doc.add_text(title, book_title);
+        doc.add_u32(popularity, book_popularity);
+        try!(index_writer.add_document(doc));
+    }
+    try!(index_writer.commit());
+
+
+    // # Searching
+    let searcher = index.searcher();
+    let query_parser = QueryParser::new(index.schema(), vec!(title));
+    let query = try!(query_parser.parse_query("sea"));
+
+    // Ranking by the query score alone would put the
+    // shortest title, "Sea", first, even though
+    // nobody reads it.
+    //
+    // The `TweakedScoreTopCollector` ranks the documents
+    // by a score computed by our own closure, given the
+    // score of the query and the fast field reader of
+    // the popularity.
+    //
+    // Taking the log of the popularity avoids the very
+    // popular documents to win no matter how poorly
+    // they match the query.
+    let popularity_score = |doc: DocId, score: Score, popularity_reader: &U32FastFieldReader| {
+        let popularity = popularity_reader.get(doc);
+        score * (1f32 + popularity as f32).ln()
+    };
+
+    // The fast field reader is fetched once for each segment,
+    // before the closure is called for its documents.
+    let mut top_collector = try!(TweakedScoreTopCollector::new(10, popularity, popularity_score));
+    try!(query.search(&searcher, &mut top_collector));
+
+    for (tweaked_score, doc_address) in top_collector.top_docs() {
+         let retrieved_doc = try!(searcher.doc(&doc_address));
+         println!("{} {}", tweaked_score, schema.to_json(&retrieved_doc));
+    }
+
+    Ok(())
+}
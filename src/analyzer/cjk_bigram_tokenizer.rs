@@ -0,0 +1,241 @@
+use std::ascii::AsciiExt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+use super::{Tokenizer, TokenStream, Token};
+
+
+/// Returns true iff `c` is a Han ideograph, a Hiragana or a Katakana.
+fn is_cjk(c: char) -> bool {
+    let c = c as u32;
+    // Hiragana, Katakana
+    (c >= 0x3040 && c <= 0x30FF) ||
+    // Katakana phonetic extensions
+    (c >= 0x31F0 && c <= 0x31FF) ||
+    // CJK unified ideographs extension A
+    (c >= 0x3400 && c <= 0x4DBF) ||
+    // CJK unified ideographs
+    (c >= 0x4E00 && c <= 0x9FFF) ||
+    // CJK compatibility ideographs
+    (c >= 0xF900 && c <= 0xFAFF) ||
+    // Halfwidth Katakana
+    (c >= 0xFF66 && c <= 0xFF9F) ||
+    // CJK unified ideographs extensions B to F,
+    // and the compatibility ideographs supplement
+    (c >= 0x20000 && c <= 0x2FA1F)
+}
+
+
+/// Tokenizer for the Chinese and Japanese texts.
+///
+/// These languages do not separate their words with spaces,
+/// so that the `SimpleTokenizer` emits a whole sentence as one
+/// single token. This tokenizer emits the overlapping bigrams of
+/// the runs of Han, Hiragana and Katakana characters instead, so that
+/// `東京タワー` emits `東京`, `京タ`, `タワ` and `ワー`. A run of one single
+/// character emits that character.
+///
+/// The other characters are handled as by the `SimpleTokenizer`:
+/// the text is split on the non-alphanumeric characters, and the
+/// ASCII characters are lowercased. A word is also split where a run
+/// of CJK characters starts, so that `iPhone用` emits `iphone` and `用`.
+///
+/// Each token has its own position, and the offsets of a bigram
+/// are the byte offsets of its two characters within the text.
+///
+/// The `QueryParser` tokenizes the query with the same tokenizer,
+/// so that a query for `東京` matches the documents containing
+/// the bigram `東京`.
+///
+/// It is registered under the name `cjk`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CjkBigramTokenizer;
+
+/// Token stream of the `CjkBigramTokenizer`.
+pub struct CjkBigramTokenStream<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    // byte offsets of the characters of the current CJK run,
+    // followed by the byte offset of its end.
+    cjk_offsets: Vec<usize>,
+    // index of the first character of the next bigram of the run.
+    cjk_index: usize,
+    token: Token,
+    num_tokens: u32,
+}
+
+impl<'a> CjkBigramTokenStream<'a> {
+
+    fn emit(&mut self, offset_from: usize, offset_to: usize) -> bool {
+        self.token.offset_from = offset_from;
+        self.token.offset_to = offset_to;
+        self.token.text.clear();
+        for c in self.text[offset_from..offset_to].chars() {
+            self.token.text.push(c.to_ascii_lowercase());
+        }
+        self.token.position = self.num_tokens;
+        self.num_tokens += 1;
+        true
+    }
+
+    fn end_offset(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|&(offset, _)| offset)
+            .unwrap_or(self.text.len())
+    }
+}
+
+impl<'a> TokenStream<'a> for CjkBigramTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if self.cjk_index + 2 < self.cjk_offsets.len() {
+            let (offset_from, offset_to) = (self.cjk_offsets[self.cjk_index], self.cjk_offsets[self.cjk_index + 2]);
+            self.cjk_index += 1;
+            return self.emit(offset_from, offset_to);
+        }
+        self.cjk_offsets.clear();
+        self.cjk_index = 0;
+        loop {
+            let (offset_from, c) = match self.chars.peek() {
+                Some(&offset_char) => offset_char,
+                None => { return false; }
+            };
+            if is_cjk(c) {
+                while let Some(&(offset, c)) = self.chars.peek() {
+                    if !is_cjk(c) {
+                        break;
+                    }
+                    self.cjk_offsets.push(offset);
+                    self.chars.next();
+                }
+                let offset_to = self.end_offset();
+                self.cjk_offsets.push(offset_to);
+                if self.cjk_offsets.len() == 2 {
+                    return self.emit(offset_from, offset_to);
+                }
+                let offset_to = self.cjk_offsets[2];
+                self.cjk_index = 1;
+                return self.emit(offset_from, offset_to);
+            }
+            self.chars.next();
+            if c.is_alphanumeric() {
+                while let Some(&(_, c)) = self.chars.peek() {
+                    if !c.is_alphanumeric() || is_cjk(c) {
+                        break;
+                    }
+                    self.chars.next();
+                }
+                let offset_to = self.end_offset();
+                return self.emit(offset_from, offset_to);
+            }
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.text = text;
+        self.chars = text.char_indices().peekable();
+        self.cjk_offsets.clear();
+        self.cjk_index = 0;
+        self.num_tokens = 0u32;
+    }
+}
+
+impl Tokenizer for CjkBigramTokenizer {
+    fn description(&self) -> String {
+        String::from("cjk_bigram")
+    }
+
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+        Box::new(CjkBigramTokenStream {
+            text: text,
+            chars: text.char_indices().peekable(),
+            cjk_offsets: Vec::new(),
+            cjk_index: 0,
+            token: Token::default(),
+            num_tokens: 0u32,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use query::{QueryParser, Query};
+    use collector::tests::TestCollector;
+    use analyzer::tests::tokens;
+
+    #[test]
+    fn test_cjk_bigram_tokenizer() {
+        // the CJK characters take 3 bytes.
+        assert_eq!(
+            tokens(&CjkBigramTokenizer, "東京タワー, Tokyo Tower!"),
+            vec!(
+                (String::from("東京"), 0, 6, 0),
+                (String::from("京タ"), 3, 9, 1),
+                (String::from("タワ"), 6, 12, 2),
+                (String::from("ワー"), 9, 15, 3),
+                (String::from("tokyo"), 17, 22, 4),
+                (String::from("tower"), 23, 28, 5),
+            ));
+        assert_eq!(
+            tokens(&CjkBigramTokenizer, "我用iPhone看新闻。"),
+            vec!(
+                (String::from("我用"), 0, 6, 0),
+                (String::from("iphone"), 6, 12, 1),
+                (String::from("看新"), 12, 18, 2),
+                (String::from("新闻"), 15, 21, 3),
+            ));
+        // a lone CJK character is emitted as is.
+        assert_eq!(
+            tokens(&CjkBigramTokenizer, "Rust 言語 と C"),
+            vec!(
+                (String::from("rust"), 0, 4, 0),
+                (String::from("言語"), 5, 11, 1),
+                (String::from("と"), 12, 15, 2),
+                (String::from("c"), 16, 17, 3),
+            ));
+        assert!(tokens(&CjkBigramTokenizer, "").is_empty());
+        assert!(tokens(&CjkBigramTokenizer, "、。 !").is_empty());
+    }
+
+    #[test]
+    fn test_cjk_bigram_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("cjk"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["東京タワーは高い", "京都の金閣寺", "Tokyo Towerは東京にある"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("東京"), vec!(0, 2));
+        assert_eq!(search("京都"), vec!(1));
+        assert_eq!(search("+金閣寺"), vec!(1));
+        assert_eq!(search("tower"), vec!(2));
+        assert!(search("大阪").is_empty());
+    }
+}
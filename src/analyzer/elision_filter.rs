@@ -0,0 +1,216 @@
+use std::ascii::AsciiExt;
+use std::sync::Arc;
+use super::{TokenFilter, TokenStream, Token};
+
+
+/// Elided articles and pronouns of the French language, as used
+/// by `ElisionFilter::default()`.
+pub const FRENCH_ELISIONS: &'static [&'static str] = &[
+    "l", "m", "t", "qu", "n", "s", "j", "d", "c",
+    "jusqu", "quoiqu", "lorsqu", "puisqu",
+];
+
+/// Elided articles and prepositions of the Italian language.
+pub const ITALIAN_ELISIONS: &'static [&'static str] = &[
+    "c", "l", "all", "dall", "dell", "nell", "sull", "coll", "pell",
+    "gl", "agl", "dagl", "degl", "negl", "sugl", "un", "m", "t", "s", "v", "d",
+];
+
+
+/// Returns the byte length of the apostrophe starting `text`, if any.
+///
+/// Both the ASCII apostrophe and the typographic
+/// apostrophe (U+2019) are recognized.
+fn apostrophe_len(text: &str) -> Option<usize> {
+    if text.starts_with('\'') {
+        Some(1)
+    }
+    else if text.starts_with('\u{2019}') {
+        Some('\u{2019}'.len_utf8())
+    }
+    else {
+        None
+    }
+}
+
+
+/// Token filter removing the elided prefixes of the tokens,
+/// so that the French `l'avion` is indexed as `avion`.
+///
+/// A prefix is removed if it is followed by an ASCII
+/// apostrophe or by a typographic apostrophe (`’`), and if
+/// it is one of the elisions of the filter, regardless of its case.
+/// `aujourd'hui` is therefore kept as is.
+///
+/// The `SimpleTokenizer` splits the text on the apostrophes,
+/// the filter should rather follow a tokenizer keeping them,
+/// e.g. `WhitespaceTokenizer.filter(ElisionFilter::default()).filter(LowerCaser)`.
+///
+/// The `offset_from` of the token is moved after the removed prefix,
+/// so that the offsets of the token delimit the remaining word.
+#[derive(Clone)]
+pub struct ElisionFilter {
+    elisions: Arc<Vec<String>>,
+}
+
+impl ElisionFilter {
+    /// Creates a filter removing the given elisions.
+    ///
+    /// The elisions are given without their apostrophe, e.g. `qu`,
+    /// a trailing apostrophe being ignored.
+    pub fn new(elisions: Vec<String>) -> ElisionFilter {
+        let elisions = elisions
+            .into_iter()
+            .map(|elision| String::from(elision.trim_right_matches(|c| c == '\'' || c == '\u{2019}')))
+            .filter(|elision| !elision.is_empty())
+            .collect();
+        ElisionFilter {
+            elisions: Arc::new(elisions),
+        }
+    }
+
+    /// Returns the byte length of the elided prefix of `word`,
+    /// apostrophe included, or 0 if it has none.
+    fn elided_len(&self, word: &str) -> usize {
+        for elision in self.elisions.iter() {
+            if word.len() > elision.len()
+                && word.is_char_boundary(elision.len())
+                && word[..elision.len()].eq_ignore_ascii_case(elision) {
+                if let Some(len) = apostrophe_len(&word[elision.len()..]) {
+                    if word.len() > elision.len() + len {
+                        return elision.len() + len;
+                    }
+                }
+            }
+        }
+        0
+    }
+}
+
+/// The default filter removes the `FRENCH_ELISIONS`.
+impl Default for ElisionFilter {
+    fn default() -> ElisionFilter {
+        ElisionFilter::new(
+            FRENCH_ELISIONS
+                .iter()
+                .map(|&elision| String::from(elision))
+                .collect())
+    }
+}
+
+/// Token stream of the `ElisionFilter`.
+pub struct ElisionTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    filter: ElisionFilter,
+}
+
+impl<'a> TokenStream<'a> for ElisionTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let elided_len = self.filter.elided_len(&self.tail.token().text);
+        if elided_len > 0 {
+            let token = self.tail.token_mut();
+            token.text.drain(..elided_len);
+            token.offset_from += elided_len;
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+    }
+}
+
+impl TokenFilter for ElisionFilter {
+    fn description(&self) -> String {
+        format!("elision({:?})", self.elisions)
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(ElisionTokenStream {
+            tail: token_stream,
+            filter: self.clone(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::{Tokenizer, WhitespaceTokenizer, LowerCaser};
+    use analyzer::tests::tokens;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use query::{QueryParser, Query};
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_elision_filter() {
+        let tokenizer = WhitespaceTokenizer.filter(ElisionFilter::default()).filter(LowerCaser);
+        // the typographic apostrophe takes 3 bytes.
+        let text = "L'avion d\u{2019}été qu'elle aujourd'hui l'";
+        let tokens = tokens(&tokenizer, text);
+        assert_eq!(tokens, vec!(
+            (String::from("avion"), 2, 7, 0),
+            (String::from("été"), 12, 17, 1),
+            (String::from("elle"), 21, 25, 2),
+            (String::from("aujourd'hui"), 26, 37, 3),
+            (String::from("l'"), 38, 40, 4),
+        ));
+        assert_eq!(&text[12..17], "été");
+        assert_eq!(&text[21..25], "elle");
+    }
+
+    #[test]
+    fn test_elision_filter_custom() {
+        let tokenizer = WhitespaceTokenizer.filter(ElisionFilter::new(vec!(String::from("dell'"), String::from("l"))));
+        let text = "dell\u{2019}Università l'Étà";
+        assert_eq!(tokens(&tokenizer, text), vec!(
+            (String::from("Università"), 7, 18, 0),
+            (String::from("Étà"), 21, 26, 1),
+        ));
+        assert_eq!(&text[7..18], "Università");
+        assert_eq!(&text[21..26], "Étà");
+    }
+
+    #[test]
+    fn test_elision_filter_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("fr"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("fr", WhitespaceTokenizer.filter(ElisionFilter::default()).filter(LowerCaser));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["L'avion décolle", "un avion", "l\u{2019}été"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("avion"), vec!(0, 1));
+        assert_eq!(search("\"l'avion\""), vec!(0, 1));
+        assert_eq!(search("\"d'été\""), vec!(2));
+    }
+}
@@ -1,8 +1,38 @@
 extern crate regex;
 
+mod tokenizer;
+mod tokenizer_manager;
+mod token_filter;
+mod stemmer;
+mod stop_word_filter;
+mod ngram_tokenizer;
+mod remove_long_filter;
+mod whitespace_tokenizer;
+mod synonym_filter;
+mod cjk_bigram_tokenizer;
+mod elision_filter;
+mod split_compound_filter;
+
+pub use self::tokenizer::{Token, TokenStream, Tokenizer};
+pub use self::tokenizer::{SimpleTokenStream, RawTokenizer, RawTokenStream};
+pub use self::ngram_tokenizer::{NgramTokenizer, NgramTokenStream};
+pub use self::whitespace_tokenizer::{WhitespaceTokenizer, WhitespaceTokenStream};
+pub use self::cjk_bigram_tokenizer::{CjkBigramTokenizer, CjkBigramTokenStream};
+pub use self::tokenizer_manager::{TokenizerManager, DEFAULT_TOKENIZER};
+pub use self::token_filter::{TokenFilter, ChainTokenizer};
+pub use self::token_filter::{LowerCaser, LowerCaserTokenStream, AsciiFoldingFilter, AsciiFoldingTokenStream};
+pub use self::stemmer::{Stemmer, StemmerTokenStream, Language};
+pub use self::stop_word_filter::{StopWordFilter, StopWordTokenStream, ENGLISH_STOP_WORDS};
+pub use self::remove_long_filter::{RemoveLongFilter, RemoveLongTokenStream, DEFAULT_MAX_TOKEN_LEN};
+pub use self::synonym_filter::{SynonymFilter, SynonymTokenStream};
+pub use self::elision_filter::{ElisionFilter, ElisionTokenStream, FRENCH_ELISIONS, ITALIAN_ELISIONS};
+pub use self::split_compound_filter::{SplitCompoundFilter, SplitCompoundTokenStream};
+
 use std::str::Chars;
 use std::ascii::AsciiExt;
 
+/// Iterator over the tokens of a text, as returned
+/// by `SimpleTokenizer::tokenize`.
 pub struct TokenIter<'a> {
     chars: Chars<'a>,
     term_buffer: String,
@@ -12,7 +42,9 @@ fn append_char_lowercase(c: char, term_buffer: &mut String) {
     term_buffer.push(c.to_ascii_lowercase());
 }
 
+/// Iterator whose items may borrow the iterator itself.
 pub trait StreamingIterator<'a, T> {
+    /// Returns the next item, if any.
     fn next(&'a mut self) -> Option<T>;
 }
 
@@ -52,14 +84,21 @@ impl<'a, 'b> StreamingIterator<'a, &'a str> for TokenIter<'b> {
     
 }
 
+/// Tokenizer splitting the text on non-alphanumeric characters,
+/// and lowercasing the ASCII characters.
+///
+/// This is the tokenizer used by default for the text fields.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct SimpleTokenizer;
 
 
 impl SimpleTokenizer {
+    /// Creates a `SimpleTokenizer`.
     pub fn new() -> SimpleTokenizer {
         SimpleTokenizer
     }
 
+    /// Returns an iterator over the tokens of `text`.
     pub fn tokenize<'a>(&self, text: &'a str) -> TokenIter<'a> {
         TokenIter {
            term_buffer: String::new(),
@@ -87,3 +126,21 @@ fn test_tokenizer_empty() {
     let mut term_reader = simple_tokenizer.tokenize("");
     assert_eq!(term_reader.next(), None);
 }
+
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::Tokenizer;
+
+    /// Returns the text, the offsets and the position of each
+    /// of the tokens emitted by `tokenizer` for `text`.
+    pub fn tokens(tokenizer: &Tokenizer, text: &str) -> Vec<(String, usize, usize, u32)> {
+        let mut token_stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while let Some(token) = token_stream.next() {
+            tokens.push((token.text.clone(), token.offset_from, token.offset_to, token.position));
+        }
+        tokens
+    }
+}
@@ -0,0 +1,242 @@
+use std::str::CharIndices;
+use super::{Tokenizer, TokenStream, Token};
+
+
+/// Tokenizer emitting the n-grams of the words of a text,
+/// for substring and autocomplete search.
+///
+/// The text is split into words on non-alphanumeric characters,
+/// as with the `SimpleTokenizer`, and each word emits all of its
+/// grams of `min_gram` to `max_gram` characters. With `edges_only`,
+/// only the grams starting at the beginning of the word are emitted,
+/// so that `search` emits `se`, `sea`, `sear`, ... for a `min_gram` of 2.
+///
+/// Words shorter than `min_gram` do not emit any gram.
+///
+/// # Positions
+///
+/// All of the grams of a word share the position of the word,
+/// so that the positions of the words are the same as with the
+/// `SimpleTokenizer`. The offsets of each gram are its own byte
+/// offsets within the text.
+///
+/// The grams are not lowercased, a `LowerCaser` can be appended
+/// with `Tokenizer::filter`.
+///
+/// The `QueryParser` tokenizes the query with the same tokenizer. For
+/// autocomplete, a term query on the typed prefix is usually preferable.
+#[derive(Clone, Copy, Debug)]
+pub struct NgramTokenizer {
+    min_gram: usize,
+    max_gram: usize,
+    edges_only: bool,
+}
+
+impl NgramTokenizer {
+    /// Creates a tokenizer emitting the grams of `min_gram` to
+    /// `max_gram` characters, both included.
+    ///
+    /// # Panics
+    /// If `min_gram` is 0, or greater than `max_gram`.
+    pub fn new(min_gram: usize, max_gram: usize, edges_only: bool) -> NgramTokenizer {
+        assert!(min_gram > 0, "min_gram must be greater than 0");
+        assert!(min_gram <= max_gram, "min_gram must not be greater than max_gram");
+        NgramTokenizer {
+            min_gram: min_gram,
+            max_gram: max_gram,
+            edges_only: edges_only,
+        }
+    }
+}
+
+/// Token stream of the `NgramTokenizer`.
+pub struct NgramTokenStream<'a> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+    min_gram: usize,
+    max_gram: usize,
+    edges_only: bool,
+    // byte offsets of the characters of the current word,
+    // followed by the byte offset of its end.
+    char_offsets: Vec<usize>,
+    gram_start: usize,
+    gram_len: usize,
+    num_words: u32,
+    token: Token,
+}
+
+impl<'a> NgramTokenStream<'a> {
+
+    /// Moves to the next word of the text.
+    /// Returns false iff there are no more words.
+    fn advance_word(&mut self) -> bool {
+        self.char_offsets.clear();
+        let mut word_end = self.text.len();
+        for (offset, c) in &mut self.chars {
+            if c.is_alphanumeric() {
+                self.char_offsets.push(offset);
+            }
+            else if !self.char_offsets.is_empty() {
+                word_end = offset;
+                break;
+            }
+        }
+        if self.char_offsets.is_empty() {
+            return false;
+        }
+        self.char_offsets.push(word_end);
+        self.gram_start = 0;
+        self.gram_len = self.min_gram;
+        self.token.position = self.num_words;
+        self.num_words += 1;
+        true
+    }
+
+    /// Returns the byte offsets of the next gram of the current word, if any.
+    fn next_gram(&mut self) -> Option<(usize, usize)> {
+        let num_chars = if self.char_offsets.is_empty() { 0 } else { self.char_offsets.len() - 1 };
+        loop {
+            if self.gram_start >= num_chars || (self.edges_only && self.gram_start > 0) {
+                return None;
+            }
+            if self.gram_len > self.max_gram || self.gram_start + self.gram_len > num_chars {
+                self.gram_start += 1;
+                self.gram_len = self.min_gram;
+                continue;
+            }
+            let offsets = (self.char_offsets[self.gram_start], self.char_offsets[self.gram_start + self.gram_len]);
+            self.gram_len += 1;
+            return Some(offsets);
+        }
+    }
+}
+
+impl<'a> TokenStream<'a> for NgramTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        loop {
+            if let Some((offset_from, offset_to)) = self.next_gram() {
+                self.token.offset_from = offset_from;
+                self.token.offset_to = offset_to;
+                self.token.text.clear();
+                self.token.text.push_str(&self.text[offset_from..offset_to]);
+                return true;
+            }
+            if !self.advance_word() {
+                return false;
+            }
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.text = text;
+        self.chars = text.char_indices();
+        self.char_offsets.clear();
+        self.gram_start = 0;
+        self.gram_len = self.min_gram;
+        self.num_words = 0u32;
+    }
+}
+
+impl Tokenizer for NgramTokenizer {
+    fn description(&self) -> String {
+        format!("ngram({}, {}, {})", self.min_gram, self.max_gram, self.edges_only)
+    }
+
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+        Box::new(NgramTokenStream {
+            text: text,
+            chars: text.char_indices(),
+            min_gram: self.min_gram,
+            max_gram: self.max_gram,
+            edges_only: self.edges_only,
+            char_offsets: Vec::new(),
+            gram_start: 0,
+            gram_len: self.min_gram,
+            num_words: 0u32,
+            token: Token::default(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::LowerCaser;
+    use analyzer::tests::tokens;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{MultiTermQuery, Query};
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_ngram_tokenizer() {
+        assert_eq!(
+            tokens(&NgramTokenizer::new(2, 3, false), "abcd é"),
+            vec!(
+                (String::from("ab"), 0, 2, 0),
+                (String::from("abc"), 0, 3, 0),
+                (String::from("bc"), 1, 3, 0),
+                (String::from("bcd"), 1, 4, 0),
+                (String::from("cd"), 2, 4, 0),
+            ));
+        assert_eq!(
+            tokens(&NgramTokenizer::new(1, 2, false), "ab, é"),
+            vec!(
+                (String::from("a"), 0, 1, 0),
+                (String::from("ab"), 0, 2, 0),
+                (String::from("b"), 1, 2, 0),
+                (String::from("é"), 4, 6, 1),
+            ));
+        assert_eq!(
+            tokens(&NgramTokenizer::new(2, 4, true), "Café search"),
+            vec!(
+                (String::from("Ca"), 0, 2, 0),
+                (String::from("Caf"), 0, 3, 0),
+                (String::from("Café"), 0, 5, 0),
+                (String::from("se"), 6, 8, 1),
+                (String::from("sea"), 6, 9, 1),
+                (String::from("sear"), 6, 10, 1),
+            ));
+        assert!(tokens(&NgramTokenizer::new(2, 4, true), "").is_empty());
+    }
+
+    #[test]
+    fn test_edge_ngram_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let edge_field = schema_builder.add_text_field("name_edge", TEXT.set_tokenizer("edge_ngram"));
+        let standard_field = schema_builder.add_text_field("name", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("edge_ngram", NgramTokenizer::new(2, 10, true).filter(LowerCaser));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(edge_field, "Search");
+            doc.add_text(standard_field, "Search");
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search = |term: Term| {
+            let query = MultiTermQuery::from(vec!(term));
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search(Term::from_field_text(edge_field, "sea")), vec!(0));
+        assert_eq!(search(Term::from_field_text(edge_field, "search")), vec!(0));
+        assert!(search(Term::from_field_text(edge_field, "ear")).is_empty());
+        assert!(search(Term::from_field_text(standard_field, "sea")).is_empty());
+        assert_eq!(search(Term::from_field_text(standard_field, "search")), vec!(0));
+    }
+}
@@ -0,0 +1,125 @@
+use super::{TokenFilter, TokenStream, Token};
+
+
+/// Maximum length, in bytes, of the tokens indexed
+/// by the `default` tokenizer.
+pub const DEFAULT_MAX_TOKEN_LEN: usize = 255;
+
+
+/// Token filter removing the tokens longer than a given number of bytes.
+///
+/// Over-long tokens, like base64 blobs pasted in a text field, carry
+/// no search value but inflate the term dictionary and the memory of
+/// the indexer. The `default` tokenizer removes the tokens longer than
+/// `DEFAULT_MAX_TOKEN_LEN` bytes. A field can use another limit by
+/// registering its own chain, e.g. `SimpleTokenizer.filter(RemoveLongFilter::limit(1_000))`.
+///
+/// As for the stop words, the removed tokens still consume their position.
+#[derive(Clone, Copy, Debug)]
+pub struct RemoveLongFilter {
+    length_limit: usize,
+}
+
+impl RemoveLongFilter {
+    /// Creates a filter removing the tokens of more than `length_limit` bytes.
+    pub fn limit(length_limit: usize) -> RemoveLongFilter {
+        RemoveLongFilter {
+            length_limit: length_limit,
+        }
+    }
+}
+
+/// Token stream of the `RemoveLongFilter`.
+pub struct RemoveLongTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    length_limit: usize,
+}
+
+impl<'a> TokenStream<'a> for RemoveLongTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        while self.tail.advance() {
+            if self.tail.token().text.len() <= self.length_limit {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+    }
+}
+
+impl TokenFilter for RemoveLongFilter {
+    fn description(&self) -> String {
+        format!("remove_long({})", self.length_limit)
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(RemoveLongTokenStream {
+            tail: token_stream,
+            length_limit: self.length_limit,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::{Tokenizer, SimpleTokenizer};
+    use analyzer::tests::tokens;
+    use core::{Index, SegmentComponent};
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use postings::{DocSet, Postings};
+
+    #[test]
+    fn test_remove_long_filter() {
+        let tokenizer = SimpleTokenizer.filter(RemoveLongFilter::limit(5));
+        assert_eq!(
+            tokens(&tokenizer, "hello wonderful world"),
+            vec!((String::from("hello"), 0, 5, 0), (String::from("world"), 16, 21, 2)));
+    }
+
+    #[test]
+    fn test_pathological_token() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let long_field = schema_builder.add_text_field("long", TEXT.set_tokenizer("no_limit"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("no_limit", SimpleTokenizer.filter(RemoveLongFilter::limit(1_000)));
+        let blob: String = (0..2_000_000).map(|i| if i % 3 == 0 { 'A' } else { 'z' }).collect();
+        let long_token: String = vec!('x'; 500).into_iter().collect();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(text_field, &format!("before {} after", blob));
+            doc.add_text(long_field, &format!("{} {}", long_token, blob));
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let segment_reader = &searcher.segment_readers()[0];
+        let positions = |term: Term| {
+            let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+            assert!(postings.advance());
+            postings.positions().to_vec()
+        };
+        assert_eq!(positions(Term::from_field_text(text_field, "before")), vec!(0));
+        assert_eq!(positions(Term::from_field_text(text_field, "after")), vec!(2));
+        assert!(segment_reader.read_postings_all_info(&Term::from_field_text(text_field, &blob.to_lowercase())).is_none());
+        // the field overrides the limit of the default tokenizer.
+        assert_eq!(positions(Term::from_field_text(long_field, &long_token)), vec!(0));
+        assert!(searcher.space_usage().component(SegmentComponent::TERMS) < 10_000);
+    }
+}
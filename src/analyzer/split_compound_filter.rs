@@ -0,0 +1,353 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+use super::{TokenFilter, TokenStream, Token};
+
+
+/// Token filter splitting the compound words, like the identifiers
+/// `getUserName` or `user_name`, into their sub-words.
+///
+/// Each token is emitted as is, followed by its sub-words, at the
+/// same position, so that `getUserName` emits `getUserName`, `get`,
+/// `User` and `Name`. The tokens that are not compound are left alone.
+///
+/// The sub-words are split :
+///
+/// * on the case changes, e.g. `getUser` or `HTTPServer`,
+/// * on the non-alphanumeric characters, which are removed,
+///   e.g. `user_name` or `utf-8`,
+/// * between letters and digits, e.g. `md5sum`.
+///
+/// Each rule can be disabled. With `set_catenate(true)`, the filter
+/// also emits the catenations of the consecutive sub-words, so that a
+/// search for `username` matches `getUserName`. A compound of `n`
+/// sub-words then emits about `n * n / 2` tokens.
+///
+/// The filter needs to see the case and the punctuation of the tokens.
+/// It should follow a tokenizer keeping them, and precede the `LowerCaser`,
+/// e.g. `WhitespaceTokenizer.filter(SplitCompoundFilter::default()).filter(LowerCaser)`.
+///
+/// The offsets of a sub-word are its own offsets within the text,
+/// as long as the previous filters did not rewrite the token.
+#[derive(Clone, Copy, Debug)]
+pub struct SplitCompoundFilter {
+    case_change: bool,
+    delimiters: bool,
+    digits: bool,
+    catenate: bool,
+}
+
+impl SplitCompoundFilter {
+
+    /// Sets whether the words are split on the case changes.
+    pub fn set_case_change(mut self, case_change: bool) -> SplitCompoundFilter {
+        self.case_change = case_change;
+        self
+    }
+
+    /// Sets whether the words are split on the
+    /// non-alphanumeric characters, like `_` or `-`.
+    pub fn set_delimiters(mut self, delimiters: bool) -> SplitCompoundFilter {
+        self.delimiters = delimiters;
+        self
+    }
+
+    /// Sets whether the words are split between letters and digits.
+    pub fn set_digits(mut self, digits: bool) -> SplitCompoundFilter {
+        self.digits = digits;
+        self
+    }
+
+    /// Sets whether the catenations of the consecutive
+    /// sub-words are emitted as well.
+    pub fn set_catenate(mut self, catenate: bool) -> SplitCompoundFilter {
+        self.catenate = catenate;
+        self
+    }
+
+    fn is_boundary(&self, previous: char, c: char, next: Option<char>) -> bool {
+        if self.case_change {
+            if previous.is_lowercase() && c.is_uppercase() {
+                return true;
+            }
+            // the last capital letter of an acronym starts the next word.
+            if previous.is_uppercase() && c.is_uppercase() && next.map(|next| next.is_lowercase()).unwrap_or(false) {
+                return true;
+            }
+        }
+        self.digits && previous.is_numeric() != c.is_numeric()
+    }
+
+    /// Fills `sub_words` with the byte ranges of the sub-words of `word`.
+    fn split(&self, word: &str, sub_words: &mut Vec<(usize, usize)>) {
+        sub_words.clear();
+        let mut sub_word_start: Option<usize> = None;
+        let mut previous: Option<char> = None;
+        let mut chars: Peekable<CharIndices> = word.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            if self.delimiters && !c.is_alphanumeric() {
+                if let Some(start) = sub_word_start.take() {
+                    sub_words.push((start, offset));
+                }
+                previous = None;
+                continue;
+            }
+            if let Some(previous) = previous {
+                let next = chars.peek().map(|&(_, next)| next);
+                if self.is_boundary(previous, c, next) {
+                    if let Some(start) = sub_word_start.take() {
+                        sub_words.push((start, offset));
+                    }
+                }
+            }
+            if sub_word_start.is_none() {
+                sub_word_start = Some(offset);
+            }
+            previous = Some(c);
+        }
+        if let Some(start) = sub_word_start {
+            sub_words.push((start, word.len()));
+        }
+    }
+}
+
+/// The default filter applies all of the splitting
+/// rules, and does not emit the catenations.
+impl Default for SplitCompoundFilter {
+    fn default() -> SplitCompoundFilter {
+        SplitCompoundFilter {
+            case_change: true,
+            delimiters: true,
+            digits: true,
+            catenate: false,
+        }
+    }
+}
+
+/// Token stream of the `SplitCompoundFilter`.
+pub struct SplitCompoundTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    filter: SplitCompoundFilter,
+    // text of the last token of the tail, and
+    // byte ranges of its sub-words.
+    word: String,
+    sub_words: Vec<(usize, usize)>,
+    // (first, last) sub-words of the tokens remaining
+    // to be emitted, in reverse order.
+    pending: Vec<(usize, usize)>,
+    // offset of the last token of the tail, if its
+    // text still matches the original text.
+    word_offset: Option<usize>,
+    sub_token: Token,
+    on_sub_token: bool,
+}
+
+impl<'a> TokenStream<'a> for SplitCompoundTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if let Some((first, last)) = self.pending.pop() {
+            self.sub_token.text.clear();
+            for &(from, to) in &self.sub_words[first..last + 1] {
+                self.sub_token.text.push_str(&self.word[from..to]);
+            }
+            if let Some(word_offset) = self.word_offset {
+                self.sub_token.offset_from = word_offset + self.sub_words[first].0;
+                self.sub_token.offset_to = word_offset + self.sub_words[last].1;
+            }
+            self.on_sub_token = true;
+            return true;
+        }
+        self.on_sub_token = false;
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token();
+        self.word.clear();
+        self.word.push_str(&token.text);
+        self.filter.split(&self.word, &mut self.sub_words);
+        let num_sub_words = self.sub_words.len();
+        if num_sub_words == 0 || (num_sub_words == 1 && self.sub_words[0] == (0, self.word.len())) {
+            // not a compound.
+            return true;
+        }
+        self.word_offset =
+            if token.offset_to - token.offset_from == self.word.len() {
+                Some(token.offset_from)
+            }
+            else {
+                None
+            };
+        self.sub_token.offset_from = token.offset_from;
+        self.sub_token.offset_to = token.offset_to;
+        self.sub_token.position = token.position;
+        if self.filter.catenate {
+            // the catenation of all of the sub-words is
+            // the token itself, unless delimiters were removed.
+            let sub_words_len: usize = self.sub_words.iter().map(|&(from, to)| to - from).sum();
+            if sub_words_len != self.word.len() {
+                self.pending.push((0, num_sub_words - 1));
+            }
+            for len in (2..num_sub_words).rev() {
+                for first in (0..num_sub_words - len + 1).rev() {
+                    self.pending.push((first, first + len - 1));
+                }
+            }
+        }
+        for sub_word in (0..num_sub_words).rev() {
+            self.pending.push((sub_word, sub_word));
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        if self.on_sub_token {
+            &self.sub_token
+        }
+        else {
+            self.tail.token()
+        }
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        if self.on_sub_token {
+            &mut self.sub_token
+        }
+        else {
+            self.tail.token_mut()
+        }
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+        self.pending.clear();
+        self.on_sub_token = false;
+    }
+}
+
+impl TokenFilter for SplitCompoundFilter {
+    fn description(&self) -> String {
+        format!("split_compound({}, {}, {}, {})", self.case_change, self.delimiters, self.digits, self.catenate)
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(SplitCompoundTokenStream {
+            tail: token_stream,
+            filter: *self,
+            word: String::new(),
+            sub_words: Vec::new(),
+            pending: Vec::new(),
+            word_offset: None,
+            sub_token: Token::default(),
+            on_sub_token: false,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::{Tokenizer, WhitespaceTokenizer, LowerCaser};
+    use analyzer::tests::tokens;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{QueryParser, Query};
+    use postings::{DocSet, Postings};
+    use collector::tests::TestCollector;
+
+    fn texts(filter: SplitCompoundFilter, text: &str) -> Vec<String> {
+        tokens(&WhitespaceTokenizer.filter(filter), text)
+            .into_iter()
+            .map(|(text, _, _, _)| text)
+            .collect()
+    }
+
+    #[test]
+    fn test_split_compound_filter() {
+        assert_eq!(
+            tokens(&WhitespaceTokenizer.filter(SplitCompoundFilter::default()), "call getUserName"),
+            vec!(
+                (String::from("call"), 0, 4, 0),
+                (String::from("getUserName"), 5, 16, 1),
+                (String::from("get"), 5, 8, 1),
+                (String::from("User"), 8, 12, 1),
+                (String::from("Name"), 12, 16, 1),
+            ));
+        assert_eq!(
+            tokens(&WhitespaceTokenizer.filter(SplitCompoundFilter::default()), "__user_name"),
+            vec!(
+                (String::from("__user_name"), 0, 11, 0),
+                (String::from("user"), 2, 6, 0),
+                (String::from("name"), 7, 11, 0),
+            ));
+        assert_eq!(
+            texts(SplitCompoundFilter::default(), "HTTPServer2Go"),
+            vec!("HTTPServer2Go", "HTTP", "Server", "2", "Go"));
+        assert_eq!(
+            texts(SplitCompoundFilter::default().set_digits(false), "HTTPServer2Go md5sum"),
+            vec!("HTTPServer2Go", "HTTP", "Server2Go", "md5sum"));
+        assert_eq!(
+            texts(SplitCompoundFilter::default().set_case_change(false), "getUserName user_name"),
+            vec!("getUserName", "user_name", "user", "name"));
+        assert_eq!(
+            texts(SplitCompoundFilter::default().set_delimiters(false), "user_name"),
+            vec!("user_name"));
+        assert_eq!(texts(SplitCompoundFilter::default(), "hello _"), vec!("hello", "_"));
+    }
+
+    #[test]
+    fn test_split_compound_filter_catenate() {
+        let filter = SplitCompoundFilter::default().set_catenate(true);
+        assert_eq!(
+            texts(filter, "getUserName"),
+            vec!("getUserName", "get", "User", "Name", "getUser", "UserName"));
+        assert_eq!(
+            tokens(&WhitespaceTokenizer.filter(filter), "user_name"),
+            vec!(
+                (String::from("user_name"), 0, 9, 0),
+                (String::from("user"), 0, 4, 0),
+                (String::from("name"), 5, 9, 0),
+                (String::from("username"), 0, 9, 0),
+            ));
+    }
+
+    #[test]
+    fn test_split_compound_filter_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("code"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        let split_compound_filter = SplitCompoundFilter::default().set_catenate(true);
+        index.tokenizers().register("code", WhitespaceTokenizer.filter(split_compound_filter).filter(LowerCaser));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["call getUserName now", "the user_name field", "a title"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        {
+            let segment_reader = &searcher.segment_readers()[0];
+            let mut postings = segment_reader.read_postings_all_info(&Term::from_field_text(text_field, "user")).unwrap();
+            assert!(postings.advance());
+            assert_eq!(postings.doc(), 0);
+            assert_eq!(postings.positions(), [1]);
+            // the sub-words do not count in the field norm.
+            assert_eq!(segment_reader.get_fieldnorm_reader(text_field).unwrap().get(0), 3);
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("username"), vec!(0, 1));
+        assert_eq!(search("user"), vec!(0, 1));
+        assert_eq!(search("getUserName"), vec!(0, 1));
+        assert_eq!(search("+getUserName"), vec!(0));
+        assert_eq!(search("title"), vec!(2));
+    }
+}
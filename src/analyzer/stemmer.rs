@@ -0,0 +1,453 @@
+use std::str;
+use super::{TokenFilter, TokenStream, Token};
+
+
+/// Languages supported by the `Stemmer`.
+///
+/// More languages are expected to be added, so that
+/// matching on a `Language` should include a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// English, stemmed with the Porter algorithm.
+    English,
+}
+
+
+/// Token filter reducing the words to their stem,
+/// so that `running` and `runs` both become `run`.
+///
+/// The stemmer expects lowercased tokens, and should therefore
+/// be appended after a `LowerCaser`, e.g.
+/// `SimpleTokenizer.filter(LowerCaser).filter(Stemmer::new(Language::English))`.
+///
+/// # Limitation
+///
+/// Stemming algorithms only strip and rewrite suffixes. Irregular
+/// forms are not related to their lemma : `ran` stays `ran`, and
+/// does not match `run`.
+#[derive(Clone, Copy, Debug)]
+pub struct Stemmer {
+    language: Language,
+}
+
+impl Stemmer {
+    /// Creates a stemmer for a given language.
+    pub fn new(language: Language) -> Stemmer {
+        Stemmer {
+            language: language,
+        }
+    }
+
+    /// Returns the language of the stemmer.
+    pub fn language(&self,) -> Language {
+        self.language
+    }
+}
+
+impl Default for Stemmer {
+    fn default() -> Stemmer {
+        Stemmer::new(Language::English)
+    }
+}
+
+/// Token stream of the `Stemmer`.
+pub struct StemmerTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    language: Language,
+    porter_stemmer: PorterStemmer,
+}
+
+impl<'a> TokenStream<'a> for StemmerTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        match self.language {
+            Language::English => {
+                self.porter_stemmer.stem(&mut self.tail.token_mut().text);
+            }
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+    }
+}
+
+impl TokenFilter for Stemmer {
+    fn description(&self) -> String {
+        format!("stemmer({:?})", self.language)
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(StemmerTokenStream {
+            tail: token_stream,
+            language: self.language,
+            porter_stemmer: PorterStemmer::default(),
+        })
+    }
+}
+
+
+/// State of the Porter stemmer.
+///
+/// `b` is the word being stemmed, and `b[..j]` the stem
+/// left when removing the suffix last matched by `ends`.
+///
+/// The buffer is reused from one word to the next,
+/// so that stemming a token does not allocate.
+#[derive(Default)]
+struct PorterStemmer {
+    b: Vec<u8>,
+    j: usize,
+}
+
+impl PorterStemmer {
+
+    /// Replaces an English word by its stem, following
+    /// the Porter stemming algorithm.
+    ///
+    /// Returns false if the word is left unchanged, which is
+    /// always the case for words that are not made of lowercase
+    /// ASCII letters, and for words of less than 3 letters.
+    fn stem(&mut self, word: &mut String) -> bool {
+        if word.len() <= 2 || !word.bytes().all(|b| b'a' <= b && b <= b'z') {
+            return false;
+        }
+        self.b.clear();
+        self.b.extend_from_slice(word.as_bytes());
+        self.j = 0;
+        self.step1ab();
+        self.step1c();
+        self.step2();
+        self.step3();
+        self.step4();
+        self.step5();
+        if self.b == word.as_bytes() {
+            return false;
+        }
+        word.clear();
+        // the stemmer only ever writes ASCII letters.
+        word.push_str(str::from_utf8(&self.b).expect("The stem is made of ASCII letters"));
+        true
+    }
+
+    /// Returns true iff `b[i]` is a consonant.
+    fn cons(&self, i: usize) -> bool {
+        match self.b[i] {
+            b'a' | b'e' | b'i' | b'o' | b'u' => false,
+            b'y' => i == 0 || !self.cons(i - 1),
+            _ => true,
+        }
+    }
+
+    /// Returns the number of vowel-consonant sequences in the stem.
+    ///
+    /// With `c` a sequence of consonants and `v` a sequence of vowels,
+    /// the stem is `[c](vc)^m[v]`.
+    fn m(&self,) -> usize {
+        let mut n = 0;
+        let mut i = 0;
+        loop {
+            if i >= self.j {
+                return n;
+            }
+            if !self.cons(i) {
+                break;
+            }
+            i += 1;
+        }
+        i += 1;
+        loop {
+            loop {
+                if i >= self.j {
+                    return n;
+                }
+                if self.cons(i) {
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            n += 1;
+            loop {
+                if i >= self.j {
+                    return n;
+                }
+                if !self.cons(i) {
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+
+    /// Returns true iff the stem contains a vowel.
+    fn vowel_in_stem(&self,) -> bool {
+        (0..self.j).any(|i| !self.cons(i))
+    }
+
+    /// Returns true iff `b[i - 1..i + 1]` is a double consonant.
+    fn doublec(&self, i: usize) -> bool {
+        i >= 1 && self.b[i] == self.b[i - 1] && self.cons(i)
+    }
+
+    /// Returns true iff `b[i - 2..i + 1]` is consonant-vowel-consonant,
+    /// the last consonant not being `w`, `x` or `y`.
+    ///
+    /// This is used to restore an `e` at the end of short words,
+    /// e.g. `hoping` becomes `hope`.
+    fn cvc(&self, i: usize) -> bool {
+        if i < 2 || !self.cons(i) || self.cons(i - 1) || !self.cons(i - 2) {
+            return false;
+        }
+        match self.b[i] {
+            b'w' | b'x' | b'y' => false,
+            _ => true,
+        }
+    }
+
+    /// Returns true iff the word ends with `suffix`,
+    /// in which case the stem is set to the rest of the word.
+    fn ends(&mut self, suffix: &str) -> bool {
+        if self.b.ends_with(suffix.as_bytes()) {
+            self.j = self.b.len() - suffix.len();
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    /// Replaces the suffix last matched by `ends` with `suffix`.
+    fn set_to(&mut self, suffix: &str) {
+        let j = self.j;
+        self.b.truncate(j);
+        self.b.extend_from_slice(suffix.as_bytes());
+    }
+
+    /// Replaces the suffix if the stem contains at
+    /// least one vowel-consonant sequence.
+    fn replace(&mut self, suffix: &str) {
+        if self.m() > 0 {
+            self.set_to(suffix);
+        }
+    }
+
+    /// Replaces the first matching suffix of `rules`.
+    fn replace_first(&mut self, rules: &[(&str, &str)]) {
+        for &(suffix, replacement) in rules {
+            if self.ends(suffix) {
+                self.replace(replacement);
+                return;
+            }
+        }
+    }
+
+    /// Removes the plurals and the `-ed` and `-ing` suffixes.
+    fn step1ab(&mut self,) {
+        if self.b.ends_with(b"s") {
+            if self.ends("sses") || self.ends("ies") {
+                let len = self.b.len();
+                self.b.truncate(len - 2);
+            }
+            else if !self.b.ends_with(b"ss") {
+                self.b.pop();
+            }
+        }
+        if self.ends("eed") {
+            if self.m() > 0 {
+                self.b.pop();
+            }
+        }
+        else if (self.ends("ed") || self.ends("ing")) && self.vowel_in_stem() {
+            let j = self.j;
+            self.b.truncate(j);
+            if self.ends("at") {
+                self.set_to("ate");
+            }
+            else if self.ends("bl") {
+                self.set_to("ble");
+            }
+            else if self.ends("iz") {
+                self.set_to("ize");
+            }
+            else {
+                let last = self.b.len() - 1;
+                self.j = self.b.len();
+                if self.doublec(last) {
+                    match self.b[last] {
+                        b'l' | b's' | b'z' => {}
+                        _ => {
+                            self.b.pop();
+                        }
+                    }
+                }
+                else if self.m() == 1 && self.cvc(last) {
+                    self.b.push(b'e');
+                }
+            }
+        }
+    }
+
+    /// Turns a terminal `y` into an `i` when there
+    /// is another vowel in the stem.
+    fn step1c(&mut self,) {
+        if self.ends("y") && self.vowel_in_stem() {
+            let last = self.b.len() - 1;
+            self.b[last] = b'i';
+        }
+    }
+
+    /// Maps double suffixes to single ones,
+    /// e.g. `-ization` becomes `-ize`.
+    fn step2(&mut self,) {
+        self.replace_first(&[
+            ("ational", "ate"), ("tional", "tion"),
+            ("enci", "ence"), ("anci", "ance"),
+            ("izer", "ize"),
+            ("bli", "ble"), ("alli", "al"), ("entli", "ent"), ("eli", "e"), ("ousli", "ous"),
+            ("ization", "ize"), ("ation", "ate"), ("ator", "ate"),
+            ("alism", "al"), ("iveness", "ive"), ("fulness", "ful"), ("ousness", "ous"),
+            ("aliti", "al"), ("iviti", "ive"), ("biliti", "ble"),
+            ("logi", "log"),
+        ]);
+    }
+
+    /// Deals with `-ic-`, `-full`, `-ness` etc.
+    fn step3(&mut self,) {
+        self.replace_first(&[
+            ("icate", "ic"), ("ative", ""), ("alize", "al"),
+            ("iciti", "ic"), ("ical", "ic"),
+            ("ful", ""), ("ness", ""),
+        ]);
+    }
+
+    /// Removes the suffixes of stems with
+    /// at least two vowel-consonant sequences.
+    fn step4(&mut self,) {
+        let suffixes = [
+            "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment", "ent",
+            "ion", "ou", "ism", "ate", "iti", "ous", "ive", "ize",
+        ];
+        for suffix in &suffixes {
+            if self.ends(suffix) {
+                if *suffix == "ion" {
+                    // `-ion` is only removed after `s` or `t`.
+                    let j = self.j;
+                    if j == 0 || (self.b[j - 1] != b's' && self.b[j - 1] != b't') {
+                        return;
+                    }
+                }
+                if self.m() > 1 {
+                    let j = self.j;
+                    self.b.truncate(j);
+                }
+                return;
+            }
+        }
+    }
+
+    /// Removes a final `-e`, and turns `-ll` into `-l`
+    /// for long enough stems.
+    fn step5(&mut self,) {
+        self.j = self.b.len();
+        if self.b.ends_with(b"e") {
+            let m = self.m();
+            if m > 1 || (m == 1 && !self.cvc(self.b.len() - 2)) {
+                self.b.pop();
+            }
+        }
+        let last = self.b.len() - 1;
+        if self.b[last] == b'l' && self.doublec(last) && self.m() > 1 {
+            self.b.pop();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::{Tokenizer, SimpleTokenizer, LowerCaser};
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use query::{QueryParser, Query};
+    use collector::tests::TestCollector;
+
+    fn stem(word: &str) -> String {
+        let mut stemmed = String::from(word);
+        PorterStemmer::default().stem(&mut stemmed);
+        stemmed
+    }
+
+    #[test]
+    fn test_porter_stem() {
+        assert_eq!(stem("runs"), "run");
+        assert_eq!(stem("running"), "run");
+        assert_eq!(stem("run"), "run");
+        // irregular forms are not stemmed to their lemma.
+        assert_eq!(stem("ran"), "ran");
+        assert_eq!(stem("caresses"), "caress");
+        assert_eq!(stem("ponies"), "poni");
+        assert_eq!(stem("hoping"), "hope");
+        assert_eq!(stem("falling"), "fall");
+        assert_eq!(stem("happy"), "happi");
+        assert_eq!(stem("relational"), "relat");
+        assert_eq!(stem("generalizations"), "gener");
+        assert_eq!(stem("hopeful"), "hope");
+        assert_eq!(stem("adjustment"), "adjust");
+        assert_eq!(stem("controlling"), "control");
+        assert_eq!(stem("is"), "is");
+        assert_eq!(stem("café"), "café");
+        let mut porter_stemmer = PorterStemmer::default();
+        assert!(!porter_stemmer.stem(&mut String::from("run")));
+        // the buffer of the stemmer is reused from one word to the next.
+        let mut word = String::from("generalizations");
+        assert!(porter_stemmer.stem(&mut word));
+        assert_eq!(word, "gener");
+        let mut word = String::from("hoping");
+        assert!(porter_stemmer.stem(&mut word));
+        assert_eq!(word, "hope");
+    }
+
+    #[test]
+    fn test_stemmer_query_parser() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("en_stem"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(LowerCaser).filter(Stemmer::new(Language::English)));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["I run", "She runs", "Running fast", "He ran"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("running"), vec!(0, 1, 2));
+        assert_eq!(search("RUNS"), vec!(0, 1, 2));
+        assert_eq!(search("ran"), vec!(3));
+    }
+}
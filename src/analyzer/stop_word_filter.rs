@@ -0,0 +1,155 @@
+use std::collections::{HashSet, BTreeSet};
+use std::sync::Arc;
+use super::{TokenFilter, TokenStream, Token};
+
+
+/// Stop words of the English language, as used by
+/// `StopWordFilter::default()`.
+pub const ENGLISH_STOP_WORDS: &'static [&'static str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by",
+    "for", "if", "in", "into", "is", "it",
+    "no", "not", "of", "on", "or", "such",
+    "that", "the", "their", "then", "there", "these",
+    "they", "this", "to", "was", "will", "with",
+];
+
+
+/// Token filter removing the stop words.
+///
+/// The words are compared with the tokens as they come out of
+/// the previous filters, so that the filter should typically be
+/// appended after a `LowerCaser`.
+///
+/// The removed tokens still consume their position, so that
+/// `president of france` indexes `france` two positions after
+/// `president`.
+#[derive(Clone)]
+pub struct StopWordFilter {
+    words: Arc<HashSet<String>>,
+}
+
+impl StopWordFilter {
+    /// Creates a filter removing the given words.
+    pub fn new(words: Vec<String>) -> StopWordFilter {
+        StopWordFilter {
+            words: Arc::new(words.into_iter().collect()),
+        }
+    }
+
+    /// Returns true iff `word` is one of the stop words of the filter.
+    pub fn is_stop_word(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+}
+
+/// The default filter removes the `ENGLISH_STOP_WORDS`.
+impl Default for StopWordFilter {
+    fn default() -> StopWordFilter {
+        StopWordFilter::new(
+            ENGLISH_STOP_WORDS
+                .iter()
+                .map(|&word| String::from(word))
+                .collect())
+    }
+}
+
+/// Token stream of the `StopWordFilter`.
+pub struct StopWordTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    words: Arc<HashSet<String>>,
+}
+
+impl<'a> TokenStream<'a> for StopWordTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        while self.tail.advance() {
+            if !self.words.contains(&self.tail.token().text) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+    }
+}
+
+impl TokenFilter for StopWordFilter {
+    fn description(&self) -> String {
+        let words: BTreeSet<&String> = self.words.iter().collect();
+        format!("stop_words({:?})", words)
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(StopWordTokenStream {
+            tail: token_stream,
+            words: self.words.clone(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::{Tokenizer, SimpleTokenizer};
+    use analyzer::tests::tokens;
+    use core::{Index, SegmentComponent};
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use postings::{DocSet, Postings};
+
+    #[test]
+    fn test_stop_word_filter_positions() {
+        let tokenizer = SimpleTokenizer.filter(StopWordFilter::default());
+        assert_eq!(
+            tokens(&tokenizer, "The President of France"),
+            vec!((String::from("president"), 4, 13, 1), (String::from("france"), 17, 23, 3)));
+        let custom_filter = StopWordFilter::new(vec!(String::from("france")));
+        assert!(custom_filter.is_stop_word("france"));
+        assert!(!custom_filter.is_stop_word("the"));
+    }
+
+    #[test]
+    fn test_stop_word_filter_postings_size() {
+        let index_postings_size = |tokenizer_name: &str| {
+            let mut schema_builder = SchemaBuilder::default();
+            let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer(tokenizer_name));
+            let index = Index::create_in_ram(schema_builder.build().unwrap());
+            index.tokenizers().register("stop_words", SimpleTokenizer.filter(StopWordFilter::default()));
+            {
+                let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+                for i in 0..1_000 {
+                    let mut doc = Document::default();
+                    doc.add_text(text_field, &format!("The president of the republic {} is in the palace of the city", i));
+                    index_writer.add_document(doc).unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+            let searcher = index.searcher();
+            {
+                let segment_reader = &searcher.segment_readers()[0];
+                let term = Term::from_field_text(text_field, "the");
+                assert_eq!(segment_reader.read_postings_all_info(&term).is_some(), tokenizer_name == "default");
+                let term = Term::from_field_text(text_field, "republic");
+                let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+                assert!(postings.advance());
+                assert_eq!(postings.positions(), [4]);
+            }
+            searcher.space_usage().component(SegmentComponent::POSTINGS)
+        };
+        let postings_size = index_postings_size("default");
+        let postings_size_without_stop_words = index_postings_size("stop_words");
+        assert!(postings_size_without_stop_words < postings_size,
+            "{} vs {}", postings_size_without_stop_words, postings_size);
+    }
+}
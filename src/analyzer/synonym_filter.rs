@@ -0,0 +1,242 @@
+use std::io;
+use std::io::Read;
+use std::collections::{HashMap, BTreeMap};
+use std::sync::Arc;
+use super::{TokenFilter, TokenStream, Token};
+
+
+/// Token filter emitting the synonyms of a token
+/// at the position of the token.
+///
+/// The filter is configured with groups of equivalent words. Each
+/// token belonging to a group is followed by the other words of its
+/// groups, with the same position and the same offsets, so that a
+/// document containing `tv` is also indexed as containing `television`.
+///
+/// The words are compared with the tokens as they come out of
+/// the previous filters, so that the filter should typically be
+/// appended after a `LowerCaser`.
+///
+/// As the `QueryParser` runs the same chain on the query, a query
+/// for one of the words searches for all of the words of its groups.
+#[derive(Clone)]
+pub struct SynonymFilter {
+    synonyms: Arc<HashMap<String, Vec<String>>>,
+}
+
+impl SynonymFilter {
+
+    /// Creates a filter from groups of equivalent words.
+    ///
+    /// A word may belong to several groups, in which case
+    /// its synonyms are the words of all of its groups.
+    pub fn new(groups: Vec<Vec<String>>) -> SynonymFilter {
+        let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+        for group in &groups {
+            for word in group {
+                let word_synonyms = synonyms
+                    .entry(word.clone())
+                    .or_insert_with(Vec::new);
+                for synonym in group {
+                    if synonym != word && !word_synonyms.contains(synonym) {
+                        word_synonyms.push(synonym.clone());
+                    }
+                }
+            }
+        }
+        SynonymFilter {
+            synonyms: Arc::new(synonyms),
+        }
+    }
+
+    /// Creates a filter from a list of groups, one group per line,
+    /// with the words of a group separated by commas, e.g.
+    ///
+    /// ```text
+    /// tv,television
+    /// car,automobile,auto
+    /// ```
+    ///
+    /// The words are trimmed. Empty lines and lines
+    /// starting with `#` are ignored.
+    pub fn from_lines(lines: &str) -> SynonymFilter {
+        let groups: Vec<Vec<String>> = lines
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split(',')
+                    .map(|word| word.trim())
+                    .filter(|word| !word.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .collect();
+        SynonymFilter::new(groups)
+    }
+
+    /// Reads a list of groups in the format of `SynonymFilter::from_lines`.
+    pub fn from_reader(reader: &mut Read) -> io::Result<SynonymFilter> {
+        let mut lines = String::new();
+        try!(reader.read_to_string(&mut lines));
+        Ok(SynonymFilter::from_lines(&lines))
+    }
+
+    /// Returns the synonyms of a word.
+    pub fn synonyms(&self, word: &str) -> &[String] {
+        self.synonyms
+            .get(word)
+            .map(|synonyms| &synonyms[..])
+            .unwrap_or(&[])
+    }
+}
+
+/// Token stream of the `SynonymFilter`.
+pub struct SynonymTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    synonyms: Arc<HashMap<String, Vec<String>>>,
+    // text of the last token of the tail, and number of
+    // its synonyms emitted so far, if it has synonyms.
+    word: String,
+    num_emitted_synonyms: usize,
+    synonym_token: Token,
+    on_synonym: bool,
+}
+
+impl<'a> TokenStream<'a> for SynonymTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if !self.word.is_empty() {
+            if let Some(synonym) = self.synonyms[&self.word].get(self.num_emitted_synonyms) {
+                self.synonym_token.text.clear();
+                self.synonym_token.text.push_str(synonym);
+                self.num_emitted_synonyms += 1;
+                self.on_synonym = true;
+                return true;
+            }
+        }
+        self.word.clear();
+        self.on_synonym = false;
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token();
+        if self.synonyms.contains_key(&token.text) {
+            self.word.push_str(&token.text);
+            self.num_emitted_synonyms = 0;
+            self.synonym_token.offset_from = token.offset_from;
+            self.synonym_token.offset_to = token.offset_to;
+            self.synonym_token.position = token.position;
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        if self.on_synonym {
+            &self.synonym_token
+        }
+        else {
+            self.tail.token()
+        }
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        if self.on_synonym {
+            &mut self.synonym_token
+        }
+        else {
+            self.tail.token_mut()
+        }
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+        self.word.clear();
+        self.on_synonym = false;
+    }
+}
+
+impl TokenFilter for SynonymFilter {
+    fn description(&self) -> String {
+        let synonyms: BTreeMap<&String, &Vec<String>> = self.synonyms.iter().collect();
+        format!("synonyms({:?})", synonyms)
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(SynonymTokenStream {
+            tail: token_stream,
+            synonyms: self.synonyms.clone(),
+            word: String::new(),
+            num_emitted_synonyms: 0,
+            synonym_token: Token::default(),
+            on_synonym: false,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::{Tokenizer, SimpleTokenizer};
+    use analyzer::tests::tokens;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{QueryParser, Query};
+    use postings::{DocSet, Postings};
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_synonym_filter() {
+        let synonym_filter = SynonymFilter::from_lines("# comment\n tv, television \n\ncar,auto\nauto,automobile\n");
+        assert_eq!(synonym_filter.synonyms("auto"), &[String::from("car"), String::from("automobile")]);
+        assert!(synonym_filter.synonyms("bike").is_empty());
+        let tokenizer = SimpleTokenizer.filter(synonym_filter);
+        assert_eq!(tokens(&tokenizer, "my tv is old"), vec!(
+            (String::from("my"), 0, 2, 0),
+            (String::from("tv"), 3, 5, 1),
+            (String::from("television"), 3, 5, 1),
+            (String::from("is"), 6, 8, 2),
+            (String::from("old"), 9, 12, 3),
+        ));
+    }
+
+    #[test]
+    fn test_synonym_filter_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("synonyms"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        let synonym_filter = SynonymFilter::from_reader(&mut &b"tv,television\n"[..]).unwrap();
+        index.tokenizers().register("synonyms", SimpleTokenizer.filter(synonym_filter));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["a new tv set", "the television", "a radio"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        {
+            let segment_reader = &searcher.segment_readers()[0];
+            let mut postings = segment_reader.read_postings_all_info(&Term::from_field_text(text_field, "television")).unwrap();
+            assert!(postings.advance());
+            assert_eq!(postings.doc(), 0);
+            assert_eq!(postings.positions(), [2]);
+            // synonyms do not count in the field norm.
+            assert_eq!(segment_reader.get_fieldnorm_reader(text_field).unwrap().get(0), 4);
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("tv"), vec!(0, 1));
+        assert_eq!(search("television"), vec!(0, 1));
+        assert_eq!(search("radio"), vec!(2));
+    }
+}
@@ -0,0 +1,425 @@
+use std::mem;
+use super::{Tokenizer, TokenStream, Token};
+
+
+/// A `TokenFilter` transforms the tokens emitted by a `TokenStream`.
+///
+/// Filters are chained onto a tokenizer with `Tokenizer::filter`,
+/// and the resulting `ChainTokenizer` is registered in the
+/// `TokenizerManager` like any other tokenizer.
+///
+/// As the `QueryParser` tokenizes the values of a field with the
+/// tokenizer of the field, the query terms go through the same
+/// chain of filters as the indexed terms.
+pub trait TokenFilter: Send + Sync {
+    /// Returns a description of the filter and of its parameters,
+    /// like `remove_long(40)`, appended to the description of
+    /// the tokenizer by the `ChainTokenizer`.
+    ///
+    /// Two filters transforming the tokens differently
+    /// must have different descriptions.
+    fn description(&self) -> String;
+
+    /// Wraps a token stream into a stream of the transformed tokens.
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a>;
+}
+
+
+/// Tokenizer applying a token filter to the tokens of another tokenizer.
+///
+/// It is created by `Tokenizer::filter`.
+pub struct ChainTokenizer<T: Tokenizer, F: TokenFilter> {
+    tokenizer: T,
+    token_filter: F,
+}
+
+impl<T: Tokenizer, F: TokenFilter> ChainTokenizer<T, F> {
+    /// Creates a tokenizer applying `token_filter`
+    /// to the tokens of `tokenizer`.
+    pub fn new(tokenizer: T, token_filter: F) -> ChainTokenizer<T, F> {
+        ChainTokenizer {
+            tokenizer: tokenizer,
+            token_filter: token_filter,
+        }
+    }
+}
+
+impl<T: Tokenizer, F: TokenFilter> Tokenizer for ChainTokenizer<T, F> {
+    fn description(&self) -> String {
+        format!("{}.{}", self.tokenizer.description(), self.token_filter.description())
+    }
+
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+        self.token_filter.transform(self.tokenizer.token_stream(text))
+    }
+}
+
+
+/// Token filter lowercasing the tokens.
+///
+/// Unlike the `SimpleTokenizer`, which only lowercases
+/// ASCII characters, it relies on unicode lowercasing,
+/// so that `CRÈME` becomes `crème`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LowerCaser;
+
+/// Token stream of the `LowerCaser`.
+pub struct LowerCaserTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    buffer: String,
+}
+
+impl<'a> TokenStream<'a> for LowerCaserTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        self.buffer.clear();
+        for c in self.tail.token().text.chars() {
+            self.buffer.extend(c.to_lowercase());
+        }
+        mem::swap(&mut self.tail.token_mut().text, &mut self.buffer);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+    }
+}
+
+impl TokenFilter for LowerCaser {
+    fn description(&self) -> String {
+        String::from("lowercase")
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(LowerCaserTokenStream {
+            tail: token_stream,
+            buffer: String::new(),
+        })
+    }
+}
+
+
+/// Token filter replacing the latin characters with diacritics
+/// and the latin ligatures by their ASCII equivalent,
+/// so that `café` becomes `cafe` and `œuvre` becomes `oeuvre`.
+///
+/// The characters of the Latin-1 Supplement and Latin Extended-A
+/// blocks are folded. The other characters are left unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsciiFoldingFilter;
+
+/// Token stream of the `AsciiFoldingFilter`.
+pub struct AsciiFoldingTokenStream<'a> {
+    tail: Box<TokenStream<'a> + 'a>,
+    buffer: String,
+}
+
+impl<'a> TokenStream<'a> for AsciiFoldingTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        if self.tail.token().text.chars().all(|c| (c as u32) < 128) {
+            // nothing to fold.
+            return true;
+        }
+        self.buffer.clear();
+        for c in self.tail.token().text.chars() {
+            match fold_char(c) {
+                Some(folded) => self.buffer.push_str(folded),
+                None => self.buffer.push(c),
+            }
+        }
+        mem::swap(&mut self.tail.token_mut().text, &mut self.buffer);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.tail.reset(text);
+    }
+}
+
+impl TokenFilter for AsciiFoldingFilter {
+    fn description(&self) -> String {
+        String::from("ascii_folding")
+    }
+
+    fn transform<'a>(&self, token_stream: Box<TokenStream<'a> + 'a>) -> Box<TokenStream<'a> + 'a> {
+        Box::new(AsciiFoldingTokenStream {
+            tail: token_stream,
+            buffer: String::new(),
+        })
+    }
+}
+
+/// Returns the ASCII equivalent of a non-ASCII character,
+/// if it has one.
+fn fold_char(c: char) -> Option<&'static str> {
+    let folded = match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĳ' => "IJ",
+        'ĳ' => "ij",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'ĸ' => "q",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' | 'ŋ' => "n",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "TH",
+        'þ' => "th",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => {
+            return None;
+        }
+    };
+    Some(folded)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use analyzer::{Tokenizer, TokenStream, SimpleTokenizer, RawTokenizer, WhitespaceTokenizer};
+    use analyzer::{LowerCaser, AsciiFoldingFilter, StopWordFilter, Stemmer, Language, SynonymFilter, SplitCompoundFilter};
+    use analyzer::RemoveLongFilter;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use query::{QueryParser, Query};
+    use collector::tests::TestCollector;
+    use test::Bencher;
+    use analyzer::tests::tokens;
+
+    #[test]
+    fn test_token_filters() {
+        assert_eq!(
+            tokens(&RawTokenizer.filter(LowerCaser), "CRÈME Brûlée"),
+            vec!((String::from("crème brûlée"), 0, 15, 0)));
+        assert_eq!(
+            tokens(&RawTokenizer.filter(AsciiFoldingFilter), "Œuvre Straße ÅÇ"),
+            vec!((String::from("OEuvre Strasse AC"), 0, 19, 0)));
+        assert_eq!(
+            tokens(&SimpleTokenizer.filter(LowerCaser).filter(AsciiFoldingFilter), "Le CAFÉ, là-bas"),
+            vec!(
+                (String::from("le"), 0, 2, 0),
+                (String::from("cafe"), 3, 8, 1),
+                (String::from("la"), 10, 13, 2),
+                (String::from("bas"), 14, 17, 3),
+            ));
+        // without the lowercaser, the folded uppercase letters stay uppercase.
+        assert_eq!(
+            tokens(&SimpleTokenizer.filter(AsciiFoldingFilter), "CAFÉ"),
+            vec!((String::from("cafE"), 0, 5, 0)));
+    }
+
+    fn remaining_tokens<'a>(token_stream: &mut TokenStream<'a>) -> Vec<(String, usize, usize, u32)> {
+        let mut tokens = Vec::new();
+        while let Some(token) = token_stream.next() {
+            tokens.push((token.text.clone(), token.offset_from, token.offset_to, token.position));
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_token_stream_reset() {
+        let tokenizer = WhitespaceTokenizer
+            .filter(SplitCompoundFilter::default())
+            .filter(LowerCaser)
+            .filter(SynonymFilter::from_lines("powershot,camera"));
+        let mut token_stream = tokenizer.token_stream("PowerShot G7");
+        // stops on the synonym of `powershot`, before its sub-words.
+        assert!(token_stream.advance());
+        assert!(token_stream.advance());
+        assert_eq!(token_stream.token().text, "camera");
+        token_stream.reset("Wi-Fi camera");
+        assert_eq!(
+            remaining_tokens(&mut *token_stream),
+            vec!(
+                (String::from("wi-fi"), 0, 5, 0),
+                (String::from("wi"), 0, 2, 0),
+                (String::from("fi"), 3, 5, 0),
+                (String::from("camera"), 6, 12, 1),
+                (String::from("powershot"), 6, 12, 1),
+            ));
+        token_stream.reset("");
+        assert!(remaining_tokens(&mut *token_stream).is_empty());
+        token_stream.reset("G7");
+        assert_eq!(remaining_tokens(&mut *token_stream), tokens(&tokenizer, "G7"));
+    }
+
+    #[test]
+    fn test_chain_tokenizer_description() {
+        let tokenizer = SimpleTokenizer
+            .filter(LowerCaser)
+            .filter(RemoveLongFilter::limit(40))
+            .filter(Stemmer::new(Language::English));
+        assert_eq!(tokenizer.description(), "simple.lowercase.remove_long(40).stemmer(English)");
+        assert_eq!(
+            WhitespaceTokenizer.filter(StopWordFilter::new(vec!(String::from("the"), String::from("a")))).description(),
+            "whitespace.stop_words({\"a\", \"the\"})");
+    }
+
+    #[test]
+    fn test_token_filters_query_parser() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("folded"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("folded", SimpleTokenizer.filter(LowerCaser).filter(AsciiFoldingFilter));
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["Un café crème", "a cafe", "a tea"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("CAFÉ"), vec!(0, 1));
+        assert_eq!(search("cafe"), vec!(0, 1));
+        assert_eq!(search("Crème"), vec!(0));
+    }
+
+    /// Returns about 1MB of text, in the range of the size of
+    /// the longest articles of Wikipedia.
+    fn article() -> String {
+        let words = [
+            "The", "running", "Café", "of", "généralisations", "is", "TV",
+            "relational", "and", "Hopefully", "adjustments", "in", "the",
+            "Controlling", "PONIES", "television", "à", "la", "carte,",
+        ];
+        let mut text = String::new();
+        let mut i = 0;
+        while text.len() < 1_000_000 {
+            text.push_str(words[(i * 7 + i / 3) % words.len()]);
+            text.push(if i % 17 == 0 { '\n' } else { ' ' });
+            i += 1;
+        }
+        text
+    }
+
+    /// The chain of all of the token filters, as a field would typically use.
+    fn analyzer_chain() -> Box<Tokenizer> {
+        Box::new(SimpleTokenizer
+            .filter(LowerCaser)
+            .filter(AsciiFoldingFilter)
+            .filter(StopWordFilter::default())
+            .filter(Stemmer::new(Language::English))
+            .filter(SynonymFilter::from_lines("tv,televis")))
+    }
+
+    #[bench]
+    fn bench_analyzer_chain(b: &mut Bencher) {
+        let tokenizer = analyzer_chain();
+        let text = article();
+        b.iter(|| {
+            let mut token_stream = tokenizer.token_stream(&text);
+            let mut num_bytes = 0;
+            while let Some(token) = token_stream.next() {
+                num_bytes += token.text.len();
+            }
+            num_bytes
+        });
+    }
+
+    /// Analyzes each line of the article as a value of a multi-valued
+    /// field, with a new chain of streams for each value.
+    #[bench]
+    fn bench_analyzer_chain_values_new_stream(b: &mut Bencher) {
+        let tokenizer = analyzer_chain();
+        let text = article();
+        b.iter(|| {
+            let mut num_bytes = 0;
+            for line in text.lines() {
+                let mut token_stream = tokenizer.token_stream(line);
+                while let Some(token) = token_stream.next() {
+                    num_bytes += token.text.len();
+                }
+            }
+            num_bytes
+        });
+    }
+
+    /// Same as `bench_analyzer_chain_values_new_stream`, but a single
+    /// chain of streams is reset from one value to the next, like
+    /// in the `PostingsWriter`. The difference is the cost of allocating
+    /// the streams and their buffers for each value.
+    #[bench]
+    fn bench_analyzer_chain_values_reset(b: &mut Bencher) {
+        let tokenizer = analyzer_chain();
+        let text = article();
+        b.iter(|| {
+            let mut num_bytes = 0;
+            let mut token_stream = tokenizer.token_stream("");
+            for line in text.lines() {
+                token_stream.reset(line);
+                while let Some(token) = token_stream.next() {
+                    num_bytes += token.text.len();
+                }
+            }
+            num_bytes
+        });
+    }
+}
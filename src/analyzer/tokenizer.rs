@@ -0,0 +1,278 @@
+use std::str::CharIndices;
+use std::ascii::AsciiExt;
+use super::SimpleTokenizer;
+use super::{TokenFilter, ChainTokenizer};
+
+
+/// Token emitted by a `TokenStream`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Token {
+    /// Byte offset of the first character of the token
+    /// within the original text.
+    pub offset_from: usize,
+    /// Byte offset right after the last character of the token
+    /// within the original text.
+    ///
+    /// Token filters may rewrite the text of a token, so that
+    /// its length may differ from `offset_to - offset_from`.
+    pub offset_to: usize,
+    /// Text of the token, as it will be indexed.
+    pub text: String,
+    /// Position of the token within the text, expressed in tokens.
+    ///
+    /// Positions must not decrease along the stream.
+    /// Two tokens may share a position (e.g. synonyms),
+    /// and positions may skip (e.g. removed stop words).
+    pub position: u32,
+    /// Payload of the token, recorded along with its position
+    /// in the fields indexed with `TokenizedWithFreqPositionAndPayload`.
+    ///
+    /// It defaults to 0. Tokenizers and token filters may set it,
+    /// e.g. to a weight for the tokens that appeared in bold.
+    pub payload: u32,
+}
+
+
+/// Stream of the tokens of a text.
+///
+/// The stream owns a single token buffer, that is
+/// updated on each call to `advance`.
+///
+/// Tokenizers and token filters are expected to reuse the
+/// `text` of that buffer, and their own working buffers, from
+/// one token to the next, so that the analysis of a text does
+/// not allocate a `String` per token. The `PostingsWriter`
+/// likewise copies each token into a single reused `Term`.
+///
+/// A stream can also be `reset` on another text of the same
+/// lifetime, keeping all of these buffers, so that the values of
+/// a multi-valued field go through a single chain of streams.
+pub trait TokenStream<'a> {
+
+    /// Moves to the next token.
+    /// Returns false iff there are no more tokens.
+    fn advance(&mut self) -> bool;
+
+    /// Returns the current token.
+    ///
+    /// It only makes sense after `advance` returned true.
+    fn token(&self) -> &Token;
+
+    /// Returns the current token, mutably.
+    ///
+    /// Token filters rely on it to transform
+    /// the tokens in place.
+    fn token_mut(&mut self) -> &mut Token;
+
+    /// Restarts the stream on `text`, as if it had just been
+    /// returned by `Tokenizer::token_stream(text)`.
+    ///
+    /// Token filters reset their own state, and forward
+    /// the call to the stream they wrap.
+    fn reset(&mut self, text: &'a str);
+
+    /// Advances the stream and returns the next token, if any.
+    fn next(&mut self) -> Option<&Token> {
+        if self.advance() {
+            Some(self.token())
+        }
+        else {
+            None
+        }
+    }
+}
+
+
+/// A `Tokenizer` splits a text into a stream of tokens.
+///
+/// Tokenizers are registered by name in the `TokenizerManager`
+/// of the index, and associated to a text field with
+/// `TextOptions::set_tokenizer`.
+///
+/// # Positions
+///
+/// When indexing a text field, the `PostingsWriter` suscribes
+/// each token at the position of the value within the field
+/// plus `token.position`. The next value of the field starts
+/// right after the last position of the stream, plus
+/// the position gap of the field.
+///
+/// The field norm is the number of tokens emitted, not counting
+/// the tokens sharing the position of the previous token (e.g. synonyms
+/// or n-grams of the same word).
+pub trait Tokenizer: Send + Sync {
+    /// Returns a description of the tokenizer and of its parameters,
+    /// like `simple` or `ngram(2, 3, false)`.
+    ///
+    /// The analyzer fingerprints of the fields are computed from it,
+    /// see `Index::analyzer_fingerprint`, so that two tokenizers
+    /// analyzing a text differently must have different descriptions.
+    fn description(&self) -> String;
+
+    /// Returns the stream of the tokens of `text`.
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a>;
+
+    /// Appends a token filter to the tokenizer.
+    ///
+    /// Filters are applied in the order they are appended, e.g.
+    /// `SimpleTokenizer.filter(LowerCaser).filter(AsciiFoldingFilter)`.
+    fn filter<F: TokenFilter>(self, token_filter: F) -> ChainTokenizer<Self, F>
+        where Self: Sized {
+        ChainTokenizer::new(self, token_filter)
+    }
+}
+
+
+/// Token stream of the `SimpleTokenizer`.
+pub struct SimpleTokenStream<'a> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+    token: Token,
+    num_tokens: u32,
+}
+
+impl<'a> TokenStream<'a> for SimpleTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        self.token.offset_to = self.text.len();
+        for (offset, c) in &mut self.chars {
+            if c.is_alphanumeric() {
+                if self.token.text.is_empty() {
+                    self.token.offset_from = offset;
+                }
+                self.token.text.push(c.to_ascii_lowercase());
+            }
+            else if !self.token.text.is_empty() {
+                self.token.offset_to = offset;
+                break;
+            }
+        }
+        if self.token.text.is_empty() {
+            return false;
+        }
+        self.token.position = self.num_tokens;
+        self.num_tokens += 1;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.text = text;
+        self.chars = text.char_indices();
+        self.num_tokens = 0u32;
+    }
+}
+
+impl Tokenizer for SimpleTokenizer {
+    fn description(&self) -> String {
+        String::from("simple")
+    }
+
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+        Box::new(SimpleTokenStream {
+            text: text,
+            chars: text.char_indices(),
+            token: Token::default(),
+            num_tokens: 0u32,
+        })
+    }
+}
+
+
+/// Tokenizer emitting the whole text as one single token,
+/// without any normalization.
+///
+/// An empty text does not emit any token.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawTokenizer;
+
+/// Token stream of the `RawTokenizer`.
+pub struct RawTokenStream {
+    token: Token,
+    has_token: bool,
+}
+
+impl<'a> TokenStream<'a> for RawTokenStream {
+
+    fn advance(&mut self) -> bool {
+        let has_token = self.has_token;
+        self.has_token = false;
+        has_token
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.token.offset_from = 0;
+        self.token.offset_to = text.len();
+        self.token.text.clear();
+        self.token.text.push_str(text);
+        self.token.position = 0u32;
+        self.has_token = !text.is_empty();
+    }
+}
+
+impl Tokenizer for RawTokenizer {
+    fn description(&self) -> String {
+        String::from("raw")
+    }
+
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+        Box::new(RawTokenStream {
+            token: Token {
+                offset_from: 0,
+                offset_to: text.len(),
+                text: String::from(text),
+                position: 0u32,
+                payload: 0u32,
+            },
+            has_token: !text.is_empty(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use analyzer::SimpleTokenizer;
+    use analyzer::tests::tokens;
+
+    #[test]
+    fn test_simple_token_stream() {
+        assert_eq!(
+            tokens(&SimpleTokenizer, "Hello, happy tax payer!"),
+            vec!(
+                (String::from("hello"), 0, 5, 0),
+                (String::from("happy"), 7, 12, 1),
+                (String::from("tax"), 13, 16, 2),
+                (String::from("payer"), 17, 22, 3),
+            ));
+        assert!(tokens(&SimpleTokenizer, "").is_empty());
+        assert_eq!(
+            tokens(&SimpleTokenizer, "¡Olé, toro"),
+            vec!((String::from("olé"), 2, 6, 0), (String::from("toro"), 8, 12, 1)));
+        assert!(tokens(&SimpleTokenizer, " ,; ").is_empty());
+    }
+
+    #[test]
+    fn test_raw_token_stream() {
+        assert_eq!(tokens(&RawTokenizer, "AB-123/X"), vec!((String::from("AB-123/X"), 0, 8, 0)));
+        assert!(tokens(&RawTokenizer, "").is_empty());
+    }
+}
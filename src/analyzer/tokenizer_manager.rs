@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use super::{Tokenizer, SimpleTokenizer, RawTokenizer, WhitespaceTokenizer, CjkBigramTokenizer};
+use super::{RemoveLongFilter, DEFAULT_MAX_TOKEN_LEN};
+
+
+/// Name of the tokenizer used by the text fields
+/// that do not define one.
+pub const DEFAULT_TOKENIZER: &'static str = "default";
+
+
+/// The `TokenizerManager` holds the tokenizers of an index,
+/// by name.
+///
+/// It is shared by all of the clones of the `Index`, so
+/// that a tokenizer registered on the index is visible to
+/// its writers.
+///
+/// The following tokenizers are registered by default.
+///
+/// * `default`: the `SimpleTokenizer`, removing the tokens
+///   longer than `DEFAULT_MAX_TOKEN_LEN` bytes
+/// * `raw`: the `RawTokenizer`
+/// * `whitespace`: the `WhitespaceTokenizer`
+/// * `cjk`: the `CjkBigramTokenizer`, for the Chinese and Japanese texts
+///
+/// A chain of token filters is configured for a field by
+/// registering it under a name of its own, e.g.
+/// `SimpleTokenizer.filter(LowerCaser).filter(AsciiFoldingFilter)`.
+#[derive(Clone)]
+pub struct TokenizerManager {
+    tokenizers: Arc<RwLock<HashMap<String, Arc<Tokenizer>>>>,
+}
+
+impl TokenizerManager {
+
+    /// Registers a tokenizer under a given name.
+    ///
+    /// A tokenizer previously registered under
+    /// the same name is replaced.
+    pub fn register<T: Tokenizer + 'static>(&self, tokenizer_name: &str, tokenizer: T) {
+        self.tokenizers
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .insert(String::from(tokenizer_name), Arc::new(tokenizer));
+    }
+
+    /// Returns the tokenizer registered under a given name, if any.
+    pub fn get(&self, tokenizer_name: &str) -> Option<Arc<Tokenizer>> {
+        self.tokenizers
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .get(tokenizer_name)
+            .cloned()
+    }
+}
+
+impl Default for TokenizerManager {
+    fn default() -> TokenizerManager {
+        let manager = TokenizerManager {
+            tokenizers: Arc::new(RwLock::new(HashMap::new())),
+        };
+        manager.register(DEFAULT_TOKENIZER, SimpleTokenizer.filter(RemoveLongFilter::limit(DEFAULT_MAX_TOKEN_LEN)));
+        manager.register("raw", RawTokenizer);
+        manager.register("whitespace", WhitespaceTokenizer);
+        manager.register("cjk", CjkBigramTokenizer);
+        manager
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use analyzer::{Tokenizer, TokenStream, Token};
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{QueryParser, Query};
+    use collector::tests::TestCollector;
+    use postings::{DocSet, Postings};
+    use std::str::SplitWhitespace;
+
+    /// Splits on whitespaces and removes the word `the`,
+    /// without reusing its position.
+    struct StopWordTokenizer;
+
+    struct StopWordTokenStream<'a> {
+        words: SplitWhitespace<'a>,
+        token: Token,
+        num_words: u32,
+    }
+
+    impl<'a> TokenStream<'a> for StopWordTokenStream<'a> {
+        fn advance(&mut self) -> bool {
+            while let Some(word) = self.words.next() {
+                let position = self.num_words;
+                self.num_words += 1;
+                let word = word.to_lowercase();
+                if word != "the" {
+                    self.token.text = word;
+                    self.token.position = position;
+                    return true;
+                }
+            }
+            false
+        }
+
+        fn token(&self) -> &Token {
+            &self.token
+        }
+
+        fn token_mut(&mut self) -> &mut Token {
+            &mut self.token
+        }
+
+        fn reset(&mut self, text: &'a str) {
+            self.words = text.split_whitespace();
+            self.num_words = 0u32;
+        }
+    }
+
+    impl Tokenizer for StopWordTokenizer {
+        fn description(&self) -> String {
+            String::from("stop_words")
+        }
+
+        fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+            Box::new(StopWordTokenStream {
+                words: text.split_whitespace(),
+                token: Token::default(),
+                num_words: 0u32,
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_tokenizer() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("stop_words"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("stop_words", StopWordTokenizer);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(text_field, "The quick-brown fox");
+            doc.add_text(text_field, "the dog");
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let segment_reader = &searcher.segment_readers()[0];
+        {
+            let term = Term::from_field_text(text_field, "the");
+            assert!(segment_reader.read_postings_all_info(&term).is_none());
+        }
+        let positions = |text: &str| {
+            let term = Term::from_field_text(text_field, text);
+            let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+            assert!(postings.advance());
+            postings.positions().to_vec()
+        };
+        assert_eq!(positions("quick-brown"), vec!(1));
+        assert_eq!(positions("fox"), vec!(2));
+        // the second value starts after the 3 positions
+        // of the first value and the position gap.
+        assert_eq!(positions("dog"), vec!(5));
+        assert_eq!(segment_reader.get_fieldnorm_reader(text_field).unwrap().get(0), 3);
+        {
+            let query_parser = QueryParser::for_index(&index, vec!(text_field));
+            let query = query_parser.parse_query("\"Quick-Brown\"").unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(0));
+        }
+        {
+            let query_parser = QueryParser::new(index.schema(), vec!(text_field));
+            assert!(query_parser.parse_query("fox").is_err());
+        }
+    }
+}
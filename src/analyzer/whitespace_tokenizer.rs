@@ -0,0 +1,141 @@
+use std::str::CharIndices;
+use super::{Tokenizer, TokenStream, Token};
+
+
+/// Tokenizer splitting the text on unicode whitespaces only,
+/// including tabs and non-breaking spaces.
+///
+/// Unlike the `SimpleTokenizer`, the punctuation is kept, so that
+/// `C++`, `.net` or `foo_bar` are indexed as is. The tokens are
+/// not lowercased either.
+///
+/// It is registered under the name `whitespace`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WhitespaceTokenizer;
+
+/// Token stream of the `WhitespaceTokenizer`.
+pub struct WhitespaceTokenStream<'a> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+    token: Token,
+    num_tokens: u32,
+}
+
+impl<'a> TokenStream<'a> for WhitespaceTokenStream<'a> {
+
+    fn advance(&mut self) -> bool {
+        let mut offset_from = None;
+        let mut offset_to = self.text.len();
+        for (offset, c) in &mut self.chars {
+            if !c.is_whitespace() {
+                if offset_from.is_none() {
+                    offset_from = Some(offset);
+                }
+            }
+            else if offset_from.is_some() {
+                offset_to = offset;
+                break;
+            }
+        }
+        match offset_from {
+            Some(offset_from) => {
+                self.token.offset_from = offset_from;
+                self.token.offset_to = offset_to;
+                self.token.text.clear();
+                self.token.text.push_str(&self.text[offset_from..offset_to]);
+                self.token.position = self.num_tokens;
+                self.num_tokens += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+
+    fn reset(&mut self, text: &'a str) {
+        self.text = text;
+        self.chars = text.char_indices();
+        self.num_tokens = 0u32;
+    }
+}
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn description(&self) -> String {
+        String::from("whitespace")
+    }
+
+    fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+        Box::new(WhitespaceTokenStream {
+            text: text,
+            chars: text.char_indices(),
+            token: Token::default(),
+            num_tokens: 0u32,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use query::{QueryParser, Query};
+    use collector::tests::TestCollector;
+    use analyzer::tests::tokens;
+
+    #[test]
+    fn test_whitespace_tokenizer() {
+        assert_eq!(
+            tokens(&WhitespaceTokenizer, "C++  .net\tfoo_bar"),
+            vec!(
+                (String::from("C++"), 0, 3, 0),
+                (String::from(".net"), 5, 9, 1),
+                (String::from("foo_bar"), 10, 17, 2),
+            ));
+        // the non-breaking space takes 2 bytes.
+        assert_eq!(
+            tokens(&WhitespaceTokenizer, " \t café\u{a0}crème \n"),
+            vec!(
+                (String::from("café"), 3, 8, 0),
+                (String::from("crème"), 10, 16, 1),
+            ));
+        assert!(tokens(&WhitespaceTokenizer, "").is_empty());
+        assert!(tokens(&WhitespaceTokenizer, " \t\u{a0}\n").is_empty());
+    }
+
+    #[test]
+    fn test_whitespace_tokenizer_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("whitespace"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["I like C++", "I like C#", "I like C"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("\"C++\""), vec!(0));
+        assert_eq!(search("\"C#\""), vec!(1));
+        assert_eq!(search("C"), vec!(2));
+    }
+}
@@ -0,0 +1,51 @@
+use Result;
+use std::collections::HashMap;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use core::SegmentId;
+use common::BitSet;
+
+
+/// `BitSetCollector` records the documents matching the query
+/// in one `BitSet` per segment, keyed by the id of the segment.
+///
+/// The bitsets can then be replayed as a filter of later searches
+/// on the same segments, with a `PreComputedFilterQuery`, rather
+/// than running the query again.
+#[derive(Default)]
+pub struct BitSetCollector {
+    bitsets: Vec<(SegmentId, BitSet)>,
+}
+
+impl BitSetCollector {
+
+    /// Returns the bitset of the documents of each
+    /// of the segments the collector went through.
+    pub fn bitsets(&self,) -> &[(SegmentId, BitSet)] {
+        &self.bitsets
+    }
+
+    /// Consumes the collector, and returns the bitset
+    /// of each segment, keyed by the id of the segment.
+    pub fn into_bitsets(self,) -> HashMap<SegmentId, BitSet> {
+        self.bitsets.into_iter().collect()
+    }
+}
+
+impl Collector for BitSetCollector {
+
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.bitsets.push((reader.segment_id(), BitSet::with_max_doc(reader.max_doc())));
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        self.bitsets
+            .last_mut()
+            .expect("collect called before set_segment")
+            .1
+            .insert(scored_doc.doc());
+    }
+}
@@ -1,8 +1,10 @@
 use collector::Collector;
 use SegmentLocalId;
 use SegmentReader;
-use std::io;
+use Result;
+use std::f32;
 use ScoredDoc;
+use Score;
 
 
 /// Collector that does nothing.
@@ -11,11 +13,22 @@ use ScoredDoc;
 pub struct DoNothingCollector;
 impl Collector for DoNothingCollector {
     #[inline]
-    fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> io::Result<()> {
+    fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> Result<()> {
         Ok(())
     }
     #[inline]
     fn collect(&mut self, _: ScoredDoc) {}
+
+    // no document changes the result of the collector.
+    #[inline]
+    fn min_score(&self,) -> Option<Score> {
+        Some(f32::INFINITY)
+    }
+
+    #[inline]
+    fn should_terminate(&self,) -> bool {
+        true
+    }
 }
 
 /// Zero-cost abstraction used to collect on multiple collectors.
@@ -37,7 +50,7 @@ impl<Left: Collector, Right: Collector> ChainedCollector<Left, Right> {
 }
 
 impl<Left: Collector, Right: Collector> Collector for ChainedCollector<Left, Right> {
-    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> io::Result<()> {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
         try!(self.left.set_segment(segment_local_id, segment));
         try!(self.right.set_segment(segment_local_id, segment));
         Ok(())
@@ -47,6 +60,23 @@ impl<Left: Collector, Right: Collector> Collector for ChainedCollector<Left, Rig
         self.left.collect(scored_doc);
         self.right.collect(scored_doc);
     }
+
+    fn min_score(&self,) -> Option<Score> {
+        match (self.left.min_score(), self.right.min_score()) {
+            (Some(left_min_score), Some(right_min_score)) => Some(left_min_score.min(right_min_score)),
+            _ => None,
+        }
+    }
+
+    fn should_terminate(&self,) -> bool {
+        self.left.should_terminate() && self.right.should_terminate()
+    }
+
+    fn finish(&mut self,) -> Result<()> {
+        let left_result = self.left.finish();
+        let right_result = self.right.finish();
+        left_result.and(right_result)
+    }
 }
 
 /// Creates a `ChainedCollector`
@@ -80,4 +110,13 @@ mod tests {
         assert_eq!(count_collector.count(), 3);
         assert!(top_collector.at_capacity());
     }
+
+    #[test]
+    fn test_chained_collector_min_score() {
+        let mut top_collector = TopCollector::with_limit(1);
+        top_collector.collect(ScoredDoc(0.2, 1));
+        assert_eq!(chain().push(&mut top_collector).min_score(), Some(0.2));
+        let mut count_collector = CountCollector::default();
+        assert_eq!(chain().push(&mut top_collector).push(&mut count_collector).min_score(), None);
+    }
 }
\ No newline at end of file
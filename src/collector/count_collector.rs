@@ -1,4 +1,4 @@
-use std::io;
+use Result;
 use super::Collector;
 use ScoredDoc;
 use SegmentReader;
@@ -6,33 +6,68 @@ use SegmentLocalId;
 
 /// `CountCollector` collector only counts how many
 /// documents match the query.  
+///
+/// With a limit, the count stops at the limit, and
+/// the search terminates as soon as it is reached.
+/// The counts below the limit are exact.
 pub struct CountCollector {
     count: usize,
+    limit: Option<usize>,
 }
 
 impl CountCollector {
+    /// Creates a count collector, terminating the search
+    /// once `limit` documents have been counted.
+    pub fn with_limit(limit: usize) -> CountCollector {
+        CountCollector {
+            count: 0,
+            limit: Some(limit),
+        }
+    }
+
     /// Returns the count of documents that were
     /// collected.
+    ///
+    /// It is at most the limit of the collector, if any.
     pub fn count(&self,) -> usize {
         self.count
     }
+
+    /// Returns true iff the count reached the limit
+    /// of the collector, in which case more documents
+    /// may match the query.
+    pub fn at_limit(&self,) -> bool {
+        match self.limit {
+            Some(limit) => self.count >= limit,
+            None => false,
+        }
+    }
 }
 
 impl Default for CountCollector {
     fn default() -> CountCollector {
         CountCollector {count: 0,
+                        limit: None,
         }
     }
 }
 
 impl Collector for CountCollector {
 
-    fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> io::Result<()> {
+    fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> Result<()> {
         Ok(())
     }
 
     fn collect(&mut self, _: ScoredDoc) {
-        self.count += 1;
+        // other collectors of a `MultiCollector` may
+        // keep the search going past the limit.
+        if !self.at_limit() {
+            self.count += 1;
+        }
+    }
+
+    fn should_terminate(&self,) -> bool {
+        self.at_limit()
     }
 }
 
@@ -42,7 +77,88 @@ mod tests {
     use super::*;
     use test::Bencher;
     use ScoredDoc;
-    use collector::Collector;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{Query, MultiTermQuery, AllQuery};
+    use collector::{Collector, MultiCollector, TopCollector};
+    use collector::tests::create_index;
+
+    #[test]
+    fn test_count_collector_with_limit() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = create_index(schema_builder.build().unwrap(), &[vec!("a", "a b", "b", "a"), vec!("a", "b", "a")], |text| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let count = |limit: usize| {
+            let mut count_collector = CountCollector::with_limit(limit);
+            query.search(&searcher, &mut count_collector).unwrap();
+            (count_collector.count(), count_collector.at_limit())
+        };
+        assert_eq!(count(2), (2, true));
+        assert_eq!(count(5), (5, true));
+        assert_eq!(count(6), (5, false));
+        assert_eq!(count(100), (5, false));
+        {
+            let mut count_collector = CountCollector::with_limit(1);
+            AllQuery.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 1);
+            assert!(count_collector.should_terminate());
+        }
+        {
+            // the top collector keeps the search going.
+            let mut count_collector = CountCollector::with_limit(2);
+            let mut top_collector = TopCollector::with_limit(10);
+            {
+                let mut collectors = MultiCollector::from(vec!(&mut count_collector, &mut top_collector));
+                query.search(&searcher, &mut collectors).unwrap();
+                assert!(!collectors.should_terminate());
+            }
+            assert_eq!(count_collector.count(), 2);
+            assert_eq!(top_collector.docs().len(), 5);
+        }
+        {
+            let mut count_collector = CountCollector::with_limit(1);
+            let mut other_count_collector = CountCollector::with_limit(3);
+            let mut push_counter = PushCounter {
+                count_collector: &mut count_collector,
+                num_pushed: 0,
+            };
+            {
+                let mut collectors = MultiCollector::from(vec!(&mut push_counter, &mut other_count_collector));
+                query.search(&searcher, &mut collectors).unwrap();
+                assert!(collectors.should_terminate());
+            }
+            // the search stopped once both of the collectors were at their limit.
+            assert_eq!(push_counter.num_pushed, 3);
+            assert_eq!(other_count_collector.count(), 3);
+        }
+    }
+
+    /// Count collector counting the documents it is pushed.
+    struct PushCounter<'a> {
+        count_collector: &'a mut CountCollector,
+        num_pushed: usize,
+    }
+
+    impl<'a> Collector for PushCounter<'a> {
+        fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
+            self.count_collector.set_segment(segment_local_id, segment)
+        }
+
+        fn collect(&mut self, scored_doc: ScoredDoc) {
+            self.num_pushed += 1;
+            self.count_collector.collect(scored_doc);
+        }
+
+        fn should_terminate(&self,) -> bool {
+            self.count_collector.should_terminate()
+        }
+    }
 
     #[bench]
     fn build_collector(b: &mut Bencher) {
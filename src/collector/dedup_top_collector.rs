@@ -0,0 +1,213 @@
+use Result;
+use Error;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocAddress;
+use Score;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use schema::Field;
+use fastfield::U32FastFieldReader;
+
+
+/// Group of the documents sharing a key, as returned
+/// by `DedupTopCollector::top_groups`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DedupGroup {
+    /// Value of the key field of the documents of the group.
+    pub key: u32,
+    /// Score of the best document of the group.
+    pub score: Score,
+    /// Address of the best document of the group.
+    pub doc_address: DocAddress,
+    /// Number of documents of the group, including the best one.
+    /// The number of collapsed duplicates is `num_docs - 1`.
+    pub num_docs: u32,
+}
+
+impl DedupGroup {
+    // the groups are ordered by decreasing score, and
+    // the ties are broken by increasing `DocAddress`.
+    fn cmp_best(&self, other: &DedupGroup) -> Ordering {
+        match other.score.partial_cmp(&self.score) {
+            Some(Ordering::Equal) | None => self.doc_address.cmp(&other.doc_address),
+            Some(ordering) => ordering,
+        }
+    }
+}
+
+
+/// The Dedup Top Collector keeps track of the K best documents,
+/// keeping at most one document per value of a u32 fast field,
+/// like the best version of each document, whatever its language.
+/// This is also known as field collapsing.
+///
+/// The best document of each key is kept in a `HashMap`
+/// during the collection, so that the keys are deduplicated
+/// across the segments.
+/// The documents with the same score are sorted by their `DocAddress`.
+pub struct DedupTopCollector {
+    key_field: Field,
+    limit: usize,
+    groups: HashMap<u32, DedupGroup>,
+    segment_id: u32,
+    ff_reader: Option<U32FastFieldReader>,
+}
+
+impl DedupTopCollector {
+
+    /// Creates a dedup top collector, returning at most `limit`
+    /// documents, with distinct values of the u32 fast field `key_field`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if limit is 0.
+    pub fn new(key_field: Field, limit: usize) -> Result<DedupTopCollector> {
+        if limit < 1 {
+            return Err(Error::InvalidArgument(String::from("Limit must be strictly greater than 0.")));
+        }
+        Ok(DedupTopCollector {
+            key_field: key_field,
+            limit: limit,
+            groups: HashMap::new(),
+            segment_id: 0,
+            ff_reader: None,
+        })
+    }
+
+    /// Returns the K best groups, sorted by decreasing score
+    /// of their best document.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn top_groups(&self) -> Vec<DedupGroup> {
+        let mut groups: Vec<DedupGroup> = self.groups
+            .values()
+            .cloned()
+            .collect();
+        groups.sort_by(|left, right| left.cmp_best(right));
+        groups.truncate(self.limit);
+        groups
+    }
+
+    /// Returns the best document of each of the K best groups,
+    /// along with its score, sorted by decreasing score.
+    pub fn top_docs(&self) -> Vec<(Score, DocAddress)> {
+        self.top_groups()
+            .into_iter()
+            .map(|group| (group.score, group.doc_address))
+            .collect()
+    }
+
+    /// Returns the best document of each of the K best groups,
+    /// sorted by decreasing score.
+    pub fn docs(&self) -> Vec<DocAddress> {
+        self.top_groups()
+            .into_iter()
+            .map(|group| group.doc_address)
+            .collect()
+    }
+}
+
+impl Collector for DedupTopCollector {
+
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        self.ff_reader = Some(try!(reader.get_fast_field_reader(self.key_field)));
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let key = self.ff_reader
+            .as_ref()
+            .expect("collect called before set_segment")
+            .get(scored_doc.doc());
+        let candidate = DedupGroup {
+            key: key,
+            score: scored_doc.score(),
+            doc_address: DocAddress(self.segment_id, scored_doc.doc()),
+            num_docs: 1,
+        };
+        let group = self.groups.entry(key).or_insert(DedupGroup {
+            num_docs: 0,
+            .. candidate
+        });
+        if candidate.cmp_best(group) == Ordering::Less {
+            group.score = candidate.score;
+            group.doc_address = candidate.doc_address;
+        }
+        group.num_docs += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, FAST, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, CountCollector};
+
+    #[test]
+    fn test_dedup_top_collector_limit() {
+        assert!(DedupTopCollector::new(Field(0), 0).is_err());
+    }
+
+    #[test]
+    fn test_dedup_top_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let key_field = schema_builder.add_u32_field("doc_key", FAST);
+        // the versions of the documents 1 and 2 are spread over the segments,
+        // and the shorter texts score more.
+        let segments = vec!(
+            vec!(("a b c", 1), ("a", 2), ("a b", 3)),
+            vec!(("a", 1), ("a b c d", 2), ("b", 4)),
+            vec!(("a b", 1), ("a b c", 5)),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, key)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc.add_u32(key_field, key);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut dedup_top_collector = DedupTopCollector::new(key_field, 3).unwrap();
+        let mut count_collector = CountCollector::default();
+        {
+            let mut collectors = MultiCollector::from(vec!(&mut dedup_top_collector, &mut count_collector));
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        assert_eq!(count_collector.count(), 7);
+        let top_groups = dedup_top_collector.top_groups();
+        assert_eq!(top_groups.len(), 3);
+        // "a" for the keys 1 and 2, then "a b" for the key 3.
+        let keys: Vec<u32> = top_groups.iter().map(|group| group.key).collect();
+        assert_eq!(keys[2], 3);
+        let mut best_keys = keys[..2].to_vec();
+        best_keys.sort();
+        assert_eq!(best_keys, vec!(1, 2));
+        for group in &top_groups {
+            let segment_reader = searcher.segment_reader(group.doc_address.segment_ord() as usize);
+            let key = segment_reader.get_fast_field_reader(key_field).unwrap().get(group.doc_address.doc());
+            assert_eq!(key, group.key);
+        }
+        let num_docs: Vec<(u32, u32)> = top_groups.iter().map(|group| (group.key, group.num_docs)).collect();
+        assert!(num_docs.contains(&(1, 3)));
+        assert!(num_docs.contains(&(2, 2)));
+        assert!(num_docs.contains(&(3, 1)));
+        let mut best_docs: Vec<DocAddress> = top_groups[..2].iter().map(|group| group.doc_address).collect();
+        best_docs.sort();
+        assert_eq!(best_docs, vec!(DocAddress(0, 1), DocAddress(1, 0)));
+        assert_eq!(top_groups[2].doc_address, DocAddress(0, 2));
+        assert_eq!(dedup_top_collector.docs(), top_groups.iter().map(|group| group.doc_address).collect::<Vec<DocAddress>>());
+        let scores: Vec<Score> = dedup_top_collector.top_docs().iter().map(|&(score, _)| score).collect();
+        assert!(scores[0] >= scores[1]);
+        assert!(scores[1] > scores[2]);
+    }
+}
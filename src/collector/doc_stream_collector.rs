@@ -0,0 +1,135 @@
+use Result;
+use Error;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocAddress;
+use Score;
+
+
+/// `DocStreamCollector` calls a callback on each of the documents
+/// matching the query, as they are collected, so that all of
+/// the matching documents can be exported without keeping
+/// their addresses in memory.
+///
+/// If the callback fails, it is not called anymore, the search
+/// terminates, and the error is returned by the search.
+/// Under a `MultiCollector`, the other collectors may keep
+/// the search going, but the callback is still not called.
+pub struct DocStreamCollector<F: FnMut(DocAddress, Score) -> Result<()>> {
+    callback: F,
+    segment_id: u32,
+    error: Option<Error>,
+}
+
+impl<F: FnMut(DocAddress, Score) -> Result<()>> DocStreamCollector<F> {
+
+    /// Creates a collector calling `callback` on each
+    /// of the collected documents, with their score.
+    pub fn new(callback: F) -> DocStreamCollector<F> {
+        DocStreamCollector {
+            callback: callback,
+            segment_id: 0,
+            error: None,
+        }
+    }
+}
+
+impl<F: FnMut(DocAddress, Score) -> Result<()>> Collector for DocStreamCollector<F> {
+
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        if self.error.is_some() {
+            return;
+        }
+        let doc_address = DocAddress(self.segment_id, scored_doc.doc());
+        if let Err(error) = (self.callback)(doc_address, scored_doc.score()) {
+            self.error = Some(error);
+        }
+    }
+
+    fn should_terminate(&self,) -> bool {
+        self.error.is_some()
+    }
+
+    fn finish(&mut self,) -> Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{SchemaBuilder, Document, Term, TEXT, STORED};
+    use query::MultiTermQuery;
+    use collector::{MultiCollector, CountCollector};
+    use collector::tests::create_index;
+
+    #[test]
+    fn test_doc_stream_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = create_index(schema_builder.build().unwrap(), &[vec!("a", "b", "a b"), vec!("a", "a", "b")], |text| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        {
+            // the stored documents are read as they are collected.
+            let mut texts: Vec<String> = Vec::new();
+            {
+                let mut doc_stream_collector = DocStreamCollector::new(|doc_address, score| {
+                    assert!(score > 0f32);
+                    let doc = try!(searcher.doc(&doc_address));
+                    texts.push(doc.get_first(text_field).unwrap().text().to_string());
+                    Ok(())
+                });
+                let mut count_collector = CountCollector::default();
+                {
+                    let mut collectors = MultiCollector::from(vec!(&mut doc_stream_collector, &mut count_collector));
+                    searcher.search(&query, &mut collectors).unwrap();
+                }
+                assert_eq!(count_collector.count(), 4);
+            }
+            texts.sort();
+            assert_eq!(texts, vec!("a", "a", "a", "a b"));
+        }
+        {
+            // the error of the callback aborts the search.
+            let mut num_calls = 0;
+            {
+                let mut doc_stream_collector = DocStreamCollector::new(|_, _| {
+                    num_calls += 1;
+                    if num_calls == 2 {
+                        Err(Error::InvalidArgument(String::from("export failed")))
+                    }
+                    else {
+                        Ok(())
+                    }
+                });
+                match searcher.search(&query, &mut doc_stream_collector) {
+                    Err(Error::InvalidArgument(msg)) => {
+                        assert_eq!(msg, "export failed");
+                    }
+                    _ => {
+                        panic!("the error of the callback should be returned");
+                    }
+                }
+            }
+            assert_eq!(num_calls, 2);
+        }
+    }
+}
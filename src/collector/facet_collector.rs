@@ -0,0 +1,204 @@
+use Result;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use schema::Field;
+use fastfield::U32FastFieldReader;
+
+
+/// Segments whose values of the field span at most this
+/// range are counted in an array rather than in a `HashMap`.
+const MAX_DENSE_RANGE: u32 = 1 << 16;
+
+
+/// Counts of the values of the current segment.
+enum SegmentCounts {
+    Empty,
+    // `counts[val - min_val]` is the count of `val`.
+    Dense {
+        ff_reader: U32FastFieldReader,
+        min_val: u32,
+        counts: Vec<u32>,
+    },
+    Sparse {
+        ff_reader: U32FastFieldReader,
+        counts: HashMap<u32, u32>,
+    },
+}
+
+impl SegmentCounts {
+
+    fn new(ff_reader: U32FastFieldReader) -> SegmentCounts {
+        let min_val = ff_reader.min_val();
+        let range = ff_reader.max_val() - min_val;
+        if range < MAX_DENSE_RANGE {
+            SegmentCounts::Dense {
+                ff_reader: ff_reader,
+                min_val: min_val,
+                counts: vec!(0u32; range as usize + 1),
+            }
+        }
+        else {
+            SegmentCounts::Sparse {
+                ff_reader: ff_reader,
+                counts: HashMap::new(),
+            }
+        }
+    }
+
+    #[inline]
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        match *self {
+            SegmentCounts::Empty => {
+                panic!("collect called before set_segment");
+            }
+            SegmentCounts::Dense { ref ff_reader, min_val, ref mut counts } => {
+                counts[(ff_reader.get(scored_doc.doc()) - min_val) as usize] += 1;
+            }
+            SegmentCounts::Sparse { ref ff_reader, ref mut counts } => {
+                *counts.entry(ff_reader.get(scored_doc.doc())).or_insert(0u32) += 1;
+            }
+        }
+    }
+
+    /// Adds the non-zero counts of the segment to `counts`.
+    fn merge_into(&self, counts: &mut HashMap<u32, u32>) {
+        match *self {
+            SegmentCounts::Empty => {}
+            SegmentCounts::Dense { min_val, counts: ref segment_counts, .. } => {
+                for (offset, &count) in segment_counts.iter().enumerate() {
+                    if count > 0 {
+                        *counts.entry(min_val + offset as u32).or_insert(0u32) += count;
+                    }
+                }
+            }
+            SegmentCounts::Sparse { counts: ref segment_counts, .. } => {
+                for (&val, &count) in segment_counts {
+                    *counts.entry(val).or_insert(0u32) += count;
+                }
+            }
+        }
+    }
+}
+
+
+/// `FacetCollector` counts the documents matching the query
+/// for each of the values of a u32 fast field, like the
+/// documents of each category.
+///
+/// Within a segment, the values are counted in an array if
+/// their range is small, and in a `HashMap` otherwise.
+/// The counts of the segments are merged as the collector
+/// moves to the next segment.
+pub struct FacetCollector {
+    field: Field,
+    counts: HashMap<u32, u32>,
+    segment_counts: SegmentCounts,
+}
+
+impl FacetCollector {
+
+    /// Creates a new facet collector for the given u32 fast field.
+    pub fn for_field(field: Field) -> FacetCollector {
+        FacetCollector {
+            field: field,
+            counts: HashMap::new(),
+            segment_counts: SegmentCounts::Empty,
+        }
+    }
+
+    /// Returns the count of every value of the field
+    /// that appeared in at least one document.
+    pub fn counts(&self,) -> HashMap<u32, u32> {
+        let mut counts = self.counts.clone();
+        self.segment_counts.merge_into(&mut counts);
+        counts
+    }
+
+    /// Returns the number of documents with the given value.
+    pub fn count(&self, val: u32) -> u32 {
+        self.counts()
+            .get(&val)
+            .cloned()
+            .unwrap_or(0u32)
+    }
+
+    /// Returns the `n` values with the largest counts, together
+    /// with their count, sorted by decreasing count.
+    ///
+    /// The ties are sorted by increasing value.
+    pub fn top_n(&self, n: usize) -> Vec<(u32, u32)> {
+        let mut buckets: Vec<(u32, u32)> = self.counts().into_iter().collect();
+        buckets.sort_by(|&(left_val, left_count), &(right_val, right_count)| {
+            match right_count.cmp(&left_count) {
+                Ordering::Equal => left_val.cmp(&right_val),
+                count_ordering => count_ordering,
+            }
+        });
+        buckets.truncate(n);
+        buckets
+    }
+}
+
+impl Collector for FacetCollector {
+
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_counts.merge_into(&mut self.counts);
+        let ff_reader = try!(reader.get_fast_field_reader(self.field));
+        self.segment_counts = SegmentCounts::new(ff_reader);
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        self.segment_counts.collect(scored_doc);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, FAST, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, TopCollector};
+
+    #[test]
+    fn test_facet_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let category_field = schema_builder.add_u32_field("category", FAST);
+        // the values of the last segment are too far
+        // apart to be counted in an array.
+        let segments = vec!(
+            vec!(("a", 3), ("a", 1), ("b", 3), ("a", 3)),
+            vec!(("a", 2), ("a b", 1)),
+            vec!(("a", 3), ("a", 1_000_000), ("b", 2), ("a", 1_000_000)),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, category)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc.add_u32(category_field, category);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut top_collector = TopCollector::with_limit(3);
+        let mut facet_collector = FacetCollector::for_field(category_field);
+        {
+            let mut collectors = MultiCollector::from(vec!(&mut top_collector, &mut facet_collector));
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        assert_eq!(top_collector.docs().len(), 3);
+        assert_eq!(facet_collector.top_n(10), vec!((3, 3), (1, 2), (1_000_000, 2), (2, 1)));
+        assert_eq!(facet_collector.top_n(2), vec!((3, 3), (1, 2)));
+        assert_eq!(facet_collector.count(1_000_000), 2);
+        assert_eq!(facet_collector.count(4), 0);
+        assert_eq!(facet_collector.counts().len(), 4);
+    }
+}
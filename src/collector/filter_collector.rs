@@ -0,0 +1,134 @@
+use Result;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use Score;
+use schema::Field;
+use fastfield::U32FastFieldReader;
+
+
+/// `FilterCollector` wraps a collector, and only forwards
+/// to it the documents whose value of a u32 fast field
+/// passes the predicate, like the documents with a
+/// given status.
+///
+/// This is post-filtering: the query still goes through,
+/// and scores, all of the documents it matches, and the
+/// predicate is evaluated on each of them.
+/// It does not make the search any faster.
+pub struct FilterCollector<P: Fn(u32) -> bool, C: Collector> {
+    field: Field,
+    predicate: P,
+    collector: C,
+    ff_reader: Option<U32FastFieldReader>,
+}
+
+impl<P: Fn(u32) -> bool, C: Collector> FilterCollector<P, C> {
+
+    /// Creates a filter collector forwarding to `collector`
+    /// the documents whose value of the u32 fast field `field`
+    /// passes `predicate`.
+    pub fn new(field: Field, predicate: P, collector: C) -> FilterCollector<P, C> {
+        FilterCollector {
+            field: field,
+            predicate: predicate,
+            collector: collector,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the wrapped collector.
+    pub fn collector(&self,) -> &C {
+        &self.collector
+    }
+
+    /// Consumes the filter collector, and returns the wrapped collector.
+    pub fn into_inner(self,) -> C {
+        self.collector
+    }
+}
+
+impl<P: Fn(u32) -> bool, C: Collector> Collector for FilterCollector<P, C> {
+
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(try!(reader.get_fast_field_reader(self.field)));
+        self.collector.set_segment(segment_local_id, reader)
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let val = self.ff_reader
+            .as_ref()
+            .expect("collect called before set_segment")
+            .get(scored_doc.doc());
+        if (self.predicate)(val) {
+            self.collector.collect(scored_doc);
+        }
+    }
+
+    // the documents not scoring more than the min score of the
+    // wrapped collector cannot change its result, whether they
+    // pass the predicate or not.
+    fn min_score(&self,) -> Option<Score> {
+        self.collector.min_score()
+    }
+
+    fn should_terminate(&self,) -> bool {
+        self.collector.should_terminate()
+    }
+
+    fn finish(&mut self,) -> Result<()> {
+        self.collector.finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, FAST, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, TopCollector, CountCollector};
+    use DocAddress;
+
+    #[test]
+    fn test_filter_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let status_field = schema_builder.add_u32_field("status", FAST);
+        let segments = vec!(
+            vec!(("a", 2), ("a", 1), ("b", 2)),
+            vec!(("a b", 2), ("a", 3), ("a", 2)),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, status)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc.add_u32(status_field, status);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut count_collector = CountCollector::default();
+        let mut status_2_count_collector = FilterCollector::new(status_field, |status| status == 2, CountCollector::default());
+        let mut top_collector = TopCollector::with_limit(10);
+        let mut status_2_top_collector = FilterCollector::new(status_field, |status| status == 2, &mut top_collector);
+        let mut status_3_count_collector = FilterCollector::new(status_field, |status| status >= 3, CountCollector::default());
+        {
+            let mut collectors = MultiCollector::from(vec!(
+                &mut count_collector,
+                &mut status_2_count_collector,
+                &mut status_2_top_collector,
+                &mut status_3_count_collector));
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        assert_eq!(count_collector.count(), 5);
+        assert_eq!(status_2_count_collector.collector().count(), 3);
+        assert_eq!(status_3_count_collector.into_inner().count(), 1);
+        let mut docs: Vec<DocAddress> = top_collector.docs();
+        docs.sort();
+        assert_eq!(docs, vec!(DocAddress(0, 0), DocAddress(1, 0), DocAddress(1, 2)));
+    }
+}
@@ -0,0 +1,237 @@
+use Result;
+use std::collections::{BTreeSet, HashMap};
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocId;
+use schema::{Field, Facet, Term};
+use schema::FACET_SEP_BYTE;
+use datastruct::{TermDictionary, TermDictionaryKeys};
+use postings::DocSet;
+use postings::SegmentPostingsOption;
+
+
+/// Doc ids of the current segment associated to a facet.
+struct FacetDocs {
+    facet_ord: usize,
+    docs: Vec<DocId>,
+    cursor: usize,
+}
+
+impl FacetDocs {
+    /// Returns true iff the doc contains the facet.
+    ///
+    /// Docs are expected to be collected in increasing order.
+    fn contains(&mut self, doc: DocId) -> bool {
+        while self.cursor < self.docs.len() && self.docs[self.cursor] < doc {
+            self.cursor += 1;
+        }
+        self.cursor < self.docs.len() && self.docs[self.cursor] == doc
+    }
+}
+
+
+/// `HierarchicalFacetCollector` counts the documents matching
+/// the query under a given facet, as well as under each
+/// of its direct children.
+///
+/// For instance, registering `/electronics` will count the documents
+/// under `/electronics`, `/electronics/phones`, `/electronics/tv`, ...
+///
+/// The facet field needs to be indexed.
+/// The children of a facet are found by scanning the term dictionary,
+/// as they are lexicographically adjacent to their parent.
+pub struct HierarchicalFacetCollector {
+    field: Field,
+    facets: Vec<Facet>,
+    facet_ords: HashMap<Facet, usize>,
+    counts: Vec<u32>,
+    segment_facet_docs: Vec<FacetDocs>,
+}
+
+impl HierarchicalFacetCollector {
+
+    /// Creates a new facet collector for the given field.
+    pub fn for_field(field: Field) -> HierarchicalFacetCollector {
+        HierarchicalFacetCollector {
+            field: field,
+            facets: Vec::new(),
+            facet_ords: HashMap::new(),
+            counts: Vec::new(),
+            segment_facet_docs: Vec::new(),
+        }
+    }
+
+    /// Registers a facet to be counted, together with its
+    /// direct children.
+    ///
+    /// Facets must be registered before the search.
+    pub fn add_facet(&mut self, facet: Facet) {
+        self.facets.push(facet);
+    }
+
+    fn facet_ord(&mut self, facet: Facet) -> usize {
+        let num_facets = self.counts.len();
+        let ord = *self.facet_ords
+            .entry(facet)
+            .or_insert(num_facets);
+        if ord == num_facets {
+            self.counts.push(0u32);
+        }
+        ord
+    }
+
+    /// Returns the number of documents under the facet.
+    ///
+    /// Only registered facets and their direct children
+    /// are counted. Other facets return 0.
+    pub fn count(&self, facet: &Facet) -> u32 {
+        self.facet_ords
+            .get(facet)
+            .map(|&ord| self.counts[ord])
+            .unwrap_or(0u32)
+    }
+
+    /// Returns the direct children of a registered facet
+    /// that appeared in at least one document, together with their count,
+    /// sorted by facet.
+    pub fn children(&self, facet: &Facet) -> Vec<(Facet, u32)> {
+        let num_segments = facet.segments().len() + 1;
+        let mut children: Vec<(Facet, u32)> = self.facet_ords
+            .iter()
+            .filter(|&(child, _)| child.segments().len() == num_segments && facet.is_prefix_of(child))
+            .map(|(child, &ord)| (child.clone(), self.counts[ord]))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        children.sort();
+        children
+    }
+
+    /// Returns the facets present in the segment that should be counted,
+    /// that is the registered facets and their direct children.
+    fn segment_facets(&self, reader: &SegmentReader) -> BTreeSet<Facet> {
+        let mut facets = BTreeSet::new();
+        let field_byte = self.field.0;
+        for facet in &self.facets {
+            let facet_term = Term::from_facet(self.field, facet);
+            let mut prefix: Vec<u8> = Vec::from(facet_term.as_slice());
+            if !facet.is_root() {
+                facets.insert(facet.clone());
+                prefix.push(FACET_SEP_BYTE);
+            }
+            let mut keys = reader.term_infos().keys_from(&prefix);
+            while let Some(key) = keys.next() {
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                debug_assert_eq!(key[0], field_byte);
+                if key[prefix.len()..].contains(&FACET_SEP_BYTE) {
+                    // grand children are not counted.
+                    continue;
+                }
+                if let Ok(encoded_facet) = String::from_utf8(Vec::from(&key[1..])) {
+                    facets.insert(Facet::from_encoded(encoded_facet));
+                }
+            }
+        }
+        facets
+    }
+}
+
+impl Collector for HierarchicalFacetCollector {
+
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_facet_docs.clear();
+        for facet in self.segment_facets(reader) {
+            let term = Term::from_facet(self.field, &facet);
+            let facet_ord = self.facet_ord(facet);
+            let mut docs = Vec::new();
+            if let Some(mut postings) = reader.read_postings(&term, SegmentPostingsOption::NoFreq) {
+                while postings.advance() {
+                    docs.push(postings.doc());
+                }
+            }
+            self.segment_facet_docs.push(FacetDocs {
+                facet_ord: facet_ord,
+                docs: docs,
+                cursor: 0,
+            });
+        }
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let doc = scored_doc.doc();
+        for facet_docs in &mut self.segment_facet_docs {
+            if facet_docs.contains(doc) {
+                self.counts[facet_docs.facet_ord] += 1;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{self, SchemaBuilder, Document, FacetOptions, Facet};
+    use query::QueryParser;
+    use query::Query;
+
+    #[test]
+    fn test_hierarchical_facet_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let facet_field = schema_builder.add_facet_field("category", FacetOptions::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let docs = vec!(
+                ("a", "/electronics/phones/android"),
+                ("a", "/electronics/phones/ios"),
+                ("a b", "/electronics/tv"),
+                ("b", "/electronics/tv"),
+                ("a", "/electronicsx"),
+                ("a", "/books"),
+            );
+            for (i, (text, facet_text)) in docs.into_iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                doc.add_facet(facet_field, Facet::from_text(facet_text));
+                index_writer.add_document(doc).unwrap();
+                if i == 2 {
+                    // spread the documents over two segments
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(text_field));
+        let query = query_parser.parse_query("a").unwrap();
+        let mut facet_collector = HierarchicalFacetCollector::for_field(facet_field);
+        facet_collector.add_facet(Facet::from_text("/electronics"));
+        facet_collector.add_facet(Facet::root());
+        query.search(&searcher, &mut facet_collector).unwrap();
+        assert_eq!(facet_collector.count(&Facet::from_text("/electronics")), 3);
+        assert_eq!(facet_collector.count(&Facet::from_text("/electronics/phones")), 2);
+        assert_eq!(facet_collector.count(&Facet::from_text("/electronics/tv")), 1);
+        assert_eq!(facet_collector.count(&Facet::from_text("/electronics/phones/android")), 0);
+        assert_eq!(
+            facet_collector.children(&Facet::from_text("/electronics")),
+            vec!(
+                (Facet::from_text("/electronics/phones"), 2),
+                (Facet::from_text("/electronics/tv"), 1),
+            ));
+        assert_eq!(
+            facet_collector.children(&Facet::root()),
+            vec!(
+                (Facet::from_text("/books"), 1),
+                (Facet::from_text("/electronics"), 3),
+                (Facet::from_text("/electronicsx"), 1),
+            ));
+    }
+}
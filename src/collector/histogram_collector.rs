@@ -0,0 +1,211 @@
+use Result;
+use Error;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use schema::Field;
+use fastfield::U32FastFieldReader;
+
+
+/// `HistogramCollector` counts the documents matching the query
+/// in buckets of the values of a u32 fast field, like the
+/// ranges of prices `[0, 10)`, `[10, 20)`, ...
+///
+/// The buckets span `[min, max)`, each of them but the last one
+/// covering `interval` values. The values out of this range are
+/// dropped by default, or counted in the first or the last bucket
+/// if `set_clamp` is called.
+pub struct HistogramCollector {
+    field: Field,
+    interval: u32,
+    min: u32,
+    max: u32,
+    clamp: bool,
+    counts: Vec<u32>,
+    num_dropped: u32,
+    ff_reader: Option<U32FastFieldReader>,
+}
+
+impl HistogramCollector {
+
+    /// Creates a histogram collector for the given u32 fast field,
+    /// with buckets of `interval` values in `[min, max)`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if the interval is 0,
+    /// or if `min` is not smaller than `max`.
+    pub fn new(field: Field, interval: u32, min: u32, max: u32) -> Result<HistogramCollector> {
+        if interval == 0 {
+            return Err(Error::InvalidArgument(String::from("The interval must be strictly greater than 0.")));
+        }
+        if min >= max {
+            return Err(Error::InvalidArgument(format!("The min {} must be smaller than the max {}.", min, max)));
+        }
+        let range = max - min;
+        let num_buckets = range / interval + if range % interval == 0 { 0 } else { 1 };
+        Ok(HistogramCollector {
+            field: field,
+            interval: interval,
+            min: min,
+            max: max,
+            clamp: false,
+            counts: vec!(0u32; num_buckets as usize),
+            num_dropped: 0,
+            ff_reader: None,
+        })
+    }
+
+    /// If `clamp` is true, the values smaller than `min` are counted
+    /// in the first bucket, and the values greater than or equal to `max`
+    /// in the last bucket, rather than being dropped.
+    pub fn set_clamp(&mut self, clamp: bool) {
+        self.clamp = clamp;
+    }
+
+    /// Returns the count of each of the buckets.
+    pub fn counts(&self,) -> &[u32] {
+        &self.counts
+    }
+
+    /// Returns the lower bound of each of the buckets,
+    /// together with its count.
+    pub fn buckets(&self,) -> Vec<(u32, u32)> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(bucket, &count)| (self.min + bucket as u32 * self.interval, count))
+            .collect()
+    }
+
+    /// Returns the number of documents whose value was out
+    /// of `[min, max)`, and that were dropped.
+    pub fn num_dropped(&self,) -> u32 {
+        self.num_dropped
+    }
+
+    #[inline]
+    fn bucket(&self, val: u32) -> Option<usize> {
+        if val >= self.min && val < self.max {
+            Some(((val - self.min) / self.interval) as usize)
+        }
+        else if !self.clamp {
+            None
+        }
+        else if val < self.min {
+            Some(0)
+        }
+        else {
+            Some(self.counts.len() - 1)
+        }
+    }
+}
+
+impl Collector for HistogramCollector {
+
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(try!(reader.get_fast_field_reader(self.field)));
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let val = self.ff_reader
+            .as_ref()
+            .expect("collect called before set_segment")
+            .get(scored_doc.doc());
+        match self.bucket(val) {
+            Some(bucket) => {
+                self.counts[bucket] += 1;
+            }
+            None => {
+                self.num_dropped += 1;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, FAST, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, TopCollector, CountCollector};
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    #[test]
+    fn test_histogram_collector_new() {
+        assert!(HistogramCollector::new(Field(0), 0, 0, 10).is_err());
+        assert!(HistogramCollector::new(Field(0), 1, 10, 10).is_err());
+        assert_eq!(HistogramCollector::new(Field(0), 10, 0, 100).unwrap().counts().len(), 10);
+        // the last bucket is shorter.
+        let histogram_collector = HistogramCollector::new(Field(0), 10, 5, 100).unwrap();
+        assert_eq!(histogram_collector.counts().len(), 10);
+        assert_eq!(histogram_collector.buckets()[9], (95, 0));
+    }
+
+    fn check_histogram(seed: u32, clamp: bool) {
+        let mut rng = XorShiftRng::from_seed([seed, 1, 2, 3]);
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u32_field("price", FAST.set_stored());
+        let num_docs = 500;
+        let docs: Vec<(bool, u32)> = (0..num_docs)
+            .map(|_| (rng.gen_weighted_bool(3), rng.gen_range(0, 120)))
+            .collect();
+        let segments: Vec<Vec<(bool, u32)>> = docs.chunks(200).map(|segment_docs| segment_docs.to_vec()).collect();
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(is_b, price)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, if is_b { "b" } else { "a" });
+            doc.add_u32(price_field, price);
+            doc
+        });
+        let searcher = index.searcher();
+        let (interval, min, max) = (10, rng.gen_range(0, 20), rng.gen_range(80, 110));
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut histogram_collector = HistogramCollector::new(price_field, interval, min, max).unwrap();
+        histogram_collector.set_clamp(clamp);
+        let mut top_collector = TopCollector::with_limit(num_docs);
+        let mut count_collector = CountCollector::default();
+        {
+            let mut collectors = MultiCollector::from(vec!(&mut histogram_collector, &mut top_collector, &mut count_collector));
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        // counts the stored prices of the matching documents.
+        let mut expected_counts = vec!(0u32; histogram_collector.counts().len());
+        let mut expected_num_dropped = 0u32;
+        for doc_address in top_collector.docs() {
+            let price = searcher.doc(&doc_address).unwrap().get_first(price_field).unwrap().u32_value();
+            let bucket = if price < min {
+                if clamp { Some(0) } else { None }
+            }
+            else if price >= max {
+                if clamp { Some(expected_counts.len() - 1) } else { None }
+            }
+            else {
+                Some(((price - min) / interval) as usize)
+            };
+            match bucket {
+                Some(bucket) => { expected_counts[bucket] += 1; }
+                None => { expected_num_dropped += 1; }
+            }
+        }
+        assert_eq!(histogram_collector.counts(), &expected_counts[..]);
+        assert_eq!(histogram_collector.num_dropped(), expected_num_dropped);
+        let num_counted = histogram_collector.counts().iter().fold(0u32, |sum, &count| sum + count);
+        assert_eq!((num_counted + expected_num_dropped) as usize, count_collector.count());
+        if clamp {
+            assert_eq!(expected_num_dropped, 0);
+        }
+    }
+
+    #[test]
+    fn test_histogram_collector() {
+        for seed in 0..5 {
+            check_histogram(seed, false);
+            check_histogram(seed, true);
+        }
+    }
+}
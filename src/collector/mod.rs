@@ -1,20 +1,65 @@
 use SegmentReader;
 use SegmentLocalId;
 use ScoredDoc;
-use std::io;
+use Score;
+use Result;
 
 mod count_collector;
 pub use self::count_collector::CountCollector;
 
+mod segment_count_collector;
+pub use self::segment_count_collector::SegmentCountCollector;
+
 mod multi_collector;
 pub use self::multi_collector::MultiCollector;
 
 mod top_collector;
 pub use self::top_collector::TopCollector;
 
+mod top_field_collector;
+pub use self::top_field_collector::{TopFieldCollector, Order};
+
+mod top_str_field_collector;
+pub use self::top_str_field_collector::TopStrFieldCollector;
+
+mod tweaked_score_top_collector;
+pub use self::tweaked_score_top_collector::TweakedScoreTopCollector;
+
+mod dedup_top_collector;
+pub use self::dedup_top_collector::{DedupTopCollector, DedupGroup};
+
 mod chained_collector;
 pub use self::chained_collector::chain;
 
+mod hierarchical_facet_collector;
+pub use self::hierarchical_facet_collector::HierarchicalFacetCollector;
+
+mod facet_collector;
+pub use self::facet_collector::FacetCollector;
+
+mod str_facet_collector;
+pub use self::str_facet_collector::StrFacetCollector;
+
+mod histogram_collector;
+pub use self::histogram_collector::HistogramCollector;
+
+mod stats_collector;
+pub use self::stats_collector::StatsCollector;
+
+mod filter_collector;
+pub use self::filter_collector::FilterCollector;
+
+mod reservoir_sampling_collector;
+pub use self::reservoir_sampling_collector::ReservoirSamplingCollector;
+
+mod doc_stream_collector;
+pub use self::doc_stream_collector::DocStreamCollector;
+
+mod bitset_collector;
+pub use self::bitset_collector::BitSetCollector;
+
+mod tuple_collector;
+
 /// Collectors are in charge of collecting and retaining relevant 
 /// information from the document found and scored by the query.
 ///
@@ -44,23 +89,72 @@ pub use self::chained_collector::chain;
 /// - `.collect(last_doc_of_last_segment)`
 ///
 /// Segments are not guaranteed to be visited in any specific order.
+///
+/// Tuples of up to 4 collectors are collectors too, pushing the documents
+/// to each of their collectors, whose results are then accessible by field.
 pub trait Collector {
     /// `set_segment` is called before beginning to enumerate 
     /// on this segment.
-    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> io::Result<()>;
+    ///
+    /// The segment reader gives access to everything the collector
+    /// may need during the collection of the segment, like its fast
+    /// fields or its store.
+    /// Errors, like a fast field missing from the schema, should be
+    /// returned here rather than surfacing later, and abort the search.
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()>;
     /// The query pushes the scored document to the collector via this method.
     fn collect(&mut self, scored_doc: ScoredDoc);
+
+    /// Returns the score a document must exceed to change the result
+    /// of the collector, if there is one.
+    ///
+    /// Queries may then skip the documents that cannot score
+    /// more than this score. They may still push them to the collector,
+    /// which must ignore them.
+    fn min_score(&self,) -> Option<Score> {
+        None
+    }
+
+    /// Returns true if no more document can change the result
+    /// of the collector, like a count that reached its limit.
+    ///
+    /// Queries then stop pushing documents to the collector,
+    /// and skip the remaining segments.
+    fn should_terminate(&self,) -> bool {
+        false
+    }
+
+    /// `finish` is called once the search went through all of the
+    /// segments, or terminated.
+    ///
+    /// Errors which occurred during the collection, like the errors
+    /// of a callback, are returned here, and then by the search.
+    fn finish(&mut self,) -> Result<()> {
+        Ok(())
+    }
 }
 
 
 impl<'a, C: Collector> Collector for &'a mut C {
-    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> io::Result<()> {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
         (*self).set_segment(segment_local_id, segment)
     }
     /// The query pushes the scored document to the collector via this method.
     fn collect(&mut self, scored_doc: ScoredDoc) {
         (*self).collect(scored_doc);
     }
+
+    fn min_score(&self,) -> Option<Score> {
+        (**self).min_score()
+    }
+
+    fn should_terminate(&self,) -> bool {
+        (**self).should_terminate()
+    }
+
+    fn finish(&mut self,) -> Result<()> {
+        (*self).finish()
+    }
 }
 
 
@@ -75,7 +169,9 @@ pub mod tests {
     use std::io;
     use SegmentLocalId;
     use fastfield::U32FastFieldReader;
-    use schema::Field;
+    use fastfield::I64FastFieldReader;
+    use schema::{Field, Schema, Document};
+    use core::Index;
     
     /// Stores all of the doc ids.
     /// This collector is only used for tests.
@@ -106,7 +202,7 @@ pub mod tests {
 
     impl Collector for TestCollector {
 
-        fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> io::Result<()> {
+        fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
             self.offset += self.segment_max_doc;
             self.segment_max_doc = reader.max_doc();
             Ok(())
@@ -145,7 +241,7 @@ pub mod tests {
     }
         
     impl Collector for FastFieldTestCollector {
-        fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> io::Result<()> {
+        fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
             self.ff_reader = Some(try!(reader.get_fast_field_reader(self.field)));
             Ok(())
         }
@@ -157,6 +253,93 @@ pub mod tests {
     }
 
 
+    /// Collects in order all of the values of a date fast field
+    /// for all of the doc in the `DocSet`
+    ///
+    /// This collector is mainly useful for tests.
+    pub struct DateFastFieldTestCollector {
+        vals: Vec<i64>,
+        field: Field,
+        ff_reader: Option<I64FastFieldReader>,
+    }
+
+    impl DateFastFieldTestCollector {
+        pub fn for_field(field: Field) -> DateFastFieldTestCollector {
+            DateFastFieldTestCollector {
+                vals: Vec::new(),
+                field: field,
+                ff_reader: None,
+            }
+        }
+
+        pub fn vals(&self,) -> &Vec<i64> {
+            &self.vals
+        }
+    }
+        
+    impl Collector for DateFastFieldTestCollector {
+        fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+            self.ff_reader = Some(try!(reader.get_date_fast_field_reader(self.field)));
+            Ok(())
+        }
+
+        fn collect(&mut self, scored_doc: ScoredDoc) {
+            let val = self.ff_reader.as_ref().unwrap().get(scored_doc.doc());
+            self.vals.push(val);
+        }
+    }
+
+    /// Creates an index in RAM with a segment per element of `segments`,
+    /// building each of their documents with `make_doc`.
+    ///
+    /// The documents are added by a single indexing thread,
+    /// so that the segments keep their order and their documents.
+    pub fn create_index<T, F: Fn(&T) -> Document>(schema: Schema, segments: &[Vec<T>], make_doc: F) -> Index {
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for segment_docs in segments {
+                for doc_values in segment_docs {
+                    index_writer.add_document(make_doc(doc_values)).unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+        }
+        index
+    }
+
+    #[test]
+    fn test_set_segment_error() {
+        use schema::{SchemaBuilder, U32Options, FAST};
+        use query::{Query, AllQuery};
+        let mut schema_builder = SchemaBuilder::default();
+        let fast_field = schema_builder.add_u32_field("fast", FAST);
+        let stored_field = schema_builder.add_u32_field("stored", U32Options::default().set_stored());
+        let index = create_index(schema_builder.build().unwrap(), &[vec!((1, 2))], |&(fast_val, stored_val)| {
+            let mut doc = Document::default();
+            doc.add_u32(fast_field, fast_val);
+            doc.add_u32(stored_field, stored_val);
+            doc
+        });
+        let searcher = index.searcher();
+        {
+            let mut fast_field_collector = FastFieldTestCollector::for_field(fast_field);
+            assert!(AllQuery.search(&searcher, &mut fast_field_collector).is_ok());
+            assert_eq!(fast_field_collector.vals(), &vec!(1));
+        }
+        {
+            // the stored field is not a fast field.
+            let mut fast_field_collector = FastFieldTestCollector::for_field(stored_field);
+            let mut count_collector = CountCollector::default();
+            {
+                let mut collectors = MultiCollector::from(vec!(&mut count_collector, &mut fast_field_collector));
+                assert!(AllQuery.search(&searcher, &mut collectors).is_err());
+            }
+            assert_eq!(count_collector.count(), 0);
+            assert!(fast_field_collector.vals().is_empty());
+        }
+    }
+
     #[bench]
     fn build_collector(b: &mut Bencher) {
         b.iter(|| {
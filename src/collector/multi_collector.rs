@@ -1,14 +1,17 @@
-use std::io;
+use Result;
 use super::Collector;
 use ScoredDoc;
 use SegmentReader;
 use SegmentLocalId;
+use Score;
+use std::f32;
 
 
 /// Multicollector makes it possible to collect on more than one collector.
 /// It should only be used for use cases where the Collector types is unknown 
 /// at compile time.
-/// If the type of the collectors is known, you should prefer to use `ChainedCollector`.
+/// If the type of the collectors is known, you should prefer to use `ChainedCollector`,
+/// or a tuple of collectors, like `(TopCollector, CountCollector)`.
 pub struct MultiCollector<'a> {
     collectors: Vec<&'a mut Collector>,
 }
@@ -24,7 +27,7 @@ impl<'a> MultiCollector<'a> {
 
 
 impl<'a> Collector for MultiCollector<'a> {
-    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> io::Result<()> {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
         for collector in &mut self.collectors {
             try!(collector.set_segment(segment_local_id, segment));
         }
@@ -36,6 +39,41 @@ impl<'a> Collector for MultiCollector<'a> {
             collector.collect(scored_doc);
         }
     }
+
+    fn min_score(&self,) -> Option<Score> {
+        let mut min_score = f32::INFINITY;
+        for collector in &self.collectors {
+            match collector.min_score() {
+                Some(collector_min_score) => {
+                    min_score = min_score.min(collector_min_score);
+                }
+                None => {
+                    return None;
+                }
+            }
+        }
+        Some(min_score)
+    }
+
+    // the search can only stop once none of the
+    // collectors needs more documents.
+    fn should_terminate(&self,) -> bool {
+        self.collectors
+            .iter()
+            .all(|collector| collector.should_terminate())
+    }
+
+    // all of the collectors are finished, even if one of them fails.
+    fn finish(&mut self,) -> Result<()> {
+        let mut result = Ok(());
+        for collector in &mut self.collectors {
+            let collector_result = collector.finish();
+            if result.is_ok() {
+                result = collector_result;
+            }
+        }
+        result
+    }
 }
 
 
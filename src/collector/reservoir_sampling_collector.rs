@@ -0,0 +1,142 @@
+use Result;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocAddress;
+use rand::{Rng, SeedableRng, Isaac64Rng};
+
+
+/// `ReservoirSamplingCollector` keeps a uniform random sample
+/// of N of the documents matching the query, rather than the best ones.
+///
+/// It relies on reservoir sampling: the k-th document
+/// replaces one of the documents of the sample with
+/// probability `N / k`.
+/// The sample is deterministic for a given seed, as long as the
+/// documents are collected in the same order.
+pub struct ReservoirSamplingCollector {
+    limit: usize,
+    num_collected: u64,
+    sample: Vec<DocAddress>,
+    rng: Isaac64Rng,
+    segment_id: u32,
+}
+
+impl ReservoirSamplingCollector {
+
+    /// Creates a collector sampling `limit` documents,
+    /// the sample being drawn from `seed`.
+    pub fn new(limit: usize, seed: u64) -> ReservoirSamplingCollector {
+        ReservoirSamplingCollector {
+            limit: limit,
+            num_collected: 0u64,
+            sample: Vec::with_capacity(limit),
+            // unlike `XorShiftRng`, ISAAC-64 gives unrelated
+            // sequences for close seeds.
+            rng: Isaac64Rng::from_seed(&[seed]),
+            segment_id: 0,
+        }
+    }
+
+    /// Returns the sampled documents, sorted by `DocAddress`.
+    ///
+    /// If less than N documents were collected,
+    /// all of them are returned.
+    pub fn docs(&self) -> Vec<DocAddress> {
+        let mut docs = self.sample.clone();
+        docs.sort();
+        docs
+    }
+}
+
+impl Collector for ReservoirSamplingCollector {
+
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let doc_address = DocAddress(self.segment_id, scored_doc.doc());
+        self.num_collected += 1;
+        if self.sample.len() < self.limit {
+            self.sample.push(doc_address);
+        }
+        else {
+            let pos = self.rng.gen_range(0u64, self.num_collected) as usize;
+            if pos < self.limit {
+                self.sample[pos] = doc_address;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, CountCollector};
+
+    #[test]
+    fn test_reservoir_sampling_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let segments: Vec<Vec<usize>> = vec!((0..15).collect(), (15..30).collect());
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&i| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, if i % 3 == 0 { "b" } else { "a" });
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let sample = |limit: usize, seed: u64| {
+            let mut sampling_collector = ReservoirSamplingCollector::new(limit, seed);
+            let mut count_collector = CountCollector::default();
+            {
+                let mut collectors = MultiCollector::from(vec!(&mut sampling_collector, &mut count_collector));
+                query.search(&searcher, &mut collectors).unwrap();
+            }
+            assert_eq!(count_collector.count(), 20);
+            sampling_collector.docs()
+        };
+        // all of the documents are returned if there are less than N of them.
+        assert_eq!(sample(100, 1).len(), 20);
+        assert_eq!(sample(100, 1), sample(100, 2));
+        let first_sample = sample(5, 1);
+        assert_eq!(first_sample.len(), 5);
+        assert_eq!(first_sample, sample(5, 1));
+        for doc_address in &first_sample {
+            // the documents "b" are the multiples of 3.
+            let doc = doc_address.doc() + 15 * doc_address.segment_ord();
+            assert!(doc % 3 != 0);
+        }
+        assert!(first_sample != sample(5, 2) || first_sample != sample(5, 3));
+        assert!(sample(0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_reservoir_sampling_uniformity() {
+        let num_docs = 10;
+        let num_samples = 2_000;
+        let mut counts = vec!(0u32; num_docs);
+        for seed in 0..num_samples {
+            let mut sampling_collector = ReservoirSamplingCollector::new(2, seed);
+            for doc in 0..num_docs {
+                sampling_collector.collect(ScoredDoc(1f32, doc as u32));
+            }
+            for doc_address in sampling_collector.docs() {
+                counts[doc_address.doc() as usize] += 1;
+            }
+        }
+        // each document is expected to be sampled 400 times.
+        for &count in &counts {
+            assert!(count > 300 && count < 500, "count: {}", count);
+        }
+    }
+}
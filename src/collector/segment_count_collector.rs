@@ -0,0 +1,95 @@
+use Result;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use core::SegmentId;
+
+
+/// `SegmentCountCollector` counts how many documents
+/// match the query in each of the segments, e.g. to
+/// spot a segment matching an unexpected share of the hits.
+pub struct SegmentCountCollector {
+    counts: Vec<(SegmentId, usize)>,
+}
+
+impl SegmentCountCollector {
+
+    /// Returns the count of documents of each of the segments
+    /// the collector went through, in the order of the search.
+    pub fn counts_per_segment(&self,) -> Vec<(SegmentId, usize)> {
+        self.counts.clone()
+    }
+
+    /// Returns the overall count of documents.
+    pub fn count(&self,) -> usize {
+        self.counts
+            .iter()
+            .map(|&(_, count)| count)
+            .fold(0, |total, count| total + count)
+    }
+}
+
+impl Default for SegmentCountCollector {
+    fn default() -> SegmentCountCollector {
+        SegmentCountCollector {
+            counts: Vec::new(),
+        }
+    }
+}
+
+impl Collector for SegmentCountCollector {
+
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.counts.push((reader.segment_id(), 0));
+        Ok(())
+    }
+
+    fn collect(&mut self, _: ScoredDoc) {
+        self.counts
+            .last_mut()
+            .expect("collect called before set_segment")
+            .1 += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, CountCollector};
+    use collector::tests::create_index;
+
+    #[test]
+    fn test_segment_count_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = create_index(schema_builder.build().unwrap(), &[vec!("a", "b", "a b"), vec!("b", "b"), vec!("a", "a", "a", "b")], |text| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut segment_count_collector = SegmentCountCollector::default();
+        let mut count_collector = CountCollector::default();
+        {
+            let mut collectors = MultiCollector::from(vec!(&mut segment_count_collector, &mut count_collector));
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        assert_eq!(segment_count_collector.count(), count_collector.count());
+        let counts_per_segment = segment_count_collector.counts_per_segment();
+        assert_eq!(counts_per_segment.len(), 3);
+        for (segment_reader, &(segment_id, count)) in searcher.segment_readers().iter().zip(counts_per_segment.iter()) {
+            assert_eq!(segment_reader.segment_id(), segment_id);
+            assert_eq!(count as u32, segment_reader.doc_freq(&Term::from_field_text(text_field, "a")));
+        }
+        let mut counts: Vec<usize> = counts_per_segment.iter().map(|&(_, count)| count).collect();
+        counts.sort();
+        assert_eq!(counts, vec!(0, 2, 3));
+    }
+}
@@ -0,0 +1,227 @@
+use Result;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use schema::Field;
+use fastfield::U32FastFieldReader;
+
+
+/// `StatsCollector` computes the count, min, max, sum,
+/// mean and variance of the values of a u32 fast field
+/// over the documents matching the query, like the
+/// average price of the matching products.
+///
+/// The values are accumulated as the collector goes through
+/// the segments, the sum being accumulated in a `u64`.
+///
+/// The documents of the segments that do not contain the field,
+/// because they were created before it was added to the schema,
+/// are skipped by default. They can be accounted for with the
+/// default value of the field with `set_use_default_value`.
+pub struct StatsCollector {
+    field: Field,
+    use_default_value: bool,
+    count: u64,
+    min: u32,
+    max: u32,
+    sum: u64,
+    // running mean and sum of the squares of the differences
+    // to the mean, as in Welford's algorithm.
+    mean: f64,
+    m2: f64,
+    ff_reader: Option<U32FastFieldReader>,
+}
+
+impl StatsCollector {
+
+    /// Creates a stats collector for the given u32 fast field.
+    pub fn new(field: Field) -> StatsCollector {
+        StatsCollector {
+            field: field,
+            use_default_value: false,
+            count: 0u64,
+            min: u32::max_value(),
+            max: 0u32,
+            sum: 0u64,
+            mean: 0f64,
+            m2: 0f64,
+            ff_reader: None,
+        }
+    }
+
+    /// If `use_default_value` is true, the documents of the segments
+    /// that do not contain the field are accounted for with the default
+    /// value of the field (see `U32Options::set_default_value`),
+    /// rather than being skipped.
+    pub fn set_use_default_value(&mut self, use_default_value: bool) {
+        self.use_default_value = use_default_value;
+    }
+
+    /// Returns the number of values accumulated.
+    pub fn count(&self,) -> u64 {
+        self.count
+    }
+
+    /// Returns the smallest value, or `None` if no value
+    /// was accumulated.
+    pub fn min(&self,) -> Option<u32> {
+        if self.count == 0 { None } else { Some(self.min) }
+    }
+
+    /// Returns the largest value, or `None` if no value
+    /// was accumulated.
+    pub fn max(&self,) -> Option<u32> {
+        if self.count == 0 { None } else { Some(self.max) }
+    }
+
+    /// Returns the sum of the values.
+    pub fn sum(&self,) -> u64 {
+        self.sum
+    }
+
+    /// Returns the mean of the values, or `None` if no value
+    /// was accumulated.
+    pub fn mean(&self,) -> Option<f64> {
+        if self.count == 0 { None } else { Some(self.sum as f64 / self.count as f64) }
+    }
+
+    /// Returns the (population) variance of the values, or `None`
+    /// if no value was accumulated.
+    pub fn variance(&self,) -> Option<f64> {
+        if self.count == 0 { None } else { Some(self.m2 / self.count as f64) }
+    }
+
+    fn add(&mut self, val: u32) {
+        self.count += 1;
+        self.sum += val as u64;
+        if val < self.min {
+            self.min = val;
+        }
+        if val > self.max {
+            self.max = val;
+        }
+        let delta = val as f64 - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (val as f64 - self.mean);
+    }
+}
+
+impl Collector for StatsCollector {
+
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = if self.use_default_value || reader.has_fast_field(self.field) {
+            Some(try!(reader.get_fast_field_reader(self.field)))
+        }
+        else {
+            None
+        };
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let val = match self.ff_reader {
+            Some(ref ff_reader) => ff_reader.get(scored_doc.doc()),
+            None => {
+                return;
+            }
+        };
+        self.add(val);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use tempdir::TempDir;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, U32Options, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, CountCollector};
+
+    fn abs_diff(left: f64, right: f64) -> f64 {
+        (right - left).abs()
+    }
+
+    #[test]
+    fn test_stats_collector_empty() {
+        let stats_collector = StatsCollector::new(Field(0));
+        assert_eq!(stats_collector.count(), 0);
+        assert_eq!(stats_collector.sum(), 0);
+        assert_eq!(stats_collector.min(), None);
+        assert_eq!(stats_collector.max(), None);
+        assert_eq!(stats_collector.mean(), None);
+        assert_eq!(stats_collector.variance(), None);
+    }
+
+    #[test]
+    fn test_stats_collector() {
+        let temp_dir = TempDir::new("index").unwrap();
+        {
+            // the first segment is created before the price field.
+            let mut schema_builder = SchemaBuilder::default();
+            let text_field = schema_builder.add_text_field("text", TEXT);
+            let index = Index::create(temp_dir.path(), schema_builder.build().unwrap()).unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["a", "a", "b"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_fast().set_default_value(7));
+        let index = Index::open_or_extend(temp_dir.path(), schema_builder.build().unwrap()).unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // the sum of the prices overflows a u32.
+            for segment_docs in &[vec!(("a", 4_000_000_000), ("b", 100), ("a", 2)), vec!(("a", 4_000_000_000), ("a", 6))] {
+                for &(text, price) in segment_docs {
+                    let mut doc = Document::default();
+                    doc.add_text(text_field, text);
+                    doc.add_u32(price_field, price);
+                    index_writer.add_document(doc).unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+        }
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let check_stats = |stats_collector: &StatsCollector, prices: &[u32]| {
+            let count = prices.len() as u64;
+            let sum = prices.iter().fold(0u64, |sum, &price| sum + price as u64);
+            let mean = sum as f64 / count as f64;
+            let variance = prices
+                .iter()
+                .map(|&price| (price as f64 - mean) * (price as f64 - mean))
+                .fold(0f64, |sum, square| sum + square) / count as f64;
+            assert_eq!(stats_collector.count(), count);
+            assert_eq!(stats_collector.sum(), sum);
+            assert_eq!(stats_collector.min(), prices.iter().cloned().min());
+            assert_eq!(stats_collector.max(), prices.iter().cloned().max());
+            assert!(abs_diff(stats_collector.mean().unwrap(), mean) < 0.001f64);
+            assert!(abs_diff(stats_collector.variance().unwrap(), variance) / variance < 0.000_001f64);
+        };
+        {
+            let mut stats_collector = StatsCollector::new(price_field);
+            let mut count_collector = CountCollector::default();
+            {
+                let mut collectors = MultiCollector::from(vec!(&mut stats_collector, &mut count_collector));
+                query.search(&searcher, &mut collectors).unwrap();
+            }
+            assert_eq!(count_collector.count(), 6);
+            check_stats(&stats_collector, &[4_000_000_000, 2, 4_000_000_000, 6]);
+        }
+        {
+            let mut stats_collector = StatsCollector::new(price_field);
+            stats_collector.set_use_default_value(true);
+            query.search(&searcher, &mut stats_collector).unwrap();
+            check_stats(&stats_collector, &[7, 7, 4_000_000_000, 2, 4_000_000_000, 6]);
+        }
+    }
+}
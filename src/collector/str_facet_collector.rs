@@ -0,0 +1,261 @@
+use Result;
+use Error;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use core::SegmentId;
+use core::searcher::Searcher;
+use schema::Field;
+use fastfield::MultiU32FastFieldReader;
+
+
+/// Counts of the term ordinals of a segment.
+struct SegmentOrdCounts {
+    segment_local_id: SegmentLocalId,
+    segment_id: SegmentId,
+    // `counts[ord]` is the count of the term of ordinal `ord`.
+    counts: Vec<u32>,
+}
+
+
+/// `StrFacetCollector` counts the documents matching the query
+/// for each of the values of a fast text field, like the documents
+/// of each tag, without any external string to u32 mapping.
+///
+/// Within a segment, the documents are counted by term ordinals,
+/// in an array. The term ordinals are only resolved back to their
+/// terms at the end, in a single pass over the terms of the field
+/// of each segment, so that the results are read with the searcher
+/// the collector was run on.
+///
+/// A document with several values is counted once for each
+/// of its distinct values.
+pub struct StrFacetCollector {
+    field: Field,
+    segment_counts: Vec<SegmentOrdCounts>,
+    term_ords_reader: Option<MultiU32FastFieldReader>,
+    ords: Vec<u32>,
+}
+
+impl StrFacetCollector {
+
+    /// Creates a new facet collector for the given fast text field.
+    pub fn for_field(field: Field) -> StrFacetCollector {
+        StrFacetCollector {
+            field: field,
+            segment_counts: Vec::new(),
+            term_ords_reader: None,
+            ords: Vec::new(),
+        }
+    }
+
+    /// Returns the count of every value of the field
+    /// that appeared in at least one document.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `searcher` does
+    /// not have the segments the collector was run on.
+    pub fn counts(&self, searcher: &Searcher) -> Result<HashMap<String, u32>> {
+        let segment_readers = searcher.segment_readers();
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for segment_counts in &self.segment_counts {
+            let segment_reader = match segment_readers.get(segment_counts.segment_local_id as usize) {
+                Some(segment_reader) if segment_reader.segment_id() == segment_counts.segment_id => segment_reader,
+                _ => {
+                    return Err(Error::InvalidArgument(format!("The segment {:?} is not part of the searcher.", segment_counts.segment_id)));
+                }
+            };
+            let ords: Vec<u32> = segment_counts.counts
+                .iter()
+                .enumerate()
+                .filter(|&(_, &count)| count > 0)
+                .map(|(ord, _)| ord as u32)
+                .collect();
+            let terms = try!(segment_reader.ords_to_terms(self.field, &ords));
+            for (&ord, term_bytes) in ords.iter().zip(terms.iter()) {
+                let term = String::from_utf8_lossy(term_bytes).into_owned();
+                *counts.entry(term).or_insert(0u32) += segment_counts.counts[ord as usize];
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Returns the `n` values with the largest counts, together
+    /// with their count, sorted by decreasing count.
+    ///
+    /// The ties are sorted by increasing value.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `searcher` does
+    /// not have the segments the collector was run on.
+    pub fn top_n(&self, searcher: &Searcher, n: usize) -> Result<Vec<(String, u32)>> {
+        let mut buckets: Vec<(String, u32)> = try!(self.counts(searcher)).into_iter().collect();
+        buckets.sort_by(|&(ref left_term, left_count), &(ref right_term, right_count)| {
+            match right_count.cmp(&left_count) {
+                Ordering::Equal => left_term.cmp(right_term),
+                count_ordering => count_ordering,
+            }
+        });
+        buckets.truncate(n);
+        Ok(buckets)
+    }
+}
+
+impl Collector for StrFacetCollector {
+
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        let term_ords_reader = try!(reader.term_ords_fast_field(self.field));
+        // the ordinals are the ranks of the terms of the
+        // segment, from 0 to the largest ordinal.
+        let num_terms = if term_ords_reader.idx_reader().get(reader.max_doc()) > 0 {
+            term_ords_reader.vals_reader().max_val() as usize + 1
+        }
+        else {
+            0
+        };
+        self.segment_counts.push(SegmentOrdCounts {
+            segment_local_id: segment_local_id,
+            segment_id: reader.segment_id(),
+            counts: vec!(0u32; num_terms),
+        });
+        self.term_ords_reader = Some(term_ords_reader);
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let term_ords_reader = self.term_ords_reader
+            .as_ref()
+            .expect("collect called before set_segment");
+        term_ords_reader.get_vals(scored_doc.doc(), &mut self.ords);
+        let segment_counts = self.segment_counts
+            .last_mut()
+            .expect("collect called before set_segment");
+        for &ord in &self.ords {
+            segment_counts.counts[ord as usize] += 1;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, TextIndexingOptions, STRING, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::{MultiCollector, CountCollector};
+
+    fn tagged_doc(text_field: Field, tag_field: Field, &(text, ref tags): &(&str, Vec<&str>)) -> Document {
+        let mut doc = Document::default();
+        doc.add_text(text_field, text);
+        for tag in tags {
+            doc.add_text(tag_field, tag);
+        }
+        doc
+    }
+
+    fn expected_counts(counts: &[(&str, u32)]) -> HashMap<String, u32> {
+        counts
+            .iter()
+            .map(|&(term, count)| (String::from(term), count))
+            .collect()
+    }
+
+    #[test]
+    fn test_str_facet_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let tag_field = schema_builder.add_text_field("tag", STRING.set_fast().set_indexing_options(TextIndexingOptions::UntokenizedLowercase));
+        // the ordinal of `rust` differs in the first two segments.
+        let segments = vec!(
+            vec!(("a", vec!("rust", "search")), ("a", vec!("Rust", "rust")), ("b", vec!("rust"))),
+            vec!(("a", vec!()), ("a", vec!("index", "search", "rust")), ("b", vec!("index"))),
+            vec!(("a", vec!("zebra")), ("b", vec!())),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |doc_values| tagged_doc(text_field, tag_field, doc_values));
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        {
+            let segment_reader = searcher
+                .segment_readers()
+                .iter()
+                .find(|segment_reader| segment_reader.doc_freq(&Term::from_field_text(tag_field, "index")) > 0)
+                .unwrap();
+            let term_ords_reader = segment_reader.term_ords_fast_field(tag_field).unwrap();
+            let mut ords = Vec::new();
+            term_ords_reader.get_vals(1, &mut ords);
+            assert_eq!(ords, vec!(0, 1, 2));
+            assert_eq!(segment_reader.ords_to_terms(tag_field, &[2, 0, 2]).unwrap(), vec!(b"search".to_vec(), b"index".to_vec(), b"search".to_vec()));
+            term_ords_reader.get_vals(0, &mut ords);
+            assert!(ords.is_empty());
+            assert_eq!(segment_reader.ords_to_terms(tag_field, &[1]).unwrap(), vec!(b"rust".to_vec()));
+            // the segment only has 3 tags.
+            assert!(segment_reader.ords_to_terms(tag_field, &[1, 3]).is_err());
+        }
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut count_collector = CountCollector::default();
+        let mut str_facet_collector = StrFacetCollector::for_field(tag_field);
+        {
+            let mut collectors = MultiCollector::from(vec!(&mut count_collector, &mut str_facet_collector));
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        assert_eq!(count_collector.count(), 5);
+        // the values of a field are counted once per document.
+        assert_eq!(str_facet_collector.counts(&searcher).unwrap(), expected_counts(&[("rust", 3), ("search", 2), ("index", 1), ("zebra", 1)]));
+        assert_eq!(str_facet_collector.top_n(&searcher, 3).unwrap(), vec!((String::from("rust"), 3), (String::from("search"), 2), (String::from("index"), 1)));
+        // the segments got merged.
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        assert!(str_facet_collector.counts(&index.searcher()).is_err());
+        let merged_searcher = index.searcher();
+        assert_eq!(merged_searcher.segment_readers().len(), 1);
+        let mut merged_facet_collector = StrFacetCollector::for_field(tag_field);
+        query.search(&merged_searcher, &mut merged_facet_collector).unwrap();
+        assert_eq!(merged_facet_collector.counts(&merged_searcher).unwrap(), str_facet_collector.counts(&searcher).unwrap());
+        {
+            let segment_reader = merged_searcher.segment_reader(0);
+            assert_eq!(segment_reader.ords_to_terms(tag_field, &[0, 1, 2, 3]).unwrap(), vec!(b"index".to_vec(), b"rust".to_vec(), b"search".to_vec(), b"zebra".to_vec()));
+            // the ordinals got remapped to the merged terms.
+            let term_ords_reader = segment_reader.term_ords_fast_field(tag_field).unwrap();
+            let mut ords = Vec::new();
+            let mut doc_terms: Vec<Vec<Vec<u8>>> = (0..segment_reader.max_doc())
+                .map(|doc| {
+                    term_ords_reader.get_vals(doc, &mut ords);
+                    segment_reader.ords_to_terms(tag_field, &ords).unwrap()
+                })
+                .collect();
+            doc_terms.sort();
+            let mut expected_doc_terms: Vec<Vec<Vec<u8>>> = segments
+                .iter()
+                .flat_map(|segment_docs| segment_docs.iter())
+                .map(|&(_, ref tags)| {
+                    let mut terms: Vec<Vec<u8>> = tags.iter().map(|tag| tag.to_lowercase().into_bytes()).collect();
+                    terms.sort();
+                    terms.dedup();
+                    terms
+                })
+                .collect();
+            expected_doc_terms.sort();
+            assert_eq!(doc_terms, expected_doc_terms);
+        }
+    }
+
+    #[test]
+    fn test_str_facet_collector_not_fast() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = create_index(schema_builder.build().unwrap(), &[vec!(("a", vec!("rust")))], |doc_values| tagged_doc(text_field, tag_field, doc_values));
+        let searcher = index.searcher();
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut str_facet_collector = StrFacetCollector::for_field(tag_field);
+        assert!(query.search(&searcher, &mut str_facet_collector).is_err());
+    }
+}
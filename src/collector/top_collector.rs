@@ -1,4 +1,5 @@
-use std::io;
+use Result;
+use Error;
 use super::Collector;
 use ScoredDoc;
 use SegmentReader;
@@ -21,13 +22,15 @@ impl PartialOrd for GlobalScoredDoc {
     }
 }
 
+// The documents are ordered by decreasing score, and
+// the ties are broken by increasing `DocAddress`.
 impl Ord for GlobalScoredDoc {
     #[inline]
     fn cmp(&self, other: &GlobalScoredDoc) -> Ordering {
-        other.score.partial_cmp(&self.score)
-        .unwrap_or(
-            other.doc_address.cmp(&self.doc_address)
-        )
+        match other.score.partial_cmp(&self.score) {
+            Some(Ordering::Equal) | None => self.doc_address.cmp(&other.doc_address),
+            Some(ordering) => ordering,
+        }
     }
 }
 
@@ -43,10 +46,18 @@ impl Eq for GlobalScoredDoc {}
 /// The Top Collector keeps track of the K documents
 /// with the best scores.
 ///
+/// The documents with the same score are sorted
+/// by their `DocAddress`, so that the result does
+/// not depend on the order of the collection.
+///
+/// With an offset, the `offset` best documents are skipped,
+/// to return a page of results.
+///
 /// The implementation is based on a `BinaryHeap`.
-/// The theorical complexity is `O(n log K)`.
+/// The theorical complexity is `O(n log (K + offset))`.
 pub struct TopCollector {
     limit: usize,
+    offset: usize,
     heap: BinaryHeap<GlobalScoredDoc>,
     segment_id: u32,
 }
@@ -55,17 +66,35 @@ impl TopCollector {
 
     /// Creates a top collector, with a number of documents equal to "limit".
     ///
-    /// # Panics
-    /// The method panics if limit is 0
-    pub fn with_limit(limit: usize) -> TopCollector {
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if limit is 0.
+    pub fn new(limit: usize) -> Result<TopCollector> {
+        TopCollector::with_capacity_and_offset(limit, 0)
+    }
+
+    /// Creates a top collector returning the `limit` documents
+    /// following the `offset` best documents.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if limit is 0.
+    pub fn with_capacity_and_offset(limit: usize, offset: usize) -> Result<TopCollector> {
         if limit < 1 {
-            panic!("Limit must be strictly greater than 0.");
+            return Err(Error::InvalidArgument(String::from("Limit must be strictly greater than 0.")));
         }
-        TopCollector {
+        Ok(TopCollector {
             limit: limit,
-            heap: BinaryHeap::with_capacity(limit),
+            offset: offset,
+            heap: BinaryHeap::with_capacity(limit + offset),
             segment_id: 0,
-        }
+        })
+    }
+
+    /// Creates a top collector, with a number of documents equal to "limit".
+    ///
+    /// # Panics
+    /// The method panics if limit is 0, see `new`.
+    pub fn with_limit(limit: usize) -> TopCollector {
+        TopCollector::new(limit).expect("Limit must be strictly greater than 0.")
     }
     
     /// Returns K best documents sorted in decreasing order.
@@ -73,38 +102,49 @@ impl TopCollector {
     /// Calling this method triggers the sort.
     /// The result of the sort is not cached.
     pub fn docs(&self) -> Vec<DocAddress> {
-        self.score_docs()
+        self.top_docs()
             .into_iter()
             .map(|score_doc| score_doc.1)
             .collect()
     }
 
-    /// Returns K best ScoredDocument sorted in decreasing order.
-    /// 
+    /// Returns the K best documents along with their scores,
+    /// sorted by decreasing score, after the `offset` best ones.
+    ///
+    /// The ties are sorted by increasing `DocAddress`.
+    ///
     /// Calling this method triggers the sort.
     /// The result of the sort is not cached.
-    pub fn score_docs(&self) -> Vec<(Score, DocAddress)> {
+    pub fn top_docs(&self) -> Vec<(Score, DocAddress)> {
         let mut scored_docs: Vec<GlobalScoredDoc> = self.heap
             .iter()
             .cloned()
             .collect();
         scored_docs.sort();
         scored_docs.into_iter()
+            .skip(self.offset)
             .map(|GlobalScoredDoc {score, doc_address}| (score, doc_address))
             .collect()
     }
 
-    /// Return true iff at least K documents have gone through
-    /// the collector.
+    /// Returns K best ScoredDocument sorted in decreasing order.
+    ///
+    /// Same as `top_docs`.
+    pub fn score_docs(&self) -> Vec<(Score, DocAddress)> {
+        self.top_docs()
+    }
+
+    /// Return true iff at least K documents, plus the offset,
+    /// have gone through the collector.
     #[inline]
     pub fn at_capacity(&self, ) -> bool {
-        self.heap.len() >= self.limit
+        self.heap.len() >= self.limit + self.offset
     }
 }
 
 impl Collector for TopCollector {
 
-    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> io::Result<()> {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
         self.segment_id = segment_id;
         Ok(())
     }
@@ -128,6 +168,17 @@ impl Collector for TopCollector {
         }
 
     }
+
+    /// Once the collector holds K documents, only the documents
+    /// scoring more than the worst of them are collected.
+    fn min_score(&self,) -> Option<Score> {
+        if self.at_capacity() {
+            self.heap.peek().map(|limit_doc| limit_doc.score)
+        }
+        else {
+            None
+        }
+    }
 }
 
 
@@ -147,6 +198,7 @@ mod tests {
         top_collector.collect(ScoredDoc(0.2, 3));
         top_collector.collect(ScoredDoc(0.3, 5));
         assert!(!top_collector.at_capacity());
+        assert_eq!(top_collector.min_score(), None);
         let score_docs: Vec<(Score, DocId)> = top_collector.score_docs()
             .into_iter()
             .map(|(score, doc_address)| (score, doc_address.doc()))
@@ -165,6 +217,7 @@ mod tests {
         top_collector.collect(ScoredDoc(0.9, 7));
         top_collector.collect(ScoredDoc(-0.2, 9));
         assert!(top_collector.at_capacity());
+        assert_eq!(top_collector.min_score(), Some(0.2));
         {
             let score_docs: Vec<(Score, DocId)> = top_collector
                 .score_docs()
@@ -192,4 +245,44 @@ mod tests {
     fn test_top_0() {
         TopCollector::with_limit(0);
     }
+
+    #[test]
+    fn test_top_collector_new() {
+        assert!(TopCollector::new(0).is_err());
+        assert!(TopCollector::with_capacity_and_offset(0, 10).is_err());
+        assert!(TopCollector::new(1).is_ok());
+    }
+
+    #[test]
+    fn test_top_collector_ties() {
+        let mut top_collector = TopCollector::new(3).unwrap();
+        top_collector.segment_id = 0;
+        top_collector.collect(ScoredDoc(0.5, 1));
+        top_collector.collect(ScoredDoc(0.5, 2));
+        top_collector.segment_id = 1;
+        top_collector.collect(ScoredDoc(0.8, 0));
+        top_collector.collect(ScoredDoc(0.5, 0));
+        assert_eq!(top_collector.top_docs(), vec!(
+            (0.8, DocAddress(1, 0)), (0.5, DocAddress(0, 1)), (0.5, DocAddress(0, 2)),
+        ));
+    }
+
+    #[test]
+    fn test_top_collector_offset() {
+        let mut top_collector = TopCollector::with_capacity_and_offset(2, 3).unwrap();
+        for (doc, &score) in [0.1, 0.7, 0.3, 0.9, 0.5, 0.2, 0.8].iter().enumerate() {
+            top_collector.collect(ScoredDoc(score, doc as DocId));
+        }
+        assert!(top_collector.at_capacity());
+        assert_eq!(top_collector.min_score(), Some(0.3));
+        assert_eq!(top_collector.top_docs(), vec!(
+            (0.5, DocAddress(0, 4)), (0.3, DocAddress(0, 2)),
+        ));
+        assert_eq!(top_collector.docs(), vec!(DocAddress(0, 4), DocAddress(0, 2)));
+        // a page past the documents is empty.
+        let mut top_collector = TopCollector::with_capacity_and_offset(2, 10).unwrap();
+        top_collector.collect(ScoredDoc(0.5, 1));
+        assert!(top_collector.top_docs().is_empty());
+        assert_eq!(top_collector.min_score(), None);
+    }
 }
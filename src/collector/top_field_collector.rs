@@ -0,0 +1,385 @@
+use Result;
+use Error;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocAddress;
+use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use Score;
+use schema::Field;
+use fastfield::{U32FastFieldReader, I64FastFieldReader};
+use common::{i64_to_u64, u64_to_i64};
+
+
+/// Order of the documents sorted by a `TopFieldCollector`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Order {
+    /// Smallest values first.
+    Asc,
+    /// Largest values first.
+    Desc,
+}
+
+// Rust heap is a max-heap and we need a min heap.
+//
+// `key` is the value of the fast field, mapped to a u64 in an
+// order preserving way and complemented for `Order::Asc`,
+// so that the best documents always have the largest keys.
+#[derive(Clone, Copy)]
+struct FieldScoredDoc {
+    key: u64,
+    score: Score,
+    doc_address: DocAddress,
+}
+
+// The documents are ordered by decreasing key, the ties
+// being broken by decreasing score, then by increasing `DocAddress`.
+impl Ord for FieldScoredDoc {
+    #[inline]
+    fn cmp(&self, other: &FieldScoredDoc) -> Ordering {
+        match other.key.cmp(&self.key) {
+            Ordering::Equal => {
+                match other.score.partial_cmp(&self.score).unwrap_or(Ordering::Equal) {
+                    Ordering::Equal => self.doc_address.cmp(&other.doc_address),
+                    score_ordering => score_ordering,
+                }
+            }
+            key_ordering => key_ordering,
+        }
+    }
+}
+
+impl PartialOrd for FieldScoredDoc {
+    fn partial_cmp(&self, other: &FieldScoredDoc) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for FieldScoredDoc {
+    fn eq(&self, other: &FieldScoredDoc) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FieldScoredDoc {}
+
+
+/// Reader of the values the documents are sorted by.
+enum SortFieldReader {
+    U32(U32FastFieldReader),
+    // i64 and date fast fields.
+    I64(I64FastFieldReader),
+}
+
+impl SortFieldReader {
+    fn get(&self, doc: u32) -> u64 {
+        match *self {
+            SortFieldReader::U32(ref u32_reader) => u32_reader.get(doc) as u64,
+            SortFieldReader::I64(ref i64_reader) => i64_to_u64(i64_reader.get(doc)),
+        }
+    }
+}
+
+
+/// The Top Field Collector keeps track of the K documents
+/// with the best values of a u32, i64 or date fast field,
+/// like the most recent documents given their timestamp.
+///
+/// The values of a u32 field are returned by `top_docs`, and
+/// the values of an i64 or date field by `top_i64_docs`.
+///
+/// The documents with the same value are sorted by decreasing
+/// score, then by their `DocAddress`.
+///
+/// The implementation is based on a `BinaryHeap`.
+/// The theorical complexity is `O(n log K)`.
+pub struct TopFieldCollector {
+    field: Field,
+    limit: usize,
+    order: Order,
+    heap: BinaryHeap<FieldScoredDoc>,
+    segment_id: u32,
+    ff_reader: Option<SortFieldReader>,
+    // true iff the field is an i64 or date field.
+    i64_values: bool,
+}
+
+impl TopFieldCollector {
+
+    /// Creates a top field collector, with a number of documents
+    /// equal to "limit", sorted by the u32, i64 or date fast field `field`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if limit is 0.
+    pub fn new(field: Field, limit: usize, order: Order) -> Result<TopFieldCollector> {
+        if limit < 1 {
+            return Err(Error::InvalidArgument(String::from("Limit must be strictly greater than 0.")));
+        }
+        Ok(TopFieldCollector {
+            field: field,
+            limit: limit,
+            order: order,
+            heap: BinaryHeap::with_capacity(limit),
+            segment_id: 0,
+            ff_reader: None,
+            i64_values: false,
+        })
+    }
+
+    // the complement is its own inverse, so that
+    // `key` also maps back a key to its value.
+    fn key(&self, val: u64) -> u64 {
+        match self.order {
+            Order::Asc => !val,
+            Order::Desc => val,
+        }
+    }
+
+    // returns the K best documents along with their u64 value,
+    // sorted in the order of the collector.
+    fn sorted_docs(&self,) -> Vec<(u64, DocAddress)> {
+        let mut field_scored_docs: Vec<FieldScoredDoc> = self.heap
+            .iter()
+            .cloned()
+            .collect();
+        field_scored_docs.sort();
+        field_scored_docs.into_iter()
+            .map(|field_scored_doc| (self.key(field_scored_doc.key), field_scored_doc.doc_address))
+            .collect()
+    }
+
+    /// Returns the K best documents along with the value of
+    /// their u32 field, sorted in the order of the collector.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    ///
+    /// # Panics
+    /// Panics if the field is an i64 or date field,
+    /// whose values are returned by `top_i64_docs`.
+    pub fn top_docs(&self) -> Vec<(u32, DocAddress)> {
+        assert!(!self.i64_values, "The values of an i64 or date field are returned by top_i64_docs");
+        self.sorted_docs()
+            .into_iter()
+            .map(|(val, doc_address)| (val as u32, doc_address))
+            .collect()
+    }
+
+    /// Returns the K best documents along with the value of
+    /// their field, sorted in the order of the collector.
+    ///
+    /// The values of a date field are expressed in milliseconds
+    /// since Epoch. The values of a u32 field are returned as well.
+    pub fn top_i64_docs(&self) -> Vec<(i64, DocAddress)> {
+        self.sorted_docs()
+            .into_iter()
+            .map(|(val, doc_address)| {
+                let val = if self.i64_values { u64_to_i64(val) } else { val as i64 };
+                (val, doc_address)
+            })
+            .collect()
+    }
+
+    /// Returns K best documents sorted in the order of the collector.
+    pub fn docs(&self) -> Vec<DocAddress> {
+        self.sorted_docs()
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect()
+    }
+
+    /// Return true iff at least K documents have gone through
+    /// the collector.
+    #[inline]
+    pub fn at_capacity(&self, ) -> bool {
+        self.heap.len() >= self.limit
+    }
+}
+
+impl Collector for TopFieldCollector {
+
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        let field_entry = reader.schema().get_field_entry(self.field);
+        let ff_reader = if field_entry.is_i64_fast() {
+            SortFieldReader::I64(try!(reader.get_i64_fast_field_reader(self.field)))
+        }
+        else if field_entry.is_date_fast() {
+            SortFieldReader::I64(try!(reader.get_date_fast_field_reader(self.field)))
+        }
+        else {
+            SortFieldReader::U32(try!(reader.get_fast_field_reader(self.field)))
+        };
+        self.segment_id = segment_id;
+        self.i64_values = match ff_reader {
+            SortFieldReader::I64(_) => true,
+            SortFieldReader::U32(_) => false,
+        };
+        self.ff_reader = Some(ff_reader);
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let val = self.ff_reader
+            .as_ref()
+            .expect("collect called before set_segment")
+            .get(scored_doc.doc());
+        let field_scored_doc = FieldScoredDoc {
+            key: self.key(val),
+            score: scored_doc.score(),
+            doc_address: DocAddress(self.segment_id, scored_doc.doc()),
+        };
+        if self.at_capacity() {
+            // It's ok to unwrap as long as a limit of 0 is forbidden.
+            let mut mut_head = self.heap.peek_mut().expect("Top field collector with size 0 is forbidden");
+            if field_scored_doc < *mut_head {
+                *mut_head = field_scored_doc;
+            }
+        }
+        else {
+            self.heap.push(field_scored_doc);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, FAST, TEXT};
+    use schema::{DateOptions, I64Options, FieldValue, Value};
+    use std::i64;
+    use query::{AllQuery, Query, MultiTermQuery};
+    use schema::Term;
+
+    #[test]
+    fn test_top_field_collector_limit() {
+        assert!(TopFieldCollector::new(Field(0), 0, Order::Desc).is_err());
+    }
+
+    #[test]
+    fn test_top_field_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let timestamp_field = schema_builder.add_u32_field("timestamp", FAST);
+        // the order of the timestamps interleaves the segments.
+        let segments = vec!(
+            vec!(("a", 5), ("a b", 20), ("b", 1)),
+            vec!(("a", 10), ("b", 30), ("a", 20), ("a", 2)),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, timestamp)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc.add_u32(timestamp_field, timestamp);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let timestamp = |doc_address: &DocAddress| {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+            segment_reader.get_fast_field_reader(timestamp_field).unwrap().get(doc_address.doc())
+        };
+        {
+            let mut top_field_collector = TopFieldCollector::new(timestamp_field, 4, Order::Desc).unwrap();
+            AllQuery.search(&searcher, &mut top_field_collector).unwrap();
+            let top_docs = top_field_collector.top_docs();
+            let timestamps: Vec<u32> = top_docs.iter().map(|&(val, _)| val).collect();
+            assert_eq!(timestamps, vec!(30, 20, 20, 10));
+            for &(val, ref doc_address) in &top_docs {
+                assert_eq!(timestamp(doc_address), val);
+            }
+            // the ties are broken by `DocAddress`, as all of the scores are equal.
+            assert!(top_docs[1].1 < top_docs[2].1);
+            assert_eq!(top_field_collector.docs(), top_docs.iter().map(|&(_, doc_address)| doc_address).collect::<Vec<DocAddress>>());
+        }
+        {
+            let mut top_field_collector = TopFieldCollector::new(timestamp_field, 3, Order::Asc).unwrap();
+            AllQuery.search(&searcher, &mut top_field_collector).unwrap();
+            let top_docs = top_field_collector.top_docs();
+            let timestamps: Vec<u32> = top_docs.iter().map(|&(val, _)| val).collect();
+            assert_eq!(timestamps, vec!(1, 2, 5));
+            for &(val, ref doc_address) in &top_docs {
+                assert_eq!(timestamp(doc_address), val);
+            }
+        }
+        {
+            // the ties are broken by decreasing score.
+            let mut top_field_collector = TopFieldCollector::new(timestamp_field, 2, Order::Desc).unwrap();
+            let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+            query.search(&searcher, &mut top_field_collector).unwrap();
+            let top_docs = top_field_collector.top_docs();
+            assert_eq!(top_docs.len(), 2);
+            assert_eq!(top_docs[0].0, 20);
+            assert_eq!(top_docs[1].0, 20);
+            // "a" scores more than "a b".
+            let explanations: Vec<f32> = top_docs
+                .iter()
+                .map(|&(_, ref doc_address)| query.explain(&searcher, doc_address).unwrap().val())
+                .collect();
+            assert!(explanations[0] > explanations[1]);
+        }
+    }
+
+    #[test]
+    fn test_top_field_collector_date() {
+        let mut schema_builder = SchemaBuilder::default();
+        let date_field = schema_builder.add_date_field("date", DateOptions::default().set_fast());
+        // the dates before Epoch are negative.
+        let segments = vec!(
+            vec!(1_469_279_051_123i64, -5_000i64, 0i64),
+            vec!(-1_469_279_051_123i64, 1_469_279_051_999i64, 7i64),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&date| {
+            let mut doc = Document::default();
+            doc.add(FieldValue::new(date_field, Value::Date(date)));
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let date = |doc_address: &DocAddress| {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+            segment_reader.get_date_fast_field_reader(date_field).unwrap().get(doc_address.doc())
+        };
+        {
+            let mut top_field_collector = TopFieldCollector::new(date_field, 3, Order::Desc).unwrap();
+            searcher.search(&AllQuery, &mut top_field_collector).unwrap();
+            let top_docs = top_field_collector.top_i64_docs();
+            let dates: Vec<i64> = top_docs.iter().map(|&(val, _)| val).collect();
+            assert_eq!(dates, vec!(1_469_279_051_999i64, 1_469_279_051_123i64, 7i64));
+            for &(val, ref doc_address) in &top_docs {
+                assert_eq!(date(doc_address), val);
+            }
+        }
+        {
+            let mut top_field_collector = TopFieldCollector::new(date_field, 3, Order::Asc).unwrap();
+            searcher.search(&AllQuery, &mut top_field_collector).unwrap();
+            let top_docs = top_field_collector.top_i64_docs();
+            let dates: Vec<i64> = top_docs.iter().map(|&(val, _)| val).collect();
+            assert_eq!(dates, vec!(-1_469_279_051_123i64, -5_000i64, 0i64));
+            for &(val, ref doc_address) in &top_docs {
+                assert_eq!(date(doc_address), val);
+            }
+            assert_eq!(top_field_collector.docs(), top_docs.iter().map(|&(_, doc_address)| doc_address).collect::<Vec<DocAddress>>());
+        }
+    }
+
+    #[test]
+    fn test_top_field_collector_i64() {
+        let mut schema_builder = SchemaBuilder::default();
+        let i64_field = schema_builder.add_i64_field("i64", I64Options::default().set_fast());
+        let segments = vec!(vec!(4i64, -3i64), vec!(i64::MIN, 0i64));
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&val| {
+            let mut doc = Document::default();
+            doc.add_i64(i64_field, val);
+            doc
+        });
+        let searcher = index.searcher();
+        let mut top_field_collector = TopFieldCollector::new(i64_field, 3, Order::Asc).unwrap();
+        searcher.search(&AllQuery, &mut top_field_collector).unwrap();
+        let vals: Vec<i64> = top_field_collector.top_i64_docs().iter().map(|&(val, _)| val).collect();
+        assert_eq!(vals, vec!(i64::MIN, -3i64, 0i64));
+    }
+}
@@ -0,0 +1,350 @@
+use Result;
+use Error;
+use super::Collector;
+use super::Order;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocAddress;
+use core::SegmentId;
+use core::searcher::Searcher;
+use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use std::u32;
+use std::u64;
+use schema::Field;
+use fastfield::StrFastFieldReader;
+
+
+// Within a segment, the documents are compared by the ordinal of
+// their term. `key` is the ordinal, complemented for `Order::Desc`,
+// so that the best documents always have the smallest keys, and
+// the documents without any value get the largest key.
+//
+// Rust heap is a max-heap: its head is the worst document.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+struct SegmentOrdDoc {
+    key: u64,
+    doc_address: DocAddress,
+}
+
+/// The K best documents of a segment.
+struct SegmentTopDocs {
+    segment_local_id: SegmentLocalId,
+    segment_id: SegmentId,
+    heap: BinaryHeap<SegmentOrdDoc>,
+}
+
+
+/// The Top Str Field Collector keeps track of the K documents
+/// with the best values of a fast text field, like the documents
+/// sorted by `country`.
+///
+/// Within a segment, the documents are compared by the ordinal of
+/// their term, given by `SegmentReader::str_fast_field`. The K best
+/// documents of each segment are kept, and their ordinals are only
+/// resolved back to their terms at the end, through the term
+/// dictionary, to compare them with the documents of the other
+/// segments. The results are therefore read with the searcher
+/// the collector was run on.
+///
+/// The documents without any value for the field come last,
+/// and the documents with the same value are sorted by their
+/// `DocAddress`. A document with several values is sorted
+/// by its smallest value.
+pub struct TopStrFieldCollector {
+    field: Field,
+    limit: usize,
+    order: Order,
+    segment_top_docs: Vec<SegmentTopDocs>,
+    str_reader: Option<StrFastFieldReader>,
+}
+
+impl TopStrFieldCollector {
+
+    /// Creates a top str field collector, with a number of documents
+    /// equal to "limit", sorted by the fast text field `field`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if limit is 0.
+    pub fn new(field: Field, limit: usize, order: Order) -> Result<TopStrFieldCollector> {
+        if limit < 1 {
+            return Err(Error::InvalidArgument(String::from("Limit must be strictly greater than 0.")));
+        }
+        Ok(TopStrFieldCollector {
+            field: field,
+            limit: limit,
+            order: order,
+            segment_top_docs: Vec::new(),
+            str_reader: None,
+        })
+    }
+
+    fn key(&self, ord: Option<u32>) -> u64 {
+        match (ord, self.order) {
+            (Some(ord), Order::Asc) => ord as u64,
+            (Some(ord), Order::Desc) => (u32::MAX - ord) as u64,
+            (None, _) => u64::MAX,
+        }
+    }
+
+    fn ord(&self, key: u64) -> Option<u32> {
+        match (key, self.order) {
+            (u64::MAX, _) => None,
+            (key, Order::Asc) => Some(key as u32),
+            (key, Order::Desc) => Some(u32::MAX - key as u32),
+        }
+    }
+
+    fn cmp_terms(&self, left: &(Option<Vec<u8>>, DocAddress), right: &(Option<Vec<u8>>, DocAddress)) -> Ordering {
+        let terms_ordering = match (&left.0, &right.0) {
+            (&Some(ref left_term), &Some(ref right_term)) => {
+                match self.order {
+                    Order::Asc => left_term.cmp(right_term),
+                    Order::Desc => right_term.cmp(left_term),
+                }
+            }
+            (&Some(_), &None) => Ordering::Less,
+            (&None, &Some(_)) => Ordering::Greater,
+            (&None, &None) => Ordering::Equal,
+        };
+        match terms_ordering {
+            Ordering::Equal => left.1.cmp(&right.1),
+            terms_ordering => terms_ordering,
+        }
+    }
+
+    // resolves the terms of the K best documents of each segment,
+    // and merges them.
+    fn merged_top_docs(&self, searcher: &Searcher) -> Result<Vec<(Option<Vec<u8>>, DocAddress)>> {
+        let segment_readers = searcher.segment_readers();
+        let mut top_docs = Vec::new();
+        for segment_top_docs in &self.segment_top_docs {
+            let segment_reader = match segment_readers.get(segment_top_docs.segment_local_id as usize) {
+                Some(segment_reader) if segment_reader.segment_id() == segment_top_docs.segment_id => segment_reader,
+                _ => {
+                    return Err(Error::InvalidArgument(format!("The segment {:?} is not part of the searcher.", segment_top_docs.segment_id)));
+                }
+            };
+            let str_reader = try!(segment_reader.str_fast_field(self.field));
+            for segment_ord_doc in segment_top_docs.heap.iter() {
+                let term = match self.ord(segment_ord_doc.key) {
+                    Some(ord) => {
+                        let mut term_bytes = Vec::new();
+                        if !str_reader.term(ord, &mut term_bytes) {
+                            return Err(Error::InvalidArgument(format!("The segment {:?} does not have a term with the ordinal {}.", segment_top_docs.segment_id, ord)));
+                        }
+                        Some(term_bytes)
+                    }
+                    None => None,
+                };
+                top_docs.push((term, segment_ord_doc.doc_address));
+            }
+        }
+        top_docs.sort_by(|left, right| self.cmp_terms(left, right));
+        top_docs.truncate(self.limit);
+        Ok(top_docs)
+    }
+
+    /// Returns the K best documents along with the value of
+    /// their field, sorted in the order of the collector.
+    ///
+    /// The value is `None` for the documents without any value.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `searcher` does
+    /// not have the segments the collector was run on.
+    pub fn top_docs(&self, searcher: &Searcher) -> Result<Vec<(Option<String>, DocAddress)>> {
+        let top_docs = try!(self.merged_top_docs(searcher));
+        Ok(top_docs
+            .into_iter()
+            .map(|(term, doc_address)| {
+                let term = term.map(|term_bytes| String::from_utf8_lossy(&term_bytes).into_owned());
+                (term, doc_address)
+            })
+            .collect())
+    }
+
+    /// Returns K best documents sorted in the order of the collector.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `searcher` does
+    /// not have the segments the collector was run on.
+    pub fn docs(&self, searcher: &Searcher) -> Result<Vec<DocAddress>> {
+        let top_docs = try!(self.merged_top_docs(searcher));
+        Ok(top_docs
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect())
+    }
+}
+
+impl Collector for TopStrFieldCollector {
+
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        let str_reader = try!(reader.str_fast_field(self.field));
+        self.segment_top_docs.push(SegmentTopDocs {
+            segment_local_id: segment_local_id,
+            segment_id: reader.segment_id(),
+            heap: BinaryHeap::with_capacity(self.limit),
+        });
+        self.str_reader = Some(str_reader);
+        Ok(())
+    }
+
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let ord = self.str_reader
+            .as_ref()
+            .expect("collect called before set_segment")
+            .ord(scored_doc.doc());
+        let key = self.key(ord);
+        let limit = self.limit;
+        let segment_top_docs = self.segment_top_docs
+            .last_mut()
+            .expect("collect called before set_segment");
+        let segment_ord_doc = SegmentOrdDoc {
+            key: key,
+            doc_address: DocAddress(segment_top_docs.segment_local_id, scored_doc.doc()),
+        };
+        if segment_top_docs.heap.len() >= limit {
+            // It's ok to unwrap as long as a limit of 0 is forbidden.
+            let mut mut_head = segment_top_docs.heap.peek_mut().expect("Top str field collector with size 0 is forbidden");
+            if segment_ord_doc < *mut_head {
+                *mut_head = segment_ord_doc;
+            }
+        }
+        else {
+            segment_top_docs.heap.push(segment_ord_doc);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, STRING, TEXT};
+    use query::{AllQuery, Query, MultiTermQuery};
+
+    #[test]
+    fn test_top_str_field_collector_limit() {
+        assert!(TopStrFieldCollector::new(Field(0), 0, Order::Asc).is_err());
+    }
+
+    #[test]
+    fn test_top_str_field_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let country_field = schema_builder.add_text_field("country", STRING.set_fast());
+        // the ordinals of `france` differ in the two segments.
+        let segments = vec!(
+            vec!(("a", Some("france")), ("a b", Some("spain")), ("b", None), ("a", Some("chile"))),
+            vec!(("a", Some("italy")), ("a", None), ("b", Some("france")), ("a b", Some("austria"))),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, country)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            if let Some(country) = country {
+                doc.add_text(country_field, country);
+            }
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let country = |doc_address: &DocAddress| {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+            let str_reader = segment_reader.str_fast_field(country_field).unwrap();
+            str_reader.ord(doc_address.doc()).map(|ord| {
+                let mut term_bytes = Vec::new();
+                assert!(str_reader.term(ord, &mut term_bytes));
+                String::from_utf8(term_bytes).unwrap()
+            })
+        };
+        {
+            let segment_reader = searcher
+                .segment_readers()
+                .iter()
+                .find(|segment_reader| segment_reader.doc_freq(&Term::from_field_text(country_field, "chile")) > 0)
+                .unwrap();
+            let str_reader = segment_reader.str_fast_field(country_field).unwrap();
+            assert_eq!(str_reader.num_terms(), 3);
+            assert_eq!(str_reader.ord(0), Some(1));
+            assert_eq!(str_reader.ord(1), Some(2));
+            assert_eq!(str_reader.ord(2), None);
+            assert_eq!(str_reader.ord(3), Some(0));
+            let mut term_bytes = Vec::new();
+            assert!(str_reader.term(2, &mut term_bytes));
+            assert_eq!(term_bytes, b"spain".to_vec());
+            assert!(str_reader.term(0, &mut term_bytes));
+            assert_eq!(term_bytes, b"chile".to_vec());
+            // the ordinals past the last term of the field.
+            assert!(!str_reader.term(3, &mut term_bytes));
+            assert_eq!(segment_reader.ords_to_terms(country_field, &[2, 0]).unwrap(), vec!(b"spain".to_vec(), b"chile".to_vec()));
+        }
+        let countries = |top_docs: &[(Option<String>, DocAddress)]| -> Vec<Option<String>> {
+            top_docs.iter().map(|&(ref country, _)| country.clone()).collect()
+        };
+        let some = |country: &str| Some(String::from(country));
+        {
+            let mut top_collector = TopStrFieldCollector::new(country_field, 4, Order::Asc).unwrap();
+            AllQuery.search(&searcher, &mut top_collector).unwrap();
+            let top_docs = top_collector.top_docs(&searcher).unwrap();
+            assert_eq!(countries(&top_docs), vec!(some("austria"), some("chile"), some("france"), some("france")));
+            for &(ref val, ref doc_address) in &top_docs {
+                assert_eq!(&country(doc_address), val);
+            }
+            // the ties are broken by `DocAddress`.
+            assert!(top_docs[2].1 < top_docs[3].1);
+            assert_eq!(top_collector.docs(&searcher).unwrap(), top_docs.iter().map(|&(_, doc_address)| doc_address).collect::<Vec<DocAddress>>());
+        }
+        {
+            // the documents without any value come last.
+            let mut top_collector = TopStrFieldCollector::new(country_field, 10, Order::Desc).unwrap();
+            AllQuery.search(&searcher, &mut top_collector).unwrap();
+            assert_eq!(countries(&top_collector.top_docs(&searcher).unwrap()), vec!(some("spain"), some("italy"), some("france"), some("france"), some("chile"), some("austria"), None, None));
+        }
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut top_collector = TopStrFieldCollector::new(country_field, 3, Order::Desc).unwrap();
+        query.search(&searcher, &mut top_collector).unwrap();
+        assert_eq!(countries(&top_collector.top_docs(&searcher).unwrap()), vec!(some("spain"), some("italy"), some("france")));
+        // the ordinals got remapped to the merged terms.
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        let merged_searcher = index.searcher();
+        assert_eq!(merged_searcher.segment_readers().len(), 1);
+        // the terms are resolved with the searcher the collector was run on.
+        assert!(top_collector.top_docs(&merged_searcher).is_err());
+        {
+            let segment_reader = merged_searcher.segment_reader(0);
+            let str_reader = segment_reader.str_fast_field(country_field).unwrap();
+            assert_eq!(str_reader.num_terms(), 5);
+            let mut docs_ords: Vec<Option<u32>> = (0..segment_reader.max_doc()).map(|doc| str_reader.ord(doc)).collect();
+            docs_ords.sort();
+            assert_eq!(docs_ords, vec!(None, None, Some(0), Some(1), Some(2), Some(2), Some(3), Some(4)));
+            let terms: Vec<&[u8]> = vec!(b"austria", b"chile", b"france", b"italy", b"spain");
+            assert_eq!(segment_reader.ords_to_terms(country_field, &[0, 1, 2, 3, 4]).unwrap(), terms.iter().map(|term| term.to_vec()).collect::<Vec<Vec<u8>>>());
+        }
+        let mut top_collector = TopStrFieldCollector::new(country_field, 10, Order::Asc).unwrap();
+        query.search(&merged_searcher, &mut top_collector).unwrap();
+        assert_eq!(countries(&top_collector.top_docs(&merged_searcher).unwrap()), vec!(some("austria"), some("chile"), some("france"), some("italy"), some("spain"), None));
+    }
+
+    #[test]
+    fn test_top_str_field_collector_not_fast() {
+        let mut schema_builder = SchemaBuilder::default();
+        let country_field = schema_builder.add_text_field("country", STRING);
+        let index = create_index(schema_builder.build().unwrap(), &[vec!("france")], |country| {
+            let mut doc = Document::default();
+            doc.add_text(country_field, country);
+            doc
+        });
+        let searcher = index.searcher();
+        let mut top_collector = TopStrFieldCollector::new(country_field, 10, Order::Asc).unwrap();
+        assert!(AllQuery.search(&searcher, &mut top_collector).is_err());
+    }
+}
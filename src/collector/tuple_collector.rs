@@ -0,0 +1,115 @@
+use Result;
+use super::Collector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use Score;
+use std::f32;
+
+#[inline]
+fn min_of(left: Option<Score>, right: Option<Score>) -> Option<Score> {
+    match (left, right) {
+        (Some(left_min_score), Some(right_min_score)) => Some(left_min_score.min(right_min_score)),
+        _ => None,
+    }
+}
+
+// Tuples of collectors are collectors, pushing the documents
+// to each of their collectors.
+//
+// Contrary to `MultiCollector`, the type of the collectors is
+// kept, so that their results are accessible by field after the
+// search, as in `collectors.0.docs()`.
+//
+// The min score is the smallest min score of the collectors,
+// and the search terminates once all of them should terminate.
+macro_rules! tuple_collector_impl {
+    ($($collector:ident: $index:tt),+) => {
+        impl<$($collector: Collector),+> Collector for ($($collector,)+) {
+            fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
+                $(try!(self.$index.set_segment(segment_local_id, segment));)+
+                Ok(())
+            }
+
+            fn collect(&mut self, scored_doc: ScoredDoc) {
+                $(self.$index.collect(scored_doc);)+
+            }
+
+            fn min_score(&self,) -> Option<Score> {
+                let min_score = Some(f32::INFINITY);
+                $(let min_score = min_of(min_score, self.$index.min_score());)+
+                min_score
+            }
+
+            fn should_terminate(&self,) -> bool {
+                $(self.$index.should_terminate())&&+
+            }
+
+            // all of the collectors are finished, even if one of them fails.
+            fn finish(&mut self,) -> Result<()> {
+                let result = Ok(());
+                $(let result = result.and(self.$index.finish());)+
+                result
+            }
+        }
+    }
+}
+
+tuple_collector_impl!(A: 0, B: 1);
+tuple_collector_impl!(A: 0, B: 1, C: 2);
+tuple_collector_impl!(A: 0, B: 1, C: 2, D: 3);
+
+
+#[cfg(test)]
+mod tests {
+
+    use collector::{Collector, CountCollector, TopCollector, FacetCollector};
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, FAST, TEXT};
+    use query::{Query, MultiTermQuery};
+    use ScoredDoc;
+
+    #[test]
+    fn test_tuple_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let category_field = schema_builder.add_u32_field("category", FAST);
+        let segments = vec!(
+            vec!(("a", 1), ("a b", 2), ("b", 1)),
+            vec!(("a", 2), ("a", 2)),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, category)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc.add_u32(category_field, category);
+            doc
+        });
+        let searcher = index.searcher();
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        let mut collectors = (TopCollector::with_limit(2), CountCollector::default(), FacetCollector::for_field(category_field));
+        query.search(&searcher, &mut collectors).unwrap();
+        let (top_collector, count_collector, facet_collector) = collectors;
+        assert_eq!(top_collector.docs().len(), 2);
+        assert_eq!(count_collector.count(), 4);
+        assert_eq!(facet_collector.top_n(2), vec!((2, 3), (1, 1)));
+    }
+
+    #[test]
+    fn test_tuple_collector_of_references() {
+        let mut top_collector = TopCollector::with_limit(1);
+        let mut count_collector = CountCollector::with_limit(2);
+        {
+            let mut collectors = (&mut top_collector, &mut count_collector);
+            collectors.collect(ScoredDoc(0.2, 1));
+            assert_eq!(collectors.min_score(), None);
+            assert!(!collectors.should_terminate());
+            collectors.collect(ScoredDoc(0.5, 2));
+            collectors.collect(ScoredDoc(0.1, 3));
+            assert_eq!(collectors.1.count(), 2);
+        }
+        assert_eq!(top_collector.score_docs()[0].0, 0.5);
+        assert_eq!(count_collector.count(), 2);
+        // the top collector alone has a min score.
+        assert_eq!((&mut top_collector, ::collector::chain()).min_score(), Some(0.5));
+    }
+}
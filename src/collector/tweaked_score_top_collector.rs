@@ -0,0 +1,149 @@
+use Result;
+use super::Collector;
+use super::TopCollector;
+use ScoredDoc;
+use SegmentReader;
+use SegmentLocalId;
+use DocAddress;
+use DocId;
+use Score;
+use schema::Field;
+use fastfield::U32FastFieldReader;
+
+
+/// The Tweaked Score Top Collector keeps track of the K documents
+/// with the best scores, as computed by a closure from the score
+/// of the query and the value of a u32 fast field, like
+/// `score * log(1 + popularity)`.
+///
+/// The closure is given the doc id of the document within
+/// its segment, its score, and the fast field reader of the
+/// segment, which is resolved once per segment.
+///
+/// The documents with the same tweaked score are sorted
+/// by their `DocAddress`.
+pub struct TweakedScoreTopCollector<F: Fn(DocId, Score, &U32FastFieldReader) -> Score> {
+    field: Field,
+    tweak: F,
+    top_collector: TopCollector,
+    ff_reader: Option<U32FastFieldReader>,
+}
+
+impl<F: Fn(DocId, Score, &U32FastFieldReader) -> Score> TweakedScoreTopCollector<F> {
+
+    /// Creates a tweaked score top collector, with a number of documents
+    /// equal to "limit", scored by `tweak` given the u32 fast field `field`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if limit is 0.
+    pub fn new(limit: usize, field: Field, tweak: F) -> Result<TweakedScoreTopCollector<F>> {
+        let top_collector = try!(TopCollector::new(limit));
+        Ok(TweakedScoreTopCollector {
+            field: field,
+            tweak: tweak,
+            top_collector: top_collector,
+            ff_reader: None,
+        })
+    }
+
+    /// Returns the K best documents, along with their
+    /// tweaked score, sorted by decreasing tweaked score.
+    pub fn top_docs(&self) -> Vec<(Score, DocAddress)> {
+        self.top_collector.top_docs()
+    }
+
+    /// Returns the K best documents, sorted by decreasing tweaked score.
+    pub fn docs(&self) -> Vec<DocAddress> {
+        self.top_collector.docs()
+    }
+}
+
+impl<F: Fn(DocId, Score, &U32FastFieldReader) -> Score> Collector for TweakedScoreTopCollector<F> {
+
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(try!(reader.get_fast_field_reader(self.field)));
+        self.top_collector.set_segment(segment_id, reader)
+    }
+
+    // the min score of the top collector is a tweaked score,
+    // it cannot bound the scores of the query.
+    fn collect(&mut self, scored_doc: ScoredDoc) {
+        let score = {
+            let ff_reader = self.ff_reader
+                .as_ref()
+                .expect("collect called before set_segment");
+            (self.tweak)(scored_doc.doc(), scored_doc.score(), ff_reader)
+        };
+        self.top_collector.collect(ScoredDoc(score, scored_doc.doc()));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use collector::tests::create_index;
+    use schema::{SchemaBuilder, Document, Term, FAST, TEXT};
+    use query::{Query, MultiTermQuery};
+    use collector::TopCollector;
+
+    #[test]
+    fn test_tweaked_score_top_collector_limit() {
+        assert!(TweakedScoreTopCollector::new(0, Field(0), |_, score, _| score).is_err());
+    }
+
+    #[test]
+    fn test_tweaked_score_top_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let popularity_field = schema_builder.add_u32_field("popularity", FAST);
+        // "a" scores more than "a b c", which scores more than "a b c d e f".
+        let segments = vec!(
+            vec!(("a", 0), ("a b c", 100), ("b", 1_000)),
+            vec!(("a b c d e f", 1_000_000), ("a", 1)),
+        );
+        let index = create_index(schema_builder.build().unwrap(), &segments, |&(text, popularity)| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text);
+            doc.add_u32(popularity_field, popularity);
+            doc
+        });
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        {
+            let mut top_collector = TopCollector::with_limit(4);
+            query.search(&searcher, &mut top_collector).unwrap();
+            let mut best_docs = top_collector.docs()[..2].to_vec();
+            best_docs.sort();
+            assert_eq!(best_docs, vec!(DocAddress(0, 0), DocAddress(1, 1)));
+            assert_eq!(&top_collector.docs()[2..], &[DocAddress(0, 1), DocAddress(1, 0)]);
+        }
+        {
+            let popularity_score = |doc: DocId, score: Score, popularity_reader: &U32FastFieldReader| {
+                score * (1f32 + popularity_reader.get(doc) as f32).ln()
+            };
+            let mut tweaked_collector = TweakedScoreTopCollector::new(4, popularity_field, popularity_score).unwrap();
+            query.search(&searcher, &mut tweaked_collector).unwrap();
+            // the popularity of 0 cancels the score of the first document.
+            assert_eq!(tweaked_collector.docs(), vec!(DocAddress(1, 0), DocAddress(0, 1), DocAddress(1, 1), DocAddress(0, 0)));
+            let top_docs = tweaked_collector.top_docs();
+            assert_eq!(top_docs[3].0, 0f32);
+            for &(tweaked_score, ref doc_address) in &top_docs {
+                let score = query.explain(&searcher, doc_address).unwrap().val();
+                let popularity = searcher
+                    .segment_reader(doc_address.segment_ord() as usize)
+                    .get_fast_field_reader(popularity_field)
+                    .unwrap()
+                    .get(doc_address.doc());
+                assert!((tweaked_score - score * (1f32 + popularity as f32).ln()).abs() < 0.0001f32);
+            }
+        }
+        {
+            let mut tweaked_collector = TweakedScoreTopCollector::new(1, popularity_field, |_, score, _| -score).unwrap();
+            query.search(&searcher, &mut tweaked_collector).unwrap();
+            assert_eq!(tweaked_collector.docs(), vec!(DocAddress(1, 0)));
+        }
+    }
+}
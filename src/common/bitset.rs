@@ -0,0 +1,146 @@
+use std::io;
+use std::io::{Read, Write};
+use common::BinarySerializable;
+use DocId;
+
+
+/// Set of doc ids, represented with one bit per document.
+///
+/// The bitset grows as doc ids are inserted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    max_doc: DocId,
+}
+
+impl BitSet {
+
+    /// Creates an empty bitset for a segment of `max_doc` documents.
+    pub fn with_max_doc(max_doc: DocId) -> BitSet {
+        BitSet {
+            words: vec!(0u64; (max_doc as usize + 63) / 64),
+            max_doc: max_doc,
+        }
+    }
+
+    /// Adds a doc id to the set.
+    ///
+    /// If needed, the bitset grows so that `max_doc` becomes `doc + 1`.
+    pub fn insert(&mut self, doc: DocId) {
+        let word = doc as usize / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0u64);
+        }
+        if doc >= self.max_doc {
+            self.max_doc = doc + 1;
+        }
+        self.words[word] |= 1u64 << (doc % 64);
+    }
+
+    /// Returns true iff the set contains the doc id.
+    pub fn contains(&self, doc: DocId) -> bool {
+        self.words
+            .get(doc as usize / 64)
+            .map(|word| word & (1u64 << (doc % 64)) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Extends the bitset so that it spans `max_doc` documents.
+    ///
+    /// The documents added are not in the set.
+    pub fn set_max_doc(&mut self, max_doc: DocId) {
+        if max_doc > self.max_doc {
+            self.max_doc = max_doc;
+            self.words.resize((max_doc as usize + 63) / 64, 0u64);
+        }
+    }
+
+    /// Returns the number of documents spanned by the bitset.
+    pub fn max_doc(&self,) -> DocId {
+        self.max_doc
+    }
+
+    /// Returns the number of doc ids in the set.
+    pub fn len(&self,) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .fold(0, |acc, count| acc + count)
+    }
+
+    /// Returns true iff the set does not contain any doc id.
+    pub fn is_empty(&self,) -> bool {
+        self.words.iter().all(|&word| word == 0u64)
+    }
+
+    /// Returns the smallest doc id of the set greater
+    /// or equal to `doc`, if any.
+    pub fn next_from(&self, doc: DocId) -> Option<DocId> {
+        let mut word_ord = doc as usize / 64;
+        if word_ord >= self.words.len() {
+            return None;
+        }
+        // ignore the bits of the documents before `doc`.
+        let mut word = self.words[word_ord] & (!0u64 << (doc % 64));
+        loop {
+            if word != 0u64 {
+                return Some((word_ord * 64) as DocId + word.trailing_zeros());
+            }
+            word_ord += 1;
+            if word_ord == self.words.len() {
+                return None;
+            }
+            word = self.words[word_ord];
+        }
+    }
+}
+
+impl BinarySerializable for BitSet {
+    fn serialize(&self, writer: &mut Write) -> io::Result<usize> {
+        let mut written_len = try!(self.max_doc.serialize(writer));
+        written_len += try!(self.words.serialize(writer));
+        Ok(written_len)
+    }
+
+    fn deserialize(reader: &mut Read) -> io::Result<BitSet> {
+        let max_doc = try!(DocId::deserialize(reader));
+        let words = try!(Vec::deserialize(reader));
+        Ok(BitSet {
+            words: words,
+            max_doc: max_doc,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use common::BinarySerializable;
+
+    #[test]
+    fn test_bitset() {
+        let mut bitset = BitSet::with_max_doc(10);
+        assert!(bitset.is_empty());
+        for &doc in &[1u32, 3u32, 64u32, 200u32] {
+            bitset.insert(doc);
+        }
+        assert_eq!(bitset.max_doc(), 201);
+        assert_eq!(bitset.len(), 4);
+        assert!(bitset.contains(3));
+        assert!(!bitset.contains(2));
+        assert!(!bitset.contains(1_000));
+        assert_eq!(bitset.next_from(0), Some(1));
+        assert_eq!(bitset.next_from(2), Some(3));
+        assert_eq!(bitset.next_from(4), Some(64));
+        assert_eq!(bitset.next_from(65), Some(200));
+        assert_eq!(bitset.next_from(201), None);
+        bitset.set_max_doc(300);
+        assert_eq!(bitset.max_doc(), 300);
+        let mut buffer: Vec<u8> = Vec::new();
+        bitset.serialize(&mut buffer).unwrap();
+        let deserialized = BitSet::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(deserialized, bitset);
+    }
+}
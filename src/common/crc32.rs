@@ -0,0 +1,144 @@
+// reversed polynomial of the CRC-32 (IEEE 802.3), as used by zlib.
+const CRC32_POLYNOMIAL: u32 = 0xEDB8_8320;
+
+lazy_static! {
+    static ref CRC32_TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut crc = i as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 { CRC32_POLYNOMIAL ^ (crc >> 1) } else { crc >> 1 };
+            }
+            *entry = crc;
+        }
+        table
+    };
+}
+
+
+/// Computes the CRC-32 checksum of a stream of bytes,
+/// fed in one or more calls to `update`.
+pub struct Crc32Hasher {
+    crc: u32,
+}
+
+impl Crc32Hasher {
+
+    /// Creates a hasher, for an empty stream of bytes.
+    pub fn new() -> Crc32Hasher {
+        Crc32Hasher {
+            crc: !0u32,
+        }
+    }
+
+    /// Appends bytes to the stream.
+    pub fn update(&mut self, data: &[u8]) {
+        let mut crc = self.crc;
+        for &byte in data {
+            crc = CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.crc = crc;
+    }
+
+    /// Returns the checksum of the bytes appended so far.
+    pub fn finish(&self,) -> u32 {
+        !self.crc
+    }
+}
+
+/// Returns the CRC-32 checksum of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = Crc32Hasher::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+fn gf2_matrix_times(matrix: &[u32; 32], mut vec: u32) -> u32 {
+    let mut sum = 0u32;
+    let mut i = 0;
+    while vec != 0 {
+        if vec & 1 != 0 {
+            sum ^= matrix[i];
+        }
+        vec >>= 1;
+        i += 1;
+    }
+    sum
+}
+
+fn gf2_matrix_square(square: &mut [u32; 32], matrix: &[u32; 32]) {
+    for n in 0..32 {
+        square[n] = gf2_matrix_times(matrix, matrix[n]);
+    }
+}
+
+/// Returns the CRC-32 checksum of the concatenation of two
+/// streams of bytes, given their checksums and the length
+/// of the second one, as `crc32_combine` in zlib.
+///
+/// The second stream does not need to be read again,
+/// as the computation only takes `O(log(len2))` steps.
+pub fn crc32_combine(mut crc1: u32, crc2: u32, mut len2: u64) -> u32 {
+    if len2 == 0 {
+        return crc1;
+    }
+    // operator for one zero bit, in `odd`,
+    // then for two and four zero bits.
+    let mut even = [0u32; 32];
+    let mut odd = [0u32; 32];
+    odd[0] = CRC32_POLYNOMIAL;
+    let mut row = 1u32;
+    for entry in odd.iter_mut().skip(1) {
+        *entry = row;
+        row <<= 1;
+    }
+    gf2_matrix_square(&mut even, &odd);
+    gf2_matrix_square(&mut odd, &even);
+    // applies `len2` zero bytes to `crc1`, squaring the
+    // operator for each bit of `len2`.
+    loop {
+        gf2_matrix_square(&mut even, &odd);
+        if len2 & 1 != 0 {
+            crc1 = gf2_matrix_times(&even, crc1);
+        }
+        len2 >>= 1;
+        if len2 == 0 {
+            break;
+        }
+        gf2_matrix_square(&mut odd, &even);
+        if len2 & 1 != 0 {
+            crc1 = gf2_matrix_times(&odd, crc1);
+        }
+        len2 >>= 1;
+        if len2 == 0 {
+            break;
+        }
+    }
+    crc1 ^ crc2
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        let mut hasher = Crc32Hasher::new();
+        hasher.update(b"1234");
+        hasher.update(b"56789");
+        assert_eq!(hasher.finish(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_crc32_combine() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7_919 % 251) as u8).collect();
+        for &split in &[0usize, 1, 4, 8, 100, 9_999, 10_000] {
+            let (left, right) = data.split_at(split);
+            assert_eq!(crc32_combine(crc32(left), crc32(right), right.len() as u64), crc32(&data));
+        }
+    }
+}
@@ -1,12 +1,16 @@
 mod serialize;
 mod timer;
 mod vint;
+mod bitset;
+mod crc32;
 
 pub use self::serialize::BinarySerializable;
 pub use self::timer::Timing;
 pub use self::timer::TimerTree;
 pub use self::timer::OpenTimer;
 pub use self::vint::VInt;
+pub use self::bitset::BitSet;
+pub use self::crc32::{Crc32Hasher, crc32, crc32_combine};
 use std::io;
 
 
@@ -15,6 +19,53 @@ pub fn make_io_err(msg: String) -> io::Error {
 }
 
 
+/// Maps an `i64` to a `u64` while preserving order.
+///
+/// The sign bit is flipped, so that negative values
+/// come before positive values, both in numerical order
+/// and in big-endian lexicographical order.
+pub fn i64_to_u64(val: i64) -> u64 {
+    (val as u64) ^ HIGHEST_BIT
+}
+
+/// Reverse the mapping given by [`i64_to_u64`](./fn.i64_to_u64.html).
+pub fn u64_to_i64(val: u64) -> i64 {
+    (val ^ HIGHEST_BIT) as i64
+}
+
+/// Maps an `f64` to a `u64` while preserving order.
+///
+/// Positive floats get their sign bit flipped, while
+/// negative floats get all of their bits flipped.
+/// The resulting `u64` sort in the same order as the
+/// original floats. (`NaN` values excepted)
+///
+/// `-0.0` is normalized to `0.0` beforehand, as the two
+/// values are equal and must be mapped to the same `u64`.
+pub fn f64_to_u64(val: f64) -> u64 {
+    let bits = if val == 0f64 { 0u64 } else { val.to_bits() };
+    if bits & HIGHEST_BIT == 0 {
+        bits ^ HIGHEST_BIT
+    }
+    else {
+        !bits
+    }
+}
+
+/// Reverse the mapping given by [`f64_to_u64`](./fn.f64_to_u64.html).
+pub fn u64_to_f64(val: u64) -> f64 {
+    let bits = if val & HIGHEST_BIT != 0 {
+        val ^ HIGHEST_BIT
+    }
+    else {
+        !val
+    };
+    f64::from_bits(bits)
+}
+
+const HIGHEST_BIT: u64 = 1 << 63;
+
+
 /// Has length trait
 pub trait HasLen {
     /// Return length
@@ -25,3 +76,41 @@ pub trait HasLen {
         self.len() == 0
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{i64_to_u64, u64_to_i64, f64_to_u64, u64_to_f64};
+
+    fn test_i64_converter_helper(val: i64) {
+        assert_eq!(u64_to_i64(i64_to_u64(val)), val);
+    }
+
+    #[test]
+    fn test_i64_converter() {
+        assert_eq!(i64_to_u64(i64::min_value()), u64::min_value());
+        assert_eq!(i64_to_u64(i64::max_value()), u64::max_value());
+        test_i64_converter_helper(0i64);
+        test_i64_converter_helper(i64::min_value());
+        test_i64_converter_helper(i64::max_value());
+        for i in -1000i64..1000i64 {
+            test_i64_converter_helper(i);
+            assert!(i64_to_u64(i) < i64_to_u64(i + 1));
+        }
+    }
+
+    #[test]
+    fn test_f64_converter() {
+        let vals = [::std::f64::NEG_INFINITY, -1e300f64, -3.5f64, 0.0f64, 1e-300f64, 2.0f64, 1e300f64, ::std::f64::INFINITY];
+        for &val in &vals {
+            assert_eq!(u64_to_f64(f64_to_u64(val)).to_bits(), val.to_bits());
+        }
+        for (left, right) in vals.iter().zip(vals.iter().skip(1)) {
+            assert!(f64_to_u64(*left) < f64_to_u64(*right));
+        }
+        assert_eq!(f64_to_u64(0.0f64), i64_to_u64(0i64));
+        assert_eq!(f64_to_u64(-0.0f64), f64_to_u64(0.0f64));
+        assert_eq!(u64_to_f64(f64_to_u64(-0.0f64)).to_bits(), 0.0f64.to_bits());
+    }
+}
@@ -83,6 +83,18 @@ impl BinarySerializable for u64 {
     }
 }
 
+impl BinarySerializable for i64 {
+    fn serialize(&self, writer: &mut Write) -> io::Result<usize> {
+        writer.write_i64::<NativeEndian>(*self)
+              .map(|_| 8)
+              .map_err(convert_byte_order_error)
+    }
+    fn deserialize(reader: &mut Read) -> io::Result<i64> {
+        reader.read_i64::<NativeEndian>()
+              .map_err(convert_byte_order_error)
+    }
+}
+
 
 impl BinarySerializable for u8 {
     fn serialize(&self, writer: &mut Write) -> io::Result<usize> {
@@ -148,6 +160,14 @@ mod test {
         serialize_test(u32::max_value(), 4);
     }
 
+    #[test]
+    fn test_serialize_i64() {
+        serialize_test(3i64, 8);
+        serialize_test(-5i64, 8);
+        serialize_test(i64::min_value(), 8);
+        serialize_test(i64::max_value(), 8);
+    }
+
     #[test]
     fn test_serialize_string() {
         serialize_test(String::from(""), 1);
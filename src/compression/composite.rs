@@ -1,17 +1,24 @@
-use compression::SIMDBlockEncoder;
-use compression::SIMDBlockDecoder;
+use compression::{BlockEncoder, BlockCompressor, BlockDecompressor};
 use super::NUM_DOCS_PER_BLOCK;
 
 pub struct CompositeEncoder {
-    block_encoder: SIMDBlockEncoder,
+    block_encoder: BlockCompressor,
     output: Vec<u8>,
 }
 
 impl CompositeEncoder {
     
     pub fn new() -> CompositeEncoder {
+        CompositeEncoder::from_compressor(BlockCompressor::new())
+    }
+
+    pub fn with_encoder(block_encoder: &'static BlockEncoder) -> CompositeEncoder {
+        CompositeEncoder::from_compressor(BlockCompressor::with_encoder(block_encoder))
+    }
+
+    fn from_compressor(block_encoder: BlockCompressor) -> CompositeEncoder {
         CompositeEncoder {
-            block_encoder: SIMDBlockEncoder::new(),
+            block_encoder: block_encoder,
             output: Vec::with_capacity(500_000),
         }
     }
@@ -47,15 +54,23 @@ impl CompositeEncoder {
 
 
 pub struct CompositeDecoder {
-    block_decoder: SIMDBlockDecoder,
+    block_decoder: BlockDecompressor,
     vals: Vec<u32>,
 }
 
 
 impl CompositeDecoder {
     pub fn new() -> CompositeDecoder {
+        CompositeDecoder::from_decompressor(BlockDecompressor::new())
+    }
+
+    pub fn with_encoder(block_encoder: &'static BlockEncoder) -> CompositeDecoder {
+        CompositeDecoder::from_decompressor(BlockDecompressor::with_encoder_and_val(block_encoder, 0u32))
+    }
+
+    fn from_decompressor(block_decoder: BlockDecompressor) -> CompositeDecoder {
         CompositeDecoder {
-            block_decoder: SIMDBlockDecoder::new(),
+            block_decoder: block_decoder,
             vals: Vec::with_capacity(500_000),
         }    
     }
@@ -103,6 +118,7 @@ pub mod tests {
     
     use test::Bencher;
     use super::*;
+    use compression::ScalarBlockEncoder;
     use compression::tests::generate_array;
 
     #[test]
@@ -130,6 +146,25 @@ pub mod tests {
             assert_eq!(data[i], result[i]);
         } 
     }
+
+    #[test]
+    fn test_composite_scalar_same_bytes() {
+        // the same fixtures, through the scalar bitpacking.
+        let data = generate_array(10_000, 0.1);
+        let mut encoder = CompositeEncoder::new();
+        let mut scalar_encoder = CompositeEncoder::with_encoder(&ScalarBlockEncoder);
+        let mut scalar_decoder = CompositeDecoder::with_encoder(&ScalarBlockEncoder);
+        {
+            let compressed = encoder.compress_sorted(&data).to_vec();
+            assert_eq!(scalar_encoder.compress_sorted(&data), &compressed[..]);
+            assert_eq!(scalar_decoder.uncompress_sorted(&compressed, data.len()), &data[..]);
+        }
+        {
+            let compressed = encoder.compress_unsorted(&data).to_vec();
+            assert_eq!(scalar_encoder.compress_unsorted(&data), &compressed[..]);
+            assert_eq!(scalar_decoder.uncompress_unsorted(&compressed, data.len()), &data[..]);
+        }
+    }
     
     
     const BENCH_NUM_INTS: usize = 99_968;
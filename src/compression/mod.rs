@@ -1,7 +1,15 @@
 #![allow(dead_code)]
 
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
 mod simdcomp;
-pub use self::simdcomp::{SIMDBlockEncoder, SIMDBlockDecoder};
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+pub use self::simdcomp::SIMDBlockEncoder;
+
+mod scalar;
+pub use self::scalar::ScalarBlockEncoder;
+
+mod block;
+pub use self::block::{BlockCompressor, BlockDecompressor};
 
 mod composite;
 pub use self::composite::CompositeEncoder;
@@ -9,6 +17,66 @@ pub use self::composite::CompositeDecoder;
 
 pub const NUM_DOCS_PER_BLOCK: usize = 128; //< should be a power of 2 to let the compiler optimize.
 
+/// Bitpacking of the blocks of `NUM_DOCS_PER_BLOCK` values.
+///
+/// A compressed block starts with its number of bits per value `b`,
+/// followed by `b * 16` bytes of bitpacked values, laid out as
+/// by the SIMD bitpacking of `simdcomp`: the values are dispatched
+/// in 4 interleaved lanes of little endian `u32` words, the value `i`
+/// going to the lane `i % 4`.
+///
+/// All of the implementations write exactly the same bytes,
+/// so that an index written with one of them can be read with any other.
+pub trait BlockEncoder: Sync {
+
+    /// Compresses a block of sorted values into `output`, as
+    /// the deltas between consecutive values, `offset` being
+    /// the value preceding the block.
+    ///
+    /// Returns the number of bytes written.
+    fn compress_block_sorted(&self, vals: &[u32], output: &mut [u8], offset: u32) -> usize;
+
+    /// Uncompresses a block written by `compress_block_sorted` into `output`.
+    ///
+    /// Returns the number of bytes read.
+    fn uncompress_block_sorted(&self, compressed_data: &[u8], output: &mut [u32], offset: u32) -> usize;
+
+    /// Compresses a block of values into `output`.
+    ///
+    /// Returns the number of bytes written.
+    fn compress_block_unsorted(&self, vals: &[u32], output: &mut [u8]) -> usize;
+
+    /// Uncompresses a block written by `compress_block_unsorted` into `output`.
+    ///
+    /// Returns the number of bytes read.
+    fn uncompress_block_unsorted(&self, compressed_data: &[u8], output: &mut [u32]) -> usize;
+}
+
+/// Returns the fastest `BlockEncoder` available on this machine.
+///
+/// The SIMD implementation is only compiled with the `simd` feature,
+/// on x86 targets, and only used if the CPU supports SSSE3.
+/// Otherwise, the values are bitpacked by the portable `ScalarBlockEncoder`.
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+pub fn default_block_encoder() -> &'static BlockEncoder {
+    if is_x86_feature_detected!("ssse3") {
+        &SIMDBlockEncoder
+    }
+    else {
+        &ScalarBlockEncoder
+    }
+}
+
+/// Returns the fastest `BlockEncoder` available on this machine.
+///
+/// The SIMD implementation is only compiled with the `simd` feature,
+/// on x86 targets, and only used if the CPU supports SSSE3.
+/// Otherwise, the values are bitpacked by the portable `ScalarBlockEncoder`.
+#[cfg(not(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64"))))]
+pub fn default_block_encoder() -> &'static BlockEncoder {
+    &ScalarBlockEncoder
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -16,7 +84,7 @@ pub mod tests {
     use rand::SeedableRng;
     use rand::XorShiftRng;
 
-    fn generate_array_with_seed(n: usize, ratio: f32, seed_val: u32) -> Vec<u32> {
+    pub fn generate_array_with_seed(n: usize, ratio: f32, seed_val: u32) -> Vec<u32> {
         let seed: &[u32; 4] = &[1, 2, 3, seed_val];
         let mut rng: XorShiftRng = XorShiftRng::from_seed(*seed);
         (0..u32::max_value())
@@ -30,4 +98,3 @@ pub mod tests {
     }
 }
 
-
@@ -0,0 +1,138 @@
+use byteorder::{ByteOrder, LittleEndian};
+use super::{BlockEncoder, NUM_DOCS_PER_BLOCK};
+
+// the values are dispatched in 4 lanes,
+// as for the 128 bits registers of the SIMD implementation.
+const NUM_LANES: usize = 4;
+
+
+/// Portable `BlockEncoder`, bitpacking the values
+/// one at a time without any SIMD instruction.
+///
+/// It writes the same bytes as the `SIMDBlockEncoder`.
+pub struct ScalarBlockEncoder;
+
+fn compute_num_bits(val: u32) -> usize {
+    32 - val.leading_zeros() as usize
+}
+
+// writes the number of bits, followed by the packed values.
+fn pack(vals: &[u32], num_bits: usize, output: &mut [u8]) -> usize {
+    let mut words = [0u32; NUM_DOCS_PER_BLOCK];
+    for (i, &val) in vals[..NUM_DOCS_PER_BLOCK].iter().enumerate() {
+        let lane = i % NUM_LANES;
+        let bit_offset = (i / NUM_LANES) * num_bits;
+        let word = NUM_LANES * (bit_offset / 32) + lane;
+        let shift = bit_offset % 32;
+        words[word] |= val << shift;
+        if shift + num_bits > 32 {
+            words[word + NUM_LANES] |= val >> (32 - shift);
+        }
+    }
+    output[0] = num_bits as u8;
+    let num_words = num_bits * NUM_LANES;
+    for (word_id, &word) in words[..num_words].iter().enumerate() {
+        LittleEndian::write_u32(&mut output[1 + word_id * 4..], word);
+    }
+    1 + num_words * 4
+}
+
+// reads the values packed by `pack`.
+fn unpack(compressed_data: &[u8], output: &mut [u32]) -> usize {
+    let num_bits = compressed_data[0] as usize;
+    let mask: u32 = if num_bits == 32 { u32::max_value() } else { (1u32 << num_bits) - 1 };
+    let words_data = &compressed_data[1..];
+    let read_word = |word: usize| LittleEndian::read_u32(&words_data[word * 4..]);
+    for (i, val) in output[..NUM_DOCS_PER_BLOCK].iter_mut().enumerate() {
+        if num_bits == 0 {
+            *val = 0;
+            continue;
+        }
+        let lane = i % NUM_LANES;
+        let bit_offset = (i / NUM_LANES) * num_bits;
+        let word = NUM_LANES * (bit_offset / 32) + lane;
+        let shift = bit_offset % 32;
+        let mut unpacked = read_word(word) >> shift;
+        if shift + num_bits > 32 {
+            unpacked |= read_word(word + NUM_LANES) << (32 - shift);
+        }
+        *val = unpacked & mask;
+    }
+    1 + num_bits * NUM_LANES * 4
+}
+
+impl BlockEncoder for ScalarBlockEncoder {
+
+    fn compress_block_sorted(&self, vals: &[u32], output: &mut [u8], mut offset: u32) -> usize {
+        let mut deltas = [0u32; NUM_DOCS_PER_BLOCK];
+        let mut deltas_or = 0u32;
+        for (delta, &val) in deltas.iter_mut().zip(vals[..NUM_DOCS_PER_BLOCK].iter()) {
+            *delta = val.wrapping_sub(offset);
+            deltas_or |= *delta;
+            offset = val;
+        }
+        pack(&deltas, compute_num_bits(deltas_or), output)
+    }
+
+    fn uncompress_block_sorted(&self, compressed_data: &[u8], output: &mut [u32], mut offset: u32) -> usize {
+        let consumed_size = unpack(compressed_data, output);
+        for val in output[..NUM_DOCS_PER_BLOCK].iter_mut() {
+            offset = offset.wrapping_add(*val);
+            *val = offset;
+        }
+        consumed_size
+    }
+
+    fn compress_block_unsorted(&self, vals: &[u32], output: &mut [u8]) -> usize {
+        let vals_or = vals[..NUM_DOCS_PER_BLOCK].iter().fold(0u32, |vals_or, &val| vals_or | val);
+        pack(vals, compute_num_bits(vals_or), output)
+    }
+
+    fn uncompress_block_unsorted(&self, compressed_data: &[u8], output: &mut [u32]) -> usize {
+        unpack(compressed_data, output)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use compression::{BlockEncoder, NUM_DOCS_PER_BLOCK};
+    use compression::tests::generate_array;
+
+    #[test]
+    fn test_scalar_block_layout() {
+        // with 1 bit per value, the value `i` is the bit `i / 4`
+        // of the word of its lane `i % 4`.
+        let vals: Vec<u32> = (0..NUM_DOCS_PER_BLOCK).map(|i| if i == 5 || i == 127 { 1 } else { 0 }).collect();
+        let mut output = [0u8; 1 + 32 * 16];
+        assert_eq!(ScalarBlockEncoder.compress_block_unsorted(&vals, &mut output), 17);
+        let mut expected = [0u8; 17];
+        expected[0] = 1;
+        // value 5, bit 1 of the lane 1.
+        expected[1 + 4] = 2;
+        // value 127, bit 31 of the lane 3.
+        expected[1 + 12 + 3] = 128;
+        assert_eq!(&output[..17], &expected[..]);
+    }
+
+    #[test]
+    fn test_scalar_block_roundtrip() {
+        let mut output = [0u8; 1 + 32 * 16];
+        let mut vals_output = [0u32; NUM_DOCS_PER_BLOCK];
+        {
+            let vals = generate_array(NUM_DOCS_PER_BLOCK, 0.1);
+            let compressed_len = ScalarBlockEncoder.compress_block_sorted(&vals, &mut output, 3);
+            assert_eq!(ScalarBlockEncoder.uncompress_block_sorted(&output, &mut vals_output, 3), compressed_len);
+            assert_eq!(&vals_output[..], &vals[..]);
+        }
+        for &max_val in &[0u32, 1, 17, u32::max_value()] {
+            let vals: Vec<u32> = (0..NUM_DOCS_PER_BLOCK as u32).map(|i| max_val - i % 2 * (max_val / 2)).collect();
+            let compressed_len = ScalarBlockEncoder.compress_block_unsorted(&vals, &mut output);
+            assert_eq!(compressed_len, 1 + (32 - max_val.leading_zeros() as usize) * 16);
+            assert_eq!(ScalarBlockEncoder.uncompress_block_unsorted(&output, &mut vals_output), compressed_len);
+            assert_eq!(&vals_output[..], &vals[..]);
+        }
+    }
+}
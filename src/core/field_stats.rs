@@ -0,0 +1,47 @@
+use std::ops::AddAssign;
+
+
+/// Statistics about the indexed values of a field.
+///
+/// They are computed when a segment is serialized, and
+/// stored in the `.stats` file of the segment, so that
+/// accessing them does not require to scan the term dictionary.
+///
+/// For text fields, tokens are the tokens emitted by the tokenizer.
+/// For the other indexed fields, each indexed term counts as a token.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct FieldStats {
+    /// Number of documents with at least one token in the field.
+    pub num_docs_with_field: u32,
+    /// Overall number of tokens in the field.
+    pub total_num_tokens: u64,
+    /// Number of distinct terms of the field.
+    ///
+    /// When stats are aggregated over several segments, this
+    /// is the sum of the number of terms of each segment, and therefore
+    /// an upper bound of the number of distinct terms.
+    pub num_unique_terms: u32,
+}
+
+impl FieldStats {
+    /// Returns the average number of tokens of the documents
+    /// having the field, or `0` if no document has it.
+    ///
+    /// It is the average field norm used by `Bm25`.
+    pub fn average_num_tokens(&self,) -> f32 {
+        if self.num_docs_with_field == 0 {
+            0f32
+        }
+        else {
+            self.total_num_tokens as f32 / self.num_docs_with_field as f32
+        }
+    }
+}
+
+impl AddAssign for FieldStats {
+    fn add_assign(&mut self, other: FieldStats) {
+        self.num_docs_with_field += other.num_docs_with_field;
+        self.total_num_tokens += other.total_num_tokens;
+        self.num_unique_terms += other.num_unique_terms;
+    }
+}
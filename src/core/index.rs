@@ -1,8 +1,9 @@
 use Result;
 use Error;
 use schema::Schema;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::fmt;
+use std::collections::BTreeMap;
 use rustc_serialize::json;
 use core::SegmentId;
 use directory::{Directory, MmapDirectory, RAMDirectory};
@@ -12,13 +13,19 @@ use std::convert::From;
 use num_cpus;
 use super::segment::Segment;
 use core::SegmentReader;
+use super::segment_reader::validate_segment;
 use super::pool::Pool;
 use super::pool::LeasedItem;
 use std::path::Path;
 use indexer::SegmentManager;
+use indexer::save_metas;
 use core::IndexMeta;
 use core::META_FILEPATH;
 use super::segment::create_segment;
+use analyzer::{TokenizerManager, Tokenizer, Token};
+use schema::{Field, FieldType, TextOptions, TextIndexingOptions};
+use datastruct::TermDictionaryType;
+use query::BoxedSimilarityModel;
 
 const NUM_SEARCHERS: usize = 12;
 
@@ -49,6 +56,9 @@ pub struct Index {
     schema: Schema,
     searcher_pool: Arc<Pool<Searcher>>,
     docstamp: u64,
+    tokenizers: TokenizerManager,
+    term_dictionary_type: TermDictionaryType,
+    similarity_model: Arc<RwLock<Option<Arc<BoxedSimilarityModel>>>>,
 }
 
 impl Index {
@@ -86,6 +96,7 @@ impl Index {
     fn create_from_metas(directory: Box<Directory>, metas: IndexMeta) -> Result<Index> {
         let schema = metas.schema.clone();
         let docstamp = metas.docstamp;
+        let term_dictionary_type = metas.term_dictionary_type.unwrap_or(TermDictionaryType::Fst);
         let committed_segments = metas.committed_segments;
         // TODO log somethings is uncommitted is not empty.
         let index = Index {
@@ -94,6 +105,9 @@ impl Index {
             schema: schema,
             searcher_pool: Arc::new(Pool::new()),
             docstamp: docstamp,
+            tokenizers: TokenizerManager::default(),
+            term_dictionary_type: term_dictionary_type,
+            similarity_model: Arc::new(RwLock::new(None)),
         };
         try!(index.load_searchers());
         Ok(index)
@@ -111,6 +125,41 @@ impl Index {
         Index::create_from_metas(directory.box_clone(), metas)
     }
 
+    /// Opens an existing index, extending its schema with
+    /// the new fields of `schema`.
+    ///
+    /// The fields of the index schema must appear unchanged, and
+    /// in the same order, at the beginning of `schema`.
+    /// New fields may only be appended. They are registered in
+    /// the `meta.json` file of the index.
+    ///
+    /// Segments created before the extension do not contain any value
+    /// for the new fields: searching for one of their terms will not
+    /// match any of their documents, and their fast fields return
+    /// the default value.
+    ///
+    /// # Errors
+    /// If a field of the index schema was removed, moved, or
+    /// had its type or options changed, returns `Error::InvalidArgument`.
+    pub fn open_or_extend(directory_path: &Path, schema: Schema) -> Result<Index> {
+        let directory = try!(MmapDirectory::open(directory_path));
+        let mut metas = try!(load_metas(&directory));
+        try!(schema
+            .check_extends(&metas.schema)
+            .map_err(|e| Error::InvalidArgument(format!("The schema is not compatible with the schema of the index: {:?}", e))));
+        let is_extended = schema.fields().len() > metas.schema.fields().len();
+        metas.schema = schema;
+        let mut index = try!(Index::create_from_metas(directory.box_clone(), metas));
+        if is_extended {
+            let segment_manager = index.segment_manager.clone();
+            let schema = index.schema();
+            let docstamp = index.docstamp;
+            let term_dictionary_type = index.term_dictionary_type;
+            try!(save_metas(&*segment_manager, schema, docstamp, term_dictionary_type, index.directory_mut()));
+        }
+        Ok(index)
+    }
+
     /// Returns the index docstamp.
     ///
     /// The docstamp is the number of documents that have been added
@@ -151,6 +200,198 @@ impl Index {
         self.schema.clone()
     }
 
+    /// Returns the implementation of the term dictionary
+    /// of the segments written by this index.
+    pub fn term_dictionary_type(&self) -> TermDictionaryType {
+        self.term_dictionary_type
+    }
+
+    /// Sets the implementation of the term dictionary
+    /// of the segments written from now on.
+    ///
+    /// It is persisted in the `meta.json` file on the next commit.
+    /// The existing segments keep their term dictionary until they
+    /// get merged. It must be set before creating the `IndexWriter`,
+    /// as the writer works on its own copy of the index.
+    pub fn set_term_dictionary_type(&mut self, term_dictionary_type: TermDictionaryType) {
+        self.term_dictionary_type = term_dictionary_type;
+    }
+
+    /// Sets the model scoring the documents in the queries
+    /// of the searchers of the index, and reloads the searchers.
+    ///
+    /// The model is not persisted, and replaces the
+    /// similarity of the queries, like the one set by
+    /// `MultiTermQuery::set_similarity_type`. It is shared by all
+    /// of the copies of the index, including the one of the `IndexWriter`.
+    pub fn set_similarity_model<TModel: BoxedSimilarityModel + 'static>(&self, similarity_model: TModel) -> Result<()> {
+        *self.similarity_model
+            .write()
+            .expect("Acquiring the lock should never fail") = Some(Arc::new(similarity_model));
+        self.load_searchers()
+    }
+
+    /// Returns the tokenizers of the index.
+    ///
+    /// Custom tokenizers must be registered before the
+    /// documents using them are indexed.
+    pub fn tokenizers(&self) -> &TokenizerManager {
+        &self.tokenizers
+    }
+
+    /// Returns the tokens a text gets indexed as in a given text field.
+    ///
+    /// This is mostly useful to debug the configuration of
+    /// the tokenizers. The offsets of the tokens are byte offsets
+    /// within `text`.
+    ///
+    /// Untokenized fields return the whole text as one single token,
+    /// lowercased for the `UntokenizedLowercase` fields.
+    /// Fails if the field is not a text field, or if its
+    /// tokenizer is not registered.
+    pub fn tokenize(&self, field: Field, text: &str) -> Result<Vec<Token>> {
+        let text_options = try!(self.text_options(field));
+        let indexing_options = text_options.get_indexing_options();
+        if !indexing_options.is_tokenized() {
+            let token_text = if indexing_options == TextIndexingOptions::UntokenizedLowercase {
+                text.to_lowercase()
+            }
+            else {
+                String::from(text)
+            };
+            return Ok(vec!(Token {
+                offset_from: 0,
+                offset_to: text.len(),
+                text: token_text,
+                position: 0,
+                payload: 0,
+            }));
+        }
+        let tokenizer = try!(self.tokenizer(field, text_options));
+        let mut token_stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while let Some(token) = token_stream.next() {
+            tokens.push(token.clone());
+        }
+        Ok(tokens)
+    }
+
+    /// Returns the fingerprint of the analyzer of a text field.
+    ///
+    /// The fingerprint is computed from the name of the tokenizer of
+    /// the field and from its `description`, which includes the
+    /// parameters of its token filters, so that registering another
+    /// chain of filters under the same name changes the fingerprint.
+    /// The untokenized fields only depend on their indexing options.
+    ///
+    /// The fingerprints of the analyzers the segments were indexed with
+    /// are recorded in the `meta.json` file, see `analyzer_divergences`.
+    ///
+    /// Fails if the field is not a text field, or if its
+    /// tokenizer is not registered.
+    pub fn analyzer_fingerprint(&self, field: Field) -> Result<u64> {
+        let text_options = try!(self.text_options(field));
+        let indexing_options = text_options.get_indexing_options();
+        let (tokenizer_name, description) =
+            if indexing_options.is_tokenized() {
+                let tokenizer = try!(self.tokenizer(field, text_options));
+                (text_options.get_tokenizer(), tokenizer.description())
+            }
+            else if indexing_options == TextIndexingOptions::UntokenizedLowercase {
+                ("", String::from("untokenized_lowercase"))
+            }
+            else {
+                ("", String::from("untokenized"))
+            };
+        // djb2, which, unlike the hasher of the standard library,
+        // is guaranteed to be stable across versions.
+        let mut fingerprint: u64 = 5381;
+        {
+            let mut fold = |bytes: &[u8]| {
+                for &b in bytes {
+                    fingerprint = (fingerprint << 5).wrapping_add(fingerprint).wrapping_add(b as u64);
+                }
+            };
+            fold(tokenizer_name.as_bytes());
+            // 0xff never appears in utf-8.
+            fold(&[0xff]);
+            fold(description.as_bytes());
+        }
+        Ok(fingerprint)
+    }
+
+    fn text_options(&self, field: Field) -> Result<&TextOptions> {
+        let field_entry = self.schema.get_field_entry(field);
+        match *field_entry.field_type() {
+            FieldType::Str(ref text_options) => Ok(text_options),
+            _ => Err(Error::InvalidArgument(format!("Field {:?} is not a text field", field_entry.name()))),
+        }
+    }
+
+    fn tokenizer(&self, field: Field, text_options: &TextOptions) -> Result<Arc<Tokenizer>> {
+        let tokenizer_name = text_options.get_tokenizer();
+        self.tokenizers
+            .get(tokenizer_name)
+            .ok_or_else(|| {
+                let field_name = self.schema.get_field_entry(field).name();
+                Error::InvalidArgument(format!("Tokenizer {:?} of field {:?} is not registered", tokenizer_name, field_name))
+            })
+    }
+
+    /// Returns the fingerprints of the analyzers of
+    /// the indexed text fields, by field name.
+    ///
+    /// The fields whose tokenizer is not registered are omitted.
+    ///
+    /// This method is not part of tantivy's public API
+    pub fn analyzer_fingerprints(&self) -> BTreeMap<String, u64> {
+        let mut fingerprints = BTreeMap::new();
+        for (field_id, field_entry) in self.schema.fields().iter().enumerate() {
+            if let FieldType::Str(ref text_options) = *field_entry.field_type() {
+                if !text_options.get_indexing_options().is_indexed() {
+                    continue;
+                }
+                if let Ok(fingerprint) = self.analyzer_fingerprint(Field(field_id as u8)) {
+                    fingerprints.insert(field_entry.name().clone(), fingerprint);
+                }
+            }
+        }
+        fingerprints
+    }
+
+    /// Returns the text fields whose current analyzer differs from
+    /// the analyzer some of the segments of the index were indexed with.
+    ///
+    /// The terms of the queries on these fields may not match
+    /// the terms of these segments anymore. The fields whose tokenizer
+    /// is not registered are reported as well.
+    ///
+    /// The custom tokenizers are registered after the index is opened,
+    /// so that the check must run once they are, e.g. before creating
+    /// the `QueryParser`. The segments written by the versions of
+    /// tantivy that did not record their analyzers are not checked.
+    pub fn analyzer_divergences(&self) -> Vec<Field> {
+        let current_analyzers = self.analyzer_fingerprints();
+        let (committed_segments, uncommitted_segments) = self.segment_manager.segment_metas();
+        let mut fields: Vec<Field> = Vec::new();
+        for segment_meta in committed_segments.iter().chain(uncommitted_segments.iter()) {
+            if let Some(ref analyzers) = segment_meta.analyzers {
+                for (field_name, fingerprint) in analyzers {
+                    if current_analyzers.get(field_name) == Some(fingerprint) {
+                        continue;
+                    }
+                    if let Some(field) = self.schema.get_field(field_name) {
+                        if !fields.contains(&field) {
+                            fields.push(field);
+                        }
+                    }
+                }
+            }
+        }
+        fields.sort();
+        fields
+    }
+
     /// Returns the list of segments that are searchable
     pub fn searchable_segments(&self) -> Vec<Segment> {
         self.searchable_segment_ids()
@@ -159,6 +400,22 @@ impl Index {
             .collect()
     }
 
+    /// Checks the integrity of all of the searchable segments.
+    ///
+    /// The footers of their components are checked, as well as the
+    /// checksum of all of their data, without decoding it.
+    /// This is typically useful after a crash.
+    ///
+    /// # Errors
+    /// `Error::CorruptedFile` for the first component
+    /// found truncated or corrupted.
+    pub fn validate(&self) -> Result<()> {
+        for segment in self.searchable_segments() {
+            try!(validate_segment(&segment));
+        }
+        Ok(())
+    }
+
     /// Remove all of the file associated with the segment.
     ///
     /// This method cannot fail. If a problem occurs,
@@ -202,13 +459,17 @@ impl Index {
     /// published or after a merge.
     pub fn load_searchers(&self) -> Result<()> {
         let searchable_segments = self.searchable_segments();
+        let similarity_model = self.similarity_model
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .clone();
         let mut searchers = Vec::new();
         for _ in 0..NUM_SEARCHERS {
             let searchable_segments_clone = searchable_segments.clone();
             let segment_readers: Vec<SegmentReader> = try!(searchable_segments_clone.into_iter()
                 .map(SegmentReader::open)
                 .collect());
-            let searcher = Searcher::from(segment_readers);
+            let searcher = Searcher::new(segment_readers, similarity_model.clone());
             searchers.push(searcher);
         }
         self.searcher_pool.publish_new_generation(searchers);
@@ -246,6 +507,434 @@ impl Clone for Index {
             schema: self.schema.clone(),
             searcher_pool: self.searcher_pool.clone(),
             docstamp: self.docstamp,
+            tokenizers: self.tokenizers.clone(),
+            term_dictionary_type: self.term_dictionary_type,
+            similarity_model: self.similarity_model.clone(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use tempdir::TempDir;
+    use core::META_FILEPATH;
+    use schema::{TEXT, STRING, FAST, TextOptions, TextIndexingOptions, U32Options};
+    use schema::FieldType;
+    use schema::{SchemaBuilder, Document, Term, Field};
+    use analyzer::{Tokenizer, SimpleTokenizer, LowerCaser, Stemmer, Language};
+    use analyzer::{RemoveLongFilter, DEFAULT_MAX_TOKEN_LEN};
+    use collector::CountCollector;
+    use query::{Query, MultiTermQuery};
+    use core::SegmentComponent;
+    use directory::FOOTER_LEN;
+    use datastruct::{TermDictionary, TermDictionaryKeys};
+
+    fn create_title_index(temp_dir: &TempDir) -> Field {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create(temp_dir.path(), schema_builder.build().unwrap()).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        let mut doc = Document::default();
+        doc.add_text(title_field, "hello");
+        index_writer.add_document(doc).unwrap();
+        index_writer.commit().unwrap();
+        title_field
+    }
+
+    #[test]
+    fn test_open_or_extend() {
+        let temp_dir = TempDir::new("index").unwrap();
+        let title_field = create_title_index(&temp_dir);
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let count_field = schema_builder.add_u32_field("count", FAST);
+        let index = Index::open_or_extend(temp_dir.path(), schema_builder.build().unwrap()).unwrap();
+        let count_docs = |term: Term| {
+            let searcher = index.searcher();
+            let mut count_collector = CountCollector::default();
+            MultiTermQuery::from(vec!(term)).search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        };
+        assert_eq!(count_docs(Term::from_field_text(title_field, "hello")), 1);
+        assert_eq!(count_docs(Term::from_field_text(body_field, "hello")), 0);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(title_field, "hello");
+            doc.add_text(body_field, "hello");
+            doc.add_u32(count_field, 3);
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        assert_eq!(count_docs(Term::from_field_text(title_field, "hello")), 2);
+        assert_eq!(count_docs(Term::from_field_text(body_field, "hello")), 1);
+        {
+            // merging segments created before and after the extension.
+            let segments = index.searchable_segments();
+            assert_eq!(segments.len(), 2);
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        assert_eq!(count_docs(Term::from_field_text(body_field, "hello")), 1);
+        {
+            let searcher = index.searcher();
+            let segment_reader = searcher.segment_reader(0);
+            let count_reader = segment_reader.get_fast_field_reader(count_field).unwrap();
+            let mut counts: Vec<u32> = (0..segment_reader.max_doc())
+                .map(|doc| count_reader.get(doc))
+                .collect();
+            counts.sort();
+            assert_eq!(counts, vec!(0, 3));
+        }
+        let reopened_index = Index::open(temp_dir.path()).unwrap();
+        assert_eq!(reopened_index.schema().get_field("count"), Some(count_field));
+    }
+
+    #[test]
+    fn test_open_or_extend_rejected() {
+        let temp_dir = TempDir::new("index").unwrap();
+        create_title_index(&temp_dir);
+        let assert_rejected = |schema: Schema, expected_msg: &str| {
+            match Index::open_or_extend(temp_dir.path(), schema) {
+                Err(Error::InvalidArgument(msg)) => {
+                    assert!(msg.contains(expected_msg), "unexpected error message {:?}", msg);
+                }
+                _ => { panic!("The schema should have been rejected"); }
+            }
+        };
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_u32_field("title", FAST);
+            assert_rejected(schema_builder.build().unwrap(), "FieldTypeChanged(\"title\")");
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", STRING);
+            assert_rejected(schema_builder.build().unwrap(), "FieldOptionsChanged(\"title\")");
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("body", TEXT);
+            assert_rejected(schema_builder.build().unwrap(), "FieldRemoved(\"title\")");
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("body", TEXT);
+            schema_builder.add_text_field("title", TEXT);
+            assert_rejected(schema_builder.build().unwrap(), "FieldMoved(\"title\")");
+        }
+        // rejected extensions leave the index untouched.
+        let index = Index::open(temp_dir.path()).unwrap();
+        assert_eq!(index.schema().fields().len(), 1);
+    }
+
+    #[test]
+    fn test_open_index_with_unknown_options() {
+        let temp_dir = TempDir::new("index").unwrap();
+        {
+            let mut meta_file = File::create(temp_dir.path().join(&*META_FILEPATH)).unwrap();
+            meta_file.write_all(br#"{
+                "committed_segments": [],
+                "uncommitted_segments": [],
+                "schema": [
+                    {
+                        "name": "title",
+                        "type": "text",
+                        "options": {
+                            "indexing": "position",
+                            "stored": false,
+                            "future_option": true
+                        }
+                    }
+                ],
+                "docstamp": 0
+            }"#).unwrap();
+        }
+        let index = Index::open(temp_dir.path()).unwrap();
+        let schema = index.schema();
+        let title_field = schema.get_field("title").unwrap();
+        assert_eq!(schema.get_field_entry(title_field).field_type(), &FieldType::Str(TEXT));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("en_stem"));
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let count_field = schema_builder.add_u32_field("count", FAST);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        assert!(index.tokenize(text_field, "hello").is_err());
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(LowerCaser).filter(Stemmer::new(Language::English)));
+        let text = "Über NAÏVE «running» 東京";
+        let tokens: Vec<(String, usize, usize, u32)> = index
+            .tokenize(text_field, text)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.text, token.offset_from, token.offset_to, token.position))
+            .collect();
+        assert_eq!(tokens, vec!(
+            (String::from("über"), 0, 5, 0),
+            (String::from("naïve"), 6, 12, 1),
+            (String::from("run"), 15, 22, 2),
+            (String::from("東京"), 25, 31, 3),
+        ));
+        assert_eq!(&text[15..22], "running");
+        assert_eq!(&text[25..31], "東京");
+        let tag_tokens = index.tokenize(tag_field, "Hello World").unwrap();
+        assert_eq!(tag_tokens.len(), 1);
+        assert_eq!(tag_tokens[0].text, "Hello World");
+        assert!(index.tokenize(count_field, "3").is_err());
+    }
+
+    #[test]
+    fn test_analyzer_fingerprint() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let stem_field = schema_builder.add_text_field("stem", TEXT.set_tokenizer("en_stem"));
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let lowercase_tag_field = schema_builder.add_text_field("lowercase_tag", TextOptions::default().set_indexing_options(TextIndexingOptions::UntokenizedLowercase));
+        let count_field = schema_builder.add_u32_field("count", FAST);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        assert!(index.analyzer_fingerprint(stem_field).is_err());
+        assert!(index.analyzer_fingerprint(count_field).is_err());
+        let default_fingerprint = index.analyzer_fingerprint(text_field).unwrap();
+        // the fingerprint depends on the name of the tokenizer.
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(RemoveLongFilter::limit(DEFAULT_MAX_TOKEN_LEN)));
+        let stem_fingerprint = index.analyzer_fingerprint(stem_field).unwrap();
+        assert!(stem_fingerprint != default_fingerprint);
+        // ... and on the parameters of its filters.
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(RemoveLongFilter::limit(10)));
+        assert!(index.analyzer_fingerprint(stem_field).unwrap() != stem_fingerprint);
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(RemoveLongFilter::limit(DEFAULT_MAX_TOKEN_LEN)));
+        assert_eq!(index.analyzer_fingerprint(stem_field).unwrap(), stem_fingerprint);
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(LowerCaser).filter(Stemmer::new(Language::English)));
+        assert!(index.analyzer_fingerprint(stem_field).unwrap() != stem_fingerprint);
+        assert!(index.analyzer_fingerprint(tag_field).unwrap() != index.analyzer_fingerprint(lowercase_tag_field).unwrap());
+        let field_names: Vec<String> = index.analyzer_fingerprints().keys().cloned().collect();
+        assert_eq!(field_names, vec!("lowercase_tag", "stem", "tag", "text"));
+    }
+
+    #[test]
+    fn test_analyzer_divergences() {
+        let temp_dir = TempDir::new("index").unwrap();
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT.set_tokenizer("en_stem"));
+        let stem_tokenizer = || SimpleTokenizer.filter(LowerCaser).filter(Stemmer::new(Language::English));
+        let add_doc = |index: &Index| {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(title_field, "Hello");
+            doc.add_text(body_field, "Running dogs");
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        };
+        {
+            let index = Index::create(temp_dir.path(), schema_builder.build().unwrap()).unwrap();
+            index.tokenizers().register("en_stem", stem_tokenizer());
+            add_doc(&index);
+            assert!(index.analyzer_divergences().is_empty());
+        }
+        let mut meta_content = String::new();
+        File::open(temp_dir.path().join(&*META_FILEPATH)).unwrap().read_to_string(&mut meta_content).unwrap();
+        assert!(meta_content.contains("\"analyzers\""));
+        let index = Index::open(temp_dir.path()).unwrap();
+        // the custom tokenizer is not registered yet.
+        assert_eq!(index.analyzer_divergences(), vec!(body_field));
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(LowerCaser));
+        assert_eq!(index.analyzer_divergences(), vec!(body_field));
+        index.tokenizers().register("en_stem", stem_tokenizer());
+        assert!(index.analyzer_divergences().is_empty());
+        // a segment indexed with another analyzer.
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(LowerCaser));
+        add_doc(&index);
+        index.tokenizers().register("en_stem", stem_tokenizer());
+        assert_eq!(index.analyzer_divergences(), vec!(body_field));
+        {
+            // the merged segment is mixed, and diverges
+            // from both of the analyzers.
+            let segments = index.searchable_segments();
+            assert_eq!(segments.len(), 2);
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        assert_eq!(index.searchable_segments().len(), 1);
+        assert_eq!(index.analyzer_divergences(), vec!(body_field));
+        index.tokenizers().register("en_stem", SimpleTokenizer.filter(LowerCaser));
+        assert_eq!(index.analyzer_divergences(), vec!(body_field));
+    }
+    fn create_ram_index() -> (Index, Field) {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT.set_stored());
+        schema_builder.add_u32_field("count", FAST);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..100 {
+                let mut doc = Document::default();
+                doc.add_text(title_field, &format!("hello title{}", i));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        (index, title_field)
+    }
+
+    fn assert_corrupted(result: Result<()>, segment: &Segment, component: SegmentComponent) {
+        match result {
+            Err(Error::CorruptedFile(path, _)) => {
+                assert_eq!(path, segment.relative_path(component));
+            }
+            _ => {
+                panic!("Expected the {} component to be detected as corrupted", component.path_suffix());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_corrupted_components() {
+        let (index, _) = create_ram_index();
+        let mut index_for_write = index.clone();
+        assert!(index.validate().is_ok());
+        let segment = index.searchable_segments()[0].clone();
+        for component in SegmentComponent::values().filter(|component| component.has_footer()) {
+            let path = segment.relative_path(component);
+            let data: Vec<u8> = segment.open_read(component).unwrap().to_vec();
+            assert!(data.len() >= FOOTER_LEN);
+            // a byte in the middle of the data, or the checksum
+            // itself if the component has no data.
+            let data_len = data.len() - FOOTER_LEN;
+            let corrupted_offset = if data_len > 0 { data_len / 2 } else { data.len() - 1 };
+            let mut corrupted_data = data.clone();
+            corrupted_data[corrupted_offset] ^= 255u8;
+            index_for_write.directory_mut().atomic_write(&path, &corrupted_data).unwrap();
+            assert_corrupted(index.validate(), &segment, component);
+            index_for_write.directory_mut().atomic_write(&path, &data).unwrap();
+            assert!(index.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_segment_reader_checks_footers() {
+        let (index, title_field) = create_ram_index();
+        let mut index_for_write = index.clone();
+        let segment = index.searchable_segments()[0].clone();
+        let postings_path = segment.relative_path(SegmentComponent::POSTINGS);
+        let postings_data: Vec<u8> = segment.open_read(SegmentComponent::POSTINGS).unwrap().to_vec();
+        {
+            // the postings are only checked against their checksum on demand.
+            let mut corrupted_data = postings_data.clone();
+            corrupted_data[0] ^= 255u8;
+            index_for_write.directory_mut().atomic_write(&postings_path, &corrupted_data).unwrap();
+            let segment_reader = SegmentReader::open(segment.clone()).unwrap();
+            assert!(segment_reader.read_postings_all_info(&Term::from_field_text(title_field, "hello")).is_some());
+            assert_corrupted(segment_reader.validate_checksum(), &segment, SegmentComponent::POSTINGS);
+        }
+        {
+            // a truncated component fails to open.
+            let truncated_data = &postings_data[..postings_data.len() - 1];
+            index_for_write.directory_mut().atomic_write(&postings_path, truncated_data).unwrap();
+            assert_corrupted(SegmentReader::open(segment.clone()).map(|_| ()), &segment, SegmentComponent::POSTINGS);
+        }
+        index_for_write.directory_mut().atomic_write(&postings_path, &postings_data).unwrap();
+        let segment_reader = SegmentReader::open(segment.clone()).unwrap();
+        assert!(segment_reader.validate_checksum().is_ok());
+        let space_usage = segment_reader.space_usage();
+        assert_eq!(space_usage.component(SegmentComponent::POSTINGS), postings_data.len());
+    }
+
+    #[test]
+    fn test_segment_reader_checks_terms_format() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let count_field = schema_builder.add_u32_field("count", U32Options::default().set_indexed());
+        let mut index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(title_field, "hello");
+            doc.add_u32(count_field, 3u32);
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let segment = index.searchable_segments()[0].clone();
+        assert!(SegmentReader::open(segment.clone()).is_ok());
+        let info_path = segment.relative_path(SegmentComponent::INFO);
+        let info_data = String::from_utf8(segment.open_read(SegmentComponent::INFO).unwrap().to_vec()).unwrap();
+        assert!(info_data.contains("\"terms_format_version\":1"));
+        // a segment with little-endian u32 terms.
+        let legacy_info_data = info_data.replace("\"terms_format_version\":1", "\"terms_format_version\":null");
+        index.directory_mut().atomic_write(&info_path, legacy_info_data.as_bytes()).unwrap();
+        match SegmentReader::open(segment.clone()) {
+            Err(Error::IncompatibleIndex(_)) => {}
+            _ => { panic!("Expected the legacy segment to be refused"); }
+        }
+    }
+
+    #[test]
+    fn test_term_dictionary_type() {
+        let temp_dir = TempDir::new("index").unwrap();
+        let title_field = create_title_index(&temp_dir);
+        let count_docs = |index: &Index, text: &str| {
+            let searcher = index.searcher();
+            let mut count_collector = CountCollector::default();
+            let term = Term::from_field_text(title_field, text);
+            MultiTermQuery::from(vec!(term)).search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        };
+        {
+            let mut index = Index::open(temp_dir.path()).unwrap();
+            assert_eq!(index.term_dictionary_type(), TermDictionaryType::Fst);
+            index.set_term_dictionary_type(TermDictionaryType::SSTable);
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(title_field, "hello happy");
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let index = Index::open(temp_dir.path()).unwrap();
+        assert_eq!(index.term_dictionary_type(), TermDictionaryType::SSTable);
+        {
+            let searcher = index.searcher();
+            let mut types: Vec<TermDictionaryType> = searcher
+                .segment_readers()
+                .iter()
+                .map(|segment_reader| segment_reader.term_dictionary_type())
+                .collect();
+            types.sort_by_key(|term_dictionary_type| *term_dictionary_type == TermDictionaryType::SSTable);
+            assert_eq!(types, vec!(TermDictionaryType::Fst, TermDictionaryType::SSTable));
+            for segment_reader in searcher.segment_readers() {
+                let stats = segment_reader.term_dictionary_stats().unwrap();
+                let mut num_terms = 0u64;
+                let mut keys = segment_reader.term_infos().keys();
+                while keys.next().is_some() {
+                    num_terms += 1;
+                }
+                assert_eq!(stats.num_terms, num_terms);
+            }
+        }
+        assert_eq!(count_docs(&index, "hello"), 2);
+        assert_eq!(count_docs(&index, "happy"), 1);
+        {
+            // merging a fst segment with a sstable segment.
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
         }
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.segment_reader(0).term_dictionary_type(), TermDictionaryType::SSTable);
+        let stats = searcher.segment_reader(0).term_dictionary_stats().unwrap();
+        assert_eq!(stats.num_terms, 2);
+        // "happy" and "hello" share their field byte and the "h".
+        assert_eq!(stats.shared_prefix_bytes, 2);
+        assert_eq!(count_docs(&index, "hello"), 2);
+        assert_eq!(count_docs(&index, "happy"), 1);
     }
 }
@@ -1,15 +1,24 @@
 
+use std::collections::BTreeMap;
 use schema::Schema;
 use core::SegmentId;
+use datastruct::TermDictionaryType;
+use indexer::SegmentManager;
+
+
+/// Fingerprint of the fields of a segment merged from
+/// segments indexed with different analyzers.
+pub const MIXED_ANALYZERS: u64 = 0u64;
 
 
 /// Meta information about the `Index`.
 /// 
 /// This object is serialized on disk in the `meta.json` file.
 /// It keeps information about 
-/// * the searchable segments,
+/// * the searchable segments, and the analyzers they were indexed with,
 /// * the index docstamp
 /// * the schema
+/// * the implementation of the term dictionary
 ///
 #[derive(Clone,Debug,RustcDecodable,RustcEncodable)]
 pub struct IndexMeta {
@@ -17,6 +26,11 @@ pub struct IndexMeta {
     pub uncommitted_segments: Vec<SegmentMeta>,
     pub schema: Schema,
     pub docstamp: u64,
+    /// Implementation of the term dictionary of the new segments.
+    ///
+    /// `None` for the indexes created by the versions of
+    /// tantivy that only wrote a `TermDictionaryType::Fst`.
+    pub term_dictionary_type: Option<TermDictionaryType>,
 }
 
 impl IndexMeta {
@@ -26,6 +40,7 @@ impl IndexMeta {
             uncommitted_segments: Vec::new(),
             schema: schema,
             docstamp: 0u64,
+            term_dictionary_type: None,
         }
     }
 }
@@ -34,6 +49,59 @@ impl IndexMeta {
 pub struct SegmentMeta {
     pub segment_id: SegmentId,
     pub num_docs: u32,
+    /// Fingerprints of the analyzers the text fields of the
+    /// segment were indexed with, by field name.
+    /// See `Index::analyzer_fingerprint`.
+    ///
+    /// `None` for the segments written by the versions of
+    /// tantivy that did not record them.
+    pub analyzers: Option<BTreeMap<String, u64>>,
+}
+
+impl SegmentMeta {
+
+    /// Returns the analyzer fingerprints of the segment resulting
+    /// from the merge of the segments `segment_ids`, given the metas
+    /// of the committed and uncommitted segments of `segment_manager`.
+    ///
+    /// See `SegmentMeta::merged_analyzers`.
+    pub fn merged_segment_analyzers(segment_manager: &SegmentManager, segment_ids: &[SegmentId]) -> Option<BTreeMap<String, u64>> {
+        let (committed_segments, uncommitted_segments) = segment_manager.segment_metas();
+        let merged_segment_metas: Vec<SegmentMeta> = committed_segments
+            .into_iter()
+            .chain(uncommitted_segments.into_iter())
+            .filter(|segment_meta| segment_ids.contains(&segment_meta.segment_id))
+            .collect();
+        SegmentMeta::merged_analyzers(&merged_segment_metas)
+    }
+
+    /// Returns the analyzer fingerprints of the segment
+    /// resulting from the merge of `segment_metas`.
+    ///
+    /// A field indexed with different analyzers in the merged
+    /// segments gets the `MIXED_ANALYZERS` fingerprint, so that
+    /// it is reported as diverging whatever the current analyzer.
+    pub fn merged_analyzers(segment_metas: &[SegmentMeta]) -> Option<BTreeMap<String, u64>> {
+        let mut merged_analyzers: BTreeMap<String, u64> = BTreeMap::new();
+        let mut has_analyzers = false;
+        for analyzers in segment_metas.iter().filter_map(|segment_meta| segment_meta.analyzers.as_ref()) {
+            has_analyzers = true;
+            for (field_name, &fingerprint) in analyzers {
+                let merged_fingerprint = merged_analyzers
+                    .entry(field_name.clone())
+                    .or_insert(fingerprint);
+                if *merged_fingerprint != fingerprint {
+                    *merged_fingerprint = MIXED_ANALYZERS;
+                }
+            }
+        }
+        if has_analyzers {
+            Some(merged_analyzers)
+        }
+        else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -42,6 +110,7 @@ impl SegmentMeta {
         SegmentMeta {
             segment_id: segment_id,
             num_docs: num_docs,
+            analyzers: None,
         }
     }
 }
\ No newline at end of file
@@ -6,6 +6,8 @@ mod segment_id;
 mod segment_component;
 mod segment;
 mod index_meta;
+mod field_stats;
+mod space_usage;
 mod pool;
 
 
@@ -19,6 +21,8 @@ pub use self::segment::SegmentInfo;
 pub use self::segment::SerializableSegment;
 pub use self::index::Index;
 pub use self::index_meta::{IndexMeta, SegmentMeta};
+pub use self::field_stats::FieldStats;
+pub use self::space_usage::{SegmentSpaceUsage, FieldSpaceUsage};
 
 
 lazy_static! {
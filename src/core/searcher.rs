@@ -4,9 +4,15 @@ use schema::Document;
 use collector::Collector;
 use common::TimerTree;
 use query::Query;
+use query::BoxedSimilarityModel;
 use DocId;
 use DocAddress;
 use schema::Term;
+use schema::Field;
+use core::FieldStats;
+use core::SegmentSpaceUsage;
+use std::sync::Arc;
+use std::fmt;
 
 
 /// Holds a list of `SegmentReader`s ready for search.
@@ -14,12 +20,21 @@ use schema::Term;
 /// It guarantees that the `Segment` will not be removed before  
 /// the destruction of the `Searcher`.
 /// 
-#[derive(Debug)]
 pub struct Searcher {
     segment_readers: Vec<SegmentReader>,
+    similarity_model: Option<Arc<BoxedSimilarityModel>>,
 }
 
 impl Searcher {
+
+    /// Creates a searcher over the segment readers, whose queries
+    /// score the documents with `similarity_model`, if any.
+    pub fn new(segment_readers: Vec<SegmentReader>, similarity_model: Option<Arc<BoxedSimilarityModel>>) -> Searcher {
+        Searcher {
+            segment_readers: segment_readers,
+            similarity_model: similarity_model,
+        }
+    }
       
     /// Fetches a document from tantivy's store given a `DocAddress`.
     ///
@@ -41,12 +56,70 @@ impl Searcher {
     
     /// Return the overall number of documents containing
     /// the given term. 
+    ///
+    /// It is read from the term dictionaries, without
+    /// opening the postings of the term.
     pub fn doc_freq(&self, term: &Term) -> u32 {
         self.segment_readers
             .iter()
             .map(|segment_reader| segment_reader.doc_freq(term))
             .fold(0u32, |acc, val| acc + val)
     }
+
+    /// Returns the overall number of documents containing
+    /// each of the terms, in the order of `terms`.
+    ///
+    /// The term dictionary of each segment is only
+    /// searched once for all of the terms,
+    /// see `SegmentReader::doc_freqs`.
+    pub fn doc_freqs(&self, terms: &[Term]) -> Vec<u32> {
+        let mut doc_freqs = vec!(0u32; terms.len());
+        for segment_reader in &self.segment_readers {
+            for (doc_freq, segment_doc_freq) in doc_freqs.iter_mut().zip(segment_reader.doc_freqs(terms)) {
+                *doc_freq += segment_doc_freq;
+            }
+        }
+        doc_freqs
+    }
+    
+    /// Returns the overall number of documents containing the term,
+    /// along with its overall number of occurrences.
+    ///
+    /// The number of occurrences is `None` if one of the segments
+    /// containing the term was written before it was recorded.
+    pub fn doc_freq_and_total_tf(&self, term: &Term) -> (u32, Option<u64>) {
+        let mut doc_freq = 0u32;
+        let mut total_term_freq = Some(0u64);
+        for segment_reader in &self.segment_readers {
+            let (segment_doc_freq, segment_total_term_freq) = segment_reader.doc_freq_and_total_tf(term);
+            doc_freq += segment_doc_freq;
+            total_term_freq = match (total_term_freq, segment_total_term_freq) {
+                (Some(total_term_freq), Some(segment_total_term_freq)) => Some(total_term_freq + segment_total_term_freq),
+                _ => None,
+            };
+        }
+        (doc_freq, total_term_freq)
+    }
+    
+    /// Returns the statistics of the field, aggregated
+    /// over all of the segments.
+    pub fn field_stats(&self, field: Field) -> FieldStats {
+        let mut field_stats = FieldStats::default();
+        for segment_reader in &self.segment_readers {
+            field_stats += segment_reader.field_stats(field);
+        }
+        field_stats
+    }
+    
+    /// Returns the space taken on disk by the segments,
+    /// summed over all of the segments.
+    pub fn space_usage(&self,) -> SegmentSpaceUsage {
+        let mut space_usage = SegmentSpaceUsage::default();
+        for segment_reader in &self.segment_readers {
+            space_usage += segment_reader.space_usage();
+        }
+        space_usage
+    }
     
     /// Return the list of segment readers
     pub fn segment_readers(&self,) -> &Vec<SegmentReader> {
@@ -57,6 +130,14 @@ impl Searcher {
     pub fn segment_reader(&self, segment_ord: usize) -> &SegmentReader {
         &self.segment_readers[segment_ord]
     }
+
+    /// Returns the model scoring the documents, if one
+    /// was set on the index, see `Index::set_similarity_model`.
+    ///
+    /// Otherwise, the queries use their own similarity.
+    pub fn similarity_model(&self,) -> Option<&BoxedSimilarityModel> {
+        self.similarity_model.as_ref().map(|similarity_model| &**similarity_model)
+    }
        
     /// Runs a query on the segment readers wrapped by the searcher
     pub fn search<Q: Query, C: Collector>(&self, query: &Q, collector: &mut C) -> Result<TimerTree> {
@@ -66,8 +147,15 @@ impl Searcher {
 
 impl From<Vec<SegmentReader>> for Searcher {
     fn from(segment_readers: Vec<SegmentReader>) -> Searcher {
-        Searcher {
-            segment_readers: segment_readers,
-        }
+        Searcher::new(segment_readers, None)
+    }
+}
+
+impl fmt::Debug for Searcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Searcher")
+            .field("segment_readers", &self.segment_readers)
+            .field("has_similarity_model", &self.similarity_model.is_some())
+            .finish()
     }
 }
\ No newline at end of file
@@ -4,12 +4,14 @@ use schema::Schema;
 use DocId;
 use std::fmt;
 use core::SegmentId;
-use directory::{ReadOnlySource, WritePtr};
+use directory::{ReadOnlySource, WritePtr, FooterWrite};
+use std::io::BufWriter;
 use indexer::segment_serializer::SegmentSerializer;
 use super::SegmentComponent;
 use core::Index;
 use std::result;
 use directory::error::{FileError, OpenWriteError};
+use datastruct::{TermDictionaryType, TermDictionaryStats};
 
 
 
@@ -40,6 +42,11 @@ pub fn create_segment(index: Index, segment_id: SegmentId) -> Segment {
 impl Segment {
 
 
+    /// Returns the index the segment belongs to.
+    pub fn index(&self,) -> &Index {
+        &self.index
+    }
+
     /// Returns our index's schema.
     pub fn schema(&self,) -> Schema {
         self.index.schema()
@@ -85,6 +92,8 @@ impl Segment {
 
 
     /// Open one of the component file for read.
+    ///
+    /// The data is returned as is, including its footer, if any.
     pub fn open_read(&self, component: SegmentComponent) -> result::Result<ReadOnlySource, FileError> {
         let path = self.relative_path(component);
         let source = try!(self.index.directory().open_read(&path));
@@ -92,10 +101,18 @@ impl Segment {
     }
 
     /// Open one of the component file for write.
+    ///
+    /// The components with a footer, see `SegmentComponent::has_footer`,
+    /// get it appended when their `WritePtr` is terminated.
     pub fn open_write(&mut self, component: SegmentComponent) -> result::Result<WritePtr, OpenWriteError> {
         let path = self.relative_path(component);
         let write = try!(self.index.directory_mut().open_write(&path));
-        Ok(write)
+        if component.has_footer() {
+            Ok(BufWriter::new(Box::new(FooterWrite::new(write))))
+        }
+        else {
+            Ok(write)
+        }
     }
 }
 
@@ -111,4 +128,38 @@ pub trait SerializableSegment {
 #[derive(Clone,Debug,RustcDecodable,RustcEncodable)]
 pub struct SegmentInfo {
 	pub max_doc: DocId,
+	/// Version of the format of the postings of the segment,
+	/// see `POSTINGS_FORMAT_VERSION`.
+	///
+	/// `None` for the segments written by the versions of
+	/// tantivy that did not record it.
+	pub postings_format_version: Option<u32>,
+	/// Version of the footer of the components of the segment,
+	/// see `FOOTER_VERSION`.
+	///
+	/// `None` for the segments written by the versions of
+	/// tantivy that did not append a footer to their components.
+	pub footer_version: Option<u32>,
+	/// Version of the encoding of the field norms of the segment,
+	/// see `FIELDNORMS_FORMAT_VERSION`.
+	///
+	/// `None` for the segments written by the versions of
+	/// tantivy that stored the field norms themselves.
+	pub fieldnorms_format_version: Option<u32>,
+	/// Version of the encoding of the terms of the segment,
+	/// see `TERMS_FORMAT_VERSION`.
+	///
+	/// `None` for the segments written by the versions of
+	/// tantivy that encoded the u32 terms in little-endian.
+	pub terms_format_version: Option<u32>,
+	/// Implementation of the term dictionary of the segment.
+	///
+	/// `None` for the segments written by the versions of
+	/// tantivy that only wrote a `TermDictionaryType::Fst`.
+	pub term_dictionary_type: Option<TermDictionaryType>,
+	/// Statistics of the terms of the term dictionary.
+	///
+	/// `None` for the segments written by the versions of
+	/// tantivy that did not record them.
+	pub term_dictionary_stats: Option<TermDictionaryStats>,
 }
\ No newline at end of file
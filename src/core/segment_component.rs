@@ -5,8 +5,13 @@ pub enum SegmentComponent {
     INFO,
     POSTINGS,
     POSITIONS,
+    PAYLOADS,
     FASTFIELDS,
+    BYTESFASTFIELDS,
     FIELDNORMS,
+    FIELDSTATS,
+    SPACEUSAGE,
+    FIELDPRESENCE,
     TERMS,
     STORE,
 }
@@ -17,22 +22,44 @@ impl SegmentComponent {
             SegmentComponent::INFO,
             SegmentComponent::POSTINGS,
             SegmentComponent::POSITIONS,
+            SegmentComponent::PAYLOADS,
             SegmentComponent::FASTFIELDS,
+            SegmentComponent::BYTESFASTFIELDS,
             SegmentComponent::FIELDNORMS,
+            SegmentComponent::FIELDSTATS,
+            SegmentComponent::SPACEUSAGE,
+            SegmentComponent::FIELDPRESENCE,
             SegmentComponent::TERMS,
             SegmentComponent::STORE,
         ).into_iter()
     }
     
+    /// Returns true if the component ends with a `Footer`,
+    /// as all of the components but the `.info` file do.
+    ///
+    /// The `.info` file tells whether the segment was written
+    /// with footers, so that it is read before any other component.
+    pub fn has_footer(&self,) -> bool {
+        match *self {
+            SegmentComponent::INFO => false,
+            _ => true,
+        }
+    }
+
     pub fn path_suffix(&self)-> &'static str {
         match *self {
             SegmentComponent::POSITIONS => ".pos",
+            SegmentComponent::PAYLOADS => ".payload",
             SegmentComponent::INFO => ".info",
             SegmentComponent::POSTINGS => ".idx",
             SegmentComponent::TERMS => ".term",
             SegmentComponent::STORE => ".store",
             SegmentComponent::FASTFIELDS => ".fast",
+            SegmentComponent::BYTESFASTFIELDS => ".bytes",
             SegmentComponent::FIELDNORMS => ".fieldnorm",
+            SegmentComponent::FIELDSTATS => ".stats",
+            SegmentComponent::SPACEUSAGE => ".space",
+            SegmentComponent::FIELDPRESENCE => ".presence",
         }
     }
 }
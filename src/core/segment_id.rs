@@ -9,6 +9,10 @@ use std::cmp::{Ordering, Ord};
 #[cfg(test)]
 use std::sync::atomic;
 
+/// Unique identifier of a segment.
+///
+/// Contrary to the segment ordinals of a `Searcher`, it does not
+/// depend on the other segments of the index.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SegmentId(Uuid);
 
@@ -37,18 +41,22 @@ fn create_uuid() -> Uuid {
 }
 
 impl SegmentId {
+    /// Generates a new random segment id.
     pub fn generate_random() -> SegmentId {
         SegmentId(create_uuid())
     }
     
+    /// Returns the first 8 characters of the uuid of the segment.
     pub fn short_uuid_string(&self,) -> String {
         (&self.0.to_simple_string()[..8]).to_string()
     }
     
+    /// Returns the uuid of the segment, as an hexadecimal string.
     pub fn uuid_string(&self,) -> String {
         self.0.to_simple_string()
     }
     
+    /// Returns the path of the file of the given component of the segment.
     pub fn relative_path(&self, component: SegmentComponent) -> PathBuf {
         let filename = self.uuid_string() + component.path_suffix();
         PathBuf::from(filename)
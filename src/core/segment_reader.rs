@@ -5,24 +5,42 @@ use core::SegmentComponent;
 use schema::Term;
 use store::StoreReader;
 use schema::Document;
-use directory::ReadOnlySource;
+use directory::{ReadOnlySource, Footer, FOOTER_LEN};
+use directory::error::FileError;
 use DocId;
 use std::io;
 use std::str;
 use postings::TermInfo;
-use datastruct::FstMap;
+use postings::TermStream;
+use postings::TermDict;
+use datastruct::{TermDictionary, TermDictionaryKeys, SegmentTermDictionary, TermDictionaryType, TermDictionaryStats};
+use datastruct::SetAutomaton;
+use fst::Set;
 use std::fmt;
 use rustc_serialize::json;
 use core::SegmentInfo;
+use core::FieldStats;
+use core::{SegmentSpaceUsage, FieldSpaceUsage};
+use rustc_serialize::Decodable;
+use common::HasLen;
+use common::BitSet;
+use common::BinarySerializable;
 use schema::Field;
 use postings::SegmentPostingsOption;
 use postings::SegmentPostings;
-use fastfield::{U32FastFieldsReader, U32FastFieldReader};
+use fastfield::{U32FastFieldsReader, U32FastFieldReader, U64FastFieldReader, I64FastFieldReader, F64FastFieldReader};
+use fastfield::{BytesFastFieldsReader, BytesFastFieldReader};
+use fastfield::{MultiU32FastFieldReader, BoolFastFieldReader, IpFastFieldReader};
+use fastfield::StrFastFieldReader;
+use fastfield::FieldNormReader;
 use schema::Schema;
 use schema::FieldType;
 use postings::FreqHandler;
 use schema::TextIndexingOptions;
 use error::Error;
+use common::i64_to_u64;
+use std::net::Ipv6Addr;
+use std::sync::Arc;
 
 
 /// Entry point to access all of the datastructures of the `Segment`
@@ -39,15 +57,173 @@ use error::Error;
 pub struct SegmentReader {
     segment_info: SegmentInfo,
     segment_id: SegmentId,
-    term_infos: FstMap<TermInfo>,
+    term_infos: Arc<SegmentTermDictionary<TermInfo>>,
     postings_data: ReadOnlySource,
     store_reader: StoreReader,
     fast_fields_reader: U32FastFieldsReader,
+    bytes_fast_fields_reader: BytesFastFieldsReader,
     fieldnorms_reader: U32FastFieldsReader,
+    field_stats: Vec<FieldStats>,
+    space_usage: SegmentSpaceUsage,
+    field_presence: Vec<BitSet>,
+    empty_field_presence: BitSet,
     positions_data: ReadOnlySource,
+    payloads_data: ReadOnlySource,
+    components: Vec<ComponentData>,
     schema: Schema,
 }
 
+
+/// Data of one of the components of a segment, without its footer.
+///
+/// The footer is `None` for the `.info` file,
+/// and for the segments written without footers.
+struct ComponentData {
+    component: SegmentComponent,
+    data: ReadOnlySource,
+    footer: Option<Footer>,
+}
+
+/// Reads and decodes the `.info` file of the segment.
+fn read_segment_info(segment: &Segment) -> Result<SegmentInfo> {
+    let segment_info_reader = try!(segment.open_read(SegmentComponent::INFO));
+    let segment_info_data = try!(
+        str::from_utf8(&*segment_info_reader)
+            .map_err(|err| {
+                let segment_info_filepath = segment.relative_path(SegmentComponent::INFO);
+                Error::CorruptedFile(segment_info_filepath, Box::new(err))
+            })
+     );
+    json::decode(&segment_info_data)
+        .map_err(|err| {
+            let file_path = segment.relative_path(SegmentComponent::INFO);
+            Error::CorruptedFile(file_path, Box::new(err))
+        })
+}
+
+/// Opens all of the components of the segment, but the ones it does not have,
+/// as is the case of the components introduced after it was created.
+///
+/// The magic number and the version of the footers are checked eagerly,
+/// so that a truncated component fails to open.
+fn open_components(segment: &Segment, segment_info: &SegmentInfo) -> Result<Vec<ComponentData>> {
+    let mut components = Vec::new();
+    for component in SegmentComponent::values() {
+        let source = match segment.open_read(component) {
+            Ok(source) => source,
+            Err(FileError::FileDoesNotExist(_)) => { continue; }
+            Err(err) => { return Err(Error::from(err)); }
+        };
+        if segment_info.footer_version.is_some() && component.has_footer() {
+            let (data, footer) = try!(
+                Footer::extract(&source)
+                    .map_err(|err| Error::CorruptedFile(segment.relative_path(component), Box::new(err)))
+            );
+            components.push(ComponentData {
+                component: component,
+                data: data,
+                footer: Some(footer),
+            });
+        }
+        else {
+            components.push(ComponentData {
+                component: component,
+                data: source,
+                footer: None,
+            });
+        }
+    }
+    Ok(components)
+}
+
+/// Checks that the terms of the segment can be read as
+/// they are encoded today.
+///
+/// Before `TERMS_FORMAT_VERSION`, the u32 terms were encoded in
+/// little-endian. Their term dictionary cannot be read, nor merged,
+/// as if they were in big-endian, so that the segments having indexed
+/// u32 fields need to be reindexed.
+fn check_terms_format(segment: &Segment, segment_info: &SegmentInfo) -> Result<()> {
+    if segment_info.terms_format_version.is_some() {
+        return Ok(());
+    }
+    let schema = segment.schema();
+    for (_, field_entry) in schema.field_entries() {
+        if let FieldType::U32(ref u32_options) = *field_entry.field_type() {
+            if u32_options.is_indexed() {
+                return Err(Error::IncompatibleIndex(format!(
+                    "Segment {:?} encodes the terms of the u32 field {:?} in little-endian, it needs to be reindexed",
+                    segment.id(), field_entry.name())));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks the data of each of the components against the checksum of its footer.
+fn validate_checksums(segment_id: SegmentId, components: &[ComponentData]) -> Result<()> {
+    for component_data in components {
+        if let Some(footer) = component_data.footer {
+            try!(
+                footer.validate_checksum(&component_data.data)
+                    .map_err(|err| {
+                        Error::CorruptedFile(segment_id.relative_path(component_data.component), Box::new(err))
+                    })
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks the footers of all of the components of the segment,
+/// and the data of the components against their checksum.
+///
+/// Unlike `SegmentReader::open`, the data is not decoded.
+pub fn validate_segment(segment: &Segment) -> Result<()> {
+    let segment_info = try!(read_segment_info(segment));
+    let components = try!(open_components(segment, &segment_info));
+    validate_checksums(segment.id(), &components)
+}
+
+// returns the data of the component, if the segment has it.
+fn component_data(components: &[ComponentData], component: SegmentComponent) -> Option<ReadOnlySource> {
+    components
+        .iter()
+        .find(|component_data| component_data.component.path_suffix() == component.path_suffix())
+        .map(|component_data| component_data.data.clone())
+}
+
+// returns the data of a component every segment has.
+fn required_component_data(segment: &Segment, components: &[ComponentData], component: SegmentComponent) -> Result<ReadOnlySource> {
+    component_data(components, component)
+        .ok_or_else(|| Error::PathDoesNotExist(segment.relative_path(component)))
+}
+
+
+/// Reads and decodes a component of the segment encoded in JSON.
+///
+/// Returns `None` if the segment does not have the component,
+/// as is the case of the segments created before it was introduced.
+fn read_json_component<T: Decodable>(segment: &Segment, components: &[ComponentData], component: SegmentComponent) -> Result<Option<T>> {
+    let source = match component_data(components, component) {
+        Some(source) => source,
+        None => { return Ok(None); }
+    };
+    let data = try!(
+        str::from_utf8(&*source)
+            .map_err(|err| {
+                Error::CorruptedFile(segment.relative_path(component), Box::new(err))
+            })
+    );
+    let decoded = try!(
+        json::decode(data)
+            .map_err(|err| {
+                Error::CorruptedFile(segment.relative_path(component), Box::new(err))
+            })
+    );
+    Ok(Some(decoded))
+}
+
 impl SegmentReader {
     /// Returns the highest document id ever attributed in
     /// this segment + 1.
@@ -66,18 +242,281 @@ impl SegmentReader {
         self.segment_info.max_doc
     }
     
+    /// Returns the id of the segment.
+    pub fn segment_id(&self,) -> SegmentId {
+        self.segment_id
+    }
+
+    /// Returns true iff the segment contains the fast field.
+    ///
+    /// Segments created before the field was added to the schema
+    /// (see `Index::open_or_extend`) do not contain it.
+    pub fn has_fast_field(&self, field: Field) -> bool {
+        self.fast_fields_reader.has_field(field)
+    }
+
     /// Accessor to a segment's fast field reader given a field.
+    ///
+    /// Segments created before the field was added to the schema
+    /// (see `Index::open_or_extend`) behave as if none of their
+    /// documents had a value for it, and return the default value
+    /// of the field. This holds for all of the fast field accessors.
+    ///
+    /// # Errors
+    /// Returns an error if the field is not a single-valued u32 fast field.
     pub fn get_fast_field_reader(&self, field: Field) -> io::Result<U32FastFieldReader> {
         let field_entry = self.schema.get_field_entry(field);
         match *field_entry.field_type() {
             FieldType::Str(_) => {
-                Err(io::Error::new(io::ErrorKind::Other, "fast field are not yet supported for text fields."))
+                Err(io::Error::new(io::ErrorKind::Other, "the term ordinals of text fast fields should be accessed via term_ords_fast_field."))
             },
-            FieldType::U32(_) => {
+            FieldType::U32(ref u32_options) => {
+                if field_entry.is_multivalued_u32_fast() {
+                    return Err(io::Error::new(io::ErrorKind::Other, "multivalued fast fields should be accessed via multi_u32_fast_field."));
+                }
+                if !u32_options.is_fast() {
+                    return Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a fast field.", field_entry.name())));
+                }
                 // TODO check that the schema allows that
                 //Err(io::Error::new(io::ErrorKind::Other, "fast field are not yet supported for text fields."))
+                if !self.fast_fields_reader.has_field(field) {
+                    return Ok(U32FastFieldReader::from_constant(u32_options.get_default_value()));
+                }
                 self.fast_fields_reader.get_field(field)
             },
+            FieldType::I64(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "i64 fast fields should be accessed via get_i64_fast_field_reader."))
+            },
+            FieldType::F64(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "f64 fast fields should be accessed via get_f64_fast_field_reader."))
+            },
+            FieldType::Date(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "date fast fields should be accessed via get_date_fast_field_reader."))
+            },
+            FieldType::Bytes(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "bytes fast fields should be accessed via get_bytes_fast_field_reader."))
+            },
+            FieldType::HierarchicalFacet(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "fast field are not supported for facet fields."))
+            },
+            FieldType::Bool(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "bool fast fields should be accessed via get_bool_fast_field_reader."))
+            },
+            FieldType::Ip(_) => {
+                Err(io::Error::new(io::ErrorKind::Other, "ip fast fields should be accessed via get_ip_fast_field_reader."))
+            },
+        }
+    }
+
+    /// Accessor to a segment's multivalued u32 fast field reader given a field.
+    ///
+    /// The reader gives access to all of the values associated to each document.
+    pub fn multi_u32_fast_field(&self, field: Field) -> io::Result<MultiU32FastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_multivalued_u32_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(MultiU32FastFieldReader::empty());
+            }
+            self.fast_fields_reader.get_multivalued_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a multivalued u32 fast field.", field_entry.name())))
+        }
+    }
+
+    /// Returns the min and max values of a u32 fast field,
+    /// single-valued or multivalued, in the segment.
+    ///
+    /// They are read from the header of the fast field, without
+    /// scanning its values, and bound all of the values of the
+    /// field in the segment, so that a query on a range of values
+    /// may skip the segments whose bounds do not intersect its range.
+    /// The bounds of a single-valued field also account for its default
+    /// value, which is associated to the documents without any value.
+    ///
+    /// # Errors
+    /// Returns an error if the field is not a u32 fast field.
+    pub fn u32_fast_field_bounds(&self, field: Field) -> io::Result<(u32, u32)> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_multivalued_u32_fast() {
+            let multi_reader = try!(self.multi_u32_fast_field(field));
+            let vals_reader = multi_reader.vals_reader();
+            Ok((vals_reader.min_val(), vals_reader.max_val()))
+        }
+        else {
+            let u32_reader = try!(self.get_fast_field_reader(field));
+            Ok((u32_reader.min_val(), u32_reader.max_val()))
+        }
+    }
+
+    /// Accessor to the term ordinals of a fast text field.
+    ///
+    /// The reader associates each document to the sorted ordinals
+    /// of its terms, that is their rank among the terms streamed
+    /// by `terms(field)`. They can be resolved back to the terms
+    /// with `ords_to_terms`, or with the `StrFastFieldReader`.
+    pub fn term_ords_fast_field(&self, field: Field) -> io::Result<MultiU32FastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_str_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(MultiU32FastFieldReader::empty());
+            }
+            self.fast_fields_reader.get_multivalued_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a fast text field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to a fast text field, used as a single-valued field,
+    /// to sort or group the documents by their term.
+    ///
+    /// The reader associates each document to the ordinal of its term,
+    /// and resolves the ordinals back to the terms.
+    pub fn str_fast_field(&self, field: Field) -> io::Result<StrFastFieldReader> {
+        let term_ords_reader = try!(self.term_ords_fast_field(field));
+        // ordinal of the first key of the field, or of the following field,
+        // in which case the field has no term.
+        let first_term_ord = {
+            let field_prefix = Term::allocate(field, 1);
+            let mut keys = self.term_infos.keys_from(field_prefix.as_slice());
+            keys.next()
+                .and_then(|key| self.term_infos.term_ord(key))
+                .unwrap_or(0u64)
+        };
+        Ok(StrFastFieldReader::new(field, term_ords_reader, self.term_infos.clone(), first_term_ord))
+    }
+
+    /// Returns the value bytes of the terms of the fast text field
+    /// with the given ordinals, in the order of `ords`.
+    ///
+    /// Each ordinal is looked up on its own in the term dictionary,
+    /// so that the ordinals do not need to be sorted.
+    ///
+    /// # Errors
+    /// Returns an error if one of the ordinals is not
+    /// the ordinal of a term of the field.
+    pub fn ords_to_terms(&self, field: Field, ords: &[u32]) -> io::Result<Vec<Vec<u8>>> {
+        let str_reader = try!(self.str_fast_field(field));
+        let mut terms = Vec::with_capacity(ords.len());
+        for &ord in ords {
+            let mut term_bytes = Vec::new();
+            if !str_reader.term(ord, &mut term_bytes) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not the ordinal of a term of the field {:?}.", ord, field)));
+            }
+            terms.push(term_bytes);
+        }
+        Ok(terms)
+    }
+
+    /// Accessor to a segment's i64 fast field reader given a field.
+    pub fn get_i64_fast_field_reader(&self, field: Field) -> io::Result<I64FastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_i64_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(I64FastFieldReader::from_constant(0i64));
+            }
+            self.fast_fields_reader.get_i64_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not an i64 fast field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to a segment's bool fast field reader given a field.
+    ///
+    /// Documents without any value are associated to `false`.
+    pub fn get_bool_fast_field_reader(&self, field: Field) -> io::Result<BoolFastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_bool_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(BoolFastFieldReader::from_constant(false));
+            }
+            self.fast_fields_reader.get_bool_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a bool fast field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to a segment's IP fast field reader given a field.
+    ///
+    /// Addresses are returned in their IPv6 form.
+    /// Documents without any value are associated to `::`.
+    pub fn get_ip_fast_field_reader(&self, field: Field) -> io::Result<IpFastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_ip_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(IpFastFieldReader::from_constant(Ipv6Addr::from([0u8; 16])));
+            }
+            self.fast_fields_reader.get_ip_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not an IP fast field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to a segment's f64 fast field reader given a field.
+    pub fn get_f64_fast_field_reader(&self, field: Field) -> io::Result<F64FastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_f64_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(F64FastFieldReader::from_constant(0f64));
+            }
+            self.fast_fields_reader.get_f64_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not an f64 fast field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to a segment's date fast field reader given a field.
+    ///
+    /// Values are returned as a number of milliseconds since Epoch.
+    pub fn get_date_fast_field_reader(&self, field: Field) -> io::Result<I64FastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_date_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(I64FastFieldReader::from_constant(0i64));
+            }
+            self.fast_fields_reader.get_i64_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a date fast field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to a segment's bytes fast field reader given a field.
+    ///
+    /// The reader gives access to the `&[u8]` associated to each document.
+    pub fn get_bytes_fast_field_reader(&self, field: Field) -> io::Result<BytesFastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_bytes_fast() {
+            if !self.bytes_fast_fields_reader.has_field(field) {
+                return Ok(BytesFastFieldReader::empty(self.max_doc()));
+            }
+            self.bytes_fast_fields_reader.get_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a bytes fast field.", field_entry.name())))
+        }
+    }
+
+    /// Accessor to the raw `u64` representation of a 64-bits fast field.
+    ///
+    /// i64, date and f64 fast fields are all stored as `u64`, after
+    /// an order preserving mapping.
+    /// This is used by the merger, which does not need to decode the values.
+    pub fn get_u64_fast_field_reader(&self, field: Field) -> io::Result<U64FastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_u64_fast() {
+            if !self.fast_fields_reader.has_field(field) {
+                return Ok(U64FastFieldReader::from_constant(i64_to_u64(0i64)));
+            }
+            self.fast_fields_reader.get_u64_field(field)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Field {:?} is not a 64-bits fast field.", field_entry.name())))
         }
     }
     
@@ -86,19 +525,118 @@ impl SegmentReader {
     /// Field norms are the length (in tokens) of the fields.
     /// It is used in the computation of the [TfIdf](https://fulmicoton.gitbooks.io/tantivy-doc/content/tfidf.html).
     ///
-    /// They are simply stored as a fast field, serialized in 
-    /// the `.fieldnorm` file of the segment. 
-    pub fn get_fieldnorms_reader(&self, field: Field) -> io::Result<U32FastFieldReader> {
-        self.fieldnorms_reader.get_field(field) 
+    /// They are stored as a fast field of their ids, see `fieldnorm_to_id`,
+    /// serialized in the `.fieldnorm` file of the segment, so that they take
+    /// at most one byte per document. The encoding is lossy: the field norms
+    /// returned are the lower bounds of the field norms sharing their id.
+    ///
+    /// Fields without field norms (non-text fields, or text fields
+    /// with field norms disabled) return a reader with a constant
+    /// field norm of 1.
+    ///
+    /// Segments created before the field was added to the schema
+    /// return a constant field norm of 0.
+    pub fn get_fieldnorm_reader(&self, field: Field) -> io::Result<FieldNormReader> {
+        if self.schema.get_field_entry(field).has_fieldnorms() {
+            if !self.fieldnorms_reader.has_field(field) {
+                return Ok(FieldNormReader::from_constant(0u32));
+            }
+            let reader = try!(self.fieldnorms_reader.get_field(field));
+            if self.segment_info.fieldnorms_format_version.is_some() {
+                Ok(FieldNormReader::from_fieldnorm_ids(reader))
+            }
+            else {
+                Ok(FieldNormReader::from_fieldnorms(reader))
+            }
+        }
+        else {
+            Ok(FieldNormReader::from_constant(1u32))
+        }
     }
         
+    /// Returns the statistics of the field within the segment.
+    ///
+    /// Segments created before the field was added to the schema
+    /// return empty statistics.
+    pub fn field_stats(&self, field: Field) -> FieldStats {
+        self.field_stats
+            .get(field.0 as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the set of the documents having at least
+    /// one value for the field, whether it is indexed, stored or fast.
+    ///
+    /// Segments created before the field was added to the schema,
+    /// or before field presence was recorded, return an empty set.
+    pub fn has_field(&self, field: Field) -> &BitSet {
+        self.field_presence
+            .get(field.0 as usize)
+            .unwrap_or(&self.empty_field_presence)
+    }
+
+    /// Returns the number of bytes taken by each of the files
+    /// of the segment, and by each field in the inverted index.
+    pub fn space_usage(&self,) -> SegmentSpaceUsage {
+        self.space_usage.clone()
+    }
+
     /// Returns the number of documents containing the term.
+    ///
+    /// It is read from the term dictionary, without
+    /// opening the postings of the term.
     pub fn doc_freq(&self, term: &Term) -> u32 {
         match self.get_term_info(term) {
             Some(term_info) => term_info.doc_freq,
             None => 0,
         }
+    }
+
+    /// Returns the number of documents containing each of the terms,
+    /// in the order of `terms`.
+    ///
+    /// Rather than looking the terms up one by one, they are sorted
+    /// and searched for in a single pass over the term dictionary.
+    /// As for `doc_freq`, the postings are not opened.
+    pub fn doc_freqs(&self, terms: &[Term]) -> Vec<u32> {
+        let mut doc_freqs = vec!(0u32; terms.len());
+        let mut term_ords: Vec<usize> = (0..terms.len()).collect();
+        term_ords.sort_by(|&left, &right| terms[left].as_slice().cmp(terms[right].as_slice()));
+        let mut sorted_terms: Vec<&[u8]> = term_ords
+            .iter()
+            .map(|&term_ord| terms[term_ord].as_slice())
+            .collect();
+        sorted_terms.dedup();
+        let set = Set::from_iter(sorted_terms).expect("The terms have been sorted and deduplicated");
+        let mut found_terms = self.term_dict().search(SetAutomaton::new(&set));
+        let mut term_ords_it = term_ords.iter().cloned().peekable();
+        while let Some((term_bytes, term_info)) = found_terms.next() {
+            while let Some(term_ord) = term_ords_it.peek().cloned() {
+                let term = terms[term_ord].as_slice();
+                if term > term_bytes {
+                    break;
+                }
+                if term == term_bytes {
+                    doc_freqs[term_ord] = term_info.doc_freq;
+                }
+                term_ords_it.next();
+            }
+        }
+        doc_freqs
     }    
+
+    /// Returns the number of documents containing the term, along
+    /// with its number of occurrences within the segment.
+    ///
+    /// The number of occurrences is `None` if the segment
+    /// was written before it was recorded.
+    pub fn doc_freq_and_total_tf(&self, term: &Term) -> (u32, Option<u64>) {
+        match self.get_term_info(term) {
+            Some(term_info) => (term_info.doc_freq, term_info.total_term_freq),
+            None => (0, Some(0)),
+        }
+    }
     
     /// Accessor to the segment's `StoreReader`.
     pub fn get_store_reader(&self) -> &StoreReader {
@@ -106,55 +644,141 @@ impl SegmentReader {
     }
 
     /// Open a new segment for reading.
+    ///
+    /// The footers of the components are checked, but not their checksums,
+    /// as it would require to read all of the data of the segment.
+    /// See `validate_checksum`.
+    ///
+    /// # Errors
+    /// `Error::IncompatibleIndex` if the segment has indexed u32
+    /// fields, and predates `TERMS_FORMAT_VERSION`.
     pub fn open(segment: Segment) -> Result<SegmentReader> {
-        let segment_info_reader = try!(segment.open_read(SegmentComponent::INFO));
-        let segment_info_data = try!(
-            str::from_utf8(&*segment_info_reader)
-                .map_err(|err| {
-                    let segment_info_filepath = segment.relative_path(SegmentComponent::INFO);
-                    Error::CorruptedFile(segment_info_filepath, Box::new(err))
-                })
-         );
-        let segment_info: SegmentInfo = try!(
-            json::decode(&segment_info_data)
-            .map_err(|err| {
-                let file_path = segment.relative_path(SegmentComponent::INFO);
-                Error::CorruptedFile(file_path, Box::new(err))
-            })
-        );
-        let source = try!(segment.open_read(SegmentComponent::TERMS));
-        let term_infos = try!(FstMap::from_source(source));
-        let store_reader = StoreReader::from(try!(segment.open_read(SegmentComponent::STORE)));
-        let postings_shared_mmap = try!(segment.open_read(SegmentComponent::POSTINGS));
+        let segment_info = try!(read_segment_info(&segment));
+        try!(check_terms_format(&segment, &segment_info));
+        let components = try!(open_components(&segment, &segment_info));
+        let source = try!(required_component_data(&segment, &components, SegmentComponent::TERMS));
+        let term_dictionary_type = segment_info.term_dictionary_type.unwrap_or(TermDictionaryType::Fst);
+        let term_infos = match segment_info.postings_format_version {
+            Some(postings_format_version) if postings_format_version >= 5 => {
+                try!(SegmentTermDictionary::from_source(term_dictionary_type, source))
+            }
+            Some(4) => {
+                try!(SegmentTermDictionary::from_source_with_deserializer(term_dictionary_type, source, TermInfo::deserialize_v4))
+            }
+            _ => {
+                try!(SegmentTermDictionary::from_source_with_deserializer(term_dictionary_type, source, TermInfo::deserialize_legacy))
+            }
+        };
+        let store_reader = StoreReader::from(try!(required_component_data(&segment, &components, SegmentComponent::STORE)));
+        let postings_shared_mmap = try!(required_component_data(&segment, &components, SegmentComponent::POSTINGS));
         
-        let fast_field_data = try!(segment.open_read(SegmentComponent::FASTFIELDS));
+        let fast_field_data = try!(required_component_data(&segment, &components, SegmentComponent::FASTFIELDS));
         let fast_fields_reader = try!(U32FastFieldsReader::open(fast_field_data));
         
-        let fieldnorms_data = try!(segment.open_read(SegmentComponent::FIELDNORMS));
+        let bytes_fast_field_data = component_data(&components, SegmentComponent::BYTESFASTFIELDS)
+            .unwrap_or_else(ReadOnlySource::empty);
+        let bytes_fast_fields_reader = try!(BytesFastFieldsReader::open(bytes_fast_field_data));
+
+        let fieldnorms_data = try!(required_component_data(&segment, &components, SegmentComponent::FIELDNORMS));
         let fieldnorms_reader = try!(U32FastFieldsReader::open(fieldnorms_data));
         
-        let positions_data = segment
-            .open_read(SegmentComponent::POSITIONS)
-            .unwrap_or_else(|_| ReadOnlySource::empty());
+        let positions_data = component_data(&components, SegmentComponent::POSITIONS)
+            .unwrap_or_else(ReadOnlySource::empty);
+
+        // segments created before payloads were introduced
+        // do not have the component.
+        let payloads_data = component_data(&components, SegmentComponent::PAYLOADS)
+            .unwrap_or_else(ReadOnlySource::empty);
+
+        let field_stats: Vec<FieldStats> = try!(read_json_component(&segment, &components, SegmentComponent::FIELDSTATS))
+            .unwrap_or_else(Vec::new);
+        
+        let field_space_usage: Vec<FieldSpaceUsage> = try!(read_json_component(&segment, &components, SegmentComponent::SPACEUSAGE))
+            .unwrap_or_else(Vec::new);
+        // segments created before field presence was introduced
+        // do not have the component.
+        let field_presence: Vec<BitSet> = match component_data(&components, SegmentComponent::FIELDPRESENCE) {
+            Some(field_presence_source) => {
+                try!(Vec::deserialize(&mut field_presence_source.as_slice()))
+            }
+            None => Vec::new(),
+        };
+        let empty_field_presence = BitSet::with_max_doc(segment_info.max_doc);
+        
+        // the space usage of the components includes their footer.
+        let mut space_usage = SegmentSpaceUsage::default();
+        for component_data in &components {
+            let footer_len = if component_data.footer.is_some() { FOOTER_LEN } else { 0 };
+            let component_len = component_data.data.len() + footer_len;
+            space_usage.components.insert(String::from(component_data.component.path_suffix()), component_len);
+        }
         
         let schema = segment.schema();
+        for (field_entry, &field_usage) in schema.fields().iter().zip(field_space_usage.iter()) {
+            space_usage.fields.insert(field_entry.name().clone(), field_usage);
+        }
         Ok(SegmentReader {
             segment_info: segment_info,
             postings_data: postings_shared_mmap,
-            term_infos: term_infos,
+            term_infos: Arc::new(term_infos),
             segment_id: segment.id(),
             store_reader: store_reader,
             fast_fields_reader: fast_fields_reader,
+            bytes_fast_fields_reader: bytes_fast_fields_reader,
             fieldnorms_reader: fieldnorms_reader,
+            field_stats: field_stats,
+            space_usage: space_usage,
+            field_presence: field_presence,
+            empty_field_presence: empty_field_presence,
             positions_data: positions_data,
+            payloads_data: payloads_data,
+            components: components,
             schema: schema,
         })
     }
+
+    /// Checks the data of all of the components of the segment
+    /// against the checksum of their footer.
+    ///
+    /// All of the data of the segment is read, which may be slow
+    /// for a large segment. The segments written without footers
+    /// are not checked.
+    ///
+    /// # Errors
+    /// `Error::CorruptedFile` for the first component
+    /// whose data does not match its checksum.
+    pub fn validate_checksum(&self,) -> Result<()> {
+        validate_checksums(self.segment_id, &self.components)
+    }
     
+    /// Returns the schema of the segment.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
     /// Return the term dictionary datastructure.
-    pub fn term_infos(&self) -> &FstMap<TermInfo> {
+    pub fn term_infos(&self) -> &SegmentTermDictionary<TermInfo> {
         &self.term_infos
     }
+
+    /// Returns the implementation of the term dictionary of the segment.
+    pub fn term_dictionary_type(&self) -> TermDictionaryType {
+        self.term_infos.term_dictionary_type()
+    }
+
+    /// Returns the statistics of the terms of the segment.
+    ///
+    /// `None` for the segments written by the versions of
+    /// tantivy that did not record them.
+    pub fn term_dictionary_stats(&self) -> Option<TermDictionaryStats> {
+        self.segment_info.term_dictionary_stats
+    }
+
+    /// Returns the term dictionary, to look up, stream,
+    /// or search its terms.
+    pub fn term_dict(&self) -> TermDict {
+        TermDict::new(&self.term_infos)
+    }
        
     /// Returns the document (or to be accurate, its stored field)
     /// bearing the given doc id.
@@ -175,9 +799,17 @@ impl SegmentReader {
     /// For instance, requesting `SegmentPostingsOption::FreqAndPositions` for a `TextIndexingOptions`
     /// that does not index position will return a `SegmentPostings` with `DocId`s and frequencies.
     pub fn read_postings(&self, term: &Term, option: SegmentPostingsOption) -> Option<SegmentPostings> {
-        let field = term.field();
-        let field_entry = self.schema.get_field_entry(field);
         let term_info = get!(self.get_term_info(&term));
+        Some(self.read_postings_from_term_info(term.field(), &term_info, option))
+    }
+
+    /// Returns the segment postings associated with the term info of a term of `field`,
+    /// and with the given option, like `read_postings`.
+    ///
+    /// This saves the lookup of the term, when its term info was
+    /// already read from the term dictionary.
+    pub fn read_postings_from_term_info(&self, field: Field, term_info: &TermInfo, option: SegmentPostingsOption) -> SegmentPostings {
+        let field_entry = self.schema.get_field_entry(field);
         let offset = term_info.postings_offset as usize;
         let postings_data = &self.postings_data[offset..];
         let freq_handler = match *field_entry.field_type() {
@@ -185,7 +817,12 @@ impl SegmentReader {
                 let indexing_options = options.get_indexing_options();
                 match option {
                     SegmentPostingsOption::NoFreq => {
-                        FreqHandler::new_without_freq()
+                        if indexing_options.is_termfreq_enabled() {
+                            FreqHandler::new_skipping_freq()
+                        }
+                        else {
+                            FreqHandler::new_without_freq()
+                        }
                     }
                     SegmentPostingsOption::Freq => {
                         if indexing_options.is_termfreq_enabled() {
@@ -196,9 +833,17 @@ impl SegmentReader {
                         }
                     }
                     SegmentPostingsOption::FreqAndPositions => {
-                        if indexing_options == TextIndexingOptions::TokenizedWithFreqAndPosition {
-                            let offseted_position_data = &self.positions_data[term_info.positions_offset as usize ..];
-                            FreqHandler::new_with_freq_and_position(offseted_position_data)
+                        if indexing_options.is_position_enabled() {
+                            let offseted_position_data = self.term_positions_data(term_info);
+                            if indexing_options.is_payload_enabled() {
+                                FreqHandler::new_with_freq_block_positions_and_payloads(offseted_position_data, &self.payloads_data)
+                            }
+                            else if self.segment_info.postings_format_version.map_or(false, |version| version >= 2) {
+                                FreqHandler::new_with_freq_and_block_positions(offseted_position_data)
+                            }
+                            else {
+                                FreqHandler::new_with_freq_and_position(offseted_position_data)
+                            }
                         }
                         else if indexing_options.is_termfreq_enabled() 
                         {
@@ -214,9 +859,31 @@ impl SegmentReader {
                 FreqHandler::new_without_freq()
             }
         };
-        Some(SegmentPostings::from_data(term_info.doc_freq, postings_data, freq_handler))
+        match self.segment_info.postings_format_version {
+            Some(postings_format_version) => {
+                SegmentPostings::from_data_with_format(term_info.doc_freq, postings_data, freq_handler, postings_format_version)
+            }
+            None => {
+                SegmentPostings::from_data(term_info.doc_freq, postings_data, freq_handler)
+            }
+        }
     }
         
+    /// Returns the segment postings associated with the term, with
+    /// their term frequencies and positions,
+    /// or `None` if the term has never been encounterred and indexed.
+    ///
+    /// Unlike `read_postings`, this method fails if the field
+    /// of the term was not indexed with positions, as queries relying
+    /// on positions, like phrase queries, would silently return wrong results.
+    pub fn read_postings_with_positions(&self, term: &Term) -> Result<Option<SegmentPostings>> {
+        let field_entry = self.schema.get_field_entry(term.field());
+        if !field_entry.has_positions() {
+            return Err(Error::InvalidArgument(format!("positions not indexed for field {:?}", field_entry.name())));
+        }
+        Ok(self.read_postings(term, SegmentPostingsOption::FreqAndPositions))
+    }
+    
     /// Returns the posting list associated with a term.
     pub fn read_postings_all_info(&self, term: &Term) -> Option<SegmentPostings> {
         let field_entry = self.schema.get_field_entry(term.field());
@@ -224,19 +891,51 @@ impl SegmentReader {
             FieldType::Str(ref text_options) => {
                 match text_options.get_indexing_options() {
                     TextIndexingOptions::TokenizedWithFreq => SegmentPostingsOption::Freq,
-                    TextIndexingOptions::TokenizedWithFreqAndPosition => SegmentPostingsOption::FreqAndPositions,
+                    TextIndexingOptions::TokenizedWithFreqAndPosition
+                    | TextIndexingOptions::TokenizedWithFreqPositionAndPayload => SegmentPostingsOption::FreqAndPositions,
                     _ => SegmentPostingsOption::NoFreq,
                 }
             }
-            FieldType::U32(_) => SegmentPostingsOption::NoFreq
+            _ => SegmentPostingsOption::NoFreq
         };
         self.read_postings(term, segment_posting_option)
     }
     
+    /// Returns the bytes of the positions of a term, in the `.pos` file,
+    /// given its term info.
+    ///
+    /// The segments written before the version 5 of the postings format
+    /// do not record the length of the positions of their terms: the bytes
+    /// then run up to the end of the file, and the positions of the
+    /// term are only delimited by its postings.
+    pub fn term_positions_data(&self, term_info: &TermInfo) -> &[u8] {
+        match term_info.positions_range() {
+            Some(positions_range) => &self.positions_data[positions_range],
+            None => &self.positions_data[term_info.positions_offset as usize..],
+        }
+    }
+
     /// Returns the term info associated with the term.
     pub fn get_term_info(&self, term: &Term) -> Option<TermInfo> {
         self.term_infos.get(term.as_slice())
     }
+
+    /// Returns a stream over the terms of the field, along
+    /// with their `TermInfo`, in lexicographical order.
+    pub fn terms(&self, field: Field) -> TermStream {
+        self.terms_from(field, &[])
+    }
+
+    /// Returns a stream over the terms of the field whose value bytes
+    /// are greater or equal to `lower_bound`, in lexicographical order.
+    ///
+    /// Given the last term returned by a stream, this resumes the
+    /// scan from this term.
+    pub fn terms_from(&self, field: Field, lower_bound: &[u8]) -> TermStream {
+        let mut lower_bound_term = Term::allocate(field, 1 + lower_bound.len()).as_slice().to_vec();
+        lower_bound_term.extend_from_slice(lower_bound);
+        TermStream::new(field, self.term_infos.keys_from(&lower_bound_term))
+    }
 }
 
 
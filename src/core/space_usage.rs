@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+use core::SegmentComponent;
+
+
+/// Number of bytes taken by a field in the inverted index.
+///
+/// The bytes of the postings and of the positions are exact.
+/// The term dictionary is shared by all of the fields, and
+/// written lazily, so that its breakdown is only approximate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct FieldSpaceUsage {
+    /// Number of bytes in the term dictionary (`.term`)
+    pub terms: usize,
+    /// Number of bytes in the postings file (`.idx`)
+    pub postings: usize,
+    /// Number of bytes in the positions file (`.pos`)
+    pub positions: usize,
+}
+
+impl FieldSpaceUsage {
+    /// Returns the overall number of bytes taken by the field.
+    pub fn total(&self,) -> usize {
+        self.terms + self.postings + self.positions
+    }
+}
+
+impl AddAssign for FieldSpaceUsage {
+    fn add_assign(&mut self, other: FieldSpaceUsage) {
+        self.terms += other.terms;
+        self.postings += other.postings;
+        self.positions += other.positions;
+    }
+}
+
+
+/// Space taken on disk by a segment.
+///
+/// When obtained from a `Searcher`, it is the sum of
+/// the space usage of all of its segments.
+#[derive(Clone, Debug, Default, PartialEq, Eq, RustcEncodable)]
+pub struct SegmentSpaceUsage {
+    /// Number of bytes of each of the segment files,
+    /// keyed by the suffix of the file (e.g. `.idx`).
+    pub components: BTreeMap<String, usize>,
+    /// Number of bytes taken by each field in the inverted
+    /// index, keyed by field name.
+    ///
+    /// Segments created before the breakdown was introduced
+    /// do not report any field.
+    pub fields: BTreeMap<String, FieldSpaceUsage>,
+}
+
+impl SegmentSpaceUsage {
+    /// Returns the number of bytes of a given component.
+    pub fn component(&self, component: SegmentComponent) -> usize {
+        self.components
+            .get(component.path_suffix())
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of bytes taken by a field
+    /// in the inverted index.
+    pub fn field(&self, field_name: &str) -> FieldSpaceUsage {
+        self.fields
+            .get(field_name)
+            .cloned()
+            .unwrap_or(FieldSpaceUsage::default())
+    }
+
+    /// Returns the overall number of bytes of the segment files.
+    pub fn total(&self,) -> usize {
+        self.components
+            .values()
+            .fold(0, |acc, &num_bytes| acc + num_bytes)
+    }
+}
+
+impl AddAssign for SegmentSpaceUsage {
+    fn add_assign(&mut self, other: SegmentSpaceUsage) {
+        for (suffix, num_bytes) in other.components {
+            *self.components.entry(suffix).or_insert(0) += num_bytes;
+        }
+        for (field_name, field_space_usage) in other.fields {
+            *self.fields.entry(field_name).or_insert_with(FieldSpaceUsage::default) += field_space_usage;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use core::Index;
+    use core::SegmentComponent;
+    use schema::{SchemaBuilder, Document, TEXT, STRING};
+    use rustc_serialize::json;
+    use directory::FOOTER_LEN;
+
+    #[test]
+    fn test_space_usage() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..200 {
+                let mut doc = Document::default();
+                doc.add_text(text_field, &format!("a b c d{}", i));
+                doc.add_text(tag_field, if i % 2 == 0 { "even" } else { "odd" });
+                index_writer.add_document(doc).unwrap();
+                if i == 99 {
+                    // spread the documents over two segments
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        for segment_reader in searcher.segment_readers() {
+            let space_usage = segment_reader.space_usage();
+            let text_usage = space_usage.field("text");
+            let tag_usage = space_usage.field("tag");
+            assert!(text_usage.postings > tag_usage.postings);
+            assert!(text_usage.positions > 0);
+            assert!(text_usage.terms > 0);
+            assert_eq!(tag_usage.positions, 0);
+            // the components end with a footer, which does not belong to any field.
+            assert_eq!(text_usage.postings + tag_usage.postings, space_usage.component(SegmentComponent::POSTINGS) - FOOTER_LEN);
+            assert_eq!(text_usage.positions, space_usage.component(SegmentComponent::POSITIONS) - FOOTER_LEN);
+            assert!(text_usage.terms + tag_usage.terms <= space_usage.component(SegmentComponent::TERMS));
+        }
+        let space_usage = searcher.space_usage();
+        let segments_total = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.space_usage().total())
+            .fold(0, |acc, num_bytes| acc + num_bytes);
+        assert_eq!(space_usage.total(), segments_total);
+        assert_eq!(
+            space_usage.field("text").postings + space_usage.field("tag").postings,
+            space_usage.component(SegmentComponent::POSTINGS) - 2 * FOOTER_LEN);
+        let space_usage_json = json::encode(&space_usage).unwrap();
+        assert!(space_usage_json.contains(r#""fields":{"tag":{"terms":"#));
+    }
+}
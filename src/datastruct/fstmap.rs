@@ -1,14 +1,16 @@
-#![allow(should_implement_trait)]
-
 use std::io;
 use std::io::Write;
 use fst;
 use fst::raw::Fst;
 use fst::Streamer;
+use fst::IntoStreamer;
+use fst::Automaton;
+use fst::automaton::AlwaysMatch;
 
 use directory::ReadOnlySource;
 use common::BinarySerializable;
 use std::marker::PhantomData;
+use super::termdict::{TermDictionaryBuilder, TermDictionary, TermDictionaryKeys, TermDictionaryRange};
 
 fn convert_fst_error(e: fst::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, e)
@@ -30,8 +32,11 @@ impl<W: Write, V: BinarySerializable> FstMapBuilder<W, V> {
             _phantom_: PhantomData,
         })
     }
+}
+
+impl<W: Write, V: BinarySerializable> TermDictionaryBuilder<W, V> for FstMapBuilder<W, V> {
 
-    pub fn insert(&mut self, key: &[u8], value: &V) -> io::Result<()>{
+    fn insert(&mut self, key: &[u8], value: &V) -> io::Result<()>{
         try!(self.fst_builder
             .insert(key, self.data.len() as u64)
             .map_err(convert_fst_error));
@@ -39,7 +44,14 @@ impl<W: Write, V: BinarySerializable> FstMapBuilder<W, V> {
         Ok(())
     }
 
-    pub fn finish(self,) -> io::Result<W> {
+    // the values that will be appended when the map is finished
+    // are included. The fst nodes are only written once they cannot
+    // change anymore, so that the most recent keys are not accounted for yet.
+    fn num_bytes(&self,) -> usize {
+        self.fst_builder.bytes_written() as usize + self.data.len()
+    }
+
+    fn finish(self,) -> io::Result<W> {
         let mut file = try!(
             self.fst_builder
                  .into_inner()
@@ -52,9 +64,17 @@ impl<W: Write, V: BinarySerializable> FstMapBuilder<W, V> {
     }
 }
 
+/// Sorted map stored as a fst, whose outputs are the offsets
+/// of the values, serialized after the fst.
+///
+/// The ordinals of the keys rely on the values having
+/// a fixed serialized size, as the `TermInfo` do.
 pub struct FstMap<V: BinarySerializable> {
     fst_index: fst::Map,
     values_mmap: ReadOnlySource,
+    // serialized size of the values, or 0 if the map is empty.
+    value_size: usize,
+    deserialize_value: fn(&mut io::Read) -> io::Result<V>,
     _phantom_: PhantomData<V>,
 }
 
@@ -66,30 +86,77 @@ fn open_fst_index(source: ReadOnlySource) -> io::Result<fst::Map> {
     }))
 }
 
-pub struct FstKeyIter<'a, V: 'static + BinarySerializable> {
-    streamer: fst::map::Stream<'a>,
-    __phantom__: PhantomData<V>
+pub struct FstKeyIter<'a, V: 'static + BinarySerializable, A: Automaton = AlwaysMatch> {
+    streamer: fst::map::Stream<'a, A>,
+    fstmap: &'a FstMap<V>,
 }
 
-impl<'a, V: 'static + BinarySerializable> FstKeyIter<'a, V> {
-    pub fn next(&mut self) -> Option<(&[u8])> {
+impl<'a, V: 'static + BinarySerializable, A: Automaton> TermDictionaryKeys<V> for FstKeyIter<'a, V, A> {
+
+    fn next(&mut self) -> Option<&[u8]> {
         self.streamer
             .next()
             .map(|(k, _)| k)
     }
+
+    fn next_with_value(&mut self) -> Option<(&[u8], V)> {
+        let fstmap = self.fstmap;
+        self.streamer
+            .next()
+            .map(|(k, offset)| (k, fstmap.read_value(offset)))
+    }
 }
 
+/// Range of the keys of a `FstMap`, built by
+/// restricting its bounds one at a time.
+pub struct FstRange<'a, V: 'static + BinarySerializable> {
+    stream_builder: fst::map::StreamBuilder<'a>,
+    fstmap: &'a FstMap<V>,
+}
 
-impl<V: BinarySerializable> FstMap<V> {
+impl<'a, V: 'static + BinarySerializable> TermDictionaryRange<V> for FstRange<'a, V> {
 
-    pub fn keys(&self,) -> FstKeyIter<V> {
+    type Keys = FstKeyIter<'a, V>;
+
+    fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> FstRange<'a, V> {
+        self.stream_builder = self.stream_builder.ge(bound);
+        self
+    }
+
+    fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> FstRange<'a, V> {
+        self.stream_builder = self.stream_builder.gt(bound);
+        self
+    }
+
+    fn le<T: AsRef<[u8]>>(mut self, bound: T) -> FstRange<'a, V> {
+        self.stream_builder = self.stream_builder.le(bound);
+        self
+    }
+
+    fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> FstRange<'a, V> {
+        self.stream_builder = self.stream_builder.lt(bound);
+        self
+    }
+
+    fn into_stream(self,) -> FstKeyIter<'a, V> {
         FstKeyIter {
-            streamer: self.fst_index.stream(),
-            __phantom__: PhantomData,
+            streamer: self.stream_builder.into_stream(),
+            fstmap: self.fstmap,
         }
     }
+}
+
+
+impl<V: BinarySerializable> FstMap<V> {
 
     pub fn from_source(source: ReadOnlySource)  -> io::Result<FstMap<V>> {
+        FstMap::from_source_with_deserializer(source, V::deserialize)
+    }
+
+    /// Opens a map whose values are read with `deserialize_value`
+    /// rather than `V::deserialize`, typically to read the values
+    /// written in a previous format.
+    pub fn from_source_with_deserializer(source: ReadOnlySource, deserialize_value: fn(&mut io::Read) -> io::Result<V>) -> io::Result<FstMap<V>> {
         let total_len = source.len();
         let length_offset = total_len - 4;
         let mut split_len_buffer: &[u8] = &source.as_slice()[length_offset..];
@@ -98,9 +165,12 @@ impl<V: BinarySerializable> FstMap<V> {
         let fst_source = source.slice(0, split_len);
         let values_source = source.slice(split_len, length_offset);
         let fst_index = try!(open_fst_index(fst_source));
+        let value_size = if fst_index.is_empty() { 0 } else { values_source.len() / fst_index.len() };
         Ok(FstMap {
             fst_index: fst_index,
             values_mmap: values_source,
+            value_size: value_size,
+            deserialize_value: deserialize_value,
             _phantom_: PhantomData,
         })
     }
@@ -108,14 +178,82 @@ impl<V: BinarySerializable> FstMap<V> {
     fn read_value(&self, offset: u64) -> V {
         let buffer = self.values_mmap.as_slice();
         let mut cursor = &buffer[(offset as usize)..];
-        V::deserialize(&mut cursor).expect("Data in FST is corrupted")
+        (self.deserialize_value)(&mut cursor).expect("Data in FST is corrupted")
     }
 
-    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<V> {
+    // walks down the fst to the key whose output is `offset`.
+    //
+    // The offsets increase with the keys, so that the output of a
+    // transition is the smallest offset of the keys it leads to,
+    // and the key is reached through the last transition whose
+    // output does not exceed `offset`.
+    fn offset_to_key(&self, offset: u64, bytes: &mut Vec<u8>) -> bool {
+        let fst = self.fst_index.as_fst();
+        let mut node = fst.root();
+        let mut node_output = 0u64;
+        let mut key = Vec::new();
+        loop {
+            if node.is_final() && node_output + node.final_output().value() == offset {
+                *bytes = key;
+                return true;
+            }
+            let next_transition = node.transitions()
+                .take_while(|transition| node_output + transition.out.value() <= offset)
+                .last();
+            match next_transition {
+                Some(transition) => {
+                    node_output += transition.out.value();
+                    key.push(transition.inp);
+                    node = fst.node(transition.addr);
+                }
+                None => {
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+impl<'a, V: 'static + BinarySerializable> TermDictionary<'a, V> for FstMap<V> {
+
+    type Keys = FstKeyIter<'a, V>;
+
+    type Range = FstRange<'a, V>;
+
+    fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<V> {
         self.fst_index
             .get(key)
             .map(|offset| self.read_value(offset))
     }
+
+    fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> Option<u64> {
+        self.fst_index
+            .get(key)
+            .map(|offset| offset / self.value_size as u64)
+    }
+
+    fn ord_to_term(&self, ord: u64, bytes: &mut Vec<u8>) -> bool {
+        if ord >= self.fst_index.len() as u64 {
+            return false;
+        }
+        self.offset_to_key(ord * self.value_size as u64, bytes)
+    }
+
+    fn range(&'a self) -> FstRange<'a, V> {
+        FstRange {
+            stream_builder: self.fst_index.range(),
+            fstmap: self,
+        }
+    }
+
+    // only the prefixes of the keys that the automaton
+    // can still match are visited.
+    fn search<A: Automaton + 'a>(&'a self, automaton: A) -> Box<TermDictionaryKeys<V> + 'a> {
+        Box::new(FstKeyIter {
+            streamer: self.fst_index.search(automaton).into_stream(),
+            fstmap: self,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -143,7 +281,48 @@ mod tests {
         assert_eq!(keys.next().unwrap(), "abc".as_bytes());
         assert_eq!(keys.next().unwrap(), "abcd".as_bytes());
         assert_eq!(keys.next(), None);
- 
+        let mut keys = fstmap.keys();
+        assert_eq!(keys.next_with_value().unwrap(), ("abc".as_bytes(), 34u32));
+        assert_eq!(keys.next_with_value().unwrap(), ("abcd".as_bytes(), 346u32));
+        assert_eq!(keys.next_with_value(), None);
+    }
+
+    #[test]
+    fn test_fstmap_keys_from() {
+        let mut directory = RAMDirectory::create();
+        let path = PathBuf::from("fstmap");
+        {
+            let write = directory.open_write(&path).unwrap();
+            let mut fstmap_builder = FstMapBuilder::new(write).unwrap();
+            fstmap_builder.insert("abc".as_bytes(), &34u32).unwrap();
+            fstmap_builder.insert("abcd".as_bytes(), &346u32).unwrap();
+            fstmap_builder.insert("b".as_bytes(), &1u32).unwrap();
+            fstmap_builder.finish().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        let fstmap: FstMap<u32> = FstMap::from_source(source).unwrap();
+        {
+            let mut keys = fstmap.keys_from("abca".as_bytes());
+            assert_eq!(keys.next().unwrap(), "abcd".as_bytes());
+            assert_eq!(keys.next().unwrap(), "b".as_bytes());
+            assert_eq!(keys.next(), None);
+        }
+        {
+            let mut keys = fstmap.keys_from("c".as_bytes());
+            assert_eq!(keys.next(), None);
+        }
+        {
+            let mut keys = fstmap.range().gt("abc").lt("b").into_stream();
+            assert_eq!(keys.next_with_value().unwrap(), ("abcd".as_bytes(), 346u32));
+            assert_eq!(keys.next(), None);
+        }
+        {
+            let mut keys = fstmap.range().ge("abc").le("b").into_stream();
+            assert_eq!(keys.next().unwrap(), "abc".as_bytes());
+            assert_eq!(keys.next().unwrap(), "abcd".as_bytes());
+            assert_eq!(keys.next().unwrap(), "b".as_bytes());
+            assert_eq!(keys.next(), None);
+        }
     }
 
 }
@@ -1,8 +1,12 @@
 mod fstmap;
+mod sstable;
+mod termdict;
+mod set_automaton;
 mod skip;
 pub mod stacker;
 
-pub use self::fstmap::FstMapBuilder;
-pub use self::fstmap::FstMap;
-pub use self::fstmap::FstKeyIter;
+pub use self::termdict::{TermDictionaryType, TermDictionaryStats};
+pub use self::termdict::{TermDictionaryBuilder, TermDictionary, TermDictionaryKeys, TermDictionaryRange};
+pub use self::termdict::{SegmentTermDictionaryBuilder, SegmentTermDictionary, SegmentTermKeys, SegmentTermRange};
+pub use self::set_automaton::SetAutomaton;
 pub use self::skip::{SkipListBuilder, SkipList};
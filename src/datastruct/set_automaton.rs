@@ -0,0 +1,47 @@
+use fst::{Automaton, Set};
+use fst::raw::CompiledAddr;
+
+
+/// Automaton accepting exactly the keys of a `Set`.
+///
+/// Searching a term dictionary with it looks up all of
+/// the keys of the set in a single pass.
+pub struct SetAutomaton<'a> {
+    set: &'a Set,
+}
+
+impl<'a> SetAutomaton<'a> {
+
+    /// Creates an automaton accepting the keys of `set`.
+    pub fn new(set: &'a Set) -> SetAutomaton<'a> {
+        SetAutomaton {
+            set: set,
+        }
+    }
+}
+
+impl<'a> Automaton for SetAutomaton<'a> {
+
+    type State = Option<CompiledAddr>;
+
+    fn start(&self) -> Option<CompiledAddr> {
+        Some(self.set.as_fst().root().addr())
+    }
+
+    fn is_match(&self, state: &Option<CompiledAddr>) -> bool {
+        state
+            .map(|addr| self.set.as_fst().node(addr).is_final())
+            .unwrap_or(false)
+    }
+
+    fn can_match(&self, state: &Option<CompiledAddr>) -> bool {
+        state.is_some()
+    }
+
+    fn accept(&self, state: &Option<CompiledAddr>, byte: u8) -> Option<CompiledAddr> {
+        state.and_then(|addr| {
+            let node = self.set.as_fst().node(addr);
+            node.find_input(byte).map(|i| node.transition_addr(i))
+        })
+    }
+}
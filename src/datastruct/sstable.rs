@@ -0,0 +1,627 @@
+#![allow(should_implement_trait)]
+
+use std::io;
+use std::io::Write;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use fst::Automaton;
+use fst::automaton::AlwaysMatch;
+
+use directory::ReadOnlySource;
+use common::BinarySerializable;
+use super::termdict::{TermDictionaryBuilder, TermDictionary, TermDictionaryKeys, TermDictionaryRange};
+
+/// Number of keys in each block of a `SSTableMap`.
+///
+/// A lookup decodes up to this number of keys,
+/// after a binary search in the index of the blocks.
+const NUM_KEYS_PER_BLOCK: usize = 32;
+
+
+// first key of the block starting at `block_offset`,
+// which is not prefix compressed.
+fn block_key(data: &[u8], block_offset: usize) -> &[u8] {
+    let (_, offset) = read_vint(data, block_offset);
+    let (key_len, offset) = read_vint(data, offset);
+    let (_, offset) = read_vint(data, offset);
+    &data[offset..offset + key_len]
+}
+
+// appends a `VInt` to `buffer`, without going through `io::Write`.
+fn write_vint(buffer: &mut Vec<u8>, mut val: usize) {
+    while val >= 128 {
+        buffer.push((val & 127) as u8);
+        val >>= 7;
+    }
+    buffer.push(val as u8 | 128u8);
+}
+
+// reads a `VInt` at `offset`, without going through `io::Read`,
+// returning its value along with the offset following it.
+fn read_vint(data: &[u8], mut offset: usize) -> (usize, usize) {
+    let mut val = 0usize;
+    let mut shift = 0;
+    loop {
+        let b = data[offset];
+        offset += 1;
+        val |= ((b & 127u8) as usize) << shift;
+        if b & 128u8 != 0u8 {
+            return (val, offset);
+        }
+        shift += 7;
+    }
+}
+
+
+fn common_prefix_len(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right.iter())
+        .take_while(|&(left_byte, right_byte)| left_byte == right_byte)
+        .count()
+}
+
+/// Builds a `SSTableMap`.
+///
+/// The keys must be inserted in strictly increasing
+/// lexicographical order.
+pub struct SSTableMapBuilder<W: Write, V: BinarySerializable> {
+    write: W,
+    block: Vec<u8>,
+    block_num_keys: usize,
+    written_bytes: usize,
+    block_offsets: Vec<u64>,
+    last_key: Vec<u8>,
+    value_buffer: Vec<u8>,
+    num_keys: u64,
+    _phantom_: PhantomData<V>,
+}
+
+impl<W: Write, V: BinarySerializable> SSTableMapBuilder<W, V> {
+
+    /// Creates a builder writing the map to `w`.
+    pub fn new(w: W) -> io::Result<SSTableMapBuilder<W, V>> {
+        Ok(SSTableMapBuilder {
+            write: w,
+            block: Vec::new(),
+            block_num_keys: 0,
+            written_bytes: 0,
+            block_offsets: Vec::new(),
+            last_key: Vec::new(),
+            value_buffer: Vec::new(),
+            num_keys: 0,
+            _phantom_: PhantomData,
+        })
+    }
+
+    fn flush_block(&mut self) -> io::Result<()> {
+        try!(self.write.write_all(&self.block));
+        self.written_bytes += self.block.len();
+        self.block.clear();
+        self.block_num_keys = 0;
+        Ok(())
+    }
+}
+
+impl<W: Write, V: BinarySerializable> TermDictionaryBuilder<W, V> for SSTableMapBuilder<W, V> {
+
+    // Errors if the key is not greater than the previous key.
+    fn insert(&mut self, key: &[u8], value: &V) -> io::Result<()> {
+        if self.num_keys > 0 && key <= &self.last_key[..] {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "keys must be inserted in strictly increasing order"));
+        }
+        if self.block_num_keys == NUM_KEYS_PER_BLOCK {
+            try!(self.flush_block());
+        }
+        // the first key of a block is not prefix compressed,
+        // so that each block can be decoded on its own.
+        let prefix_len = if self.block_num_keys == 0 {
+            self.block_offsets.push(self.written_bytes as u64);
+            0
+        }
+        else {
+            common_prefix_len(&self.last_key, key)
+        };
+        self.value_buffer.clear();
+        try!(value.serialize(&mut self.value_buffer));
+        write_vint(&mut self.block, prefix_len);
+        write_vint(&mut self.block, key.len() - prefix_len);
+        // the length of the value lets a lookup skip
+        // the values of the keys it does not match.
+        write_vint(&mut self.block, self.value_buffer.len());
+        self.block.extend_from_slice(&key[prefix_len..]);
+        self.block.extend_from_slice(&self.value_buffer);
+        self.block_num_keys += 1;
+        self.last_key.truncate(prefix_len);
+        self.last_key.extend_from_slice(&key[prefix_len..]);
+        self.num_keys += 1;
+        Ok(())
+    }
+
+    // the current block is included, but not the index of the blocks.
+    fn num_bytes(&self,) -> usize {
+        self.written_bytes + self.block.len()
+    }
+
+    // the last block is followed by the index of the blocks.
+    fn finish(mut self,) -> io::Result<W> {
+        try!(self.flush_block());
+        let mut footer: Vec<u8> = Vec::new();
+        try!(self.block_offsets.serialize(&mut footer));
+        try!(self.num_keys.serialize(&mut footer));
+        try!(self.write.write_all(&footer));
+        try!((footer.len() as u32).serialize(&mut self.write));
+        try!(self.write.flush());
+        Ok(self.write)
+    }
+}
+
+
+/// Sorted map stored as a sequence of blocks of keys, along
+/// with their values, as an alternative to the `FstMap`.
+///
+/// Within a block, each key only records the suffix it does not
+/// share with the previous key, but the first key, which is kept
+/// whole. The first key of each block is copied in memory when the map
+/// is opened, so that a lookup only decodes one single block, after
+/// a binary search over these keys.
+///
+/// It is much cheaper to build than a fst, when the keys share
+/// few suffixes, but lookups are slower, and searching for the keys
+/// accepted by an automaton requires to decode all of the keys.
+pub struct SSTableMap<V: BinarySerializable> {
+    data: ReadOnlySource,
+    block_offsets: Vec<usize>,
+    // the first key of the blocks, concatenated, and the offset
+    // of each of them, for the binary search to stay in cache.
+    block_keys: Vec<u8>,
+    block_key_offsets: Vec<usize>,
+    num_keys: u64,
+    deserialize_value: fn(&mut io::Read) -> io::Result<V>,
+}
+
+impl<V: BinarySerializable> SSTableMap<V> {
+
+    /// Opens a map written by a `SSTableMapBuilder`.
+    pub fn from_source(source: ReadOnlySource) -> io::Result<SSTableMap<V>> {
+        SSTableMap::from_source_with_deserializer(source, V::deserialize)
+    }
+
+    /// Opens a map whose values are read with `deserialize_value`
+    /// rather than `V::deserialize`.
+    pub fn from_source_with_deserializer(source: ReadOnlySource, deserialize_value: fn(&mut io::Read) -> io::Result<V>) -> io::Result<SSTableMap<V>> {
+        let total_len = source.len();
+        if total_len < 4 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "sstable is too short"));
+        }
+        let length_offset = total_len - 4;
+        let mut footer_len_buffer: &[u8] = &source.as_slice()[length_offset..];
+        let footer_len = try!(u32::deserialize(&mut footer_len_buffer)) as usize;
+        if footer_len > length_offset {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid sstable footer length"));
+        }
+        let data_len = length_offset - footer_len;
+        let (block_offsets, num_keys): (Vec<usize>, u64) = {
+            let mut footer: &[u8] = &source.as_slice()[data_len..length_offset];
+            let block_offsets: Vec<u64> = try!(Vec::deserialize(&mut footer));
+            let num_keys = try!(u64::deserialize(&mut footer));
+            (block_offsets.into_iter().map(|offset| offset as usize).collect(), num_keys)
+        };
+        let data = source.slice(0, data_len);
+        let mut block_keys: Vec<u8> = Vec::new();
+        let mut block_key_offsets: Vec<usize> = Vec::with_capacity(block_offsets.len() + 1);
+        for &block_offset in &block_offsets {
+            if block_offset >= data_len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid sstable block offset"));
+            }
+            block_key_offsets.push(block_keys.len());
+            block_keys.extend_from_slice(block_key(data.as_slice(), block_offset));
+        }
+        block_key_offsets.push(block_keys.len());
+        Ok(SSTableMap {
+            data: data,
+            block_offsets: block_offsets,
+            block_keys: block_keys,
+            block_key_offsets: block_key_offsets,
+            num_keys: num_keys,
+            deserialize_value: deserialize_value,
+        })
+    }
+
+    /// Returns the number of keys of the map.
+    pub fn num_keys(&self,) -> u64 {
+        self.num_keys
+    }
+
+    /// Returns the number of blocks of the map.
+    pub fn num_blocks(&self,) -> usize {
+        self.block_offsets.len()
+    }
+
+    fn block_key_at(&self, block_ord: usize) -> &[u8] {
+        &self.block_keys[self.block_key_offsets[block_ord]..self.block_key_offsets[block_ord + 1]]
+    }
+
+    // ordinal of the last block whose first key is lower
+    // or equal to `key`, or of the first block.
+    fn block_ord(&self, key: &[u8]) -> usize {
+        let mut low = 0;
+        let mut high = self.block_offsets.len();
+        while low < high {
+            let middle = (low + high) / 2;
+            if self.block_key_at(middle) <= key {
+                low = middle + 1;
+            }
+            else {
+                high = middle;
+            }
+        }
+        if low == 0 { 0 } else { low - 1 }
+    }
+
+    fn stream_from_block<A: Automaton>(&self, block_ord: usize, lower_bound: Bound, automaton: A) -> SSTableKeyIter<V, A> {
+        let offset = self.block_offsets.get(block_ord).cloned().unwrap_or(self.data.len());
+        let start_state = automaton.start();
+        SSTableKeyIter {
+            sstable: self,
+            offset: offset,
+            key: Vec::new(),
+            lower_bound: lower_bound,
+            upper_bound: Bound::Unbounded,
+            automaton: automaton,
+            states: vec!(start_state),
+        }
+    }
+}
+
+impl<'a, V: 'static + BinarySerializable> TermDictionary<'a, V> for SSTableMap<V> {
+
+    type Keys = SSTableKeyIter<'a, V>;
+
+    type Range = SSTableRange<'a, V>;
+
+    fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<V> {
+        let key = key.as_ref();
+        let mut keys = self.stream_from_block(self.block_ord(key), Bound::Unbounded, AlwaysMatch);
+        while let Some((_, value_data)) = keys.advance() {
+            match keys.key[..].cmp(key) {
+                Ordering::Less => {}
+                Ordering::Equal => {
+                    return Some(keys.read_value(value_data));
+                }
+                Ordering::Greater => {
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    // the blocks but the last one are full, so that the ordinal
+    // of a key follows from the ordinal of its block.
+    fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> Option<u64> {
+        let key = key.as_ref();
+        let block_ord = self.block_ord(key);
+        let mut keys = self.stream_from_block(block_ord, Bound::Unbounded, AlwaysMatch);
+        let mut ord = (block_ord * NUM_KEYS_PER_BLOCK) as u64;
+        while keys.advance().is_some() {
+            match keys.key[..].cmp(key) {
+                Ordering::Less => {}
+                Ordering::Equal => {
+                    return Some(ord);
+                }
+                Ordering::Greater => {
+                    return None;
+                }
+            }
+            ord += 1;
+        }
+        None
+    }
+
+    fn ord_to_term(&self, ord: u64, bytes: &mut Vec<u8>) -> bool {
+        if ord >= self.num_keys {
+            return false;
+        }
+        let block_ord = (ord / NUM_KEYS_PER_BLOCK as u64) as usize;
+        let mut keys = self.stream_from_block(block_ord, Bound::Unbounded, AlwaysMatch);
+        for _ in 0..(ord % NUM_KEYS_PER_BLOCK as u64) + 1 {
+            keys.advance();
+        }
+        bytes.clear();
+        bytes.extend_from_slice(&keys.key);
+        true
+    }
+
+    fn range(&'a self) -> SSTableRange<'a, V> {
+        SSTableRange {
+            sstable: self,
+            lower_bound: Bound::Unbounded,
+            upper_bound: Bound::Unbounded,
+        }
+    }
+
+    // all of the keys are decoded, and run through the automaton
+    // from the first byte they do not share with the previous key.
+    fn search<A: Automaton + 'a>(&'a self, automaton: A) -> Box<TermDictionaryKeys<V> + 'a> {
+        Box::new(self.stream_from_block(0, Bound::Unbounded, automaton))
+    }
+}
+
+
+/// Bound of a `SSTableRange`.
+#[derive(Clone, Debug)]
+enum Bound {
+    Included(Vec<u8>),
+    Excluded(Vec<u8>),
+    Unbounded,
+}
+
+impl Bound {
+    fn is_above(&self, key: &[u8]) -> bool {
+        match *self {
+            Bound::Included(ref bound) => key <= &bound[..],
+            Bound::Excluded(ref bound) => key < &bound[..],
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn is_below(&self, key: &[u8]) -> bool {
+        match *self {
+            Bound::Included(ref bound) => key >= &bound[..],
+            Bound::Excluded(ref bound) => key > &bound[..],
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+
+/// Range of the keys of a `SSTableMap`, built by
+/// restricting its bounds one at a time.
+pub struct SSTableRange<'a, V: BinarySerializable> {
+    sstable: &'a SSTableMap<V>,
+    lower_bound: Bound,
+    upper_bound: Bound,
+}
+
+impl<'a, V: 'static + BinarySerializable> TermDictionaryRange<V> for SSTableRange<'a, V> {
+
+    type Keys = SSTableKeyIter<'a, V>;
+
+    fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> SSTableRange<'a, V> {
+        self.lower_bound = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> SSTableRange<'a, V> {
+        self.lower_bound = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    fn le<T: AsRef<[u8]>>(mut self, bound: T) -> SSTableRange<'a, V> {
+        self.upper_bound = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> SSTableRange<'a, V> {
+        self.upper_bound = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    fn into_stream(self,) -> SSTableKeyIter<'a, V> {
+        let block_ord = match self.lower_bound {
+            Bound::Included(ref bound) | Bound::Excluded(ref bound) => self.sstable.block_ord(bound),
+            Bound::Unbounded => 0,
+        };
+        let mut keys = self.sstable.stream_from_block(block_ord, self.lower_bound, AlwaysMatch);
+        keys.upper_bound = self.upper_bound;
+        keys
+    }
+}
+
+
+/// Iterator over the keys of a `SSTableMap`,
+/// along with their values.
+pub struct SSTableKeyIter<'a, V: BinarySerializable, A: Automaton = AlwaysMatch> {
+    sstable: &'a SSTableMap<V>,
+    offset: usize,
+    key: Vec<u8>,
+    lower_bound: Bound,
+    upper_bound: Bound,
+    automaton: A,
+    // states of the automaton after each of the bytes of
+    // the current key, starting with its start state.
+    states: Vec<A::State>,
+}
+
+impl<'a, V: BinarySerializable, A: Automaton> SSTableKeyIter<'a, V, A> {
+
+    // decodes the next key in the key buffer, returning the number
+    // of bytes it shares with the previous key, along with the bytes
+    // of its value. The first key of a block shares none.
+    fn advance(&mut self) -> Option<(usize, &'a [u8])> {
+        let data: &'a [u8] = self.sstable.data.as_slice();
+        if self.offset >= data.len() {
+            return None;
+        }
+        let (prefix_len, offset) = read_vint(data, self.offset);
+        let (suffix_len, offset) = read_vint(data, offset);
+        let (value_len, offset) = read_vint(data, offset);
+        let value_offset = offset + suffix_len;
+        self.key.truncate(prefix_len);
+        self.key.extend_from_slice(&data[offset..value_offset]);
+        self.offset = value_offset + value_len;
+        Some((prefix_len, &data[value_offset..self.offset]))
+    }
+
+    fn read_value(&self, mut value_data: &[u8]) -> V {
+        (self.sstable.deserialize_value)(&mut value_data).expect("Data in SSTable is corrupted")
+    }
+
+    // runs the automaton over the bytes of the key
+    // that are not shared with the previous key.
+    fn is_match(&mut self, prefix_len: usize) -> bool {
+        self.states.truncate(prefix_len + 1);
+        while self.states.len() <= self.key.len() {
+            let state = {
+                let last_state = &self.states[self.states.len() - 1];
+                if !self.automaton.can_match(last_state) {
+                    return false;
+                }
+                self.automaton.accept(last_state, self.key[self.states.len() - 1])
+            };
+            self.states.push(state);
+        }
+        self.automaton.is_match(&self.states[self.key.len()])
+    }
+
+}
+
+impl<'a, V: 'static + BinarySerializable, A: Automaton> TermDictionaryKeys<V> for SSTableKeyIter<'a, V, A> {
+
+    fn next_with_value(&mut self) -> Option<(&[u8], V)> {
+        loop {
+            let (prefix_len, value_data) = get!(self.advance());
+            if !self.upper_bound.is_above(&self.key) {
+                // stops the iteration for good.
+                self.offset = self.sstable.data.len();
+                return None;
+            }
+            if !self.lower_bound.is_below(&self.key) {
+                continue;
+            }
+            self.lower_bound = Bound::Unbounded;
+            if self.is_match(prefix_len) {
+                let value = self.read_value(value_data);
+                return Some((&self.key[..], value));
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use directory::{RAMDirectory, Directory};
+    use std::path::PathBuf;
+    use fst::Automaton;
+
+    fn build_sstable(keys: &[(&str, u32)]) -> SSTableMap<u32> {
+        let mut directory = RAMDirectory::create();
+        let path = PathBuf::from("sstable");
+        {
+            let write = directory.open_write(&path).unwrap();
+            let mut sstable_builder = SSTableMapBuilder::new(write).unwrap();
+            for &(key, value) in keys {
+                sstable_builder.insert(key.as_bytes(), &value).unwrap();
+            }
+            sstable_builder.finish().unwrap();
+        }
+        SSTableMap::from_source(directory.open_read(&path).unwrap()).unwrap()
+    }
+
+    fn collect_keys<K: TermDictionaryKeys<u32> + ?Sized>(keys: &mut K) -> Vec<(String, u32)> {
+        let mut key_values = Vec::new();
+        while let Some((key, value)) = keys.next_with_value() {
+            key_values.push((String::from_utf8(key.to_vec()).unwrap(), value));
+        }
+        key_values
+    }
+
+    #[test]
+    fn test_sstable() {
+        let sstable = build_sstable(&[("abc", 34u32), ("abcd", 346u32), ("b", 1u32)]);
+        assert_eq!(sstable.num_keys(), 3);
+        assert_eq!(sstable.get("abc"), Some(34u32));
+        assert_eq!(sstable.get("abcd"), Some(346u32));
+        assert_eq!(sstable.get("b"), Some(1u32));
+        assert_eq!(sstable.get("ab"), None);
+        assert_eq!(sstable.get("abce"), None);
+        assert_eq!(sstable.get("c"), None);
+        let mut keys = sstable.keys();
+        assert_eq!(keys.next().unwrap(), "abc".as_bytes());
+        assert_eq!(keys.next().unwrap(), "abcd".as_bytes());
+        assert_eq!(keys.next().unwrap(), "b".as_bytes());
+        assert_eq!(keys.next(), None);
+        assert_eq!(collect_keys(&mut sstable.keys_from("abca".as_bytes())), vec!((String::from("abcd"), 346u32), (String::from("b"), 1u32)));
+        assert!(collect_keys(&mut sstable.keys_from("c".as_bytes())).is_empty());
+        assert_eq!(collect_keys(&mut sstable.range().gt("abc").lt("b").into_stream()), vec!((String::from("abcd"), 346u32)));
+        assert_eq!(collect_keys(&mut sstable.range().ge("abc").le("b").into_stream()).len(), 3);
+    }
+
+    #[test]
+    fn test_sstable_empty() {
+        let sstable = build_sstable(&[]);
+        assert_eq!(sstable.num_keys(), 0);
+        assert_eq!(sstable.num_blocks(), 0);
+        assert_eq!(sstable.get(""), None);
+        assert!(collect_keys(&mut sstable.keys()).is_empty());
+        assert!(collect_keys(&mut sstable.keys_from("a".as_bytes())).is_empty());
+    }
+
+    #[test]
+    fn test_sstable_out_of_order() {
+        let mut sstable_builder: SSTableMapBuilder<Vec<u8>, u32> = SSTableMapBuilder::new(Vec::new()).unwrap();
+        sstable_builder.insert(b"", &1u32).unwrap();
+        sstable_builder.insert(b"b", &2u32).unwrap();
+        assert!(sstable_builder.insert(b"b", &3u32).is_err());
+        assert!(sstable_builder.insert(b"a", &3u32).is_err());
+    }
+
+    #[test]
+    fn test_sstable_blocks() {
+        let keys: Vec<String> = (0..1_000u32).map(|i| format!("key{:05}", i * 2)).collect();
+        let key_values: Vec<(&str, u32)> = keys.iter().enumerate().map(|(i, key)| (&key[..], i as u32)).collect();
+        let sstable = build_sstable(&key_values);
+        assert_eq!(sstable.num_keys(), 1_000);
+        assert_eq!(sstable.num_blocks(), 32);
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(sstable.get(key), Some(i as u32));
+            assert_eq!(sstable.get(format!("{}0", key)), None);
+        }
+        assert_eq!(sstable.get("key00001"), None);
+        assert_eq!(sstable.get("key99999"), None);
+        assert_eq!(sstable.get("a"), None);
+        assert_eq!(collect_keys(&mut sstable.keys()).len(), 1_000);
+        // ranges across the boundaries of the blocks.
+        let range = collect_keys(&mut sstable.range().gt("key00127").lt("key00258").into_stream());
+        assert_eq!(range.len(), 65);
+        assert_eq!(range[0], (String::from("key00128"), 64));
+        assert_eq!(range[64], (String::from("key00256"), 128));
+        let range = collect_keys(&mut sstable.range().ge("key00128").le("key00256").into_stream());
+        assert_eq!(range.len(), 65);
+        assert_eq!(collect_keys(&mut sstable.keys_from("key00999".as_bytes()))[0], (String::from("key01000"), 500));
+    }
+
+    // accepts the keys ending with a given byte.
+    struct EndsWith(u8);
+
+    impl Automaton for EndsWith {
+        type State = bool;
+
+        fn start(&self) -> bool {
+            false
+        }
+
+        fn is_match(&self, state: &bool) -> bool {
+            *state
+        }
+
+        fn accept(&self, _: &bool, byte: u8) -> bool {
+            byte == self.0
+        }
+    }
+
+    #[test]
+    fn test_sstable_search() {
+        let keys: Vec<String> = (0..500u32).map(|i| format!("{}", i)).collect();
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        let key_values: Vec<(&str, u32)> = sorted_keys.iter().map(|key| (&key[..], key.parse().unwrap())).collect();
+        let sstable = build_sstable(&key_values);
+        let matches = collect_keys(&mut *sstable.search(EndsWith(b'7')));
+        assert_eq!(matches.len(), 50);
+        assert!(matches.iter().all(|&(ref key, value)| key.ends_with('7') && value % 10 == 7));
+        assert_eq!(collect_keys(&mut *sstable.search(AlwaysMatch)).len(), 500);
+    }
+}
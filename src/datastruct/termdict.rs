@@ -0,0 +1,613 @@
+use std::io;
+use std::io::Write;
+use fst::Automaton;
+
+use directory::ReadOnlySource;
+use common::BinarySerializable;
+use super::fstmap::{FstMapBuilder, FstMap, FstKeyIter, FstRange};
+use super::sstable::{SSTableMapBuilder, SSTableMap, SSTableKeyIter, SSTableRange};
+
+
+/// Implementation of the term dictionary of the segments.
+///
+/// It is a setting of the index, persisted in its `meta.json` file,
+/// and recorded in the `SegmentInfo` of each segment, so that the
+/// segments written with different implementations can be read,
+/// and merged, together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub enum TermDictionaryType {
+    /// Finite state transducer, see the `fst` crate.
+    ///
+    /// It is compact, and searching it with an automaton, as
+    /// the fuzzy and regex queries do, only visits the matching
+    /// prefixes, but it is slow to build.
+    Fst,
+    /// Sorted blocks of prefix compressed terms, along with
+    /// an in-memory index of the first term of each block.
+    ///
+    /// It is much faster to build, in particular for terms sharing
+    /// few suffixes, like numeric ids, but lookups are slower, and
+    /// searching it with an automaton decodes all of the terms.
+    SSTable,
+}
+
+impl Default for TermDictionaryType {
+    fn default() -> TermDictionaryType {
+        TermDictionaryType::Fst
+    }
+}
+
+
+/// Statistics about the prefixes shared by the consecutive
+/// terms of a term dictionary, whatever its implementation.
+///
+/// They tell how much a prefix compression could save on the keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct TermDictionaryStats {
+    /// Number of terms in the dictionary.
+    pub num_terms: u64,
+    /// Number of bytes of all of the terms, including their field.
+    pub term_bytes: u64,
+    /// Number of bytes each term shares with the previous one.
+    pub shared_prefix_bytes: u64,
+}
+
+impl TermDictionaryStats {
+    /// Returns the number of bytes of the terms
+    /// once stripped of their shared prefix.
+    pub fn suffix_bytes(&self,) -> u64 {
+        self.term_bytes - self.shared_prefix_bytes
+    }
+}
+
+
+/// Builds a term dictionary.
+///
+/// The keys must be inserted in strictly increasing
+/// lexicographical order.
+pub trait TermDictionaryBuilder<W: Write, V: BinarySerializable>: Sized {
+
+    /// Inserts a key, along with its value.
+    fn insert(&mut self, key: &[u8], value: &V) -> io::Result<()>;
+
+    /// Returns the number of bytes written so far.
+    ///
+    /// It may only be approximate, as the most recent
+    /// keys can be written lazily.
+    fn num_bytes(&self,) -> usize;
+
+    /// Writes the remaining data of the term dictionary.
+    fn finish(self,) -> io::Result<W>;
+}
+
+
+/// Sorted map from the bytes of the terms to their values.
+///
+/// The lifetime is the one of the borrow of the dictionary
+/// by its ranges and its iterators.
+pub trait TermDictionary<'a, V: 'static + BinarySerializable>: Sized {
+
+    /// Iterator over the keys of the dictionary.
+    type Keys: TermDictionaryKeys<V> + 'a;
+
+    /// Range of the keys of the dictionary.
+    type Range: TermDictionaryRange<V, Keys=Self::Keys> + 'a;
+
+    /// Returns the value associated to `key`, if any.
+    fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<V>;
+
+    /// Returns the ordinal of `key`, that is its rank among the keys
+    /// of the dictionary, if it belongs to the dictionary.
+    fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> Option<u64>;
+
+    /// Writes the key with the ordinal `ord` in `bytes`, in place
+    /// of its previous content.
+    ///
+    /// Returns false, and leaves `bytes` unchanged,
+    /// if the dictionary has less than `ord + 1` keys.
+    fn ord_to_term(&self, ord: u64, bytes: &mut Vec<u8>) -> bool;
+
+    /// Returns a range containing all of the keys,
+    /// to be restricted with its bounds.
+    fn range(&'a self) -> Self::Range;
+
+    /// Returns an iterator over all of the keys,
+    /// in lexicographical order.
+    fn keys(&'a self) -> Self::Keys {
+        self.range().into_stream()
+    }
+
+    /// Returns an iterator over the keys greater or equal to `lower_bound`,
+    /// in lexicographical order.
+    fn keys_from(&'a self, lower_bound: &[u8]) -> Self::Keys {
+        self.range().ge(lower_bound).into_stream()
+    }
+
+    /// Returns an iterator over the keys accepted by `automaton`,
+    /// in lexicographical order.
+    fn search<A: Automaton + 'a>(&'a self, automaton: A) -> Box<TermDictionaryKeys<V> + 'a>;
+}
+
+
+/// Range of the keys of a `TermDictionary`, built by
+/// restricting its bounds one at a time.
+pub trait TermDictionaryRange<V: 'static + BinarySerializable>: Sized {
+
+    /// Iterator over the keys of the range.
+    type Keys: TermDictionaryKeys<V>;
+
+    /// Restricts the range to the keys greater or equal to `bound`.
+    fn ge<T: AsRef<[u8]>>(self, bound: T) -> Self;
+
+    /// Restricts the range to the keys strictly greater than `bound`.
+    fn gt<T: AsRef<[u8]>>(self, bound: T) -> Self;
+
+    /// Restricts the range to the keys lower or equal to `bound`.
+    fn le<T: AsRef<[u8]>>(self, bound: T) -> Self;
+
+    /// Restricts the range to the keys strictly lower than `bound`.
+    fn lt<T: AsRef<[u8]>>(self, bound: T) -> Self;
+
+    /// Returns an iterator over the keys of the range,
+    /// in lexicographical order.
+    fn into_stream(self,) -> Self::Keys;
+}
+
+
+/// Iterator over the keys of a `TermDictionary`,
+/// along with their values.
+pub trait TermDictionaryKeys<V: 'static + BinarySerializable> {
+
+    /// Returns the next key.
+    fn next(&mut self) -> Option<&[u8]> {
+        self.next_with_value().map(|(key, _)| key)
+    }
+
+    /// Returns the next key, along with its value.
+    fn next_with_value(&mut self) -> Option<(&[u8], V)>;
+}
+
+
+enum MapBuilder<W: Write, V: BinarySerializable> {
+    Fst(FstMapBuilder<W, V>),
+    SSTable(SSTableMapBuilder<W, V>),
+}
+
+/// Builds the term dictionary of a segment,
+/// of a given `TermDictionaryType`.
+///
+/// It also records the `TermDictionaryStats` of the keys.
+pub struct SegmentTermDictionaryBuilder<W: Write, V: BinarySerializable> {
+    map_builder: MapBuilder<W, V>,
+    last_key: Vec<u8>,
+    stats: TermDictionaryStats,
+}
+
+impl<W: Write, V: BinarySerializable> SegmentTermDictionaryBuilder<W, V> {
+
+    /// Creates a builder writing a term dictionary to `w`.
+    pub fn new(term_dictionary_type: TermDictionaryType, w: W) -> io::Result<SegmentTermDictionaryBuilder<W, V>> {
+        let map_builder = match term_dictionary_type {
+            TermDictionaryType::Fst => MapBuilder::Fst(try!(FstMapBuilder::new(w))),
+            TermDictionaryType::SSTable => MapBuilder::SSTable(try!(SSTableMapBuilder::new(w))),
+        };
+        Ok(SegmentTermDictionaryBuilder {
+            map_builder: map_builder,
+            last_key: Vec::new(),
+            stats: TermDictionaryStats::default(),
+        })
+    }
+
+    /// Returns the type of the term dictionary being built.
+    pub fn term_dictionary_type(&self,) -> TermDictionaryType {
+        match self.map_builder {
+            MapBuilder::Fst(_) => TermDictionaryType::Fst,
+            MapBuilder::SSTable(_) => TermDictionaryType::SSTable,
+        }
+    }
+
+    /// Returns the statistics of the keys inserted so far.
+    pub fn stats(&self,) -> TermDictionaryStats {
+        self.stats
+    }
+}
+
+impl<W: Write, V: BinarySerializable> TermDictionaryBuilder<W, V> for SegmentTermDictionaryBuilder<W, V> {
+
+    fn insert(&mut self, key: &[u8], value: &V) -> io::Result<()> {
+        try!(match self.map_builder {
+            MapBuilder::Fst(ref mut fst_builder) => fst_builder.insert(key, value),
+            MapBuilder::SSTable(ref mut sstable_builder) => sstable_builder.insert(key, value),
+        });
+        let shared_prefix_len = self.last_key
+            .iter()
+            .zip(key.iter())
+            .take_while(|&(left_byte, right_byte)| left_byte == right_byte)
+            .count();
+        self.stats.num_terms += 1;
+        self.stats.term_bytes += key.len() as u64;
+        self.stats.shared_prefix_bytes += shared_prefix_len as u64;
+        self.last_key.truncate(shared_prefix_len);
+        self.last_key.extend_from_slice(&key[shared_prefix_len..]);
+        Ok(())
+    }
+
+    fn num_bytes(&self,) -> usize {
+        match self.map_builder {
+            MapBuilder::Fst(ref fst_builder) => fst_builder.num_bytes(),
+            MapBuilder::SSTable(ref sstable_builder) => sstable_builder.num_bytes(),
+        }
+    }
+
+    fn finish(self,) -> io::Result<W> {
+        match self.map_builder {
+            MapBuilder::Fst(fst_builder) => fst_builder.finish(),
+            MapBuilder::SSTable(sstable_builder) => sstable_builder.finish(),
+        }
+    }
+}
+
+
+/// Term dictionary of a segment, of either of the `TermDictionaryType`.
+///
+/// The implementation is only known once the `SegmentInfo`
+/// of the segment is read.
+pub enum SegmentTermDictionary<V: BinarySerializable> {
+    /// Term dictionary written as a fst.
+    Fst(FstMap<V>),
+    /// Term dictionary written as a sstable.
+    SSTable(SSTableMap<V>),
+}
+
+impl<V: BinarySerializable> SegmentTermDictionary<V> {
+
+    /// Opens a term dictionary written by a `SegmentTermDictionaryBuilder`.
+    pub fn from_source(term_dictionary_type: TermDictionaryType, source: ReadOnlySource) -> io::Result<SegmentTermDictionary<V>> {
+        SegmentTermDictionary::from_source_with_deserializer(term_dictionary_type, source, V::deserialize)
+    }
+
+    /// Opens a term dictionary whose values are read with
+    /// `deserialize_value` rather than `V::deserialize`.
+    pub fn from_source_with_deserializer(term_dictionary_type: TermDictionaryType, source: ReadOnlySource, deserialize_value: fn(&mut io::Read) -> io::Result<V>) -> io::Result<SegmentTermDictionary<V>> {
+        Ok(match term_dictionary_type {
+            TermDictionaryType::Fst => {
+                SegmentTermDictionary::Fst(try!(FstMap::from_source_with_deserializer(source, deserialize_value)))
+            }
+            TermDictionaryType::SSTable => {
+                SegmentTermDictionary::SSTable(try!(SSTableMap::from_source_with_deserializer(source, deserialize_value)))
+            }
+        })
+    }
+
+    /// Returns the type of the term dictionary.
+    pub fn term_dictionary_type(&self,) -> TermDictionaryType {
+        match *self {
+            SegmentTermDictionary::Fst(_) => TermDictionaryType::Fst,
+            SegmentTermDictionary::SSTable(_) => TermDictionaryType::SSTable,
+        }
+    }
+}
+
+impl<'a, V: 'static + BinarySerializable> TermDictionary<'a, V> for SegmentTermDictionary<V> {
+
+    type Keys = SegmentTermKeys<'a, V>;
+
+    type Range = SegmentTermRange<'a, V>;
+
+    fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<V> {
+        match *self {
+            SegmentTermDictionary::Fst(ref fstmap) => fstmap.get(key),
+            SegmentTermDictionary::SSTable(ref sstable) => sstable.get(key),
+        }
+    }
+
+    fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> Option<u64> {
+        match *self {
+            SegmentTermDictionary::Fst(ref fstmap) => fstmap.term_ord(key),
+            SegmentTermDictionary::SSTable(ref sstable) => sstable.term_ord(key),
+        }
+    }
+
+    fn ord_to_term(&self, ord: u64, bytes: &mut Vec<u8>) -> bool {
+        match *self {
+            SegmentTermDictionary::Fst(ref fstmap) => fstmap.ord_to_term(ord, bytes),
+            SegmentTermDictionary::SSTable(ref sstable) => sstable.ord_to_term(ord, bytes),
+        }
+    }
+
+    fn range(&'a self) -> SegmentTermRange<'a, V> {
+        match *self {
+            SegmentTermDictionary::Fst(ref fstmap) => SegmentTermRange::Fst(fstmap.range()),
+            SegmentTermDictionary::SSTable(ref sstable) => SegmentTermRange::SSTable(sstable.range()),
+        }
+    }
+
+    fn search<A: Automaton + 'a>(&'a self, automaton: A) -> Box<TermDictionaryKeys<V> + 'a> {
+        match *self {
+            SegmentTermDictionary::Fst(ref fstmap) => fstmap.search(automaton),
+            SegmentTermDictionary::SSTable(ref sstable) => sstable.search(automaton),
+        }
+    }
+}
+
+
+/// Range of the keys of a `SegmentTermDictionary`.
+pub enum SegmentTermRange<'a, V: 'static + BinarySerializable> {
+    /// Range of a fst.
+    Fst(FstRange<'a, V>),
+    /// Range of a sstable.
+    SSTable(SSTableRange<'a, V>),
+}
+
+impl<'a, V: 'static + BinarySerializable> TermDictionaryRange<V> for SegmentTermRange<'a, V> {
+
+    type Keys = SegmentTermKeys<'a, V>;
+
+    fn ge<T: AsRef<[u8]>>(self, bound: T) -> SegmentTermRange<'a, V> {
+        match self {
+            SegmentTermRange::Fst(range) => SegmentTermRange::Fst(range.ge(bound)),
+            SegmentTermRange::SSTable(range) => SegmentTermRange::SSTable(range.ge(bound)),
+        }
+    }
+
+    fn gt<T: AsRef<[u8]>>(self, bound: T) -> SegmentTermRange<'a, V> {
+        match self {
+            SegmentTermRange::Fst(range) => SegmentTermRange::Fst(range.gt(bound)),
+            SegmentTermRange::SSTable(range) => SegmentTermRange::SSTable(range.gt(bound)),
+        }
+    }
+
+    fn le<T: AsRef<[u8]>>(self, bound: T) -> SegmentTermRange<'a, V> {
+        match self {
+            SegmentTermRange::Fst(range) => SegmentTermRange::Fst(range.le(bound)),
+            SegmentTermRange::SSTable(range) => SegmentTermRange::SSTable(range.le(bound)),
+        }
+    }
+
+    fn lt<T: AsRef<[u8]>>(self, bound: T) -> SegmentTermRange<'a, V> {
+        match self {
+            SegmentTermRange::Fst(range) => SegmentTermRange::Fst(range.lt(bound)),
+            SegmentTermRange::SSTable(range) => SegmentTermRange::SSTable(range.lt(bound)),
+        }
+    }
+
+    fn into_stream(self,) -> SegmentTermKeys<'a, V> {
+        match self {
+            SegmentTermRange::Fst(range) => SegmentTermKeys::Fst(range.into_stream()),
+            SegmentTermRange::SSTable(range) => SegmentTermKeys::SSTable(range.into_stream()),
+        }
+    }
+}
+
+
+/// Iterator over the keys of a `SegmentTermDictionary`,
+/// along with their values.
+pub enum SegmentTermKeys<'a, V: 'static + BinarySerializable> {
+    /// Keys of a fst.
+    Fst(FstKeyIter<'a, V>),
+    /// Keys of a sstable.
+    SSTable(SSTableKeyIter<'a, V>),
+}
+
+impl<'a, V: 'static + BinarySerializable> TermDictionaryKeys<V> for SegmentTermKeys<'a, V> {
+
+    fn next(&mut self) -> Option<&[u8]> {
+        match *self {
+            SegmentTermKeys::Fst(ref mut keys) => keys.next(),
+            SegmentTermKeys::SSTable(ref mut keys) => keys.next(),
+        }
+    }
+
+    fn next_with_value(&mut self) -> Option<(&[u8], V)> {
+        match *self {
+            SegmentTermKeys::Fst(ref mut keys) => keys.next_with_value(),
+            SegmentTermKeys::SSTable(ref mut keys) => keys.next_with_value(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::Bencher;
+    use directory::ReadOnlySource;
+    use std::io::Write;
+    use byteorder::{BigEndian, WriteBytesExt};
+
+    const TERM_DICTIONARY_TYPES: [TermDictionaryType; 2] = [TermDictionaryType::Fst, TermDictionaryType::SSTable];
+
+    fn build_term_dictionary<I: Iterator<Item=Vec<u8>>>(term_dictionary_type: TermDictionaryType, keys: I) -> (SegmentTermDictionary<u32>, TermDictionaryStats) {
+        let mut term_dictionary_builder = SegmentTermDictionaryBuilder::new(term_dictionary_type, Vec::new()).unwrap();
+        for (i, key) in keys.enumerate() {
+            term_dictionary_builder.insert(&key, &(i as u32)).unwrap();
+        }
+        let stats = term_dictionary_builder.stats();
+        let data = term_dictionary_builder.finish().unwrap();
+        (SegmentTermDictionary::from_source(term_dictionary_type, ReadOnlySource::from(data)).unwrap(), stats)
+    }
+
+    // the keys of the numeric terms of a field, in order.
+    fn numeric_keys(num_keys: u32) -> Box<Iterator<Item=Vec<u8>>> {
+        Box::new((0..num_keys).map(|i| {
+            let mut key: Vec<u8> = vec!(0u8);
+            key.write_u32::<BigEndian>(i * 3).unwrap();
+            key
+        }))
+    }
+
+    fn string_keys() -> Vec<Vec<u8>> {
+        let mut keys: Vec<Vec<u8>> = (0..1_000u32)
+            .map(|i| format!("term{}", i * 7 % 1_013).into_bytes())
+            .collect();
+        keys.push(Vec::new());
+        keys.push(b"term".to_vec());
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    #[test]
+    fn test_term_dictionary_same_keys() {
+        let keys = string_keys();
+        for &term_dictionary_type in &TERM_DICTIONARY_TYPES {
+            let (term_dictionary, _) = build_term_dictionary(term_dictionary_type, keys.clone().into_iter());
+            assert_eq!(term_dictionary.term_dictionary_type(), term_dictionary_type);
+            for (i, key) in keys.iter().enumerate() {
+                assert_eq!(term_dictionary.get(key), Some(i as u32));
+                let mut missing_key = key.clone();
+                missing_key.push(b'!');
+                assert_eq!(term_dictionary.get(&missing_key), None);
+            }
+            {
+                let mut streamed_keys: Vec<Vec<u8>> = Vec::new();
+                let mut term_dictionary_keys = term_dictionary.keys();
+                while let Some((key, value)) = term_dictionary_keys.next_with_value() {
+                    assert_eq!(keys[value as usize], key);
+                    streamed_keys.push(key.to_vec());
+                }
+                assert_eq!(streamed_keys, keys);
+            }
+            {
+                let mut range_keys = term_dictionary.range().gt(b"term5").le(b"term6").into_stream();
+                let mut num_range_keys = 0;
+                while let Some(key) = range_keys.next() {
+                    assert!(key > &b"term5"[..] && key <= &b"term6"[..]);
+                    num_range_keys += 1;
+                }
+                let expected_num_range_keys = keys.iter().filter(|key| &key[..] > &b"term5"[..] && &key[..] <= &b"term6"[..]).count();
+                assert_eq!(num_range_keys, expected_num_range_keys);
+            }
+            {
+                let mut keys_from = term_dictionary.keys_from(b"term998");
+                assert_eq!(keys_from.next(), Some(&b"term998"[..]));
+                assert_eq!(keys_from.next(), None);
+            }
+            // `term999` is missing.
+            assert_eq!(term_dictionary.keys_from(b"term999").next(), None);
+        }
+    }
+
+    #[test]
+    fn test_term_dictionary_ords() {
+        let keys = string_keys();
+        for &term_dictionary_type in &TERM_DICTIONARY_TYPES {
+            let (term_dictionary, _) = build_term_dictionary(term_dictionary_type, keys.clone().into_iter());
+            let mut bytes = b"previous".to_vec();
+            for (i, key) in keys.iter().enumerate() {
+                assert_eq!(term_dictionary.term_ord(key), Some(i as u64));
+                let mut missing_key = key.clone();
+                missing_key.push(b'!');
+                assert_eq!(term_dictionary.term_ord(&missing_key), None);
+                assert!(term_dictionary.ord_to_term(i as u64, &mut bytes));
+                assert_eq!(&bytes, key);
+            }
+            assert!(!term_dictionary.ord_to_term(keys.len() as u64, &mut bytes));
+            assert_eq!(&bytes, &keys[keys.len() - 1]);
+            let (empty_dictionary, _) = build_term_dictionary(term_dictionary_type, Vec::new().into_iter());
+            assert_eq!(empty_dictionary.term_ord(b"term"), None);
+            assert!(!empty_dictionary.ord_to_term(0, &mut bytes));
+        }
+    }
+
+    #[test]
+    fn test_term_dictionary_stats() {
+        for &term_dictionary_type in &TERM_DICTIONARY_TYPES {
+            let keys = vec!(b"abc".to_vec(), b"abcd".to_vec(), b"abd".to_vec(), b"b".to_vec());
+            let (_, stats) = build_term_dictionary(term_dictionary_type, keys.into_iter());
+            assert_eq!(stats, TermDictionaryStats {
+                num_terms: 4,
+                term_bytes: 11,
+                shared_prefix_bytes: 3 + 2,
+            });
+            assert_eq!(stats.suffix_bytes(), 6);
+        }
+    }
+
+    #[test]
+    fn test_term_dictionary_numeric_keys() {
+        let num_keys = 100_000;
+        let (fst_dictionary, _) = build_term_dictionary(TermDictionaryType::Fst, numeric_keys(num_keys));
+        let (sstable_dictionary, stats) = build_term_dictionary(TermDictionaryType::SSTable, numeric_keys(num_keys));
+        assert_eq!(stats.num_terms, num_keys as u64);
+        assert_eq!(stats.term_bytes, 5 * num_keys as u64);
+        for i in (0..num_keys).filter(|i| i % 101 == 0) {
+            let mut key: Vec<u8> = vec!(0u8);
+            key.write_u32::<BigEndian>(i * 3).unwrap();
+            assert_eq!(fst_dictionary.get(&key), Some(i));
+            assert_eq!(sstable_dictionary.get(&key), Some(i));
+            assert_eq!(fst_dictionary.term_ord(&key), Some(i as u64));
+            assert_eq!(sstable_dictionary.term_ord(&key), Some(i as u64));
+            key.write_all(&[0u8]).unwrap();
+            assert_eq!(fst_dictionary.get(&key), None);
+            assert_eq!(sstable_dictionary.get(&key), None);
+        }
+    }
+
+    // 10 millions numeric terms, as in the indexes of numeric ids.
+    const NUM_BENCH_KEYS: u64 = 10_000_000;
+
+    // sparse ids, sharing fewer suffixes than consecutive ones.
+    fn bench_ids() -> Vec<u64> {
+        let mut ids: Vec<u64> = (0..NUM_BENCH_KEYS)
+            .map(|i| i.wrapping_mul(11_400_714_819_323_198_485u64))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    fn id_key(id: u64) -> Vec<u8> {
+        let mut key: Vec<u8> = vec!(0u8);
+        key.write_u64::<BigEndian>(id).unwrap();
+        key
+    }
+
+    fn bench_build(b: &mut Bencher, term_dictionary_type: TermDictionaryType) {
+        let ids = bench_ids();
+        b.iter(|| {
+            let mut term_dictionary_builder = SegmentTermDictionaryBuilder::new(term_dictionary_type, Vec::new()).unwrap();
+            for (i, &id) in ids.iter().enumerate() {
+                term_dictionary_builder.insert(&id_key(id), &(i as u32)).unwrap();
+            }
+            term_dictionary_builder.finish().unwrap().len()
+        });
+    }
+
+    fn bench_lookup(b: &mut Bencher, term_dictionary_type: TermDictionaryType) {
+        let ids = bench_ids();
+        let (term_dictionary, _) = build_term_dictionary(term_dictionary_type, ids.iter().map(|&id| id_key(id)));
+        // half of the keys are missing.
+        let lookup_keys: Vec<Vec<u8>> = (0..1_000u64)
+            .map(|i| {
+                let id = ids[(i.wrapping_mul(2_654_435_761u64) % NUM_BENCH_KEYS) as usize];
+                id_key(id + i % 2)
+            })
+            .collect();
+        b.iter(|| {
+            lookup_keys
+                .iter()
+                .filter(|key| term_dictionary.get(key).is_some())
+                .count()
+        });
+    }
+
+    #[bench]
+    fn bench_term_dictionary_fst_build(b: &mut Bencher) {
+        bench_build(b, TermDictionaryType::Fst);
+    }
+
+    #[bench]
+    fn bench_term_dictionary_sstable_build(b: &mut Bencher) {
+        bench_build(b, TermDictionaryType::SSTable);
+    }
+
+    #[bench]
+    fn bench_term_dictionary_fst_lookup(b: &mut Bencher) {
+        bench_lookup(b, TermDictionaryType::Fst);
+    }
+
+    #[bench]
+    fn bench_term_dictionary_sstable_lookup(b: &mut Bencher) {
+        bench_lookup(b, TermDictionaryType::SSTable);
+    }
+}
@@ -0,0 +1,257 @@
+use std::cmp;
+use std::io::{self, Write, Seek, SeekFrom};
+use common::{BinarySerializable, Crc32Hasher, crc32, crc32_combine};
+use directory::{ReadOnlySource, SeekableWrite, WritePtr};
+use directory;
+
+
+/// Magic number starting the footer of the components of a segment.
+pub const FOOTER_MAGIC_NUMBER: u32 = 0x7A4E_F007;
+
+/// Version of the footer written by the `FooterWrite`.
+pub const FOOTER_VERSION: u32 = 1;
+
+/// Number of bytes taken by a footer,
+/// at the end of the components of a segment.
+pub const FOOTER_LEN: usize = 12;
+
+// Number of bytes at the start of a `FooterWrite` that may still be
+// rewritten after seeking back, like the pointer to the table
+// of the fast fields written when they are closed.
+const REWRITABLE_HEAD_LEN: usize = 8;
+
+
+fn corrupted_footer_err(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+
+/// Footer appended to each of the components of a segment,
+/// but its `.info` file.
+///
+/// * `magic_number` : `FOOTER_MAGIC_NUMBER`, telling a footer
+///   apart from a truncated file.
+/// * `version` : the version of the footer.
+/// * `crc` : the CRC-32 checksum of the data of the component,
+///   before its footer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Footer {
+    /// Version of the footer.
+    pub version: u32,
+    /// CRC-32 checksum of the data of the component.
+    pub crc: u32,
+}
+
+impl Footer {
+
+    /// Splits the content of a component in its data and its footer.
+    ///
+    /// Only the magic number and the version of the footer are checked,
+    /// the data of the component is not read.
+    ///
+    /// # Errors
+    /// If the component is too short to have a footer,
+    /// if the magic number does not match, as is the case
+    /// of a truncated file, or if the version is not supported.
+    pub fn extract(source: &ReadOnlySource) -> io::Result<(ReadOnlySource, Footer)> {
+        if source.len() < FOOTER_LEN {
+            return Err(corrupted_footer_err(format!("{} bytes are too few for a footer", source.len())));
+        }
+        let data_len = source.len() - FOOTER_LEN;
+        let footer = try!(Footer::deserialize(&mut &source[data_len..]));
+        if footer.version > FOOTER_VERSION {
+            return Err(corrupted_footer_err(format!("unsupported footer version {}", footer.version)));
+        }
+        Ok((source.slice(0, data_len), footer))
+    }
+
+    /// Checks that `data` matches the checksum of the footer.
+    pub fn validate_checksum(&self, data: &[u8]) -> io::Result<()> {
+        let crc = crc32(data);
+        if crc != self.crc {
+            return Err(corrupted_footer_err(format!("checksum mismatch, expected {:x}, got {:x}", self.crc, crc)));
+        }
+        Ok(())
+    }
+}
+
+impl BinarySerializable for Footer {
+    fn serialize(&self, writer: &mut io::Write) -> io::Result<usize> {
+        Ok(
+            try!(FOOTER_MAGIC_NUMBER.serialize(writer)) +
+            try!(self.version.serialize(writer)) +
+            try!(self.crc.serialize(writer))
+        )
+    }
+    fn deserialize(reader: &mut io::Read) -> io::Result<Self> {
+        let magic_number = try!(u32::deserialize(reader));
+        if magic_number != FOOTER_MAGIC_NUMBER {
+            return Err(corrupted_footer_err(format!("invalid footer magic number {:x}", magic_number)));
+        }
+        let version = try!(u32::deserialize(reader));
+        let crc = try!(u32::deserialize(reader));
+        Ok(Footer {
+            version: version,
+            crc: crc,
+        })
+    }
+}
+
+
+/// Write computing the checksum of its data on the fly,
+/// and appending a `Footer` when it is terminated.
+///
+/// Only the first bytes of the data may be rewritten after
+/// seeking back. They are hashed last, and their checksum
+/// is combined with the one of the rest of the data.
+pub struct FooterWrite {
+    write: WritePtr,
+    head: Vec<u8>,
+    hasher: Crc32Hasher,
+    position: usize,
+    len: usize,
+    terminated: bool,
+}
+
+impl FooterWrite {
+
+    /// Wraps the write of a component.
+    pub fn new(write: WritePtr) -> FooterWrite {
+        FooterWrite {
+            write: write,
+            head: Vec::with_capacity(REWRITABLE_HEAD_LEN),
+            hasher: Crc32Hasher::new(),
+            position: 0,
+            len: 0,
+            terminated: false,
+        }
+    }
+
+    // checksum of all of the data written so far.
+    fn crc(&self,) -> u32 {
+        let tail_len = self.len - self.head.len();
+        crc32_combine(crc32(&self.head), self.hasher.finish(), tail_len as u64)
+    }
+}
+
+impl Write for FooterWrite {
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.terminated {
+            return Err(io::Error::new(io::ErrorKind::Other, "write after the footer"));
+        }
+        let head_len = cmp::min(buf.len(), REWRITABLE_HEAD_LEN.saturating_sub(self.position));
+        if head_len < buf.len() && cmp::max(self.position, REWRITABLE_HEAD_LEN) != self.len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "only the first bytes of a component may be rewritten"));
+        }
+        let written = try!(self.write.write(buf));
+        let (head_bytes, tail_bytes) = buf[..written].split_at(cmp::min(head_len, written));
+        if !head_bytes.is_empty() {
+            let head_end = self.position + head_bytes.len();
+            if head_end > self.head.len() {
+                self.head.resize(head_end, 0u8);
+            }
+            self.head[self.position..head_end].copy_from_slice(head_bytes);
+        }
+        self.hasher.update(tail_bytes);
+        self.position += written;
+        self.len = cmp::max(self.len, self.position);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write.flush()
+    }
+}
+
+impl Seek for FooterWrite {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(delta) => self.position as i64 + delta,
+            SeekFrom::End(delta) => self.len as i64 + delta,
+        };
+        if position < 0 || position as usize > self.len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "seek out of the written data"));
+        }
+        try!(self.write.seek(SeekFrom::Start(position as u64)));
+        self.position = position as usize;
+        Ok(position as u64)
+    }
+}
+
+impl SeekableWrite for FooterWrite {
+    fn terminate(&mut self) -> io::Result<()> {
+        if self.terminated {
+            return Ok(());
+        }
+        if self.position != self.len {
+            try!(self.seek(SeekFrom::End(0)));
+        }
+        let footer = Footer {
+            version: FOOTER_VERSION,
+            crc: self.crc(),
+        };
+        try!(footer.serialize(&mut self.write));
+        self.terminated = true;
+        directory::terminate(&mut self.write)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::path::Path;
+    use std::io::{BufWriter, Write, Seek, SeekFrom};
+    use common::crc32;
+    use directory::{self, Directory, RAMDirectory, SeekableWrite, WritePtr};
+
+    #[test]
+    fn test_footer_write() {
+        let path = Path::new("component");
+        let mut directory = RAMDirectory::create();
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i % 253) as u8).collect();
+        {
+            let write = directory.open_write(path).unwrap();
+            let mut footer_write = FooterWrite::new(write);
+            footer_write.write_all(&[0u8; 4]).unwrap();
+            footer_write.write_all(&data[4..]).unwrap();
+            // the head may be rewritten, but not the rest of the data.
+            footer_write.seek(SeekFrom::Start(0)).unwrap();
+            footer_write.write_all(&data[..4]).unwrap();
+            footer_write.seek(SeekFrom::Start(100)).unwrap();
+            assert!(footer_write.write(&[1u8]).is_err());
+            assert!(footer_write.seek(SeekFrom::Start(1_001)).is_err());
+            footer_write.terminate().unwrap();
+            assert!(footer_write.write(&[1u8]).is_err());
+        }
+        let source = directory.open_read(path).unwrap();
+        assert_eq!(source.len(), data.len() + FOOTER_LEN);
+        let (component_data, footer) = Footer::extract(&source).unwrap();
+        assert_eq!(&*component_data, &data[..]);
+        assert_eq!(footer, Footer { version: FOOTER_VERSION, crc: crc32(&data) });
+        assert!(footer.validate_checksum(&component_data).is_ok());
+        assert!(footer.validate_checksum(&data[1..]).is_err());
+        // a truncated component does not end with a footer.
+        assert!(Footer::extract(&source.slice(0, source.len() - 1)).is_err());
+        assert!(Footer::extract(&source.slice(0, 3)).is_err());
+    }
+
+    #[test]
+    fn test_footer_write_empty() {
+        let path = Path::new("component");
+        let mut directory = RAMDirectory::create();
+        {
+            let write = directory.open_write(path).unwrap();
+            let mut footer_write: WritePtr = BufWriter::new(Box::new(FooterWrite::new(write)));
+            directory::terminate(&mut footer_write).unwrap();
+        }
+        let source = directory.open_read(path).unwrap();
+        assert_eq!(source.len(), FOOTER_LEN);
+        let (component_data, footer) = Footer::extract(&source).unwrap();
+        assert!(component_data.is_empty());
+        assert!(footer.validate_checksum(&component_data).is_ok());
+    }
+}
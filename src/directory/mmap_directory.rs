@@ -12,7 +12,7 @@ use std::io;
 use std::io::{Seek, SeekFrom};
 use directory::Directory;
 use directory::ReadOnlySource;
-use directory::WritePtr;
+use directory::{WritePtr, SeekableWrite};
 use std::io::BufWriter;
 use std::fs::OpenOptions;
 use directory::error::{OpenWriteError, FileError, OpenDirectoryError};
@@ -125,6 +125,8 @@ impl Seek for SafeFileWriter {
     }
 }
 
+impl SeekableWrite for SafeFileWriter {}
+
 
 impl Directory for MmapDirectory {
     
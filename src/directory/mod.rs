@@ -3,21 +3,31 @@ mod ram_directory;
 mod directory;
 mod read_only_source;
 mod shared_vec_slice;
+mod footer;
 
 /// Errors specific to the directory module.
 pub mod error;
 
-use std::io::{Write, Seek};
+use std::io::{self, Write, Seek};
 
 use std::io::BufWriter;
 pub use self::read_only_source::ReadOnlySource;
 pub use self::directory::Directory;
 pub use self::ram_directory::RAMDirectory;
 pub use self::mmap_directory::MmapDirectory;
+pub use self::footer::{Footer, FooterWrite, FOOTER_MAGIC_NUMBER, FOOTER_VERSION, FOOTER_LEN};
 
 /// Synonym of Seek + Write
-pub trait SeekableWrite: Seek + Write {}
-impl<T: Seek + Write> SeekableWrite for T {}
+pub trait SeekableWrite: Seek + Write {
+    /// Terminates the write, once all of its data has been written.
+    ///
+    /// The files of a directory only need to be flushed,
+    /// but the components of a segment get a `Footer`
+    /// when they are terminated.
+    fn terminate(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
 /// Write object for Directory.
 ///
@@ -25,6 +35,15 @@ impl<T: Seek + Write> SeekableWrite for T {}
 /// and Seek.
 pub type WritePtr = BufWriter<Box<SeekableWrite>>;
 
+/// Flushes a `WritePtr`, and terminates the write it wraps.
+///
+/// The writers of the components of a segment must be terminated
+/// rather than just flushed, so that their footer gets written.
+pub fn terminate(write: &mut WritePtr) -> io::Result<()> {
+    try!(write.flush());
+    write.get_mut().terminate()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -7,7 +7,7 @@ use std::sync::{Arc, RwLock};
 use common::make_io_err;
 use directory::{Directory, ReadOnlySource};
 use directory::error::{OpenWriteError, FileError};
-use directory::WritePtr;
+use directory::{WritePtr, SeekableWrite};
 use super::shared_vec_slice::SharedVecSlice;
 
 /// Writer associated with the `RAMDirectory`
@@ -52,6 +52,8 @@ impl Seek for VecWriter {
     }
 }
 
+impl SeekableWrite for VecWriter {}
+
 impl Write for VecWriter {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.is_flushed = false;
@@ -1,5 +1,6 @@
 use fst::raw::MmapReadOnly;
 use std::ops::Deref;
+use std::sync::Arc;
 use super::shared_vec_slice::SharedVecSlice;
 use common::HasLen;
 
@@ -68,6 +69,12 @@ impl ReadOnlySource {
     }
 }
 
+impl From<Vec<u8>> for ReadOnlySource {
+    fn from(data: Vec<u8>) -> ReadOnlySource {
+        ReadOnlySource::Anonymous(SharedVecSlice::new(Arc::new(data)))
+    }
+}
+
 impl HasLen for ReadOnlySource {
     fn len(&self,) -> usize {
         self.as_slice().len()
@@ -10,6 +10,7 @@ use std::sync::PoisonError;
 use directory::error::{FileError, OpenWriteError, OpenDirectoryError};
 use query;
 use schema;
+use postings::SerializerError;
 
 
 /// Tantivy result.
@@ -35,6 +36,12 @@ pub enum Error {
     CorruptedFile(PathBuf, Box<error::Error + Send>),
     /// Invalid argument was passed by the user.
     InvalidArgument(String),
+    /// The postings given to the postings serializer were invalid,
+    /// for instance with their terms out of order.
+    SerializerError(SerializerError),
+    /// The index was written in a format this version
+    /// of tantivy cannot read anymore, and needs to be reindexed.
+    IncompatibleIndex(String),
     /// An Error happened in one of the thread
     ErrorInThread(String), // TODO investigate better solution
 }
@@ -72,6 +79,21 @@ impl From<schema::DocParsingError> for Error {
     }
 }
 
+impl From<schema::SchemaError> for Error {
+    fn from(error: schema::SchemaError) -> Error {
+        Error::InvalidArgument(format!("Invalid schema {:?}", error))
+    }
+}
+
+impl From<SerializerError> for Error {
+    fn from(error: SerializerError) -> Error {
+        match error {
+            SerializerError::IOError(io_error) => Error::IOError(io_error),
+            _ => Error::SerializerError(error),
+        }
+    }
+}
+
 impl From<OpenWriteError> for Error {
     fn from(error: OpenWriteError) -> Error {
         match error {
@@ -0,0 +1,351 @@
+use std::io::{self, SeekFrom, Seek, Write};
+use std::collections::HashMap;
+use byteorder::{ByteOrder, NativeEndian};
+
+use common::BinarySerializable;
+use common::HasLen;
+use directory::ReadOnlySource;
+use directory::{self, WritePtr};
+use schema::{Schema, Field, Document, Value};
+use DocId;
+
+
+/// `BytesFastFieldSerializer` is in charge of serializing
+/// bytes fast fields on disk.
+///
+/// For each field, the layout is the following
+///
+/// * `num_offsets: u32` (that is `max_doc + 1`)
+/// * `offsets: [u32; num_offsets]`, the start offset
+///    of the values of each document.
+/// * the values, concatenated.
+///
+/// The file ends with a header listing the start offset
+/// of each field, and the file starts with a pointer
+/// to this header, as for u32 fast fields.
+///
+/// The serializer expects to receive the following calls.
+///
+/// * `new_bytes_field(...)`
+/// * `write_data(...)`
+/// * `write_data(...)`
+/// * ...
+/// * `new_bytes_field(...)`
+/// * `write_data(...)`
+/// * ...
+/// * `close()`
+pub struct BytesFastFieldSerializer {
+    write: WritePtr,
+    written_size: usize,
+    fields: Vec<(Field, u32)>,
+}
+
+impl BytesFastFieldSerializer {
+
+    /// Constructor
+    pub fn new(mut write: WritePtr) -> io::Result<BytesFastFieldSerializer> {
+        // just making room for the pointer to header.
+        let written_size: usize = try!(0u32.serialize(&mut write));
+        Ok(BytesFastFieldSerializer {
+            write: write,
+            written_size: written_size,
+            fields: Vec::new(),
+        })
+    }
+
+    /// Start serializing a new bytes fast field.
+    ///
+    /// `offsets` must contain `max_doc + 1` values, the last one
+    /// being the overall length of the data.
+    pub fn new_bytes_field(&mut self, field: Field, offsets: &[u32]) -> io::Result<()> {
+        self.fields.push((field, self.written_size as u32));
+        let write: &mut Write = &mut self.write;
+        self.written_size += try!((offsets.len() as u32).serialize(write));
+        for offset in offsets {
+            self.written_size += try!(offset.serialize(write));
+        }
+        Ok(())
+    }
+
+    /// Appends values to the currently open field.
+    pub fn write_data(&mut self, data: &[u8]) -> io::Result<()> {
+        try!(self.write.write_all(data));
+        self.written_size += data.len();
+        Ok(())
+    }
+
+    /// Closes the serializer
+    ///
+    /// After this call the data must be persistently save on disk.
+    pub fn close(mut self,) -> io::Result<usize> {
+        let header_offset: usize = self.written_size;
+        self.written_size += try!(self.fields.serialize(&mut self.write));
+        try!(self.write.seek(SeekFrom::Start(0)));
+        try!((header_offset as u32).serialize(&mut self.write));
+        try!(directory::terminate(&mut self.write));
+        Ok(self.written_size)
+    }
+}
+
+
+/// Writer for all of the bytes fast fields of a segment.
+pub struct BytesFastFieldsWriter {
+    field_writers: Vec<BytesFastFieldWriter>,
+}
+
+impl BytesFastFieldsWriter {
+
+    pub fn from_schema(schema: &Schema) -> BytesFastFieldsWriter {
+        let bytes_fields: Vec<Field> = schema.field_entries()
+            .filter(|&(_, field_entry)| field_entry.is_bytes_fast())
+            .map(|(field, _)| field)
+            .collect();
+        BytesFastFieldsWriter {
+            field_writers: bytes_fields
+                .into_iter()
+                .map(BytesFastFieldWriter::new)
+                .collect(),
+        }
+    }
+
+    pub fn add_document(&mut self, doc: &Document) {
+        for field_writer in &mut self.field_writers {
+            field_writer.add_document(doc);
+        }
+    }
+
+    pub fn serialize(&self, serializer: &mut BytesFastFieldSerializer) -> io::Result<()> {
+        for field_writer in &self.field_writers {
+            try!(field_writer.serialize(serializer));
+        }
+        Ok(())
+    }
+}
+
+
+/// Writer for a single bytes fast field.
+///
+/// Documents without any value for the field are
+/// associated to an empty slice.
+pub struct BytesFastFieldWriter {
+    field: Field,
+    offsets: Vec<u32>,
+    data: Vec<u8>,
+}
+
+impl BytesFastFieldWriter {
+
+    pub fn new(field: Field) -> BytesFastFieldWriter {
+        BytesFastFieldWriter {
+            field: field,
+            offsets: vec!(0u32),
+            data: Vec::new(),
+        }
+    }
+
+    pub fn add_document(&mut self, doc: &Document) {
+        if let Some(value) = doc.get_first(self.field) {
+            match *value {
+                Value::Bytes(ref bytes) => {
+                    self.data.extend_from_slice(bytes);
+                }
+                _ => {
+                    panic!("Expected a bytes field, got {:?} ", value)
+                }
+            }
+        }
+        self.offsets.push(self.data.len() as u32);
+    }
+
+    pub fn serialize(&self, serializer: &mut BytesFastFieldSerializer) -> io::Result<()> {
+        try!(serializer.new_bytes_field(self.field, &self.offsets));
+        serializer.write_data(&self.data)
+    }
+}
+
+
+/// Reader for the bytes fast fields of a segment.
+pub struct BytesFastFieldsReader {
+    source: ReadOnlySource,
+    field_offsets: HashMap<Field, (u32, u32)>,
+}
+
+impl BytesFastFieldsReader {
+
+    pub fn open(source: ReadOnlySource) -> io::Result<BytesFastFieldsReader> {
+        if source.is_empty() {
+            // segments created before bytes fast fields
+            // were introduced do not have the component.
+            return Ok(BytesFastFieldsReader {
+                source: source,
+                field_offsets: HashMap::new(),
+            });
+        }
+        let header_offset;
+        let field_offsets: Vec<(Field, u32)>;
+        {
+            let buffer = source.as_slice();
+            {
+                let mut cursor = buffer;
+                header_offset = try!(u32::deserialize(&mut cursor));
+            }
+            {
+                let mut cursor = &buffer[header_offset as usize..];
+                field_offsets = try!(Vec::deserialize(&mut cursor));
+            }
+        }
+        let mut end_offsets: Vec<u32> = field_offsets
+            .iter()
+            .map(|&(_, offset)| offset)
+            .collect();
+        end_offsets.push(header_offset);
+        let mut field_offsets_map: HashMap<Field, (u32, u32)> = HashMap::new();
+        for (field_start_offsets, stop_offset) in field_offsets.iter().zip(end_offsets.iter().skip(1)) {
+            let (field, start_offset) = *field_start_offsets;
+            field_offsets_map.insert(field, (start_offset, *stop_offset));
+        }
+        Ok(BytesFastFieldsReader {
+            field_offsets: field_offsets_map,
+            source: source,
+        })
+    }
+
+    /// Returns true iff the field has been serialized.
+    ///
+    /// Segments created before a field was added to the
+    /// schema do not contain it.
+    pub fn has_field(&self, field: Field) -> bool {
+        self.field_offsets.contains_key(&field)
+    }
+
+    pub fn get_field(&self, field: Field) -> io::Result<BytesFastFieldReader> {
+        match self.field_offsets.get(&field) {
+            Some(&(start, stop)) => {
+                let field_source = self.source.slice(start as usize, stop as usize);
+                BytesFastFieldReader::open(field_source)
+            }
+            None => {
+                Err(io::Error::new(io::ErrorKind::InvalidInput, "Could not find field"))
+            }
+        }
+    }
+}
+
+
+/// Reader for a bytes fast field.
+///
+/// Random access to the value of a document
+/// only requires to read two offsets.
+pub struct BytesFastFieldReader {
+    offsets: ReadOnlySource,
+    data: ReadOnlySource,
+}
+
+impl BytesFastFieldReader {
+
+    pub fn open(source: ReadOnlySource) -> io::Result<BytesFastFieldReader> {
+        let num_offsets;
+        {
+            let mut cursor = source.as_slice();
+            num_offsets = try!(u32::deserialize(&mut cursor)) as usize;
+        }
+        let data_start = 4 + num_offsets * 4;
+        if data_start > source.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Bytes fast field is truncated"));
+        }
+        Ok(BytesFastFieldReader {
+            offsets: source.slice(4, data_start),
+            data: source.slice(data_start, source.len()),
+        })
+    }
+
+    /// Creates a reader in which all of the `num_docs` documents
+    /// are associated to an empty slice.
+    pub fn empty(num_docs: DocId) -> BytesFastFieldReader {
+        let mut data: Vec<u8> = Vec::new();
+        (num_docs + 1).serialize(&mut data).expect("Writing to a Vec should never fail");
+        for _ in 0..num_docs + 1 {
+            0u32.serialize(&mut data).expect("Writing to a Vec should never fail");
+        }
+        BytesFastFieldReader::open(ReadOnlySource::from(data))
+            .expect("Opening an empty bytes fast field should never fail")
+    }
+
+    fn offset(&self, ord: usize) -> usize {
+        NativeEndian::read_u32(&self.offsets.as_slice()[ord * 4..]) as usize
+    }
+
+    /// Returns the number of documents in the field.
+    pub fn num_docs(&self,) -> DocId {
+        (self.offsets.len() / 4 - 1) as DocId
+    }
+
+    /// Returns the raw offsets of the documents.
+    ///
+    /// The merger uses them to re-offset the values
+    /// without decoding them.
+    pub fn offsets(&self,) -> Vec<u32> {
+        (0..self.offsets.len() / 4)
+            .map(|ord| self.offset(ord) as u32)
+            .collect()
+    }
+
+    /// Returns all of the values, concatenated.
+    pub fn data(&self,) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    /// Returns the bytes associated to the document.
+    ///
+    /// Documents without any value return an empty slice.
+    pub fn get(&self, doc: DocId) -> &[u8] {
+        let start = self.offset(doc as usize);
+        let stop = self.offset(doc as usize + 1);
+        &self.data.as_slice()[start..stop]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::path::Path;
+    use directory::{Directory, WritePtr, RAMDirectory};
+    use schema::{Document, SchemaBuilder, BytesOptions};
+
+    #[test]
+    fn test_bytes_fastfield() {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = SchemaBuilder::default();
+        let bytes_field = schema_builder.add_bytes_field("bytes", BytesOptions::default().set_fast());
+        let schema = schema_builder.build().unwrap();
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = BytesFastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = BytesFastFieldsWriter::from_schema(&schema);
+            {
+                let mut doc = Document::default();
+                doc.add_bytes(bytes_field, vec!(1u8, 2u8, 3u8));
+                fast_field_writers.add_document(&doc);
+            }
+            fast_field_writers.add_document(&Document::default());
+            {
+                let mut doc = Document::default();
+                doc.add_bytes(bytes_field, vec!(255u8; 1_000));
+                fast_field_writers.add_document(&doc);
+            }
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        let fast_field_readers = BytesFastFieldsReader::open(source).unwrap();
+        let fast_field_reader = fast_field_readers.get_field(bytes_field).unwrap();
+        assert_eq!(fast_field_reader.num_docs(), 3);
+        assert_eq!(fast_field_reader.get(0), &[1u8, 2u8, 3u8]);
+        assert!(fast_field_reader.get(1).is_empty());
+        assert_eq!(fast_field_reader.get(2), &vec!(255u8; 1_000)[..]);
+        assert_eq!(fast_field_reader.offsets(), vec!(0, 3, 3, 1_003));
+    }
+}
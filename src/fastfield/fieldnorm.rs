@@ -0,0 +1,207 @@
+use DocId;
+use super::U32FastFieldReader;
+
+
+/// Version of the encoding of the field norms written by
+/// the `SegmentWriter` and the `IndexMerger`.
+///
+/// Version 1 stores the field norm ids of `fieldnorm_to_id`,
+/// rather than the field norms themselves.
+pub const FIELDNORMS_FORMAT_VERSION: u32 = 1;
+
+// field norms below this value are encoded exactly.
+const NUM_EXACT_FIELDNORMS: u32 = 24;
+
+// largest field norm that can be encoded,
+// greater field norms are encoded as this one.
+const MAX_FIELDNORM: u32 = i32::max_value() as u32;
+
+lazy_static! {
+    static ref FIELDNORM_TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        for (id, fieldnorm) in table.iter_mut().enumerate() {
+            *fieldnorm = decode_fieldnorm_id(id as u32);
+        }
+        table
+    };
+}
+
+// encodes `val` on its 4 most significant bits,
+// along with the position of these bits.
+fn encode_on_4_bits(val: u32) -> u32 {
+    let num_bits = 32 - val.leading_zeros();
+    if num_bits < 4 {
+        return val;
+    }
+    let shift = num_bits - 4;
+    ((val >> shift) & 7) | ((shift + 1) << 3)
+}
+
+fn decode_from_4_bits(encoded: u32) -> u32 {
+    let bits = encoded & 7;
+    let shift = encoded >> 3;
+    if shift == 0 {
+        bits
+    }
+    else {
+        (bits | 8) << (shift - 1)
+    }
+}
+
+fn decode_fieldnorm_id(id: u32) -> u32 {
+    if id < NUM_EXACT_FIELDNORMS {
+        id
+    }
+    else {
+        NUM_EXACT_FIELDNORMS + decode_from_4_bits(id - NUM_EXACT_FIELDNORMS)
+    }
+}
+
+/// Encodes a field norm in a single byte.
+///
+/// The encoding is lossy, as the classic encoding of the
+/// norms of Lucene: the field norms up to 23 are encoded
+/// exactly, and the greater ones on their 4 most significant
+/// bits, so that the relative error stays under 1/8.
+///
+/// The ids are sorted as the field norms they encode.
+pub fn fieldnorm_to_id(fieldnorm: u32) -> u8 {
+    if fieldnorm < NUM_EXACT_FIELDNORMS {
+        fieldnorm as u8
+    }
+    else {
+        let fieldnorm = if fieldnorm > MAX_FIELDNORM { MAX_FIELDNORM } else { fieldnorm };
+        (NUM_EXACT_FIELDNORMS + encode_on_4_bits(fieldnorm - NUM_EXACT_FIELDNORMS)) as u8
+    }
+}
+
+/// Decodes a field norm encoded by `fieldnorm_to_id`.
+///
+/// It returns the smallest of the field norms sharing the id.
+pub fn id_to_fieldnorm(id: u8) -> u32 {
+    FIELDNORM_TABLE[id as usize]
+}
+
+
+/// Reader of the field norms of a field, within a segment.
+///
+/// The field norms are stored as a `U32FastFieldReader` of their ids,
+/// taking at most one byte per document, and decoded on access.
+pub struct FieldNormReader {
+    reader: U32FastFieldReader,
+    // false for the segments storing the field norms
+    // themselves, before `FIELDNORMS_FORMAT_VERSION`.
+    encoded: bool,
+}
+
+impl FieldNormReader {
+
+    /// Wraps a fast field storing the ids of the field norms.
+    pub fn from_fieldnorm_ids(reader: U32FastFieldReader) -> FieldNormReader {
+        FieldNormReader {
+            reader: reader,
+            encoded: true,
+        }
+    }
+
+    /// Wraps a fast field storing the field norms themselves,
+    /// as written by the versions of tantivy that did not encode them.
+    pub fn from_fieldnorms(reader: U32FastFieldReader) -> FieldNormReader {
+        FieldNormReader {
+            reader: reader,
+            encoded: false,
+        }
+    }
+
+    /// Creates a reader returning the same field norm for all documents.
+    pub fn from_constant(fieldnorm: u32) -> FieldNormReader {
+        FieldNormReader::from_fieldnorms(U32FastFieldReader::from_constant(fieldnorm))
+    }
+
+    fn decode(&self, val: u32) -> u32 {
+        if self.encoded {
+            id_to_fieldnorm(val as u8)
+        }
+        else {
+            val
+        }
+    }
+
+    /// Returns the field norm of the document.
+    pub fn get(&self, doc: DocId) -> u32 {
+        self.decode(self.reader.get(doc))
+    }
+
+    /// Returns the id of the field norm of the document,
+    /// see `fieldnorm_to_id`.
+    pub fn get_id(&self, doc: DocId) -> u8 {
+        if self.encoded {
+            self.reader.get(doc) as u8
+        }
+        else {
+            fieldnorm_to_id(self.reader.get(doc))
+        }
+    }
+
+    /// Returns a lower bound of the field norms of the segment.
+    pub fn min_val(&self,) -> u32 {
+        self.decode(self.reader.min_val())
+    }
+
+    /// Returns an upper bound of the field norms of the segment.
+    pub fn max_val(&self,) -> u32 {
+        self.decode(self.reader.max_val())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_fieldnorm_to_id() {
+        for fieldnorm in 0..NUM_EXACT_FIELDNORMS {
+            assert_eq!(id_to_fieldnorm(fieldnorm_to_id(fieldnorm)), fieldnorm);
+        }
+        assert_eq!(id_to_fieldnorm(fieldnorm_to_id(24)), 24);
+        assert_eq!(id_to_fieldnorm(fieldnorm_to_id(1_000)), 984);
+        assert_eq!(fieldnorm_to_id(u32::max_value()), 255);
+        assert_eq!(fieldnorm_to_id(MAX_FIELDNORM), 255);
+        let mut prev_fieldnorm = 0u32;
+        for id in 1..256u32 {
+            let fieldnorm = id_to_fieldnorm(id as u8);
+            // the ids are sorted, and each is the id of its own field norm.
+            assert!(fieldnorm > prev_fieldnorm);
+            assert_eq!(fieldnorm_to_id(fieldnorm) as u32, id);
+            assert_eq!(fieldnorm_to_id(fieldnorm - 1) as u32, id - 1);
+            prev_fieldnorm = fieldnorm;
+        }
+    }
+
+    #[test]
+    fn test_fieldnorm_relative_error() {
+        let mut fieldnorm = 1u32;
+        while fieldnorm < MAX_FIELDNORM / 2 {
+            for &val in &[fieldnorm, fieldnorm + 1, fieldnorm * 3 / 2] {
+                let decoded = id_to_fieldnorm(fieldnorm_to_id(val));
+                assert!(decoded <= val);
+                assert!(((val - decoded) as f64) < val as f64 / 8f64);
+            }
+            fieldnorm *= 2;
+        }
+    }
+
+    #[test]
+    fn test_fieldnorm_reader() {
+        let fieldnorms = FieldNormReader::from_fieldnorms(U32FastFieldReader::from_constant(1_000));
+        assert_eq!(fieldnorms.get(3), 1_000);
+        assert_eq!(fieldnorms.get_id(3), fieldnorm_to_id(1_000));
+        let fieldnorm_ids = FieldNormReader::from_fieldnorm_ids(U32FastFieldReader::from_constant(fieldnorm_to_id(1_000) as u32));
+        assert_eq!(fieldnorm_ids.get(3), 984);
+        assert_eq!(fieldnorm_ids.get_id(3), fieldnorm_to_id(1_000));
+        assert_eq!(fieldnorm_ids.min_val(), 984);
+        assert_eq!(fieldnorm_ids.max_val(), 984);
+    }
+}
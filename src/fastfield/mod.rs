@@ -8,15 +8,30 @@
 /// They are useful when a field is required for all or most of
 /// the `DocSet` : for instance for scoring, grouping, filtering, or facetting.
 /// 
-/// Currently only u32 fastfield are supported.
+/// Currently u32, i64 and f64 fastfield are supported.
+/// u32 fast fields may also be multivalued.
+/// i64 and f64 values are mapped to u64 in an order preserving
+/// way before being bitpacked.
+/// IP addresses are stored as two u64 columns.
+/// Untokenized text fields may record the ordinals of their
+/// terms, as a multivalued u32 fast field.
 
 mod reader;
 mod writer;
 mod serializer;
+mod bytes;
+mod str_fast_field_reader;
+mod fieldnorm;
 
-pub use self::writer::{U32FastFieldsWriter, U32FastFieldWriter};
-pub use self::reader::{U32FastFieldsReader, U32FastFieldReader};
+pub use self::writer::{U32FastFieldsWriter, U32FastFieldWriter, U64FastFieldWriter, MultiU32FastFieldWriter};
+pub use self::writer::{IpFastFieldWriter, TermOrdsFastFieldWriter};
+pub use self::reader::{U32FastFieldsReader, U32FastFieldReader, U64FastFieldReader, I64FastFieldReader, F64FastFieldReader};
+pub use self::reader::{MultiU32FastFieldReader, BoolFastFieldReader, IpFastFieldReader};
 pub use self::serializer::FastFieldSerializer;
+pub use self::bytes::{BytesFastFieldSerializer, BytesFastFieldsWriter, BytesFastFieldWriter};
+pub use self::bytes::{BytesFastFieldsReader, BytesFastFieldReader};
+pub use self::str_fast_field_reader::StrFastFieldReader;
+pub use self::fieldnorm::{FieldNormReader, fieldnorm_to_id, id_to_fieldnorm, FIELDNORMS_FORMAT_VERSION};
 
 fn count_leading_zeros(mut val: u32) -> u8 {
     if val == 0 {
@@ -35,10 +50,15 @@ fn compute_num_bits(amplitude: u32) -> u8 {
     32u8 - count_leading_zeros(amplitude)
 }
 
+fn compute_num_bits_u64(amplitude: u64) -> u8 {
+    64u8 - amplitude.leading_zeros() as u8
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::compute_num_bits;
+    use super::compute_num_bits_u64;
     use super::U32FastFieldsReader;
     use super::U32FastFieldsWriter;
     use super::FastFieldSerializer;
@@ -48,6 +68,12 @@ mod tests {
     use schema::Document;
     use schema::{Schema, SchemaBuilder};
     use schema::FAST;
+    use schema::{U32Options, Cardinality};
+    use schema::I64Options;
+    use schema::F64Options;
+    use schema::{IpOptions, parse_ip};
+    use std::net::IpAddr;
+    use std::str::FromStr;
     use test::Bencher;
     use test;
     use rand::Rng;
@@ -58,7 +84,7 @@ mod tests {
         static ref SCHEMA: Schema = {
             let mut schema_builder = SchemaBuilder::default();
             schema_builder.add_u32_field("field", FAST);
-            schema_builder.build()
+            schema_builder.build().unwrap()
         };
         static ref FIELD: Field = { 
             SCHEMA.get_field("field").unwrap()
@@ -76,6 +102,199 @@ mod tests {
         assert_eq!(compute_num_bits(256), 9u8);
     }
 
+    #[test]
+    fn test_compute_num_bits_u64() {
+        assert_eq!(compute_num_bits_u64(0), 0u8);
+        assert_eq!(compute_num_bits_u64(1), 1u8);
+        assert_eq!(compute_num_bits_u64(256), 9u8);
+        assert_eq!(compute_num_bits_u64(u32::max_value() as u64 + 1), 33u8);
+        assert_eq!(compute_num_bits_u64(u64::max_value()), 64u8);
+    }
+
+    fn test_intfastfield_i64_helper(vals: &[i64]) {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = SchemaBuilder::default();
+        let i64_field = schema_builder.add_i64_field("field", I64Options::default().set_fast());
+        let schema = schema_builder.build().unwrap();
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = U32FastFieldsWriter::from_schema(&schema);
+            for &val in vals {
+                let mut doc = Document::default();
+                doc.add_i64(i64_field, val);
+                fast_field_writers.add_document(&doc);
+            }
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            let fast_field_readers = U32FastFieldsReader::open(source).unwrap();
+            let fast_field_reader = fast_field_readers.get_i64_field(i64_field).unwrap();
+            for (doc, &val) in vals.iter().enumerate() {
+                assert_eq!(fast_field_reader.get(doc as u32), val);
+            }
+            assert_eq!(fast_field_reader.min_val(), *vals.iter().min().unwrap());
+            assert_eq!(fast_field_reader.max_val(), *vals.iter().max().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_fastfield_f64() {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = SchemaBuilder::default();
+        let f64_field = schema_builder.add_f64_field("field", F64Options::default().set_fast());
+        let schema = schema_builder.build().unwrap();
+        let vals = [3.5f64, -1e100f64, 0f64, 1e-100f64, -0.25f64];
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = U32FastFieldsWriter::from_schema(&schema);
+            for &val in &vals {
+                let mut doc = Document::default();
+                doc.add_f64(f64_field, val);
+                fast_field_writers.add_document(&doc);
+            }
+            // missing value
+            fast_field_writers.add_document(&Document::default());
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            let fast_field_readers = U32FastFieldsReader::open(source).unwrap();
+            let fast_field_reader = fast_field_readers.get_f64_field(f64_field).unwrap();
+            for (doc, &val) in vals.iter().enumerate() {
+                assert_eq!(fast_field_reader.get(doc as u32), val);
+                assert_eq!(fast_field_reader.get_f64(doc as u32), val);
+            }
+            assert_eq!(fast_field_reader.get(vals.len() as u32), 0f64);
+            assert_eq!(fast_field_reader.min_val(), -1e100f64);
+            assert_eq!(fast_field_reader.max_val(), 3.5f64);
+        }
+    }
+
+    #[test]
+    fn test_multivalued_fastfield() {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = SchemaBuilder::default();
+        let u32_options = U32Options::default().set_fast().set_cardinality(Cardinality::MultiValues);
+        let multi_field = schema_builder.add_u32_field("multi", u32_options);
+        let single_field = schema_builder.add_u32_field("single", FAST);
+        let schema = schema_builder.build().unwrap();
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = U32FastFieldsWriter::from_schema(&schema);
+            {
+                let mut doc = Document::default();
+                doc.add_u32(multi_field, 5u32);
+                doc.add_u32(single_field, 1u32);
+                doc.add_u32(multi_field, 3u32);
+                doc.add_u32(multi_field, 1_000u32);
+                fast_field_writers.add_document(&doc);
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_u32(single_field, 2u32);
+                fast_field_writers.add_document(&doc);
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_u32(multi_field, 7u32);
+                fast_field_writers.add_document(&doc);
+            }
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            let fast_field_readers = U32FastFieldsReader::open(source).unwrap();
+            let multi_reader = fast_field_readers.get_multivalued_field(multi_field).unwrap();
+            let mut vals = Vec::new();
+            multi_reader.get_vals(0, &mut vals);
+            assert_eq!(vals, vec!(5u32, 3u32, 1_000u32));
+            multi_reader.get_vals(1, &mut vals);
+            assert!(vals.is_empty());
+            multi_reader.get_vals(2, &mut vals);
+            assert_eq!(vals, vec!(7u32));
+            assert_eq!(multi_reader.num_vals(0), 3);
+            assert_eq!(multi_reader.num_vals(1), 0);
+            let single_reader = fast_field_readers.get_field(single_field).unwrap();
+            assert_eq!(single_reader.get(0), 1u32);
+            assert_eq!(single_reader.get(1), 2u32);
+            assert_eq!(single_reader.get(2), 0u32);
+        }
+    }
+
+    #[test]
+    fn test_term_ords_mem_usage() {
+        let mut term_ords_writer = super::TermOrdsFastFieldWriter::new(Field(0));
+        assert_eq!(term_ords_writer.mem_usage(), 0);
+        term_ords_writer.add_term(b"france");
+        term_ords_writer.add_document();
+        let mem_usage = term_ords_writer.mem_usage();
+        assert!(mem_usage >= 6);
+        // the terms already seen do not use any more memory.
+        term_ords_writer.add_term(b"france");
+        term_ords_writer.add_document();
+        assert_eq!(term_ords_writer.mem_usage(), mem_usage);
+        for i in 0..1_000 {
+            term_ords_writer.add_term(format!("country{}", i).as_bytes());
+            term_ords_writer.add_document();
+        }
+        assert!(term_ords_writer.mem_usage() >= mem_usage + 8_000);
+    }
+
+    #[test]
+    fn test_ip_fastfield() {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = SchemaBuilder::default();
+        let ip_field = schema_builder.add_ip_field("ip", IpOptions::default().set_fast());
+        let schema = schema_builder.build().unwrap();
+        let ips = ["10.0.0.1", "fe80::1:2", "192.168.1.254"];
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = U32FastFieldsWriter::from_schema(&schema);
+            for ip_text in &ips {
+                let mut doc = Document::default();
+                doc.add_ip(ip_field, IpAddr::from_str(ip_text).unwrap());
+                fast_field_writers.add_document(&doc);
+            }
+            fast_field_writers.add_document(&Document::default());
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            let fast_field_readers = U32FastFieldsReader::open(source).unwrap();
+            let ip_reader = fast_field_readers.get_ip_field(ip_field).unwrap();
+            for (doc, ip_text) in ips.iter().enumerate() {
+                assert_eq!(ip_reader.get(doc as u32), parse_ip(ip_text).unwrap());
+            }
+            assert_eq!(ip_reader.get(3), parse_ip("::").unwrap());
+            assert_eq!(ip_reader.high_reader().get(0), 0u64);
+            assert_eq!(ip_reader.low_reader().get(0), 0xffff_0a00_0001u64);
+        }
+    }
+
+    #[test]
+    fn test_intfastfield_i64() {
+        test_intfastfield_i64_helper(&[-100i64, 0i64, 13i64, -14i64, 2i64]);
+        test_intfastfield_i64_helper(&[7i64; 100]);
+        test_intfastfield_i64_helper(&[i64::min_value(), 0i64, i64::max_value(), -1i64, 1i64]);
+        let vals: Vec<i64> = (0i64..10_000i64)
+            .map(|i| (i - 5_000i64) * 1_000_000_007i64)
+            .collect();
+        test_intfastfield_i64_helper(&vals);
+    }
+
     fn add_single_field_doc(fast_field_writers: &mut U32FastFieldsWriter, field: Field, value: u32) {
         let mut doc = Document::default();
         doc.add_u32(field, value);
@@ -177,6 +396,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_intfastfield_bitpacked_size() {
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = U32FastFieldsWriter::from_schema(&SCHEMA);
+            for i in 0..10_000u32 {
+                add_single_field_doc(&mut fast_field_writers, *FIELD, 1_000_000u32 + i % 8);
+            }
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            // the values take 3 bits per document rather than 32,
+            // that is 938 u32 words, on top of the 22 bytes
+            // of the headers and of the padding.
+            assert_eq!(source.len(), 22 + 938 * 4 as usize);
+            assert!(source.len() * 8 < 10_000 * 4);
+        }
+        {
+            let fast_field_readers = U32FastFieldsReader::open(source).unwrap();
+            let fast_field_reader = fast_field_readers.get_field(*FIELD).unwrap();
+            assert_eq!(fast_field_reader.min_val(), 1_000_000u32);
+            assert_eq!(fast_field_reader.max_val(), 1_000_007u32);
+            for doc in 0..10_000u32 {
+                assert_eq!(fast_field_reader.get(doc), 1_000_000u32 + doc % 8);
+            }
+        }
+    }
+
     fn generate_permutation() -> Vec<u32> {
         let seed: &[u32; 4] = &[1, 2, 3, 4];
         let mut rng = XorShiftRng::from_seed(*seed);
@@ -298,4 +550,35 @@ mod tests {
             });
         }
     }
+
+    #[bench]
+    fn bench_intfastfield_bitpacked_fflookup(b: &mut Bencher) {
+        // same as `bench_intfastfield_linear_fflookup`,
+        // on a field whose values take 3 bits.
+        let path = Path::new("test");
+        let mut directory: RAMDirectory = RAMDirectory::create();
+        {
+            let write: WritePtr = directory.open_write(Path::new("test")).unwrap();
+            let mut serializer = FastFieldSerializer::new(write).unwrap();
+            let mut fast_field_writers = U32FastFieldsWriter::from_schema(&SCHEMA);
+            for i in 0..10_000u32 {
+                add_single_field_doc(&mut fast_field_writers, *FIELD, 1_000_000u32 + i % 8);
+            }
+            fast_field_writers.serialize(&mut serializer).unwrap();
+            serializer.close().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        {
+            let fast_field_readers = U32FastFieldsReader::open(source).unwrap();
+            let fast_field_reader = fast_field_readers.get_field(*FIELD).unwrap();
+            b.iter(|| {
+                let n = test::black_box(7000u32);
+                let mut a = 0u32;
+                for i in (0u32..n).step_by(7) {
+                    a ^= fast_field_reader.get(i);
+                }
+                a
+            });
+        }
+    }
 }
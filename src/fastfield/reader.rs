@@ -6,9 +6,18 @@ use directory::ReadOnlySource;
 use common::BinarySerializable;
 use DocId;
 use schema::Field;
+use common::u64_to_i64;
+use common::u64_to_f64;
+use common::i64_to_u64;
+use common::f64_to_u64;
+use std::net::Ipv6Addr;
+use byteorder::{BigEndian, ByteOrder};
 
 use super::compute_num_bits;
+use super::compute_num_bits_u64;
 
+/// Reader of the values of a u32 fast field of a segment,
+/// as returned by `SegmentReader::get_fast_field_reader`.
 pub struct U32FastFieldReader {
     _data: ReadOnlySource,
     data_ptr: *const u8,
@@ -20,14 +29,36 @@ pub struct U32FastFieldReader {
 
 impl U32FastFieldReader {
 
+    /// Returns the smallest value of the fast field.
+    ///
+    /// It is read from the header of the field, without scanning
+    /// the values. For a single-valued fast field, the min and max
+    /// values also account for the values of the field following
+    /// the first value of a document, which are not stored, so that
+    /// they bound all of the values of the field in the segment.
     pub fn min_val(&self,) -> u32 {
         self.min_val
     }
 
+    /// Returns the largest value of the fast field.
+    ///
+    /// As for `min_val`, it is read from the header of the field.
     pub fn max_val(&self,) -> u32 {
         self.max_val
     }
 
+    /// Creates a reader returning the same value for all documents.
+    pub fn from_constant(val: u32) -> U32FastFieldReader {
+        let mut data: Vec<u8> = Vec::new();
+        val.serialize(&mut data).expect("Writing to a Vec should never fail");
+        0u32.serialize(&mut data).expect("Writing to a Vec should never fail");
+        // padding, as the reader reads 8 bytes at a time.
+        data.extend_from_slice(&[0u8; 8]);
+        U32FastFieldReader::open(ReadOnlySource::from(data))
+            .expect("Opening a constant fast field should never fail")
+    }
+
+    /// Opens the fast field serialized in `data`.
     pub fn open(data: ReadOnlySource) -> io::Result<U32FastFieldReader> {
         let min_val;
         let amplitude;
@@ -49,6 +80,7 @@ impl U32FastFieldReader {
         })
     }
 
+    /// Returns the value of the fast field for the document `doc`.
     pub fn get(&self, doc: DocId) -> u32 {
         if self.num_bits == 0u32 {
             return self.min_val;
@@ -62,9 +94,321 @@ impl U32FastFieldReader {
     }
 }
 
+/// Reader for a u64 fast field.
+///
+/// Values are bitpacked, and may straddle over
+/// 9 bytes.
+pub struct U64FastFieldReader {
+    _data: ReadOnlySource,
+    data_ptr: *const u8,
+    min_val: u64,
+    max_val: u64,
+    num_bits: u64,
+    mask: u64,
+}
+
+impl U64FastFieldReader {
+
+    pub fn min_val(&self,) -> u64 {
+        self.min_val
+    }
+
+    pub fn max_val(&self,) -> u64 {
+        self.max_val
+    }
+
+    /// Creates a reader returning the same value for all documents.
+    pub fn from_constant(val: u64) -> U64FastFieldReader {
+        let mut data: Vec<u8> = Vec::new();
+        val.serialize(&mut data).expect("Writing to a Vec should never fail");
+        0u64.serialize(&mut data).expect("Writing to a Vec should never fail");
+        // padding, as the reader reads 8 bytes at a time.
+        data.extend_from_slice(&[0u8; 8]);
+        U64FastFieldReader::open(ReadOnlySource::from(data))
+            .expect("Opening a constant fast field should never fail")
+    }
+
+    pub fn open(data: ReadOnlySource) -> io::Result<U64FastFieldReader> {
+        let min_val;
+        let amplitude;
+        {
+            let mut cursor = data.as_slice();
+            min_val = try!(u64::deserialize(&mut cursor));
+            amplitude = try!(u64::deserialize(&mut cursor));
+        }
+        let num_bits = compute_num_bits_u64(amplitude);
+        let mask = if num_bits == 64 {
+            u64::max_value()
+        }
+        else {
+            (1u64 << num_bits) - 1
+        };
+        let ptr: *const u8 = &(data.deref()[16 as usize]);
+        Ok(U64FastFieldReader {
+            _data: data,
+            data_ptr: ptr,
+            min_val: min_val,
+            max_val: min_val + amplitude,
+            num_bits: num_bits as u64,
+            mask: mask,
+        })
+    }
+
+    pub fn get(&self, doc: DocId) -> u64 {
+        if self.num_bits == 0u64 {
+            return self.min_val;
+        }
+        let bit_addr = (doc as u64) * self.num_bits;
+        let addr = bit_addr / 8;
+        let bit_shift = bit_addr - addr * 8;
+        let val_unshifted_unmasked: u64 = unsafe { * (self.data_ptr.offset(addr as isize) as *const u64) };
+        let mut val_shifted = val_unshifted_unmasked >> bit_shift;
+        if bit_shift + self.num_bits > 64 {
+            // the value straddles over the next byte.
+            let high_byte: u64 = unsafe { * self.data_ptr.offset(addr as isize + 8) } as u64;
+            val_shifted |= high_byte << (64 - bit_shift);
+        }
+        self.min_val + (val_shifted & self.mask)
+    }
+}
+
+
+/// Reader for an i64 fast field.
+///
+/// i64 are stored as u64 fast field, after
+/// going through an order preserving mapping.
+pub struct I64FastFieldReader {
+    underlying: U64FastFieldReader,
+}
+
+impl I64FastFieldReader {
+
+    pub fn min_val(&self,) -> i64 {
+        u64_to_i64(self.underlying.min_val())
+    }
+
+    pub fn max_val(&self,) -> i64 {
+        u64_to_i64(self.underlying.max_val())
+    }
+
+    /// Creates a reader returning the same value for all documents.
+    pub fn from_constant(val: i64) -> I64FastFieldReader {
+        I64FastFieldReader { underlying: U64FastFieldReader::from_constant(i64_to_u64(val)) }
+    }
+
+    pub fn open(data: ReadOnlySource) -> io::Result<I64FastFieldReader> {
+        U64FastFieldReader::open(data)
+            .map(|underlying| I64FastFieldReader { underlying: underlying })
+    }
+
+    pub fn get(&self, doc: DocId) -> i64 {
+        u64_to_i64(self.underlying.get(doc))
+    }
+}
+
+/// Reader for a bool fast field.
+///
+/// bool are stored as a u32 fast field,
+/// `false` being `0` and `true` being `1`.
+pub struct BoolFastFieldReader {
+    underlying: U32FastFieldReader,
+}
+
+impl BoolFastFieldReader {
+
+    /// Creates a reader returning the same value for all documents.
+    pub fn from_constant(val: bool) -> BoolFastFieldReader {
+        BoolFastFieldReader { underlying: U32FastFieldReader::from_constant(val as u32) }
+    }
+
+    pub fn open(data: ReadOnlySource) -> io::Result<BoolFastFieldReader> {
+        U32FastFieldReader::open(data)
+            .map(|underlying| BoolFastFieldReader { underlying: underlying })
+    }
+
+    pub fn get(&self, doc: DocId) -> bool {
+        self.underlying.get(doc) != 0u32
+    }
+}
+
+/// Reader for an f64 fast field.
+///
+/// f64 are stored as u64 fast field, after
+/// going through an order preserving mapping.
+pub struct F64FastFieldReader {
+    underlying: U64FastFieldReader,
+}
+
+impl F64FastFieldReader {
+
+    /// Returns the smallest value of the fast field.
+    pub fn min_val(&self,) -> f64 {
+        u64_to_f64(self.underlying.min_val())
+    }
+
+    /// Returns the largest value of the fast field.
+    pub fn max_val(&self,) -> f64 {
+        u64_to_f64(self.underlying.max_val())
+    }
+
+    /// Creates a reader returning the same value for all documents.
+    pub fn from_constant(val: f64) -> F64FastFieldReader {
+        F64FastFieldReader { underlying: U64FastFieldReader::from_constant(f64_to_u64(val)) }
+    }
+
+    /// Opens the f64 fast field serialized in `data`.
+    pub fn open(data: ReadOnlySource) -> io::Result<F64FastFieldReader> {
+        U64FastFieldReader::open(data)
+            .map(|underlying| F64FastFieldReader { underlying: underlying })
+    }
+
+    /// Returns the value of the document, like the `get`
+    /// of the other fast field readers.
+    pub fn get(&self, doc: DocId) -> f64 {
+        u64_to_f64(self.underlying.get(doc))
+    }
+
+    /// Returns the f64 value of the document.
+    ///
+    /// Same as `get`.
+    pub fn get_f64(&self, doc: DocId) -> f64 {
+        self.get(doc)
+    }
+}
+
+/// Reader for an IP address fast field.
+///
+/// The field is stored as two u64 fast fields, containing
+/// respectively the high and the low 64 bits of the IPv6 form
+/// of the addresses.
+pub struct IpFastFieldReader {
+    high_reader: U64FastFieldReader,
+    low_reader: U64FastFieldReader,
+}
+
+impl IpFastFieldReader {
+
+    /// Creates a reader returning the same address for all documents.
+    pub fn from_constant(ipv6: Ipv6Addr) -> IpFastFieldReader {
+        let octets = ipv6.octets();
+        IpFastFieldReader {
+            high_reader: U64FastFieldReader::from_constant(BigEndian::read_u64(&octets[..8])),
+            low_reader: U64FastFieldReader::from_constant(BigEndian::read_u64(&octets[8..])),
+        }
+    }
+
+    pub fn open(high_data: ReadOnlySource, low_data: ReadOnlySource) -> io::Result<IpFastFieldReader> {
+        let high_reader = try!(U64FastFieldReader::open(high_data));
+        let low_reader = try!(U64FastFieldReader::open(low_data));
+        Ok(IpFastFieldReader {
+            high_reader: high_reader,
+            low_reader: low_reader,
+        })
+    }
+
+    /// Returns the address associated to the document, in its IPv6 form.
+    ///
+    /// Documents without any value are associated to `::`.
+    pub fn get(&self, doc: DocId) -> Ipv6Addr {
+        let mut octets = [0u8; 16];
+        BigEndian::write_u64(&mut octets[..8], self.high_reader.get(doc));
+        BigEndian::write_u64(&mut octets[8..], self.low_reader.get(doc));
+        Ipv6Addr::from(octets)
+    }
+
+    /// Accessor to the high 64 bits of the addresses.
+    ///
+    /// Bucketing documents by their high 64 bits is
+    /// a cheap way to group them by IPv6 subnet.
+    pub fn high_reader(&self,) -> &U64FastFieldReader {
+        &self.high_reader
+    }
+
+    /// Accessor to the low 64 bits of the addresses.
+    ///
+    /// For IPv4 addresses, the IPv4 address is the
+    /// lowest 32 bits of the low part.
+    pub fn low_reader(&self,) -> &U64FastFieldReader {
+        &self.low_reader
+    }
+}
+
+/// Reader for a multivalued u32 fast field.
+///
+/// The field is stored as two u32 fast fields :
+///
+/// * an index, associating to each document the offset
+///   of its first value. It contains `max_doc + 1` values,
+///   the last one being the overall number of values.
+/// * the values, concatenated.
+pub struct MultiU32FastFieldReader {
+    idx_reader: U32FastFieldReader,
+    vals_reader: U32FastFieldReader,
+}
+
+impl MultiU32FastFieldReader {
+
+    /// Creates a reader in which no document has any value.
+    pub fn empty() -> MultiU32FastFieldReader {
+        MultiU32FastFieldReader {
+            idx_reader: U32FastFieldReader::from_constant(0u32),
+            vals_reader: U32FastFieldReader::from_constant(0u32),
+        }
+    }
+
+    pub fn open(idx_data: ReadOnlySource, vals_data: ReadOnlySource) -> io::Result<MultiU32FastFieldReader> {
+        let idx_reader = try!(U32FastFieldReader::open(idx_data));
+        let vals_reader = try!(U32FastFieldReader::open(vals_data));
+        Ok(MultiU32FastFieldReader {
+            idx_reader: idx_reader,
+            vals_reader: vals_reader,
+        })
+    }
+
+    fn range(&self, doc: DocId) -> (u32, u32) {
+        let start = self.idx_reader.get(doc);
+        let stop = self.idx_reader.get(doc + 1);
+        (start, stop)
+    }
+
+    /// Returns the number of values associated to the document.
+    pub fn num_vals(&self, doc: DocId) -> u32 {
+        let (start, stop) = self.range(doc);
+        stop - start
+    }
+
+    /// Fills `vals` with the values associated to the document,
+    /// in the order in which they were added.
+    ///
+    /// The buffer is cleared beforehand, so that it can be reused
+    /// from one document to the other.
+    pub fn get_vals(&self, doc: DocId, vals: &mut Vec<u32>) {
+        vals.clear();
+        let (start, stop) = self.range(doc);
+        for val_id in start..stop {
+            vals.push(self.vals_reader.get(val_id));
+        }
+    }
+
+    /// Accessor to the index (doc -> offset of its first value).
+    ///
+    /// The merger uses it to re-offset the values.
+    pub fn idx_reader(&self,) -> &U32FastFieldReader {
+        &self.idx_reader
+    }
+
+    /// Accessor to the concatenated values.
+    pub fn vals_reader(&self,) -> &U32FastFieldReader {
+        &self.vals_reader
+    }
+}
+
 pub struct U32FastFieldsReader {
     source: ReadOnlySource,
-    field_offsets: HashMap<Field, (u32, u32)>,
+    // a field may be associated to more than one column.
+    // (multivalued fast fields have an index and a value column,
+    // IP fast fields have a high and a low column)
+    field_offsets: HashMap<Field, Vec<(u32, u32)>>,
 }
 
 impl U32FastFieldsReader {
@@ -87,10 +431,13 @@ impl U32FastFieldsReader {
             .map(|&(_, offset)| offset)
             .collect();
         end_offsets.push(header_offset);
-        let mut field_offsets_map: HashMap<Field, (u32, u32)> = HashMap::new();
+        let mut field_offsets_map: HashMap<Field, Vec<(u32, u32)>> = HashMap::new();
         for (field_start_offsets, stop_offset) in field_offsets.iter().zip(end_offsets.iter().skip(1)) {
             let (field, start_offset) = *field_start_offsets;
-            field_offsets_map.insert(field, (start_offset, *stop_offset));
+            field_offsets_map
+                .entry(field)
+                .or_insert_with(Vec::new)
+                .push((start_offset, *stop_offset));
         }
         Ok(U32FastFieldsReader {
             field_offsets: field_offsets_map,
@@ -98,17 +445,59 @@ impl U32FastFieldsReader {
         })
     }
     
-    pub fn get_field(&self, field: Field) -> io::Result<U32FastFieldReader> {
-        match self.field_offsets.get(&field) {
+    /// Returns true iff the field has been serialized.
+    ///
+    /// Segments created before a field was added to the
+    /// schema do not contain it.
+    pub fn has_field(&self, field: Field) -> bool {
+        self.field_offsets.contains_key(&field)
+    }
+    
+    fn field_source(&self, field: Field) -> io::Result<ReadOnlySource> {
+        self.column_source(field, 0)
+    }
+    
+    fn column_source(&self, field: Field, column: usize) -> io::Result<ReadOnlySource> {
+        match self.field_offsets.get(&field).and_then(|columns| columns.get(column)) {
             Some(&(start, stop)) => {
-                let field_source = self.source.slice(start as usize, stop as usize);
-                U32FastFieldReader::open(field_source)
+                Ok(self.source.slice(start as usize, stop as usize))
             }
             None => {
                 Err(io::Error::new(io::ErrorKind::InvalidInput, "Could not find field"))
             }
 
         }
+    }
+    
+    pub fn get_multivalued_field(&self, field: Field) -> io::Result<MultiU32FastFieldReader> {
+        let idx_source = try!(self.column_source(field, 0));
+        let vals_source = try!(self.column_source(field, 1));
+        MultiU32FastFieldReader::open(idx_source, vals_source)
+    }
+    
+    pub fn get_ip_field(&self, field: Field) -> io::Result<IpFastFieldReader> {
+        let high_source = try!(self.column_source(field, 0));
+        let low_source = try!(self.column_source(field, 1));
+        IpFastFieldReader::open(high_source, low_source)
+    }
+    
+    pub fn get_field(&self, field: Field) -> io::Result<U32FastFieldReader> {
+        self.field_source(field).and_then(U32FastFieldReader::open)
+    }
+
+    pub fn get_u64_field(&self, field: Field) -> io::Result<U64FastFieldReader> {
+        self.field_source(field).and_then(U64FastFieldReader::open)
+    }
+
+    pub fn get_i64_field(&self, field: Field) -> io::Result<I64FastFieldReader> {
+        self.field_source(field).and_then(I64FastFieldReader::open)
+    }
+
+    pub fn get_bool_field(&self, field: Field) -> io::Result<BoolFastFieldReader> {
+        self.field_source(field).and_then(BoolFastFieldReader::open)
+    }
 
+    pub fn get_f64_field(&self, field: Field) -> io::Result<F64FastFieldReader> {
+        self.field_source(field).and_then(F64FastFieldReader::open)
     }
 }
@@ -1,14 +1,21 @@
 use common::BinarySerializable;
-use directory::WritePtr;
+use directory::{self, WritePtr};
 use schema::Field;
 use std::io::{self, SeekFrom, Seek, Write};
 use super::compute_num_bits;
+use super::compute_num_bits_u64;
 
 
 /// `FastFieldSerializer` is in charge of serializing
 /// fastfields on disk.
 /// 
 /// Fast fields are encoded using bit-packing.
+///
+/// Each field starts with its minimum value and its amplitude,
+/// from which the number of bits per value is derived, followed
+/// by `val - min_value` for each document, packed with that number
+/// of bits. A field whose values fit in 3 bits takes 3 bits per
+/// document, and the value of a document is read in `O(1)`.
 /// 
 /// `FastFieldWriter`s are in charge of pushing the data to
 /// the serializer.
@@ -25,6 +32,17 @@ use super::compute_num_bits;
 /// * ...
 /// * `close_field()`
 /// * `close()`
+///
+/// 64-bits fast fields follow the same protocol, using
+/// `new_u64_fast_field(...)` and `add_val_u64(...)`.
+///
+/// Multivalued u32 fast fields are serialized as two u32 fast fields
+/// for the same field : the index first, then the values.
+/// The term ordinals of the fast text fields are serialized
+/// the same way.
+///
+/// Similarly, IP fast fields are serialized as two u64 fast fields
+/// for the same field : the high 64 bits first, then the low 64 bits.
 pub struct FastFieldSerializer {
     write: WritePtr,
     written_size: usize,
@@ -35,6 +53,9 @@ pub struct FastFieldSerializer {
     
     mini_buffer_written: usize,
     mini_buffer: u32,
+
+    min_value_u64: u64,
+    mini_buffer_u64: u64,
 }
 
 
@@ -54,6 +75,9 @@ impl FastFieldSerializer {
             
             mini_buffer_written: 0,
             mini_buffer: 0u32,
+
+            min_value_u64: 0u64,
+            mini_buffer_u64: 0u64,
         })
     }
     
@@ -80,6 +104,67 @@ impl FastFieldSerializer {
     }
 
 
+    /// Start serializing a new u64 fast field
+    ///
+    /// The field is encoded the same way as u32 fast field,
+    /// except that the minimum value and the amplitude are
+    /// written as u64, and the bits are packed into u64 words.
+    pub fn new_u64_fast_field(&mut self, field: Field, min_value: u64, max_value: u64) -> io::Result<()> {
+        if self.field_open {
+            return Err(io::Error::new(io::ErrorKind::Other, "Previous field not closed"));
+        }
+        self.min_value_u64 = min_value;
+        self.field_open = true;
+        self.fields.push((field, self.written_size as u32));
+        let write: &mut Write = &mut self.write;
+        self.written_size += try!(min_value.serialize(write));
+        let amplitude = max_value - min_value;
+        self.written_size += try!(amplitude.serialize(write));
+        self.num_bits = compute_num_bits_u64(amplitude);
+        Ok(())
+    }
+
+    /// Pushes a new value to the currently open u64 fast field. 
+    pub fn add_val_u64(&mut self, val: u64) -> io::Result<()> {
+        let write: &mut Write = &mut self.write;
+        let val_to_write: u64 = val - self.min_value_u64;
+        if self.mini_buffer_written + self.num_bits as usize > 64 {
+            self.mini_buffer_u64 |= val_to_write.wrapping_shl(self.mini_buffer_written as u32);
+            self.written_size += try!(self.mini_buffer_u64.serialize(write));
+            // overflow of the shift operand is guarded here by the if case.
+            self.mini_buffer_u64 = val_to_write.wrapping_shr(64u32 - self.mini_buffer_written as u32);
+            self.mini_buffer_written = self.mini_buffer_written + (self.num_bits as usize) - 64;
+        }
+        else {
+            self.mini_buffer_u64 |= val_to_write.wrapping_shl(self.mini_buffer_written as u32);
+            self.mini_buffer_written += self.num_bits as usize;
+            if self.mini_buffer_written == 64 {
+                self.written_size += try!(self.mini_buffer_u64.serialize(write));
+                self.mini_buffer_written = 0;
+                self.mini_buffer_u64 = 0u64;
+            }    
+        }
+        Ok(())
+    }
+
+    /// Close the u64 fast field. 
+    pub fn close_field_u64(&mut self,) -> io::Result<()> {
+        if !self.field_open {
+            return Err(io::Error::new(io::ErrorKind::Other, "Current field is already closed"));
+        }
+        self.field_open = false;
+        if self.mini_buffer_written > 0 {
+            self.mini_buffer_written = 0;
+            self.written_size += try!(self.mini_buffer_u64.serialize(&mut self.write));
+        }
+        // adding some padding to make sure we
+        // can read the last elements with our u64
+        // cursor, as well as the straddling byte.
+        self.written_size += try!(0u64.serialize(&mut self.write));
+        self.mini_buffer_u64 = 0;
+        Ok(())
+    }
+
     /// Pushes a new value to the currently open u32 fast field. 
     pub fn add_val(&mut self, val: u32) -> io::Result<()> {
         let write: &mut Write = &mut self.write;
@@ -133,7 +218,7 @@ impl FastFieldSerializer {
         self.written_size += try!(self.fields.serialize(&mut self.write));
         try!(self.write.seek(SeekFrom::Start(0)));
         try!((header_offset as u32).serialize(&mut self.write));
-        try!(self.write.flush());
+        try!(directory::terminate(&mut self.write));
         Ok(self.written_size)
     }
 }
@@ -0,0 +1,92 @@
+use DocId;
+use std::sync::Arc;
+use super::MultiU32FastFieldReader;
+use datastruct::{TermDictionary, SegmentTermDictionary};
+use postings::TermInfo;
+use schema::Field;
+
+
+/// Reader for a fast text field, used as a single-valued field,
+/// to sort or group the documents by their value.
+///
+/// Each document is associated to the ordinal of its term,
+/// that is the rank of the term among the terms of the field
+/// within the segment. The ordinals therefore follow
+/// the lexicographical order of the terms, and two documents
+/// of a same segment can be compared without reading their terms.
+///
+/// The ordinals are resolved back to their terms with `term`,
+/// through an ordinal lookup in the term dictionary, as the
+/// ordinal of a term within the field is its ordinal in the term
+/// dictionary, minus the one of the first term of the field.
+pub struct StrFastFieldReader {
+    field: Field,
+    term_ords_reader: MultiU32FastFieldReader,
+    term_infos: Arc<SegmentTermDictionary<TermInfo>>,
+    first_term_ord: u64,
+}
+
+impl StrFastFieldReader {
+
+    /// Creates a reader from the term ordinals of the documents,
+    /// along with the term dictionary of the segment and the ordinal
+    /// of the first term of the field within this dictionary.
+    pub fn new(field: Field, term_ords_reader: MultiU32FastFieldReader, term_infos: Arc<SegmentTermDictionary<TermInfo>>, first_term_ord: u64) -> StrFastFieldReader {
+        StrFastFieldReader {
+            field: field,
+            term_ords_reader: term_ords_reader,
+            term_infos: term_infos,
+            first_term_ord: first_term_ord,
+        }
+    }
+
+    /// Returns the ordinal of the term of the document,
+    /// or `None` if the document has no value for the field.
+    ///
+    /// If the document has several values, the ordinal
+    /// of its smallest term is returned.
+    pub fn ord(&self, doc: DocId) -> Option<u32> {
+        let start = self.term_ords_reader.idx_reader().get(doc);
+        let stop = self.term_ords_reader.idx_reader().get(doc + 1);
+        if start < stop {
+            Some(self.term_ords_reader.vals_reader().get(start))
+        }
+        else {
+            None
+        }
+    }
+
+    /// Writes the value bytes of the term with the ordinal `ord`
+    /// in `term_bytes`, in place of its previous content.
+    ///
+    /// Returns false if `ord` is not the ordinal
+    /// of a term of the field.
+    pub fn term(&self, ord: u32, term_bytes: &mut Vec<u8>) -> bool {
+        if !self.term_infos.ord_to_term(self.first_term_ord + ord as u64, term_bytes) {
+            return false;
+        }
+        // the keys of the other fields are ordered
+        // before or after the keys of the field.
+        if term_bytes.first() != Some(&self.field.0) {
+            return false;
+        }
+        term_bytes.drain(..1);
+        true
+    }
+
+    /// Returns the number of terms of the field in the segment.
+    ///
+    /// The ordinals range from `0` to `num_terms() - 1`.
+    pub fn num_terms(&self,) -> u32 {
+        // the offsets are increasing, so that the largest one
+        // is the overall number of values.
+        let num_vals = self.term_ords_reader.idx_reader().max_val();
+        if num_vals > 0 {
+            // every term of the field belongs to a document.
+            self.term_ords_reader.vals_reader().max_val() + 1
+        }
+        else {
+            0
+        }
+    }
+}
@@ -1,23 +1,71 @@
-use schema::{Schema, Field, Document};
+use schema::{Schema, Field, Document, FieldType};
 use fastfield::FastFieldSerializer;
 use std::io;
+use std::collections::HashMap;
+use std::mem;
+use std::cmp::{min, max};
 use schema::Value;
+use common::i64_to_u64;
+use common::f64_to_u64;
 use DocId;
+use byteorder::{BigEndian, ByteOrder};
 
 pub struct U32FastFieldsWriter {
     field_writers: Vec<U32FastFieldWriter>,
+    u64_field_writers: Vec<U64FastFieldWriter>,
+    multivalued_field_writers: Vec<MultiU32FastFieldWriter>,
+    ip_field_writers: Vec<IpFastFieldWriter>,
+    term_ords_writers: Vec<TermOrdsFastFieldWriter>,
 }
 
 impl U32FastFieldsWriter {
 
     pub fn from_schema(schema: &Schema) -> U32FastFieldsWriter {
-        let u32_fields: Vec<Field> = schema.fields()
-            .iter()
-            .enumerate()
-            .filter(|&(_, field_entry)| field_entry.is_u32_fast()) 
-            .map(|(field_id, _)| Field(field_id as u8))
+        let u32_field_writers: Vec<U32FastFieldWriter> = schema.field_entries()
+            .filter(|&(_, field_entry)| field_entry.is_u32_fast() || field_entry.is_bool_fast())
+            .map(|(field, field_entry)| {
+                let default_value = match *field_entry.field_type() {
+                    FieldType::U32(ref u32_options) => u32_options.get_default_value(),
+                    _ => 0u32,
+                };
+                U32FastFieldWriter::with_default_value(field, default_value)
+            })
+            .collect();
+        let u64_fields: Vec<Field> = schema.field_entries()
+            .filter(|&(_, field_entry)| field_entry.is_u64_fast())
+            .map(|(field, _)| field)
+            .collect();
+        let multivalued_fields: Vec<Field> = schema.field_entries()
+            .filter(|&(_, field_entry)| field_entry.is_multivalued_u32_fast())
+            .map(|(field, _)| field)
+            .collect();
+        let ip_fields: Vec<Field> = schema.field_entries()
+            .filter(|&(_, field_entry)| field_entry.is_ip_fast())
+            .map(|(field, _)| field)
+            .collect();
+        let str_fields: Vec<Field> = schema.field_entries()
+            .filter(|&(_, field_entry)| field_entry.is_str_fast())
+            .map(|(field, _)| field)
+            .collect();
+        let mut fast_fields_writer = U32FastFieldsWriter::new(Vec::new());
+        fast_fields_writer.field_writers = u32_field_writers;
+        fast_fields_writer.u64_field_writers = u64_fields
+            .into_iter()
+            .map(U64FastFieldWriter::new)
+            .collect();
+        fast_fields_writer.multivalued_field_writers = multivalued_fields
+            .into_iter()
+            .map(MultiU32FastFieldWriter::new)
             .collect();
-        U32FastFieldsWriter::new(u32_fields)
+        fast_fields_writer.ip_field_writers = ip_fields
+            .into_iter()
+            .map(IpFastFieldWriter::new)
+            .collect();
+        fast_fields_writer.term_ords_writers = str_fields
+            .into_iter()
+            .map(TermOrdsFastFieldWriter::new)
+            .collect();
+        fast_fields_writer
     }
 
     pub fn new(fields: Vec<Field>) -> U32FastFieldsWriter {
@@ -26,6 +74,10 @@ impl U32FastFieldsWriter {
                 .into_iter()
                 .map(U32FastFieldWriter::new)
                 .collect(),
+            u64_field_writers: Vec::new(),
+            multivalued_field_writers: Vec::new(),
+            ip_field_writers: Vec::new(),
+            term_ords_writers: Vec::new(),
         }
     }
     
@@ -34,54 +86,124 @@ impl U32FastFieldsWriter {
             .iter_mut()
             .find(|field_writer| field_writer.field == field)
     }
+
+    /// Returns the writer of the term ordinals of a fast text field.
+    ///
+    /// Unlike the other fast fields, their values are not extracted
+    /// from the document, but handed over by the segment writer as it
+    /// indexes the terms, before calling `add_document`.
+    pub fn get_term_ords_writer(&mut self, field: Field) -> Option<&mut TermOrdsFastFieldWriter> {
+        self.term_ords_writers
+            .iter_mut()
+            .find(|term_ords_writer| term_ords_writer.field == field)
+    }
     
     pub fn add_document(&mut self, doc: &Document) {
         for field_writer in &mut self.field_writers {
             field_writer.add_document(doc);
         }
+        for field_writer in &mut self.u64_field_writers {
+            field_writer.add_document(doc);
+        }
+        for field_writer in &mut self.multivalued_field_writers {
+            field_writer.add_document(doc);
+        }
+        for field_writer in &mut self.ip_field_writers {
+            field_writer.add_document(doc);
+        }
+        for term_ords_writer in &mut self.term_ords_writers {
+            term_ords_writer.add_document();
+        }
     }
 
     pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
         for field_writer in &self.field_writers {
             try!(field_writer.serialize(serializer));
         }
+        for field_writer in &self.u64_field_writers {
+            try!(field_writer.serialize(serializer));
+        }
+        for field_writer in &self.multivalued_field_writers {
+            try!(field_writer.serialize(serializer));
+        }
+        for field_writer in &self.ip_field_writers {
+            try!(field_writer.serialize(serializer));
+        }
+        for term_ords_writer in &self.term_ords_writers {
+            try!(term_ords_writer.serialize(serializer));
+        }
         Ok(())
     }
+
+    /// Returns the number of bytes used by the term ordinals
+    /// writers outside of the heap of the segment writer.
+    pub fn term_ords_mem_usage(&self,) -> usize {
+        self.term_ords_writers
+            .iter()
+            .map(|term_ords_writer| term_ords_writer.mem_usage())
+            .sum()
+    }
     
     /// Ensures all of the fast field writers have
     /// reached `doc`. (included)
     /// 
-    /// The missing values will be filled with 0.
+    /// The missing values will be filled with the default
+    /// value of each field, that is 0 unless configured otherwise.
     pub fn fill_val_up_to(&mut self, doc: DocId) {
         for field_writer in &mut self.field_writers {
             field_writer.fill_val_up_to(doc);
         }
-            
+        for field_writer in &mut self.u64_field_writers {
+            field_writer.fill_val_up_to(doc);
+        }
+        for field_writer in &mut self.multivalued_field_writers {
+            field_writer.fill_val_up_to(doc);
+        }
+        for field_writer in &mut self.ip_field_writers {
+            field_writer.fill_val_up_to(doc);
+        }
+        for term_ords_writer in &mut self.term_ords_writers {
+            term_ords_writer.fill_val_up_to(doc);
+        }
     }
 }
 
 pub struct U32FastFieldWriter {
     field: Field,
     vals: Vec<u32>,
+    default_value: u32,
+    // bounds of the values following the first value of a
+    // document, which are not recorded but still widen the
+    // min and max values of the field.
+    ignored_vals_bounds: Option<(u32, u32)>,
 }
 
 impl U32FastFieldWriter {
     pub fn new(field: Field) -> U32FastFieldWriter {
+        U32FastFieldWriter::with_default_value(field, 0u32)
+    }
+
+    /// Creates a writer recording `default_value`
+    /// for the documents without any value.
+    pub fn with_default_value(field: Field, default_value: u32) -> U32FastFieldWriter {
         U32FastFieldWriter {
             field: field,
             vals: Vec::new(),
+            default_value: default_value,
+            ignored_vals_bounds: None,
         }
     }
     
     /// Ensures all of the fast field writer have
     /// reached `doc`. (included)
     /// 
-    /// The missing values will be filled with 0.
+    /// The missing values will be filled with the default value.
     fn fill_val_up_to(&mut self, doc: DocId) {
         let target = doc as usize + 1;
         debug_assert!(self.vals.len() <= target);
         while self.vals.len() < target {
-            self.add_val(0u32)
+            let default_value = self.default_value;
+            self.add_val(default_value)
         }
     }
     
@@ -94,12 +216,100 @@ impl U32FastFieldWriter {
             Some(v) => {
                 match *v {
                     Value::U32(ref val) => { *val }
+                    // bool fast fields are stored as 0 or 1.
+                    Value::Bool(val) => { val as u32 }
                     _ => { panic!("Expected a u32field, got {:?} ", v) }
                 }
             },
+            None => {
+                self.default_value
+            }            
+        }
+    }
+    
+    pub fn add_document(&mut self, doc: &Document) {
+        let val = self.extract_val(doc);
+        self.add_val(val);
+        for ignored_val in doc.get_all(self.field).into_iter().skip(1) {
+            if let Value::U32(ignored_val) = *ignored_val {
+                self.ignored_vals_bounds = Some(match self.ignored_vals_bounds {
+                    Some((min_val, max_val)) => (min(min_val, ignored_val), max(max_val, ignored_val)),
+                    None => (ignored_val, ignored_val),
+                });
+            }
+        }
+    }
+
+    /// Only the first value of a document is recorded, but the min and
+    /// max values of the field account for all of the values, so that
+    /// they bound the values of the field in the segment, as indexed.
+    pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        let zero = 0;
+        let mut min_val = *self.vals.iter().min().unwrap_or(&zero);
+        let mut max_val = *self.vals.iter().max().unwrap_or(&min_val);
+        if let Some((ignored_min, ignored_max)) = self.ignored_vals_bounds {
+            min_val = min(min_val, ignored_min);
+            max_val = max(max_val, ignored_max);
+        }
+        try!(serializer.new_u32_fast_field(self.field, min_val, max_val));
+        for &val in &self.vals {
+            try!(serializer.add_val(val));
+        }
+        serializer.close_field()
+    }
+}
+
+
+/// Fast field writer for 64-bits values.
+///
+/// i64, date and f64 values are mapped to u64 using the order preserving
+/// `common::i64_to_u64` and `common::f64_to_u64` mappings.
+///
+/// Both mappings send `0` to the same `u64`, so that
+/// missing values default to `0` for both types.
+pub struct U64FastFieldWriter {
+    field: Field,
+    vals: Vec<u64>,
+}
+
+impl U64FastFieldWriter {
+    pub fn new(field: Field) -> U64FastFieldWriter {
+        U64FastFieldWriter {
+            field: field,
+            vals: Vec::new(),
+        }
+    }
+    
+    /// Ensures all of the fast field writer have
+    /// reached `doc`. (included)
+    /// 
+    /// The missing values will be filled with 0.
+    fn fill_val_up_to(&mut self, doc: DocId) {
+        let target = doc as usize + 1;
+        debug_assert!(self.vals.len() <= target);
+        let default_val = i64_to_u64(0i64);
+        while self.vals.len() < target {
+            self.add_val(default_val)
+        }
+    }
+    
+    pub fn add_val(&mut self, val: u64) {
+        self.vals.push(val);
+    }
+    
+    fn extract_val(&self, doc: &Document) -> u64 {
+        match doc.get_first(self.field) {
+            Some(v) => {
+                match *v {
+                    Value::I64(ref val) => { i64_to_u64(*val) }
+                    Value::F64(ref val) => { f64_to_u64(*val) }
+                    Value::Date(ref val) => { i64_to_u64(*val) }
+                    _ => { panic!("Expected a i64, f64 or date field, got {:?} ", v) }
+                }
+            },
             None => {
                 // TODO make default value configurable
-                0u32
+                i64_to_u64(0i64)
             }            
         }
     }
@@ -110,6 +320,68 @@ impl U32FastFieldWriter {
     }
 
     pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        let zero = i64_to_u64(0i64);
+        let min = *self.vals.iter().min().unwrap_or(&zero);
+        let max = *self.vals.iter().max().unwrap_or(&min);
+        try!(serializer.new_u64_fast_field(self.field, min, max));
+        for &val in &self.vals {
+            try!(serializer.add_val_u64(val));
+        }
+        serializer.close_field_u64()
+    }
+}
+
+
+/// Fast field writer for multivalued u32 fields.
+///
+/// Documents without any value are simply
+/// associated to an empty range of values.
+pub struct MultiU32FastFieldWriter {
+    field: Field,
+    // offset of the first value of each document
+    doc_index: Vec<u32>,
+    vals: Vec<u32>,
+}
+
+impl MultiU32FastFieldWriter {
+    pub fn new(field: Field) -> MultiU32FastFieldWriter {
+        MultiU32FastFieldWriter {
+            field: field,
+            doc_index: Vec::new(),
+            vals: Vec::new(),
+        }
+    }
+    
+    /// Ensures all of the fast field writer have
+    /// reached `doc`. (included)
+    /// 
+    /// The missing documents are associated to no value.
+    fn fill_val_up_to(&mut self, doc: DocId) {
+        let target = doc as usize + 1;
+        debug_assert!(self.doc_index.len() <= target);
+        while self.doc_index.len() < target {
+            self.doc_index.push(self.vals.len() as u32);
+        }
+    }
+    
+    pub fn add_document(&mut self, doc: &Document) {
+        self.doc_index.push(self.vals.len() as u32);
+        for field_value in doc.get_all(self.field) {
+            match *field_value {
+                Value::U32(ref val) => { self.vals.push(*val); }
+                _ => { panic!("Expected a u32field, got {:?} ", field_value) }
+            }
+        }
+    }
+
+    pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        let num_vals = self.vals.len() as u32;
+        try!(serializer.new_u32_fast_field(self.field, 0, num_vals));
+        for &offset in &self.doc_index {
+            try!(serializer.add_val(offset));
+        }
+        try!(serializer.add_val(num_vals));
+        try!(serializer.close_field());
         let zero = 0;
         let min = *self.vals.iter().min().unwrap_or(&zero);
         let max = *self.vals.iter().max().unwrap_or(&min);
@@ -120,3 +392,199 @@ impl U32FastFieldWriter {
         serializer.close_field()
     }
 }
+
+
+/// Fast field writer for the term ordinals of a fast text field.
+///
+/// The ordinals are only known once all of the terms of the
+/// segment have been seen: the terms are recorded as ids, in the
+/// order in which they first appeared, and are translated into
+/// ordinals when the field gets serialized, as a multivalued u32
+/// fast field.
+///
+/// The ordinals of a document are sorted and deduplicated.
+pub struct TermOrdsFastFieldWriter {
+    field: Field,
+    // lives outside of the heap of the segment writer,
+    // see `mem_usage`.
+    term_ids: HashMap<Vec<u8>, u32>,
+    num_term_bytes: usize,
+    // offset past the last term id of each document
+    doc_ends: Vec<u32>,
+    vals: Vec<u32>,
+}
+
+impl TermOrdsFastFieldWriter {
+    pub fn new(field: Field) -> TermOrdsFastFieldWriter {
+        TermOrdsFastFieldWriter {
+            field: field,
+            term_ids: HashMap::new(),
+            num_term_bytes: 0,
+            doc_ends: Vec::new(),
+            vals: Vec::new(),
+        }
+    }
+
+    /// Ensures all of the fast field writer have
+    /// reached `doc`. (included)
+    /// 
+    /// The missing documents are associated to no term.
+    fn fill_val_up_to(&mut self, doc: DocId) {
+        let target = doc as usize + 1;
+        debug_assert!(self.doc_ends.len() <= target);
+        while self.doc_ends.len() < target {
+            self.doc_ends.push(self.vals.len() as u32);
+        }
+    }
+
+    /// Records that the document being indexed contains the
+    /// term of the field whose value bytes are `term_bytes`.
+    pub fn add_term(&mut self, term_bytes: &[u8]) {
+        let num_terms = self.term_ids.len() as u32;
+        let term_id = *self.term_ids
+            .entry(term_bytes.to_vec())
+            .or_insert(num_terms);
+        if term_id == num_terms {
+            self.num_term_bytes += term_bytes.len();
+        }
+        self.vals.push(term_id);
+    }
+
+    /// Returns an estimate of the number of bytes used by
+    /// the dictionary of the terms, which is not allocated
+    /// in the heap of the segment writer.
+    pub fn mem_usage(&self,) -> usize {
+        // each bucket holds the term, its id and its hash.
+        let bucket_num_bytes = mem::size_of::<(Vec<u8>, u32, u64)>();
+        self.term_ids.capacity() * bucket_num_bytes + self.num_term_bytes
+    }
+
+    /// Closes the document being indexed, once all
+    /// of its terms have been added.
+    pub fn add_document(&mut self,) {
+        self.doc_ends.push(self.vals.len() as u32);
+    }
+
+    /// Returns the ordinal of each term id, that is the rank of
+    /// the term in the lexicographical order, as in the term dictionary.
+    fn term_id_to_ord(&self,) -> Vec<u32> {
+        let mut terms: Vec<(&[u8], u32)> = self.term_ids
+            .iter()
+            .map(|(term_bytes, &term_id)| (&term_bytes[..], term_id))
+            .collect();
+        terms.sort();
+        let mut term_id_to_ord = vec!(0u32; terms.len());
+        for (ord, &(_, term_id)) in terms.iter().enumerate() {
+            term_id_to_ord[term_id as usize] = ord as u32;
+        }
+        term_id_to_ord
+    }
+
+    pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        let term_id_to_ord = self.term_id_to_ord();
+        let mut doc_ords: Vec<u32> = Vec::new();
+        let mut doc_index: Vec<u32> = Vec::with_capacity(self.doc_ends.len());
+        let mut ords: Vec<u32> = Vec::with_capacity(self.vals.len());
+        let mut doc_start = 0u32;
+        for &doc_end in &self.doc_ends {
+            doc_ords.clear();
+            doc_ords.extend(self.vals[doc_start as usize..doc_end as usize]
+                .iter()
+                .map(|&term_id| term_id_to_ord[term_id as usize]));
+            doc_ords.sort();
+            doc_ords.dedup();
+            doc_index.push(ords.len() as u32);
+            ords.extend_from_slice(&doc_ords);
+            doc_start = doc_end;
+        }
+        let num_ords = ords.len() as u32;
+        try!(serializer.new_u32_fast_field(self.field, 0, num_ords));
+        for &offset in &doc_index {
+            try!(serializer.add_val(offset));
+        }
+        try!(serializer.add_val(num_ords));
+        try!(serializer.close_field());
+        let max_ord = (term_id_to_ord.len() as u32).saturating_sub(1);
+        try!(serializer.new_u32_fast_field(self.field, 0, max_ord));
+        for &ord in &ords {
+            try!(serializer.add_val(ord));
+        }
+        serializer.close_field()
+    }
+}
+
+
+/// Fast field writer for IP address fields.
+///
+/// The IPv6 form of the address is split into two u64,
+/// serialized as two u64 fast fields for the same field :
+/// the high 64 bits first, then the low 64 bits.
+///
+/// Documents without any value are associated to `::`.
+pub struct IpFastFieldWriter {
+    field: Field,
+    high_vals: Vec<u64>,
+    low_vals: Vec<u64>,
+}
+
+impl IpFastFieldWriter {
+    pub fn new(field: Field) -> IpFastFieldWriter {
+        IpFastFieldWriter {
+            field: field,
+            high_vals: Vec::new(),
+            low_vals: Vec::new(),
+        }
+    }
+    
+    /// Ensures all of the fast field writer have
+    /// reached `doc`. (included)
+    /// 
+    /// The missing values will be filled with `::`.
+    fn fill_val_up_to(&mut self, doc: DocId) {
+        let target = doc as usize + 1;
+        debug_assert!(self.high_vals.len() <= target);
+        while self.high_vals.len() < target {
+            self.add_val((0u64, 0u64))
+        }
+    }
+    
+    pub fn add_val(&mut self, (high, low): (u64, u64)) {
+        self.high_vals.push(high);
+        self.low_vals.push(low);
+    }
+    
+    fn extract_val(&self, doc: &Document) -> (u64, u64) {
+        match doc.get_first(self.field) {
+            Some(v) => {
+                match *v {
+                    Value::Ip(ref octets) => {
+                        (BigEndian::read_u64(&octets[..8]), BigEndian::read_u64(&octets[8..]))
+                    }
+                    _ => { panic!("Expected an ip field, got {:?} ", v) }
+                }
+            },
+            None => {
+                (0u64, 0u64)
+            }            
+        }
+    }
+    
+    pub fn add_document(&mut self, doc: &Document) {
+        let val = self.extract_val(doc);
+        self.add_val(val);
+    }
+
+    pub fn serialize(&self, serializer: &mut FastFieldSerializer) -> io::Result<()> {
+        for vals in &[&self.high_vals, &self.low_vals] {
+            let zero = 0u64;
+            let min = *vals.iter().min().unwrap_or(&zero);
+            let max = *vals.iter().max().unwrap_or(&min);
+            try!(serializer.new_u64_fast_field(self.field, min, max));
+            for &val in vals.iter() {
+                try!(serializer.add_val_u64(val));
+            }
+            try!(serializer.close_field_u64());
+        }
+        Ok(())
+    }
+}
@@ -15,6 +15,7 @@ use indexer::merger::IndexMerger;
 use core::SegmentId;
 use datastruct::stacker::Heap;
 use std::mem::swap;
+use std::ops::AddAssign;
 use chan;
 use core::SegmentMeta;
 use super::segment_updater::{SegmentUpdater, SegmentUpdate, SegmentUpdateSender};
@@ -56,7 +57,9 @@ pub struct IndexWriter {
     index: Index,
     heap_size_in_bytes_per_thread: usize,
 
-    workers_join_handle: Vec<JoinHandle<Result<()>>>,
+    // the workers return the number of truncated and
+    // clamped documents they indexed.
+    workers_join_handle: Vec<JoinHandle<Result<IndexingStats>>>,
 
     document_receiver: DocumentReceiver,
     document_sender: DocumentSender,
@@ -72,6 +75,36 @@ pub struct IndexWriter {
     committed_docstamp: u64,
 }
 
+/// Returned by `IndexWriter::commit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitStats {
+    /// `docstamp` of the last document that made it in the commit.
+    pub docstamp: u64,
+    /// Number of documents of the commit with at least one field
+    /// truncated to its `max_positions_per_doc`.
+    pub num_truncated_docs: u64,
+    /// Number of documents of the commit with at least one position
+    /// beyond `MAX_POSITION`, clamped to it.
+    ///
+    /// Phrase queries may miss the matches of these documents
+    /// beyond `MAX_POSITION`.
+    pub num_clamped_docs: u64,
+}
+
+// Counts of the documents needing attention, for a `CommitStats`.
+#[derive(Clone, Copy, Debug, Default)]
+struct IndexingStats {
+    num_truncated_docs: u64,
+    num_clamped_docs: u64,
+}
+
+impl AddAssign for IndexingStats {
+    fn add_assign(&mut self, other: IndexingStats) {
+        self.num_truncated_docs += other.num_truncated_docs;
+        self.num_clamped_docs += other.num_clamped_docs;
+    }
+}
+
 // IndexWriter cannot be sent to another thread.
 impl !Send for IndexWriter {}
 impl !Sync for IndexWriter {}
@@ -82,9 +115,11 @@ fn index_documents(heap: &mut Heap,
                    schema: &Schema,
                    document_iterator: &mut Iterator<Item = Document>,
                    segment_update_sender: &mut SegmentUpdateSender)
-                   -> Result<()> {
+                   -> Result<IndexingStats> {
     heap.clear();
     let segment_id = segment.id();
+    // the segment writer fetches the same tokenizers.
+    let analyzers = segment.index().analyzer_fingerprints();
     let mut segment_writer = try!(SegmentWriter::for_segment(heap, segment, &schema));
     for doc in document_iterator {
         try!(segment_writer.add_document(&doc, &schema));
@@ -95,14 +130,19 @@ fn index_documents(heap: &mut Heap,
         }
     }
     let num_docs = segment_writer.max_doc();
+    let indexing_stats = IndexingStats {
+        num_truncated_docs: segment_writer.num_truncated_docs(),
+        num_clamped_docs: segment_writer.num_clamped_docs(),
+    };
     let segment_meta = SegmentMeta {
         segment_id: segment_id,
         num_docs: num_docs,
+        analyzers: Some(analyzers),
     };
 
     try!(segment_writer.finalize());
     segment_update_sender.send(SegmentUpdate::AddSegment(segment_meta));
-    Ok(())
+    Ok(indexing_stats)
 
 }
 
@@ -144,9 +184,10 @@ impl IndexWriter {
         let document_receiver_clone = self.document_receiver.clone();
         let mut segment_update_sender = self.segment_update_sender.clone();
         let mut heap = Heap::with_capacity(self.heap_size_in_bytes_per_thread);
-        let join_handle: JoinHandle<Result<()>> = try!(thread::Builder::new()
+        let join_handle: JoinHandle<Result<IndexingStats>> = try!(thread::Builder::new()
             .name(format!("indexing_thread_{}", self.worker_id))
             .spawn(move || {
+                let mut indexing_stats = IndexingStats::default();
                 loop {
                     let segment = index.new_segment();
                     let mut document_iterator = document_receiver_clone.clone()
@@ -156,16 +197,16 @@ impl IndexWriter {
                     // creating a new segment's files
                     // if no document are available.
                     if document_iterator.peek().is_some() {
-                        try!(index_documents(&mut heap,
-                                             segment,
-                                             &schema,
-                                             &mut document_iterator,
-                                             &mut segment_update_sender));
+                        indexing_stats += try!(index_documents(&mut heap,
+                                                               segment,
+                                                               &schema,
+                                                               &mut document_iterator,
+                                                               &mut segment_update_sender));
                     } else {
                         // No more documents.
                         // Happens when there is a commit, or if the `IndexWriter`
                         // was dropped.
-                        return Ok(());
+                        return Ok(indexing_stats);
                     }
                 }
             }));
@@ -282,6 +323,7 @@ impl IndexWriter {
         let segment_meta = SegmentMeta {
             segment_id: merged_segment.id(),
             num_docs: num_docs,
+            analyzers: SegmentMeta::merged_segment_analyzers(&segment_manager, &merged_segment_ids),
         };
 
         segment_manager.end_merge(&merged_segment_ids, &segment_meta);
@@ -376,9 +418,10 @@ impl IndexWriter {
     /// to resume indexing from this point.
     ///
     /// Commit returns the `docstamp` of the last document
-    /// that made it in the commit.
+    /// that made it in the commit, along with the number of
+    /// documents that got truncated or had positions clamped.
     ///
-    pub fn commit(&mut self) -> Result<u64> {
+    pub fn commit(&mut self) -> Result<CommitStats> {
 
         // this will drop the current document channel
         // and recreate a new one channels.
@@ -391,10 +434,11 @@ impl IndexWriter {
         swap(&mut former_workers_join_handle,
              &mut self.workers_join_handle);
 
+        let mut indexing_stats = IndexingStats::default();
         for worker_handle in former_workers_join_handle {
             let indexing_worker_result = try!(worker_handle.join()
                 .map_err(|e| Error::ErrorInThread(format!("{:?}", e))));
-            try!(indexing_worker_result);
+            indexing_stats += try!(indexing_worker_result);
             // add a new worker for the next generation.
             try!(self.add_indexing_worker());
         }
@@ -416,7 +460,11 @@ impl IndexWriter {
             thread::sleep(Duration::from_millis(100));
         }
 
-        Ok(self.committed_docstamp)
+        Ok(CommitStats {
+            docstamp: self.committed_docstamp,
+            num_truncated_docs: indexing_stats.num_truncated_docs,
+            num_clamped_docs: indexing_stats.num_clamped_docs,
+        })
     }
 
 
@@ -449,7 +497,7 @@ mod tests {
     #[test]
     fn test_lockfile_stops_duplicates() {
         let schema_builder = schema::SchemaBuilder::default();
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
         let _index_writer = index.writer(40_000_000).unwrap();
         match index.writer(40_000_000) {
             Err(Error::FileAlreadyExists(_)) => {}
@@ -460,7 +508,7 @@ mod tests {
     #[test]
     fn test_lockfile_released_on_drop() {
         let schema_builder = schema::SchemaBuilder::default();
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
         {
             let _index_writer = index.writer(40_000_000).unwrap();
             // the lock should be released when the 
@@ -473,7 +521,7 @@ mod tests {
     fn test_commit_and_rollback() {
         let mut schema_builder = schema::SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", schema::TEXT);
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
 
 
         let num_docs_containing = |s: &str| {
@@ -503,7 +551,7 @@ mod tests {
                 doc.add_text(text_field, "c");
                 index_writer.add_document(doc).unwrap();
             }
-            assert_eq!(index_writer.commit().unwrap(), 2u64);
+            assert_eq!(index_writer.commit().unwrap().docstamp, 2u64);
 
             assert_eq!(num_docs_containing("a"), 0);
             assert_eq!(num_docs_containing("b"), 1);
@@ -516,7 +564,7 @@ mod tests {
     fn test_with_merges() {
         let mut schema_builder = schema::SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", schema::TEXT);
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
         let num_docs_containing = |s: &str| {
             let searcher = index.searcher();
             let term_a = Term::from_field_text(text_field, s);
@@ -7,17 +7,24 @@ use core::SerializableSegment;
 use indexer::SegmentSerializer;
 use postings::PostingsSerializer;
 use postings::TermInfo;
+use postings::POSTINGS_FORMAT_VERSION;
+use directory::FOOTER_VERSION;
 use postings::Postings;
 use postings::DocSet;
 use std::collections::BinaryHeap;
-use datastruct::FstKeyIter;
-use schema::{Term, Schema, Field};
+use datastruct::{TermDictionary, TermDictionaryKeys, SegmentTermKeys};
+use schema::{Term, Schema, Field, TERMS_FORMAT_VERSION};
 use fastfield::FastFieldSerializer;
+use fastfield::U64FastFieldReader;
+use fastfield::{fieldnorm_to_id, FIELDNORMS_FORMAT_VERSION};
+use fastfield::BytesFastFieldSerializer;
 use store::StoreWriter;
 use postings::ChainedPostings;
 use postings::HasLen;
 use postings::OffsetPostings;
 use core::SegmentInfo;
+use core::FieldStats;
+use common::BitSet;
 use std::cmp::{min, max, Ordering};
 use std::iter;
 
@@ -25,7 +32,7 @@ use std::iter;
 struct PostingsMerger<'a> {
     doc_offsets: Vec<DocId>,
     heap: BinaryHeap<HeapItem>,
-    term_streams: Vec<FstKeyIter<'a, TermInfo>>,
+    term_streams: Vec<SegmentTermKeys<'a, TermInfo>>,
     readers: &'a [SegmentReader],
 }
 
@@ -33,6 +40,7 @@ struct PostingsMerger<'a> {
 struct HeapItem {
     term: Term,
     segment_ord: usize,
+    total_term_freq: Option<u64>,
 }
 
 impl PartialOrd for HeapItem {
@@ -74,10 +82,11 @@ impl<'a> PostingsMerger<'a> {
     // pushes the term_reader associated with the given segment ordinal
     // into the heap.
     fn push_next_segment_el(&mut self, segment_ord: usize) {
-        if let Some(term) = self.term_streams[segment_ord].next() {
+        if let Some((term, term_info)) = self.term_streams[segment_ord].next_with_value() {
             let it = HeapItem {
                 term: Term::from(term),
                 segment_ord: segment_ord,
+                total_term_freq: term_info.total_term_freq,
             };
             self.heap.push(it);
         }
@@ -102,13 +111,16 @@ impl<'a> PostingsMerger<'a> {
 
 impl<'a> Iterator for PostingsMerger<'a> {
     
-    type Item = (Term, ChainedPostings<'a>);
+    /// The merged postings of each term, along with its total term frequency,
+    /// unless one of the segments did not record it.
+    type Item = (Term, Option<u64>, ChainedPostings<'a>);
     
-    fn next(&mut self,) -> Option<(Term, ChainedPostings<'a>)> {
+    fn next(&mut self,) -> Option<(Term, Option<u64>, ChainedPostings<'a>)> {
         // TODO remove the Vec<u8> allocations
         match self.heap.pop() {
             Some(heap_it) => {
                 let mut segment_postings_list = Vec::new();
+                let mut total_term_freq = heap_it.total_term_freq;
                 self.append_segment(&heap_it, &mut segment_postings_list);
                 loop {
                     match self.heap.peek() {
@@ -116,10 +128,14 @@ impl<'a> Iterator for PostingsMerger<'a> {
                         _ => { break; }
                     }
                     let next_heap_it = self.heap.pop().expect("This is only reached if an element was peeked beforehand.");
+                    total_term_freq = match (total_term_freq, next_heap_it.total_term_freq) {
+                        (Some(total_term_freq), Some(segment_total_term_freq)) => Some(total_term_freq + segment_total_term_freq),
+                        _ => None,
+                    };
                     self.append_segment(&next_heap_it, &mut segment_postings_list);
                 }
                 let chained_posting = ChainedPostings::from(segment_postings_list);
-                Some((heap_it.term, chained_posting))
+                Some((heap_it.term, total_term_freq, chained_posting))
             },
             None => None
         }
@@ -151,7 +167,9 @@ impl DeltaPositionComputer {
         }
         let mut last_pos = 0u32;
         for (i, position) in positions.iter().cloned().enumerate() {
-            self.buffer[i] = position - last_pos;
+            self.buffer[i] = position
+                .checked_sub(last_pos)
+                .expect("The positions of a document are sorted. This should never happen. Please report the bug.");
             last_pos = position;
         }
         &self.buffer[..positions.len()]
@@ -173,7 +191,14 @@ impl IndexMerger {
             schema: schema,
             readers: readers,
             segment_info: SegmentInfo {
-                max_doc: max_doc
+                max_doc: max_doc,
+                postings_format_version: Some(POSTINGS_FORMAT_VERSION),
+                footer_version: Some(FOOTER_VERSION),
+                fieldnorms_format_version: Some(FIELDNORMS_FORMAT_VERSION),
+                terms_format_version: Some(TERMS_FORMAT_VERSION),
+                // filled by the `SegmentSerializer`.
+                term_dictionary_type: None,
+                term_dictionary_stats: None,
             },
         })
     }
@@ -181,25 +206,23 @@ impl IndexMerger {
     
     fn write_fieldnorms(&self, fast_field_serializer: &mut FastFieldSerializer) -> Result<()> {
         // TODO make sure that works even if the field is never here.
-        for field in self.schema.fields()
-             .iter()
-             .enumerate()
-             .filter(|&(_, field_entry)| field_entry.is_indexed())
-             .map(|(field_id, _)| Field(field_id as u8)) {
-            let mut u32_readers = Vec::new();
-            let mut min_val = u32::min_value();
-            let mut max_val = 0;
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.has_fieldnorms())
+             .map(|(field, _)| field) {
+            // the field norms of the segments written before
+            // `FIELDNORMS_FORMAT_VERSION` get encoded.
+            let mut fieldnorm_readers = Vec::new();
+            let mut max_id = 0u8;
             for reader in &self.readers {
-                let u32_reader = try!(reader.get_fieldnorms_reader(field));
-                min_val = min(min_val, u32_reader.min_val());
-                max_val = max(max_val, u32_reader.max_val());
-                u32_readers.push((reader.max_doc(), u32_reader));
+                let fieldnorm_reader = try!(reader.get_fieldnorm_reader(field));
+                max_id = max(max_id, fieldnorm_to_id(fieldnorm_reader.max_val()));
+                fieldnorm_readers.push((reader.max_doc(), fieldnorm_reader));
             }
-            try!(fast_field_serializer.new_u32_fast_field(field, min_val, max_val));
-            for (max_doc, u32_reader) in u32_readers {
+            try!(fast_field_serializer.new_u32_fast_field(field, 0u32, max_id as u32));
+            for (max_doc, fieldnorm_reader) in fieldnorm_readers {
                 for doc_id in 0..max_doc {
-                    let val = u32_reader.get(doc_id);
-                    try!(fast_field_serializer.add_val(val));
+                    let id = fieldnorm_reader.get_id(doc_id);
+                    try!(fast_field_serializer.add_val(id as u32));
                 }
             }
             try!(fast_field_serializer.close_field());
@@ -208,13 +231,11 @@ impl IndexMerger {
     }
 
     fn write_fast_fields(&self, fast_field_serializer: &mut FastFieldSerializer) -> Result<()> {
-        for field in self.schema.fields()
-             .iter()
-             .enumerate()
+        for field in self.schema.field_entries()
              .filter(|&(_, field_entry)| field_entry.is_u32_fast())
-             .map(|(field_id, _)| Field(field_id as u8)) {
+             .map(|(field, _)| field) {
             let mut u32_readers = Vec::new();
-            let mut min_val = u32::min_value();
+            let mut min_val = u32::max_value();
             let mut max_val = 0;
             for reader in &self.readers {
                 let u32_reader = try!(reader.get_fast_field_reader(field));
@@ -231,23 +252,279 @@ impl IndexMerger {
             }
             try!(fast_field_serializer.close_field());
         }
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.is_bool_fast())
+             .map(|(field, _)| field) {
+            let mut bool_readers = Vec::new();
+            for reader in &self.readers {
+                let bool_reader = try!(reader.get_bool_fast_field_reader(field));
+                bool_readers.push((reader.max_doc(), bool_reader));
+            }
+            try!(fast_field_serializer.new_u32_fast_field(field, 0u32, 1u32));
+            for (max_doc, bool_reader) in bool_readers {
+                for doc_id in 0..max_doc {
+                    let val = bool_reader.get(doc_id) as u32;
+                    try!(fast_field_serializer.add_val(val));
+                }
+            }
+            try!(fast_field_serializer.close_field());
+        }
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.is_multivalued_u32_fast())
+             .map(|(field, _)| field) {
+            let mut multi_readers = Vec::new();
+            let mut num_vals = 0u32;
+            let mut min_val = u32::max_value();
+            let mut max_val = 0u32;
+            for reader in &self.readers {
+                let multi_reader = try!(reader.multi_u32_fast_field(field));
+                let reader_num_vals = multi_reader.idx_reader().get(reader.max_doc());
+                if reader_num_vals > 0 {
+                    // segments without any value have a meaningless min and max.
+                    min_val = min(min_val, multi_reader.vals_reader().min_val());
+                    max_val = max(max_val, multi_reader.vals_reader().max_val());
+                }
+                num_vals += reader_num_vals;
+                multi_readers.push((reader.max_doc(), reader_num_vals, multi_reader));
+            }
+            if num_vals == 0 {
+                min_val = 0;
+                max_val = 0;
+            }
+            // the index of each segment is shifted by the number
+            // of values of the previous segments.
+            try!(fast_field_serializer.new_u32_fast_field(field, 0, num_vals));
+            let mut shift = 0u32;
+            for &(max_doc, reader_num_vals, ref multi_reader) in &multi_readers {
+                for doc_id in 0..max_doc {
+                    let offset = multi_reader.idx_reader().get(doc_id);
+                    try!(fast_field_serializer.add_val(shift + offset));
+                }
+                shift += reader_num_vals;
+            }
+            try!(fast_field_serializer.add_val(num_vals));
+            try!(fast_field_serializer.close_field());
+            try!(fast_field_serializer.new_u32_fast_field(field, min_val, max_val));
+            for &(_, reader_num_vals, ref multi_reader) in &multi_readers {
+                for val_id in 0..reader_num_vals {
+                    let val = multi_reader.vals_reader().get(val_id);
+                    try!(fast_field_serializer.add_val(val));
+                }
+            }
+            try!(fast_field_serializer.close_field());
+        }
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.is_u64_fast())
+             .map(|(field, _)| field) {
+            // i64, f64 and dates are merged using their u64 representation.
+            let mut u64_readers = Vec::new();
+            let mut min_val = u64::max_value();
+            let mut max_val = u64::min_value();
+            for reader in &self.readers {
+                let u64_reader = try!(reader.get_u64_fast_field_reader(field));
+                min_val = min(min_val, u64_reader.min_val());
+                max_val = max(max_val, u64_reader.max_val());
+                u64_readers.push((reader.max_doc(), u64_reader));
+            }
+            if u64_readers.is_empty() {
+                min_val = 0;
+                max_val = 0;
+            }
+            try!(fast_field_serializer.new_u64_fast_field(field, min_val, max_val));
+            for (max_doc, u64_reader) in u64_readers {
+                for doc_id in 0..max_doc {
+                    let val = u64_reader.get(doc_id);
+                    try!(fast_field_serializer.add_val_u64(val));
+                }
+            }
+            try!(fast_field_serializer.close_field_u64());
+        }
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.is_ip_fast())
+             .map(|(field, _)| field) {
+            let mut ip_readers = Vec::new();
+            for reader in &self.readers {
+                let ip_reader = try!(reader.get_ip_fast_field_reader(field));
+                ip_readers.push((reader.max_doc(), ip_reader));
+            }
+            // the high and the low bits are merged as
+            // two independent u64 columns.
+            for &high in &[true, false] {
+                let column_readers: Vec<(DocId, &U64FastFieldReader)> = ip_readers
+                    .iter()
+                    .map(|&(max_doc, ref ip_reader)| {
+                        if high {
+                            (max_doc, ip_reader.high_reader())
+                        }
+                        else {
+                            (max_doc, ip_reader.low_reader())
+                        }
+                    })
+                    .collect();
+                let min_val = column_readers.iter().map(|&(_, u64_reader)| u64_reader.min_val()).min().unwrap_or(0u64);
+                let max_val = column_readers.iter().map(|&(_, u64_reader)| u64_reader.max_val()).max().unwrap_or(0u64);
+                try!(fast_field_serializer.new_u64_fast_field(field, min_val, max_val));
+                for (max_doc, u64_reader) in column_readers {
+                    for doc_id in 0..max_doc {
+                        let val = u64_reader.get(doc_id);
+                        try!(fast_field_serializer.add_val_u64(val));
+                    }
+                }
+                try!(fast_field_serializer.close_field_u64());
+            }
+        }
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.is_str_fast())
+             .map(|(field, _)| field) {
+            try!(self.write_term_ords(field, fast_field_serializer));
+        }
+        Ok(())
+    }
+
+    // The ordinals of each segment are remapped to the rank of their
+    // term among the terms of all of the segments, that is its rank
+    // in the merged term dictionary.
+    //
+    // The mapping is increasing, so that the ordinals of
+    // each document remain sorted.
+    fn write_term_ords(&self, field: Field, fast_field_serializer: &mut FastFieldSerializer) -> Result<()> {
+        let mut segments_terms: Vec<Vec<Vec<u8>>> = Vec::with_capacity(self.readers.len());
+        for reader in &self.readers {
+            let mut segment_terms = Vec::new();
+            let mut term_stream = reader.terms(field);
+            while let Some((term_bytes, _)) = term_stream.next() {
+                segment_terms.push(term_bytes.to_vec());
+            }
+            segments_terms.push(segment_terms);
+        }
+        let mut merged_terms: Vec<&[u8]> = segments_terms
+            .iter()
+            .flat_map(|segment_terms| segment_terms.iter().map(|term_bytes| &term_bytes[..]))
+            .collect();
+        merged_terms.sort();
+        merged_terms.dedup();
+        let ord_mappings: Vec<Vec<u32>> = segments_terms
+            .iter()
+            .map(|segment_terms| {
+                segment_terms
+                    .iter()
+                    .map(|term_bytes| {
+                        merged_terms
+                            .binary_search(&&term_bytes[..])
+                            .expect("The merged terms contain the terms of all of the segments") as u32
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut term_ords_readers = Vec::with_capacity(self.readers.len());
+        let mut num_vals = 0u32;
+        for reader in &self.readers {
+            let term_ords_reader = try!(reader.term_ords_fast_field(field));
+            let reader_num_vals = term_ords_reader.idx_reader().get(reader.max_doc());
+            num_vals += reader_num_vals;
+            term_ords_readers.push((reader.max_doc(), reader_num_vals, term_ords_reader));
+        }
+        try!(fast_field_serializer.new_u32_fast_field(field, 0, num_vals));
+        let mut shift = 0u32;
+        for &(max_doc, reader_num_vals, ref term_ords_reader) in &term_ords_readers {
+            for doc_id in 0..max_doc {
+                let offset = term_ords_reader.idx_reader().get(doc_id);
+                try!(fast_field_serializer.add_val(shift + offset));
+            }
+            shift += reader_num_vals;
+        }
+        try!(fast_field_serializer.add_val(num_vals));
+        try!(fast_field_serializer.close_field());
+        let max_ord = (merged_terms.len() as u32).saturating_sub(1);
+        try!(fast_field_serializer.new_u32_fast_field(field, 0, max_ord));
+        for (&(_, reader_num_vals, ref term_ords_reader), ord_mapping) in term_ords_readers.iter().zip(ord_mappings.iter()) {
+            for val_id in 0..reader_num_vals {
+                let ord = term_ords_reader.vals_reader().get(val_id);
+                try!(fast_field_serializer.add_val(ord_mapping[ord as usize]));
+            }
+        }
+        try!(fast_field_serializer.close_field());
         Ok(())
     }
 
     fn write_postings(&self, postings_serializer: &mut PostingsSerializer) -> Result<()> {
         let postings_merger = PostingsMerger::new(&self.readers);
         let mut delta_position_computer = DeltaPositionComputer::new();
-        for (term, mut merged_doc_ids) in postings_merger {
-            try!(postings_serializer.new_term(&term, merged_doc_ids.len() as DocId));
+        for (term, total_term_freq, mut merged_doc_ids) in postings_merger {
+            try!(postings_serializer.new_term(&term, merged_doc_ids.len() as DocId, total_term_freq));
             while merged_doc_ids.advance() {
                 let delta_positions: &[u32] = delta_position_computer.compute_delta_positions(merged_doc_ids.positions());
-                try!(postings_serializer.write_doc(merged_doc_ids.doc(), merged_doc_ids.term_freq(), delta_positions));
+                try!(postings_serializer.write_doc(merged_doc_ids.doc(), merged_doc_ids.term_freq(), delta_positions, Some(merged_doc_ids.payloads())));
             }
             try!(postings_serializer.close_term());
         }
         Ok(())
     }
 
+    fn write_bytes_fast_fields(&self, bytes_fast_field_serializer: &mut BytesFastFieldSerializer) -> Result<()> {
+        for field in self.schema.field_entries()
+             .filter(|&(_, field_entry)| field_entry.is_bytes_fast())
+             .map(|(field, _)| field) {
+            let mut bytes_readers = Vec::new();
+            for reader in &self.readers {
+                let bytes_reader = try!(reader.get_bytes_fast_field_reader(field));
+                bytes_readers.push(bytes_reader);
+            }
+            // the values are simply concatenated, and
+            // the offsets are shifted accordingly.
+            let mut offsets: Vec<u32> = vec!(0u32);
+            let mut shift = 0u32;
+            for bytes_reader in &bytes_readers {
+                let reader_offsets = bytes_reader.offsets();
+                offsets.extend(reader_offsets[1..]
+                    .iter()
+                    .map(|offset| offset + shift));
+                shift += bytes_reader.data().len() as u32;
+            }
+            try!(bytes_fast_field_serializer.new_bytes_field(field, &offsets));
+            for bytes_reader in &bytes_readers {
+                try!(bytes_fast_field_serializer.write_data(bytes_reader.data()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sums the field stats of the merged segments.
+    ///
+    /// The number of unique terms cannot be summed, and is
+    /// computed by the serializer while merging the postings.
+    fn field_stats(&self,) -> Vec<FieldStats> {
+        self.schema.field_entries()
+            .map(|(field, _)| {
+                let mut field_stats = FieldStats::default();
+                for reader in &self.readers {
+                    field_stats += reader.field_stats(field);
+                }
+                field_stats
+            })
+            .collect()
+    }
+
+    /// Concatenates the field presence bitsets of the merged segments.
+    fn field_presence(&self,) -> Vec<BitSet> {
+        self.schema.field_entries()
+            .map(|(field, _)| {
+                let mut field_presence = BitSet::with_max_doc(self.segment_info.max_doc);
+                let mut offset = 0u32;
+                for reader in &self.readers {
+                    let reader_presence = reader.has_field(field);
+                    let mut doc_cursor = reader_presence.next_from(0);
+                    while let Some(doc) = doc_cursor {
+                        field_presence.insert(offset + doc);
+                        doc_cursor = reader_presence.next_from(doc + 1);
+                    }
+                    offset += reader.max_doc();
+                }
+                field_presence
+            })
+            .collect()
+    }
+
     fn write_storable_fields(&self, store_writer: &mut StoreWriter) -> Result<()> {
         for reader in &self.readers {
             let store_reader = reader.get_store_reader();
@@ -262,7 +539,10 @@ impl SerializableSegment for IndexMerger {
         try!(self.write_postings(serializer.get_postings_serializer()));
         try!(self.write_fieldnorms(serializer.get_fieldnorms_serializer()));
         try!(self.write_fast_fields(serializer.get_fast_field_serializer()));
+        try!(self.write_bytes_fast_fields(serializer.get_bytes_fast_field_serializer()));
         try!(self.write_storable_fields(serializer.get_store_writer()));
+        try!(serializer.write_field_stats(&self.field_stats()));
+        try!(serializer.write_field_presence(&self.field_presence(), self.segment_info.max_doc));
         try!(serializer.write_segment_info(&self.segment_info));
         try!(serializer.close());
         Ok(self.segment_info.max_doc)
@@ -280,6 +560,9 @@ mod tests {
     use collector::tests::TestCollector;
     use query::MultiTermQuery;
     use schema::TextIndexingOptions;
+    use std::net::{IpAddr, Ipv6Addr};
+    use std::str::FromStr;
+    use core::FieldStats;
 
     #[test]
     fn test_index_merger() {
@@ -288,7 +571,7 @@ mod tests {
         let text_field = schema_builder.add_text_field("text", text_fieldtype);
         let score_fieldtype = schema::U32Options::default().set_fast();
         let score_field = schema_builder.add_u32_field("score", score_fieldtype);
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
@@ -397,4 +680,362 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_index_merger_64bits_fast_field() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let i64_options = schema::I64Options::default().set_fast().set_indexed();
+        let i64_field = schema_builder.add_i64_field("timestamp", i64_options);
+        let f64_field = schema_builder.add_f64_field("price", schema::F64Options::default().set_fast());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for vals in &[vec!(-3i64, 14i64), vec!(i64::min_value(), 0i64, i64::max_value())] {
+                for &val in vals {
+                    let mut doc = Document::default();
+                    doc.add_text(text_field, "a");
+                    doc.add_i64(i64_field, val);
+                    doc.add_f64(f64_field, val as f64 / 4f64);
+                    index_writer.add_document(doc).unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let segment_reader = searcher.segment_reader(0);
+            let i64_reader = segment_reader.get_i64_fast_field_reader(i64_field).unwrap();
+            assert_eq!(i64_reader.get(0), -3i64);
+            assert_eq!(i64_reader.get(1), 14i64);
+            assert_eq!(i64_reader.get(2), i64::min_value());
+            assert_eq!(i64_reader.get(3), 0i64);
+            assert_eq!(i64_reader.get(4), i64::max_value());
+            let f64_reader = segment_reader.get_f64_fast_field_reader(f64_field).unwrap();
+            assert_eq!(f64_reader.get(0), -0.75f64);
+            assert_eq!(f64_reader.get(1), 3.5f64);
+            assert_eq!(f64_reader.get(3), 0f64);
+            assert_eq!(searcher.doc_freq(&Term::from_field_i64(i64_field, -3i64)), 1);
+            assert_eq!(searcher.doc_freq(&Term::from_field_i64(i64_field, 3i64)), 0);
+        }
+    }
+
+    #[test]
+    fn test_index_merger_bytes_fast_field() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let bytes_options = schema::BytesOptions::default().set_fast().set_stored();
+        let bytes_field = schema_builder.add_bytes_field("payload", bytes_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_bytes(bytes_field, vec!(1u8, 2u8));
+                index_writer.add_document(doc).unwrap();
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_bytes(bytes_field, vec!(3u8, 4u8, 5u8));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let segment_reader = searcher.segment_reader(0);
+            let bytes_reader = segment_reader.get_bytes_fast_field_reader(bytes_field).unwrap();
+            assert_eq!(bytes_reader.get(0), &[1u8, 2u8]);
+            assert!(bytes_reader.get(1).is_empty());
+            assert_eq!(bytes_reader.get(2), &[3u8, 4u8, 5u8]);
+            let doc = searcher.doc(&DocAddress(0, 2)).unwrap();
+            assert_eq!(doc.get_first(bytes_field).unwrap().bytes_value(), &[3u8, 4u8, 5u8]);
+        }
+    }
+
+    #[test]
+    fn test_index_merger_bool_field() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let bool_options = schema::BoolOptions::default().set_indexed().set_fast().set_stored();
+        let bool_field = schema_builder.add_bool_field("is_published", bool_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_bool(bool_field, true);
+                index_writer.add_document(doc).unwrap();
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_bool(bool_field, false);
+                index_writer.add_document(doc).unwrap();
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_bool(bool_field, true);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let segment_reader = searcher.segment_reader(0);
+            let bool_reader = segment_reader.get_bool_fast_field_reader(bool_field).unwrap();
+            assert!(bool_reader.get(0));
+            assert!(!bool_reader.get(1));
+            assert!(!bool_reader.get(2));
+            assert!(bool_reader.get(3));
+            assert!(segment_reader.get_fast_field_reader(bool_field).is_err());
+            assert_eq!(searcher.doc_freq(&Term::from_field_bool(bool_field, true)), 2);
+            assert_eq!(searcher.doc_freq(&Term::from_field_bool(bool_field, false)), 1);
+            let doc = searcher.doc(&DocAddress(0, 3)).unwrap();
+            assert!(doc.get_first(bool_field).unwrap().bool_value());
+        }
+    }
+
+    #[test]
+    fn test_index_merger_ip_field() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let ip_options = schema::IpOptions::default().set_indexed().set_fast();
+        let ip_field = schema_builder.add_ip_field("src_ip", ip_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        let ips = ["10.0.0.1", "2001:db8::ff", "192.168.0.12"];
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for ip_text in &ips {
+                let mut doc = Document::default();
+                doc.add_ip(ip_field, IpAddr::from_str(ip_text).unwrap());
+                index_writer.add_document(doc).unwrap();
+                index_writer.commit().unwrap();
+            }
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let segment_reader = searcher.segment_reader(0);
+            let ip_reader = segment_reader.get_ip_fast_field_reader(ip_field).unwrap();
+            let mut merged_ips: Vec<Ipv6Addr> = (0..3)
+                .map(|doc| ip_reader.get(doc))
+                .collect();
+            merged_ips.sort();
+            let mut expected_ips: Vec<Ipv6Addr> = ips
+                .iter()
+                .map(|ip_text| schema::parse_ip(ip_text).unwrap())
+                .collect();
+            expected_ips.sort();
+            assert_eq!(merged_ips, expected_ips);
+            for expected_ip in expected_ips {
+                assert_eq!(searcher.doc_freq(&Term::from_field_ip(ip_field, expected_ip)), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_merger_multivalued_fast_field() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let u32_options = schema::U32Options::default()
+            .set_fast()
+            .set_cardinality(schema::Cardinality::MultiValues);
+        let multi_field = schema_builder.add_u32_field("multi", u32_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_u32(multi_field, 3u32);
+                doc.add_u32(multi_field, 1u32);
+                index_writer.add_document(doc).unwrap();
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+            {
+                // a segment without any value
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add_u32(multi_field, 10u32);
+                doc.add_u32(multi_field, 2u32);
+                doc.add_u32(multi_field, 10u32);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let segment_reader = searcher.segment_reader(0);
+            let multi_reader = segment_reader.multi_u32_fast_field(multi_field).unwrap();
+            let mut vals = Vec::new();
+            multi_reader.get_vals(0, &mut vals);
+            assert_eq!(vals, vec!(3u32, 1u32));
+            multi_reader.get_vals(1, &mut vals);
+            assert!(vals.is_empty());
+            multi_reader.get_vals(2, &mut vals);
+            assert!(vals.is_empty());
+            multi_reader.get_vals(3, &mut vals);
+            assert_eq!(vals, vec!(10u32, 2u32, 10u32));
+            assert!(segment_reader.get_fast_field_reader(multi_field).is_err());
+        }
+    }
+
+    #[test]
+    fn test_index_merger_facet() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let facet_options = schema::FacetOptions::default().set_indexed().set_stored();
+        let facet_field = schema_builder.add_facet_field("category", facet_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_facet(facet_field, schema::Facet::from_text("/electronics/phones"));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_facet(facet_field, schema::Facet::from_text("/electronics/tv"));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let doc_freq = |facet_text: &str| {
+                let term = Term::from_facet(facet_field, &schema::Facet::from_text(facet_text));
+                searcher.doc_freq(&term)
+            };
+            assert_eq!(doc_freq("/electronics"), 2);
+            assert_eq!(doc_freq("/electronics/phones"), 1);
+            assert_eq!(doc_freq("/electronics/tv"), 1);
+            let doc = searcher.doc(&DocAddress(0, 1)).unwrap();
+            assert_eq!(doc.get_first(facet_field).unwrap().facet_value(), &schema::Facet::from_text("/electronics/tv"));
+        }
+    }
+
+    #[test]
+    fn test_index_merger_field_stats() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let count_field = schema_builder.add_u32_field("count", schema::U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let docs = vec!(
+                ("a b", Some(1u32)),
+                ("b c c", None),
+                ("", Some(2u32)),
+                ("c d", Some(1u32)),
+            );
+            for (i, (text, count)) in docs.into_iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                if let Some(count) = count {
+                    doc.add_u32(count_field, count);
+                }
+                index_writer.add_document(doc).unwrap();
+                if i == 1 {
+                    // a, b, c in the first segment, c, d in the second one.
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let text_stats = FieldStats {
+            num_docs_with_field: 3,
+            total_num_tokens: 7,
+            num_unique_terms: 5,
+        };
+        let count_stats = FieldStats {
+            num_docs_with_field: 3,
+            total_num_tokens: 3,
+            num_unique_terms: 3,
+        };
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 2);
+            assert_eq!(searcher.field_stats(text_field), text_stats);
+            assert_eq!(searcher.field_stats(count_field), count_stats);
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            // the terms shared by both segments are only counted once.
+            assert_eq!(
+                searcher.segment_reader(0).field_stats(text_field),
+                FieldStats { num_unique_terms: 4, .. text_stats });
+            assert_eq!(
+                searcher.segment_reader(0).field_stats(count_field),
+                FieldStats { num_unique_terms: 2, .. count_stats });
+        }
+    }
 }
@@ -12,9 +12,10 @@ mod directory_lock;
 
 pub use self::segment_serializer::SegmentSerializer;
 pub use self::segment_writer::SegmentWriter;
-pub use self::index_writer::IndexWriter;
+pub use self::index_writer::{IndexWriter, CommitStats};
 pub use self::simple_merge_policy::SimpleMergePolicy;
 pub use self::merge_policy::{NoMergePolicy, MergeCandidate, MergePolicy};
 pub use self::segment_manager::SegmentManager;
+pub use self::segment_updater::save_metas;
 
 pub type DefaultMergePolicy = SimpleMergePolicy;
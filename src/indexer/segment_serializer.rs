@@ -4,8 +4,15 @@ use std::io::Write;
 use rustc_serialize::json;
 use core::Segment;
 use core::SegmentInfo;
+use core::FieldStats;
+use common::BitSet;
+use common::BinarySerializable;
+use DocId;
+use schema::Field;
 use core::SegmentComponent;
+use directory;
 use fastfield::FastFieldSerializer;
+use fastfield::BytesFastFieldSerializer;
 use store::StoreWriter;
 use postings::PostingsSerializer;
 
@@ -16,6 +23,7 @@ pub struct SegmentSerializer {
     segment: Segment,
     store_writer: StoreWriter,
     fast_field_serializer: FastFieldSerializer,
+    bytes_fast_field_serializer: BytesFastFieldSerializer,
     fieldnorms_serializer: FastFieldSerializer,
     postings_serializer: PostingsSerializer,
 }
@@ -28,6 +36,9 @@ impl SegmentSerializer {
         let fast_field_write = try!(segment.open_write(SegmentComponent::FASTFIELDS));
         let fast_field_serializer = try!(FastFieldSerializer::new(fast_field_write));
 
+        let bytes_fast_field_write = try!(segment.open_write(SegmentComponent::BYTESFASTFIELDS));
+        let bytes_fast_field_serializer = try!(BytesFastFieldSerializer::new(bytes_fast_field_write));
+
         let fieldnorms_write = try!(segment.open_write(SegmentComponent::FIELDNORMS));
         let fieldnorms_serializer = try!(FastFieldSerializer::new(fieldnorms_write));
 
@@ -37,6 +48,7 @@ impl SegmentSerializer {
             postings_serializer: postings_serializer,
             store_writer: StoreWriter::new(store_write),
             fast_field_serializer: fast_field_serializer,
+            bytes_fast_field_serializer: bytes_fast_field_serializer,
             fieldnorms_serializer: fieldnorms_serializer,
         })
     }
@@ -51,6 +63,11 @@ impl SegmentSerializer {
         &mut self.fast_field_serializer
     }
 
+    /// Accessor to the `BytesFastFieldSerializer`.
+    pub fn get_bytes_fast_field_serializer(&mut self) -> &mut BytesFastFieldSerializer {
+        &mut self.bytes_fast_field_serializer
+    }
+
     /// Accessor to the field norm serializer.
     pub fn get_fieldnorms_serializer(&mut self) -> &mut FastFieldSerializer {
         &mut self.fieldnorms_serializer
@@ -62,21 +79,84 @@ impl SegmentSerializer {
     }
 
     /// Write the `SegmentInfo`
+    ///
+    /// The type and the statistics of the term dictionary are
+    /// filled from the postings serializer, so this needs to be
+    /// called after the postings have been serialized.
     pub fn write_segment_info(&mut self, segment_info: &SegmentInfo) -> Result<()> {
+        let segment_info = SegmentInfo {
+            term_dictionary_type: Some(self.postings_serializer.term_dictionary_type()),
+            term_dictionary_stats: Some(self.postings_serializer.term_dictionary_stats()),
+            .. segment_info.clone()
+        };
         let mut write = try!(self.segment.open_write(SegmentComponent::INFO));
-        let json_data = json::encode(segment_info)
+        let json_data = json::encode(&segment_info)
             .expect("Encoding to segment_info to JSON failed. This should never happen");
         try!(write.write_all(json_data.as_bytes()));
         try!(write.flush());
         Ok(())
     }
 
+    /// Write the `FieldStats` of each field of the schema.
+    ///
+    /// The number of unique terms is filled from the postings
+    /// serializer, so this needs to be called after the postings
+    /// have been serialized.
+    pub fn write_field_stats(&mut self, field_stats: &[FieldStats]) -> Result<()> {
+        let field_stats: Vec<FieldStats> = field_stats
+            .iter()
+            .enumerate()
+            .map(|(field_id, &stats)| {
+                FieldStats {
+                    num_unique_terms: self.postings_serializer.num_terms(Field(field_id as u8)),
+                    .. stats
+                }
+            })
+            .collect();
+        let mut write = try!(self.segment.open_write(SegmentComponent::FIELDSTATS));
+        let json_data = json::encode(&field_stats)
+            .expect("Encoding field stats to JSON failed. This should never happen");
+        try!(write.write_all(json_data.as_bytes()));
+        try!(directory::terminate(&mut write));
+        Ok(())
+    }
+
+    /// Write the set of documents having at least one
+    /// value, for each field of the schema.
+    ///
+    /// The bitsets are extended to span the `max_doc` documents
+    /// of the segment.
+    pub fn write_field_presence(&mut self, field_presence: &[BitSet], max_doc: DocId) -> Result<()> {
+        let field_presence: Vec<BitSet> = field_presence
+            .iter()
+            .map(|bitset| {
+                let mut bitset = bitset.clone();
+                bitset.set_max_doc(max_doc);
+                bitset
+            })
+            .collect();
+        let mut write = try!(self.segment.open_write(SegmentComponent::FIELDPRESENCE));
+        try!(field_presence.serialize(&mut write));
+        try!(directory::terminate(&mut write));
+        Ok(())
+    }
+
     /// Finalize the segment serialization.
+    ///
+    /// The number of bytes taken by each field in the
+    /// inverted index is only known once the postings serializer
+    /// is closed, and is written at this point.
     pub fn close(self) -> Result<()> {
         try!(self.fast_field_serializer.close());
-        try!(self.postings_serializer.close());
+        try!(self.bytes_fast_field_serializer.close());
+        let field_space_usage = try!(self.postings_serializer.close());
         try!(self.store_writer.close());
         try!(self.fieldnorms_serializer.close());
+        let mut write = try!(self.segment.open_write(SegmentComponent::SPACEUSAGE));
+        let json_data = json::encode(&field_space_usage)
+            .expect("Encoding field space usage to JSON failed. This should never happen");
+        try!(write.write_all(json_data.as_bytes()));
+        try!(directory::terminate(&mut write));
         Ok(())
     }
 }
@@ -13,6 +13,7 @@ use indexer::merger::IndexMerger;
 use indexer::SegmentSerializer;
 use std::thread;
 use schema::Schema;
+use datastruct::TermDictionaryType;
 use directory::Directory;
 use std::thread::JoinHandle;
 use std::sync::Arc;
@@ -29,13 +30,18 @@ pub type SegmentUpdateSender = chan::Sender<SegmentUpdate>;
 pub type SegmentUpdateReceiver = chan::Receiver<SegmentUpdate>;
 
 
-fn create_metas(segment_manager: &SegmentManager, schema: Schema, docstamp: u64) -> IndexMeta {
+fn create_metas(segment_manager: &SegmentManager,
+                schema: Schema,
+                docstamp: u64,
+                term_dictionary_type: TermDictionaryType)
+                -> IndexMeta {
     let (committed_segments, uncommitted_segments) = segment_manager.segment_metas();
     IndexMeta {
         committed_segments: committed_segments,
         uncommitted_segments: uncommitted_segments,
         schema: schema,
         docstamp: docstamp,
+        term_dictionary_type: Some(term_dictionary_type),
     }
 }
 
@@ -52,9 +58,10 @@ fn create_metas(segment_manager: &SegmentManager, schema: Schema, docstamp: u64)
 pub fn save_metas(segment_manager: &SegmentManager,
                   schema: Schema,
                   docstamp: u64,
+                  term_dictionary_type: TermDictionaryType,
                   directory: &mut Directory)
                   -> Result<()> {
-    let metas = create_metas(segment_manager, schema, docstamp);
+    let metas = create_metas(segment_manager, schema, docstamp, term_dictionary_type);
     let mut w = Vec::new();
     try!(write!(&mut w, "{}\n", json::as_pretty_json(&metas)));
     directory.atomic_write(&META_FILEPATH, &w[..])
@@ -162,6 +169,7 @@ impl SegmentUpdater {
             &*segment_manager,
             self.index.schema(),
             self.index.docstamp(),
+            self.index.term_dictionary_type(),
             self.index.directory_mut()).expect("Could not save metas.");
         for segment_id in segment_ids {
             self.index.delete_segment(segment_id);
@@ -202,6 +210,7 @@ impl SegmentUpdater {
                     let segment_meta = SegmentMeta {
                         segment_id: merged_segment.id(),
                         num_docs: num_docs,
+                        analyzers: SegmentMeta::merged_segment_analyzers(&get_segment_manager(&index_clone), &segment_ids),
                     };
                     let segment_update = SegmentUpdate::EndMerge(merging_thread_id, segment_ids.clone(), segment_meta.clone());
                     segment_update_sender_clone.send(segment_update.clone());
@@ -263,6 +272,7 @@ impl SegmentUpdater {
                     &*segment_manager,
                     self.index.schema(),
                     self.index.docstamp(),
+                    self.index.term_dictionary_type(),
                     self.index.directory_mut()).expect("Could not save metas.");
 
 
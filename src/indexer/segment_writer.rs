@@ -1,24 +1,35 @@
 use Result;
+use Error;
 use DocId;
 use std::io;
+use std::collections::BTreeMap;
 use schema::Schema;
 use schema::Document;
-use schema::Term;
+use schema::{Term, TERMS_FORMAT_VERSION};
 use core::SegmentInfo;
+use core::FieldStats;
+use common::BitSet;
 use core::Segment;
 use core::SerializableSegment;
 use postings::PostingsWriter;
 use fastfield::U32FastFieldsWriter;
+use fastfield::BytesFastFieldsWriter;
+use fastfield::{fieldnorm_to_id, FIELDNORMS_FORMAT_VERSION};
 use schema::Field;
 use schema::FieldEntry;
 use schema::FieldValue;
+use schema::Value;
 use schema::FieldType;
 use schema::TextIndexingOptions;
 use postings::SpecializedPostingsWriter;
-use postings::{NothingRecorder, TermFrequencyRecorder, TFAndPositionRecorder};
+use postings::POSTINGS_FORMAT_VERSION;
+use directory::FOOTER_VERSION;
+use postings::{NothingRecorder, TermFrequencyRecorder, TFAndPositionRecorder, TFPositionAndPayloadRecorder};
 use indexer::segment_serializer::SegmentSerializer;
 use datastruct::stacker::Heap;
 use indexer::index_writer::MARGIN_IN_BYTES;
+use analyzer::{Tokenizer, TokenizerManager};
+use std::sync::Arc;
 
 /// A `SegmentWriter` is in charge of creating segment index from a
 /// documents.
@@ -31,21 +42,74 @@ pub struct SegmentWriter<'a> {
 	per_field_postings_writers: Vec<Box<PostingsWriter + 'a>>,
 	segment_serializer: SegmentSerializer,
 	fast_field_writers: U32FastFieldsWriter,
+	bytes_fast_field_writers: BytesFastFieldsWriter,
 	fieldnorms_writer: U32FastFieldsWriter,
+	field_stats: Vec<FieldStats>,
+	field_presence: Vec<BitSet>,
+	tokenizers: Vec<Option<Arc<Tokenizer>>>,
+	num_truncated_docs: u64,
+	num_clamped_docs: u64,
 }
 
 
 fn create_fieldnorms_writer(schema: &Schema) -> U32FastFieldsWriter {
-	let u32_fields: Vec<Field> = schema.fields()
-		.iter()
-		.enumerate()
-		.filter(|&(_, field_entry)| field_entry.is_indexed()) 
-		.map(|(field_id, _)| Field(field_id as u8))
+	let u32_fields: Vec<Field> = schema.field_entries()
+		.filter(|&(_, field_entry)| field_entry.has_fieldnorms())
+		.map(|(field, _)| field)
 		.collect();
 	U32FastFieldsWriter::new(u32_fields)
 }
 
 
+/// Returns the field values of the document, grouped and sorted by field,
+/// in which the values of the fields with a `copy_to` option 
+/// are appended to the values of their destination field.
+fn with_copied_field_values<'a>(doc: &'a Document, schema: &Schema) -> BTreeMap<Field, Vec<&'a FieldValue>> {
+	let mut field_values_map: BTreeMap<Field, Vec<&'a FieldValue>> = doc
+		.get_sorted_field_values()
+		.into_iter()
+		.collect();
+	let mut copied_field_values: Vec<(Field, Vec<&'a FieldValue>)> = Vec::new();
+	for (field, field_values) in &field_values_map {
+		if let FieldType::Str(ref text_options) = *schema.get_field_entry(*field).field_type() {
+			if let Some(dest_field) = text_options.get_copy_to() {
+				copied_field_values.push((dest_field, field_values.clone()));
+			}
+		}
+	}
+	for (dest_field, field_values) in copied_field_values {
+		field_values_map
+			.entry(dest_field)
+			.or_insert_with(Vec::new)
+			.extend(field_values);
+	}
+	field_values_map
+}
+
+
+/// Returns the tokenizer of each field, or `None` for the
+/// fields that are not tokenized.
+fn tokenizers_from_schema(schema: &Schema, tokenizer_manager: &TokenizerManager) -> Result<Vec<Option<Arc<Tokenizer>>>> {
+	let mut tokenizers = Vec::with_capacity(schema.fields().len());
+	for field_entry in schema.fields() {
+		let tokenizer_opt = match *field_entry.field_type() {
+			FieldType::Str(ref text_options) if text_options.get_indexing_options().is_tokenized() => {
+				let tokenizer_name = text_options.get_tokenizer();
+				let tokenizer = try!(tokenizer_manager
+					.get(tokenizer_name)
+					.ok_or_else(|| {
+						Error::InvalidArgument(format!("Tokenizer {:?} of field {:?} is not registered", tokenizer_name, field_entry.name()))
+					}));
+				Some(tokenizer)
+			}
+			_ => None,
+		};
+		tokenizers.push(tokenizer_opt);
+	}
+	Ok(tokenizers)
+}
+
+
 fn posting_from_field_entry<'a>(field_entry: &FieldEntry, heap: &'a Heap) -> Box<PostingsWriter + 'a> {
 	match *field_entry.field_type() {
 		FieldType::Str(ref text_options) => {
@@ -56,12 +120,15 @@ fn posting_from_field_entry<'a>(field_entry: &FieldEntry, heap: &'a Heap) -> Box
 				TextIndexingOptions::TokenizedWithFreqAndPosition => {
 					SpecializedPostingsWriter::<TFAndPositionRecorder>::new_boxed(heap)
 				}
+				TextIndexingOptions::TokenizedWithFreqPositionAndPayload => {
+					SpecializedPostingsWriter::<TFPositionAndPayloadRecorder>::new_boxed(heap)
+				}
 				_ => {
 					SpecializedPostingsWriter::<NothingRecorder>::new_boxed(heap)
 				}
 			}
 		} 
-		FieldType::U32(_) => {
+		FieldType::U32(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::Date(_) | FieldType::Bytes(_) | FieldType::HierarchicalFacet(_) | FieldType::Bool(_) | FieldType::Ip(_) => {
 			SpecializedPostingsWriter::<NothingRecorder>::new_boxed(heap)
 		}
 	}
@@ -80,7 +147,11 @@ impl<'a> SegmentWriter<'a> {
 	/// the flushing behavior as a buffer limit
 	/// - segment: The segment being written  
 	/// - schema
+	///
+	/// The tokenizers of the text fields are looked up in the
+	/// `TokenizerManager` of the segment's index.
 	pub fn for_segment(heap: &'a Heap, mut segment: Segment, schema: &Schema) -> Result<SegmentWriter<'a>> {
+		let tokenizers = try!(tokenizers_from_schema(schema, segment.index().tokenizers()));
 		let segment_serializer = try!(SegmentSerializer::for_segment(&mut segment));
 		let mut per_field_postings_writers: Vec<Box<PostingsWriter + 'a>> = Vec::new();
 		for field_entry in schema.fields() {
@@ -94,6 +165,12 @@ impl<'a> SegmentWriter<'a> {
 			fieldnorms_writer: create_fieldnorms_writer(schema),
 			segment_serializer: segment_serializer,
 			fast_field_writers: U32FastFieldsWriter::from_schema(schema),
+			bytes_fast_field_writers: BytesFastFieldsWriter::from_schema(schema),
+			field_stats: vec!(FieldStats::default(); schema.fields().len()),
+			field_presence: vec!(BitSet::default(); schema.fields().len()),
+			tokenizers: tokenizers,
+			num_truncated_docs: 0u64,
+			num_clamped_docs: 0u64,
 		})
 	}
 	
@@ -108,7 +185,10 @@ impl<'a> SegmentWriter<'a> {
 		}
 		try!(write(&self.per_field_postings_writers,
 			  &self.fast_field_writers,
+			  &self.bytes_fast_field_writers,
 			  &self.fieldnorms_writer,
+			  &self.field_stats,
+			  &self.field_presence,
 			  segment_info,
 			  self.segment_serializer,
 			  self.heap));
@@ -122,8 +202,12 @@ impl<'a> SegmentWriter<'a> {
 	///
 	/// Because, we cannot cut through a document, the margin is there to ensure that we rarely
 	/// exceeds the heap size.  
+	///
+	/// The terms of the fast text fields are not stored in the heap:
+	/// they are counted as if they were.
 	pub fn is_buffer_full(&self,) -> bool {
-		self.heap.num_free_bytes() <= MARGIN_IN_BYTES
+		let term_ords_mem_usage = self.fast_field_writers.term_ords_mem_usage() as u64;
+		self.heap.num_free_bytes() as u64 <= MARGIN_IN_BYTES as u64 + term_ords_mem_usage
 	}
 	
 	/// Indexes a new document
@@ -131,28 +215,51 @@ impl<'a> SegmentWriter<'a> {
 	/// As a user, you should rather use `IndexWriter`'s add_document.
     pub fn add_document(&mut self, doc: &Document, schema: &Schema) -> io::Result<()> {
         let doc_id = self.max_doc;
-        for (field, field_values) in doc.get_sorted_field_values() {
+		let mut doc_truncated = false;
+		let mut doc_clamped = false;
+        for (field, field_values) in with_copied_field_values(doc, schema) {
 			let field_posting_writer: &mut Box<PostingsWriter> = &mut self.per_field_postings_writers[field.0 as usize];
 			let field_options = schema.get_field_entry(field);
-			match *field_options.field_type() {
+			let num_field_values = field_values.len() as u32;
+			let num_tokens: u32 = match *field_options.field_type() {
 				FieldType::Str(ref text_options) => {
+					if !text_options.get_indexing_options().is_indexed() {
+						// stored-only fields are only handed to the store writer.
+						continue;
+					}
 					let num_tokens: u32 =
-						if text_options.get_indexing_options().is_tokenized() {
-							field_posting_writer.index_text(doc_id, field, &field_values, self.heap)
+						if let Some(ref tokenizer) = self.tokenizers[field.0 as usize] {
+							let indexed_text = field_posting_writer.index_text(
+								doc_id, field, &field_values, &**tokenizer,
+								text_options.get_position_gap(),
+								text_options.get_max_positions_per_doc(),
+								self.heap);
+							doc_truncated |= indexed_text.truncated;
+							doc_clamped |= indexed_text.clamped;
+							indexed_text.num_tokens
 						}
 						else {
-							let num_field_values = field_values.len() as u32;
+							let indexing_options = text_options.get_indexing_options();
 							for field_value in field_values {
-								let term = Term::from_field_text(field, field_value.value().text());
+								let term = match *field_value.value() {
+									// an untokenized field indexes the text
+									// that gets stored, that is the joined tokens.
+									Value::PreTokenized(ref payload) => indexing_options.untokenized_term(field, &payload.text()),
+									ref value => indexing_options.untokenized_term(field, value.text()),
+								};
 								field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+								if let Some(term_ords_writer) = self.fast_field_writers.get_term_ords_writer(field) {
+									term_ords_writer.add_term(term.value_bytes());
+								}
 							}
 							num_field_values
 						};
 					self.fieldnorms_writer
 						.get_field_writer(field)
 						.map(|field_norms_writer| {
-							field_norms_writer.add_val(num_tokens as u32)
+							field_norms_writer.add_val(fieldnorm_to_id(num_tokens) as u32)
 						});
+					num_tokens
 				}
 				FieldType::U32(ref u32_options) => {
 					if u32_options.is_indexed() {
@@ -160,12 +267,109 @@ impl<'a> SegmentWriter<'a> {
 							let term = Term::from_field_u32(field_value.field(), field_value.value().u32_value());
 							field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
 						}
+						num_field_values
+					}
+					else {
+						0u32
+					}
+				}
+				FieldType::I64(ref i64_options) => {
+					if i64_options.is_indexed() {
+						for field_value in field_values {
+							let term = Term::from_field_i64(field_value.field(), field_value.value().i64_value());
+							field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+						}
+						num_field_values
+					}
+					else {
+						0u32
+					}
+				}
+				FieldType::F64(ref f64_options) => {
+					if f64_options.is_indexed() {
+						for field_value in field_values {
+							let term = Term::from_field_f64(field_value.field(), field_value.value().f64_value());
+							field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+						}
+						num_field_values
+					}
+					else {
+						0u32
 					}
 				}
+				FieldType::Date(ref date_options) => {
+					if date_options.is_indexed() {
+						let precision = date_options.get_precision();
+						for field_value in field_values {
+							let millis = precision.truncate(field_value.value().date_value());
+							let term = Term::from_field_date(field_value.field(), millis);
+							field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+						}
+						num_field_values
+					}
+					else {
+						0u32
+					}
+				}
+				FieldType::Bytes(_) => {
+					// bytes fields are never indexed.
+					0u32
+				}
+				FieldType::Bool(ref bool_options) => {
+					if bool_options.is_indexed() {
+						for field_value in field_values {
+							let term = Term::from_field_bool(field_value.field(), field_value.value().bool_value());
+							field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+						}
+						num_field_values
+					}
+					else {
+						0u32
+					}
+				}
+				FieldType::Ip(ref ip_options) => {
+					if ip_options.is_indexed() {
+						for field_value in field_values {
+							let term = Term::from_field_ip(field_value.field(), field_value.value().ip_value());
+							field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+						}
+						num_field_values
+					}
+					else {
+						0u32
+					}
+				}
+				FieldType::HierarchicalFacet(ref facet_options) => {
+					if facet_options.is_indexed() {
+						// the document suscribes to all of the prefixes of its facets,
+						// so that searching for `/electronics` matches `/electronics/phones`.
+						let mut num_terms = 0u32;
+						for field_value in field_values {
+							for facet_prefix in field_value.value().facet_value().prefixes() {
+								let term = Term::from_facet(field_value.field(), &facet_prefix);
+								field_posting_writer.suscribe(doc_id, 0, &term, self.heap);
+								num_terms += 1;
+							}
+						}
+						num_terms
+					}
+					else {
+						0u32
+					}
+				}
+			};
+			if num_tokens > 0 {
+				let field_stats = &mut self.field_stats[field.0 as usize];
+				field_stats.num_docs_with_field += 1;
+				field_stats.total_num_tokens += num_tokens as u64;
 			}
 		}
+		for field_value in doc.field_values() {
+			self.field_presence[field_value.field().0 as usize].insert(doc_id);
+		}
 		self.fieldnorms_writer.fill_val_up_to(doc_id);
 		self.fast_field_writers.add_document(doc);
+		self.bytes_fast_field_writers.add_document(doc);
 		let stored_fieldvalues: Vec<&FieldValue> = doc
 			.field_values()
 			.iter()
@@ -173,6 +377,12 @@ impl<'a> SegmentWriter<'a> {
 			.collect();
 		let doc_writer = self.segment_serializer.get_store_writer();
 		try!(doc_writer.store(&stored_fieldvalues));
+		if doc_truncated {
+			self.num_truncated_docs += 1;
+		}
+		if doc_clamped {
+			self.num_clamped_docs += 1;
+		}
         self.max_doc += 1;
 		Ok(())
     }
@@ -181,11 +391,30 @@ impl<'a> SegmentWriter<'a> {
 	/// with the index in JSON format.  
  	fn segment_info(&self,) -> SegmentInfo {
 		SegmentInfo {
-			max_doc: self.max_doc
+			max_doc: self.max_doc,
+			postings_format_version: Some(POSTINGS_FORMAT_VERSION),
+			footer_version: Some(FOOTER_VERSION),
+			fieldnorms_format_version: Some(FIELDNORMS_FORMAT_VERSION),
+			terms_format_version: Some(TERMS_FORMAT_VERSION),
+			// filled by the `SegmentSerializer`.
+			term_dictionary_type: None,
+			term_dictionary_stats: None,
 		}
 	}
 	
 	
+	/// Number of documents with a field that got truncated
+	/// because of its `max_positions_per_doc` option.
+	pub fn num_truncated_docs(&self,) -> u64 {
+		self.num_truncated_docs
+	}
+
+	/// Number of documents with a position that got
+	/// clamped to `MAX_POSITION`.
+	pub fn num_clamped_docs(&self,) -> u64 {
+		self.num_clamped_docs
+	}
+	
 	/// Max doc is 
 	/// - the number of documents in the segment assuming there is no deletes
 	/// - the maximum document id (including deleted documents) + 1
@@ -211,7 +440,10 @@ impl<'a> SegmentWriter<'a> {
 // This method is used as a trick to workaround the borrow checker
 fn write<'a>(per_field_postings_writers: &[Box<PostingsWriter + 'a>],
 		 fast_field_writers: &U32FastFieldsWriter,
+		 bytes_fast_field_writers: &BytesFastFieldsWriter,
 		 fieldnorms_writer: &U32FastFieldsWriter,
+		 field_stats: &[FieldStats],
+		 field_presence: &[BitSet],
 		 segment_info: SegmentInfo,
 	  	 mut serializer: SegmentSerializer,
 		 heap: &'a Heap,) -> Result<u32> {
@@ -219,7 +451,10 @@ fn write<'a>(per_field_postings_writers: &[Box<PostingsWriter + 'a>],
 			try!(per_field_postings_writer.serialize(serializer.get_postings_serializer(), heap));
 		}
 		try!(fast_field_writers.serialize(serializer.get_fast_field_serializer()));
+		try!(bytes_fast_field_writers.serialize(serializer.get_bytes_fast_field_serializer()));
 		try!(fieldnorms_writer.serialize(serializer.get_fieldnorms_serializer()));
+		try!(serializer.write_field_stats(field_stats));
+		try!(serializer.write_field_presence(field_presence, segment_info.max_doc));
 		try!(serializer.write_segment_info(&segment_info));
 		try!(serializer.close());
 		Ok(segment_info.max_doc)
@@ -229,9 +464,443 @@ impl<'a> SerializableSegment for SegmentWriter<'a> {
 	fn write(&self, serializer: SegmentSerializer) -> Result<u32> {
 		write(&self.per_field_postings_writers,
 		      &self.fast_field_writers,
+			  &self.bytes_fast_field_writers,
 			  &self.fieldnorms_writer,
+			  &self.field_stats,
+			  &self.field_presence,
 			  self.segment_info(),
 		      serializer,
 			  self.heap)
 	}
 }
+
+
+#[cfg(test)]
+mod tests {
+
+	use schema::{self, SchemaBuilder, Document, Field, Term, TokenStreamPayload};
+	use schema::{TextOptions, TextIndexingOptions};
+	use Error;
+	use test::Bencher;
+	use core::Index;
+	use postings::{DocSet, Postings, MAX_POSITION};
+	use core::SegmentComponent;
+	use DocAddress;
+	use query::{QueryParser, Query, PhraseQuery};
+	use collector::tests::TestCollector;
+	use datastruct::stacker::Heap;
+	use datastruct::{TermDictionary, TermDictionaryKeys};
+	use super::SegmentWriter;
+	use fastfield::{fieldnorm_to_id, id_to_fieldnorm};
+	use directory::FOOTER_LEN;
+
+	fn component_len(index: &Index, component: SegmentComponent) -> usize {
+		let segments = index.searchable_segments();
+		assert_eq!(segments.len(), 1);
+		segments[0].open_read(component).unwrap().len()
+	}
+
+	fn index_one_doc(stored_only_text: Option<&str>) -> (Index, Field) {
+		let mut schema_builder = SchemaBuilder::default();
+		let text_field = schema_builder.add_text_field("text", schema::TEXT);
+		let stored_only_field = schema_builder.add_text_field("body", schema::TEXT_STORED_ONLY);
+		let index = Index::create_in_ram(schema_builder.build().unwrap());
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			let mut doc = Document::default();
+			doc.add_text(text_field, "hello happy tax payer");
+			if let Some(text) = stored_only_text {
+				doc.add_text(stored_only_field, text);
+			}
+			index_writer.add_document(doc).unwrap();
+			index_writer.commit().unwrap();
+		}
+		(index, stored_only_field)
+	}
+
+	#[test]
+	fn test_stored_only_field_skips_postings() {
+		let large_text: String = (0..250_000)
+			.map(|i| format!("w{} ", i % 1_000))
+			.collect::<Vec<String>>()
+			.concat();
+		assert!(large_text.len() >= 1_000_000);
+		let (index_without, _) = index_one_doc(None);
+		let (index_with, stored_only_field) = index_one_doc(Some(&large_text));
+		assert_eq!(component_len(&index_with, SegmentComponent::POSTINGS), component_len(&index_without, SegmentComponent::POSTINGS));
+		assert_eq!(component_len(&index_with, SegmentComponent::TERMS), component_len(&index_without, SegmentComponent::TERMS));
+		let searcher = index_with.searcher();
+		{
+			let mut keys = searcher.segment_reader(0).term_infos().keys();
+			while let Some(key) = keys.next() {
+				assert!(key[0] != stored_only_field.0);
+			}
+		}
+		let doc = searcher.doc(&DocAddress(0, 0)).unwrap();
+		assert_eq!(doc.get_first(stored_only_field).unwrap().text(), &large_text[..]);
+	}
+
+	#[test]
+	fn test_copy_to() {
+		let mut schema_builder = SchemaBuilder::default();
+		let all_field = schema_builder.add_text_field("all", schema::TEXT.set_position_gap(10));
+		let title_field = schema_builder.add_text_field("title", schema::TEXT.set_stored().set_copy_to(all_field));
+		let body_field = schema_builder.add_text_field("body", schema::TEXT.set_copy_to(all_field));
+		let index = Index::create_in_ram(schema_builder.build().unwrap());
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			let mut doc = Document::default();
+			doc.add_text(body_field, "c d");
+			doc.add_text(title_field, "a b");
+			doc.add_text(all_field, "e");
+			index_writer.add_document(doc).unwrap();
+			index_writer.commit().unwrap();
+		}
+		let searcher = index.searcher();
+		let segment_reader = searcher.segment_reader(0);
+		{
+			// the own values of the destination field come first,
+			// followed by the copied values, in field order.
+			let expected_positions = vec!(("e", 0), ("a", 11), ("b", 12), ("c", 23), ("d", 24));
+			for (text, position) in expected_positions {
+				let term = Term::from_field_text(all_field, text);
+				let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+				assert!(postings.advance());
+				assert_eq!(postings.doc(), 0);
+				assert_eq!(postings.term_freq(), 1);
+				assert_eq!(postings.positions(), [position]);
+			}
+		}
+		{
+			let term = Term::from_field_text(title_field, "a");
+			let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+			assert!(postings.advance());
+			assert_eq!(postings.positions(), [0]);
+		}
+		assert_eq!(segment_reader.get_fieldnorm_reader(all_field).unwrap().get(0), 5);
+		assert_eq!(segment_reader.get_fieldnorm_reader(title_field).unwrap().get(0), 2);
+		let doc = searcher.doc(&DocAddress(0, 0)).unwrap();
+		assert_eq!(doc.field_values().len(), 1);
+		assert_eq!(doc.get_first(title_field).unwrap().text(), "a b");
+	}
+
+	#[test]
+	fn test_position_gap() {
+		let mut schema_builder = SchemaBuilder::default();
+		let text_field = schema_builder.add_text_field("text", schema::TEXT);
+		let far_field = schema_builder.add_text_field("far", schema::TEXT.set_position_gap(1 << 28));
+		let max_field = schema_builder.add_text_field("max", schema::TEXT.set_position_gap(u32::max_value()));
+		let index = Index::create_in_ram(schema_builder.build().unwrap());
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			// enough documents for the position deltas to
+			// be vint-encoded after the first block.
+			for _ in 0..250 {
+				let mut doc = Document::default();
+				for field in &[text_field, far_field, max_field] {
+					doc.add_text(*field, "red shoes");
+					doc.add_text(*field, "blue hat");
+					doc.add_text(*field, "green");
+				}
+				index_writer.add_document(doc).unwrap();
+			}
+			// only the positions of the `max` field are clamped.
+			assert_eq!(index_writer.commit().unwrap().num_clamped_docs, 250);
+		}
+		let searcher = index.searcher();
+		let segment_reader = searcher.segment_reader(0);
+		let positions = |field: Field, text: &str| {
+			let term = Term::from_field_text(field, text);
+			let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+			let mut positions = Vec::new();
+			while postings.advance() {
+				positions.push(Vec::from(postings.positions()));
+			}
+			assert_eq!(positions.len(), 250);
+			positions[249].clone()
+		};
+		assert_eq!(positions(text_field, "shoes"), vec!(1));
+		assert_eq!(positions(text_field, "blue"), vec!(3));
+		assert_eq!(positions(text_field, "green"), vec!(6));
+		assert_eq!(positions(far_field, "blue"), vec!(2 + (1 << 28)));
+		assert_eq!(positions(far_field, "green"), vec!(4 + (2 << 28)));
+		// positions saturate instead of overflowing.
+		assert_eq!(positions(max_field, "shoes"), vec!(1));
+		assert_eq!(positions(max_field, "blue"), vec!(MAX_POSITION));
+		assert_eq!(positions(max_field, "hat"), vec!(MAX_POSITION));
+		assert_eq!(positions(max_field, "green"), vec!(MAX_POSITION));
+	}
+
+	#[test]
+	fn test_position_overflow() {
+		let mut schema_builder = SchemaBuilder::default();
+		// without clamping, the position of the second value
+		// would wrap around to 0, so that "blue hat" would
+		// overlap with "red shoes".
+		let text_field = schema_builder.add_text_field("text", schema::TEXT.set_position_gap(u32::max_value() - 1));
+		let schema = schema_builder.build().unwrap();
+		let index = Index::create_in_ram(schema.clone());
+		let new_doc = |values: &[&str]| {
+			let mut doc = Document::default();
+			for value in values {
+				doc.add_text(text_field, value);
+			}
+			doc
+		};
+		{
+			let heap = Heap::with_capacity(10_000_000);
+			let mut segment_writer = SegmentWriter::for_segment(&heap, index.new_segment(), &schema).unwrap();
+			segment_writer.add_document(&new_doc(&["red shoes"]), &schema).unwrap();
+			assert_eq!(segment_writer.num_clamped_docs(), 0);
+			segment_writer.add_document(&new_doc(&["red shoes", "blue hat"]), &schema).unwrap();
+			assert_eq!(segment_writer.num_clamped_docs(), 1);
+		}
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			index_writer.add_document(new_doc(&["red shoes", "blue hat", "green"])).unwrap();
+			index_writer.add_document(new_doc(&["red shoes"])).unwrap();
+			index_writer.add_document(new_doc(&["blue hat red shoes", "green"])).unwrap();
+			let commit_stats = index_writer.commit().unwrap();
+			assert_eq!(commit_stats.num_clamped_docs, 2);
+			assert_eq!(commit_stats.num_truncated_docs, 0);
+			assert_eq!(index_writer.commit().unwrap().num_clamped_docs, 0);
+		}
+		let searcher = index.searcher();
+		let segment_reader = searcher.segment_reader(0);
+		{
+			let term = Term::from_field_text(text_field, "hat");
+			let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+			assert!(postings.advance());
+			assert_eq!(postings.positions(), &[MAX_POSITION]);
+			assert!(postings.advance());
+			assert_eq!(postings.positions(), &[1]);
+			assert!(!postings.advance());
+		}
+		let phrase_docs = |texts: &[&str]| {
+			let terms = texts
+				.iter()
+				.map(|text| Term::from_field_text(text_field, text))
+				.collect();
+			let mut test_collector = TestCollector::default();
+			PhraseQuery::new(terms).search(&searcher, &mut test_collector).unwrap();
+			test_collector.docs()
+		};
+		// wrapped around positions would match the phrase across the values.
+		assert!(phrase_docs(&["red", "hat"]).is_empty());
+		assert!(phrase_docs(&["shoes", "blue"]).is_empty());
+		assert_eq!(phrase_docs(&["red", "shoes"]), vec!(0, 1, 2));
+		assert_eq!(phrase_docs(&["hat", "red"]), vec!(2));
+	}
+
+	#[test]
+	fn test_pretokenized() {
+		let mut schema_builder = SchemaBuilder::default();
+		let text_field = schema_builder.add_text_field("text", schema::TEXT | schema::STORED);
+		let index = Index::create_in_ram(schema_builder.build().unwrap());
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			let mut doc = Document::default();
+			let payload = TokenStreamPayload::from(vec!(
+				(String::from("york"), 2),
+				(String::from("new"), 1),
+				(String::from("NYC"), 1),
+				(String::from("in"), 0),
+			));
+			doc.add_pretokenized(text_field, payload);
+			doc.add_text(text_field, "new york");
+			index_writer.add_document(doc).unwrap();
+			index_writer.commit().unwrap();
+		}
+		let searcher = index.searcher();
+		let segment_reader = searcher.segment_reader(0);
+		let positions = |text: &str| {
+			let term = Term::from_field_text(text_field, text);
+			let mut postings = segment_reader.read_postings_with_positions(&term).unwrap().unwrap();
+			assert!(postings.advance());
+			Vec::from(postings.positions())
+		};
+		// the pre-tokenized value spans 3 positions, followed by the position gap.
+		assert_eq!(positions("in"), vec!(0));
+		assert_eq!(positions("new"), vec!(1, 4));
+		assert_eq!(positions("NYC"), vec!(1));
+		assert_eq!(positions("york"), vec!(2, 5));
+		// tokens are indexed as is.
+		assert!(segment_reader.read_postings_with_positions(&Term::from_field_text(text_field, "nyc")).unwrap().is_none());
+		assert_eq!(segment_reader.get_fieldnorm_reader(text_field).unwrap().get(0), 6);
+		let doc = searcher.doc(&DocAddress(0, 0)).unwrap();
+		let texts: Vec<&str> = doc.get_all(text_field)
+			.iter()
+			.map(|value| value.text())
+			.collect();
+		assert_eq!(texts, vec!("in new NYC york", "new york"));
+	}
+
+	#[test]
+	fn test_untokenized_case() {
+		let mut schema_builder = SchemaBuilder::default();
+		let id_field = schema_builder.add_text_field("id", schema::STRING);
+		let tag_field = schema_builder.add_text_field("tag", TextOptions::default().set_indexing_options(TextIndexingOptions::UntokenizedLowercase));
+		let index = Index::create_in_ram(schema_builder.build().unwrap());
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			for &(id, tag) in &[("AbC-12", "Rust Lang"), ("abc-12", "RUST LANG"), ("ABC-12", "Go")] {
+				let mut doc = Document::default();
+				doc.add_text(id_field, id);
+				doc.add_text(tag_field, tag);
+				index_writer.add_document(doc).unwrap();
+			}
+			index_writer.commit().unwrap();
+		}
+		let searcher = index.searcher();
+		let query_parser = QueryParser::for_index(&index, vec!(id_field));
+		let search = |query_str: &str| {
+			let query = query_parser.parse_query(query_str).unwrap();
+			let mut test_collector = TestCollector::default();
+			query.search(&searcher, &mut test_collector).unwrap();
+			test_collector.docs()
+		};
+		// untokenized values are indexed byte-for-byte.
+		assert_eq!(search("id:\"AbC-12\""), vec!(0));
+		assert_eq!(search("id:\"abc-12\""), vec!(1));
+		assert!(search("id:\"Abc-12\"").is_empty());
+		// lowercased values match whatever the case of the query.
+		assert_eq!(search("tag:\"rust lang\""), vec!(0, 1));
+		assert_eq!(search("tag:\"Rust LANG\""), vec!(0, 1));
+		assert_eq!(search("tag:GO"), vec!(2));
+		assert_eq!(searcher.doc_freq(&Term::from_field_text(id_field, "ABC-12")), 1);
+		assert_eq!(searcher.doc_freq(&Term::from_field_text(tag_field, "Rust Lang")), 0);
+		assert_eq!(searcher.doc_freq(&TextIndexingOptions::UntokenizedLowercase.untokenized_term(tag_field, "Rust Lang")), 2);
+	}
+
+	#[test]
+	fn test_max_positions_per_doc() {
+		let mut schema_builder = SchemaBuilder::default();
+		let capped_field = schema_builder.add_text_field("capped", schema::TEXT.set_max_positions_per_doc(1_000));
+		let free_field = schema_builder.add_text_field("free", schema::TEXT);
+		let schema = schema_builder.build().unwrap();
+		let index = Index::create_in_ram(schema.clone());
+		let giant_text: String = (0..1_000_000)
+			.map(|i| format!("w{} ", i % 100))
+			.collect::<Vec<String>>()
+			.concat();
+		let giant_doc = |field: Field| {
+			let mut doc = Document::default();
+			doc.add_text(field, &giant_text);
+			doc
+		};
+		{
+			let heap = Heap::with_capacity(40_000_000);
+			let mut segment_writer = SegmentWriter::for_segment(&heap, index.new_segment(), &schema).unwrap();
+			let heap_len = heap.len();
+			segment_writer.add_document(&giant_doc(capped_field), &schema).unwrap();
+			let capped_heap_len = heap.len() - heap_len;
+			segment_writer.add_document(&giant_doc(free_field), &schema).unwrap();
+			let free_heap_len = heap.len() - heap_len - capped_heap_len;
+			// the positions of the capped document do not grow with its length.
+			assert!(capped_heap_len < 100_000);
+			assert!(free_heap_len > 4_000_000);
+			assert_eq!(segment_writer.num_truncated_docs(), 1);
+		}
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			index_writer.add_document(giant_doc(capped_field)).unwrap();
+			let mut doc = Document::default();
+			doc.add_text(capped_field, "w0 w1");
+			index_writer.add_document(doc).unwrap();
+			let commit_stats = index_writer.commit().unwrap();
+			assert_eq!(commit_stats.docstamp, 2);
+			assert_eq!(commit_stats.num_truncated_docs, 1);
+			index_writer.add_document(giant_doc(free_field)).unwrap();
+			assert_eq!(index_writer.commit().unwrap().num_truncated_docs, 0);
+		}
+		let searcher = index.searcher();
+		let segment_reader = searcher
+			.segment_readers()
+			.iter()
+			.find(|segment_reader| segment_reader.max_doc() == 2)
+			.unwrap();
+		let mut postings = segment_reader.read_postings_all_info(&Term::from_field_text(capped_field, "w0")).unwrap();
+		assert!(postings.advance());
+		assert_eq!(postings.doc(), 0);
+		let expected_positions: Vec<u32> = (0..10).map(|i| i * 100).collect();
+		assert_eq!(postings.positions(), &expected_positions[..]);
+		assert!(postings.advance());
+		assert_eq!(postings.doc(), 1);
+		assert_eq!(segment_reader.get_fieldnorm_reader(capped_field).unwrap().get(0), id_to_fieldnorm(fieldnorm_to_id(1_000)));
+		assert_eq!(segment_reader.get_fieldnorm_reader(capped_field).unwrap().get(1), 2);
+	}
+
+	fn index_corpus(indexing_options: TextIndexingOptions, num_docs: usize) -> (Index, Field) {
+		let mut schema_builder = SchemaBuilder::default();
+		let text_options = TextOptions::default().set_indexing_options(indexing_options);
+		let text_field = schema_builder.add_text_field("text", text_options);
+		let index = Index::create_in_ram(schema_builder.build().unwrap());
+		{
+			let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+			for i in 0..num_docs {
+				let mut doc = Document::default();
+				doc.add_text(text_field, &format!("w{} w{} common w{} common", i % 10, i % 100, i % 7));
+				index_writer.add_document(doc).unwrap();
+			}
+			index_writer.commit().unwrap();
+		}
+		(index, text_field)
+	}
+
+	#[test]
+	fn test_freq_without_positions() {
+		let (index_freq, text_field) = index_corpus(TextIndexingOptions::TokenizedWithFreq, 1_000);
+		let (index_positions, _) = index_corpus(TextIndexingOptions::TokenizedWithFreqAndPosition, 1_000);
+		let positions_len = component_len(&index_positions, SegmentComponent::POSITIONS);
+		assert!(positions_len > FOOTER_LEN);
+		assert_eq!(component_len(&index_freq, SegmentComponent::POSITIONS), FOOTER_LEN);
+		assert_eq!(
+			component_len(&index_freq, SegmentComponent::POSTINGS),
+			component_len(&index_positions, SegmentComponent::POSTINGS));
+		let searcher = index_freq.searcher();
+		let segment_reader = searcher.segment_reader(0);
+		let term = Term::from_field_text(text_field, "common");
+		{
+			// term frequencies are still available for scoring.
+			let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+			assert!(postings.advance());
+			assert_eq!(postings.term_freq(), 2);
+			assert!(postings.positions().is_empty());
+		}
+		match segment_reader.read_postings_with_positions(&term) {
+			Err(Error::InvalidArgument(msg)) => {
+				assert_eq!(msg, "positions not indexed for field \"text\"");
+			}
+			_ => {
+				panic!("Expected an error, as positions are not indexed");
+			}
+		}
+		let searcher = index_positions.searcher();
+		let mut postings = searcher.segment_reader(0).read_postings_with_positions(&term).unwrap().unwrap();
+		assert!(postings.advance());
+		assert_eq!(postings.positions(), [2, 4]);
+	}
+
+	#[test]
+	fn test_positions_file_size() {
+		// the document is `w0 w0 common w0 common`.
+		let (index_freq, _) = index_corpus(TextIndexingOptions::TokenizedWithFreq, 1);
+		let (index_positions, _) = index_corpus(TextIndexingOptions::TokenizedWithFreqAndPosition, 1);
+		// for each term, the number of positions, the number of bytes and
+		// the vint-encoded position deltas: 2 + 2 bytes for `common`
+		// and 2 + 3 bytes for `w0`.
+		assert_eq!(component_len(&index_positions, SegmentComponent::POSITIONS), 9 + FOOTER_LEN);
+		// only the footer is left.
+		assert_eq!(component_len(&index_freq, SegmentComponent::POSITIONS), FOOTER_LEN);
+	}
+
+	#[bench]
+	fn bench_index_with_freq(b: &mut Bencher) {
+		b.iter(|| index_corpus(TextIndexingOptions::TokenizedWithFreq, 10_000));
+	}
+
+	#[bench]
+	fn bench_index_with_freq_and_positions(b: &mut Bencher) {
+		b.iter(|| index_corpus(TextIndexingOptions::TokenizedWithFreqAndPosition, 10_000));
+	}
+}
@@ -44,11 +44,11 @@ extern crate combine;
 extern crate itertools;
 extern crate chan;
 extern crate crossbeam;
+extern crate rand;
 
 
 
 #[cfg(test)] extern crate test;
-#[cfg(test)] extern crate rand;
 
 #[macro_use]
 mod macros {
@@ -77,7 +77,6 @@ mod error;
 
 pub use error::{Result, Error};
 
-mod analyzer;
 mod datastruct;
 
 
@@ -91,6 +90,8 @@ pub mod collector;
 pub mod postings;
 /// Schema
 pub mod schema;
+/// Analyzer module (tokenizers)
+pub mod analyzer;
 
 
 pub use directory::Directory;
@@ -98,11 +99,17 @@ pub use core::searcher::Searcher;
 
 pub use core::Segment;
 pub use core::Index;
-pub use indexer::IndexWriter;
+pub use indexer::{IndexWriter, CommitStats};
 pub use schema::Term;
 pub use schema::Document;
 pub use core::SegmentReader;
+pub use core::SegmentId;
+pub use core::FieldStats;
+pub use core::{SegmentSpaceUsage, FieldSpaceUsage};
+pub use datastruct::{TermDictionaryType, TermDictionaryStats};
 pub use self::common::TimerTree;
+pub use self::common::BitSet;
+pub use fastfield::U32FastFieldReader;
 
 
 pub use postings::DocSet;
@@ -171,8 +178,12 @@ pub struct DocAddress(pub SegmentLocalId, pub DocId);
 mod tests {
 
     use collector::tests::TestCollector;
+    use collector::TopCollector;
     use query::MultiTermQuery;
+    use query::QueryParser;
+    use query::Query;
     use Index;
+    use DocAddress;
     use core::SegmentReader;
     use schema::*;
     use DocSet;
@@ -182,7 +193,7 @@ mod tests {
     fn test_indexing() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let index = Index::create_from_tempdir(schema).unwrap();
         {
             // writing the segment
@@ -207,11 +218,66 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_date_field() {
+        use collector::tests::DateFastFieldTestCollector;
+        use query::QueryParser;
+        use query::Query;
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let date_options = DateOptions::default()
+            .set_indexed()
+            .set_fast()
+            .set_precision(DatePrecision::Seconds);
+        let date_field = schema_builder.add_date_field("date", date_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        let dates = [1_469_279_051_123i64, -5_000i64, 1_469_279_051_999i64, 0i64];
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &date in &dates {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                doc.add(FieldValue::new(date_field, Value::Date(date)));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(text_field));
+        {
+            // precision is set to the second, so that both dates within 
+            // the same second match.
+            let query = query_parser.parse_query("date:\"2016-07-23T13:04:11Z\"").unwrap();
+            let mut collector = TestCollector::default();
+            query.search(&searcher, &mut collector).unwrap();
+            assert_eq!(collector.docs(), vec!(0, 2));
+        }
+        {
+            let query = query_parser.parse_query("date:\"1969-12-31T23:59:55\"").unwrap();
+            let mut collector = TestCollector::default();
+            query.search(&searcher, &mut collector).unwrap();
+            assert_eq!(collector.docs(), vec!(1));
+        }
+        {
+            assert!(query_parser.parse_query("date:\"not a date\"").is_err());
+        }
+        {
+            // fast field values are not truncated.
+            let query = query_parser.parse_query("a").unwrap();
+            let mut collector = DateFastFieldTestCollector::for_field(date_field);
+            query.search(&searcher, &mut collector).unwrap();
+            assert_eq!(collector.vals(), &dates.to_vec());
+            let mut sorted_dates = collector.vals().clone();
+            sorted_dates.sort();
+            assert_eq!(sorted_dates, vec!(-5_000i64, 0i64, 1_469_279_051_123i64, 1_469_279_051_999i64));
+        }
+    }
+
     #[test]
     fn test_docfreq() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
         {
             let mut doc = Document::default();
@@ -248,15 +314,73 @@ mod tests {
             assert_eq!(searcher.doc_freq(&term_c), 2);
             let term_d = Term::from_field_text(text_field, "d");
             assert_eq!(searcher.doc_freq(&term_d), 0);
+            assert_eq!(searcher.doc_freq_and_total_tf(&term_a), (3, Some(4)));
+            assert_eq!(searcher.doc_freq_and_total_tf(&term_c), (2, Some(2)));
+            assert_eq!(searcher.doc_freq_and_total_tf(&term_d), (0, Some(0)));
+        }
+        {
+            // merging sums the total term frequencies of the segments.
+            let segments = index.searchable_segments();
+            index_writer.merge(&segments).unwrap();
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            let term_a = Term::from_field_text(text_field, "a");
+            assert_eq!(searcher.doc_freq_and_total_tf(&term_a), (3, Some(4)));
         }
     }
+
+    #[test]
+    fn test_doc_freqs() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for segment_docs in &[vec!(("a b c", "a")), vec!(("a", "b"), ("a a", "")), vec!(("c", "c"))] {
+                for &(text, title) in segment_docs {
+                    let mut doc = Document::default();
+                    doc.add_text(text_field, text);
+                    doc.add_text(title_field, title);
+                    index_writer.add_document(doc).unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+        }
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let terms = vec!(
+            Term::from_field_text(title_field, "a"),
+            Term::from_field_text(text_field, "c"),
+            Term::from_field_text(text_field, "d"),
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(title_field, "c"),
+            Term::from_field_text(text_field, "c"),
+            Term::from_field_text(text_field, "b"),
+        );
+        let doc_freqs = searcher.doc_freqs(&terms);
+        assert_eq!(doc_freqs, vec!(1, 2, 0, 3, 1, 2, 1));
+        let expected_doc_freqs: Vec<u32> = terms
+            .iter()
+            .map(|term| searcher.doc_freq(term))
+            .collect();
+        assert_eq!(doc_freqs, expected_doc_freqs);
+        for segment_reader in searcher.segment_readers() {
+            let expected_segment_doc_freqs: Vec<u32> = terms
+                .iter()
+                .map(|term| segment_reader.doc_freq(term))
+                .collect();
+            assert_eq!(segment_reader.doc_freqs(&terms), expected_segment_doc_freqs);
+        }
+        assert!(searcher.doc_freqs(&[]).is_empty());
+    }
     
     
     #[test]
     fn test_fieldnorm() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let index = Index::create_in_ram(schema_builder.build());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
@@ -279,18 +403,193 @@ mod tests {
             
             let searcher = index.searcher();
             let segment_reader: &SegmentReader = searcher.segment_reader(0);
-            let fieldnorms_reader = segment_reader.get_fieldnorms_reader(text_field).unwrap();
+            let fieldnorms_reader = segment_reader.get_fieldnorm_reader(text_field).unwrap();
             assert_eq!(fieldnorms_reader.get(0), 3);
             assert_eq!(fieldnorms_reader.get(1), 0);
             assert_eq!(fieldnorms_reader.get(2), 2);
         }
     }
 
+    #[test]
+    fn test_fieldnorm_disabled() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT.set_fieldnorms(false));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a b c d e f");
+                index_writer.add_document(doc).unwrap();
+            }
+            {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        {
+            let searcher = index.searcher();
+            let segment_reader: &SegmentReader = searcher.segment_reader(0);
+            let fieldnorms_reader = segment_reader.get_fieldnorm_reader(text_field).unwrap();
+            assert_eq!(fieldnorms_reader.get(0), 1);
+            assert_eq!(fieldnorms_reader.get(1), 1);
+            let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+            let mut top_collector = TopCollector::with_limit(2);
+            searcher.search(&query, &mut top_collector).unwrap();
+            let score_docs = top_collector.score_docs();
+            assert_eq!(score_docs.len(), 2);
+            assert_eq!(score_docs[0].0, score_docs[1].0);
+        }
+    }
+
+    #[test]
+    fn test_u32_indexed_fast_stored() {
+        let mut schema_builder = SchemaBuilder::default();
+        let u32_options = U32Options::default()
+            .set_indexed()
+            .set_fast()
+            .set_stored();
+        let count_field = schema_builder.add_u32_field("count", u32_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for count in &[3u32, 7u32, 3u32] {
+                let mut doc = Document::default();
+                doc.add_u32(count_field, *count);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        {
+            let query = MultiTermQuery::from(vec!(Term::from_field_u32(count_field, 3u32)));
+            let mut test_collector = TestCollector::default();
+            searcher.search(&query, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(0, 2));
+        }
+        {
+            let query_parser = QueryParser::new(index.schema(), vec!(count_field));
+            let query = query_parser.parse_query("7").unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(1));
+        }
+        {
+            let segment_reader: &SegmentReader = searcher.segment_reader(0);
+            let fast_field_reader = segment_reader.get_fast_field_reader(count_field).unwrap();
+            assert_eq!(fast_field_reader.get(0), 3u32);
+            assert_eq!(fast_field_reader.get(1), 7u32);
+            assert_eq!(fast_field_reader.get(2), 3u32);
+        }
+        {
+            let doc = searcher.doc(&DocAddress(0, 1)).unwrap();
+            assert_eq!(doc.get_first(count_field).unwrap().u32_value(), 7u32);
+        }
+    }
+
+    #[test]
+    fn test_u32_fast_default_value() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let count_field = schema_builder.add_u32_field("count", U32Options::default().set_fast().set_default_value(100u32));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0u32..6u32 {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                if i % 2 == 0 {
+                    doc.add_u32(count_field, i);
+                }
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let segment_reader: &SegmentReader = searcher.segment_reader(0);
+        let fast_field_reader = segment_reader.get_fast_field_reader(count_field).unwrap();
+        let vals: Vec<u32> = (0..6).map(|doc| fast_field_reader.get(doc)).collect();
+        assert_eq!(vals, vec!(0u32, 100u32, 2u32, 100u32, 4u32, 100u32));
+    }
+
+    #[test]
+    fn test_raw_text_field() {
+        let mut schema_builder = SchemaBuilder::default();
+        let raw_options = TextOptions::default().set_indexing_options(TextIndexingOptions::Raw);
+        let sku_field = schema_builder.add_text_field("sku", raw_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for sku in &["AB-123/X", "ab-123/x", "AB-123"] {
+                let mut doc = Document::default();
+                doc.add_text(sku_field, sku);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(sku_field));
+        {
+            let query = query_parser.parse_query("sku:\"AB-123/X\"").unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(0));
+        }
+        {
+            let query = query_parser.parse_query("\"ab-123/x\"").unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(1));
+        }
+    }
+
+    #[test]
+    fn test_field_boost() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT.set_boost(3f32));
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build().unwrap();
+        let (deserialized_schema, _) = Schema::from_schema_json(&schema.to_schema_json()).unwrap();
+        assert_eq!(deserialized_schema.get_field_entry(title_field).boost(), 3f32);
+        assert_eq!(deserialized_schema.get_field_entry(body_field).boost(), 1f32);
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            {
+                // body-only match
+                let mut doc = Document::default();
+                doc.add_text(title_field, "b");
+                doc.add_text(body_field, "a");
+                index_writer.add_document(doc).unwrap();
+            }
+            {
+                // title-only match
+                let mut doc = Document::default();
+                doc.add_text(title_field, "a");
+                doc.add_text(body_field, "b");
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(title_field, body_field));
+        let query = query_parser.parse_query("a").unwrap();
+        let mut top_collector = TopCollector::with_limit(2);
+        query.search(&searcher, &mut top_collector).unwrap();
+        let score_docs = top_collector.score_docs();
+        assert_eq!(score_docs.len(), 2);
+        assert_eq!(score_docs[0].1, DocAddress(0, 1));
+        assert_eq!(score_docs[1].1, DocAddress(0, 0));
+        assert!(score_docs[0].0 > score_docs[1].0 * 2f32);
+    }
+
     #[test]
     fn test_termfreq() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let index = Index::create_in_ram(schema);
         {
             // writing the segment
@@ -318,7 +617,7 @@ mod tests {
     fn test_searcher_1() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let index = Index::create_in_ram(schema);
 
         {
@@ -387,7 +686,7 @@ mod tests {
     fn test_searcher_2() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let index = Index::create_in_ram(schema);
 
         {
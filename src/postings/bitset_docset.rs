@@ -0,0 +1,146 @@
+use DocId;
+use common::BitSet;
+use postings::{DocSet, SkipResult};
+use std::sync::Arc;
+use std::ops::Deref;
+
+
+enum BitSetHolder<'a> {
+    Borrowed(&'a BitSet),
+    Owned(BitSet),
+    Shared(Arc<BitSet>),
+}
+
+impl<'a> Deref for BitSetHolder<'a> {
+    type Target = BitSet;
+
+    fn deref(&self,) -> &BitSet {
+        match *self {
+            BitSetHolder::Borrowed(bitset) => bitset,
+            BitSetHolder::Owned(ref bitset) => bitset,
+            BitSetHolder::Shared(ref bitset) => bitset,
+        }
+    }
+}
+
+
+/// `DocSet` iterating over the doc ids of a `BitSet`.
+///
+/// The bitset may either be borrowed, owned or shared by the `DocSet`.
+pub struct BitSetDocSet<'a> {
+    bitset: BitSetHolder<'a>,
+    doc: DocId,
+    started: bool,
+}
+
+impl<'a> BitSetDocSet<'a> {
+    fn with_holder(bitset: BitSetHolder<'a>) -> BitSetDocSet<'a> {
+        BitSetDocSet {
+            bitset: bitset,
+            doc: 0u32,
+            started: false,
+        }
+    }
+}
+
+impl<'a> From<&'a BitSet> for BitSetDocSet<'a> {
+    fn from(bitset: &'a BitSet) -> BitSetDocSet<'a> {
+        BitSetDocSet::with_holder(BitSetHolder::Borrowed(bitset))
+    }
+}
+
+impl From<BitSet> for BitSetDocSet<'static> {
+    fn from(bitset: BitSet) -> BitSetDocSet<'static> {
+        BitSetDocSet::with_holder(BitSetHolder::Owned(bitset))
+    }
+}
+
+impl From<Arc<BitSet>> for BitSetDocSet<'static> {
+    fn from(bitset: Arc<BitSet>) -> BitSetDocSet<'static> {
+        BitSetDocSet::with_holder(BitSetHolder::Shared(bitset))
+    }
+}
+
+impl<'a> DocSet for BitSetDocSet<'a> {
+
+    fn advance(&mut self,) -> bool {
+        let target = if self.started { self.doc + 1 } else { 0u32 };
+        self.started = true;
+        match self.bitset.next_from(target) {
+            Some(doc) => {
+                self.doc = doc;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.started && self.doc >= target {
+            return if self.doc == target { SkipResult::Reached } else { SkipResult::OverStep };
+        }
+        self.started = true;
+        match self.bitset.next_from(target) {
+            Some(doc) => {
+                self.doc = doc;
+                if doc == target { SkipResult::Reached } else { SkipResult::OverStep }
+            }
+            None => SkipResult::End,
+        }
+    }
+
+    fn doc(&self,) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self,) -> usize {
+        self.bitset.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use common::BitSet;
+    use postings::{DocSet, SkipResult};
+
+    #[test]
+    fn test_bitset_docset() {
+        let mut bitset = BitSet::with_max_doc(300);
+        for &doc in &[0u32, 5u32, 130u32, 299u32] {
+            bitset.insert(doc);
+        }
+        {
+            let mut docset = BitSetDocSet::from(&bitset);
+            let mut docs = Vec::new();
+            while docset.advance() {
+                docs.push(docset.doc());
+            }
+            assert_eq!(docs, vec!(0u32, 5u32, 130u32, 299u32));
+        }
+        {
+            let mut docset = BitSetDocSet::from(&bitset);
+            assert_eq!(docset.skip_next(5), SkipResult::Reached);
+            assert_eq!(docset.skip_next(6), SkipResult::OverStep);
+            assert_eq!(docset.doc(), 130);
+            assert_eq!(docset.skip_next(130), SkipResult::Reached);
+            assert!(docset.advance());
+            assert_eq!(docset.doc(), 299);
+            assert_eq!(docset.skip_next(300), SkipResult::End);
+        }
+        {
+            let mut docset = BitSetDocSet::from(bitset.clone());
+            assert_eq!(docset.next(), Some(0u32));
+            assert_eq!(docset.skip_next(6), SkipResult::OverStep);
+            assert_eq!(docset.doc(), 130);
+        }
+        {
+            let mut docset = BitSetDocSet::from(Arc::new(bitset));
+            assert_eq!(docset.size_hint(), 4);
+            assert_eq!(docset.skip_next(131), SkipResult::OverStep);
+            assert_eq!(docset.doc(), 299);
+        }
+    }
+}
@@ -50,6 +50,10 @@ impl<'a> DocSet for ChainedPostings<'a> {
     fn doc(&self,) -> DocId {
         self.chained_postings[self.posting_id].doc()
     }
+
+    fn size_hint(&self,) -> usize {
+        self.len
+    }
 }
 
 impl<'a> HasLen for ChainedPostings<'a> {
@@ -67,5 +71,9 @@ impl<'a> Postings for ChainedPostings<'a> {
     fn positions(&self) -> &[u32] {
         self.chained_postings[self.posting_id].positions()
     } 
+
+    fn payloads(&self) -> &[u32] {
+        self.chained_postings[self.posting_id].payloads()
+    }
     
 }
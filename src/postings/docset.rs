@@ -43,6 +43,17 @@ pub trait DocSet {
             }
         }
     }
+
+    /// Advances the `DocSet` to the first document greater than
+    /// or equal to `target`, like `.skip_next(...)`.
+    ///
+    /// It is the entry point of the intersections leapfrogging
+    /// their `DocSet`s. It defaults to `.skip_next(...)`, which the `DocSet`s
+    /// able to skip whole blocks of documents, like `SegmentPostings`,
+    /// already implement without going through each document.
+    fn seek(&mut self, target: DocId) -> SkipResult {
+        self.skip_next(target)
+    }
     
     /// Returns the current document
     fn doc(&self,) -> DocId;
@@ -57,11 +68,19 @@ pub trait DocSet {
         else {
             None
         }
-    } 
+    }
+
+    /// Returns an estimate of the number of documents of the `DocSet`,
+    /// used to order the `DocSet`s of an intersection from the smallest.
+    ///
+    /// Defaults to `usize::max_value()`, when it is unknown.
+    fn size_hint(&self,) -> usize {
+        usize::max_value()
+    }
 }
 
 
-impl<TDocSet: DocSet> DocSet for Box<TDocSet> {
+impl<TDocSet: DocSet + ?Sized> DocSet for Box<TDocSet> {
 
     fn advance(&mut self,) -> bool {
         let unboxed: &mut TDocSet = self.borrow_mut();
@@ -73,10 +92,20 @@ impl<TDocSet: DocSet> DocSet for Box<TDocSet> {
         unboxed.skip_next(target)
     }
 
+    fn seek(&mut self, target: DocId) -> SkipResult {
+        let unboxed: &mut TDocSet = self.borrow_mut();
+        unboxed.seek(target)
+    }
+
     fn doc(&self,) -> DocId {
         let unboxed: &TDocSet = self.borrow();
         unboxed.doc()
     }
+
+    fn size_hint(&self,) -> usize {
+        let unboxed: &TDocSet = self.borrow();
+        unboxed.size_hint()
+    }
 }
 
 impl<'a, TDocSet: DocSet> DocSet for &'a mut TDocSet {
@@ -91,10 +120,20 @@ impl<'a, TDocSet: DocSet> DocSet for &'a mut TDocSet {
         unref.skip_next(target)
     }
 
+    fn seek(&mut self, target: DocId) -> SkipResult {
+        let unref: &mut TDocSet = *self;
+        unref.seek(target)
+    }
+
     fn doc(&self,) -> DocId {
         let unref: &TDocSet = *self;
         unref.doc()
     }
+
+    fn size_hint(&self,) -> usize {
+        let unref: &TDocSet = *self;
+        unref.size_hint()
+    }
 }
 
     
@@ -0,0 +1,87 @@
+use postings::{DocSet, SkipResult};
+use DocId;
+
+
+/// `DocSet` iterating through the documents of a `DocSet`
+/// that are not in another one, the excluded `DocSet`.
+///
+/// The excluded `DocSet` only skips to the documents of the
+/// underlying one. Both may be of any type, or boxed.
+pub struct ExcludeDocSet<TDocSet: DocSet, TExcludedDocSet: DocSet> {
+    underlying: TDocSet,
+    excluded: TExcludedDocSet,
+    excluded_started: bool,
+    excluded_finished: bool,
+}
+
+impl<TDocSet: DocSet, TExcludedDocSet: DocSet> ExcludeDocSet<TDocSet, TExcludedDocSet> {
+
+    /// Excludes the documents of `excluded` from `underlying`.
+    pub fn new(underlying: TDocSet, excluded: TExcludedDocSet) -> ExcludeDocSet<TDocSet, TExcludedDocSet> {
+        ExcludeDocSet {
+            underlying: underlying,
+            excluded: excluded,
+            excluded_started: false,
+            excluded_finished: false,
+        }
+    }
+
+    /// Returns true iff the document is in the excluded `DocSet`.
+    ///
+    /// The documents must be given in increasing order.
+    fn is_excluded(&mut self, doc: DocId) -> bool {
+        if !self.excluded_started {
+            self.excluded_started = true;
+            self.excluded_finished = !self.excluded.advance();
+        }
+        if self.excluded_finished {
+            return false;
+        }
+        match self.excluded.skip_next(doc) {
+            SkipResult::Reached => true,
+            SkipResult::OverStep => false,
+            SkipResult::End => {
+                self.excluded_finished = true;
+                false
+            }
+        }
+    }
+}
+
+impl<TDocSet: DocSet, TExcludedDocSet: DocSet> DocSet for ExcludeDocSet<TDocSet, TExcludedDocSet> {
+
+    fn advance(&mut self,) -> bool {
+        while self.underlying.advance() {
+            let doc = self.underlying.doc();
+            if !self.is_excluded(doc) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        let skip_result = self.underlying.skip_next(target);
+        if skip_result == SkipResult::End {
+            return SkipResult::End;
+        }
+        let doc = self.underlying.doc();
+        if !self.is_excluded(doc) {
+            return skip_result;
+        }
+        if self.advance() {
+            SkipResult::OverStep
+        }
+        else {
+            SkipResult::End
+        }
+    }
+
+    fn doc(&self,) -> DocId {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self,) -> usize {
+        self.underlying.size_hint()
+    }
+}
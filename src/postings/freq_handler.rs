@@ -1,4 +1,4 @@
-use compression::SIMDBlockDecoder;
+use compression::BlockDecompressor;
 use common::VInt;
 use common::BinarySerializable;
 use compression::CompositeDecoder;
@@ -8,11 +8,25 @@ use compression::NUM_DOCS_PER_BLOCK;
 
 /// `FreqHandler`  is in charge of decompressing
 /// frequencies and/or positions.
-pub struct FreqHandler {
-    freq_decoder: SIMDBlockDecoder,
+///
+/// The positions are either written block by block, along with
+/// the blocks of documents, or, in older segments, as a single
+/// array for all of the documents of the term.
+///
+/// The payloads of the positions, if any, are decoded
+/// block by block along with the positions.
+pub struct FreqHandler<'a> {
+    freq_decoder: BlockDecompressor,
     positions: Vec<u32>,
     option: SegmentPostingsOption,
+    skip_freq_blocks: bool,
     positions_offsets: [usize; NUM_DOCS_PER_BLOCK + 1],
+    positions_data: &'a [u8],
+    remaining_positions_data: &'a [u8],
+    positions_decoder: Option<CompositeDecoder>,
+    payloads: Vec<u32>,
+    payloads_data: &'a [u8],
+    payloads_decoder: Option<CompositeDecoder>,
 }
 
 
@@ -24,43 +38,161 @@ fn read_positions(data: &[u8]) -> Vec<u32> {
     composite_reader.into()
 }
 
+/// Splits the positions of the next block of documents,
+/// returning their number, their compressed data and
+/// the remaining data.
+fn split_positions_block(mut data: &[u8]) -> (usize, &[u8], &[u8]) {
+    let num_positions = VInt::deserialize(&mut data).unwrap().0 as usize;
+    let num_bytes = VInt::deserialize(&mut data).unwrap().0 as usize;
+    let (block_data, remaining_data) = data.split_at(num_bytes);
+    (num_positions, block_data, remaining_data)
+}
 
+/// Splits the offset of the payloads of the next block of documents
+/// off its positions, for the fields indexed with payloads.
+fn split_payloads_offset(mut data: &[u8], has_payloads: bool) -> (usize, &[u8]) {
+    if has_payloads {
+        let payloads_offset = VInt::deserialize(&mut data).unwrap().0 as usize;
+        (payloads_offset, data)
+    }
+    else {
+        (0, data)
+    }
+}
 
-impl FreqHandler {
+
+
+impl<'a> FreqHandler<'a> {
     
     /// Returns a `FreqHandler` that just decodes `DocId`s.
-    pub fn new_without_freq() -> FreqHandler {
+    pub fn new_without_freq() -> FreqHandler<'a> {
+        FreqHandler {
+            freq_decoder: BlockDecompressor::with_val(1u32),
+            positions: Vec::new(), 
+            option: SegmentPostingsOption::NoFreq,
+            skip_freq_blocks: false,
+            positions_offsets: [0; NUM_DOCS_PER_BLOCK + 1],
+            positions_data: &[],
+            remaining_positions_data: &[],
+            positions_decoder: None,
+            payloads: Vec::new(),
+            payloads_data: &[],
+            payloads_decoder: None,
+        }
+    }
+
+    /// Returns a `FreqHandler` that just decodes `DocId`s,
+    /// for postings that were written with term frequencies.
+    ///
+    /// The blocks of term frequencies are skipped without being decoded.
+    pub fn new_skipping_freq() -> FreqHandler<'a> {
         FreqHandler {
-            freq_decoder: SIMDBlockDecoder::with_val(1u32),
+            freq_decoder: BlockDecompressor::with_val(1u32),
             positions: Vec::new(), 
             option: SegmentPostingsOption::NoFreq,
+            skip_freq_blocks: true,
             positions_offsets: [0; NUM_DOCS_PER_BLOCK + 1],
+            positions_data: &[],
+            remaining_positions_data: &[],
+            positions_decoder: None,
+            payloads: Vec::new(),
+            payloads_data: &[],
+            payloads_decoder: None,
         }
     }
     
     /// Returns a `FreqHandler` that decodes `DocId`s and term frequencies.
-    pub fn new_with_freq() -> FreqHandler {
+    pub fn new_with_freq() -> FreqHandler<'a> {
         FreqHandler {
-            freq_decoder: SIMDBlockDecoder::new(),
+            freq_decoder: BlockDecompressor::new(),
             positions: Vec::new(),
             option: SegmentPostingsOption::Freq,
+            skip_freq_blocks: false,
             positions_offsets: [0; NUM_DOCS_PER_BLOCK + 1],
+            positions_data: &[],
+            remaining_positions_data: &[],
+            positions_decoder: None,
+            payloads: Vec::new(),
+            payloads_data: &[],
+            payloads_decoder: None,
         }
     }
 
 
-    /// Returns a `FreqHandler` that decodes `DocId`s, term frequencies, and term positions.
-    pub fn new_with_freq_and_position(position_data: &[u8]) -> FreqHandler {
+    /// Returns a `FreqHandler` that decodes `DocId`s, term frequencies, and term positions,
+    /// the positions of the term being written as a single array.
+    ///
+    /// The positions of all of the documents are decoded upfront.
+    pub fn new_with_freq_and_position(position_data: &[u8]) -> FreqHandler<'a> {
         let positions = read_positions(position_data);
         FreqHandler {
-            freq_decoder: SIMDBlockDecoder::new(),
+            freq_decoder: BlockDecompressor::new(),
             positions: positions, 
             option: SegmentPostingsOption::FreqAndPositions,
+            skip_freq_blocks: false,
+            positions_offsets: [0; NUM_DOCS_PER_BLOCK + 1],
+            positions_data: &[],
+            remaining_positions_data: &[],
+            positions_decoder: None,
+            payloads: Vec::new(),
+            payloads_data: &[],
+            payloads_decoder: None,
+        }
+    }
+
+    /// Returns a `FreqHandler` that decodes `DocId`s, term frequencies, and term positions,
+    /// the positions of the term being written block by block.
+    ///
+    /// The positions are decoded one block at a time, and the
+    /// positions of the skipped blocks are not decoded at all.
+    pub fn new_with_freq_and_block_positions(position_data: &'a [u8]) -> FreqHandler<'a> {
+        FreqHandler {
+            freq_decoder: BlockDecompressor::new(),
+            positions: Vec::new(), 
+            option: SegmentPostingsOption::FreqAndPositions,
+            skip_freq_blocks: false,
             positions_offsets: [0; NUM_DOCS_PER_BLOCK + 1],
+            positions_data: position_data,
+            remaining_positions_data: position_data,
+            positions_decoder: Some(CompositeDecoder::new()),
+            payloads: Vec::new(),
+            payloads_data: &[],
+            payloads_decoder: None,
+        }
+    }
+
+    /// Returns a `FreqHandler` that decodes `DocId`s, term frequencies, term positions
+    /// and their payloads, the positions of the term being written block by block.
+    ///
+    /// `payloads_data` is the whole payloads file of the segment, as the
+    /// positions of each block start with the offset of its payloads.
+    pub fn new_with_freq_block_positions_and_payloads(position_data: &'a [u8], payloads_data: &'a [u8]) -> FreqHandler<'a> {
+        FreqHandler {
+            payloads_data: payloads_data,
+            payloads_decoder: Some(CompositeDecoder::new()),
+            .. FreqHandler::new_with_freq_and_block_positions(position_data)
+        }
+    }
+    
+    /// Decodes the positions of the next block,
+    /// if the positions are written block by block.
+    fn read_positions_block(&mut self,) {
+        if let Some(ref mut positions_decoder) = self.positions_decoder {
+            let (payloads_offset, positions_data) = split_payloads_offset(self.remaining_positions_data, self.payloads_decoder.is_some());
+            let (num_positions, block_data, remaining_data) = split_positions_block(positions_data);
+            self.positions.clear();
+            self.positions.extend_from_slice(positions_decoder.uncompress_unsorted(block_data, num_positions));
+            self.positions_offsets[NUM_DOCS_PER_BLOCK] = 0;
+            self.remaining_positions_data = remaining_data;
+            if let Some(ref mut payloads_decoder) = self.payloads_decoder {
+                self.payloads.clear();
+                self.payloads.extend_from_slice(payloads_decoder.uncompress_unsorted(&self.payloads_data[payloads_offset..], num_positions));
+            }
         }
     }
     
     fn fill_positions_offset(&mut self,) {
+        self.read_positions_block();
         let mut cur_position: usize = self.positions_offsets[NUM_DOCS_PER_BLOCK];
         let mut i: usize  = 0;
         self.positions_offsets[i] = cur_position;
@@ -97,24 +229,87 @@ impl FreqHandler {
         let stop = self.positions_offsets[idx + 1];
         &self.positions[start..stop]        
     }
+
+    /// Accessor to the payloads of the positions
+    ///
+    /// idx is the offset of the current doc in the block.
+    /// It takes value between 0 and 128.
+    /// The payloads are empty if they are not decoded.
+    pub fn payloads(&self, idx: usize) -> &[u32] {
+        if self.payloads_decoder.is_none() {
+            return &[];
+        }
+        let start = self.positions_offsets[idx];
+        let stop = self.positions_offsets[idx + 1];
+        &self.payloads[start..stop]
+    }
     
     /// Decompresses a complete frequency block
-    pub fn read_freq_block<'a>(&mut self, data: &'a [u8]) -> &'a [u8] {
+    pub fn read_freq_block<'b>(&mut self, data: &'b [u8]) -> &'b [u8] {
         match self.option {
             SegmentPostingsOption::NoFreq => {
-                data
+                if self.skip_freq_blocks {
+                    BlockDecompressor::skip_block_unsorted(data)
+                }
+                else {
+                    data
+                }
             }
             SegmentPostingsOption::Freq => {
                 self.freq_decoder.uncompress_block_unsorted(data)
             }
             SegmentPostingsOption::FreqAndPositions => {
-                let remaining: &'a [u8] = self.freq_decoder.uncompress_block_unsorted(data);
+                let remaining: &'b [u8] = self.freq_decoder.uncompress_block_unsorted(data);
                 self.fill_positions_offset();
                 remaining
             }
         }
     }
+
+    /// Skips a complete frequency block, for a block of documents
+    /// that are all skipped.
+    ///
+    /// If the positions of the term are written as a single array,
+    /// the term frequencies are still decoded, as they are needed to find
+    /// out where the positions of the following blocks start.
+    pub fn skip_freq_block<'b>(&mut self, data: &'b [u8]) -> &'b [u8] {
+        match self.option {
+            SegmentPostingsOption::NoFreq => {
+                self.read_freq_block(data)
+            }
+            SegmentPostingsOption::Freq => {
+                BlockDecompressor::skip_block_unsorted(data)
+            }
+            SegmentPostingsOption::FreqAndPositions => {
+                if self.positions_decoder.is_some() {
+                    let (_, positions_data) = split_payloads_offset(self.remaining_positions_data, self.payloads_decoder.is_some());
+                    let (_, _, remaining_positions_data) = split_positions_block(positions_data);
+                    self.remaining_positions_data = remaining_positions_data;
+                    BlockDecompressor::skip_block_unsorted(data)
+                }
+                else {
+                    self.read_freq_block(data)
+                }
+            }
+        }
+    }
         
+    /// Sets the offset of the positions of the next block,
+    /// when the blocks before it are skipped without being read.
+    ///
+    /// If the positions are written block by block, the offset
+    /// is the offset of their data, relatively to the start of the
+    /// positions of the term. Otherwise, it is the number of positions
+    /// of the skipped documents.
+    pub fn seek_positions(&mut self, positions_offset: usize) {
+        if self.positions_decoder.is_some() {
+            self.remaining_positions_data = &self.positions_data[positions_offset..];
+        }
+        else {
+            self.positions_offsets[NUM_DOCS_PER_BLOCK] = positions_offset;
+        }
+    }
+
     /// Decompresses an incomplete frequency block
     pub fn read_freq_vint(&mut self, data: &[u8], num_els: usize) {
         match self.option {
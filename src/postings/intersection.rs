@@ -1,90 +1,123 @@
-use postings::DocSet;
-use std::cmp::Ordering;
+use postings::{DocSet, SkipResult};
 use DocId;
 
-// TODO Find a way to specialize `IntersectionDocSet`
 
-/// Creates a `DocSet` that iterator through the intersection of two `DocSet`s.
-pub struct IntersectionDocSet<'a> {
-    left: Box<DocSet + 'a>,
-    right: Box<DocSet + 'a>,
-    finished: bool, 
+/// Leapfrogs the `DocSet`s to their first common document, starting
+/// from their current documents: the first `DocSet` goes to the documents
+/// overstepped by the others, which catch up with it.
+///
+/// Returns false if one of the `DocSet`s is entirely consumed before.
+fn align<TDocSet: DocSet>(docsets: &mut [TDocSet]) -> bool {
+    let (first, others) = docsets.split_first_mut().unwrap();
+    'align: loop {
+        let candidate = first.doc();
+        for docset in others.iter_mut() {
+            match docset.seek(candidate) {
+                SkipResult::Reached => {}
+                SkipResult::OverStep => {
+                    if first.seek(docset.doc()) == SkipResult::End {
+                        return false;
+                    }
+                    continue 'align;
+                }
+                SkipResult::End => {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+}
+
+
+/// `DocSet` iterating through the intersection of a list of `DocSet`s.
+///
+/// The `DocSet`s may be of any type, or boxed to mix several of them.
+/// They are ordered by their `size_hint`, so that the iteration is
+/// driven by the smallest one, the others merely skipping
+/// to its documents.
+pub struct IntersectionDocSet<TDocSet: DocSet> {
+    docsets: Vec<TDocSet>,
+    started: bool,
+    finished: bool,
 }
 
-impl<'a> IntersectionDocSet<'a> {
-        
-    /// Intersect two `DocSet`s
-    fn from_pair(left: Box<DocSet + 'a>, right: Box<DocSet + 'a>) -> IntersectionDocSet<'a> {
+impl<TDocSet: DocSet> IntersectionDocSet<TDocSet> {
+
+    /// Intersects a list of `DocSet`s.
+    ///
+    /// # Panics
+    /// If `docsets` is empty.
+    pub fn new(mut docsets: Vec<TDocSet>) -> IntersectionDocSet<TDocSet> {
+        assert!(!docsets.is_empty(), "Cannot intersect an empty list of DocSets");
+        docsets.sort_by_key(|docset| docset.size_hint());
         IntersectionDocSet {
-            left: left,
-            right: right,
+            docsets: docsets,
+            started: false,
             finished: false,
-        }         
+        }
     }
-    
-    /// Intersect a list of `DocSet`s
-    pub fn new(mut postings: Vec<Box<DocSet + 'a>>) -> IntersectionDocSet<'a> {
-        let left = postings.pop().unwrap();
-        let right = 
-            if postings.len() == 1 {
-                postings.pop().unwrap()
-            }
-            else {
-                Box::new(IntersectionDocSet::new(postings))  
-            };
-        IntersectionDocSet::from_pair(left, right)        
+
+    fn align(&mut self,) -> bool {
+        if !align(&mut self.docsets) {
+            self.finished = true;
+            return false;
+        }
+        true
     }
 }
 
+impl<TDocSet: DocSet> DocSet for IntersectionDocSet<TDocSet> {
 
-impl<'a> DocSet for IntersectionDocSet<'a> {
-    
     fn advance(&mut self,) -> bool {
         if self.finished {
             return false;
         }
-        
-        if !self.left.advance() {
+        if !self.started {
+            self.started = true;
+            for docset in &mut self.docsets[1..] {
+                if !docset.advance() {
+                    self.finished = true;
+                    return false;
+                }
+            }
+        }
+        if !self.docsets[0].advance() {
             self.finished = true;
             return false;
         }
-        if !self.right.advance() {
+        self.align()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.finished {
+            return SkipResult::End;
+        }
+        if self.docsets[0].skip_next(target) == SkipResult::End {
             self.finished = true;
-            return false;
+            return SkipResult::End;
         }
-        loop {
-            match self.left.doc().cmp(&self.right.doc()) {
-                Ordering::Equal => {
-                    return true;
-                }
-                Ordering::Less => {
-                    if !self.left.advance() {
-                        self.finished = true;
-                        return false;
-                    }
-                }
-                Ordering::Greater => {
-                    if !self.right.advance() {
-                        self.finished = true;
-                        return false;
-                    }
-                }
-            }
+        if !self.align() {
+            return SkipResult::End;
+        }
+        if self.doc() == target {
+            SkipResult::Reached
+        }
+        else {
+            SkipResult::OverStep
         }
     }
-    
+
     fn doc(&self,) -> DocId {
-        self.left.doc()
+        self.docsets[0].doc()
+    }
+
+    fn size_hint(&self,) -> usize {
+        self.docsets[0].size_hint()
     }
 }
 
 /// Intersects a `Vec` of `DocSets`
-pub fn intersection<'a, TDocSet: DocSet + 'a>(postings: Vec<TDocSet>) -> IntersectionDocSet<'a> {
-    let boxed_postings: Vec<Box<DocSet + 'a>> = postings
-        .into_iter()
-        .map(|postings: TDocSet| {
-            Box::new(postings) as Box<DocSet + 'a>
-        })
-        .collect();
-    IntersectionDocSet::new(boxed_postings)
+pub fn intersection<TDocSet: DocSet>(docsets: Vec<TDocSet>) -> IntersectionDocSet<TDocSet> {
+    IntersectionDocSet::new(docsets)
 }
@@ -9,22 +9,31 @@ mod recorder;
 mod serializer;
 mod postings_writer;
 mod term_info;
+mod term_stream;
+mod term_dict;
+mod postings_checkpoint;
 mod chained_postings;
 mod vec_postings;
 mod segment_postings;
 mod intersection;
+mod union;
+mod exclude;
 mod offset_postings;
 mod freq_handler;
 mod docset;
 mod segment_postings_option;
+mod bitset_docset;
 
 pub use self::docset::{SkipResult, DocSet};
 pub use self::offset_postings::OffsetPostings;
-pub use self::recorder::{Recorder, NothingRecorder, TermFrequencyRecorder, TFAndPositionRecorder};
-pub use self::serializer::PostingsSerializer;
-pub use self::postings_writer::PostingsWriter;
+pub use self::recorder::{Recorder, NothingRecorder, TermFrequencyRecorder, TFAndPositionRecorder, TFPositionAndPayloadRecorder, MAX_POSITION};
+pub use self::serializer::{PostingsSerializer, SerializerError};
+pub use self::postings_writer::{PostingsWriter, IndexedText};
 pub use self::postings_writer::SpecializedPostingsWriter;
 pub use self::term_info::TermInfo;
+pub use self::term_stream::TermStream;
+pub use self::term_dict::{TermDict, TermDictRange, TermDictStreamer};
+pub use self::postings_checkpoint::{PostingsCheckpoint, POSTINGS_FORMAT_VERSION, SKIP_LIST_THRESHOLD, SKIP_LIST_PERIOD};
 pub use self::postings::Postings;
 
 #[cfg(test)]
@@ -34,48 +43,138 @@ pub use self::chained_postings::ChainedPostings;
 pub use self::segment_postings::SegmentPostings;
 pub use self::intersection::intersection;
 pub use self::intersection::IntersectionDocSet;
+pub use self::union::UnionDocSet;
+pub use self::exclude::ExcludeDocSet;
 pub use self::freq_handler::FreqHandler;
 pub use self::segment_postings_option::SegmentPostingsOption;
+pub use self::bitset_docset::BitSetDocSet;
 pub use common::HasLen;
+pub use compression::NUM_DOCS_PER_BLOCK;
 
 #[cfg(test)]
 mod tests {
     
     use super::*;
-    use schema::{Document, TEXT, SchemaBuilder, Term};
+    use schema::{Document, TEXT, SchemaBuilder, Term, TextOptions, TextIndexingOptions};
+    use analyzer::{Token, TokenStream, Tokenizer};
+    use std::str::SplitWhitespace;
     use core::SegmentComponent;
+    use directory::FOOTER_LEN;
     use indexer::SegmentWriter;
     use core::SegmentReader;
     use core::Index;
     use std::iter;
     use datastruct::stacker::Heap;
+    use DocId;
+    use Error;
+    use super::segment_postings::tests::{encode_postings, encode_postings_with_skip_list};
+    use compression::tests::generate_array_with_seed;
+    use common::BitSet;
+    use fastfield::{fieldnorm_to_id, id_to_fieldnorm};
+    use std::collections::BTreeSet;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use test::Bencher;
     
         
     #[test]
     pub fn test_position_write() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let index = Index::create_in_ram(schema);
         let mut segment = index.new_segment();
         let mut posting_serializer = PostingsSerializer::open(&mut segment).unwrap();
         let term = Term::from_field_text(text_field, "abc");
-        posting_serializer.new_term(&term, 3).unwrap();
+        posting_serializer.new_term(&term, 3, Some(6)).unwrap();
         for doc_id in 0u32..3u32 {
             let positions = vec!(1,2,3,2);
-            posting_serializer.write_doc(doc_id, 2, &positions).unwrap();
+            posting_serializer.write_doc(doc_id, 2, &positions, None).unwrap();
         }
         posting_serializer.close_term().unwrap();
         posting_serializer.close().unwrap();
         let read = segment.open_read(SegmentComponent::POSITIONS).unwrap();
-        assert_eq!(read.len(), 13);
+        // the number of positions, the number of bytes,
+        // and the 12 vint-encoded position deltas, followed by the footer.
+        assert_eq!(read.len(), 14 + FOOTER_LEN);
+    }
+
+    #[test]
+    fn test_serializer_errors() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        let mut segment = index.new_segment();
+        let mut posting_serializer = PostingsSerializer::open(&mut segment).unwrap();
+        let term = |text: &str| Term::from_field_text(text_field, text);
+        posting_serializer.new_term(&term("b"), 1, None).unwrap();
+        match posting_serializer.new_term(&term("c"), 1, None) {
+            Err(SerializerError::TermNotClosed) => {}
+            _ => panic!("Expected TermNotClosed"),
+        }
+        posting_serializer.close_term().unwrap();
+        for text in &["a", "b"] {
+            match posting_serializer.new_term(&term(text), 1, None) {
+                Err(SerializerError::TermsOutOfOrder { prev, next }) => {
+                    assert_eq!(prev, term("b"));
+                    assert_eq!(next, term(text));
+                }
+                _ => panic!("Expected TermsOutOfOrder"),
+            }
+        }
+        posting_serializer.new_term(&term("c"), 2, None).unwrap();
+        posting_serializer.write_doc(3, 1, &[0], None).unwrap();
+        for &doc in &[3, 2] {
+            match posting_serializer.write_doc(doc, 1, &[0], None) {
+                Err(SerializerError::DocsOutOfOrder { term: doc_term, prev, next }) => {
+                    assert_eq!(doc_term, term("c"));
+                    assert_eq!(prev, 3);
+                    assert_eq!(next, doc);
+                }
+                _ => panic!("Expected DocsOutOfOrder"),
+            }
+        }
+        posting_serializer.write_doc(4, 1, &[0], None).unwrap();
+        posting_serializer.close_term().unwrap();
+        // the documents of the next term start over.
+        posting_serializer.new_term(&term("d"), 1, None).unwrap();
+        posting_serializer.write_doc(0, 1, &[0], None).unwrap();
+        posting_serializer.close_term().unwrap();
+        posting_serializer.close().unwrap();
+        match Error::from(SerializerError::TermNotClosed) {
+            Error::SerializerError(SerializerError::TermNotClosed) => {}
+            _ => panic!("Expected SerializerError"),
+        }
+    }
+
+    #[test]
+    fn test_serializer_duplicate_doc() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        let mut segment = index.new_segment();
+        let mut posting_serializer = PostingsSerializer::open(&mut segment).unwrap();
+        let term = Term::from_field_text(text_field, "abc");
+        posting_serializer.new_term(&term, 200, None).unwrap();
+        for doc in 0..150 {
+            posting_serializer.write_doc(doc, 1, &[0], None).unwrap();
+        }
+        // the duplicate is detected past the first block as well.
+        let error = posting_serializer.write_doc(149, 1, &[0], None).unwrap_err();
+        match error {
+            SerializerError::DocsOutOfOrder { ref term, prev: 149, next: 149 } if *term == Term::from_field_text(text_field, "abc") => {}
+            _ => panic!("Expected DocsOutOfOrder"),
+        }
+        match Error::from(error) {
+            Error::SerializerError(SerializerError::DocsOutOfOrder { prev: 149, next: 149, .. }) => {}
+            _ => panic!("Expected SerializerError"),
+        }
     }
     
     #[test]
     pub fn test_position_and_fieldnorm_write_fullstack() {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let index = Index::create_in_ram(schema.clone());
         let segment = index.new_segment();
         let heap = Heap::with_capacity(10_000_000);
@@ -104,11 +203,12 @@ mod tests {
         {
             let segment_reader = SegmentReader::open(segment).unwrap();
             {
-                let fieldnorm_reader = segment_reader.get_fieldnorms_reader(text_field).unwrap();
+                let fieldnorm_reader = segment_reader.get_fieldnorm_reader(text_field).unwrap();
                 assert_eq!(fieldnorm_reader.get(0), 8 + 5);
                 assert_eq!(fieldnorm_reader.get(1), 2);
                 for i in 2 .. 1000 {
-                    assert_eq!(fieldnorm_reader.get(i), i + 1);
+                    // the field norms are encoded on one byte, with some loss.
+                    assert_eq!(fieldnorm_reader.get(i), id_to_fieldnorm(fieldnorm_to_id(i + 1)));
                 }
             }
             {
@@ -150,9 +250,266 @@ mod tests {
                 }
                 assert!(!postings_e.advance());
             }
+            {
+                // the skip list jumps over the blocks before the target,
+                // along with their positions.
+                let term_e = Term::from_field_text(text_field, "e");
+                let mut postings_e = segment_reader.read_postings_all_info(&term_e).unwrap();
+                assert!(postings_e.advance());
+                for &target in &[3u32, 300, 301, 700, 999] {
+                    assert_eq!(postings_e.skip_next(target), SkipResult::Reached);
+                    assert_eq!(postings_e.term_freq(), target);
+                    let positions = postings_e.positions();
+                    assert_eq!(positions.len(), target as usize);
+                    assert_eq!(positions[positions.len() - 1], target - 1);
+                }
+                assert_eq!(postings_e.skip_next(1000), SkipResult::End);
+            }
+            {
+                // the blocks of term frequencies are skipped.
+                let term_e = Term::from_field_text(text_field, "e");
+                let mut postings_e = segment_reader.read_postings(&term_e, SegmentPostingsOption::NoFreq).unwrap();
+                for i in 2u32 .. 1000u32 {
+                    assert!(postings_e.advance());
+                    assert_eq!(postings_e.doc(), i);
+                    assert_eq!(postings_e.term_freq(), 1);
+                }
+                assert!(!postings_e.advance());
+            }
+        }
+    }
+
+    #[test]
+    fn test_skip_positions_blocks() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1000 {
+                let mut doc = Document::default();
+                let mut text = iter::repeat("all ").take(i % 5 + 1).collect::<String>();
+                if i % 2 == 0 {
+                    text.push_str("even");
+                }
+                doc.add_text(text_field, &text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        {
+            // through the skip list.
+            let term = Term::from_field_text(text_field, "all");
+            let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+            assert!(postings.advance());
+            for &target in &[1u32, 200, 201, 555, 998, 999] {
+                assert_eq!(postings.skip_next(target), SkipResult::Reached);
+                let positions: Vec<u32> = (0..target % 5 + 1).collect();
+                assert_eq!(postings.positions(), &positions[..]);
+            }
+            assert!(!postings.advance());
+        }
+        {
+            // without skip list, the positions of the skipped blocks are not decoded either.
+            let term = Term::from_field_text(text_field, "even");
+            let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+            assert_eq!(postings.len(), 500);
+            assert!(postings.advance());
+            for &target in &[2u32, 301, 302, 998] {
+                assert_eq!(postings.skip_next(target), if target % 2 == 0 { SkipResult::Reached } else { SkipResult::OverStep });
+                let doc = postings.doc();
+                assert_eq!(postings.positions(), [doc % 5 + 1]);
+            }
+            assert_eq!(postings.skip_next(999), SkipResult::End);
+        }
+    }
+
+    #[test]
+    fn test_term_positions_len() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let freq_options = TextOptions::default().set_indexing_options(TextIndexingOptions::TokenizedWithFreq);
+        let freq_field = schema_builder.add_text_field("freq", freq_options);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1000 {
+                let mut doc = Document::default();
+                let text = iter::repeat("all ").take(i % 5 + 1).collect::<String>();
+                doc.add_text(text_field, &format!("{} doc{}", text, i % 7));
+                doc.add_text(freq_field, &text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        // the positions of the terms are contiguous,
+        // and the ones of the last term end with the data of the file.
+        let mut positions_end = 0;
+        let mut terms = segment_reader.term_dict().stream();
+        while let Some((term_bytes, term_info)) = terms.next() {
+            let positions_range = term_info.positions_range().unwrap();
+            assert_eq!(positions_range.start, positions_end);
+            if Term::from(term_bytes).field() == freq_field {
+                assert_eq!(positions_range.len(), 0);
+            }
+            positions_end = positions_range.end;
+        }
+        assert_eq!(positions_end + FOOTER_LEN, segment_reader.space_usage().component(SegmentComponent::POSITIONS));
+        {
+            // the positions are read within the bounds of the term.
+            let term = Term::from_field_text(text_field, "doc3");
+            let term_info = segment_reader.get_term_info(&term).unwrap();
+            assert_eq!(segment_reader.term_positions_data(&term_info).len(), term_info.positions_len.unwrap() as usize);
+            let mut postings = segment_reader.read_postings_all_info(&term).unwrap();
+            while postings.advance() {
+                let doc = postings.doc();
+                assert_eq!(postings.positions(), [doc % 5 + 1]);
+            }
+        }
+    }
+
+    /// Splits on whitespaces, giving a payload of 1
+    /// to the words in bold, like `*word*`.
+    struct BoldTokenizer;
+
+    struct BoldTokenStream<'a> {
+        words: SplitWhitespace<'a>,
+        token: Token,
+        num_words: u32,
+    }
+
+    impl<'a> TokenStream<'a> for BoldTokenStream<'a> {
+        fn advance(&mut self) -> bool {
+            match self.words.next() {
+                Some(word) => {
+                    self.token.text = word.trim_matches('*').to_string();
+                    self.token.payload = if word.starts_with('*') { 1 } else { 0 };
+                    self.token.position = self.num_words;
+                    self.num_words += 1;
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn token(&self) -> &Token {
+            &self.token
+        }
+
+        fn token_mut(&mut self) -> &mut Token {
+            &mut self.token
+        }
+
+        fn reset(&mut self, text: &'a str) {
+            self.words = text.split_whitespace();
+            self.num_words = 0u32;
+        }
+    }
+
+    impl Tokenizer for BoldTokenizer {
+        fn description(&self) -> String {
+            String::from("bold")
+        }
+
+        fn token_stream<'a>(&self, text: &'a str) -> Box<TokenStream<'a> + 'a> {
+            Box::new(BoldTokenStream {
+                words: text.split_whitespace(),
+                token: Token::default(),
+                num_words: 0u32,
+            })
+        }
+    }
+
+    #[test]
+    fn test_postings_payloads() {
+        let mut schema_builder = SchemaBuilder::default();
+        let payload_options = TEXT
+            .set_indexing_options(TextIndexingOptions::TokenizedWithFreqPositionAndPayload)
+            .set_tokenizer("bold");
+        let payload_field = schema_builder.add_text_field("payload", payload_options);
+        let text_field = schema_builder.add_text_field("text", TEXT.set_tokenizer("bold"));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.tokenizers().register("bold", BoldTokenizer);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1000 {
+                let text = if i % 3 == 0 { "a *b* a" } else { "*a* b" };
+                let mut doc = Document::default();
+                doc.add_text(payload_field, text);
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+                if i == 599 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        // as the first segment has a multiple of 3 documents,
+        // the payloads of both segments follow the same pattern.
+        let check_payloads = |segment_reader: &SegmentReader| {
+            let term_a = Term::from_field_text(payload_field, "a");
+            let mut postings = segment_reader.read_postings_all_info(&term_a).unwrap();
+            while postings.advance() {
+                let doc = postings.doc();
+                if doc % 3 == 0 {
+                    assert_eq!(postings.positions(), [0, 2]);
+                    assert_eq!(postings.payloads(), [0, 0]);
+                }
+                else {
+                    assert_eq!(postings.positions(), [0]);
+                    assert_eq!(postings.payloads(), [1]);
+                }
+            }
+            // the payloads of the skipped blocks are not decoded.
+            let term_b = Term::from_field_text(payload_field, "b");
+            let mut postings = segment_reader.read_postings_all_info(&term_b).unwrap();
+            assert!(postings.advance());
+            for &target in &[1u32, 201, 300, 398] {
+                assert_eq!(postings.skip_next(target), SkipResult::Reached);
+                assert_eq!(postings.payloads(), if target % 3 == 0 { [1] } else { [0] });
+            }
+            // the fields without payloads do not return any.
+            let term_text = Term::from_field_text(text_field, "a");
+            let mut postings = segment_reader.read_postings_all_info(&term_text).unwrap();
+            assert!(postings.advance());
+            assert_eq!(postings.positions(), [0, 2]);
+            assert!(postings.payloads().is_empty());
+        };
+        {
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 2);
+            for segment_reader in searcher.segment_readers() {
+                check_payloads(segment_reader);
+            }
+        }
+        {
+            // merging carries the payloads through.
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+            let searcher = index.searcher();
+            assert_eq!(searcher.segment_readers().len(), 1);
+            check_payloads(searcher.segment_reader(0));
+            let space_usage = searcher.segment_reader(0).space_usage();
+            assert!(space_usage.component(SegmentComponent::PAYLOADS) > FOOTER_LEN);
         }
     }
     
+    #[test]
+    fn test_seek() {
+        let mut postings = Box::new(VecPostings::from(vec!(1, 3, 9)));
+        assert!(postings.advance());
+        assert_eq!(postings.seek(1), SkipResult::Reached);
+        assert_eq!(postings.seek(2), SkipResult::OverStep);
+        assert_eq!(postings.doc(), 3);
+        assert_eq!(postings.seek(9), SkipResult::Reached);
+        assert_eq!(postings.seek(10), SkipResult::End);
+    }
+
     #[test]
     fn test_intersection() {
         {
@@ -174,6 +531,264 @@ mod tests {
             assert_eq!(intersection.doc(), 9);
             assert!(!intersection.advance());
         }
+        {
+            let a = Box::new(VecPostings::from(vec!(1, 3, 4, 9, 10, 15, 20)));
+            let b = Box::new(VecPostings::from(vec!(3, 4, 9, 10, 18, 20)));
+            let c = Box::new(VecPostings::from(vec!(0, 1, 4, 9, 10, 11, 20)));
+            let mut intersection = IntersectionDocSet::new(vec!(a, b, c));
+            assert!(intersection.advance());
+            assert_eq!(intersection.doc(), 4);
+            assert_eq!(intersection.skip_next(4), SkipResult::Reached);
+            assert_eq!(intersection.skip_next(5), SkipResult::OverStep);
+            assert_eq!(intersection.doc(), 9);
+            assert_eq!(intersection.skip_next(10), SkipResult::Reached);
+            assert!(intersection.advance());
+            assert_eq!(intersection.doc(), 20);
+            assert_eq!(intersection.skip_next(21), SkipResult::End);
+            assert!(!intersection.advance());
+        }
+    }
+
+    /// Returns random sorted doc ids, of various densities
+    /// over roughly the same range of doc ids.
+    fn random_docs(seed: u32) -> Vec<DocId> {
+        let ratio = [0.01f32, 0.1f32, 0.5f32][seed as usize % 3];
+        generate_array_with_seed((2_000f32 * ratio) as usize, ratio, seed)
+    }
+
+    /// Consumes the `DocSet`, randomly alternating between `.advance()`
+    /// and `.skip_next(...)`, and checks its documents against `expected`.
+    fn assert_docset_eq<TDocSet: DocSet>(mut docset: TDocSet, expected: &[DocId], seed: u32) {
+        let mut rng = XorShiftRng::from_seed([seed, 1, 2, 3]);
+        if expected.is_empty() {
+            assert!(!docset.advance());
+            return;
+        }
+        assert!(docset.advance());
+        assert_eq!(docset.doc(), expected[0]);
+        let mut cursor = 0;
+        loop {
+            if rng.gen_weighted_bool(2) {
+                cursor += 1;
+                if cursor == expected.len() {
+                    assert!(!docset.advance());
+                    return;
+                }
+                assert!(docset.advance());
+            }
+            else {
+                let target = docset.doc() + rng.gen_range(0, 50);
+                match expected[cursor..].iter().position(|&doc| doc >= target) {
+                    Some(offset) => {
+                        cursor += offset;
+                        let skip_result = if expected[cursor] == target { SkipResult::Reached } else { SkipResult::OverStep };
+                        assert_eq!(docset.skip_next(target), skip_result);
+                    }
+                    None => {
+                        assert_eq!(docset.skip_next(target), SkipResult::End);
+                        return;
+                    }
+                }
+            }
+            assert_eq!(docset.doc(), expected[cursor]);
+        }
+    }
+
+    fn vec_postings(docs_list: &[Vec<DocId>]) -> Vec<VecPostings> {
+        docs_list
+            .iter()
+            .map(|docs| VecPostings::from(docs.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_intersection_random() {
+        for seed in 1..60 {
+            let num_docsets = 1 + seed as usize % 4;
+            let docs_list: Vec<Vec<DocId>> = (0..num_docsets)
+                .map(|i| random_docs(seed * 7 + i as u32))
+                .collect();
+            let expected: Vec<DocId> = docs_list[0]
+                .iter()
+                .cloned()
+                .filter(|doc| docs_list.iter().all(|docs| docs.contains(doc)))
+                .collect();
+            assert_docset_eq(IntersectionDocSet::new(vec_postings(&docs_list)), &expected, seed);
+            // mixing different types of `DocSet`s.
+            let mut docsets: Vec<Box<DocSet>> = vec_postings(&docs_list[1..])
+                .into_iter()
+                .map(|docset| Box::new(docset) as Box<DocSet>)
+                .collect();
+            let mut bitset = BitSet::with_max_doc(*docs_list[0].last().unwrap() + 1);
+            for &doc in &docs_list[0] {
+                bitset.insert(doc);
+            }
+            docsets.push(Box::new(BitSetDocSet::from(bitset)));
+            assert_docset_eq(IntersectionDocSet::new(docsets), &expected, seed);
+        }
+        assert_docset_eq(IntersectionDocSet::new(vec!(VecPostings::from(vec!(1, 2)), VecPostings::from(vec!()))), &[], 1);
+    }
+
+    #[test]
+    fn test_union_random() {
+        for seed in 1..60 {
+            let num_docsets = seed as usize % 5;
+            let docs_list: Vec<Vec<DocId>> = (0..num_docsets)
+                .map(|i| random_docs(seed * 7 + i as u32))
+                .collect();
+            let expected: Vec<DocId> = docs_list
+                .iter()
+                .flat_map(|docs| docs.iter().cloned())
+                .collect::<BTreeSet<DocId>>()
+                .into_iter()
+                .collect();
+            let union = UnionDocSet::new(vec_postings(&docs_list));
+            assert_eq!(union.size_hint(), docs_list.iter().map(|docs| docs.len()).sum::<usize>());
+            assert_docset_eq(union, &expected, seed);
+        }
+    }
+
+    #[test]
+    fn test_exclude_random() {
+        for seed in 1..60 {
+            let num_excluded = seed as usize % 3;
+            let docs = random_docs(seed * 7);
+            let excluded_list: Vec<Vec<DocId>> = (1..num_excluded + 1)
+                .map(|i| random_docs(seed * 7 + i as u32))
+                .collect();
+            let expected: Vec<DocId> = docs
+                .iter()
+                .cloned()
+                .filter(|doc| !excluded_list.iter().any(|excluded| excluded.contains(doc)))
+                .collect();
+            let exclude = ExcludeDocSet::new(
+                VecPostings::from(docs.clone()),
+                UnionDocSet::new(vec_postings(&excluded_list)));
+            assert_eq!(exclude.size_hint(), docs.len());
+            assert_docset_eq(exclude, &expected, seed);
+        }
+    }
+
+    /// Returns the postings of the documents of `0..max_doc` multiple of `step`,
+    /// encoded along with their term frequencies.
+    fn encoded_postings(max_doc: DocId, step: DocId) -> (u32, Vec<u8>) {
+        let docs: Vec<DocId> = (0..max_doc).filter(|doc| doc % step == 0).collect();
+        let term_freqs: Vec<u32> = iter::repeat(1u32).take(docs.len()).collect();
+        (docs.len() as u32, encode_postings(&docs, Some(&term_freqs)))
+    }
+
+    #[test]
+    fn test_intersection_segment_postings() {
+        let (common_len, common_data) = encoded_postings(100_000, 1);
+        let (rare_len, rare_data) = encoded_postings(100_000, 997);
+        let mut intersection = intersection(vec!(
+            SegmentPostings::from_data(common_len, &common_data, FreqHandler::new_with_freq()),
+            SegmentPostings::from_data(rare_len, &rare_data, FreqHandler::new_with_freq())));
+        let mut docs = Vec::new();
+        while intersection.advance() {
+            docs.push(intersection.doc());
+        }
+        assert_eq!(docs, (0..100_000).filter(|doc| doc % 997 == 0).collect::<Vec<DocId>>());
+    }
+
+    #[bench]
+    fn bench_intersection_segment_postings(b: &mut Bencher) {
+        let (common_len, common_data) = encoded_postings(10_000_000, 1);
+        let (rare_len, rare_data) = encoded_postings(10_000_000, 10_000);
+        b.iter(|| {
+            let mut intersection = intersection(vec!(
+                SegmentPostings::from_data(common_len, &common_data, FreqHandler::new_with_freq()),
+                SegmentPostings::from_data(rare_len, &rare_data, FreqHandler::new_with_freq())));
+            let mut count = 0;
+            while intersection.advance() {
+                count += 1;
+            }
+            assert_eq!(count, 1_000);
+        });
+    }
+
+    #[bench]
+    fn bench_intersection_segment_postings_with_skip_list(b: &mut Bencher) {
+        let common_docs: Vec<DocId> = (0..10_000_000).collect();
+        let rare_docs: Vec<DocId> = (0..10_000_000).filter(|doc| doc % 10_000 == 0).collect();
+        let common_term_freqs: Vec<u32> = iter::repeat(1u32).take(common_docs.len()).collect();
+        let rare_term_freqs: Vec<u32> = iter::repeat(1u32).take(rare_docs.len()).collect();
+        let common_data = encode_postings_with_skip_list(&common_docs, Some(&common_term_freqs));
+        let rare_data = encode_postings_with_skip_list(&rare_docs, Some(&rare_term_freqs));
+        b.iter(|| {
+            let mut intersection = intersection(vec!(
+                SegmentPostings::from_data_with_format(common_docs.len() as u32, &common_data, FreqHandler::new_with_freq(), POSTINGS_FORMAT_VERSION),
+                SegmentPostings::from_data_with_format(rare_docs.len() as u32, &rare_data, FreqHandler::new_with_freq(), POSTINGS_FORMAT_VERSION)));
+            let mut count = 0;
+            while intersection.advance() {
+                count += 1;
+            }
+            assert_eq!(count, 1_000);
+        });
+    }
+
+    #[bench]
+    fn bench_intersection_segment_postings_without_skipping(b: &mut Bencher) {
+        // intersects the postings by advancing them one document at a time,
+        // for comparison with `bench_intersection_segment_postings`.
+        let (common_len, common_data) = encoded_postings(10_000_000, 1);
+        let (rare_len, rare_data) = encoded_postings(10_000_000, 10_000);
+        b.iter(|| {
+            let mut common = SegmentPostings::from_data(common_len, &common_data, FreqHandler::new_with_freq());
+            let mut rare = SegmentPostings::from_data(rare_len, &rare_data, FreqHandler::new_with_freq());
+            let mut count = 0;
+            let mut has_docs = common.advance() && rare.advance();
+            while has_docs {
+                if common.doc() < rare.doc() {
+                    has_docs = common.advance();
+                }
+                else if common.doc() > rare.doc() {
+                    has_docs = rare.advance();
+                }
+                else {
+                    count += 1;
+                    has_docs = common.advance() && rare.advance();
+                }
+            }
+            assert_eq!(count, 1_000);
+        });
+    }
+
+    #[bench]
+    fn bench_segment_postings_scan(b: &mut Bencher) {
+        // scans the postings one document at a time, through
+        // the trait object, for comparison with
+        // `bench_segment_postings_scan_read_block`.
+        let (len, data) = encoded_postings(10_000_000, 3);
+        b.iter(|| {
+            let mut segment_postings = SegmentPostings::from_data(len, &data, FreqHandler::new_with_freq());
+            let postings: &mut Postings = &mut segment_postings;
+            let mut sum = 0u64;
+            while postings.advance() {
+                sum += (postings.doc() + postings.term_freq()) as u64;
+            }
+            sum
+        });
+    }
+
+    #[bench]
+    fn bench_segment_postings_scan_read_block(b: &mut Bencher) {
+        let (len, data) = encoded_postings(10_000_000, 3);
+        let mut block = [0u32; NUM_DOCS_PER_BLOCK];
+        let mut freqs_block = [0u32; NUM_DOCS_PER_BLOCK];
+        b.iter(|| {
+            let mut segment_postings = SegmentPostings::from_data(len, &data, FreqHandler::new_with_freq());
+            let mut sum = 0u64;
+            loop {
+                let num_docs = segment_postings.read_block_with_freqs(&mut block, &mut freqs_block);
+                if num_docs == 0 {
+                    break;
+                }
+                for (&doc, &term_freq) in block[..num_docs].iter().zip(freqs_block[..num_docs].iter()) {
+                    sum += (doc + term_freq) as u64;
+                }
+            }
+            sum
+        });
     }
-     
 }
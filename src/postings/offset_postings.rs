@@ -33,6 +33,10 @@ impl<'a> DocSet for OffsetPostings<'a> {
     fn doc(&self,) -> DocId {
         self.underlying.doc() + self.offset
     }
+
+    fn size_hint(&self,) -> usize {
+        self.underlying.size_hint()
+    }
     
     fn skip_next(&mut self, target: DocId) -> SkipResult {
         if target >= self.offset {
@@ -59,5 +63,9 @@ impl<'a> Postings for OffsetPostings<'a> {
     fn positions(&self) -> &[u32] {
         self.underlying.positions()
     }
+
+    fn payloads(&self) -> &[u32] {
+        self.underlying.payloads()
+    }
     
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::borrow::Borrow;
 use postings::docset::DocSet;
 use common::HasLen;
+use DocId;
 
 
 
@@ -20,6 +21,22 @@ pub trait Postings: DocSet {
     /// Returns the list of positions of the term, expressed as a list of
     /// token ordinals.
     fn positions(&self) -> &[u32];
+    /// Returns the payloads of the positions of the term,
+    /// one for each of the positions.
+    ///
+    /// They are empty, unless the field has payloads
+    /// and the positions are being read.
+    fn payloads(&self) -> &[u32] {
+        &[]
+    }
+    /// Returns the last document of the block of the current document,
+    /// along with the maximum term frequency of the block, if known.
+    ///
+    /// Scorers may use it to skip the blocks whose documents
+    /// cannot score high enough.
+    fn block_max(&self,) -> Option<(DocId, u32)> {
+        None
+    }
 }
 
 impl<TPostings: Postings> Postings for Box<TPostings> {
@@ -34,6 +51,16 @@ impl<TPostings: Postings> Postings for Box<TPostings> {
         unboxed.positions()
     }
 
+    fn payloads(&self) -> &[u32] {
+        let unboxed: &TPostings = self.borrow();
+        unboxed.payloads()
+    }
+
+    fn block_max(&self,) -> Option<(DocId, u32)> {
+        let unboxed: &TPostings = self.borrow();
+        unboxed.block_max()
+    }
+
 }
 
 impl<'a, TPostings: Postings> Postings for &'a mut TPostings {
@@ -48,6 +75,16 @@ impl<'a, TPostings: Postings> Postings for &'a mut TPostings {
         unref.positions()
     }
 
+    fn payloads(&self) -> &[u32] {
+        let unref: &TPostings = *self;
+        unref.payloads()
+    }
+
+    fn block_max(&self,) -> Option<(DocId, u32)> {
+        let unref: &TPostings = *self;
+        unref.block_max()
+    }
+
 }
 
 
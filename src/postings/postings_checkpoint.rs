@@ -0,0 +1,70 @@
+use common::BinarySerializable;
+use compression::NUM_DOCS_PER_BLOCK;
+use std::io;
+
+
+/// Version of the format of the postings, as written by the `PostingsSerializer`.
+///
+/// It is recorded in the `.info` file of the segments.
+///
+/// * Version 1 prepends a skip list to the postings of the terms contained
+///   in more than `SKIP_LIST_THRESHOLD` documents.
+/// * Version 2 writes the positions block by block, rather than
+///   as a single array for all of the documents of the term.
+/// * Version 3 writes the maximum term frequency of each block
+///   of documents before it.
+/// * Version 4 records the total term frequency of the terms
+///   in their `TermInfo`, in the term dictionary.
+/// * Version 5 records the number of bytes taken by the positions
+///   of the terms in their `TermInfo` as well.
+///
+/// The segments written in the previous versions remain readable,
+/// and merging them rewrites their postings in the current format.
+pub const POSTINGS_FORMAT_VERSION: u32 = 5;
+
+/// Terms contained in more documents than this threshold get a skip list,
+/// with a checkpoint after each of their complete blocks.
+pub const SKIP_LIST_THRESHOLD: u32 = 4 * NUM_DOCS_PER_BLOCK as u32;
+
+/// Period of the layers of the skip lists of the postings.
+pub const SKIP_LIST_PERIOD: usize = 8;
+
+
+/// `PostingsCheckpoint` records where the postings of a term
+/// resume after one of their complete blocks.
+///
+/// The skip list of the postings associates each checkpoint
+/// to the last document of the block it follows.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PostingsCheckpoint {
+    /// Number of documents up to the end of the block.
+    pub num_docs: u32,
+    /// Offset of the next block, relative to the start of the postings.
+    pub postings_offset: u32,
+    /// Offset of the positions of the next block, relative to the start
+    /// of the positions of the term.
+    ///
+    /// In the version 1 of the format, it is the number of
+    /// positions up to the end of the block instead.
+    pub positions_offset: u32,
+}
+
+impl BinarySerializable for PostingsCheckpoint {
+    fn serialize(&self, writer: &mut io::Write) -> io::Result<usize> {
+        Ok(
+            try!(self.num_docs.serialize(writer)) +
+            try!(self.postings_offset.serialize(writer)) +
+            try!(self.positions_offset.serialize(writer))
+        )
+    }
+    fn deserialize(reader: &mut io::Read) -> io::Result<Self> {
+        let num_docs = try!(u32::deserialize(reader));
+        let postings_offset = try!(u32::deserialize(reader));
+        let positions_offset = try!(u32::deserialize(reader));
+        Ok(PostingsCheckpoint {
+            num_docs: num_docs,
+            postings_offset: postings_offset,
+            positions_offset: positions_offset,
+        })
+    }
+}
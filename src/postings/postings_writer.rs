@@ -1,14 +1,38 @@
 use DocId;
 use schema::Term;
 use schema::FieldValue;
-use postings::PostingsSerializer;
-use std::io;
+use schema::Value;
+use postings::{PostingsSerializer, SerializerError};
 use postings::Recorder;
-use analyzer::SimpleTokenizer;
+use postings::MAX_POSITION;
+use analyzer::{Tokenizer, TokenStream};
 use schema::Field;
-use analyzer::StreamingIterator;
 use datastruct::stacker::{HashMap, Heap};
 
+
+/// Returned by `PostingsWriter::index_text`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexedText {
+    /// Number of tokens, used as the field norm.
+    pub num_tokens: u32,
+    /// True iff tokens were dropped because of `max_positions`.
+    pub truncated: bool,
+    /// True iff the position of a token went beyond
+    /// `MAX_POSITION`, and was clamped to it.
+    pub clamped: bool,
+}
+
+// position of a token at `offset` from `pos`, clamped to `MAX_POSITION`.
+fn token_position(pos: u32, offset: u32, clamped: &mut bool) -> u32 {
+    match pos.checked_add(offset) {
+        Some(position) if position <= MAX_POSITION => position,
+        _ => {
+            *clamped = true;
+            MAX_POSITION
+        }
+    }
+}
+
 /// The `PostingsWriter` is in charge of receiving documenting  
 /// and building a `Segment` in anonymous memory.
 ///
@@ -22,35 +46,112 @@ pub trait PostingsWriter {
     /// * term - the term
     /// * heap - heap used to store the postings informations as well as the terms
     /// in the hashmap.
-    fn suscribe(&mut self,  doc: DocId, pos: u32, term: &Term, heap: &Heap);
+    fn suscribe(&mut self,  doc: DocId, pos: u32, term: &Term, heap: &Heap) {
+        self.suscribe_with_payload(doc, pos, 0u32, term, heap);
+    }
+
+    /// Record that a document contains a term at a given position,
+    /// with the given payload.
+    ///
+    /// The payload is only recorded for the fields
+    /// indexed with payloads.
+    fn suscribe_with_payload(&mut self,  doc: DocId, pos: u32, payload: u32, term: &Term, heap: &Heap);
     
     /// Serializes the postings on disk.
     /// The actual serialization format is handled by the `PostingsSerializer`.
-    fn serialize(&self, serializer: &mut PostingsSerializer, heap: &Heap) -> io::Result<()>;
+    fn serialize(&self, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError>;
     
     /// Closes all of the currently open `Recorder`'s.
     fn close(&mut self, heap: &Heap);
         
     /// Tokenize a text and suscribe all of its token.
-    fn index_text<'a>(&mut self, doc_id: DocId, field: Field, field_values: &[&'a FieldValue], heap: &Heap) -> u32  {
+    ///
+    /// Each value is tokenized by `tokenizer`, and its tokens are suscribed
+    /// at the position of the value within the field plus the position 
+    /// of the token within the stream. The next value starts right after
+    /// the last position of the stream, and `position_gap` positions 
+    /// are skipped between two values.
+    ///
+    /// Positions are clamped to `MAX_POSITION`, so that large position
+    /// gaps never make them wrap around. The tokens beyond it all
+    /// share this position, and the returned `IndexedText` is
+    /// flagged as `clamped`.
+    ///
+    /// Several tokens may share the same position. Only the first
+    /// of them counts in the number of tokens.
+    ///
+    /// A single token stream is created for all of the values,
+    /// and `reset` from one value to the next, so that the values
+    /// share the buffers of the stream.
+    ///
+    /// Pre-tokenized values are not tokenized again, their tokens
+    /// are suscribed at their own position, with a payload of 0.
+    /// The tokens of the other values are suscribed with their payload.
+    ///
+    /// At most `max_positions` tokens are suscribed, the following
+    /// tokens of the document are dropped.
+    ///
+    /// Returns the number of tokens, used as the field norm, and
+    /// whether tokens were dropped or had their position clamped.
+    fn index_text<'a>(&mut self, doc_id: DocId, field: Field, field_values: &[&'a FieldValue], tokenizer: &Tokenizer, position_gap: u32, max_positions: Option<u32>, heap: &Heap) -> IndexedText  {
         let mut pos = 0u32;
         let mut num_tokens: u32 = 0u32;
+        let mut num_suscribed: u32 = 0u32;
+        let mut clamped = false;
+        let max_positions = max_positions.unwrap_or(u32::max_value());
         let mut term = Term::allocate(field, 100);
+        let mut reusable_token_stream: Option<Box<TokenStream<'a> + 'a>> = None;
         for field_value in field_values {
-            let mut tokens = SimpleTokenizer.tokenize(field_value.value().text());
-            // right now num_tokens and pos are redundant, but it should
-            // change when we get proper analyzers
-            while let Some(token) = tokens.next() {
-                term.set_text(token);
-                self.suscribe(doc_id, pos, &term, heap);
-                pos += 1u32;
-                num_tokens += 1u32;
+            let field_value: &'a FieldValue = *field_value;
+            if let Value::PreTokenized(ref payload) = *field_value.value() {
+                // pre-tokenized values bypass the tokenizer, and
+                // their tokens keep their own positions.
+                for &(ref token, position) in payload.tokens() {
+                    if num_suscribed == max_positions {
+                        return IndexedText { num_tokens: num_tokens, truncated: true, clamped: clamped };
+                    }
+                    num_suscribed += 1;
+                    term.set_text(token);
+                    let position = token_position(pos, position, &mut clamped);
+                    self.suscribe(doc_id, position, &term, heap);
+                    num_tokens += 1u32;
+                }
+                pos = pos.saturating_add(payload.num_positions());
+            }
+            else {
+                let text = field_value.value().text();
+                let mut token_stream = match reusable_token_stream.take() {
+                    Some(mut token_stream) => {
+                        token_stream.reset(text);
+                        token_stream
+                    }
+                    None => tokenizer.token_stream(text),
+                };
+                let mut num_positions = 0u32;
+                while let Some(token) = token_stream.next() {
+                    if num_suscribed == max_positions {
+                        return IndexedText { num_tokens: num_tokens, truncated: true, clamped: clamped };
+                    }
+                    num_suscribed += 1;
+                    term.set_text(&token.text);
+                    let position = token_position(pos, token.position, &mut clamped);
+                    self.suscribe_with_payload(doc_id, position, token.payload, &term, heap);
+                    if num_positions == 0 || token.position >= num_positions {
+                        // tokens stacked at the position of the 
+                        // previous token, like synonyms, do not
+                        // count in the field norm.
+                        num_tokens += 1u32;
+                    }
+                    num_positions = token.position + 1;
+                }
+                pos = pos.saturating_add(num_positions);
+                reusable_token_stream = Some(token_stream);
             }
-            pos += 1;
+            pos = pos.saturating_add(position_gap);
             // THIS is to avoid phrase query accross field repetition.
             // span queries might still match though :|
         }
-        num_tokens
+        IndexedText { num_tokens: num_tokens, truncated: false, clamped: clamped }
     }
 }
 
@@ -101,7 +202,7 @@ impl<'a, Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<'
     }
     
     #[inline]
-    fn suscribe(&mut self, doc: DocId, position: u32, term: &Term, heap: &Heap) {
+    fn suscribe_with_payload(&mut self, doc: DocId, position: u32, payload: u32, term: &Term, heap: &Heap) {
         let mut recorder = self.term_index.get_or_create(term);
         let current_doc = recorder.current_doc();
         if current_doc != doc {
@@ -110,10 +211,10 @@ impl<'a, Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<'
             }
             recorder.new_doc(doc, heap);
         }
-        recorder.record_position(position, heap);
+        recorder.record_position(position, payload, heap);
     }
     
-    fn serialize(&self, serializer: &mut PostingsSerializer, heap: &Heap) -> io::Result<()> {
+    fn serialize(&self, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError> {
         let mut term_offsets: Vec<(&[u8], (u32, &Rec))>  = self.term_index
             .iter()
             .collect();
@@ -122,7 +223,7 @@ impl<'a, Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<'
         for (term_bytes, (addr, recorder)) in term_offsets {
             // TODO remove copy
             term.set_content(term_bytes);
-            try!(serializer.new_term(&term, recorder.doc_freq()));
+            try!(serializer.new_term(&term, recorder.doc_freq(), Some(recorder.total_term_freq())));
             try!(recorder.serialize(addr, serializer, heap));
             try!(serializer.close_term());
         }
@@ -1,11 +1,24 @@
 use DocId;
-use std::io;
-use postings::PostingsSerializer;
+use postings::{PostingsSerializer, SerializerError};
 use datastruct::stacker::{ExpUnrolledLinkedList, Heap, HeapAllocable};
 
 const EMPTY_ARRAY: [u32; 0] = [0u32; 0];
 const POSITION_END: u32 = 4294967295; 
 
+/// Largest position recorded, `u32::max_value()` being
+/// reserved to mark the end of the positions of a document.
+pub const MAX_POSITION: u32 = POSITION_END - 1;
+
+// delta between a position and the previous position of the document.
+//
+// The positions of a document are recorded in increasing order,
+// clamped to `MAX_POSITION`, so that the delta never wraps around.
+fn position_delta(prev_position: u32, position: u32) -> u32 {
+    position
+        .checked_sub(prev_position)
+        .expect("Positions are recorded in increasing order. This should never happen. Please report the bug.")
+}
+
 /// Recorder is in charge of recording relevant information about
 /// the presence of a term in a document.
 ///
@@ -15,21 +28,27 @@ const POSITION_END: u32 = 4294967295;
 ///   * the document id 
 ///   * the term frequency
 ///   * the term positions
+///   * the payloads of the positions
 pub trait Recorder: HeapAllocable {
     /// Returns the current document
     fn current_doc(&self,) -> u32;
     /// Starts recording information about a new document
     /// This method shall only be called if the term is within the document. 
     fn new_doc(&mut self, doc: DocId, heap: &Heap);
-    /// Record the position of a term. For each document, 
-    /// this method will be called `term_freq` times.
-    fn record_position(&mut self, position: u32, heap: &Heap);
+    /// Record the position of a term, along with its payload.
+    /// For each document, this method will be called `term_freq` times.
+    ///
+    /// The payload is ignored, unless the recorder records payloads.
+    fn record_position(&mut self, position: u32, payload: u32, heap: &Heap);
     /// Close the document. It will help record the term frequency. 
     fn close_doc(&mut self, heap: &Heap);
     /// Returns the number of document that have been seen so far
     fn doc_freq(&self,) -> u32;
+    /// Returns the number of positions that have been recorded so far,
+    /// over all of the documents.
+    fn total_term_freq(&self,) -> u64;
     /// Pushes the postings information to the serializer.
-    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> io::Result<()>;
+    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError>;
 }
 
 /// Only records the doc ids
@@ -38,6 +57,7 @@ pub struct NothingRecorder {
     stack: ExpUnrolledLinkedList,
     current_doc: DocId,
     doc_freq: u32,
+    total_term_freq: u64,
 }
 
 impl HeapAllocable for NothingRecorder {
@@ -46,6 +66,7 @@ impl HeapAllocable for NothingRecorder {
             stack: ExpUnrolledLinkedList::with_addr(addr),
             current_doc: u32::max_value(),
             doc_freq: 0u32,
+            total_term_freq: 0u64,
         }
     }
 }
@@ -62,17 +83,23 @@ impl Recorder for NothingRecorder {
         self.doc_freq += 1;
     }
 
-    fn record_position(&mut self, _position: u32, _heap: &Heap) {}
+    fn record_position(&mut self, _position: u32, _payload: u32, _heap: &Heap) {
+        self.total_term_freq += 1;
+    }
 
     fn close_doc(&mut self, _heap: &Heap) {}
 
     fn doc_freq(&self,) -> u32 {
         self.doc_freq
     }
+
+    fn total_term_freq(&self,) -> u64 {
+        self.total_term_freq
+    }
     
-    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> io::Result<()> {
+    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError> {
         for doc in self.stack.iter(self_addr, heap) {
-            try!(serializer.write_doc(doc, 0u32, &EMPTY_ARRAY));
+            try!(serializer.write_doc(doc, 0u32, &EMPTY_ARRAY, None));
         }
         Ok(())
     }
@@ -86,6 +113,7 @@ pub struct TermFrequencyRecorder {
     current_doc: DocId,
     current_tf: u32,
     doc_freq: u32,
+    total_term_freq: u64,
 }
 
 impl HeapAllocable for TermFrequencyRecorder {
@@ -94,7 +122,8 @@ impl HeapAllocable for TermFrequencyRecorder {
             stack: ExpUnrolledLinkedList::with_addr(addr),
             current_doc: u32::max_value(),
             current_tf: 0u32,
-            doc_freq: 0u32
+            doc_freq: 0u32,
+            total_term_freq: 0u64,
         }    
     }
 }
@@ -113,8 +142,9 @@ impl Recorder for TermFrequencyRecorder {
         self.stack.push(doc, heap);
     }
     
-    fn record_position(&mut self, _position: u32, _heap: &Heap) {
+    fn record_position(&mut self, _position: u32, _payload: u32, _heap: &Heap) {
         self.current_tf += 1;
+        self.total_term_freq += 1;
     }
     
     fn close_doc(&mut self, heap: &Heap) {
@@ -126,13 +156,17 @@ impl Recorder for TermFrequencyRecorder {
     fn doc_freq(&self,) -> u32 {
         self.doc_freq
     }
+
+    fn total_term_freq(&self,) -> u64 {
+        self.total_term_freq
+    }
     
-    fn serialize(&self, self_addr:u32, serializer: &mut PostingsSerializer, heap: &Heap) -> io::Result<()> {
+    fn serialize(&self, self_addr:u32, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError> {
         let mut doc_iter = self.stack.iter(self_addr, heap);
         loop {
             if let Some(doc) = doc_iter.next() {
                 if let Some(term_freq) = doc_iter.next() {
-                    try!(serializer.write_doc(doc, term_freq, &EMPTY_ARRAY));
+                    try!(serializer.write_doc(doc, term_freq, &EMPTY_ARRAY, None));
                     continue;
                 }
             }
@@ -149,6 +183,7 @@ pub struct TFAndPositionRecorder {
     stack: ExpUnrolledLinkedList,
     current_doc: DocId,
     doc_freq: u32,
+    total_term_freq: u64,
 }
 
 impl HeapAllocable for TFAndPositionRecorder {
@@ -157,6 +192,7 @@ impl HeapAllocable for TFAndPositionRecorder {
             stack: ExpUnrolledLinkedList::with_addr(addr),
             current_doc: u32::max_value(),
             doc_freq: 0u32,
+            total_term_freq: 0u64,
         }
     }
 }
@@ -174,7 +210,8 @@ impl Recorder for TFAndPositionRecorder {
         self.stack.push(doc, heap);
     }
 
-    fn record_position(&mut self, position: u32, heap: &Heap) {
+    fn record_position(&mut self, position: u32, _payload: u32, heap: &Heap) {
+        self.total_term_freq += 1;
         self.stack.push(position, heap);
     }
     
@@ -185,8 +222,12 @@ impl Recorder for TFAndPositionRecorder {
     fn doc_freq(&self,) -> u32 {
         self.doc_freq
     }
+
+    fn total_term_freq(&self,) -> u64 {
+        self.total_term_freq
+    }
     
-    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> io::Result<()> {
+    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError> {
         let mut doc_positions = Vec::with_capacity(100);
         let mut positions_iter = self.stack.iter(self_addr, heap);
         while let Some(doc) = positions_iter.next() {
@@ -199,7 +240,7 @@ impl Recorder for TFAndPositionRecorder {
                             break;
                         }
                         else {
-                            doc_positions.push(position - prev_position);
+                            doc_positions.push(position_delta(prev_position, position));
                             prev_position = position;
                         }
                     }
@@ -208,7 +249,91 @@ impl Recorder for TFAndPositionRecorder {
                     }
                 }
             }
-            try!(serializer.write_doc(doc, doc_positions.len() as u32, &doc_positions));
+            try!(serializer.write_doc(doc, doc_positions.len() as u32, &doc_positions, None));
+        }
+        Ok(())
+    }
+
+}
+
+/// Recorder encoding term frequencies, positions,
+/// and the payloads of the positions.
+#[repr(C, packed)]
+pub struct TFPositionAndPayloadRecorder {
+    stack: ExpUnrolledLinkedList,
+    current_doc: DocId,
+    doc_freq: u32,
+    total_term_freq: u64,
+}
+
+impl HeapAllocable for TFPositionAndPayloadRecorder {
+    fn with_addr(addr: u32) -> TFPositionAndPayloadRecorder {
+        TFPositionAndPayloadRecorder {
+            stack: ExpUnrolledLinkedList::with_addr(addr),
+            current_doc: u32::max_value(),
+            doc_freq: 0u32,
+            total_term_freq: 0u64,
+        }
+    }
+}
+
+impl Recorder for TFPositionAndPayloadRecorder {
+
+    fn current_doc(&self,) -> DocId {
+        self.current_doc
+    }
+
+    fn new_doc(&mut self, doc: DocId, heap: &Heap) {
+        self.doc_freq += 1;
+        self.current_doc = doc;
+        self.stack.push(doc, heap);
+    }
+
+    fn record_position(&mut self, position: u32, payload: u32, heap: &Heap) {
+        self.total_term_freq += 1;
+        self.stack.push(position, heap);
+        self.stack.push(payload, heap);
+    }
+
+    fn close_doc(&mut self, heap: &Heap) {
+        self.stack.push(POSITION_END, heap);
+    }
+
+    fn doc_freq(&self,) -> u32 {
+        self.doc_freq
+    }
+
+    fn total_term_freq(&self,) -> u64 {
+        self.total_term_freq
+    }
+
+    fn serialize(&self, self_addr: u32, serializer: &mut PostingsSerializer, heap: &Heap) -> Result<(), SerializerError> {
+        let mut doc_positions = Vec::with_capacity(100);
+        let mut doc_payloads = Vec::with_capacity(100);
+        let mut positions_iter = self.stack.iter(self_addr, heap);
+        while let Some(doc) = positions_iter.next() {
+            let mut prev_position = 0;
+            doc_positions.clear();
+            doc_payloads.clear();
+            loop {
+                match positions_iter.next() {
+                    Some(POSITION_END) => {
+                        break;
+                    }
+                    Some(position) => {
+                        // a payload always follows its position, so that
+                        // it cannot be mistaken for the end of the document.
+                        let payload = positions_iter.next().expect("This should never happen. Pleasee report the bug.");
+                        doc_positions.push(position_delta(prev_position, position));
+                        doc_payloads.push(payload);
+                        prev_position = position;
+                    }
+                    None => {
+                        panic!("This should never happen. Pleasee report the bug.");
+                    }
+                }
+            }
+            try!(serializer.write_doc(doc, doc_positions.len() as u32, &doc_positions, Some(&doc_payloads)));
         }
         Ok(())
     }
@@ -1,7 +1,11 @@
-use compression::{NUM_DOCS_PER_BLOCK, SIMDBlockDecoder};
+use compression::{NUM_DOCS_PER_BLOCK, BlockDecompressor};
 use DocId;
-use postings::{Postings, FreqHandler, DocSet, HasLen};
+use postings::{Postings, FreqHandler, DocSet, HasLen, SkipResult};
+use postings::{PostingsCheckpoint, SKIP_LIST_THRESHOLD};
+use datastruct::SkipList;
+use common::BinarySerializable;
 use std::num::Wrapping;
+use std::cmp;
 
 
 
@@ -11,28 +15,55 @@ use std::num::Wrapping;
 /// 
 /// As we iterate through the `SegmentPostings`, the frequencies are optionally decoded.
 /// Positions on the other hand, are optionally entirely decoded upfront.
+///
+/// If the postings have a skip list, `skip_next` uses it to jump
+/// directly to the block that may contain its target.
+///
+/// Each block may also start with the maximum term frequency of its
+/// documents, exposed along with the last document of the block by `block_max`.
+///
+/// For a full scan of the postings, `read_block` hands out the documents
+/// of each decoded block as a whole rather than one at a time.
 pub struct SegmentPostings<'a> {
     len: usize,
     doc_offset: u32,
-    block_decoder: SIMDBlockDecoder,
-    freq_handler: FreqHandler,
+    block_decoder: BlockDecompressor,
+    freq_handler: FreqHandler<'a>,
+    data: &'a [u8],
     remaining_data: &'a [u8],
+    skip_list: Option<SkipList<'a, PostingsCheckpoint>>,
+    has_block_max_term_freqs: bool,
+    block_max_term_freq: u32,
     cur: Wrapping<usize>,
 }
 
 impl<'a> SegmentPostings<'a> {
     
-    fn load_next_block(&mut self,) {
+    /// Loads the block starting at the current document.
+    ///
+    /// If all of the documents of a complete block are before `target`,
+    /// its frequencies are skipped rather than decoded, and false is returned.
+    fn load_next_block(&mut self, target: DocId) -> bool {
+        if self.has_block_max_term_freqs {
+            let mut block_data = self.remaining_data;
+            self.block_max_term_freq = u32::deserialize(&mut block_data).unwrap();
+            self.remaining_data = block_data;
+        }
         let num_remaining_docs = self.len - self.cur.0;
         if num_remaining_docs >= NUM_DOCS_PER_BLOCK {
-            self.remaining_data = self.block_decoder.uncompress_block_sorted(self.remaining_data, self.doc_offset);
-            self.remaining_data = self.freq_handler.read_freq_block(self.remaining_data);
+            let freq_data = self.block_decoder.uncompress_block_sorted(self.remaining_data, self.doc_offset);
             self.doc_offset = self.block_decoder.output(NUM_DOCS_PER_BLOCK - 1);
+            if self.doc_offset < target {
+                self.remaining_data = self.freq_handler.skip_freq_block(freq_data);
+                return false;
+            }
+            self.remaining_data = self.freq_handler.read_freq_block(freq_data);
         }
         else {
             self.remaining_data = self.block_decoder.uncompress_vint_sorted(self.remaining_data, self.doc_offset, num_remaining_docs);
             self.freq_handler.read_freq_vint(self.remaining_data, num_remaining_docs);
         }
+        true
     }
 
     /// Reads a Segment postings from an &[u8]
@@ -41,16 +72,63 @@ impl<'a> SegmentPostings<'a> {
     /// * `data` - data array. The complete data is not necessarily used.
     /// * `freq_handler` - the freq handler is in charge of decoding 
     ///   frequencies and/or positions
-    pub fn from_data(len: u32, data: &'a [u8], freq_handler: FreqHandler) -> SegmentPostings<'a> {
+    pub fn from_data(len: u32, data: &'a [u8], freq_handler: FreqHandler<'a>) -> SegmentPostings<'a> {
         SegmentPostings {
             len: len as usize,
             doc_offset: 0,
-            block_decoder: SIMDBlockDecoder::new(),
+            block_decoder: BlockDecompressor::new(),
             freq_handler: freq_handler,
+            data: data,
             remaining_data: data,
+            skip_list: None,
+            has_block_max_term_freqs: false,
+            block_max_term_freq: 0,
             cur: Wrapping(usize::max_value()),
         }
     }
+
+    /// Reads a Segment postings from an &[u8], as written by the `PostingsSerializer`
+    /// in the given version of the format (see `POSTINGS_FORMAT_VERSION`).
+    ///
+    /// Unlike `from_data`, the postings of more than `SKIP_LIST_THRESHOLD`
+    /// documents are expected to start with their skip list, and,
+    /// from the version 3, the blocks with their maximum term frequency.
+    pub fn from_data_with_format(len: u32, data: &'a [u8], freq_handler: FreqHandler<'a>, postings_format_version: u32) -> SegmentPostings<'a> {
+        let mut segment_postings = if len <= SKIP_LIST_THRESHOLD {
+            SegmentPostings::from_data(len, data, freq_handler)
+        }
+        else {
+            let mut cursor = data;
+            let skip_list_len = u32::deserialize(&mut cursor).unwrap() as usize;
+            let (skip_list_data, postings_data) = cursor.split_at(skip_list_len);
+            let mut segment_postings = SegmentPostings::from_data(len, postings_data, freq_handler);
+            segment_postings.skip_list = Some(SkipList::from(skip_list_data));
+            segment_postings
+        };
+        segment_postings.has_block_max_term_freqs = postings_format_version >= 3;
+        segment_postings
+    }
+
+    /// Moves to the block following the checkpoint of the skip list
+    /// preceding `target`, if it comes after the block starting at
+    /// `next_block_start`.
+    ///
+    /// Returns the start of the block to load next.
+    fn jump_to_checkpoint(&mut self, target: DocId, next_block_start: usize) -> usize {
+        let checkpoint = match self.skip_list {
+            Some(ref mut skip_list) => skip_list.seek(target),
+            None => None,
+        };
+        match checkpoint {
+            Some((last_doc, checkpoint)) if checkpoint.num_docs as usize > next_block_start => {
+                self.doc_offset = last_doc;
+                self.remaining_data = &self.data[checkpoint.postings_offset as usize..];
+                self.freq_handler.seek_positions(checkpoint.positions_offset as usize);
+                checkpoint.num_docs as usize
+            }
+            _ => next_block_start,
+        }
+    }
     
     /// Index within a block is used as an address when
     /// interacting with the `FreqHandler` 
@@ -58,6 +136,65 @@ impl<'a> SegmentPostings<'a> {
         self.cur.0 % NUM_DOCS_PER_BLOCK
     }
 
+    /// Moves to the last document of the block of the document
+    /// following the current one, loading the block if needed.
+    ///
+    /// Returns the range, within the block, of the documents
+    /// following the current document, or `None` if there is
+    /// no such document.
+    fn next_block_range(&mut self,) -> Option<(usize, usize)> {
+        let next = (self.cur + Wrapping(1)).0;
+        if next >= self.len {
+            return None;
+        }
+        let start = next % NUM_DOCS_PER_BLOCK;
+        if start == 0 {
+            self.load_next_block(0);
+        }
+        let block_start = next - start;
+        let stop = cmp::min(NUM_DOCS_PER_BLOCK, self.len - block_start);
+        self.cur = Wrapping(block_start + stop - 1);
+        Some((start, stop))
+    }
+
+    /// Copies the documents following the current document, up to
+    /// the end of its block, in `output`, and returns their number.
+    ///
+    /// Scanning the postings with `read_block` rather than `advance`
+    /// avoids a call per document. From the start of the postings, each
+    /// call reads a complete block, but the last one, and `0` is returned
+    /// once all of the documents have been read.
+    ///
+    /// The last document read becomes the current document, so that
+    /// `read_block` can be mixed with `advance` and `skip_next`.
+    pub fn read_block(&mut self, output: &mut [DocId; NUM_DOCS_PER_BLOCK]) -> usize {
+        match self.next_block_range() {
+            Some((start, stop)) => {
+                output[..stop - start].copy_from_slice(&self.block_decoder.output_array()[start..stop]);
+                stop - start
+            }
+            None => 0,
+        }
+    }
+
+    /// Same as `read_block`, also copying the term frequencies
+    /// of the documents in `freqs_output`.
+    ///
+    /// The term frequencies are `1` if they are not decoded,
+    /// as for `term_freq`.
+    pub fn read_block_with_freqs(&mut self, output: &mut [DocId; NUM_DOCS_PER_BLOCK], freqs_output: &mut [u32; NUM_DOCS_PER_BLOCK]) -> usize {
+        match self.next_block_range() {
+            Some((start, stop)) => {
+                output[..stop - start].copy_from_slice(&self.block_decoder.output_array()[start..stop]);
+                for (idx, freq) in (start..stop).zip(freqs_output.iter_mut()) {
+                    *freq = self.freq_handler.freq(idx);
+                }
+                stop - start
+            }
+            None => 0,
+        }
+    }
+
 }
 
 
@@ -72,16 +209,62 @@ impl<'a> DocSet for SegmentPostings<'a> {
             return false;
         }
         if self.index_within_block() == 0 {
-            self.load_next_block();
+            self.load_next_block(0);
         }
         true
     }
+
+    /// Skips to the first document greater than or equal to `target`.
+    ///
+    /// The blocks ending before the target are skipped as a whole.
+    /// If the postings have a skip list, they are not decoded at all.
+    /// Otherwise, their document ids still need to be decoded to find
+    /// out where they end, but their frequencies are not.
+    /// The target is then searched within its block by bisection.
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.cur.0 >= self.len {
+            return SkipResult::End;
+        }
+        loop {
+            let index_within_block = self.index_within_block();
+            let block_start = self.cur.0 - index_within_block;
+            let block_len = cmp::min(NUM_DOCS_PER_BLOCK, self.len - block_start);
+            if self.block_decoder.output(block_len - 1) >= target {
+                let block_docs = &self.block_decoder.output_array()[index_within_block..block_len];
+                return match block_docs.binary_search(&target) {
+                    Ok(offset) => {
+                        self.cur += Wrapping(offset);
+                        SkipResult::Reached
+                    }
+                    Err(offset) => {
+                        self.cur += Wrapping(offset);
+                        SkipResult::OverStep
+                    }
+                };
+            }
+            let mut next_block_start = self.jump_to_checkpoint(target, block_start + NUM_DOCS_PER_BLOCK);
+            loop {
+                if next_block_start >= self.len {
+                    self.cur = Wrapping(self.len);
+                    return SkipResult::End;
+                }
+                self.cur = Wrapping(next_block_start);
+                if self.load_next_block(target) {
+                    break;
+                }
+                next_block_start += NUM_DOCS_PER_BLOCK;
+            }
+        }
+    }
     
     #[inline]
     fn doc(&self,) -> DocId {
         self.block_decoder.output(self.index_within_block())
     }
 
+    fn size_hint(&self,) -> usize {
+        self.len
+    }
 }
 
 impl<'a> HasLen for SegmentPostings<'a> {
@@ -98,5 +281,271 @@ impl<'a> Postings for SegmentPostings<'a> {
     fn positions(&self) -> &[u32] {
         self.freq_handler.positions(self.index_within_block())
     }
+
+    fn payloads(&self) -> &[u32] {
+        self.freq_handler.payloads(self.index_within_block())
+    }
+
+    fn block_max(&self,) -> Option<(DocId, u32)> {
+        if !self.has_block_max_term_freqs || self.cur.0 >= self.len {
+            return None;
+        }
+        let block_start = self.cur.0 - self.index_within_block();
+        let block_len = cmp::min(NUM_DOCS_PER_BLOCK, self.len - block_start);
+        Some((self.block_decoder.output(block_len - 1), self.block_max_term_freq))
+    }
 }
 
+
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::*;
+    use compression::BlockCompressor;
+    use compression::tests::generate_array;
+    use datastruct::SkipListBuilder;
+    use postings::{SKIP_LIST_PERIOD, POSTINGS_FORMAT_VERSION};
+    use std::ops::Range;
+
+    /// Encodes postings the way the `PostingsSerializer` does,
+    /// with the term frequencies if they are given, but without
+    /// skip list nor maximum term frequencies, as read by `from_data`.
+    pub fn encode_postings(docs: &[DocId], term_freqs: Option<&[u32]>) -> Vec<u8> {
+        encode_postings_and_skip_list(docs, term_freqs, false).0
+    }
+
+    /// Encodes postings the way the `PostingsSerializer` does, in the
+    /// current format, starting with their skip list if they have more
+    /// than `SKIP_LIST_THRESHOLD` documents.
+    pub fn encode_postings_with_skip_list(docs: &[DocId], term_freqs: Option<&[u32]>) -> Vec<u8> {
+        let (postings, skip_list_builder) = encode_postings_and_skip_list(docs, term_freqs, true);
+        if docs.len() as u32 <= SKIP_LIST_THRESHOLD {
+            return postings;
+        }
+        let mut skip_list = Vec::new();
+        skip_list_builder.write::<Vec<u8>>(&mut skip_list).unwrap();
+        let mut data = Vec::new();
+        (skip_list.len() as u32).serialize(&mut data).unwrap();
+        data.extend_from_slice(&skip_list);
+        data.extend_from_slice(&postings);
+        data
+    }
+
+    fn encode_postings_and_skip_list(docs: &[DocId], term_freqs: Option<&[u32]>, block_max: bool) -> (Vec<u8>, SkipListBuilder<PostingsCheckpoint>) {
+        let mut block_encoder = BlockCompressor::new();
+        let mut skip_list_builder = SkipListBuilder::new(SKIP_LIST_PERIOD);
+        let mut data = Vec::new();
+        let mut offset = 0u32;
+        let write_block_max = |data: &mut Vec<u8>, block_range: Range<usize>| {
+            if block_max {
+                let max_term_freq = term_freqs.map_or(1, |term_freqs| term_freqs[block_range].iter().cloned().max().unwrap());
+                max_term_freq.serialize(data).unwrap();
+            }
+        };
+        let num_full_blocks = docs.len() / NUM_DOCS_PER_BLOCK;
+        for block in 0..num_full_blocks {
+            let block_range = block * NUM_DOCS_PER_BLOCK..(block + 1) * NUM_DOCS_PER_BLOCK;
+            write_block_max(&mut data, block_range.clone());
+            data.extend_from_slice(block_encoder.compress_block_sorted(&docs[block_range.clone()], offset));
+            offset = docs[block_range.end - 1];
+            if let Some(term_freqs) = term_freqs {
+                data.extend_from_slice(block_encoder.compress_block_unsorted(&term_freqs[block_range.clone()]));
+            }
+            let checkpoint = PostingsCheckpoint {
+                num_docs: block_range.end as u32,
+                postings_offset: data.len() as u32,
+                positions_offset: 0,
+            };
+            skip_list_builder.insert(offset, &checkpoint).unwrap();
+        }
+        let vint_start = num_full_blocks * NUM_DOCS_PER_BLOCK;
+        if vint_start < docs.len() {
+            write_block_max(&mut data, vint_start..docs.len());
+            data.extend_from_slice(block_encoder.compress_vint_sorted(&docs[vint_start..], offset));
+            if let Some(term_freqs) = term_freqs {
+                data.extend_from_slice(block_encoder.compress_vint_unsorted(&term_freqs[vint_start..]));
+            }
+        }
+        (data, skip_list_builder)
+    }
+
+    #[test]
+    fn test_segment_postings_skip_next() {
+        let docs = generate_array(1_000, 0.1);
+        let term_freqs: Vec<u32> = (0..docs.len() as u32).map(|i| i % 7 + 1).collect();
+        let data = encode_postings(&docs, Some(&term_freqs));
+        let postings = || SegmentPostings::from_data(docs.len() as u32, &data, FreqHandler::new_with_freq());
+        // each target is reached, or overstepped to the following document.
+        for target in (0..docs[docs.len() - 1] + 2).filter(|target| target % 3 == 0) {
+            let mut segment_postings = postings();
+            assert!(segment_postings.advance());
+            let skip_result = segment_postings.skip_next(target);
+            match docs.binary_search(&target) {
+                Ok(ord) => {
+                    assert_eq!(skip_result, SkipResult::Reached);
+                    assert_eq!(segment_postings.term_freq(), term_freqs[ord]);
+                }
+                Err(ord) if ord < docs.len() => {
+                    assert_eq!(skip_result, SkipResult::OverStep);
+                    assert_eq!(segment_postings.doc(), docs[ord]);
+                    assert_eq!(segment_postings.term_freq(), term_freqs[ord]);
+                }
+                Err(_) => {
+                    assert_eq!(skip_result, SkipResult::End);
+                    assert!(!segment_postings.advance());
+                }
+            }
+        }
+        {
+            // skipping and advancing can be mixed.
+            let mut segment_postings = postings();
+            assert!(segment_postings.advance());
+            let mut ord = 0;
+            while ord + 300 < docs.len() {
+                ord += 300;
+                assert_eq!(segment_postings.skip_next(docs[ord]), SkipResult::Reached);
+                // skipping to the current document, or to a previous one, does not move.
+                assert_eq!(segment_postings.skip_next(docs[ord]), SkipResult::Reached);
+                assert_eq!(segment_postings.skip_next(docs[ord - 1]), SkipResult::OverStep);
+                assert_eq!(segment_postings.doc(), docs[ord]);
+                for i in 1..3 {
+                    assert!(segment_postings.advance());
+                    assert_eq!(segment_postings.doc(), docs[ord + i]);
+                    assert_eq!(segment_postings.term_freq(), term_freqs[ord + i]);
+                }
+                ord += 2;
+            }
+            assert_eq!(segment_postings.skip_next(docs[docs.len() - 1] + 1), SkipResult::End);
+        }
+        {
+            // without term frequencies.
+            let data = encode_postings(&docs, None);
+            let mut segment_postings = SegmentPostings::from_data(docs.len() as u32, &data, FreqHandler::new_without_freq());
+            assert!(segment_postings.advance());
+            assert_eq!(segment_postings.skip_next(docs[500]), SkipResult::Reached);
+            let target = docs[899] + 1;
+            let skip_result = segment_postings.skip_next(target);
+            assert_eq!(segment_postings.doc(), docs[900]);
+            assert_eq!(skip_result == SkipResult::Reached, docs[900] == target);
+            assert!(segment_postings.advance());
+            assert_eq!(segment_postings.doc(), docs[901]);
+        }
+    }
+
+    #[test]
+    fn test_segment_postings_skip_list() {
+        let docs = generate_array(10_000, 0.1);
+        let term_freqs: Vec<u32> = (0..docs.len() as u32).map(|i| i % 7 + 1).collect();
+        let data = encode_postings(&docs, Some(&term_freqs));
+        let data_with_skip_list = encode_postings_with_skip_list(&docs, Some(&term_freqs));
+        assert!(data_with_skip_list.len() > data.len());
+        let mut segment_postings = SegmentPostings::from_data(docs.len() as u32, &data, FreqHandler::new_with_freq());
+        let mut segment_postings_with_skip_list = SegmentPostings::from_data_with_format(docs.len() as u32, &data_with_skip_list, FreqHandler::new_with_freq(), POSTINGS_FORMAT_VERSION);
+        assert!(segment_postings.advance());
+        assert!(segment_postings_with_skip_list.advance());
+        let mut target = 0;
+        while target < docs[docs.len() - 1] + 1_000 {
+            // the targets are sometimes within the current block.
+            target += if target % 3 == 0 { 17 } else { 1_001 };
+            let skip_result = segment_postings.skip_next(target);
+            assert_eq!(segment_postings_with_skip_list.skip_next(target), skip_result);
+            if skip_result == SkipResult::End {
+                break;
+            }
+            assert_eq!(segment_postings_with_skip_list.doc(), segment_postings.doc());
+            assert_eq!(segment_postings_with_skip_list.term_freq(), segment_postings.term_freq());
+            let has_next = segment_postings.advance();
+            assert_eq!(segment_postings_with_skip_list.advance(), has_next);
+            if !has_next {
+                break;
+            }
+            assert_eq!(segment_postings_with_skip_list.doc(), segment_postings.doc());
+        }
+        assert_eq!(segment_postings_with_skip_list.skip_next(docs[docs.len() - 1] + 1), SkipResult::End);
+        {
+            // small postings do not get a skip list,
+            // only the maximum term frequency of each of their blocks.
+            let small_docs = &docs[..SKIP_LIST_THRESHOLD as usize];
+            let num_blocks = SKIP_LIST_THRESHOLD as usize / NUM_DOCS_PER_BLOCK;
+            assert_eq!(encode_postings_with_skip_list(small_docs, None).len(), encode_postings(small_docs, None).len() + 4 * num_blocks);
+        }
+    }
+
+    #[test]
+    fn test_segment_postings_block_max() {
+        let docs: Vec<DocId> = (0..300).map(|i| i * 3).collect();
+        let term_freqs: Vec<u32> = (0..300).map(|i| if i == 200 { 12 } else { i % 5 + 1 }).collect();
+        let data = encode_postings_with_skip_list(&docs, Some(&term_freqs));
+        let mut segment_postings = SegmentPostings::from_data_with_format(docs.len() as u32, &data, FreqHandler::new_with_freq(), POSTINGS_FORMAT_VERSION);
+        assert!(segment_postings.advance());
+        assert_eq!(segment_postings.block_max(), Some((docs[127], 5)));
+        assert_eq!(segment_postings.skip_next(docs[128]), SkipResult::Reached);
+        assert_eq!(segment_postings.block_max(), Some((docs[255], 12)));
+        assert_eq!(segment_postings.skip_next(docs[299]), SkipResult::Reached);
+        assert_eq!(segment_postings.block_max(), Some((docs[299], 5)));
+        assert_eq!(segment_postings.term_freq(), 5);
+        {
+            // the legacy formats do not record it.
+            let legacy_data = encode_postings(&docs, Some(&term_freqs));
+            let mut legacy_postings = SegmentPostings::from_data_with_format(docs.len() as u32, &legacy_data, FreqHandler::new_with_freq(), 2);
+            assert!(legacy_postings.advance());
+            assert_eq!(legacy_postings.block_max(), None);
+        }
+    }
+
+    #[test]
+    fn test_segment_postings_read_block() {
+        let docs = generate_array(1_000, 0.1);
+        let term_freqs: Vec<u32> = (0..docs.len() as u32).map(|i| i % 7 + 1).collect();
+        let data = encode_postings_with_skip_list(&docs, Some(&term_freqs));
+        let postings = || SegmentPostings::from_data_with_format(docs.len() as u32, &data, FreqHandler::new_with_freq(), POSTINGS_FORMAT_VERSION);
+        let mut block = [0u32; NUM_DOCS_PER_BLOCK];
+        let mut freqs_block = [0u32; NUM_DOCS_PER_BLOCK];
+        {
+            let mut segment_postings = postings();
+            let mut read_docs = Vec::new();
+            let mut read_term_freqs = Vec::new();
+            loop {
+                let num_docs = segment_postings.read_block_with_freqs(&mut block, &mut freqs_block);
+                if num_docs == 0 {
+                    break;
+                }
+                // all of the blocks are complete, but the last one.
+                assert!(num_docs == NUM_DOCS_PER_BLOCK || read_docs.len() + num_docs == docs.len());
+                read_docs.extend_from_slice(&block[..num_docs]);
+                read_term_freqs.extend_from_slice(&freqs_block[..num_docs]);
+            }
+            assert_eq!(read_docs, docs);
+            assert_eq!(read_term_freqs, term_freqs);
+            assert_eq!(segment_postings.read_block(&mut block), 0);
+            assert!(!segment_postings.advance());
+        }
+        {
+            // reading blocks can be mixed with advancing and skipping.
+            let mut segment_postings = postings();
+            assert!(segment_postings.advance());
+            assert!(segment_postings.advance());
+            assert_eq!(segment_postings.read_block(&mut block), NUM_DOCS_PER_BLOCK - 2);
+            assert_eq!(&block[..NUM_DOCS_PER_BLOCK - 2], &docs[2..NUM_DOCS_PER_BLOCK]);
+            assert_eq!(segment_postings.doc(), docs[NUM_DOCS_PER_BLOCK - 1]);
+            assert!(segment_postings.advance());
+            assert_eq!(segment_postings.doc(), docs[NUM_DOCS_PER_BLOCK]);
+            assert_eq!(segment_postings.skip_next(docs[900]), SkipResult::Reached);
+            assert_eq!(segment_postings.read_block_with_freqs(&mut block, &mut freqs_block), 1_000 - 901);
+            assert_eq!(&block[..1_000 - 901], &docs[901..]);
+            assert_eq!(&freqs_block[..1_000 - 901], &term_freqs[901..]);
+            assert_eq!(segment_postings.doc(), docs[999]);
+            assert_eq!(segment_postings.term_freq(), term_freqs[999]);
+            assert_eq!(segment_postings.read_block(&mut block), 0);
+        }
+        {
+            // without term frequencies.
+            let data = encode_postings(&docs, None);
+            let mut segment_postings = SegmentPostings::from_data(docs.len() as u32, &data, FreqHandler::new_without_freq());
+            assert_eq!(segment_postings.read_block_with_freqs(&mut block, &mut freqs_block), NUM_DOCS_PER_BLOCK);
+            assert_eq!(&block[..], &docs[..NUM_DOCS_PER_BLOCK]);
+            assert!(freqs_block.iter().all(|&freq| freq == 1));
+        }
+    }
+}
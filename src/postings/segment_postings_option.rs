@@ -6,8 +6,12 @@
 /// avoid this extra cost when the information is not required.
 /// For instance, positions are useful when running phrase queries
 /// but useless in other queries, 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SegmentPostingsOption {
-    /// Only the doc ids are decoded
+    /// Only the doc ids are decoded.
+    ///
+    /// The blocks of term frequencies are skipped
+    /// without being decompressed.
     NoFreq,
     /// DocIds and term frequencies are decoded
     Freq,
@@ -1,27 +1,75 @@
 use Result;
-use datastruct::FstMapBuilder;
+use datastruct::{TermDictionaryBuilder, SegmentTermDictionaryBuilder, TermDictionaryType, TermDictionaryStats};
+use datastruct::SkipListBuilder;
 use super::TermInfo;
+use super::{PostingsCheckpoint, SKIP_LIST_THRESHOLD, SKIP_LIST_PERIOD};
 use schema::Term;
 use schema::Field;
 use schema::FieldEntry;
 use schema::FieldType;
 use schema::Schema;
 use schema::TextIndexingOptions;
-use directory::WritePtr;
-use compression::{NUM_DOCS_PER_BLOCK, SIMDBlockEncoder, CompositeEncoder};
+use directory::{self, WritePtr};
+use compression::{NUM_DOCS_PER_BLOCK, BlockCompressor, CompositeEncoder};
 use DocId;
 use core::Segment;
 use std::io;
 use core::SegmentComponent;
+use core::FieldSpaceUsage;
 use std::io::Write;
 use common::VInt;
 use common::BinarySerializable;
+use std::mem;
+use std::result;
+
+
+/// Error that may happen when pushing postings to the `PostingsSerializer`,
+/// when a `PostingsWriter` breaks the order expected by the serializer.
+///
+/// The serializer checks this order rather than
+/// silently writing a corrupted segment.
+#[derive(Debug)]
+pub enum SerializerError {
+    /// A new term was started while the previous term was not closed.
+    TermNotClosed,
+    /// A term does not come strictly after the previous
+    /// term, in the lexicographical order.
+    TermsOutOfOrder {
+        /// The previous term.
+        prev: Term,
+        /// The term that was pushed after it.
+        next: Term,
+    },
+    /// Within a term, a document does not come strictly
+    /// after the previous document.
+    ///
+    /// A document pushed twice in a row has the same
+    /// `prev` and `next` doc ids.
+    DocsOutOfOrder {
+        /// The term of the documents.
+        term: Term,
+        /// The previous document.
+        prev: DocId,
+        /// The document that was pushed after it.
+        next: DocId,
+    },
+    /// Any kind of IO error that happens when
+    /// writing in the underlying IO device.
+    IOError(io::Error),
+}
+
+impl From<io::Error> for SerializerError {
+    fn from(err: io::Error) -> SerializerError {
+        SerializerError::IOError(err)
+    }
+}
 
 
 /// `PostingsSerializer` is in charge of serializing
 /// postings on disk, in the 
 /// * `.idx` (inverted index)
 /// * `.pos` (positions file)
+/// * `.payload` (payloads file)
 /// * `.term` (term dictionary)
 /// 
 /// `PostingsWriter` are in charge of pushing the data to the 
@@ -44,24 +92,62 @@ use common::BinarySerializable;
 ///
 /// Terms have to be pushed in a lexicographically-sorted order.
 /// Within a term, document have to be pushed in increasing order.
+/// Otherwise, the calls fail with a `SerializerError`.
+///
+/// The positions are written along with each block of documents,
+/// starting with their number and the number of bytes they are
+/// compressed into, so that the positions of the skipped blocks
+/// do not need to be decoded.
+///
+/// For the fields indexed with payloads, the positions of each block
+/// start with the offset of the payloads of the block in the payloads
+/// file, where they are compressed like the positions. The positions
+/// of the other fields are unchanged, so that they do not pay for it.
+///
+/// The postings of the terms contained in more than `SKIP_LIST_THRESHOLD`
+/// documents start with the length of their skip list, followed by the
+/// skip list itself, which records a `PostingsCheckpoint` after each
+/// complete block, so that seeking a document does not require to decode
+/// all of the blocks before it. The postings of a term are therefore
+/// buffered until the term is closed.
+///
+/// Each block of documents, including the final `VInt` encoded one,
+/// starts with the maximum term frequency of its documents (or `1` if
+/// the term frequencies are not indexed), so that scorers can bound
+/// the score of a block without decoding it.
 ///
 /// A description of the serialization format is 
 /// [available here](https://fulmicoton.gitbooks.io/tantivy-doc/content/inverted-index.html). 
 pub struct PostingsSerializer {
-    terms_fst_builder: FstMapBuilder<WritePtr, TermInfo>, // TODO find an alternative to work around the "move"
+    term_dictionary_builder: SegmentTermDictionaryBuilder<WritePtr, TermInfo>,
     postings_write: WritePtr,
     positions_write: WritePtr,
+    payloads_write: WritePtr,
     written_bytes_postings: usize,
     written_bytes_positions: usize,
+    written_bytes_payloads: usize,
     last_doc_id_encoded: u32,
     positions_encoder: CompositeEncoder,
-    block_encoder: SIMDBlockEncoder,
+    payloads_encoder: CompositeEncoder,
+    block_encoder: BlockCompressor,
     doc_ids: Vec<DocId>,
     term_freqs: Vec<u32>,
+    term_doc_freq: DocId,
+    term_num_docs: u32,
+    term_postings: Vec<u8>,
+    term_positions_start: usize,
+    skip_list_builder: SkipListBuilder<PostingsCheckpoint>,
     position_deltas: Vec<u32>,
+    payloads: Vec<u32>,
     schema: Schema,
     text_indexing_options: TextIndexingOptions,
     term_open: bool,
+    term_info: Option<TermInfo>,
+    last_term: Vec<u8>,
+    term_last_doc: Option<DocId>,
+    num_terms_per_field: Vec<u32>,
+    field_space_usage: Vec<FieldSpaceUsage>,
+    current_field: Option<(Field, FieldSpaceUsage)>,
 }
 
 impl PostingsSerializer {
@@ -69,25 +155,43 @@ impl PostingsSerializer {
     /// Open a new `PostingsSerializer` for the given segment  
     pub fn open(segment: &mut Segment) -> Result<PostingsSerializer> {
         let terms_write = try!(segment.open_write(SegmentComponent::TERMS));
-        let terms_fst_builder = try!(FstMapBuilder::new(terms_write));
+        let term_dictionary_type = segment.index().term_dictionary_type();
+        let term_dictionary_builder = try!(SegmentTermDictionaryBuilder::new(term_dictionary_type, terms_write));
         let postings_write = try!(segment.open_write(SegmentComponent::POSTINGS));
         let positions_write = try!(segment.open_write(SegmentComponent::POSITIONS));
+        let payloads_write = try!(segment.open_write(SegmentComponent::PAYLOADS));
         let schema = segment.schema();
+        let num_fields = schema.fields().len();
         Ok(PostingsSerializer {
-            terms_fst_builder: terms_fst_builder,
+            term_dictionary_builder: term_dictionary_builder,
             postings_write: postings_write,
             positions_write: positions_write,
+            payloads_write: payloads_write,
             written_bytes_postings: 0,
             written_bytes_positions: 0,
+            written_bytes_payloads: 0,
             last_doc_id_encoded: 0u32,
             positions_encoder: CompositeEncoder::new(),
-            block_encoder: SIMDBlockEncoder::new(),
+            payloads_encoder: CompositeEncoder::new(),
+            block_encoder: BlockCompressor::new(),
             doc_ids: Vec::new(),
             term_freqs: Vec::new(),
+            term_doc_freq: 0,
+            term_num_docs: 0,
+            term_postings: Vec::new(),
+            term_positions_start: 0,
+            skip_list_builder: SkipListBuilder::new(SKIP_LIST_PERIOD),
             position_deltas: Vec::new(),
+            payloads: Vec::new(),
             schema: schema,
             text_indexing_options: TextIndexingOptions::Unindexed,
             term_open: false,
+            term_info: None,
+            last_term: Vec::new(),
+            term_last_doc: None,
+            num_terms_per_field: vec!(0u32; num_fields),
+            field_space_usage: vec!(FieldSpaceUsage::default(); num_fields),
+            current_field: None,
         })
     }
     
@@ -99,42 +203,172 @@ impl PostingsSerializer {
             }
             FieldType::U32(ref u32_options) => {
                 if u32_options.is_indexed() {
+                    TextIndexingOptions::Untokenized
+                }
+                else {
                     TextIndexingOptions::Unindexed
                 }
+            }
+            FieldType::I64(ref i64_options) => {
+                if i64_options.is_indexed() {
+                    TextIndexingOptions::Untokenized
+                }
                 else {
-                    TextIndexingOptions::Untokenized    
+                    TextIndexingOptions::Unindexed
                 }
             }
+            FieldType::F64(ref f64_options) => {
+                if f64_options.is_indexed() {
+                    TextIndexingOptions::Untokenized
+                }
+                else {
+                    TextIndexingOptions::Unindexed
+                }
+            }
+            FieldType::Date(ref date_options) => {
+                if date_options.is_indexed() {
+                    TextIndexingOptions::Untokenized
+                }
+                else {
+                    TextIndexingOptions::Unindexed
+                }
+            }
+            FieldType::Bytes(_) => {
+                TextIndexingOptions::Unindexed
+            }
+            FieldType::HierarchicalFacet(_) | FieldType::Bool(_) | FieldType::Ip(_) => {
+                TextIndexingOptions::Untokenized
+            }
         };
     }
     
+    /// Returns the number of bytes written so far
+    /// in the term dictionary, the postings and the positions files.
+    fn written_bytes(&self,) -> FieldSpaceUsage {
+        FieldSpaceUsage {
+            terms: self.term_dictionary_builder.num_bytes(),
+            postings: self.written_bytes_postings,
+            positions: self.written_bytes_positions,
+        }
+    }
+
+    /// Records the bytes written since the beginning of
+    /// the current field into its space usage.
+    fn close_field(&mut self,) {
+        if let Some((field, start)) = self.current_field.take() {
+            let end = self.written_bytes();
+            self.field_space_usage[field.0 as usize] = FieldSpaceUsage {
+                terms: end.terms - start.terms,
+                postings: end.postings - start.postings,
+                positions: end.positions - start.positions,
+            };
+        }
+    }
+
+    /// Returns the number of terms of the field
+    /// that have been serialized so far.
+    pub fn num_terms(&self, field: Field) -> u32 {
+        self.num_terms_per_field[field.0 as usize]
+    }
+
+    /// Returns the type of the term dictionary, as
+    /// set for the index of the segment.
+    pub fn term_dictionary_type(&self,) -> TermDictionaryType {
+        self.term_dictionary_builder.term_dictionary_type()
+    }
+
+    /// Returns the statistics of the terms
+    /// that have been closed so far.
+    pub fn term_dictionary_stats(&self,) -> TermDictionaryStats {
+        self.term_dictionary_builder.stats()
+    }
+    
     /// Starts the postings for a new term.
     /// * term - the term. It needs to come after the previous term according
     ///   to the lexicographical order. 
     /// * doc_freq - return the number of document containing the term.
-    pub fn new_term(&mut self, term: &Term, doc_freq: DocId) -> io::Result<()> {
+    /// * total_term_freq - the number of occurrences of the term, if known.
+    ///
+    /// # Errors
+    /// If the previous term was not closed, or if the term
+    /// does not come after the previous term.
+    pub fn new_term(&mut self, term: &Term, doc_freq: DocId, total_term_freq: Option<u64>) -> result::Result<(), SerializerError> {
         if self.term_open {
-            panic!("Called new_term, while the previous term was not closed.");
+            return Err(SerializerError::TermNotClosed);
         }
+        // terms start with their field, so that the empty
+        // initial `last_term` comes before any of them.
+        if term.as_slice() <= &self.last_term[..] {
+            return Err(SerializerError::TermsOutOfOrder {
+                prev: Term::from(&self.last_term[..]),
+                next: term.clone(),
+            });
+        }
+        self.last_term.clear();
+        self.last_term.extend_from_slice(term.as_slice());
         self.term_open = true;
+        self.term_last_doc = None;
+        if self.current_field.map(|(field, _)| field) != Some(term.field()) {
+            self.close_field();
+            self.current_field = Some((term.field(), self.written_bytes()));
+        }
         self.load_indexing_options(term.field());
+        self.num_terms_per_field[term.field().0 as usize] += 1;
         self.doc_ids.clear();
         self.last_doc_id_encoded = 0;
         self.term_freqs.clear();
         self.position_deltas.clear();
-        let term_info = TermInfo {
+        self.payloads.clear();
+        self.term_doc_freq = doc_freq;
+        self.term_num_docs = 0;
+        self.term_postings.clear();
+        self.term_positions_start = self.written_bytes_positions;
+        self.skip_list_builder = SkipListBuilder::new(SKIP_LIST_PERIOD);
+        // the term info is only inserted in the term dictionary
+        // once the term is closed, along with the length of its positions.
+        self.term_info = Some(TermInfo {
             doc_freq: doc_freq,
             postings_offset: self.written_bytes_postings as u32,
             positions_offset: self.written_bytes_positions as u32,
-        };
-        self.terms_fst_builder
-            .insert(term.as_slice(), &term_info)
+            total_term_freq: total_term_freq,
+            positions_len: None,
+        });
+        Ok(())
     }
     
+    /// Writes the maximum term frequency of the current block of documents.
+    fn write_block_max_term_freq(&mut self,) -> io::Result<()> {
+        let block_max_term_freq = self.term_freqs.iter().cloned().max().unwrap_or(1);
+        try!(block_max_term_freq.serialize(&mut self.term_postings));
+        Ok(())
+    }
+
+    /// Writes the positions of the current block of documents,
+    /// and their payloads if the field has payloads.
+    fn write_positions_block(&mut self,) -> io::Result<()> {
+        if self.text_indexing_options.is_payload_enabled() {
+            self.written_bytes_positions += try!(VInt(self.written_bytes_payloads as u64).serialize(&mut self.positions_write));
+            let payloads_encoded: &[u8] = self.payloads_encoder.compress_unsorted(&self.payloads[..]);
+            try!(self.payloads_write.write_all(payloads_encoded));
+            self.written_bytes_payloads += payloads_encoded.len();
+            self.payloads.clear();
+        }
+        let positions_encoded: &[u8] = self.positions_encoder.compress_unsorted(&self.position_deltas[..]);
+        self.written_bytes_positions += try!(VInt(self.position_deltas.len() as u64).serialize(&mut self.positions_write));
+        self.written_bytes_positions += try!(VInt(positions_encoded.len() as u64).serialize(&mut self.positions_write));
+        try!(self.positions_write.write_all(positions_encoded));
+        self.written_bytes_positions += positions_encoded.len();
+        self.position_deltas.clear();
+        Ok(())
+    }
+
     /// Finish the serialization for this term postings.
     ///
     /// If the current block is incomplete, it need to be encoded
     /// using `VInt` encoding.  
+    ///
+    /// The `TermInfo` of the term is then inserted in the term dictionary,
+    /// with the number of bytes taken by its positions.
     pub fn close_term(&mut self,) -> io::Result<()> {
         if self.term_open {
             if !self.doc_ids.is_empty() {
@@ -144,29 +378,39 @@ impl PostingsSerializer {
                 //
                 // In that case, the remaining part is encoded
                 // using variable int encoding.
+                try!(self.write_block_max_term_freq());
                 {
                     let block_encoded = self.block_encoder.compress_vint_sorted(&self.doc_ids, self.last_doc_id_encoded);
-                    self.written_bytes_postings += block_encoded.len();
-                    try!(self.postings_write.write_all(block_encoded));
+                    self.term_postings.extend_from_slice(block_encoded);
                     self.doc_ids.clear();
                 }
                 // ... Idem for term frequencies 
                 if self.text_indexing_options.is_termfreq_enabled() {
                     let block_encoded = self.block_encoder.compress_vint_unsorted(&self.term_freqs[..]);
                     for num in block_encoded {
-                        self.written_bytes_postings += try!(num.serialize(&mut self.postings_write));
+                        try!(num.serialize(&mut self.term_postings));
                     }
                     self.term_freqs.clear();
                 }
+                // ... and for positions.
+                if self.text_indexing_options.is_position_enabled() {
+                    try!(self.write_positions_block());
+                }
             }
-            // On the other hand, positions are entirely buffered until the
-            // end of the term, at which point they are compressed and written.
-            if self.text_indexing_options.is_position_enabled() {
-                self.written_bytes_positions += try!(VInt(self.position_deltas.len() as u64).serialize(&mut self.positions_write));
-                let positions_encoded: &[u8] = self.positions_encoder.compress_unsorted(&self.position_deltas[..]);
-                try!(self.positions_write.write_all(positions_encoded));
-                self.written_bytes_positions += positions_encoded.len();
-                self.position_deltas.clear();
+            if self.term_doc_freq > SKIP_LIST_THRESHOLD {
+                let skip_list_builder = mem::replace(&mut self.skip_list_builder, SkipListBuilder::new(SKIP_LIST_PERIOD));
+                let mut skip_list: Vec<u8> = Vec::new();
+                try!(skip_list_builder.write::<Vec<u8>>(&mut skip_list));
+                self.written_bytes_postings += try!((skip_list.len() as u32).serialize(&mut self.postings_write));
+                try!(self.postings_write.write_all(&skip_list));
+                self.written_bytes_postings += skip_list.len();
+            }
+            try!(self.postings_write.write_all(&self.term_postings));
+            self.written_bytes_postings += self.term_postings.len();
+            self.term_postings.clear();
+            if let Some(mut term_info) = self.term_info.take() {
+                term_info.positions_len = Some((self.written_bytes_positions - self.term_positions_start) as u32);
+                try!(self.term_dictionary_builder.insert(&self.last_term, &term_info));
             }
             self.term_open = false;
         }
@@ -181,9 +425,33 @@ impl PostingsSerializer {
     /// For instance, if the positions are `2, 3, 17`,
     /// `position_deltas` is `2, 1, 14`
     ///
-    /// Term frequencies and positions may be ignored by the serializer depending
+    /// A delta may be 0 when a term appears several times
+    /// at the same position.
+    ///
+    /// `payloads`, if given, holds the payload of each of the positions.
+    /// The positions without payloads get a payload of 0.
+    ///
+    /// Term frequencies, positions and payloads may be ignored by the serializer depending
     /// on the configuration of the field in the `Schema`.
-    pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) -> io::Result<()> {
+    ///
+    /// # Errors
+    /// If the document does not come after the previous document of the term.
+    ///
+    /// # Panics
+    /// If the payloads do not have the length of the positions.
+    pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32], payloads: Option<&[u32]>) -> result::Result<(), SerializerError> {
+        // the delta encoding of the doc ids would otherwise
+        // silently write garbage.
+        if let Some(prev_doc_id) = self.term_last_doc {
+            if doc_id <= prev_doc_id {
+                return Err(SerializerError::DocsOutOfOrder {
+                    term: Term::from(&self.last_term[..]),
+                    prev: prev_doc_id,
+                    next: doc_id,
+                });
+            }
+        }
+        self.term_last_doc = Some(doc_id);
         self.doc_ids.push(doc_id);
         if self.text_indexing_options.is_termfreq_enabled() {
             self.term_freqs.push(term_freq as u32);
@@ -191,32 +459,60 @@ impl PostingsSerializer {
         if self.text_indexing_options.is_position_enabled() {
             self.position_deltas.extend_from_slice(position_deltas);
         }
+        if self.text_indexing_options.is_payload_enabled() {
+            match payloads {
+                Some(payloads) => {
+                    assert_eq!(payloads.len(), position_deltas.len(), "Each position requires one payload");
+                    self.payloads.extend_from_slice(payloads);
+                }
+                None => {
+                    let num_payloads = self.payloads.len() + position_deltas.len();
+                    self.payloads.resize(num_payloads, 0u32);
+                }
+            }
+        }
         if self.doc_ids.len() == NUM_DOCS_PER_BLOCK {
+            try!(self.write_block_max_term_freq());
             {
                 // encode the doc ids
                 let block_encoded: &[u8] = self.block_encoder.compress_block_sorted(&self.doc_ids, self.last_doc_id_encoded);
                 self.last_doc_id_encoded = self.doc_ids[self.doc_ids.len() - 1];
-                try!(self.postings_write.write_all(block_encoded));
-                self.written_bytes_postings += block_encoded.len();
+                self.term_postings.extend_from_slice(block_encoded);
             }
             if self.text_indexing_options.is_termfreq_enabled() {
                 // encode the term_freqs
                 let block_encoded: &[u8] = self.block_encoder.compress_block_unsorted(&self.term_freqs);
-                try!(self.postings_write.write_all(block_encoded));
-                self.written_bytes_postings += block_encoded.len();
+                self.term_postings.extend_from_slice(block_encoded);
                 self.term_freqs.clear();
             }
+            if self.text_indexing_options.is_position_enabled() {
+                try!(self.write_positions_block());
+            }
             self.doc_ids.clear();
+            self.term_num_docs += NUM_DOCS_PER_BLOCK as u32;
+            if self.term_doc_freq > SKIP_LIST_THRESHOLD {
+                let checkpoint = PostingsCheckpoint {
+                    num_docs: self.term_num_docs,
+                    postings_offset: self.term_postings.len() as u32,
+                    positions_offset: (self.written_bytes_positions - self.term_positions_start) as u32,
+                };
+                try!(self.skip_list_builder.insert(self.last_doc_id_encoded, &checkpoint));
+            }
         }
         Ok(())
     }
     
     /// Closes the serializer.
-    pub fn close(mut self,) -> io::Result<()> {
+    ///
+    /// Returns the number of bytes taken by each field of the schema.
+    pub fn close(mut self,) -> io::Result<Vec<FieldSpaceUsage>> {
         try!(self.close_term());
-        try!(self.terms_fst_builder.finish());
-        try!(self.postings_write.flush());
-        try!(self.positions_write.flush());
-        Ok(())
+        self.close_field();
+        let mut terms_write = try!(self.term_dictionary_builder.finish());
+        try!(directory::terminate(&mut terms_write));
+        try!(directory::terminate(&mut self.postings_write));
+        try!(directory::terminate(&mut self.positions_write));
+        try!(directory::terminate(&mut self.payloads_write));
+        Ok(self.field_space_usage)
     }
 }
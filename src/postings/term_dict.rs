@@ -0,0 +1,224 @@
+#![allow(should_implement_trait)]
+
+use datastruct::{TermDictionary, TermDictionaryKeys, TermDictionaryRange};
+use datastruct::{SegmentTermDictionary, SegmentTermRange};
+use postings::TermInfo;
+use schema::Term;
+use fst::Automaton;
+
+
+/// The term dictionary of a segment, associating
+/// each of its terms to its `TermInfo`.
+///
+/// The keys of the dictionary are the bytes of the terms, as in
+/// `Term::as_slice`. As they start with the field, the terms of
+/// a field are contiguous, and sorted by their value.
+///
+/// ```ignore
+/// let term_dict = segment_reader.term_dict();
+/// let mut terms = term_dict
+///     .range()
+///     .ge(Term::from_field_text(field, "a").as_slice())
+///     .lt(Term::from_field_text(field, "b").as_slice())
+///     .into_stream();
+/// while let Some((term_bytes, term_info)) = terms.next() {
+///     // ...
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct TermDict<'a> {
+    term_infos: &'a SegmentTermDictionary<TermInfo>,
+}
+
+impl<'a> TermDict<'a> {
+
+    /// Wraps the term infos of a segment.
+    pub fn new(term_infos: &'a SegmentTermDictionary<TermInfo>) -> TermDict<'a> {
+        TermDict {
+            term_infos: term_infos,
+        }
+    }
+
+    /// Returns the `TermInfo` of the term, if it is in the dictionary.
+    pub fn get(&self, term: &Term) -> Option<TermInfo> {
+        self.term_infos.get(term.as_slice())
+    }
+
+    /// Returns a stream over all of the terms of the dictionary.
+    pub fn stream(&self,) -> TermDictStreamer<'a> {
+        TermDictStreamer {
+            keys: Box::new(self.term_infos.keys()),
+        }
+    }
+
+    /// Returns a range containing all of the terms of the dictionary,
+    /// to be restricted with its bounds.
+    pub fn range(&self,) -> TermDictRange<'a> {
+        TermDictRange {
+            range: self.term_infos.range(),
+        }
+    }
+
+    /// Returns a stream over the terms accepted by `automaton`.
+    ///
+    /// The automaton runs over the bytes of the terms,
+    /// including their field.
+    pub fn search<A: Automaton + 'a>(&self, automaton: A) -> TermDictStreamer<'a> {
+        TermDictStreamer {
+            keys: self.term_infos.search(automaton),
+        }
+    }
+}
+
+
+/// Range of the terms of a `TermDict`.
+///
+/// The bounds are given as the bytes of the terms, as in `Term::as_slice`.
+pub struct TermDictRange<'a> {
+    range: SegmentTermRange<'a, TermInfo>,
+}
+
+impl<'a> TermDictRange<'a> {
+
+    /// Restricts the range to the terms greater or equal to `bound`.
+    pub fn ge<T: AsRef<[u8]>>(self, bound: T) -> TermDictRange<'a> {
+        TermDictRange {
+            range: self.range.ge(bound),
+        }
+    }
+
+    /// Restricts the range to the terms strictly greater than `bound`.
+    pub fn gt<T: AsRef<[u8]>>(self, bound: T) -> TermDictRange<'a> {
+        TermDictRange {
+            range: self.range.gt(bound),
+        }
+    }
+
+    /// Restricts the range to the terms lower or equal to `bound`.
+    pub fn le<T: AsRef<[u8]>>(self, bound: T) -> TermDictRange<'a> {
+        TermDictRange {
+            range: self.range.le(bound),
+        }
+    }
+
+    /// Restricts the range to the terms strictly lower than `bound`.
+    pub fn lt<T: AsRef<[u8]>>(self, bound: T) -> TermDictRange<'a> {
+        TermDictRange {
+            range: self.range.lt(bound),
+        }
+    }
+
+    /// Returns a stream over the terms of the range.
+    pub fn into_stream(self,) -> TermDictStreamer<'a> {
+        TermDictStreamer {
+            keys: Box::new(self.range.into_stream()),
+        }
+    }
+}
+
+
+/// Stream over terms of a `TermDict`, in lexicographical order.
+///
+/// The keys of the term dictionary are boxed, as their type
+/// depends on the automaton of `TermDict::search`.
+pub struct TermDictStreamer<'a> {
+    keys: Box<TermDictionaryKeys<TermInfo> + 'a>,
+}
+
+impl<'a> TermDictStreamer<'a> {
+
+    /// Returns the bytes of the next term, as in `Term::as_slice`,
+    /// along with its `TermInfo`.
+    ///
+    /// The bytes are borrowed from the stream rather than copied,
+    /// so that they only live until the next call.
+    pub fn next(&mut self) -> Option<(&[u8], TermInfo)> {
+        self.keys.next_with_value()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Field, TEXT};
+    use datastruct::TermDictionaryType;
+    use fst::automaton::AlwaysMatch;
+
+    fn collect_texts(mut terms: TermDictStreamer) -> Vec<String> {
+        let mut texts = Vec::new();
+        while let Some((term_bytes, _)) = terms.next() {
+            texts.push(String::from_utf8(Term::from(term_bytes).value_bytes().to_vec()).unwrap());
+        }
+        texts
+    }
+
+    fn create_index(term_dictionary_type: TermDictionaryType) -> (Index, Field, Field) {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let mut index = Index::create_in_ram(schema_builder.build().unwrap());
+        index.set_term_dictionary_type(term_dictionary_type);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(title, body) in &[("apple banana", "cherry"), ("banana date", "apple")] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        (index, title_field, body_field)
+    }
+
+    fn test_term_dict_range_aux(term_dictionary_type: TermDictionaryType) {
+        let (index, title_field, body_field) = create_index(term_dictionary_type);
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_reader(0).term_dictionary_type(), term_dictionary_type);
+        let term_dict = searcher.segment_reader(0).term_dict();
+        let term = |text: &str| Term::from_field_text(title_field, text);
+        assert_eq!(collect_texts(term_dict.range().ge(term("apple").as_slice()).lt(term("date").as_slice()).into_stream()), vec!("apple", "banana"));
+        assert_eq!(collect_texts(term_dict.range().gt(term("apple").as_slice()).le(term("date").as_slice()).into_stream()), vec!("banana", "date"));
+        {
+            // the terms of the other fields are out of the range.
+            let body_start = Term::from_field_text(body_field, "");
+            assert_eq!(collect_texts(term_dict.range().gt(term("banana").as_slice()).lt(body_start.as_slice()).into_stream()), vec!("date"));
+        }
+        // a single term.
+        assert_eq!(collect_texts(term_dict.range().ge(term("banana").as_slice()).le(term("banana").as_slice()).into_stream()), vec!("banana"));
+        // empty ranges.
+        assert!(collect_texts(term_dict.range().gt(term("apple").as_slice()).lt(term("banana").as_slice()).into_stream()).is_empty());
+        assert!(collect_texts(term_dict.range().ge(term("banana").as_slice()).lt(term("banana").as_slice()).into_stream()).is_empty());
+        assert!(collect_texts(term_dict.range().ge(term("date").as_slice()).le(term("apple").as_slice()).into_stream()).is_empty());
+        {
+            // the term info is the one of the exact lookup.
+            let mut terms = term_dict.range().ge(term("banana").as_slice()).into_stream();
+            let (term_bytes, term_info) = terms.next().unwrap();
+            assert_eq!(term_bytes, term("banana").as_slice());
+            assert_eq!(term_info.doc_freq, 2);
+            assert_eq!(Some(term_info), term_dict.get(&term("banana")));
+        }
+    }
+
+    #[test]
+    fn test_term_dict_range() {
+        test_term_dict_range_aux(TermDictionaryType::Fst);
+        test_term_dict_range_aux(TermDictionaryType::SSTable);
+    }
+
+    #[test]
+    fn test_term_dict_search() {
+        for &term_dictionary_type in &[TermDictionaryType::Fst, TermDictionaryType::SSTable] {
+            let (index, _, _) = create_index(term_dictionary_type);
+            let searcher = index.searcher();
+            let term_dict = searcher.segment_reader(0).term_dict();
+            let all_texts = vec!("apple", "banana", "date", "apple", "cherry");
+            assert_eq!(collect_texts(term_dict.search(AlwaysMatch)), all_texts);
+            assert_eq!(collect_texts(term_dict.stream()), all_texts);
+            assert_eq!(collect_texts(term_dict.range().into_stream()), all_texts);
+        }
+    }
+}
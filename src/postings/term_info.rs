@@ -1,5 +1,6 @@
 use common::BinarySerializable;
 use std::io;
+use std::ops::Range;
 
 
 /// `TermInfo` contains all of the information 
@@ -12,6 +13,12 @@ use std::io;
 /// * `postings_offset` : an offset in the `.idx` file 
 /// addressing the start of the posting list associated
 /// to this term.
+/// * `positions_offset` : an offset in the `.pos` file
+/// addressing the start of the positions of this term.
+/// * `total_term_freq` : the number of occurrences of the
+/// term in the segment.
+/// * `positions_len` : the number of bytes taken by the
+/// positions of this term in the `.pos` file.
 #[derive(Debug,Ord,PartialOrd,Eq,PartialEq,Clone)]
 pub struct TermInfo {
     /// Number of documents in the segment containing the term
@@ -20,6 +27,58 @@ pub struct TermInfo {
     pub postings_offset: u32,
     /// Offset within the position (`.pos`) file.
     pub positions_offset: u32,
+    /// Sum of the term frequencies of the term over the documents
+    /// of the segment.
+    ///
+    /// It is `None` for the segments written before it was recorded,
+    /// in the version 4 of the postings format.
+    pub total_term_freq: Option<u64>,
+    /// Number of bytes taken by the positions of the term
+    /// within the position (`.pos`) file, 0 if the positions
+    /// of the field are not indexed.
+    ///
+    /// Along with `positions_offset`, it delimits the positions
+    /// of the term, so that they can be read without relying on the next term.
+    /// It is `None` for the segments written before it was recorded,
+    /// in the version 5 of the postings format.
+    pub positions_len: Option<u32>,
+}
+
+impl TermInfo {
+    /// Reads a `TermInfo` written before the version 4
+    /// of the postings format, without its total term frequency.
+    pub fn deserialize_legacy(reader: &mut io::Read) -> io::Result<TermInfo> {
+        let doc_freq = try!(u32::deserialize(reader));
+        let postings_offset = try!(u32::deserialize(reader));
+        let positions_offset = try!(u32::deserialize(reader));
+        Ok(TermInfo {
+            doc_freq: doc_freq,
+            postings_offset: postings_offset,
+            positions_offset: positions_offset,
+            total_term_freq: None,
+            positions_len: None,
+        })
+    }
+
+    /// Reads a `TermInfo` written in the version 4 of the
+    /// postings format, without the length of its positions.
+    pub fn deserialize_v4(reader: &mut io::Read) -> io::Result<TermInfo> {
+        let mut term_info = try!(TermInfo::deserialize_legacy(reader));
+        let total_term_freq = try!(u64::deserialize(reader));
+        if total_term_freq > 0 {
+            term_info.total_term_freq = Some(total_term_freq);
+        }
+        Ok(term_info)
+    }
+
+    /// Returns the range of the positions of the term
+    /// within the position (`.pos`) file, if their length is known.
+    pub fn positions_range(&self,) -> Option<Range<usize>> {
+        self.positions_len.map(|positions_len| {
+            let start = self.positions_offset as usize;
+            start..start + positions_len as usize
+        })
+    }
 }
 
 
@@ -28,17 +87,52 @@ impl BinarySerializable for TermInfo {
         Ok(
             try!(self.doc_freq.serialize(writer)) +
             try!(self.postings_offset.serialize(writer)) +
-            try!(self.positions_offset.serialize(writer))
+            try!(self.positions_offset.serialize(writer)) +
+            // a term occurs at least once, so that 0 marks a missing value.
+            try!(self.total_term_freq.unwrap_or(0u64).serialize(writer)) +
+            try!(self.positions_len.unwrap_or(0u32).serialize(writer))
         )
     }
     fn deserialize(reader: &mut io::Read) -> io::Result<Self> {
-        let doc_freq = try!(u32::deserialize(reader));
-        let postings_offset = try!(u32::deserialize(reader));
-        let positions_offset = try!(u32::deserialize(reader));
-        Ok(TermInfo {
-            doc_freq: doc_freq,
-            postings_offset: postings_offset,
-            positions_offset: positions_offset,
-        })
+        let mut term_info = try!(TermInfo::deserialize_v4(reader));
+        term_info.positions_len = Some(try!(u32::deserialize(reader)));
+        Ok(term_info)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use common::BinarySerializable;
+
+    #[test]
+    fn test_term_info_serialization() {
+        let term_info = TermInfo {
+            doc_freq: 3,
+            postings_offset: 17,
+            positions_offset: 45,
+            total_term_freq: Some(7),
+            positions_len: Some(11),
+        };
+        let mut data: Vec<u8> = Vec::new();
+        assert_eq!(term_info.serialize(&mut data).unwrap(), 24);
+        assert_eq!(TermInfo::deserialize(&mut &data[..]).unwrap(), term_info);
+        assert_eq!(term_info.positions_range(), Some(45..56));
+        {
+            // the version 4 stops before the length of the positions.
+            let term_info_v4 = TermInfo::deserialize_v4(&mut &data[..20]).unwrap();
+            assert_eq!(term_info_v4.total_term_freq, Some(7));
+            assert_eq!(term_info_v4.positions_len, None);
+            assert_eq!(term_info_v4.positions_range(), None);
+        }
+        {
+            // the legacy format stops before the total term frequency.
+            let legacy_term_info = TermInfo::deserialize_legacy(&mut &data[..12]).unwrap();
+            assert_eq!(legacy_term_info.doc_freq, 3);
+            assert_eq!(legacy_term_info.positions_offset, 45);
+            assert_eq!(legacy_term_info.total_term_freq, None);
+        }
     }
 }
@@ -0,0 +1,110 @@
+#![allow(should_implement_trait)]
+
+use datastruct::{TermDictionaryKeys, SegmentTermKeys};
+use postings::TermInfo;
+use schema::Field;
+
+
+/// Stream over the terms of a field of a segment, along
+/// with their `TermInfo`, in lexicographical order.
+///
+/// The terms are read straight out of the term dictionary,
+/// one at a time, so that streaming a field does not load
+/// all of its terms in memory.
+///
+/// ```ignore
+/// let mut terms = segment_reader.terms(field);
+/// while let Some((term_bytes, term_info)) = terms.next() {
+///     // ...
+/// }
+/// ```
+pub struct TermStream<'a> {
+    field: Field,
+    keys: SegmentTermKeys<'a, TermInfo>,
+}
+
+impl<'a> TermStream<'a> {
+
+    /// Creates a stream over the keys of the term dictionary,
+    /// stopping at the first key that does not belong to `field`.
+    pub fn new(field: Field, keys: SegmentTermKeys<'a, TermInfo>) -> TermStream<'a> {
+        TermStream {
+            field: field,
+            keys: keys,
+        }
+    }
+
+    /// Returns the field of the terms.
+    pub fn field(&self,) -> Field {
+        self.field
+    }
+
+    /// Returns the next term, along with its `TermInfo`.
+    ///
+    /// The term is returned as the bytes of its value,
+    /// as in `Term::value_bytes`, without its field.
+    pub fn next(&mut self) -> Option<(&[u8], TermInfo)> {
+        let field = self.field;
+        match self.keys.next_with_value() {
+            // the first byte of the key is the field.
+            Some((key, term_info)) if key[0] == field.0 => Some((&key[1..], term_info)),
+            _ => None,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+
+    #[test]
+    fn test_term_stream() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let other_field = schema_builder.add_text_field("other", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(title, body) in &[("a b", "c d c"), ("b e", "d"), ("f", "c")] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let collect_terms = |mut terms: TermStream| {
+            let mut term_doc_freqs = Vec::new();
+            while let Some((term_bytes, term_info)) = terms.next() {
+                term_doc_freqs.push((String::from_utf8(term_bytes.to_vec()).unwrap(), term_info.doc_freq));
+            }
+            term_doc_freqs
+        };
+        let string_doc_freqs = |term_doc_freqs: &[(&str, u32)]| {
+            term_doc_freqs
+                .iter()
+                .map(|&(text, doc_freq)| (String::from(text), doc_freq))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(collect_terms(segment_reader.terms(title_field)), string_doc_freqs(&[("a", 1), ("b", 2), ("e", 1), ("f", 1)]));
+        assert_eq!(collect_terms(segment_reader.terms(body_field)), string_doc_freqs(&[("c", 2), ("d", 2)]));
+        assert!(collect_terms(segment_reader.terms(other_field)).is_empty());
+        // resuming from a term, or from a missing one.
+        assert_eq!(collect_terms(segment_reader.terms_from(title_field, b"b")), string_doc_freqs(&[("b", 2), ("e", 1), ("f", 1)]));
+        assert_eq!(collect_terms(segment_reader.terms_from(title_field, b"c")), string_doc_freqs(&[("e", 1), ("f", 1)]));
+        assert!(collect_terms(segment_reader.terms_from(title_field, b"g")).is_empty());
+        {
+            // the term info is the one of the exact lookup.
+            let mut terms = segment_reader.terms(title_field);
+            let (_, term_info) = terms.next().unwrap();
+            assert_eq!(Some(term_info), segment_reader.get_term_info(&Term::from_field_text(title_field, "a")));
+        }
+    }
+}
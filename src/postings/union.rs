@@ -0,0 +1,205 @@
+use postings::{DocSet, SkipResult};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use DocId;
+
+
+/// Each `HeapItem` represents the head of one of
+/// the `DocSet`s of the union.
+///
+/// * `doc` - is the current doc id of the `DocSet`
+/// * `ord` - is the ordinal of the `DocSet` within the union.
+#[derive(Eq, PartialEq)]
+struct HeapItem {
+    doc: DocId,
+    ord: usize,
+}
+
+/// `HeapItem` are ordered by the document
+impl PartialOrd for HeapItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapItem {
+    fn cmp(&self, other:&Self) -> Ordering {
+         (other.doc).cmp(&self.doc)
+    }
+}
+
+
+/// `DocSet` iterating through the union of a list of `DocSet`s.
+///
+/// The `DocSet`s may be of any type, or boxed to mix several of them.
+/// Their heads are kept in a heap, so that each document is
+/// returned once, even if several of the `DocSet`s contain it.
+///
+/// The `DocSet`s containing the current document stay positioned
+/// on it until the union advances, so that the scorers built on
+/// the union, like the `BooleanScorer`, can read their scores
+/// or their term frequencies with `for_each_on_doc`.
+pub struct UnionDocSet<TDocSet: DocSet> {
+    docsets: Vec<TDocSet>,
+    queue: BinaryHeap<HeapItem>,
+    doc: DocId,
+    started: bool,
+}
+
+impl<TDocSet: DocSet> UnionDocSet<TDocSet> {
+
+    /// Unites a list of `DocSet`s.
+    ///
+    /// The `DocSet`s are advanced to their first document right away.
+    pub fn new(mut docsets: Vec<TDocSet>) -> UnionDocSet<TDocSet> {
+        let non_empty: Vec<bool> = docsets
+            .iter_mut()
+            .map(|docset| docset.advance())
+            .collect();
+        UnionDocSet::from_heads(docsets, &non_empty)
+    }
+
+    /// Unites a list of `DocSet`s that are all already
+    /// positioned on their first document.
+    ///
+    /// It is useful to the callers checking whether
+    /// some of the `DocSet`s are empty beforehand.
+    pub fn from_positioned(docsets: Vec<TDocSet>) -> UnionDocSet<TDocSet> {
+        let non_empty: Vec<bool> = docsets.iter().map(|_| true).collect();
+        UnionDocSet::from_heads(docsets, &non_empty)
+    }
+
+    fn from_heads(docsets: Vec<TDocSet>, non_empty: &[bool]) -> UnionDocSet<TDocSet> {
+        let queue: BinaryHeap<HeapItem> = docsets
+            .iter()
+            .enumerate()
+            .filter(|&(ord, _)| non_empty[ord])
+            .map(|(ord, docset)| {
+                HeapItem {
+                    doc: docset.doc(),
+                    ord: ord,
+                }
+            })
+            .collect();
+        UnionDocSet {
+            docsets: docsets,
+            queue: queue,
+            doc: 0,
+            started: false,
+        }
+    }
+
+    /// Calls `f` with the ordinal, within the list given to the
+    /// constructor, of each of the `DocSet`s positioned on the
+    /// current document, and with the `DocSet` itself.
+    ///
+    /// The `DocSet`s are visited in no particular order.
+    pub fn for_each_on_doc<F: FnMut(usize, &TDocSet)>(&self, mut f: F) {
+        for heap_item in self.queue.iter() {
+            if heap_item.doc == self.doc {
+                f(heap_item.ord, &self.docsets[heap_item.ord]);
+            }
+        }
+    }
+
+    /// Calls `f` with the ordinal and the `DocSet` of each
+    /// of the `DocSet`s that are not entirely consumed,
+    /// e.g. to bound the scores of the next documents.
+    pub fn for_each_head<F: FnMut(usize, &TDocSet)>(&self, mut f: F) {
+        for heap_item in self.queue.iter() {
+            f(heap_item.ord, &self.docsets[heap_item.ord]);
+        }
+    }
+
+    /// Advances the `DocSet`s whose current document is `self.doc`.
+    ///
+    /// The `DocSet`s entirely consumed are removed from the heap.
+    fn advance_heads(&mut self,) {
+        loop {
+            {
+                let mut mutable_head = match self.queue.peek_mut() {
+                    Some(mutable_head) => mutable_head,
+                    None => { return; }
+                };
+                if mutable_head.doc != self.doc {
+                    return;
+                }
+                let docset = &mut self.docsets[mutable_head.ord];
+                if docset.advance() {
+                    mutable_head.doc = docset.doc();
+                    continue;
+                }
+            }
+            self.queue.pop();
+        }
+    }
+
+    /// Skips the `DocSet`s that are before `target` to `target`.
+    ///
+    /// The `DocSet`s entirely consumed are removed from the heap.
+    fn skip_heads(&mut self, target: DocId) {
+        loop {
+            {
+                let mut mutable_head = match self.queue.peek_mut() {
+                    Some(mutable_head) => mutable_head,
+                    None => { return; }
+                };
+                if mutable_head.doc >= target {
+                    return;
+                }
+                let docset = &mut self.docsets[mutable_head.ord];
+                if docset.skip_next(target) != SkipResult::End {
+                    mutable_head.doc = docset.doc();
+                    continue;
+                }
+            }
+            self.queue.pop();
+        }
+    }
+
+    /// Sets the current document to the one of the head of the heap.
+    fn update_doc(&mut self,) -> bool {
+        match self.queue.peek() {
+            Some(head) => {
+                self.doc = head.doc;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<TDocSet: DocSet> DocSet for UnionDocSet<TDocSet> {
+
+    fn advance(&mut self,) -> bool {
+        if self.started {
+            self.advance_heads();
+        }
+        self.started = true;
+        self.update_doc()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.started = true;
+        self.skip_heads(target);
+        if !self.update_doc() {
+            return SkipResult::End;
+        }
+        if self.doc == target {
+            SkipResult::Reached
+        }
+        else {
+            SkipResult::OverStep
+        }
+    }
+
+    fn doc(&self,) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self,) -> usize {
+        self.docsets
+            .iter()
+            .fold(0, |size, docset| size.saturating_add(docset.size_hint()))
+    }
+}
@@ -35,6 +35,10 @@ impl DocSet for VecPostings {
     fn doc(&self,) -> DocId {
         self.doc_ids[self.cursor.0]
     }
+
+    fn size_hint(&self,) -> usize {
+        self.doc_ids.len()
+    }
     
     fn skip_next(&mut self, target: DocId) -> SkipResult {
         let mut start: usize = self.cursor.0;
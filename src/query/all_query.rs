@@ -0,0 +1,166 @@
+use Result;
+use DocId;
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use core::searcher::Searcher;
+use core::SegmentReader;
+use postings::{DocSet, SkipResult};
+use DocAddress;
+use query::Explanation;
+
+
+/// `DocSet` of all of the documents of a segment.
+struct AllDocSet {
+    doc: DocId,
+    next_doc: DocId,
+    max_doc: DocId,
+}
+
+impl AllDocSet {
+    fn new(max_doc: DocId) -> AllDocSet {
+        AllDocSet {
+            doc: 0,
+            next_doc: 0,
+            max_doc: max_doc,
+        }
+    }
+}
+
+impl DocSet for AllDocSet {
+
+    fn advance(&mut self,) -> bool {
+        if self.next_doc < self.max_doc {
+            self.doc = self.next_doc;
+            self.next_doc += 1;
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.doc > target {
+            SkipResult::OverStep
+        }
+        else if target < self.max_doc {
+            self.doc = target;
+            self.next_doc = target + 1;
+            SkipResult::Reached
+        }
+        else {
+            self.next_doc = self.max_doc;
+            SkipResult::End
+        }
+    }
+
+    fn doc(&self,) -> DocId {
+        self.doc
+    }
+}
+
+
+/// Query matching all of the documents of the index.
+///
+/// It can be used to export all of the documents, or
+/// as the base of a search only made of filters, e.g. within
+/// a `BooleanQuery` with a `Must` `AllQuery` and `MustNot` clauses.
+///
+/// All of the documents get the same score of `1`.
+#[derive(Eq, PartialEq, Debug)]
+pub struct AllQuery;
+
+impl Query for AllQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = AllDocSet::new(reader.max_doc());
+        Ok(Box::new(ConstantScorer::new(docset, 1f32)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        Ok(if doc_address.doc() < segment_reader.max_doc() {
+            let mut explanation = Explanation::with_val(1f32);
+            explanation.description("All of the documents match");
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not exist", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{BooleanQuery, MultiTermQuery, Occur};
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_all_docset() {
+        let mut docset = AllDocSet::new(10);
+        assert!(docset.advance());
+        assert_eq!(docset.doc(), 0);
+        assert_eq!(docset.skip_next(3), SkipResult::Reached);
+        assert!(docset.advance());
+        assert_eq!(docset.doc(), 4);
+        assert_eq!(docset.skip_next(2), SkipResult::OverStep);
+        assert_eq!(docset.doc(), 4);
+        assert_eq!(docset.skip_next(9), SkipResult::Reached);
+        assert!(!docset.advance());
+        let mut docset = AllDocSet::new(10);
+        assert!(docset.advance());
+        assert_eq!(docset.skip_next(10), SkipResult::End);
+        assert!(!docset.advance());
+        assert!(!AllDocSet::new(0).advance());
+    }
+
+    #[test]
+    fn test_all_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for (i, text) in ["a", "b", "a b", "c", "b c"].iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+                if i == 2 {
+                    // spread the documents over two segments
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        {
+            let mut test_collector = TestCollector::default();
+            AllQuery.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs().len(), 5);
+            assert_eq!(AllQuery.explain(&searcher, &DocAddress(1, 1)).unwrap().val(), 1f32);
+        }
+        {
+            let term_query = |text: &str| Box::new(MultiTermQuery::from(vec!(Term::from_field_text(text_field, text)))) as Box<Query>;
+            let query = BooleanQuery::from(vec!(
+                (Occur::Must, Box::new(AllQuery) as Box<Query>),
+                (Occur::MustNot, term_query("b")),
+            ));
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs().len(), 2);
+        }
+    }
+}
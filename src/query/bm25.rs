@@ -0,0 +1,283 @@
+use Score;
+use super::MultiTermAccumulator;
+use super::Explanation;
+use super::Similarity;
+use super::{SimilarityModel, TermStats};
+
+
+/// Default saturation of the term frequencies of `Bm25`.
+pub const DEFAULT_BM25_K1: f32 = 1.2f32;
+
+/// Default normalization by the field norms of `Bm25`.
+pub const DEFAULT_BM25_B: f32 = 0.75f32;
+
+
+/// `Bm25` is the Okapi BM25 pertinence score.
+///
+/// Contrary to `TfIdf`, the contribution of a term saturates
+/// as its term frequency grows, and the field norm of the
+/// document is compared to the average field norm of the field.
+///
+/// The score of a term is
+/// `<idf> * <boost> * tf * (k1 + 1) / (tf + k1 * (1 - b + b * <field_norm> / <average_field_norm>))`
+///
+/// * `k1` - controls the saturation of the term frequencies.
+///   With `k1 = 0`, the term frequencies are ignored.
+/// * `b` - controls the normalization by the field norms, from
+///   `0` (no normalization) to `1` (full normalization).
+///
+/// It is also the `SimilarityModel` of its score, in which case
+/// its idfs, average field norms and boosts are ignored, and computed
+/// from the `TermStats` of the terms instead. `Bm25::default()`,
+/// with the default parameters, is meant to be used as a model.
+#[derive(Clone)]
+pub struct Bm25 {
+    k1: f32,
+    b: f32,
+    idf: Vec<f32>,
+    average_fieldnorms: Vec<f32>,
+    boosts: Option<Vec<f32>>,
+    score: f32,
+    term_names: Option<Vec<String>>, //< only here for explain
+}
+
+impl MultiTermAccumulator for Bm25 {
+
+    #[inline]
+    fn update(&mut self, term_ord: usize, term_freq: u32, fieldnorm: u32) {
+        assert!(term_freq != 0u32);
+        self.score += self.term_ord_score(term_ord, term_freq, fieldnorm);
+    }
+
+    #[inline]
+    fn clear(&mut self,) {
+        self.score = 0f32;
+    }
+}
+
+impl Bm25 {
+    /// Constructor
+    /// * k1, b - the parameters of the score, see
+    ///   `DEFAULT_BM25_K1` and `DEFAULT_BM25_B`.
+    /// * idf - idf value for each given term. `idf` must
+    ///   have a length of `num_terms`.
+    /// * average_fieldnorms - average field norm of the field
+    ///   of each given term. `average_fieldnorms` must have
+    ///   a length of `num_terms`.
+    pub fn new(k1: f32, b: f32, idf: Vec<f32>, average_fieldnorms: Vec<f32>) -> Bm25 {
+        assert_eq!(idf.len(), average_fieldnorms.len());
+        Bm25 {
+            k1: k1,
+            b: b,
+            idf: idf,
+            average_fieldnorms: average_fieldnorms,
+            boosts: None,
+            score: 0f32,
+            term_names: None,
+        }
+    }
+
+    /// Returns the idf of a term, given the number of documents
+    /// containing it, out of the `num_docs` documents of the index.
+    pub fn idf(doc_freq: u32, num_docs: u32) -> f32 {
+        let doc_freq = doc_freq as f32;
+        (1f32 + (num_docs as f32 - doc_freq + 0.5f32) / (doc_freq + 0.5f32)).ln()
+    }
+
+    /// Set the term names for the explain function
+    pub fn set_term_names(&mut self, term_names: Vec<String>) {
+        self.term_names = Some(term_names);
+    }
+
+    /// Set the boost of each given term, typically
+    /// the boost of the field of the term.
+    /// `boosts` must have a length of `num_terms`.
+    pub fn set_boosts(&mut self, boosts: Vec<f32>) {
+        self.boosts = Some(boosts);
+    }
+
+    /// Return the boost for the ordinal `ord`
+    fn boost(&self, ord: usize) -> f32 {
+        match self.boosts {
+            Some(ref boosts_vec) => boosts_vec[ord],
+            None => 1f32,
+        }
+    }
+
+    /// Return the name for the ordinal `ord`
+    fn term_name(&self, ord: usize) -> String {
+        match self.term_names {
+            Some(ref term_names_vec) => term_names_vec[ord].clone(),
+            None => format!("Field({})", ord)
+        }
+    }
+
+    // normalization of the term frequency by the field norm,
+    // `1` for a document of average field norm.
+    #[inline]
+    fn length_norm(&self, average_fieldnorm: f32, field_norm: u32) -> f32 {
+        if average_fieldnorm <= 0f32 {
+            return 1f32;
+        }
+        1f32 - self.b + self.b * (field_norm as f32) / average_fieldnorm
+    }
+
+    #[inline]
+    fn term_ord_score(&self, term_ord: usize, term_freq: u32, field_norm: u32) -> f32 {
+        let weight = (self.idf[term_ord] * self.boost(term_ord), self.average_fieldnorms[term_ord]);
+        self.term_score(&weight, term_freq, field_norm)
+    }
+}
+
+impl Default for Bm25 {
+    fn default() -> Bm25 {
+        Bm25::new(DEFAULT_BM25_K1, DEFAULT_BM25_B, Vec::new(), Vec::new())
+    }
+}
+
+impl Similarity for Bm25 {
+
+    #[inline]
+    fn score(&self, ) -> Score {
+        self.score
+    }
+
+    fn explain(&self, vals: &[(usize, u32, u32)]) -> Explanation {
+        let score = self.score();
+        let mut explanation = Explanation::with_val(score);
+        let formula_components: Vec<String> = vals.iter()
+            .map(|&(ord, _, _)| ord)
+            .map(|ord| format!("<score for ({}>", self.term_name(ord)))
+            .collect();
+        explanation.set_formula(&formula_components.join(" + "));
+        for &(ord, term_freq, field_norm) in vals {
+            let term_score = self.term_ord_score(ord, term_freq, field_norm);
+            let term_explanation = explanation.add_child(&self.term_name(ord), term_score);
+            term_explanation.set_formula(&format!(
+                "<idf> * <boost> * <term_freq> * ({k1} + 1) / (<term_freq> + {k1} * (1 - {b} + {b} * <field_norm> / <average_field_norm>))",
+                k1=self.k1, b=self.b));
+            term_explanation.add_child("idf", self.idf[ord]);
+            term_explanation.add_child("boost", self.boost(ord));
+            term_explanation.add_child("term_freq", term_freq as f32);
+            term_explanation.add_child("field_norm", field_norm as f32);
+            term_explanation.add_child("average_field_norm", self.average_fieldnorms[ord]);
+        }
+        explanation
+    }
+
+    /// As long as `k1` and `b` are not negative, the term scores
+    /// increase with the term frequency and decrease with the field norm,
+    /// so that the bound is the sum of the scores of the terms at their bounds.
+    /// Negative term scores are bounded by `0`.
+    fn score_upper_bound(&self, vals: &[(usize, u32, u32)]) -> Option<Score> {
+        let score = vals
+            .iter()
+            .map(|&(ord, max_term_freq, min_field_norm)| self.term_ord_score(ord, max_term_freq, min_field_norm).max(0f32))
+            .fold(0f32, |sum, term_score| sum + term_score);
+        Some(score)
+    }
+}
+
+/// The weight of a term is its idf times its boost, along
+/// with the average field norm of its field.
+impl SimilarityModel for Bm25 {
+
+    /// `(idf * boost, average_fieldnorm)`
+    type Weight = (f32, f32);
+
+    fn term_weight(&self, term_stats: &TermStats) -> (f32, f32) {
+        let idf = Bm25::idf(term_stats.doc_freq, term_stats.num_docs);
+        let average_fieldnorm = if term_stats.has_fieldnorms {
+            term_stats.field_stats.average_num_tokens()
+        }
+        else {
+            1f32
+        };
+        (idf * term_stats.boost, average_fieldnorm)
+    }
+
+    #[inline]
+    fn term_score(&self, weight: &(f32, f32), term_freq: u32, fieldnorm: u32) -> Score {
+        let (weight, average_fieldnorm) = *weight;
+        let term_freq = term_freq as f32;
+        term_freq * (self.k1 + 1f32) / (term_freq + self.k1 * self.length_norm(average_fieldnorm, fieldnorm)) * weight
+    }
+
+    fn is_monotonic(&self,) -> bool {
+        self.k1 >= 0f32 && self.b >= 0f32
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use query::MultiTermAccumulator;
+    use query::Similarity;
+
+    fn abs_diff(left: f32, right: f32) -> f32 {
+        (right - left).abs()
+    }
+
+    #[test]
+    pub fn test_bm25() {
+        let mut bm25 = Bm25::new(DEFAULT_BM25_K1, DEFAULT_BM25_B, vec!(1f32, 4f32), vec!(10f32, 10f32));
+        {
+            // a document of average length.
+            bm25.update(0, 1, 10);
+            assert!(abs_diff(bm25.score(), 1f32) < 0.001f32);
+            bm25.clear();
+        }
+        {
+            bm25.update(0, 2, 10);
+            assert!(abs_diff(bm25.score(), 2f32 * 2.2f32 / 3.2f32) < 0.001f32);
+            bm25.clear();
+        }
+        {
+            // the contribution of a term saturates at `(k1 + 1) * idf`.
+            bm25.update(0, 1_000_000, 10);
+            assert!(abs_diff(bm25.score(), 2.2f32) < 0.001f32);
+            bm25.clear();
+        }
+        {
+            // shorter documents score higher.
+            bm25.update(0, 1, 5);
+            let short_score = bm25.score();
+            bm25.clear();
+            bm25.update(0, 1, 20);
+            assert!(short_score > 1f32);
+            assert!(bm25.score() < 1f32);
+            bm25.clear();
+        }
+        {
+            bm25.update(0, 1, 10);
+            bm25.update(1, 1, 10);
+            assert!(abs_diff(bm25.score(), 5f32) < 0.001f32);
+            bm25.clear();
+        }
+        bm25.set_boosts(vec!(3f32, 1f32));
+        {
+            bm25.update(0, 1, 10);
+            assert!(abs_diff(bm25.score(), 3f32) < 0.001f32);
+            bm25.clear();
+        }
+    }
+
+    #[test]
+    pub fn test_bm25_idf() {
+        assert!(Bm25::idf(1, 100) > Bm25::idf(10, 100));
+        assert!(Bm25::idf(100, 100) > 0f32);
+        assert!(abs_diff(Bm25::idf(1, 1), (1f32 + 0.5f32 / 1.5f32).ln()) < 0.001f32);
+    }
+
+    #[test]
+    pub fn test_bm25_score_upper_bound() {
+        let mut bm25 = Bm25::new(DEFAULT_BM25_K1, DEFAULT_BM25_B, vec!(1f32, 4f32), vec!(10f32, 5f32));
+        assert!(abs_diff(bm25.score_upper_bound(&[(0, 1, 10)]).unwrap(), 1f32) < 0.001f32);
+        // the bound holds for any document within the bounds.
+        bm25.update(0, 1, 4);
+        bm25.update(1, 3, 8);
+        assert!(bm25.score() <= bm25.score_upper_bound(&[(0, 2, 2), (1, 3, 8)]).unwrap());
+    }
+}
@@ -0,0 +1,195 @@
+use Result;
+use query::Query;
+use query::Occur;
+use query::Scorer;
+use query::BooleanScorer;
+use query::Explanation;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use postings::SkipResult;
+use DocAddress;
+
+
+/// Query combining several queries, each of them
+/// coming with an `Occur`.
+///
+/// * the matching documents must match all of the `Must` clauses,
+/// * they must match at least one of the `Should` clauses, if
+///   there is no `Must` clause,
+/// * they must not match any of the `MustNot` clauses.
+///
+/// The score of a document is the sum of the scores of the
+/// `Must` and `Should` clauses it matches.
+///
+/// A query without any `Must` nor `Should` clause, in particular
+/// the empty query or a query with only `MustNot` clauses,
+/// does not match any document.
+///
+/// The clauses may be any query, including boolean queries.
+/// By default, they are boxed, so that different kinds of queries
+/// can be combined:
+///
+/// ```ignore
+/// let query: BooleanQuery = BooleanQuery::from(vec!(
+///     (Occur::Must, Box::new(term_query) as Box<Query>),
+///     (Occur::MustNot, Box::new(phrase_query) as Box<Query>),
+/// ));
+/// ```
+#[derive(Eq, PartialEq, Debug)]
+pub struct BooleanQuery<TQuery = Box<Query>> {
+    clauses: Vec<(Occur, TQuery)>,
+}
+
+impl<TQuery: Query> BooleanQuery<TQuery> {
+
+    /// Adds a clause to the query.
+    pub fn add_clause(&mut self, occur: Occur, query: TQuery) {
+        self.clauses.push((occur, query));
+    }
+
+    /// Returns the clauses of the query.
+    pub fn clauses(&self,) -> &[(Occur, TQuery)] {
+        &self.clauses
+    }
+}
+
+impl<TQuery: Query> From<Vec<(Occur, TQuery)>> for BooleanQuery<TQuery> {
+    fn from(clauses: Vec<(Occur, TQuery)>) -> BooleanQuery<TQuery> {
+        BooleanQuery {
+            clauses: clauses,
+        }
+    }
+}
+
+impl<TQuery: Query> Query for BooleanQuery<TQuery> {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let mut scorers = Vec::with_capacity(self.clauses.len());
+        for &(occur, ref query) in &self.clauses {
+            scorers.push((occur, try!(query.scorer(searcher, reader))));
+        }
+        Ok(Box::new(BooleanScorer::new(scorers)))
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let mut scorers = Vec::with_capacity(self.clauses.len());
+        for &(occur, ref query) in &self.clauses {
+            scorers.push((occur, try!(query.filter_scorer(searcher, reader))));
+        }
+        Ok(Box::new(BooleanScorer::new(scorers)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let mut scorer = try!(self.scorer(searcher, segment_reader));
+        let matches = scorer.advance() && scorer.skip_next(doc_address.doc()) == SkipResult::Reached;
+        if !matches {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            return Ok(explanation);
+        }
+        let mut explanation = Explanation::with_val(scorer.score());
+        explanation.description("Sum of the scores of the matching clauses");
+        for (clause_ord, &(occur, ref query)) in self.clauses.iter().enumerate() {
+            if occur == Occur::MustNot {
+                continue;
+            }
+            let clause_explanation = try!(query.explain(searcher, doc_address));
+            if occur == Occur::Must || clause_explanation.val() > 0f32 {
+                explanation.add_child_explanation(&format!("clause_{}", clause_ord), clause_explanation);
+            }
+        }
+        Ok(explanation)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{MultiTermQuery, PhraseQuery, RangeQuery};
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_boolean_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["a b c", "a b", "b c", "a c d", "d"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let term = |text: &str| Term::from_field_text(text_field, text);
+        let term_query = |text: &str| Box::new(MultiTermQuery::from(vec!(term(text)))) as Box<Query>;
+        let search = |query: &BooleanQuery| {
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        {
+            let query = BooleanQuery::from(vec!(
+                (Occur::Must, term_query("a")),
+                (Occur::Should, term_query("b")),
+                (Occur::MustNot, term_query("d")),
+            ));
+            assert_eq!(search(&query), vec!(0, 1));
+            let explanation = query.explain(&searcher, &DocAddress(0, 1)).unwrap();
+            assert!(explanation.val() > 0f32);
+            assert_eq!(query.explain(&searcher, &DocAddress(0, 3)).unwrap().val(), 0f32);
+        }
+        {
+            let query = BooleanQuery::from(vec!(
+                (Occur::Should, term_query("b")),
+                (Occur::Should, term_query("d")),
+                (Occur::MustNot, term_query("c")),
+            ));
+            assert_eq!(search(&query), vec!(1, 4));
+        }
+        {
+            // nested boolean queries, mixed with other queries.
+            let mut query = BooleanQuery::from(vec!(
+                (Occur::Must, Box::new(BooleanQuery::from(vec!(
+                    (Occur::Should, term_query("b")),
+                    (Occur::Should, term_query("d")),
+                ))) as Box<Query>),
+                (Occur::MustNot, Box::new(PhraseQuery::new(vec!(term("b"), term("c")))) as Box<Query>),
+            ));
+            assert_eq!(search(&query), vec!(1, 3, 4));
+            query.add_clause(Occur::Must, Box::new(RangeQuery::new(term("a"), term("b"))));
+            assert_eq!(search(&query), vec!(1, 3));
+        }
+        {
+            // the scores of the matching clauses are summed.
+            let query = BooleanQuery::from(vec!(
+                (Occur::Should, term_query("a")),
+                (Occur::Should, term_query("a")),
+            ));
+            let single_query = BooleanQuery::from(vec!((Occur::Should, term_query("a"))));
+            let doc_address = DocAddress(0, 0);
+            let score = query.explain(&searcher, &doc_address).unwrap().val();
+            let single_score = single_query.explain(&searcher, &doc_address).unwrap().val();
+            assert!((score - 2f32 * single_score).abs() < 0.0001f32);
+        }
+        assert!(search(&BooleanQuery::from(Vec::new())).is_empty());
+        assert!(search(&BooleanQuery::from(vec!((Occur::MustNot, term_query("d"))))).is_empty());
+        assert!(search(&BooleanQuery::from(vec!((Occur::Must, term_query("z")), (Occur::Should, term_query("a"))))).is_empty());
+    }
+}
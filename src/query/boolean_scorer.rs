@@ -0,0 +1,266 @@
+use DocId;
+use Score;
+use postings::{DocSet, SkipResult, UnionDocSet};
+use query::Scorer;
+use query::Occur;
+
+
+/// Scorer of an optional clause, or of an excluding clause,
+/// of a `BooleanScorer`.
+///
+/// The scorer is positioned on its first document
+/// as soon as it is created.
+struct ClauseScorer<'a> {
+    scorer: Box<Scorer + 'a>,
+    finished: bool,
+}
+
+impl<'a> ClauseScorer<'a> {
+
+    fn new(mut scorer: Box<Scorer + 'a>) -> ClauseScorer<'a> {
+        let finished = !scorer.advance();
+        ClauseScorer {
+            scorer: scorer,
+            finished: finished,
+        }
+    }
+
+    /// Returns true iff the scorer contains `doc`.
+    ///
+    /// The documents must be given in increasing order.
+    fn contains(&mut self, doc: DocId) -> bool {
+        if self.finished {
+            return false;
+        }
+        match self.scorer.skip_next(doc) {
+            SkipResult::Reached => true,
+            SkipResult::OverStep => false,
+            SkipResult::End => {
+                self.finished = true;
+                false
+            }
+        }
+    }
+}
+
+
+/// Scorer combining the scorers of the clauses of a `BooleanQuery`.
+///
+/// * If there are `Must` clauses, the iteration is driven by
+///   the intersection of their scorers, and the `Should` clauses
+///   only add their score to the documents they contain.
+/// * Otherwise, the documents are the union of the scorers
+///   of the `Should` clauses, iterated with a `UnionDocSet`.
+///
+/// The documents of the `MustNot` clauses are then excluded.
+/// The score of a document is the sum of the scores of the
+/// `Must` and `Should` clauses matching it.
+///
+/// A scorer without any `Must` or `Should` clause, e.g.
+/// with only `MustNot` clauses, does not match any document.
+pub struct BooleanScorer<'a> {
+    must: Vec<Box<Scorer + 'a>>,
+    // the `Should` clauses, when there are `Must` clauses.
+    should: Vec<ClauseScorer<'a>>,
+    // the union of the `Should` clauses, when there are no `Must` clauses.
+    should_union: UnionDocSet<Box<Scorer + 'a>>,
+    must_not: Vec<ClauseScorer<'a>>,
+    doc: DocId,
+    score: Score,
+    started: bool,
+    finished: bool,
+}
+
+impl<'a> BooleanScorer<'a> {
+
+    /// Creates a scorer from the scorers of the clauses.
+    pub fn new(scorers: Vec<(Occur, Box<Scorer + 'a>)>) -> BooleanScorer<'a> {
+        let mut must = Vec::new();
+        let mut should_scorers = Vec::new();
+        let mut must_not = Vec::new();
+        let mut finished = false;
+        for (occur, mut scorer) in scorers {
+            match occur {
+                Occur::Must => {
+                    if !scorer.advance() {
+                        // an empty mandatory clause cannot be matched.
+                        finished = true;
+                    }
+                    must.push(scorer);
+                }
+                Occur::Should => {
+                    should_scorers.push(scorer);
+                }
+                Occur::MustNot => {
+                    must_not.push(ClauseScorer::new(scorer));
+                }
+            }
+        }
+        if must.is_empty() && should_scorers.is_empty() {
+            finished = true;
+        }
+        let (should, should_union) =
+            if must.is_empty() {
+                (Vec::new(), UnionDocSet::new(should_scorers))
+            }
+            else {
+                let should = should_scorers
+                    .into_iter()
+                    .map(ClauseScorer::new)
+                    .collect();
+                (should, UnionDocSet::new(Vec::new()))
+            };
+        BooleanScorer {
+            must: must,
+            should: should,
+            should_union: should_union,
+            must_not: must_not,
+            doc: 0,
+            score: 0f32,
+            started: false,
+            finished: finished,
+        }
+    }
+
+    /// Moves to the next document common to all of the
+    /// `Must` scorers, and adds the scores of the `Should` scorers.
+    fn advance_must(&mut self,) -> bool {
+        if self.started {
+            if !self.must[0].advance() {
+                return false;
+            }
+        }
+        self.started = true;
+        let mut target = self.must[0].doc();
+        'align: loop {
+            for scorer in &mut self.must {
+                match scorer.seek(target) {
+                    SkipResult::Reached => {}
+                    SkipResult::OverStep => {
+                        target = scorer.doc();
+                        continue 'align;
+                    }
+                    SkipResult::End => {
+                        return false;
+                    }
+                }
+            }
+            break;
+        }
+        let mut score = self.must
+            .iter()
+            .map(|scorer| scorer.score())
+            .fold(0f32, |sum, clause_score| sum + clause_score);
+        for clause_scorer in &mut self.should {
+            if clause_scorer.contains(target) {
+                score += clause_scorer.scorer.score();
+            }
+        }
+        self.doc = target;
+        self.score = score;
+        true
+    }
+
+    /// Moves to the next document of the union of
+    /// the `Should` scorers.
+    fn advance_should(&mut self,) -> bool {
+        if !self.should_union.advance() {
+            return false;
+        }
+        let mut score = 0f32;
+        self.should_union.for_each_on_doc(|_, scorer| {
+            score += scorer.score();
+        });
+        self.doc = self.should_union.doc();
+        self.score = score;
+        true
+    }
+}
+
+impl<'a> DocSet for BooleanScorer<'a> {
+
+    fn advance(&mut self,) -> bool {
+        if self.finished {
+            return false;
+        }
+        loop {
+            let has_doc = if self.must.is_empty() {
+                self.advance_should()
+            }
+            else {
+                self.advance_must()
+            };
+            if !has_doc {
+                self.finished = true;
+                return false;
+            }
+            let doc = self.doc;
+            if !self.must_not.iter_mut().any(|clause_scorer| clause_scorer.contains(doc)) {
+                return true;
+            }
+        }
+    }
+
+    fn doc(&self,) -> DocId {
+        self.doc
+    }
+}
+
+impl<'a> Scorer for BooleanScorer<'a> {
+    fn score(&self,) -> Score {
+        self.score
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use postings::VecPostings;
+    use query::ConstantScorer;
+
+    fn scorer(docs: Vec<DocId>, score: Score) -> Box<Scorer + 'static> {
+        Box::new(ConstantScorer::new(VecPostings::from(docs), score))
+    }
+
+    fn scored_docs(mut boolean_scorer: BooleanScorer) -> Vec<(DocId, Score)> {
+        let mut scored_docs = Vec::new();
+        while boolean_scorer.advance() {
+            scored_docs.push((boolean_scorer.doc(), boolean_scorer.score()));
+        }
+        scored_docs
+    }
+
+    #[test]
+    fn test_boolean_scorer_must() {
+        let boolean_scorer = BooleanScorer::new(vec!(
+            (Occur::Must, scorer(vec!(1, 2, 3, 5, 8, 13), 1f32)),
+            (Occur::Must, scorer(vec!(2, 3, 4, 8, 13, 20), 2f32)),
+            (Occur::Should, scorer(vec!(0, 3, 13), 4f32)),
+            (Occur::MustNot, scorer(vec!(2, 9), 8f32)),
+        ));
+        assert_eq!(scored_docs(boolean_scorer), vec!((3, 7f32), (8, 3f32), (13, 7f32)));
+    }
+
+    #[test]
+    fn test_boolean_scorer_should() {
+        let boolean_scorer = BooleanScorer::new(vec!(
+            (Occur::Should, scorer(vec!(1, 3, 5), 1f32)),
+            (Occur::MustNot, scorer(vec!(3), 8f32)),
+            (Occur::Should, scorer(vec!(0, 1, 6), 2f32)),
+            (Occur::Should, scorer(vec!(), 4f32)),
+        ));
+        assert_eq!(scored_docs(boolean_scorer), vec!((0, 2f32), (1, 3f32), (5, 1f32), (6, 2f32)));
+    }
+
+    #[test]
+    fn test_boolean_scorer_degenerate() {
+        assert!(scored_docs(BooleanScorer::new(Vec::new())).is_empty());
+        assert!(scored_docs(BooleanScorer::new(vec!((Occur::MustNot, scorer(vec!(1, 2), 1f32))))).is_empty());
+        assert!(scored_docs(BooleanScorer::new(vec!(
+            (Occur::Must, scorer(vec!(), 1f32)),
+            (Occur::Should, scorer(vec!(1, 2), 1f32)),
+        ))).is_empty());
+    }
+}
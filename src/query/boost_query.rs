@@ -0,0 +1,167 @@
+use Result;
+use DocId;
+use Score;
+use query::Query;
+use query::Scorer;
+use query::Explanation;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use postings::{DocSet, SkipResult};
+use DocAddress;
+
+
+/// `Scorer` multiplying the scores of a scorer by a boost.
+pub struct BoostScorer<'a> {
+    scorer: Box<Scorer + 'a>,
+    boost: Score,
+}
+
+impl<'a> BoostScorer<'a> {
+    /// Creates a scorer multiplying the scores of `scorer` by `boost`.
+    pub fn new(scorer: Box<Scorer + 'a>, boost: Score) -> BoostScorer<'a> {
+        BoostScorer {
+            scorer: scorer,
+            boost: boost,
+        }
+    }
+}
+
+impl<'a> DocSet for BoostScorer<'a> {
+    fn advance(&mut self,) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn doc(&self,) -> DocId {
+        self.scorer.doc()
+    }
+}
+
+impl<'a> Scorer for BoostScorer<'a> {
+    fn score(&self,) -> Score {
+        self.scorer.score() * self.boost
+    }
+}
+
+
+/// Query multiplying the scores of a query by a constant boost,
+/// e.g. to favor the documents matching one of the clauses
+/// of a `BooleanQuery`.
+///
+/// The matching documents are the ones of the wrapped query.
+/// Boosts compose: boosting a boosted query multiplies the boosts.
+///
+/// ```ignore
+/// let query = BoostQuery::new(Box::new(range_query) as Box<Query>, 2.5f32);
+/// ```
+#[derive(PartialEq, Debug)]
+pub struct BoostQuery<TQuery = Box<Query>> {
+    query: TQuery,
+    boost: Score,
+}
+
+impl<TQuery: Eq> Eq for BoostQuery<TQuery> {}
+
+impl<TQuery: Query> BoostQuery<TQuery> {
+
+    /// Creates a query multiplying the scores of `query` by `boost`.
+    ///
+    /// # Panics
+    /// If the boost is negative.
+    pub fn new(query: TQuery, boost: Score) -> BoostQuery<TQuery> {
+        assert!(boost >= 0f32, "The boost cannot be negative.");
+        BoostQuery {
+            query: query,
+            boost: boost,
+        }
+    }
+
+    /// Returns the boosted query.
+    pub fn query(&self,) -> &TQuery {
+        &self.query
+    }
+
+    /// Returns the boost.
+    pub fn boost(&self,) -> Score {
+        self.boost
+    }
+}
+
+impl<TQuery: Query> Query for BoostQuery<TQuery> {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let scorer = try!(self.query.scorer(searcher, reader));
+        Ok(Box::new(BoostScorer::new(scorer, self.boost)))
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        self.query.filter_scorer(searcher, reader)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let query_explanation = try!(self.query.explain(searcher, doc_address));
+        if query_explanation.val() == 0f32 {
+            return Ok(query_explanation);
+        }
+        let mut explanation = Explanation::with_val(query_explanation.val() * self.boost);
+        explanation.description(&format!("Score of the query, multiplied by a boost of {}", self.boost));
+        explanation.add_child_explanation("query", query_explanation);
+        Ok(explanation)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{Term, TEXT};
+    use query::{BooleanQuery, MultiTermQuery, Occur};
+    use query::tests::{create_index, ranking};
+
+    #[test]
+    fn test_boost_query() {
+        let (index, text_field) = create_index(TEXT, &[vec!("a", "b", "a b", "c")]);
+        let searcher = index.searcher();
+        let term_query = |text: &str| Box::new(MultiTermQuery::from(vec!(Term::from_field_text(text_field, text)))) as Box<Query>;
+        let score = |query: &Query, doc| query.explain(&searcher, &DocAddress(0, doc)).unwrap().val();
+        let a_score = score(&*term_query("a"), 0);
+        {
+            let query = BoostQuery::new(term_query("a"), 2.5f32);
+            assert!((score(&query, 0) - 2.5f32 * a_score).abs() < 0.0001f32);
+            assert_eq!(score(&query, 1), 0f32);
+            let scored_docs = ranking(&searcher, &query, 4);
+            assert_eq!(scored_docs.len(), 2);
+            assert!((scored_docs[0].0 - 2.5f32 * a_score).abs() < 0.0001f32);
+        }
+        {
+            // boosts compose.
+            let query = BoostQuery::new(Box::new(BoostQuery::new(term_query("a"), 2f32)) as Box<Query>, 3f32);
+            assert!((score(&query, 0) - 6f32 * a_score).abs() < 0.0001f32);
+        }
+        {
+            // boosts survive within boolean queries.
+            let b_score = score(&*term_query("b"), 1);
+            let query = BooleanQuery::from(vec!(
+                (Occur::Should, Box::new(BoostQuery::new(term_query("a"), 10f32)) as Box<Query>),
+                (Occur::Should, term_query("b")),
+            ));
+            let docs: Vec<DocId> = ranking(&searcher, &query, 4).into_iter().map(|(_, doc_address)| doc_address.doc()).collect();
+            assert_eq!(docs, vec!(0, 2, 1));
+            assert!((score(&query, 1) - b_score).abs() < 0.0001f32);
+            assert!((score(&query, 0) - 10f32 * a_score).abs() < 0.0001f32);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use Result;
+use Score;
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use query::Explanation;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use postings::{DocSet, SkipResult};
+use DocAddress;
+
+
+/// Query matching the documents of a query, all of them
+/// getting the same score, e.g. to use a query as a pure filter.
+///
+/// The scores of the wrapped query are never computed:
+/// its documents are iterated through its `filter_scorer`,
+/// so that term frequencies and positions are not decoded
+/// when they do not change the matching documents.
+///
+/// ```ignore
+/// let filter = ConstantScoreQuery::new(Box::new(term_query) as Box<Query>, 0f32);
+/// ```
+#[derive(PartialEq, Debug)]
+pub struct ConstantScoreQuery<TQuery = Box<Query>> {
+    query: TQuery,
+    score: Score,
+}
+
+impl<TQuery: Eq> Eq for ConstantScoreQuery<TQuery> {}
+
+impl<TQuery: Query> ConstantScoreQuery<TQuery> {
+
+    /// Creates a query giving `score` to all of the documents
+    /// matching `query`.
+    pub fn new(query: TQuery, score: Score) -> ConstantScoreQuery<TQuery> {
+        ConstantScoreQuery {
+            query: query,
+            score: score,
+        }
+    }
+
+    /// Returns the wrapped query.
+    pub fn query(&self,) -> &TQuery {
+        &self.query
+    }
+
+    /// Returns the score of the matching documents.
+    pub fn score(&self,) -> Score {
+        self.score
+    }
+}
+
+impl<TQuery: Query> Query for ConstantScoreQuery<TQuery> {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = try!(self.query.filter_scorer(searcher, reader));
+        Ok(Box::new(ConstantScorer::new(docset, self.score)))
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        self.query.filter_scorer(searcher, reader)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let mut docset = try!(self.query.filter_scorer(searcher, segment_reader));
+        let matches = docset.advance() && docset.skip_next(doc_address.doc()) == SkipResult::Reached;
+        Ok(if matches {
+            let mut explanation = Explanation::with_val(self.score);
+            explanation.description("Constant score of the documents matching the query");
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{Term, TEXT};
+    use query::{BooleanQuery, MultiTermQuery, PhraseQuery, Occur};
+    use query::tests::{create_index, ranking};
+    use collector::CountCollector;
+    use test::Bencher;
+
+    fn published_texts(num_docs: usize) -> Vec<&'static str> {
+        (0..num_docs)
+            .map(|i| if i % 3 == 0 { "published published article" } else { "published draft" })
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_score_query() {
+        let (index, text_field) = create_index(TEXT, &[published_texts(1_000)]);
+        let searcher = index.searcher();
+        let term_query = |text: &str| Box::new(MultiTermQuery::from(vec!(Term::from_field_text(text_field, text)))) as Box<Query>;
+        {
+            let query = ConstantScoreQuery::new(term_query("article"), 2f32);
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 334);
+            assert!(ranking(&searcher, &query, 10).iter().all(|&(score, doc_address)| score == 2f32 && doc_address.doc() % 3 == 0));
+            assert_eq!(query.explain(&searcher, &DocAddress(0, 999)).unwrap().val(), 2f32);
+            assert_eq!(query.explain(&searcher, &DocAddress(0, 998)).unwrap().val(), 0f32);
+        }
+        {
+            // the term spans several blocks of term frequencies.
+            let query = ConstantScoreQuery::new(term_query("published"), 1f32);
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 1_000);
+        }
+        {
+            // phrases still read the positions they need.
+            let phrase_query = PhraseQuery::new(vec!(
+                Term::from_field_text(text_field, "published"),
+                Term::from_field_text(text_field, "draft")));
+            let query = ConstantScoreQuery::new(Box::new(phrase_query) as Box<Query>, 1f32);
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 666);
+        }
+        {
+            // a filter restricts the documents without changing their scores.
+            let query = BooleanQuery::from(vec!(
+                (Occur::Must, term_query("published")),
+                (Occur::Must, Box::new(ConstantScoreQuery::new(term_query("article"), 0f32)) as Box<Query>),
+            ));
+            let published_score = term_query("published").explain(&searcher, &DocAddress(0, 0)).unwrap().val();
+            let scored_docs = ranking(&searcher, &query, 10);
+            assert_eq!(scored_docs.len(), 10);
+            for (score, doc_address) in scored_docs {
+                assert_eq!(doc_address.doc() % 3, 0);
+                assert!((score - published_score).abs() < 0.0001f32);
+            }
+        }
+    }
+
+    #[bench]
+    fn bench_constant_score_query(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &[published_texts(100_000)]);
+        let searcher = index.searcher();
+        let term_query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "published")));
+        let query = ConstantScoreQuery::new(term_query, 1f32);
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+
+    #[bench]
+    fn bench_constant_score_term_query(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &[published_texts(100_000)]);
+        let searcher = index.searcher();
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "published")));
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+}
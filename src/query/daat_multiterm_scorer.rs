@@ -1,40 +1,13 @@
 use DocId;
-use postings::{Postings, DocSet};
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use postings::{Postings, DocSet, SkipResult, UnionDocSet};
 use query::MultiTermAccumulator;
 use query::Similarity; 
-use fastfield::U32FastFieldReader;
+use fastfield::FieldNormReader;
 use query::Occur;
-use std::iter;
+use std::cmp;
 use super::Scorer;
 use Score;
 
-/// Each `HeapItem` represents the head of
-/// a segment postings being merged.
-///
-/// * `doc` - is the current doc id for the given segment postings 
-/// * `ord` - is the ordinal used to identify to which segment postings
-/// this heap item belong to.
-#[derive(Eq, PartialEq)]
-struct HeapItem {
-    doc: DocId,
-    ord: u32,
-}
-
-/// `HeapItem` are ordered by the document
-impl PartialOrd for HeapItem {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for HeapItem {
-    fn cmp(&self, other:&Self) -> Ordering {
-         (other.doc).cmp(&self.doc)
-    }
-}
-
 struct Filter {
     and_mask: u64,
     result: u64,    
@@ -70,13 +43,23 @@ impl Filter {
 
 /// Document-At-A-Time multi term scorer.
 ///
-/// The scorer merges multiple segment postings and pushes
-/// term information to the score accumulator. 
+/// The scorer merges multiple segment postings, with a `UnionDocSet`,
+/// and pushes term information to the score accumulator. 
+///
+/// When only the documents scoring above a given score are needed,
+/// `advance_above` skips the blocks of postings whose
+/// maximum term frequencies cannot reach it.
 pub struct DAATMultiTermScorer<TPostings: Postings, TAccumulator: MultiTermAccumulator> {
-    fieldnorm_readers: Vec<U32FastFieldReader>,
-    postings: Vec<TPostings>,
-    term_frequencies: Vec<u32>,
-    queue: BinaryHeap<HeapItem>,
+    fieldnorm_readers: Vec<FieldNormReader>,
+    // lower bound of the field norm of the documents
+    // containing the term of each of the postings.
+    min_fieldnorms: Vec<u32>,
+    // buffer of the bounds given to `Similarity::score_upper_bound`.
+    block_max_vals: Vec<(usize, u32, u32)>,
+    postings: UnionDocSet<TPostings>,
+    // ordinal of the term of each of the postings,
+    // as given to the constructor.
+    term_ords: Vec<usize>,
     doc: DocId,
     similarity: TAccumulator,
     filter: Filter,
@@ -86,31 +69,33 @@ impl<TPostings: Postings, TAccumulator: MultiTermAccumulator> DAATMultiTermScore
     
     fn new_non_empty(
         
-        fieldnorm_readers: Vec<U32FastFieldReader>,
+        fieldnorm_readers: Vec<FieldNormReader>,
         postings: Vec<TPostings>,
+        term_ords: Vec<usize>,
         similarity: TAccumulator,
         filter: Filter
     ) -> DAATMultiTermScorer<TPostings, TAccumulator> {
-        let mut term_frequencies: Vec<u32> = iter::repeat(0u32).take(postings.len()).collect();
-        let heap_items: Vec<HeapItem> = postings
+        let min_fieldnorms = fieldnorm_readers
             .iter()
-            .map(|posting| {
-                (posting.doc(), posting.term_freq())
-            })
-            .enumerate()
-            .map(|(ord, (doc, tf))| {
-                term_frequencies[ord] = tf;
-                HeapItem {
-                    doc: doc,
-                    ord: ord as u32
+            .map(|fieldnorm_reader| {
+                // a document containing a term has at least one token,
+                // unless the fieldnorms are missing altogether.
+                if fieldnorm_reader.max_val() == 0 {
+                    0
+                }
+                else {
+                    cmp::max(fieldnorm_reader.min_val(), 1)
                 }
             })
             .collect();
         DAATMultiTermScorer {
             fieldnorm_readers: fieldnorm_readers,
-            postings: postings,
-            term_frequencies: term_frequencies,
-            queue: BinaryHeap::from(heap_items),
+            min_fieldnorms: min_fieldnorms,
+            block_max_vals: Vec::new(),
+            // the postings were advanced to their first document
+            // to find the empty `Must` postings.
+            postings: UnionDocSet::from_positioned(postings),
+            term_ords: term_ords,
             doc: 0,
             similarity: similarity,
             filter: filter
@@ -118,19 +103,35 @@ impl<TPostings: Postings, TAccumulator: MultiTermAccumulator> DAATMultiTermScore
     }
     
     /// Constructor
-    pub fn new(postings_and_fieldnorms: Vec<(Occur, TPostings, U32FastFieldReader)>, similarity: TAccumulator) -> DAATMultiTermScorer<TPostings, TAccumulator> {      
+    ///
+    /// The term ordinals given to the accumulator are the
+    /// indexes of the postings within `postings_and_fieldnorms`.
+    /// If one of the `Must` postings is empty, no document matches.
+    pub fn new(postings_and_fieldnorms: Vec<(Occur, TPostings, FieldNormReader)>, similarity: TAccumulator) -> DAATMultiTermScorer<TPostings, TAccumulator> {      
         let mut postings = Vec::new();
         let mut fieldnorm_readers = Vec::new();
         let mut occurs = Vec::new();
-        for (occur, mut posting, fieldnorm_reader) in postings_and_fieldnorms {
+        let mut term_ords = Vec::new();
+        let mut missing_must = false;
+        for (term_ord, (occur, mut posting, fieldnorm_reader)) in postings_and_fieldnorms.into_iter().enumerate() {
             if posting.advance() {
                 postings.push(posting);
                 fieldnorm_readers.push(fieldnorm_reader);
                 occurs.push(occur);
+                term_ords.push(term_ord);
             }
+            else if occur == Occur::Must {
+                missing_must = true;
+            }
+        }
+        if missing_must {
+            postings.clear();
+            fieldnorm_readers.clear();
+            occurs.clear();
+            term_ords.clear();
         }
         let filter = Filter::new(&occurs);
-        DAATMultiTermScorer::new_non_empty(fieldnorm_readers, postings, similarity, filter)
+        DAATMultiTermScorer::new_non_empty(fieldnorm_readers, postings, term_ords, similarity, filter)
     }
 
     /// Returns the scorer
@@ -138,39 +139,84 @@ impl<TPostings: Postings, TAccumulator: MultiTermAccumulator> DAATMultiTermScore
         &self.similarity
     }
         
-    /// Advances the head of our heap (the segment postings with the lowest doc)
-    /// It will also update the new current `DocId` as well as the term frequency
-    /// associated with the segment postings.
-    /// 
-    /// After advancing the `SegmentPosting`, the postings is removed from the heap
-    /// if it has been entirely consumed, or pushed back into the heap.
-    /// 
-    /// # Panics
-    /// This method will panic if the head `SegmentPostings` is not empty.
-    fn advance_head(&mut self,) {
-        
+    /// Pushes the terms of the postings positioned on the
+    /// current document of the union to the accumulator.
+    ///
+    /// Returns true iff the document is accepted by the
+    /// `Must` and `MustNot` postings.
+    fn collect_doc(&mut self,) -> bool {
+        self.doc = self.postings.doc();
+        let doc = self.doc;
+        let mut ord_bitset = 0u64;
         {
-            let mut mutable_head = self.queue.peek_mut().unwrap();
-            let cur_postings = &mut self.postings[mutable_head.ord as usize];
-            if cur_postings.advance() {
-                let doc = cur_postings.doc();
-                self.term_frequencies[mutable_head.ord as usize] = cur_postings.term_freq();
-                mutable_head.doc = doc; 
-                return;
-            }
-            
+            let similarity = &mut self.similarity;
+            let fieldnorm_readers = &self.fieldnorm_readers;
+            let term_ords = &self.term_ords;
+            similarity.clear();
+            self.postings.for_each_on_doc(|ord, postings| {
+                let fieldnorm = fieldnorm_readers[ord].get(doc);
+                similarity.update(term_ords[ord], postings.term_freq(), fieldnorm);
+                ord_bitset |= 1 << ord;
+            });
         }
-        self.queue.pop();
-    }
-    
-    /// Returns the field norm for the segment postings with the given ordinal,
-    /// and the given document. 
-    fn get_field_norm(&self, ord:usize, doc:DocId) -> u32 {
-        self.fieldnorm_readers[ord].get(doc)
+        self.filter.accept(ord_bitset)
     }
 
 }
 
+impl<TPostings: Postings, TSimilarity: Similarity> DAATMultiTermScorer<TPostings, TSimilarity> {
+
+    /// Advances to the next document, skipping the documents
+    /// that cannot score more than `min_score`.
+    ///
+    /// The documents before the end of the current blocks of all
+    /// of the postings are skipped when the similarity bounds their
+    /// score below `min_score`, given the maximum term frequency of
+    /// the blocks. The documents returned may still score less
+    /// than `min_score`, as the bound is not tight.
+    ///
+    /// If some of the postings do not know the maximum term frequency
+    /// of their blocks, this is equivalent to `advance`.
+    pub fn advance_above(&mut self, min_score: Score) -> bool {
+        // true when the union was skipped to a document
+        // that has not been returned yet.
+        let mut skipped = false;
+        loop {
+            let mut window_end = DocId::max_value();
+            let mut all_blocks_known = true;
+            {
+                let block_max_vals = &mut self.block_max_vals;
+                let term_ords = &self.term_ords;
+                let min_fieldnorms = &self.min_fieldnorms;
+                block_max_vals.clear();
+                self.postings.for_each_head(|ord, postings| {
+                    match postings.block_max() {
+                        Some((block_last_doc, block_max_term_freq)) => {
+                            window_end = cmp::min(window_end, block_last_doc);
+                            block_max_vals.push((term_ords[ord], block_max_term_freq, min_fieldnorms[ord]));
+                        }
+                        None => {
+                            all_blocks_known = false;
+                        }
+                    }
+                });
+            }
+            let skippable = all_blocks_known && !self.block_max_vals.is_empty() && window_end != DocId::max_value() &&
+                match self.similarity.score_upper_bound(&self.block_max_vals) {
+                    Some(score_upper_bound) => score_upper_bound < min_score,
+                    None => false,
+                };
+            if !skippable {
+                return (skipped && self.collect_doc()) || self.advance();
+            }
+            if self.postings.skip_next(window_end + 1) == SkipResult::End {
+                return false;
+            }
+            skipped = true;
+        }
+    }
+}
+
 impl<TPostings: Postings, TSimilarity: Similarity> Scorer for DAATMultiTermScorer<TPostings, TSimilarity> {
     fn score(&self,) -> Score {
         self.similarity.score()
@@ -181,41 +227,33 @@ impl<TPostings: Postings, TAccumulator: MultiTermAccumulator> DocSet for DAATMul
     
     fn advance(&mut self,) -> bool {
         loop {
-            self.similarity.clear();
-            let mut ord_bitset = 0u64;
-            match self.queue.peek() {
-                Some(heap_item) => {
-                    self.doc = heap_item.doc;
-                    let ord: usize = heap_item.ord as usize;
-                    let fieldnorm = self.get_field_norm(ord, heap_item.doc);
-                    let tf = self.term_frequencies[ord];
-                    self.similarity.update(ord, tf, fieldnorm);
-                    ord_bitset |= 1 << ord;  
-                }
-                None => {
-                    return false;
-                }
+            if !self.postings.advance() {
+                return false;
             }
-            self.advance_head();
-            while let Some(&HeapItem {doc, ord}) = self.queue.peek() {
-                if doc == self.doc {
-                    let peek_ord: usize = ord as usize;
-                    let peek_tf = self.term_frequencies[peek_ord];
-                    let peek_fieldnorm = self.get_field_norm(peek_ord, doc);
-                    self.similarity.update(peek_ord, peek_tf, peek_fieldnorm);
-                    ord_bitset |= 1 << peek_ord;
-                }
-                else  {
-                    break;
-                }
-                self.advance_head();
-            } 
-            if self.filter.accept(ord_bitset) {
+            if self.collect_doc() {
                 return true;
             }
         }
     }
 
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.doc >= target {
+            return if self.doc == target { SkipResult::Reached } else { SkipResult::OverStep };
+        }
+        if self.postings.skip_next(target) == SkipResult::End {
+            return SkipResult::End;
+        }
+        if !self.collect_doc() && !self.advance() {
+            return SkipResult::End;
+        }
+        if self.doc == target {
+            SkipResult::Reached
+        }
+        else {
+            SkipResult::OverStep
+        }
+    }
+
     fn doc(&self,) -> DocId {
         self.doc
     }
@@ -259,8 +297,8 @@ mod tests {
        
     #[test]
     pub fn test_daat_scorer() {
-        let left_fieldnorms = create_u32_fastfieldreader(Field(1), vec!(100,200,300));
-        let right_fieldnorms = create_u32_fastfieldreader(Field(2), vec!(15,25,35));   
+        let left_fieldnorms = FieldNormReader::from_fieldnorms(create_u32_fastfieldreader(Field(1), vec!(100,200,300)));
+        let right_fieldnorms = FieldNormReader::from_fieldnorms(create_u32_fastfieldreader(Field(2), vec!(15,25,35)));   
         let left = VecPostings::from(vec!(1, 2, 3));
         let right = VecPostings::from(vec!(1, 3, 8));
         let tfidf = TfIdf::new(vec!(0f32, 1f32, 2f32), vec!(1f32, 4f32));
@@ -0,0 +1,157 @@
+use Result;
+use Score;
+use query::Query;
+use query::Scorer;
+use query::DisjunctionMaxScorer;
+use query::Explanation;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use postings::SkipResult;
+use DocAddress;
+
+
+/// Query matching the documents matching any of its subqueries,
+/// typically the same terms searched in different fields.
+///
+/// Unlike a `BooleanQuery` of `Should` clauses, the score of a
+/// document is not the sum of the scores of the subqueries it matches,
+/// but their maximum, plus the sum of the other scores multiplied by
+/// the tie breaker:
+///
+/// `max(scores) + tie_breaker * (sum(scores) - max(scores))`
+///
+/// A document repeating a term in all of its fields thus does not
+/// outrank a document holding the term in a single, more relevant field.
+/// A tie breaker of `0` only keeps the best score, while a tie breaker
+/// of `1` sums all of the scores.
+#[derive(PartialEq, Debug)]
+pub struct DisjunctionMaxQuery<TQuery = Box<Query>> {
+    queries: Vec<TQuery>,
+    tie_breaker: Score,
+}
+
+impl<TQuery: Eq> Eq for DisjunctionMaxQuery<TQuery> {}
+
+impl<TQuery: Query> DisjunctionMaxQuery<TQuery> {
+
+    /// Creates a query matching any of `queries`.
+    ///
+    /// # Panics
+    /// If the tie breaker is not within `[0, 1]`.
+    pub fn new(queries: Vec<TQuery>, tie_breaker: Score) -> DisjunctionMaxQuery<TQuery> {
+        assert!(tie_breaker >= 0f32 && tie_breaker <= 1f32, "The tie breaker must be within [0, 1].");
+        DisjunctionMaxQuery {
+            queries: queries,
+            tie_breaker: tie_breaker,
+        }
+    }
+
+    /// Returns the subqueries.
+    pub fn queries(&self,) -> &[TQuery] {
+        &self.queries
+    }
+
+    /// Returns the tie breaker.
+    pub fn tie_breaker(&self,) -> Score {
+        self.tie_breaker
+    }
+}
+
+impl<TQuery: Query> Query for DisjunctionMaxQuery<TQuery> {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let mut scorers = Vec::with_capacity(self.queries.len());
+        for query in &self.queries {
+            scorers.push(try!(query.scorer(searcher, reader)));
+        }
+        Ok(Box::new(DisjunctionMaxScorer::new(scorers, self.tie_breaker)))
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let mut scorers = Vec::with_capacity(self.queries.len());
+        for query in &self.queries {
+            scorers.push(try!(query.filter_scorer(searcher, reader)));
+        }
+        Ok(Box::new(DisjunctionMaxScorer::new(scorers, self.tie_breaker)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let mut scorer = try!(self.scorer(searcher, segment_reader));
+        let matches = scorer.advance() && scorer.skip_next(doc_address.doc()) == SkipResult::Reached;
+        if !matches {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            return Ok(explanation);
+        }
+        let mut explanation = Explanation::with_val(scorer.score());
+        explanation.description(&format!("Maximum of the scores of the matching subqueries, plus {} times the other scores", self.tie_breaker));
+        for (query_ord, query) in self.queries.iter().enumerate() {
+            let query_explanation = try!(query.explain(searcher, doc_address));
+            if query_explanation.val() > 0f32 {
+                explanation.add_child_explanation(&format!("query_{}", query_ord), query_explanation);
+            }
+        }
+        Ok(explanation)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::MultiTermQuery;
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_disjunction_max_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(title, body) in &[("a", "a"), ("a", "b"), ("b", "a"), ("c", "c")] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let term_query = |field, text: &str| MultiTermQuery::from(vec!(Term::from_field_text(field, text)));
+        let query = |tie_breaker| DisjunctionMaxQuery::new(vec!(term_query(title_field, "a"), term_query(body_field, "a")), tie_breaker);
+        {
+            let mut test_collector = TestCollector::default();
+            query(0f32).search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(0, 1, 2));
+        }
+        let title_score = term_query(title_field, "a").explain(&searcher, &DocAddress(0, 0)).unwrap().val();
+        let body_score = term_query(body_field, "a").explain(&searcher, &DocAddress(0, 0)).unwrap().val();
+        let score = |tie_breaker, doc| query(tie_breaker).explain(&searcher, &DocAddress(0, doc)).unwrap().val();
+        assert!((score(0f32, 0) - title_score.max(body_score)).abs() < 0.0001f32);
+        assert!((score(1f32, 0) - (title_score + body_score)).abs() < 0.0001f32);
+        assert!((score(0.5f32, 0) - (title_score.max(body_score) + 0.5f32 * title_score.min(body_score))).abs() < 0.0001f32);
+        assert!((score(0.5f32, 1) - title_score).abs() < 0.0001f32);
+        assert_eq!(score(0.5f32, 3), 0f32);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_disjunction_max_query_tie_breaker() {
+        DisjunctionMaxQuery::<MultiTermQuery>::new(Vec::new(), 1.5f32);
+    }
+}
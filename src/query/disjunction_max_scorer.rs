@@ -0,0 +1,105 @@
+use DocId;
+use Score;
+use postings::{DocSet, UnionDocSet};
+use query::Scorer;
+
+
+/// Scorer combining the scorers of the subqueries
+/// of a `DisjunctionMaxQuery`.
+///
+/// The documents are the union of the documents of the scorers,
+/// iterated with a `UnionDocSet`.
+/// The score of a document is the maximum of the scores of
+/// the scorers matching it, plus the sum of the other scores
+/// multiplied by the tie breaker.
+pub struct DisjunctionMaxScorer<'a> {
+    union: UnionDocSet<Box<Scorer + 'a>>,
+    tie_breaker: Score,
+    doc: DocId,
+    score: Score,
+}
+
+impl<'a> DisjunctionMaxScorer<'a> {
+
+    /// Creates a scorer from the scorers of the subqueries.
+    pub fn new(scorers: Vec<Box<Scorer + 'a>>, tie_breaker: Score) -> DisjunctionMaxScorer<'a> {
+        DisjunctionMaxScorer {
+            union: UnionDocSet::new(scorers),
+            tie_breaker: tie_breaker,
+            doc: 0,
+            score: 0f32,
+        }
+    }
+}
+
+impl<'a> DocSet for DisjunctionMaxScorer<'a> {
+
+    fn advance(&mut self,) -> bool {
+        if !self.union.advance() {
+            return false;
+        }
+        let mut max_score = 0f32;
+        let mut sum_score = 0f32;
+        self.union.for_each_on_doc(|_, scorer| {
+            let score = scorer.score();
+            if score > max_score {
+                max_score = score;
+            }
+            sum_score += score;
+        });
+        self.doc = self.union.doc();
+        self.score = max_score + self.tie_breaker * (sum_score - max_score);
+        true
+    }
+
+    fn doc(&self,) -> DocId {
+        self.doc
+    }
+}
+
+impl<'a> Scorer for DisjunctionMaxScorer<'a> {
+    fn score(&self,) -> Score {
+        self.score
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use postings::VecPostings;
+    use query::ConstantScorer;
+
+    fn scorer(docs: Vec<DocId>, score: Score) -> Box<Scorer + 'static> {
+        Box::new(ConstantScorer::new(VecPostings::from(docs), score))
+    }
+
+    fn scored_docs(mut scorer: DisjunctionMaxScorer) -> Vec<(DocId, Score)> {
+        let mut scored_docs = Vec::new();
+        while scorer.advance() {
+            scored_docs.push((scorer.doc(), scorer.score()));
+        }
+        scored_docs
+    }
+
+    #[test]
+    fn test_disjunction_max_scorer() {
+        let scorers = || vec!(
+            scorer(vec!(1, 3, 5), 1f32),
+            scorer(vec!(0, 1, 3), 4f32),
+            scorer(vec!(), 8f32),
+            scorer(vec!(3), 2f32),
+        );
+        assert_eq!(
+            scored_docs(DisjunctionMaxScorer::new(scorers(), 0f32)),
+            vec!((0, 4f32), (1, 4f32), (3, 4f32), (5, 1f32)));
+        assert_eq!(
+            scored_docs(DisjunctionMaxScorer::new(scorers(), 0.5f32)),
+            vec!((0, 4f32), (1, 4.5f32), (3, 5.5f32), (5, 1f32)));
+        assert_eq!(
+            scored_docs(DisjunctionMaxScorer::new(scorers(), 1f32)),
+            vec!((0, 4f32), (1, 5f32), (3, 7f32), (5, 1f32)));
+        assert!(scored_docs(DisjunctionMaxScorer::new(Vec::new(), 0f32)).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use Result;
+use schema::Field;
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use core::SegmentReader;
+use common::TimerTree;
+use core::searcher::Searcher;
+use collector::Collector;
+use SegmentLocalId;
+use postings::DocSet;
+use postings::BitSetDocSet;
+use ScoredDoc;
+use DocAddress;
+use query::Explanation;
+
+
+/// Query matching the documents having at least
+/// one value for a given field.
+///
+/// The field does not need to be indexed : the documents
+/// are read from the field presence bitset of each segment.
+///
+/// All of the matching documents get the same score of `1`.
+#[derive(Eq, PartialEq, Debug)]
+pub struct ExistsQuery {
+    field: Field,
+}
+
+impl ExistsQuery {
+
+    /// Creates a query matching the documents having a value for `field`.
+    pub fn new(field: Field) -> ExistsQuery {
+        ExistsQuery {
+            field: field,
+        }
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.field
+    }
+}
+
+impl Query for ExistsQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = BitSetDocSet::from(reader.has_field(self.field));
+        Ok(Box::new(ConstantScorer::new(docset, 1f32)))
+    }
+
+    fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        collector: &mut C) -> Result<TimerTree> {
+        let mut timer_tree = TimerTree::default();
+        {
+            let mut search_timer = timer_tree.open("search");
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                if collector.should_terminate() {
+                    break;
+                }
+                let mut segment_search_timer = search_timer.open("segment_search");
+                {
+                    let _ = segment_search_timer.open("set_segment");
+                    try!(collector.set_segment(segment_ord as SegmentLocalId, &segment_reader));
+                }
+                {
+                    let _collection_timer = segment_search_timer.open("collection");
+                    let mut docset = BitSetDocSet::from(segment_reader.has_field(self.field));
+                    while docset.advance() {
+                        collector.collect(ScoredDoc(1f32, docset.doc()));
+                        if collector.should_terminate() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        try!(collector.finish());
+        Ok(timer_tree)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        Ok(if segment_reader.has_field(self.field).contains(doc_address.doc()) {
+            let mut explanation = Explanation::with_val(1f32);
+            explanation.description(&format!("The document has a value for the field {:?}", self.field));
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{self, SchemaBuilder, Document, U32Options};
+    use collector::tests::TestCollector;
+
+    #[test]
+    fn test_exists_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let stored_field = schema_builder.add_text_field("comment", schema::STORED);
+        let count_field = schema_builder.add_u32_field("count", U32Options::default().set_fast());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0u32..6u32 {
+                let mut doc = Document::default();
+                doc.add_text(text_field, "a");
+                if i % 3 == 0 {
+                    doc.add_text(stored_field, "some comment");
+                }
+                if i % 2 == 0 {
+                    doc.add_u32(count_field, i);
+                }
+                index_writer.add_document(doc).unwrap();
+                if i == 3 {
+                    // spread the documents over two segments
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let exists_docs = |field: Field| {
+            let searcher = index.searcher();
+            let mut test_collector = TestCollector::default();
+            ExistsQuery::new(field).search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(exists_docs(text_field).len(), 6);
+        assert_eq!(exists_docs(stored_field).len(), 2);
+        assert_eq!(exists_docs(count_field).len(), 3);
+        {
+            let searcher = index.searcher();
+            let num_docs_per_segment: Vec<usize> = searcher
+                .segment_readers()
+                .iter()
+                .map(|segment_reader| segment_reader.has_field(count_field).len())
+                .collect();
+            assert_eq!(num_docs_per_segment.iter().fold(0, |acc, &n| acc + n), 3);
+        }
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        assert_eq!(exists_docs(text_field).len(), 6);
+        assert_eq!(exists_docs(stored_field).len(), 2);
+        assert_eq!(exists_docs(count_field).len(), 3);
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let count_presence: Vec<bool> = (0..6)
+            .map(|doc| segment_reader.has_field(count_field).contains(doc))
+            .collect();
+        assert_eq!(count_presence, vec!(true, false, true, false, true, false));
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::iter;
+use rustc_serialize::json;
 
 /// Tree representing the expression of the score of document.
 /// The explanation is organized as follows.
@@ -39,6 +40,17 @@ impl Explanation {
         self.val
     }
     
+    /// Returns the formula of the value.
+    pub fn formula(&self,) -> &str {
+        &self.formula
+    }
+
+    /// Returns the explanations of the sub expressions
+    /// of the formula, along with their names.
+    pub fn children(&self,) -> &[(String, Explanation)] {
+        &self.children
+    }
+
     /// Accessor for the description
     pub fn description(&mut self, description: &str) {
         self.description.clear();
@@ -62,8 +74,23 @@ impl Explanation {
         let &mut (_, ref mut child_experience) = self.children.last_mut().unwrap();
         child_experience
     }
+
+    /// Add the explanation of a sub query, like
+    /// one of the clauses of a `BooleanQuery`.
+    /// - name is the name of the component. It should 
+    ///   appear as `<the_name>` in the formula.
+    pub fn add_child_explanation(&mut self, name: &str, explanation: Explanation) {
+        self.children.push((String::from(name), explanation));
+    }
     
     
+    /// Encodes the explanation in JSON, for display in a UI.
+    ///
+    /// The children are encoded as an array of `[name, explanation]` pairs.
+    pub fn to_json(&self,) -> String {
+        json::encode(self).expect("Encoding an explanation cannot fail")
+    }
+
     /// Creates a `String` from the explanation.
     /// The subcomponent tree is represented by increasing 
     /// the indentation with the tree-depth.
@@ -85,4 +112,81 @@ impl fmt::Debug for Explanation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.format_with_indent(f, 0)
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, Term, TEXT};
+    use query::{Query, QueryParser, MultiTermQuery, SimilarityType};
+    use collector::TopCollector;
+    use Searcher;
+
+    #[test]
+    fn test_explanation_to_json() {
+        let mut explanation = Explanation::with_val(2f32);
+        explanation.description("sum");
+        explanation.set_formula("<a> + <b>");
+        explanation.add_child("a", 1.5f32);
+        explanation.add_child("b", 0.5f32);
+        assert_eq!(explanation.children().len(), 2);
+        assert_eq!(explanation.formula(), "<a> + <b>");
+        assert_eq!(
+            explanation.to_json(),
+            concat!(
+                r#"{"val":2.0,"description":"sum","formula":"<a> + <b>","children":["#,
+                r#"["a",{"val":1.5,"description":"","formula":"","children":[]}],"#,
+                r#"["b",{"val":0.5,"description":"","formula":"","children":[]}]]}"#));
+    }
+
+    fn check_explanations<Q: Query>(searcher: &Searcher, query: &Q) {
+        let mut top_collector = TopCollector::with_limit(10);
+        query.search(searcher, &mut top_collector).unwrap();
+        let score_docs = top_collector.score_docs();
+        assert!(!score_docs.is_empty());
+        for (score, doc_address) in score_docs {
+            let explanation = query.explain(searcher, &doc_address).unwrap();
+            assert!((explanation.val() - score).abs() < 0.001f32, "{:?} {:?} {}", query, explanation, score);
+        }
+    }
+
+    #[test]
+    fn test_explanation_matches_score() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT.set_boost(2f32));
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let docs = [
+                ("rust", "a language empowering everyone"),
+                ("red hat", "rust on linux, on red hat linux"),
+                ("java", "rust and java, java and rust"),
+                ("linux", "a kernel, and red hats"),
+            ];
+            for &(title, body) in &docs {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(title_field, body_field));
+        for query_str in &["rust", "rust linux", "title:rust^2.5 body:linux", "\"red hat\"", "+rust -java", "+rust +(linux java)"] {
+            let query = query_parser.parse_query(query_str).unwrap();
+            check_explanations(&searcher, &query);
+        }
+        let mut query = MultiTermQuery::from(vec!(
+            Term::from_field_text(title_field, "rust"),
+            Term::from_field_text(body_field, "rust"),
+        ));
+        check_explanations(&searcher, &query);
+        query.set_similarity_type(SimilarityType::bm25());
+        check_explanations(&searcher, &query);
+    }
+}
@@ -0,0 +1,81 @@
+use fst::Automaton;
+use schema::Field;
+
+
+/// State of a `FieldAutomaton`.
+pub enum FieldAutomatonState<S> {
+    /// The field byte has not been read yet.
+    Field,
+    /// The field byte matched, the value is being read.
+    Value(S),
+    /// The key belongs to another field.
+    Dead,
+}
+
+/// Automaton running an automaton over the values of 
+/// the terms of a field, within the term dictionary.
+///
+/// The keys of the term dictionary start with the byte
+/// of their field, which the wrapped automaton does not see.
+pub struct FieldAutomaton<A: Automaton> {
+    field: Field,
+    automaton: A,
+}
+
+impl<A: Automaton> FieldAutomaton<A> {
+    /// Wraps an automaton running over the values of the terms of `field`.
+    pub fn new(field: Field, automaton: A) -> FieldAutomaton<A> {
+        FieldAutomaton {
+            field: field,
+            automaton: automaton,
+        }
+    }
+}
+
+impl<A: Automaton> Automaton for FieldAutomaton<A> {
+    
+    type State = FieldAutomatonState<A::State>;
+
+    fn start(&self) -> Self::State {
+        FieldAutomatonState::Field
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        match *state {
+            FieldAutomatonState::Value(ref value_state) => self.automaton.is_match(value_state),
+            _ => false,
+        }
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        match *state {
+            FieldAutomatonState::Field => true,
+            FieldAutomatonState::Value(ref value_state) => self.automaton.can_match(value_state),
+            FieldAutomatonState::Dead => false,
+        }
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        match *state {
+            FieldAutomatonState::Value(ref value_state) => self.automaton.will_always_match(value_state),
+            _ => false,
+        }
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        match *state {
+            FieldAutomatonState::Field => {
+                if byte == self.field.0 {
+                    FieldAutomatonState::Value(self.automaton.start())
+                }
+                else {
+                    FieldAutomatonState::Dead
+                }
+            }
+            FieldAutomatonState::Value(ref value_state) => {
+                FieldAutomatonState::Value(self.automaton.accept(value_state, byte))
+            }
+            FieldAutomatonState::Dead => FieldAutomatonState::Dead,
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use Result;
+use Error;
+use schema::{Term, Field};
+use query::Query;
+use query::Occur;
+use query::{Scorer, ConstantScorer, BooleanScorer};
+use query::field_automaton::FieldAutomaton;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use postings::SegmentPostingsOption;
+use postings::SkipResult;
+use DocAddress;
+use query::Explanation;
+use fst::Automaton;
+use std::cmp;
+use std::str;
+use std::collections::BTreeSet;
+
+
+/// Default maximum number of terms a `FuzzyTermQuery`
+/// may expand to within a segment.
+pub const DEFAULT_FUZZY_MAX_EXPANSIONS: usize = 50;
+
+
+/// Query matching the documents containing a term within
+/// a given edit distance of a term, to be tolerant to typos.
+///
+/// The distance is the number of insertions, deletions and substitutions
+/// of characters needed to turn one term into the other. A transposition
+/// of two adjacent characters, like `hte` for `the`, can optionally 
+/// count as a single edit, rather than two substitutions.
+///
+/// A Levenshtein automaton is intersected with the term dictionary
+/// of each segment, and the postings of the matching terms are unioned.
+/// The number of terms the query may expand to within a segment is 
+/// limited: the search returns an error when the limit is exceeded.
+///
+/// Each of the matching terms contributes a constant score of `1`
+/// to the documents containing it.
+#[derive(Eq, PartialEq, Debug)]
+pub struct FuzzyTermQuery {
+    term: Term,
+    max_distance: u8,
+    transposition_cost_one: bool,
+    max_expansions: usize,
+}
+
+impl FuzzyTermQuery {
+
+    /// Creates a query matching the terms within 
+    /// a distance of `max_distance` from `term`.
+    ///
+    /// The term must be a text term.
+    ///
+    /// # Panics
+    /// If `max_distance` is greater than 2.
+    pub fn new(term: Term, max_distance: u8, transposition_cost_one: bool) -> FuzzyTermQuery {
+        assert!(max_distance <= 2, "The distance of a fuzzy query cannot exceed 2.");
+        FuzzyTermQuery {
+            term: term,
+            max_distance: max_distance,
+            transposition_cost_one: transposition_cost_one,
+            max_expansions: DEFAULT_FUZZY_MAX_EXPANSIONS,
+        }
+    }
+
+    /// Sets the maximum number of terms the query may expand to
+    /// within a segment.
+    ///
+    /// Defaults to `DEFAULT_FUZZY_MAX_EXPANSIONS`.
+    pub fn set_max_expansions(mut self, max_expansions: usize) -> FuzzyTermQuery {
+        self.max_expansions = max_expansions;
+        self
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.term.field()
+    }
+
+    /// Returns the terms of the segment matched by the query.
+    ///
+    /// # Errors
+    /// If the query matches more than `max_expansions` terms.
+    pub fn expanded_terms(&self, reader: &SegmentReader) -> Result<Vec<Term>> {
+        let text = String::from_utf8_lossy(self.term.value_bytes());
+        let automaton = LevenshteinAutomaton::new(&text, self.max_distance, self.transposition_cost_one);
+        let mut term_stream = reader.term_dict().search(FieldAutomaton::new(self.term.field(), automaton));
+        let mut terms = Vec::new();
+        while let Some((key, _)) = term_stream.next() {
+            if terms.len() == self.max_expansions {
+                return Err(Error::InvalidArgument(format!("The fuzzy query for {:?} matches more than {} terms.", self.term, self.max_expansions)));
+            }
+            terms.push(Term::from(key));
+        }
+        Ok(terms)
+    }
+
+    /// Returns the number of distinct terms of the index
+    /// matched by the query, to monitor the expansion of the query.
+    ///
+    /// The terms are expanded again, segment by segment.
+    pub fn num_expanded_terms(&self, searcher: &Searcher) -> Result<usize> {
+        let mut terms = BTreeSet::new();
+        for segment_reader in searcher.segment_readers() {
+            terms.extend(try!(self.expanded_terms(segment_reader)));
+        }
+        Ok(terms.len())
+    }
+}
+
+impl Query for FuzzyTermQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let mut scorers: Vec<(Occur, Box<Scorer + 'a>)> = Vec::new();
+        for term in try!(self.expanded_terms(reader)) {
+            if let Some(postings) = reader.read_postings(&term, SegmentPostingsOption::NoFreq) {
+                scorers.push((Occur::Should, Box::new(ConstantScorer::new(postings, 1f32))));
+            }
+        }
+        Ok(Box::new(BooleanScorer::new(scorers)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let mut scorer = try!(self.scorer(searcher, segment_reader));
+        Ok(if scorer.advance() && scorer.skip_next(doc_address.doc()) == SkipResult::Reached {
+            let mut explanation = Explanation::with_val(scorer.score());
+            explanation.description(&format!("Number of the terms of the document within a distance of {} of {:?}", self.max_distance, self.term));
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+/// Levenshtein automaton over the UTF-8 bytes of the values
+/// of the terms, supporting transpositions as a single edit.
+///
+/// The automaton computes the rows of the (optimal string alignment)
+/// distance matrix between the query and the key, one character
+/// of the key at a time. The distances are saturated at `max_distance + 1`.
+struct LevenshteinAutomaton {
+    query: Vec<char>,
+    max_distance: u8,
+    transpositions: bool,
+}
+
+#[derive(Clone)]
+struct LevenshteinState {
+    // distances between the prefixes of the query and the key.
+    row: Vec<u8>,
+    // the row before the last character of the key,
+    // for the transpositions.
+    prev_row: Vec<u8>,
+    prev_char: Option<char>,
+    // bytes of the character being decoded.
+    utf8_bytes: Vec<u8>,
+}
+
+/// Returns the length of the UTF-8 sequence starting with `byte`.
+fn utf8_len(byte: u8) -> Option<usize> {
+    if byte < 0x80 {
+        Some(1)
+    }
+    else if byte & 0xE0 == 0xC0 {
+        Some(2)
+    }
+    else if byte & 0xF0 == 0xE0 {
+        Some(3)
+    }
+    else if byte & 0xF8 == 0xF0 {
+        Some(4)
+    }
+    else {
+        None
+    }
+}
+
+impl LevenshteinAutomaton {
+
+    fn new(query: &str, max_distance: u8, transpositions: bool) -> LevenshteinAutomaton {
+        LevenshteinAutomaton {
+            query: query.chars().collect(),
+            max_distance: max_distance,
+            transpositions: transpositions,
+        }
+    }
+
+    /// Returns the state of a key that can never match.
+    fn dead_state(&self,) -> LevenshteinState {
+        let saturated = vec!(self.max_distance + 1; self.query.len() + 1);
+        LevenshteinState {
+            row: saturated.clone(),
+            prev_row: saturated,
+            prev_char: None,
+            utf8_bytes: Vec::new(),
+        }
+    }
+
+    fn accept_char(&self, state: &LevenshteinState, c: char) -> LevenshteinState {
+        let saturated = self.max_distance + 1;
+        let mut row = Vec::with_capacity(state.row.len());
+        row.push(cmp::min(state.row[0] + 1, saturated));
+        for i in 1..state.row.len() {
+            let substitution_cost = if self.query[i - 1] == c { 0 } else { 1 };
+            let mut distance = cmp::min(
+                cmp::min(state.row[i] + 1, row[i - 1] + 1),
+                state.row[i - 1] + substitution_cost);
+            if self.transpositions && i > 1
+                && state.prev_char == Some(self.query[i - 1])
+                && self.query[i - 2] == c {
+                distance = cmp::min(distance, state.prev_row[i - 2] + 1);
+            }
+            row.push(cmp::min(distance, saturated));
+        }
+        LevenshteinState {
+            row: row,
+            prev_row: state.row.clone(),
+            prev_char: Some(c),
+            utf8_bytes: Vec::new(),
+        }
+    }
+}
+
+impl Automaton for LevenshteinAutomaton {
+
+    type State = LevenshteinState;
+
+    fn start(&self) -> LevenshteinState {
+        let row: Vec<u8> = (0..self.query.len() + 1)
+            .map(|i| cmp::min(i, self.max_distance as usize + 1) as u8)
+            .collect();
+        LevenshteinState {
+            prev_row: vec!(self.max_distance + 1; row.len()),
+            row: row,
+            prev_char: None,
+            utf8_bytes: Vec::new(),
+        }
+    }
+
+    fn is_match(&self, state: &LevenshteinState) -> bool {
+        state.utf8_bytes.is_empty() && state.row[self.query.len()] <= self.max_distance
+    }
+
+    fn can_match(&self, state: &LevenshteinState) -> bool {
+        // a transposition may reach the next row from the previous one.
+        state.row.iter().any(|&distance| distance <= self.max_distance)
+            || (self.transpositions && state.prev_row.iter().any(|&distance| distance < self.max_distance))
+    }
+
+    fn accept(&self, state: &LevenshteinState, byte: u8) -> LevenshteinState {
+        let mut utf8_bytes = state.utf8_bytes.clone();
+        utf8_bytes.push(byte);
+        match utf8_len(utf8_bytes[0]) {
+            Some(len) if len == utf8_bytes.len() => {
+                match str::from_utf8(&utf8_bytes).ok().and_then(|text| text.chars().next()) {
+                    Some(c) => self.accept_char(state, c),
+                    None => self.dead_state(),
+                }
+            }
+            Some(len) if len > utf8_bytes.len() => {
+                let mut next_state = state.clone();
+                next_state.utf8_bytes = utf8_bytes;
+                next_state
+            }
+            _ => self.dead_state(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use collector::tests::TestCollector;
+
+    /// Optimal string alignment distance, computed with the whole matrix.
+    fn distance(left: &str, right: &str, transpositions: bool) -> usize {
+        let left: Vec<char> = left.chars().collect();
+        let right: Vec<char> = right.chars().collect();
+        let mut d = vec!(vec!(0usize; right.len() + 1); left.len() + 1);
+        for i in 0..left.len() + 1 {
+            d[i][0] = i;
+        }
+        for j in 0..right.len() + 1 {
+            d[0][j] = j;
+        }
+        for i in 1..left.len() + 1 {
+            for j in 1..right.len() + 1 {
+                let cost = if left[i - 1] == right[j - 1] { 0 } else { 1 };
+                d[i][j] = cmp::min(cmp::min(d[i - 1][j] + 1, d[i][j - 1] + 1), d[i - 1][j - 1] + cost);
+                if transpositions && i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                    d[i][j] = cmp::min(d[i][j], d[i - 2][j - 2] + 1);
+                }
+            }
+        }
+        d[left.len()][right.len()]
+    }
+
+    fn automaton_matches(automaton: &LevenshteinAutomaton, key: &str) -> bool {
+        let mut state = automaton.start();
+        for &byte in key.as_bytes() {
+            if !automaton.can_match(&state) {
+                return false;
+            }
+            state = automaton.accept(&state, byte);
+        }
+        automaton.is_match(&state)
+    }
+
+    #[test]
+    fn test_levenshtein_automaton() {
+        let words = ["", "a", "ab", "ba", "abc", "acb", "bca", "été", "ét", "tée", "日本", "本日", "abcd", "badc", "xyz"];
+        for query in &words {
+            for key in &words {
+                for &max_distance in &[0u8, 1u8, 2u8] {
+                    for &transpositions in &[false, true] {
+                        let automaton = LevenshteinAutomaton::new(query, max_distance, transpositions);
+                        let expected = distance(query, key, transpositions) <= max_distance as usize;
+                        assert_eq!(automaton_matches(&automaton, key), expected, "{:?} {:?} {} {}", query, key, max_distance, transpositions);
+                    }
+                }
+            }
+        }
+        let automaton = LevenshteinAutomaton::new("ab", 2, true);
+        let state = automaton.accept(&automaton.start(), 0xFFu8);
+        assert!(!automaton.can_match(&state));
+    }
+
+    #[test]
+    fn test_fuzzy_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let other_field = schema_builder.add_text_field("other", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["the color", "teh colour", "het colors", "tea", "them", "colorful"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            let mut doc = Document::default();
+            doc.add_text(other_field, "the");
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search = |query: &FuzzyTermQuery| {
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        let fuzzy = |text: &str, max_distance: u8, transposition_cost_one: bool| {
+            FuzzyTermQuery::new(Term::from_field_text(text_field, text), max_distance, transposition_cost_one)
+        };
+        assert_eq!(search(&fuzzy("the", 0, false)), vec!(0));
+        // `teh` is a transposition of `the`.
+        assert_eq!(search(&fuzzy("the", 1, false)), vec!(0, 4));
+        assert_eq!(search(&fuzzy("the", 1, true)), vec!(0, 1, 4));
+        assert_eq!(search(&fuzzy("the", 2, false)), vec!(0, 1, 2, 3, 4));
+        assert_eq!(search(&fuzzy("color", 1, false)), vec!(0, 1, 2));
+        assert!(search(&fuzzy("blue", 2, true)).is_empty());
+        {
+            let query = fuzzy("color", 2, false);
+            assert_eq!(query.num_expanded_terms(&searcher).unwrap(), 3);
+            let segment_reader = searcher.segment_reader(0);
+            let expanded_terms = query.expanded_terms(segment_reader).unwrap();
+            assert_eq!(expanded_terms, vec!(
+                Term::from_field_text(text_field, "color"),
+                Term::from_field_text(text_field, "colors"),
+                Term::from_field_text(text_field, "colour"),
+            ));
+        }
+        {
+            // each matching term adds a score of 1.
+            let query = fuzzy("the", 1, true);
+            assert_eq!(query.explain(&searcher, &DocAddress(0, 0)).unwrap().val(), 1f32);
+            let query = fuzzy("colors", 1, true);
+            assert_eq!(search(&query), vec!(0, 2));
+            let query = fuzzy("color", 1, true).set_max_expansions(3);
+            assert_eq!(query.explain(&searcher, &DocAddress(0, 2)).unwrap().val(), 1f32);
+        }
+        match fuzzy("the", 2, true).set_max_expansions(3).search(&searcher, &mut TestCollector::default()) {
+            Err(Error::InvalidArgument(_)) => {}
+            _ => {
+                panic!("Expected the max expansions to be exceeded");
+            }
+        }
+        assert!(fuzzy("the", 2, true).num_expanded_terms(&searcher).unwrap() > 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fuzzy_query_max_distance() {
+        FuzzyTermQuery::new(Term::from_field_text(Field(0), "the"), 3, true);
+    }
+}
@@ -5,28 +5,101 @@
 
 mod query;
 mod multi_term_query;
+mod boolean_query;
+mod boolean_scorer;
+mod disjunction_max_query;
+mod disjunction_max_scorer;
+mod boost_query;
+mod constant_score_query;
+mod phrase_query;
+mod phrase_prefix_query;
+mod range_query;
+mod prefix_query;
+mod fuzzy_query;
+mod regex_query;
+mod all_query;
+mod term_set_query;
+mod field_automaton;
+mod segment_docs_builder;
+mod exists_query;
+mod precomputed_filter_query;
 mod multi_term_accumulator;
 mod similarity_explainer;
 mod scorer;
 mod query_parser;
 mod explanation;
 mod tfidf;
+mod bm25;
 mod occur;
 mod daat_multiterm_scorer;
 mod similarity;
+mod similarity_model;
 
-pub use self::similarity::Similarity;
+pub use self::similarity::{Similarity, SimilarityType};
+pub use self::similarity_model::{SimilarityModel, BoxedSimilarityModel, ModelSimilarity, TermStats};
 
 pub use self::daat_multiterm_scorer::DAATMultiTermScorer;
 
 pub use self::occur::Occur;
 pub use self::query::Query;
 pub use self::multi_term_query::MultiTermQuery;
+pub use self::boolean_query::BooleanQuery;
+pub use self::boolean_scorer::BooleanScorer;
+pub use self::disjunction_max_query::DisjunctionMaxQuery;
+pub use self::disjunction_max_scorer::DisjunctionMaxScorer;
+pub use self::boost_query::{BoostQuery, BoostScorer};
+pub use self::constant_score_query::ConstantScoreQuery;
+pub use self::phrase_query::{PhraseQuery, PhrasePostings};
+pub use self::phrase_prefix_query::{PhrasePrefixQuery, DEFAULT_PHRASE_PREFIX_MAX_EXPANSIONS};
+pub use self::range_query::{RangeQuery, RangeBound};
+pub use self::prefix_query::{PrefixQuery, DEFAULT_MAX_EXPANSIONS};
+pub use self::fuzzy_query::{FuzzyTermQuery, DEFAULT_FUZZY_MAX_EXPANSIONS};
+pub use self::regex_query::{RegexQuery, DEFAULT_REGEX_SIZE_LIMIT};
+pub use self::all_query::AllQuery;
+pub use self::term_set_query::TermSetQuery;
+pub use self::exists_query::ExistsQuery;
+pub use self::precomputed_filter_query::PreComputedFilterQuery;
 pub use self::similarity_explainer::SimilarityExplainer;
 pub use self::tfidf::TfIdf;
+pub use self::bm25::{Bm25, DEFAULT_BM25_K1, DEFAULT_BM25_B};
 
-pub use self::scorer::Scorer;
+pub use self::scorer::{Scorer, ConstantScorer};
 pub use self::query_parser::QueryParser;
+pub use self::query_parser::Operator;
 pub use self::explanation::Explanation;
 pub use self::multi_term_accumulator::MultiTermAccumulator;
-pub use self::query_parser::ParsingError;
\ No newline at end of file
+pub use self::query_parser::ParsingError;
+
+#[cfg(test)]
+pub mod tests {
+
+    use Score;
+    use DocAddress;
+    use core::Index;
+    use core::searcher::Searcher;
+    use schema::{SchemaBuilder, Document, Field, TextOptions};
+    use collector::TopCollector;
+    use collector::tests::create_index as create_segmented_index;
+    use super::Query;
+
+    /// Creates an index in RAM, with a single text field `text` configured
+    /// by `text_options`, and with a segment per element of `segments`,
+    /// each of their texts being a document.
+    pub fn create_index<T: AsRef<str>>(text_options: TextOptions, segments: &[Vec<T>]) -> (Index, Field) {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = create_segmented_index(schema_builder.build().unwrap(), segments, |text| {
+            let mut doc = Document::default();
+            doc.add_text(text_field, text.as_ref());
+            doc
+        });
+        (index, text_field)
+    }
+
+    /// Returns the `limit` best documents for `query`, with their scores.
+    pub fn ranking<TQuery: Query>(searcher: &Searcher, query: &TQuery, limit: usize) -> Vec<(Score, DocAddress)> {
+        let mut top_collector = TopCollector::with_limit(limit);
+        query.search(searcher, &mut top_collector).unwrap();
+        top_collector.score_docs()
+    }
+}
@@ -12,3 +12,13 @@ pub trait MultiTermAccumulator {
     /// Resets the accumulator
     fn clear(&mut self,);
 }
+
+impl<TAccumulator: MultiTermAccumulator + ?Sized> MultiTermAccumulator for Box<TAccumulator> {
+    fn update(&mut self, term_ord: usize, term_freq: u32, fieldnorm: u32) {
+        (**self).update(term_ord, term_freq, fieldnorm)
+    }
+
+    fn clear(&mut self,) {
+        (**self).clear()
+    }
+}
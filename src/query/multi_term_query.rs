@@ -1,6 +1,7 @@
 use Result;
 use Error;
 use schema::Term;
+use schema::Schema;
 use query::Query;
 use common::TimerTree;
 use common::OpenTimer;
@@ -10,8 +11,13 @@ use SegmentLocalId;
 use core::SegmentReader;
 use query::SimilarityExplainer;
 use postings::SegmentPostings;
-use postings::DocSet;
+use postings::{DocSet, Postings};
 use query::TfIdf;
+use query::Bm25;
+use query::{Similarity, SimilarityType};
+use query::{BoxedSimilarityModel, TermStats};
+use std::cmp;
+use schema::Field;
 use postings::SkipResult;
 use ScoredDoc;
 use query::Scorer;
@@ -21,67 +27,284 @@ use query::Explanation;
 use query::occur::Occur;
 use postings::SegmentPostingsOption;
 use query::DAATMultiTermScorer;
+use query::PhraseQuery;
+use query::PhrasePostings;
+use DocId;
 
 
 /// Query involving one or more terms.
-#[derive(Eq, PartialEq, Debug)]
+///
+/// Besides terms, the query may have phrases as clauses,
+/// each phrase being scored as a single term.
+///
+/// The documents are scored with `TfIdf`, unless another
+/// similarity is set with `set_similarity_type`, or a
+/// `SimilarityModel` is set on the index, see `Index::set_similarity_model`.
+#[derive(PartialEq, Debug)]
 pub struct MultiTermQuery {
     occur_terms: Vec<(Occur, Term)>,    
+    occur_phrases: Vec<(Occur, PhraseQuery)>,
+    similarity_type: SimilarityType,
 }
 
+
+/// Postings of one of the clauses of a `MultiTermQuery`.
+enum ClausePostings<'a> {
+    Empty,
+    Term(SegmentPostings<'a>),
+    Phrase(PhrasePostings<'a>),
+}
+
+impl<'a> DocSet for ClausePostings<'a> {
+    fn advance(&mut self,) -> bool {
+        match *self {
+            ClausePostings::Empty => false,
+            ClausePostings::Term(ref mut postings) => postings.advance(),
+            ClausePostings::Phrase(ref mut postings) => postings.advance(),
+        }
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        match *self {
+            ClausePostings::Empty => SkipResult::End,
+            ClausePostings::Term(ref mut postings) => postings.skip_next(target),
+            ClausePostings::Phrase(ref mut postings) => postings.skip_next(target),
+        }
+    }
+
+    fn doc(&self,) -> DocId {
+        match *self {
+            ClausePostings::Empty => 0,
+            ClausePostings::Term(ref postings) => postings.doc(),
+            ClausePostings::Phrase(ref postings) => postings.doc(),
+        }
+    }
+}
+
+impl<'a> Postings for ClausePostings<'a> {
+    fn term_freq(&self,) -> u32 {
+        match *self {
+            ClausePostings::Empty => 0,
+            ClausePostings::Term(ref postings) => postings.term_freq(),
+            ClausePostings::Phrase(ref postings) => postings.term_freq(),
+        }
+    }
+
+    fn positions(&self) -> &[u32] {
+        match *self {
+            ClausePostings::Empty => &[],
+            ClausePostings::Term(ref postings) => postings.positions(),
+            ClausePostings::Phrase(ref postings) => postings.positions(),
+        }
+    }
+
+    fn block_max(&self,) -> Option<(DocId, u32)> {
+        match *self {
+            ClausePostings::Term(ref postings) => postings.block_max(),
+            _ => None,
+        }
+    }
+}
+
+
 impl MultiTermQuery {
     
-    /// Accessor for the number of terms
+    /// Accessor for the number of terms.
+    ///
+    /// A phrase counts as a single term.
     pub fn num_terms(&self,) -> usize {
-        self.occur_terms.len()
+        self.occur_terms.len() + self.occur_phrases.len()
+    }
+
+    /// Adds a phrase to the clauses of the query.
+    pub fn add_phrase(&mut self, occur: Occur, phrase_query: PhraseQuery) {
+        self.occur_phrases.push((occur, phrase_query));
     }
     
-    /// Builds the similitude object
-    fn similitude(&self, searcher: &Searcher) -> TfIdf {
-        let num_terms = self.num_terms();
-        let num_docs = searcher.num_docs() as f32;
-        let idfs: Vec<f32> = self.occur_terms
+    /// Sets the similarity scoring the documents.
+    pub fn set_similarity_type(&mut self, similarity_type: SimilarityType) {
+        self.similarity_type = similarity_type;
+    }
+
+    /// Returns the similarity scoring the documents.
+    pub fn similarity_type(&self,) -> SimilarityType {
+        self.similarity_type
+    }
+
+    /// Returns the idf of each of the clauses.
+    ///
+    /// The idf of a phrase is the sum of the idfs of its terms.
+    fn idfs<F: Fn(&Term) -> f32>(&self, idf: F) -> Vec<f32> {
+        let mut idfs: Vec<f32> = self.occur_terms
             .iter()
-            .map(|&(_, ref term)| searcher.doc_freq(term))
-            .map(|doc_freq| {
-                if doc_freq == 0 {
-                    1.
-                }
-                else {
-                    1. + ( num_docs / (doc_freq as f32) ).ln()
-                }
-            })
+            .map(|&(_, ref term)| idf(term))
             .collect();
-        let query_coords = (0..num_terms + 1)
-            .map(|i| (i as f32) / (num_terms as f32))
+        idfs.extend(self.occur_phrases
+            .iter()
+            .map(|&(_, ref phrase_query)| {
+                phrase_query
+                    .terms()
+                    .iter()
+                    .map(|&(_, ref term)| idf(term))
+                    .fold(0f32, |sum, term_idf| sum + term_idf)
+            }));
+        idfs
+    }
+
+    /// Returns the field of each of the clauses.
+    fn fields(&self,) -> Vec<Field> {
+        let mut fields: Vec<Field> = self.occur_terms
+            .iter()
+            .map(|&(_, ref term)| term.field())
             .collect();
+        fields.extend(self.occur_phrases
+            .iter()
+            .map(|&(_, ref phrase_query)| phrase_query.field()));
+        fields
+    }
+
+    /// Returns the name of each of the clauses, for `explain`.
+    fn term_names(&self,) -> Vec<String> {
         // TODO have the actual terms in these names
-        let term_names = self.occur_terms
+        let mut term_names: Vec<String> = self.occur_terms
             .iter()
             .map(|&(_, ref term)| format!("{:?}", &term))
             .collect();
+        term_names.extend(self.occur_phrases
+            .iter()
+            .map(|&(_, ref phrase_query)| format!("{:?}", phrase_query)));
+        term_names
+    }
+
+    /// Returns the statistics of each of the clauses.
+    ///
+    /// The statistics of a phrase are the smallest ones of its terms.
+    fn term_stats(&self, searcher: &Searcher, schema: &Schema) -> Vec<TermStats> {
+        let num_docs = searcher.num_docs();
+        let stats = |field: Field, doc_freq: u32, total_term_freq: Option<u64>| {
+            let field_entry = schema.get_field_entry(field);
+            TermStats {
+                doc_freq: doc_freq,
+                total_term_freq: total_term_freq,
+                num_docs: num_docs,
+                field_stats: searcher.field_stats(field),
+                has_fieldnorms: field_entry.has_fieldnorms(),
+                boost: field_entry.boost(),
+            }
+        };
+        let mut term_stats: Vec<TermStats> = self.occur_terms
+            .iter()
+            .map(|&(_, ref term)| {
+                let (doc_freq, total_term_freq) = searcher.doc_freq_and_total_tf(term);
+                stats(term.field(), doc_freq, total_term_freq)
+            })
+            .collect();
+        term_stats.extend(self.occur_phrases
+            .iter()
+            .map(|&(_, ref phrase_query)| {
+                let mut doc_freq = u32::max_value();
+                let mut total_term_freq = Some(u64::max_value());
+                for &(_, ref term) in phrase_query.terms() {
+                    let (term_doc_freq, term_total_term_freq) = searcher.doc_freq_and_total_tf(term);
+                    doc_freq = cmp::min(doc_freq, term_doc_freq);
+                    total_term_freq = match (total_term_freq, term_total_term_freq) {
+                        (Some(total_term_freq), Some(term_total_term_freq)) => Some(cmp::min(total_term_freq, term_total_term_freq)),
+                        _ => None,
+                    };
+                }
+                stats(phrase_query.field(), doc_freq, total_term_freq)
+            }));
+        term_stats
+    }
+
+    /// Builds the similitude object of the `SimilarityModel`
+    /// set on the index of the searcher.
+    fn boxed_similitude(&self, searcher: &Searcher, schema: &Schema, similarity_model: &BoxedSimilarityModel) -> Box<Similarity> {
+        similarity_model.boxed_similarity(&self.term_stats(searcher, schema), self.term_names())
+    }
+
+    /// Builds the similitude object
+    ///
+    /// The score contribution of each term is boosted by
+    /// the boost of its field.
+    fn similitude(&self, searcher: &Searcher, schema: &Schema) -> TfIdf {
+        let num_terms = self.num_terms();
+        let num_docs = searcher.num_docs();
+        let idfs = self.idfs(|term: &Term| TfIdf::idf(searcher.doc_freq(term), num_docs));
+        let query_coords = (0..num_terms + 1)
+            .map(|i| (i as f32) / (num_terms as f32))
+            .collect();
+        let boosts = self.fields()
+            .into_iter()
+            .map(|field| schema.get_field_entry(field).boost())
+            .collect();
         let mut tfidf = TfIdf::new(query_coords, idfs);
-        tfidf.set_term_names(term_names);
+        tfidf.set_term_names(self.term_names());
+        tfidf.set_boosts(boosts);
         tfidf
     }
+
+    /// Builds the `Bm25` similitude object
+    ///
+    /// The average field norms are the ones of the `FieldStats`
+    /// of the searcher. The fields without field norms are
+    /// not normalized.
+    fn bm25_similitude(&self, searcher: &Searcher, schema: &Schema, k1: f32, b: f32) -> Bm25 {
+        let num_docs = searcher.num_docs();
+        let idfs = self.idfs(|term: &Term| Bm25::idf(searcher.doc_freq(term), num_docs));
+        let fields = self.fields();
+        let average_fieldnorms = fields
+            .iter()
+            .map(|&field| {
+                if schema.get_field_entry(field).has_fieldnorms() {
+                    searcher.field_stats(field).average_num_tokens()
+                }
+                else {
+                    // the field norm of all of the documents is `1`.
+                    1f32
+                }
+            })
+            .collect();
+        let boosts = fields
+            .into_iter()
+            .map(|field| schema.get_field_entry(field).boost())
+            .collect();
+        let mut bm25 = Bm25::new(k1, b, idfs, average_fieldnorms);
+        bm25.set_term_names(self.term_names());
+        bm25.set_boosts(boosts);
+        bm25
+    }
     
     
     /// Search the segment.
     fn search_segment<'a, 'b, TAccumulator: MultiTermAccumulator>(
-            &'b self,
+            &self,
             reader: &'b SegmentReader,
             accumulator: TAccumulator,
-            mut timer: OpenTimer<'a>) -> Result<DAATMultiTermScorer<SegmentPostings, TAccumulator>> {
+            postings_option: SegmentPostingsOption,
+            mut timer: OpenTimer<'a>) -> Result<DAATMultiTermScorer<ClausePostings<'b>, TAccumulator>> {
+        // the clauses keep their ordinal, even when
+        // they do not appear in the segment.
         let mut postings_and_fieldnorms = Vec::with_capacity(self.num_terms());
         {
             let mut decode_timer = timer.open("decode_all");
             for &(occur, ref term) in &self.occur_terms {
                 let _decode_one_timer = decode_timer.open("decode_one");
-                if let Some(postings) = reader.read_postings(term, SegmentPostingsOption::Freq) {
-                    let field = term.field();
-                    let fieldnorm_reader = try!(reader.get_fieldnorms_reader(field));
-                    postings_and_fieldnorms.push((occur, postings, fieldnorm_reader));
-                }
+                let postings = match reader.read_postings(term, postings_option) {
+                    Some(postings) => ClausePostings::Term(postings),
+                    None => ClausePostings::Empty,
+                };
+                let fieldnorm_reader = try!(reader.get_fieldnorm_reader(term.field()));
+                postings_and_fieldnorms.push((occur, postings, fieldnorm_reader));
+            }
+            for &(occur, ref phrase_query) in &self.occur_phrases {
+                let _decode_one_timer = decode_timer.open("decode_one");
+                let postings = match try!(phrase_query.phrase_postings(reader)) {
+                    Some(postings) => ClausePostings::Phrase(postings),
+                    None => ClausePostings::Empty,
+                };
+                let fieldnorm_reader = try!(reader.get_fieldnorm_reader(phrase_query.field()));
+                postings_and_fieldnorms.push((occur, postings, fieldnorm_reader));
             }
         }
         if postings_and_fieldnorms.len() > 64 {
@@ -90,6 +313,115 @@ impl MultiTermQuery {
         }
         Ok(DAATMultiTermScorer::new(postings_and_fieldnorms, accumulator))
     }
+
+    fn boxed_scorer<'a, TSimilarity: Similarity + 'a>(
+            &self,
+            reader: &'a SegmentReader,
+            similarity: TSimilarity,
+            postings_option: SegmentPostingsOption) -> Result<Box<Scorer + 'a>> {
+        let mut timer_tree = TimerTree::default();
+        let scorer = try!(
+            self.search_segment(
+                reader,
+                similarity,
+                postings_option,
+                timer_tree.open("get_postings"))
+        );
+        Ok(Box::new(scorer))
+    }
+
+    fn scorer_with_option<'a>(
+            &self,
+            searcher: &Searcher,
+            reader: &'a SegmentReader,
+            postings_option: SegmentPostingsOption) -> Result<Box<Scorer + 'a>> {
+        if let Some(similarity_model) = searcher.similarity_model() {
+            return self.boxed_scorer(reader, self.boxed_similitude(searcher, reader.schema(), similarity_model), postings_option);
+        }
+        match self.similarity_type {
+            SimilarityType::TfIdf => {
+                self.boxed_scorer(reader, self.similitude(searcher, reader.schema()), postings_option)
+            }
+            SimilarityType::Bm25 { k1, b } => {
+                self.boxed_scorer(reader, self.bm25_similitude(searcher, reader.schema(), k1, b), postings_option)
+            }
+        }
+    }
+
+    fn explain_with_similarity<TSimilarity: Similarity>(
+            &self,
+            segment_reader: &SegmentReader,
+            similarity: TSimilarity,
+            doc_address: &DocAddress) -> Result<Explanation> {
+        let similitude = SimilarityExplainer::from(similarity);
+        let mut timer_tree = TimerTree::default();
+        let mut postings = try!(
+            self.search_segment(
+                segment_reader,
+                similitude,
+                SegmentPostingsOption::Freq,
+                timer_tree.open("explain"))
+        );
+        // the scorer is advanced first, as it does not
+        // point to a document before.
+        let matches = postings.advance() && postings.skip_next(doc_address.doc()) == SkipResult::Reached;
+        if !matches {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            return Ok(explanation);
+        }
+        Ok(postings.scorer().explain_score())
+    }
+
+    fn search_with_similarity<C, TSimilarity, F>(
+            &self,
+            searcher: &Searcher,
+            collector: &mut C,
+            similitude: F) -> Result<TimerTree>
+        where C: Collector, TSimilarity: Similarity, F: Fn(&SegmentReader) -> TSimilarity {
+        let mut timer_tree = TimerTree::default();        
+        {
+            let mut search_timer = timer_tree.open("search");
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                if collector.should_terminate() {
+                    break;
+                }
+                let mut segment_search_timer = search_timer.open("segment_search");
+                {
+                    let _ = segment_search_timer.open("set_segment");
+                    try!(collector.set_segment(segment_ord as SegmentLocalId, &segment_reader));
+                }
+                let mut postings = try!(
+                    self.search_segment(
+                        segment_reader,
+                        similitude(segment_reader),
+                        SegmentPostingsOption::Freq,
+                        segment_search_timer.open("get_postings"))
+                );
+                {
+                    let _collection_timer = segment_search_timer.open("collection");
+                    loop {
+                        // the documents that cannot change the result
+                        // of the collector are skipped block by block.
+                        let has_next = match collector.min_score() {
+                            Some(min_score) => postings.advance_above(min_score),
+                            None => postings.advance(),
+                        };
+                        if !has_next {
+                            break;
+                        }
+                        let scored_doc = ScoredDoc(postings.score(), postings.doc());
+                        collector.collect(scored_doc);
+                        if collector.should_terminate() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        try!(collector.finish());
+        Ok(timer_tree)
+    }
 }
 
 
@@ -97,6 +429,8 @@ impl From<Vec<(Occur, Term)>> for MultiTermQuery {
     fn from(occur_terms: Vec<(Occur, Term)>) -> MultiTermQuery {
         MultiTermQuery {
             occur_terms: occur_terms,
+            occur_phrases: Vec::new(),
+            similarity_type: SimilarityType::default(),
         }
     }
 }
@@ -109,67 +443,355 @@ impl From<Vec<Term>> for MultiTermQuery {
             .collect();
         MultiTermQuery {
             occur_terms: should_terms,
+            occur_phrases: Vec::new(),
+            similarity_type: SimilarityType::default(),
         }
     }
 }
 
 impl Query for MultiTermQuery {
 
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        self.scorer_with_option(searcher, reader, SegmentPostingsOption::Freq)
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        // the term frequencies only matter to the scores,
+        // their blocks are skipped without being decoded.
+        self.scorer_with_option(searcher, reader, SegmentPostingsOption::NoFreq)
+    }
+
     fn explain(
         &self,
         searcher: &Searcher,
         doc_address: &DocAddress) -> Result<Explanation> {
-            let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
-            let similitude = SimilarityExplainer::from(self.similitude(searcher));
-            let mut timer_tree = TimerTree::default();
-            let mut postings = try!(
-                self.search_segment(
-                    segment_reader,
-                    similitude,
-                    timer_tree.open("explain"))
-            );
-            Ok(match postings.skip_next(doc_address.doc()) {
-                SkipResult::Reached => {
-                    let scorer = postings.scorer();
-                    scorer.explain_score()
-                }
-                _ => {
-                    let mut explanation = Explanation::with_val(0f32);
-                    explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
-                    explanation
-                }
-            }) 
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        if let Some(similarity_model) = searcher.similarity_model() {
+            let similitude = self.boxed_similitude(searcher, segment_reader.schema(), similarity_model);
+            return self.explain_with_similarity(segment_reader, similitude, doc_address);
+        }
+        match self.similarity_type {
+            SimilarityType::TfIdf => {
+                let similitude = self.similitude(searcher, segment_reader.schema());
+                self.explain_with_similarity(segment_reader, similitude, doc_address)
+            }
+            SimilarityType::Bm25 { k1, b } => {
+                let similitude = self.bm25_similitude(searcher, segment_reader.schema(), k1, b);
+                self.explain_with_similarity(segment_reader, similitude, doc_address)
+            }
+        }
     }
 
     fn search<C: Collector>(
         &self,
         searcher: &Searcher,
         collector: &mut C) -> Result<TimerTree> {
-        let mut timer_tree = TimerTree::default();        
-        {
-            let mut search_timer = timer_tree.open("search");
-            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
-                let mut segment_search_timer = search_timer.open("segment_search");
-                {
-                    let _ = segment_search_timer.open("set_segment");
-                    try!(collector.set_segment(segment_ord as SegmentLocalId, &segment_reader));
-                }
-                let mut postings = try!(
-                    self.search_segment(
-                        segment_reader,
-                        self.similitude(searcher),
-                        segment_search_timer.open("get_postings"))
-                );
-                {
-                    let _collection_timer = segment_search_timer.open("collection");
-                    while postings.advance() {
-                        let scored_doc = ScoredDoc(postings.score(), postings.doc());
-                        collector.collect(scored_doc);
-                    }
-                }
+        if let Some(similarity_model) = searcher.similarity_model() {
+            return self.search_with_similarity(searcher, collector, |segment_reader| {
+                self.boxed_similitude(searcher, segment_reader.schema(), similarity_model)
+            });
+        }
+        match self.similarity_type {
+            SimilarityType::TfIdf => {
+                self.search_with_similarity(searcher, collector, |segment_reader| {
+                    self.similitude(searcher, segment_reader.schema())
+                })
+            }
+            SimilarityType::Bm25 { k1, b } => {
+                self.search_with_similarity(searcher, collector, |segment_reader| {
+                    self.bm25_similitude(searcher, segment_reader.schema(), k1, b)
+                })
             }
         }
-        Ok(timer_tree)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{Field, TEXT};
+    use query::tests::create_index;
+    use collector::{chain, TopCollector, CountCollector};
+    use query::{DEFAULT_BM25_K1, SimilarityModel, QueryParser};
+    use Score;
+    use Index;
+    use test::Bencher;
+
+    /// Top collector counting the documents it is pushed.
+    struct CountingTopCollector {
+        top_collector: TopCollector,
+        count: usize,
+    }
+
+    impl Collector for CountingTopCollector {
+        fn set_segment(&mut self, segment_local_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
+            self.top_collector.set_segment(segment_local_id, segment)
+        }
+
+        fn collect(&mut self, scored_doc: ScoredDoc) {
+            self.count += 1;
+            self.top_collector.collect(scored_doc);
+        }
+
+        fn min_score(&self,) -> Option<Score> {
+            self.top_collector.min_score()
+        }
+
+        fn should_terminate(&self,) -> bool {
+            self.top_collector.should_terminate()
+        }
+    }
+
+    fn disjunction(text_field: Field) -> MultiTermQuery {
+        MultiTermQuery::from(vec!(
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(text_field, "b"),
+            Term::from_field_text(text_field, "c"),
+        ))
+    }
+
+    #[test]
+    fn test_multi_term_query_block_max_pruning() {
+        let texts: Vec<&str> = (0..2_000)
+            .map(|i| if i == 1 || i == 2 || i == 3 { "a a a b b b c c c" } else { "a b c d e f g h" })
+            .collect();
+        let (index, text_field) = create_index(TEXT, &[texts]);
+        let searcher = index.searcher();
+        let query = disjunction(text_field);
+        let mut top_collector = TopCollector::with_limit(3);
+        let mut count_collector = CountCollector::default();
+        {
+            // the count collector needs all of the documents.
+            let mut collectors = chain().push(&mut top_collector).push(&mut count_collector);
+            query.search(&searcher, &mut collectors).unwrap();
+        }
+        assert_eq!(count_collector.count(), 2_000);
+        let mut counting_top_collector = CountingTopCollector {
+            top_collector: TopCollector::with_limit(3),
+            count: 0,
+        };
+        query.search(&searcher, &mut counting_top_collector).unwrap();
+        assert_eq!(counting_top_collector.top_collector.score_docs(), top_collector.score_docs());
+        let mut top_docs = top_collector.docs();
+        top_docs.sort();
+        assert_eq!(top_docs, vec!(DocAddress(0, 1), DocAddress(0, 2), DocAddress(0, 3)));
+        // only the first block of documents may hold a document
+        // scoring more than the top 3.
+        assert!(counting_top_collector.count <= 128);
+    }
+
+    #[test]
+    fn test_multi_term_query_count() {
+        let (index, text_field) = create_index(TEXT, &[common_terms_texts(1_000)]);
+        let searcher = index.searcher();
+        let check_count = |query: MultiTermQuery| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(query.count(&searcher).unwrap(), count_collector.count());
+        };
+        check_count(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a"))));
+        check_count(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "missing"))));
+        check_count(disjunction(text_field));
+        check_count(MultiTermQuery::from(vec!(
+            (Occur::Must, Term::from_field_text(text_field, "a")),
+            (Occur::MustNot, Term::from_field_text(text_field, "b")),
+        )));
+        assert_eq!(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "d"))).count(&searcher).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_multi_term_query_bm25() {
+        let texts = ["rust", "rust rust rust x", "x y z w", "y z w v"];
+        let (index, text_field) = create_index(TEXT, &[texts.to_vec()]);
+        let searcher = index.searcher();
+        let mut query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "rust")));
+        assert_eq!(query.similarity_type(), SimilarityType::TfIdf);
+        let top_docs = |query: &MultiTermQuery| {
+            let mut top_collector = TopCollector::with_limit(2);
+            query.search(&searcher, &mut top_collector).unwrap();
+            top_collector.docs()
+        };
+        // `TfIdf` favors the shortest document.
+        assert_eq!(top_docs(&query), vec!(DocAddress(0, 0), DocAddress(0, 1)));
+        // `Bm25` favors the repeated term, as the document
+        // is not much longer than the average.
+        query.set_similarity_type(SimilarityType::bm25());
+        assert_eq!(top_docs(&query), vec!(DocAddress(0, 1), DocAddress(0, 0)));
+        // without the normalization by the field norms,
+        // only the term frequencies matter.
+        query.set_similarity_type(SimilarityType::Bm25 { k1: DEFAULT_BM25_K1, b: 0f32 });
+        assert_eq!(top_docs(&query), vec!(DocAddress(0, 1), DocAddress(0, 0)));
+        query.set_similarity_type(SimilarityType::bm25());
+        let mut top_collector = TopCollector::with_limit(2);
+        query.search(&searcher, &mut top_collector).unwrap();
+        for (score, doc_address) in top_collector.score_docs() {
+            let explanation = query.explain(&searcher, &doc_address).unwrap();
+            assert!((explanation.val() - score).abs() < 0.001f32);
+        }
+    }
+
+    /// Language model with Dirichlet smoothing.
+    #[derive(Clone)]
+    struct DirichletModel {
+        mu: f32,
+    }
+
+    impl SimilarityModel for DirichletModel {
+        // probability of the term in the whole field.
+        type Weight = f32;
+
+        fn term_weight(&self, term_stats: &TermStats) -> f32 {
+            let total_term_freq = term_stats.total_term_freq.unwrap_or(term_stats.doc_freq as u64);
+            total_term_freq as f32 / cmp::max(term_stats.field_stats.total_num_tokens, 1) as f32
+        }
+
+        fn term_score(&self, weight: &f32, term_freq: u32, fieldnorm: u32) -> Score {
+            (1f32 + term_freq as f32 / (self.mu * weight)).ln() + (self.mu / (fieldnorm as f32 + self.mu)).ln()
+        }
+    }
+
+    #[test]
+    fn test_multi_term_query_similarity_model() {
+        let texts = ["rust", "rust rust rust x", "x y z w", "y z w v rust"];
+        let (index, text_field) = create_index(TEXT, &[texts.to_vec()]);
+        let mut query = MultiTermQuery::from(vec!(
+            Term::from_field_text(text_field, "rust"),
+            Term::from_field_text(text_field, "x"),
+        ));
+        fn score_docs<TQuery: Query>(index: &Index, query: &TQuery) -> Vec<(Score, DocAddress)> {
+            let mut top_collector = TopCollector::with_limit(4);
+            query.search(&index.searcher(), &mut top_collector).unwrap();
+            top_collector.score_docs()
+        }
+        let check_scores = |score_docs: Vec<(Score, DocAddress)>, expected_score_docs: Vec<(Score, DocAddress)>| {
+            assert_eq!(score_docs.len(), expected_score_docs.len());
+            for (&(score, doc_address), &(expected_score, expected_doc_address)) in score_docs.iter().zip(expected_score_docs.iter()) {
+                assert_eq!(doc_address, expected_doc_address);
+                assert!((score - expected_score).abs() < 0.001f32);
+            }
+        };
+        // the built-in similarities give the same scores
+        // when set as the model of the index.
+        let tfidf_score_docs = score_docs(&index, &query);
+        query.set_similarity_type(SimilarityType::bm25());
+        let bm25_score_docs = score_docs(&index, &query);
+        index.set_similarity_model(TfIdf::default()).unwrap();
+        check_scores(score_docs(&index, &query), tfidf_score_docs);
+        index.set_similarity_model(Bm25::default()).unwrap();
+        check_scores(score_docs(&index, &query), bm25_score_docs.clone());
+        // the queries of the query parser use the model as well.
+        let query_parser = QueryParser::new(index.schema(), vec!(text_field));
+        let parsed_query = query_parser.parse_query("rust x").unwrap();
+        check_scores(score_docs(&index, &parsed_query), bm25_score_docs);
+        // a custom model.
+        index.set_similarity_model(DirichletModel { mu: 2_000f32 }).unwrap();
+        let searcher = index.searcher();
+        let mut top_collector = TopCollector::with_limit(1);
+        query.search(&searcher, &mut top_collector).unwrap();
+        assert_eq!(top_collector.docs(), vec!(DocAddress(0, 1)));
+        let (score, doc_address) = top_collector.score_docs()[0];
+        let explanation = query.explain(&searcher, &doc_address).unwrap();
+        assert!((explanation.val() - score).abs() < 0.001f32);
+        check_scores(score_docs(&index, &parsed_query), score_docs(&index, &query));
+        let segment_reader = searcher.segment_reader(0);
+        let mut scorer = query.scorer(&searcher, segment_reader).unwrap();
+        let mut docs = Vec::new();
+        while scorer.advance() {
+            docs.push(scorer.doc());
+        }
+        assert_eq!(docs, vec!(0, 1, 2, 3));
+    }
+
+    fn common_terms_texts(num_docs: usize) -> Vec<String> {
+        (0..num_docs).map(common_terms_text).collect()
+    }
+
+    fn common_terms_text(i: usize) -> String {
+        // each of the terms appears in most of the documents,
+        // some of them repeating it.
+        let mut text = String::new();
+        for (&word, &modulo) in ["a", "b", "c"].iter().zip([3, 5, 7].iter()) {
+            if i % modulo != 1 {
+                text.push_str(word);
+                text.push(' ');
+            }
+            if i % (modulo * 131) == 0 {
+                text.push_str(&format!("{} {} ", word, word));
+            }
+        }
+        text.push_str("d e f g");
+        text
+    }
+
+    #[bench]
+    fn bench_disjunction_top_k_block_max(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &[common_terms_texts(100_000)]);
+        let searcher = index.searcher();
+        let query = disjunction(text_field);
+        b.iter(|| {
+            let mut top_collector = TopCollector::with_limit(10);
+            query.search(&searcher, &mut top_collector).unwrap();
+            top_collector.docs()
+        });
+    }
+
+    #[bench]
+    fn bench_disjunction_top_k_tfidf_model(b: &mut Bencher) {
+        // same as `bench_disjunction_top_k_block_max`,
+        // with `TfIdf` set as the model of the index, so that the
+        // scores go through a boxed `Similarity`.
+        let (index, text_field) = create_index(TEXT, &[common_terms_texts(100_000)]);
+        index.set_similarity_model(TfIdf::default()).unwrap();
+        let searcher = index.searcher();
+        let query = disjunction(text_field);
+        b.iter(|| {
+            let mut top_collector = TopCollector::with_limit(10);
+            query.search(&searcher, &mut top_collector).unwrap();
+            top_collector.docs()
+        });
+    }
+
+    #[bench]
+    fn bench_disjunction_top_k_exhaustive(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &[common_terms_texts(100_000)]);
+        let searcher = index.searcher();
+        let query = disjunction(text_field);
+        b.iter(|| {
+            let mut top_collector = TopCollector::with_limit(10);
+            let mut count_collector = CountCollector::default();
+            {
+                let mut collectors = chain().push(&mut top_collector).push(&mut count_collector);
+                query.search(&searcher, &mut collectors).unwrap();
+            }
+            top_collector.docs()
+        });
+    }
+
+    #[bench]
+    fn bench_term_query_count_collector(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &[common_terms_texts(100_000)]);
+        let searcher = index.searcher();
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+
+    #[bench]
+    fn bench_term_query_count(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &[common_terms_texts(100_000)]);
+        let searcher = index.searcher();
+        let query = MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a")));
+        b.iter(|| query.count(&searcher).unwrap());
+    }
+}
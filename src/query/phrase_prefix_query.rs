@@ -0,0 +1,264 @@
+use Result;
+use Error;
+use schema::{Term, Field};
+use query::Query;
+use query::Occur;
+use query::Scorer;
+use query::{PhraseQuery, BooleanQuery};
+use core::searcher::Searcher;
+use core::SegmentReader;
+use datastruct::{TermDictionary, TermDictionaryKeys};
+use DocAddress;
+use query::Explanation;
+
+
+/// Default maximum number of terms the last term of
+/// a `PhrasePrefixQuery` may expand to within a segment.
+pub const DEFAULT_PHRASE_PREFIX_MAX_EXPANSIONS: usize = 50;
+
+
+/// Query matching the documents containing a phrase whose
+/// last term is a prefix, like `"harry pot"*` matching `harry potter`,
+/// typically for search-as-you-type.
+///
+/// The terms starting with the prefix are streamed out of the term
+/// dictionary of each segment, and the documents are those matching
+/// the phrase once its last term is replaced by any of them.
+/// The last term is always a prefix, even if it is a complete term:
+/// `"harry potter"*` also matches `harry pottery`.
+/// Each of these phrases is scored as a `PhraseQuery`, and the score
+/// of a document is the sum of the scores of the phrases it matches.
+///
+/// Like for the `PrefixQuery`, the number of terms the prefix may
+/// expand to within a segment is limited, and the search returns
+/// an error when the limit is exceeded, rather than ignoring some of the terms.
+///
+/// Like phrase queries, the field must have been indexed with
+/// `TextIndexingOptions::TokenizedWithFreqAndPosition`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PhrasePrefixQuery {
+    // the terms, sorted by offset. The last one is the prefix.
+    terms: Vec<(u32, Term)>,
+    max_expansions: usize,
+}
+
+impl PhrasePrefixQuery {
+
+    /// Creates a query matching the terms at consecutive
+    /// positions, the last term being a prefix.
+    ///
+    /// # Panics
+    /// If `terms` is empty, or if the terms do not
+    /// belong to the same field.
+    pub fn new(terms: Vec<Term>) -> PhrasePrefixQuery {
+        let terms_with_offsets = terms
+            .into_iter()
+            .enumerate()
+            .map(|(offset, term)| (offset as u32, term))
+            .collect();
+        PhrasePrefixQuery::new_with_offsets(terms_with_offsets)
+    }
+
+    /// Creates a query matching the terms at the given offsets
+    /// relatively to each other, the term with the largest offset
+    /// being a prefix.
+    ///
+    /// # Panics
+    /// If `terms` is empty, or if the terms do not
+    /// belong to the same field.
+    pub fn new_with_offsets(terms: Vec<(u32, Term)>) -> PhrasePrefixQuery {
+        // the terms are checked and sorted as the ones of a phrase.
+        let phrase_query = PhraseQuery::new_with_offsets(terms);
+        PhrasePrefixQuery {
+            terms: phrase_query.terms().to_vec(),
+            max_expansions: DEFAULT_PHRASE_PREFIX_MAX_EXPANSIONS,
+        }
+    }
+
+    /// Sets the maximum number of terms the prefix may expand to
+    /// within a segment.
+    ///
+    /// Defaults to `DEFAULT_PHRASE_PREFIX_MAX_EXPANSIONS`.
+    pub fn set_max_expansions(mut self, max_expansions: usize) -> PhrasePrefixQuery {
+        self.max_expansions = max_expansions;
+        self
+    }
+
+    /// Returns the maximum number of terms the prefix may expand to.
+    pub fn max_expansions(&self,) -> usize {
+        self.max_expansions
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.terms[0].1.field()
+    }
+
+    /// Returns the terms of the query, along with their offsets.
+    /// The last term is the prefix.
+    pub fn terms(&self,) -> &[(u32, Term)] {
+        &self.terms
+    }
+
+    /// Returns the prefix, as a term.
+    pub fn prefix(&self,) -> &Term {
+        &self.terms[self.terms.len() - 1].1
+    }
+
+    /// Returns the terms of the segment starting with the prefix,
+    /// the prefix itself included if it is a term of the segment.
+    ///
+    /// # Errors
+    /// If the prefix matches more than `max_expansions` terms.
+    pub fn expanded_terms(&self, reader: &SegmentReader) -> Result<Vec<Term>> {
+        let prefix = self.prefix();
+        let prefix_bytes = prefix.as_slice();
+        let mut keys = reader.term_infos().keys_from(prefix_bytes);
+        let mut terms = Vec::new();
+        while let Some(key) = keys.next() {
+            if !key.starts_with(prefix_bytes) {
+                break;
+            }
+            if terms.len() == self.max_expansions {
+                return Err(Error::InvalidArgument(format!("The prefix {:?} matches more than {} terms.", prefix, self.max_expansions)));
+            }
+            terms.push(Term::from(key));
+        }
+        Ok(terms)
+    }
+
+    /// Returns the query of the phrases of the segment,
+    /// one per expanded term.
+    ///
+    /// # Errors
+    /// If the positions of the field were not indexed,
+    /// or if the prefix matches more than `max_expansions` terms.
+    fn segment_query(&self, reader: &SegmentReader) -> Result<BooleanQuery<PhraseQuery>> {
+        let field_entry = reader.schema().get_field_entry(self.field());
+        if !field_entry.has_positions() {
+            return Err(Error::InvalidArgument(format!("positions not indexed for field {:?}", field_entry.name())));
+        }
+        let (&(prefix_offset, _), phrase_terms) = self.terms.split_last().unwrap();
+        let clauses = try!(self.expanded_terms(reader))
+            .into_iter()
+            .map(|term| {
+                let mut terms = phrase_terms.to_vec();
+                terms.push((prefix_offset, term));
+                (Occur::Should, PhraseQuery::new_with_offsets(terms))
+            })
+            .collect::<Vec<_>>();
+        Ok(BooleanQuery::from(clauses))
+    }
+}
+
+impl Query for PhrasePrefixQuery {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        try!(self.segment_query(reader)).scorer(searcher, reader)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        try!(self.segment_query(segment_reader)).explain(searcher, doc_address)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{TextOptions, TextIndexingOptions, TEXT};
+    use query::tests::create_index;
+    use collector::tests::TestCollector;
+    use DocId;
+
+    #[test]
+    fn test_phrase_prefix_query() {
+        let (index, title_field) = create_index(TEXT, &[vec!(
+            "harry potter",
+            "harry potter and the goblet of fire",
+            "the potter harry",
+            "harry pottery",
+            "harry houdini",
+            "dirty harry",
+            "harry and potter",
+        )]);
+        let searcher = index.searcher();
+        let search = |query: PhrasePrefixQuery| -> Vec<DocId> {
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        let query = |words: &[&str]| {
+            let terms = words
+                .iter()
+                .map(|word| Term::from_field_text(title_field, word))
+                .collect();
+            PhrasePrefixQuery::new(terms)
+        };
+        assert_eq!(search(query(&["harry", "pot"])), vec!(0, 1, 3));
+        // the last term is a prefix, even when it is a complete term.
+        assert_eq!(search(query(&["harry", "potter"])), vec!(0, 1, 3));
+        assert_eq!(search(query(&["harry", "h"])), vec!(4));
+        assert_eq!(search(query(&["the", "goblet", "o"])), vec!(1));
+        assert!(search(query(&["harry", "z"])).is_empty());
+        assert!(search(query(&["potter", "harry", "pot"])).is_empty());
+        // a single term is a prefix.
+        assert_eq!(search(query(&["har"])), vec!(0, 1, 2, 3, 4, 5, 6));
+        // a hole in the phrase.
+        let hole_query = PhrasePrefixQuery::new_with_offsets(vec!(
+            (2, Term::from_field_text(title_field, "pot")),
+            (0, Term::from_field_text(title_field, "harry"))));
+        assert_eq!(hole_query.prefix(), &Term::from_field_text(title_field, "pot"));
+        assert_eq!(search(hole_query), vec!(6));
+        {
+            // exceeding the expansions is an error.
+            let segment_reader = searcher.segment_reader(0);
+            let pot_query = query(&["harry", "pot"]);
+            assert_eq!(pot_query.expanded_terms(segment_reader).unwrap(), vec!(
+                Term::from_field_text(title_field, "potter"),
+                Term::from_field_text(title_field, "pottery")));
+            assert_eq!(pot_query.clone().set_max_expansions(2).expanded_terms(segment_reader).unwrap().len(), 2);
+            assert!(pot_query.clone().set_max_expansions(1).expanded_terms(segment_reader).is_err());
+            let mut test_collector = TestCollector::default();
+            assert!(pot_query.set_max_expansions(1).search(&searcher, &mut test_collector).is_err());
+        }
+        {
+            // the score is the one of the matching phrase.
+            let phrase_query = PhraseQuery::new(vec!(
+                Term::from_field_text(title_field, "harry"),
+                Term::from_field_text(title_field, "pottery")));
+            let phrase_score = phrase_query.explain(&searcher, &DocAddress(0, 3)).unwrap().val();
+            let phrase_prefix_score = query(&["harry", "pot"]).explain(&searcher, &DocAddress(0, 3)).unwrap().val();
+            assert!(phrase_score > 0f32);
+            assert!((phrase_score - phrase_prefix_score).abs() < 0.0001f32);
+            assert_eq!(query(&["harry", "pot"]).explain(&searcher, &DocAddress(0, 4)).unwrap().val(), 0f32);
+        }
+    }
+
+    #[test]
+    fn test_phrase_prefix_query_requires_positions() {
+        let text_options = TextOptions::default().set_indexing_options(TextIndexingOptions::TokenizedWithFreq);
+        let (index, title_field) = create_index(text_options, &[vec!("harry potter")]);
+        let searcher = index.searcher();
+        let query = PhrasePrefixQuery::new(vec!(
+            Term::from_field_text(title_field, "harry"),
+            Term::from_field_text(title_field, "pot")));
+        let mut test_collector = TestCollector::default();
+        match query.search(&searcher, &mut test_collector) {
+            Err(Error::InvalidArgument(msg)) => {
+                assert_eq!(msg, "positions not indexed for field \"text\"");
+            }
+            _ => {
+                panic!("Expected an error, as positions are not indexed");
+            }
+        }
+    }
+}
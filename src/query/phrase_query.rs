@@ -1,86 +1,471 @@
-use schema::Term;
+use Result;
+use schema::{Term, Field};
 use query::Query;
+use query::MultiTermQuery;
+use query::Occur;
+use query::Scorer;
 use common::TimerTree;
-use common::OpenTimer;
-use std::io;
 use core::searcher::Searcher;
 use collector::Collector;
-use core::searcher::SegmentLocalId;
 use core::SegmentReader;
-use postings::Postings;
-use postings::SegmentPostings;
-use postings::intersection;
+use postings::{Postings, DocSet, SegmentPostings, SkipResult};
+use DocId;
+use DocAddress;
+use query::Explanation;
 
+
+/// Query matching the documents containing a sequence of terms,
+/// like `"barack obama"`.
+///
+/// The terms must belong to the same field, and this field must
+/// have been indexed with `TextIndexingOptions::TokenizedWithFreqAndPosition`.
+/// The postings of the terms are intersected, and the positions
+/// of the terms are then checked within each of the common documents.
+///
+/// Each term comes with its offset within the phrase, so that
+/// a phrase may have holes, or several terms at the same offset, as
+/// emitted by a tokenizer removing stop words or emitting synonyms.
+/// `PhraseQuery::new` gives consecutive offsets to the terms.
+///
+/// The slop is the number of positions the terms may be shifted by
+/// in total, so that with a slop of 1, `"barack obama"` also matches
+/// `barack h. obama`. The terms must appear in the order of the phrase.
+/// The default slop is 0: the terms must be found exactly at their offset.
+///
+/// The phrase is scored as a single term, whose term frequency is
+/// the number of matches of the phrase within the document,
+/// and whose idf is the sum of the idfs of its terms.
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct PhraseQuery {
-    terms: Vec<Term>,    
+    terms: Vec<(u32, Term)>,
+    slop: u32,
 }
 
-impl Query for PhraseQuery {
-     
-    fn search<C: Collector>(&self, searcher: &Searcher, collector: &mut C) -> io::Result<TimerTree> {
-        let mut timer_tree = TimerTree::default();
+impl PhraseQuery {
+
+    /// Creates a phrase query matching the terms
+    /// at consecutive positions.
+    ///
+    /// # Panics
+    /// If `terms` is empty, or if the terms do not
+    /// belong to the same field.
+    pub fn new(terms: Vec<Term>) -> PhraseQuery {
+        let terms_with_offsets = terms
+            .into_iter()
+            .enumerate()
+            .map(|(offset, term)| (offset as u32, term))
+            .collect();
+        PhraseQuery::new_with_offsets(terms_with_offsets)
+    }
+
+    /// Creates a phrase query matching the terms at the given
+    /// offsets relatively to each other.
+    ///
+    /// # Panics
+    /// If `terms` is empty, or if the terms do not
+    /// belong to the same field.
+    pub fn new_with_offsets(mut terms: Vec<(u32, Term)>) -> PhraseQuery {
+        assert!(!terms.is_empty(), "A phrase query needs at least one term.");
         {
-            let mut search_timer = timer_tree.open("search");
-            for (segment_ord, segment_reader) in searcher.segments().iter().enumerate() {
-                let mut segment_search_timer = search_timer.open("segment_search");
-                {
-                    let _ = segment_search_timer.open("set_segment");
-                    try!(collector.set_segment(segment_ord as SegmentLocalId, &segment_reader));
+            let field = terms[0].1.field();
+            assert!(terms.iter().all(|&(_, ref term)| term.field() == field), "The terms of a phrase query must belong to the same field.");
+        }
+        terms.sort_by_key(|&(offset, _)| offset);
+        let min_offset = terms[0].0;
+        for term in &mut terms {
+            term.0 -= min_offset;
+        }
+        PhraseQuery {
+            terms: terms,
+            slop: 0,
+        }
+    }
+
+    /// Sets the slop of the phrase, that is the number of
+    /// positions the terms may be moved by.
+    pub fn set_slop(mut self, slop: u32) -> PhraseQuery {
+        self.slop = slop;
+        self
+    }
+
+    /// Returns the slop of the phrase.
+    pub fn slop(&self,) -> u32 {
+        self.slop
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.terms[0].1.field()
+    }
+
+    /// Returns the terms of the phrase, along with their offsets.
+    pub fn terms(&self,) -> &[(u32, Term)] {
+        &self.terms
+    }
+
+    /// Returns the postings of the phrase within a segment,
+    /// or `None` if one of its terms does not appear in the segment.
+    ///
+    /// # Errors
+    /// If the positions of the field were not indexed.
+    pub fn phrase_postings<'a>(&self, reader: &'a SegmentReader) -> Result<Option<PhrasePostings<'a>>> {
+        let mut postings = Vec::with_capacity(self.terms.len());
+        for &(offset, ref term) in &self.terms {
+            match try!(reader.read_postings_with_positions(term)) {
+                Some(term_postings) => {
+                    postings.push((offset, term_postings));
                 }
-                let mut postings = self.search_segment(segment_reader, segment_search_timer.open("get_postings"));
-                {
-                    let _collection_timer = segment_search_timer.open("collection");
-                    while postings.next() {
-                        collector.collect(postings.doc());
-                    }
+                None => {
+                    return Ok(None);
                 }
             }
         }
-        Ok(timer_tree)
+        Ok(PhrasePostings::new(postings, self.slop))
     }
 }
 
-impl PhraseQuery {
-    pub fn new(terms: Vec<Term>) -> PhraseQuery {
-        PhraseQuery {
-            terms: terms,
-        }
+impl Query for PhraseQuery {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let mut multi_term_query = MultiTermQuery::from(Vec::<Term>::new());
+        multi_term_query.add_phrase(Occur::Must, self.clone());
+        multi_term_query.scorer(searcher, reader)
     }
-    
-    fn search_segment<'a, 'b>(&'b self, reader: &'b SegmentReader, mut timer: OpenTimer<'a>) -> Box<Postings + 'b> {
-        if self.terms.len() == 1 {
-            match reader.get_term(&self.terms[0]) {
-                Some(term_info) => {
-                    let postings: SegmentPostings<'b> = reader.read_postings(&term_info);
-                    Box::new(postings)
-                },
-                None => {
-                    Box::new(SegmentPostings::empty())
-                },
+
+    fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        collector: &mut C) -> Result<TimerTree> {
+        let mut multi_term_query = MultiTermQuery::from(Vec::<Term>::new());
+        multi_term_query.add_phrase(Occur::Must, self.clone());
+        multi_term_query.search(searcher, collector)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let mut multi_term_query = MultiTermQuery::from(Vec::<Term>::new());
+        multi_term_query.add_phrase(Occur::Must, self.clone());
+        multi_term_query.explain(searcher, doc_address)
+    }
+}
+
+
+/// Postings of a phrase within a segment.
+///
+/// Its term frequency is the number of matches of the phrase
+/// in the current document, and its positions are the positions
+/// of the first term of each of the matches.
+pub struct PhrasePostings<'a> {
+    // the postings of the terms, sorted by offset.
+    postings: Vec<(u32, SegmentPostings<'a>)>,
+    slop: u32,
+    cursors: Vec<usize>,
+    positions: Vec<u32>,
+    started: bool,
+    finished: bool,
+}
+
+impl<'a> PhrasePostings<'a> {
+
+    /// Returns `None` if one the postings is empty.
+    fn new(mut postings: Vec<(u32, SegmentPostings<'a>)>, slop: u32) -> Option<PhrasePostings<'a>> {
+        for &mut (_, ref mut term_postings) in &mut postings {
+            if !term_postings.advance() {
+                return None;
             }
-        } else {
-            let mut segment_postings: Vec<SegmentPostings> = Vec::new();
-            {
-                let mut decode_timer = timer.open("decode_all");
-                for term in self.terms.iter() {
-                    match reader.get_term(term) {
-                        Some(term_info) => {
-                            let _decode_one_timer = decode_timer.open("decode_one");
-                            let segment_posting = reader.read_postings_with_positions(&term_info);
-                            segment_postings.push(segment_posting);
-                        }
-                        None => {
-                            // currently this is a strict intersection.
-                            return Box::new(SegmentPostings::empty());
-                        }
+        }
+        let num_terms = postings.len();
+        Some(PhrasePostings {
+            postings: postings,
+            slop: slop,
+            cursors: vec!(0; num_terms),
+            positions: Vec::new(),
+            started: false,
+            finished: false,
+        })
+    }
+
+    /// Moves all of the postings to the first document they
+    /// have in common, starting from the current document of
+    /// the first postings.
+    ///
+    /// Returns false if there is no such document.
+    fn align(&mut self,) -> bool {
+        let mut target = self.postings[0].1.doc();
+        loop {
+            let mut aligned = true;
+            for &mut (_, ref mut term_postings) in &mut self.postings {
+                match term_postings.skip_next(target) {
+                    SkipResult::Reached => {}
+                    SkipResult::OverStep => {
+                        target = term_postings.doc();
+                        aligned = false;
+                    }
+                    SkipResult::End => {
+                        return false;
                     }
                 }
             }
-            Box::new(intersection(segment_postings))
+            if aligned {
+                return true;
+            }
+        }
+    }
+
+    /// Moves to the first document matching the phrase, starting
+    /// from the current document of the first postings.
+    ///
+    /// Returns false if there is no such document.
+    fn find_match(&mut self,) -> bool {
+        loop {
+            if !self.align() {
+                self.finished = true;
+                return false;
+            }
+            if self.compute_matches() {
+                return true;
+            }
+            if !self.postings[0].1.advance() {
+                self.finished = true;
+                return false;
+            }
+        }
+    }
+
+    /// Computes the matches of the phrase within the current document.
+    ///
+    /// For each position of the first term, the following terms are
+    /// matched greedily to their first position that does not come
+    /// before the previous term, once shifted by their offset.
+    /// The match is accepted if the total shift does not exceed the slop.
+    /// As the greedy choices only move forward when the position of the
+    /// first term does, the positions of each term are read once.
+    ///
+    /// Returns true iff the phrase matches at least once.
+    fn compute_matches(&mut self,) -> bool {
+        self.positions.clear();
+        for cursor in &mut self.cursors {
+            *cursor = 0;
+        }
+        let slop = self.slop as i64;
+        let postings = &self.postings;
+        let cursors = &mut self.cursors;
+        'starts: for &first_position in postings[0].1.positions() {
+            // the first term has an offset of 0.
+            let start = first_position as i64;
+            let mut previous = start;
+            for (ord, &(offset, ref term_postings)) in postings.iter().enumerate().skip(1) {
+                let positions = term_postings.positions();
+                let offset = offset as i64;
+                let cursor = &mut cursors[ord];
+                while *cursor < positions.len() && (positions[*cursor] as i64) - offset < previous {
+                    *cursor += 1;
+                }
+                if *cursor == positions.len() {
+                    // the following starts cannot match either.
+                    break 'starts;
+                }
+                previous = (positions[*cursor] as i64) - offset;
+                if previous - start > slop {
+                    continue 'starts;
+                }
+            }
+            self.positions.push(first_position);
         }
+        !self.positions.is_empty()
     }
 }
 
+impl<'a> DocSet for PhrasePostings<'a> {
 
+    fn advance(&mut self,) -> bool {
+        if self.finished {
+            return false;
+        }
+        if self.started && !self.postings[0].1.advance() {
+            self.finished = true;
+            return false;
+        }
+        self.started = true;
+        self.find_match()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        if self.finished {
+            return SkipResult::End;
+        }
+        if self.doc() >= target {
+            return if self.doc() == target { SkipResult::Reached } else { SkipResult::OverStep };
+        }
+        if self.postings[0].1.skip_next(target) == SkipResult::End {
+            self.finished = true;
+            return SkipResult::End;
+        }
+        if !self.find_match() {
+            return SkipResult::End;
+        }
+        if self.doc() == target {
+            SkipResult::Reached
+        }
+        else {
+            SkipResult::OverStep
+        }
+    }
 
+    fn doc(&self,) -> DocId {
+        self.postings[0].1.doc()
+    }
+}
 
+impl<'a> Postings for PhrasePostings<'a> {
+
+    fn term_freq(&self,) -> u32 {
+        self.positions.len() as u32
+    }
+
+    fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{self, TextOptions, TextIndexingOptions};
+    use query::tests::create_index;
+    use collector::tests::TestCollector;
+    use query::QueryParser;
+    use collector::CountCollector;
+    use Error;
+    use test::Bencher;
+
+    #[test]
+    fn test_phrase_query() {
+        let (index, text_field) = create_index(schema::TEXT, &[vec!(
+            "barack obama",
+            "obama barack",
+            "barack hussein obama",
+            "michelle obama and barack obama",
+            "barack",
+            "barack barack obama obama",
+        )]);
+        let searcher = index.searcher();
+        let search = |words: &[&str], slop: u32| {
+            let terms = words
+                .iter()
+                .map(|word| Term::from_field_text(text_field, word))
+                .collect();
+            let phrase_query = PhraseQuery::new(terms).set_slop(slop);
+            let mut test_collector = TestCollector::default();
+            phrase_query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search(&["barack", "obama"], 0), vec!(0, 3, 5));
+        assert_eq!(search(&["barack", "obama"], 1), vec!(0, 2, 3, 5));
+        // the terms must appear in order.
+        assert_eq!(search(&["obama", "barack"], 0), vec!(1));
+        assert_eq!(search(&["barack", "barack", "obama"], 0), vec!(5));
+        assert_eq!(search(&["barack"], 0), vec!(0, 1, 2, 3, 4, 5));
+        assert!(search(&["barack", "clinton"], 3).is_empty());
+    }
+
+    #[test]
+    fn test_phrase_query_parser() {
+        let (index, text_field) = create_index(schema::TEXT, &[vec!(
+            "barack obama",
+            "barack hussein obama",
+            "michelle obama and barack obama",
+            "obama barack",
+        )]);
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("\"Barack Obama\""), vec!(0, 2));
+        assert_eq!(search("\"barack obama\"~1"), vec!(0, 1, 2));
+        assert_eq!(search("+\"barack obama\" -michelle"), vec!(0));
+        assert_eq!(search("\"obama barack\" hussein"), vec!(1, 3));
+        assert!(search("+\"barack clinton\" obama").is_empty());
+    }
+
+    #[test]
+    fn test_phrase_postings() {
+        let (index, text_field) = create_index(schema::TEXT, &[vec!("a b c a b a x b c")]);
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let matches = |terms: Vec<(u32, &str)>, slop: u32| {
+            let terms = terms
+                .into_iter()
+                .map(|(offset, word)| (offset, Term::from_field_text(text_field, word)))
+                .collect();
+            let phrase_query = PhraseQuery::new_with_offsets(terms).set_slop(slop);
+            let mut phrase_postings = phrase_query.phrase_postings(segment_reader).unwrap().unwrap();
+            if phrase_postings.advance() {
+                assert_eq!(phrase_postings.term_freq() as usize, phrase_postings.positions().len());
+                phrase_postings.positions().to_vec()
+            }
+            else {
+                Vec::new()
+            }
+        };
+        assert_eq!(matches(vec!((0, "a"), (1, "b")), 0), vec!(0, 3));
+        assert_eq!(matches(vec!((0, "a"), (1, "b")), 1), vec!(0, 3, 5));
+        // phrases with a hole, given in any order.
+        assert_eq!(matches(vec!((2, "c"), (0, "a")), 0), vec!(0));
+        assert_eq!(matches(vec!((0, "a"), (2, "c")), 1), vec!(0, 5));
+        assert_eq!(matches(vec!((1, "b"), (2, "c")), 0), vec!(1, 7));
+        assert!(matches(vec!((0, "c"), (1, "a"), (2, "x")), 0).is_empty());
+        assert_eq!(matches(vec!((0, "c"), (1, "a"), (2, "x")), 2), vec!(2));
+    }
+
+    #[test]
+    fn test_phrase_query_requires_positions() {
+        let text_options = TextOptions::default().set_indexing_options(TextIndexingOptions::TokenizedWithFreq);
+        let (index, text_field) = create_index(text_options, &[vec!("barack obama")]);
+        let searcher = index.searcher();
+        let phrase_query = PhraseQuery::new(vec!(
+            Term::from_field_text(text_field, "barack"),
+            Term::from_field_text(text_field, "obama")));
+        let mut test_collector = TestCollector::default();
+        match phrase_query.search(&searcher, &mut test_collector) {
+            Err(Error::InvalidArgument(msg)) => {
+                assert_eq!(msg, "positions not indexed for field \"text\"");
+            }
+            _ => {
+                panic!("Expected an error, as positions are not indexed");
+            }
+        }
+    }
+
+    #[bench]
+    fn bench_phrase_query_rare_term(b: &mut Bencher) {
+        // all of the documents contain "a", but only a few of them
+        // contain the phrase, so that most of the blocks
+        // of positions of "a" are skipped.
+        let texts: Vec<String> = (0..500_000)
+            .map(|i| if i % 10_000 == 9_999 { "c a b" } else { "a c" })
+            .map(|text| text.to_string())
+            .collect();
+        let (index, text_field) = create_index(schema::TEXT, &[texts]);
+        let searcher = index.searcher();
+        let phrase_query = PhraseQuery::new(vec!(
+            Term::from_field_text(text_field, "a"),
+            Term::from_field_text(text_field, "b")));
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            phrase_query.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 50);
+        });
+    }
+}
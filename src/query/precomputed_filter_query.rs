@@ -0,0 +1,222 @@
+use Result;
+use Error;
+use std::fmt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use query::Explanation;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use core::SegmentId;
+use common::BitSet;
+use postings::BitSetDocSet;
+use DocAddress;
+
+
+/// Query matching the documents of precomputed bitsets,
+/// typically recorded by a `BitSetCollector`, so that an
+/// expensive filter can be computed once and combined as
+/// a `Occur::Must` clause with many other queries.
+///
+/// The matching documents get a score of `0`, so that the
+/// filter does not change the scores of the query it restricts.
+///
+/// The bitsets are only valid for the segments they were built
+/// on: searching a searcher with other segments, like after a
+/// commit or a merge, returns `Error::InvalidArgument`.
+pub struct PreComputedFilterQuery {
+    // shared with the scorers, which outlive the borrow of the query.
+    bitsets: HashMap<SegmentId, Arc<BitSet>>,
+}
+
+impl PreComputedFilterQuery {
+
+    /// Creates a query matching the documents of the bitset
+    /// of each segment, keyed by the id of the segment.
+    pub fn new(bitsets: HashMap<SegmentId, BitSet>) -> PreComputedFilterQuery {
+        PreComputedFilterQuery {
+            bitsets: bitsets
+                .into_iter()
+                .map(|(segment_id, bitset)| (segment_id, Arc::new(bitset)))
+                .collect(),
+        }
+    }
+
+    // returns the bitset of the segment, after checking that
+    // the segments of the searcher are the ones of the bitsets.
+    fn segment_bitset(&self, searcher: &Searcher, reader: &SegmentReader) -> Result<&Arc<BitSet>> {
+        let segment_readers = searcher.segment_readers();
+        let same_segments = segment_readers.len() == self.bitsets.len() &&
+            segment_readers
+                .iter()
+                .all(|segment_reader| {
+                    self.bitsets
+                        .get(&segment_reader.segment_id())
+                        .map(|bitset| bitset.max_doc() == segment_reader.max_doc())
+                        .unwrap_or(false)
+                });
+        if !same_segments {
+            return Err(Error::InvalidArgument(String::from("The filter was computed on other segments than the segments of the searcher.")));
+        }
+        self.bitsets
+            .get(&reader.segment_id())
+            .ok_or_else(|| Error::InvalidArgument(format!("The filter has no bitset for the segment {:?}.", reader.segment_id())))
+    }
+}
+
+impl fmt::Debug for PreComputedFilterQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PreComputedFilterQuery({} segments)", self.bitsets.len())
+    }
+}
+
+impl Query for PreComputedFilterQuery {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let bitset = try!(self.segment_bitset(searcher, reader));
+        // only the `Arc` is cloned, not the bitset.
+        let docset = BitSetDocSet::from(bitset.clone());
+        Ok(Box::new(ConstantScorer::new(docset, 0f32)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let bitset = try!(self.segment_bitset(searcher, segment_reader));
+        let mut explanation = Explanation::with_val(0f32);
+        if bitset.contains(doc_address.doc()) {
+            explanation.description("The document is part of the precomputed filter");
+        }
+        else {
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+        }
+        Ok(explanation)
+    }
+
+    fn count(&self, searcher: &Searcher) -> Result<usize> {
+        let mut count = 0;
+        for segment_reader in searcher.segment_readers() {
+            count += try!(self.segment_bitset(searcher, segment_reader)).len();
+        }
+        Ok(count)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::{Document, Term, Field, TEXT};
+    use query::{MultiTermQuery, PhraseQuery, BooleanQuery, ConstantScoreQuery, Occur};
+    use query::tests::{create_index, ranking};
+    use collector::{BitSetCollector, CountCollector};
+    use test::Bencher;
+
+    fn article_segments(num_docs: usize, num_segments: usize) -> Vec<Vec<&'static str>> {
+        let texts: Vec<&str> = (0..num_docs)
+            .map(|i| {
+                match i % 4 {
+                    0 => "published article",
+                    1 => "article published",
+                    2 => "published draft article",
+                    _ => "draft",
+                }
+            })
+            .collect();
+        texts
+            .chunks(num_docs / num_segments)
+            .map(|segment_texts| segment_texts.to_vec())
+            .collect()
+    }
+
+    fn phrase_filter(text_field: Field) -> PhraseQuery {
+        PhraseQuery::new(vec!(
+            Term::from_field_text(text_field, "published"),
+            Term::from_field_text(text_field, "article")))
+    }
+
+    fn precomputed_filter(searcher: &Searcher, text_field: Field) -> PreComputedFilterQuery {
+        let mut bitset_collector = BitSetCollector::default();
+        phrase_filter(text_field).search(searcher, &mut bitset_collector).unwrap();
+        PreComputedFilterQuery::new(bitset_collector.into_bitsets())
+    }
+
+    #[test]
+    fn test_precomputed_filter_query() {
+        let (index, text_field) = create_index(TEXT, &article_segments(40, 2));
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let filter = precomputed_filter(&searcher, text_field);
+        assert_eq!(filter.count(&searcher).unwrap(), 10);
+        {
+            let mut count_collector = CountCollector::default();
+            filter.search(&searcher, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 10);
+        }
+        assert_eq!(filter.explain(&searcher, &DocAddress(1, 0)).unwrap().val(), 0f32);
+        // the precomputed filter replaces the filter query.
+        let term_query = || Box::new(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "article")))) as Box<Query>;
+        let filtered_query = BooleanQuery::from(vec!(
+            (Occur::Must, term_query()),
+            (Occur::Must, Box::new(ConstantScoreQuery::new(Box::new(phrase_filter(text_field)) as Box<Query>, 0f32)) as Box<Query>),
+        ));
+        let precomputed_filtered_query = BooleanQuery::from(vec!(
+            (Occur::Must, term_query()),
+            (Occur::Must, Box::new(filter) as Box<Query>),
+        ));
+        let expected_ranking = ranking(&searcher, &filtered_query, 100);
+        assert_eq!(expected_ranking.len(), 10);
+        assert_eq!(ranking(&searcher, &precomputed_filtered_query, 100), expected_ranking);
+        // the bitsets are not valid anymore once a segment is added.
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(text_field, "published article");
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let new_searcher = index.searcher();
+        let mut count_collector = CountCollector::default();
+        assert!(precomputed_filtered_query.search(&new_searcher, &mut count_collector).is_err());
+        assert!(precomputed_filtered_query.count(&new_searcher).is_err());
+        assert_eq!(precomputed_filtered_query.count(&searcher).unwrap(), 10);
+    }
+
+    #[bench]
+    fn bench_filter_query(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &article_segments(100_000, 4));
+        let searcher = index.searcher();
+        let query = BooleanQuery::from(vec!(
+            (Occur::Must, Box::new(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "published")))) as Box<Query>),
+            (Occur::Must, Box::new(ConstantScoreQuery::new(Box::new(phrase_filter(text_field)) as Box<Query>, 0f32)) as Box<Query>),
+        ));
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+
+    #[bench]
+    fn bench_precomputed_filter_query(b: &mut Bencher) {
+        let (index, text_field) = create_index(TEXT, &article_segments(100_000, 4));
+        let searcher = index.searcher();
+        // the filter is computed once, outside of the benchmark.
+        let query = BooleanQuery::from(vec!(
+            (Occur::Must, Box::new(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "published")))) as Box<Query>),
+            (Occur::Must, Box::new(precomputed_filter(&searcher, text_field)) as Box<Query>),
+        ));
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+}
@@ -0,0 +1,186 @@
+use Result;
+use Error;
+use schema::{Term, Field};
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use common::BitSet;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use query::segment_docs_builder::SegmentDocsBuilder;
+use postings::BitSetDocSet;
+use DocAddress;
+use query::Explanation;
+
+
+/// Default maximum number of terms a `PrefixQuery`
+/// may expand to within a segment.
+pub const DEFAULT_MAX_EXPANSIONS: usize = 10_000;
+
+
+/// Query matching the documents containing a term
+/// starting with a given prefix, like `micro*`.
+///
+/// The terms starting with the prefix are streamed out of
+/// the term dictionary of each segment, and their postings are unioned.
+///
+/// As a short prefix may match a very large number of terms,
+/// the number of terms the prefix may expand to within a segment
+/// is limited. The search returns an error when the limit is
+/// exceeded, rather than ignoring some of the terms.
+///
+/// All of the matching documents get the same score of `1`.
+#[derive(Eq, PartialEq, Debug)]
+pub struct PrefixQuery {
+    prefix: Term,
+    max_expansions: usize,
+}
+
+impl PrefixQuery {
+
+    /// Creates a query matching the terms of the field of `prefix`
+    /// starting with the value of `prefix`.
+    pub fn new(prefix: Term) -> PrefixQuery {
+        PrefixQuery {
+            prefix: prefix,
+            max_expansions: DEFAULT_MAX_EXPANSIONS,
+        }
+    }
+
+    /// Sets the maximum number of terms the prefix may expand to
+    /// within a segment.
+    ///
+    /// Defaults to `DEFAULT_MAX_EXPANSIONS`.
+    pub fn set_max_expansions(mut self, max_expansions: usize) -> PrefixQuery {
+        self.max_expansions = max_expansions;
+        self
+    }
+
+    /// Returns the maximum number of terms the prefix may expand to.
+    pub fn max_expansions(&self,) -> usize {
+        self.max_expansions
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.prefix.field()
+    }
+
+    /// Returns the prefix, as a term.
+    pub fn prefix(&self,) -> &Term {
+        &self.prefix
+    }
+
+    /// Returns the set of the documents of the segment
+    /// containing a term starting with the prefix.
+    ///
+    /// # Errors
+    /// If the prefix matches more than `max_expansions` terms.
+    fn segment_docs(&self, reader: &SegmentReader) -> Result<BitSet> {
+        let mut docs_builder = SegmentDocsBuilder::new(reader, self.prefix.field());
+        let prefix_bytes = self.prefix.as_slice();
+        let mut terms = reader.term_dict().range().ge(prefix_bytes).into_stream();
+        while let Some((term_bytes, term_info)) = terms.next() {
+            if !term_bytes.starts_with(prefix_bytes) {
+                break;
+            }
+            if docs_builder.num_terms() == self.max_expansions {
+                return Err(Error::InvalidArgument(format!("The prefix {:?} matches more than {} terms.", self.prefix, self.max_expansions)));
+            }
+            docs_builder.add_term_info(&term_info);
+        }
+        Ok(docs_builder.build())
+    }
+}
+
+impl Query for PrefixQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = BitSetDocSet::from(try!(self.segment_docs(reader)));
+        Ok(Box::new(ConstantScorer::new(docset, 1f32)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let docs = try!(self.segment_docs(segment_reader));
+        Ok(if docs.contains(doc_address.doc()) {
+            let mut explanation = Explanation::with_val(1f32);
+            explanation.description(&format!("The document contains a term starting with {:?}", self.prefix));
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, TEXT};
+    use collector::tests::TestCollector;
+    use DocId;
+
+    #[test]
+    fn test_prefix_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let other_field = schema_builder.add_text_field("other", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for title in &["microsoft", "micro", "microscope lens", "macro", "mic"] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                index_writer.add_document(doc).unwrap();
+            }
+            let mut doc = Document::default();
+            doc.add_text(other_field, "microwave");
+            index_writer.add_document(doc).unwrap();
+            // 3000 terms starting with `word`.
+            for i in 0..3_000 {
+                let mut doc = Document::default();
+                doc.add_text(title_field, &format!("word{}", i));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search = |query: PrefixQuery| -> Result<Vec<DocId>> {
+            let mut test_collector = TestCollector::default();
+            try!(query.search(&searcher, &mut test_collector));
+            Ok(test_collector.docs())
+        };
+        let prefix = |text: &str| PrefixQuery::new(Term::from_field_text(title_field, text));
+        assert_eq!(search(prefix("micro")).unwrap(), vec!(0, 1, 2));
+        assert_eq!(search(prefix("microsc")).unwrap(), vec!(2));
+        assert_eq!(search(prefix("len")).unwrap(), vec!(2));
+        assert!(search(prefix("zz")).unwrap().is_empty());
+        assert!(search(prefix("microsofts")).unwrap().is_empty());
+        assert_eq!(search(prefix("m")).unwrap(), vec!(0, 1, 2, 3, 4));
+        assert_eq!(search(prefix("word")).unwrap().len(), 3_000);
+        assert_eq!(search(prefix("word12")).unwrap().len(), 111);
+        // the empty prefix matches all of the terms of the field.
+        assert_eq!(search(prefix("")).unwrap().len(), 3_005);
+        match search(prefix("word").set_max_expansions(2_999)) {
+            Err(Error::InvalidArgument(_)) => {}
+            _ => {
+                panic!("Expected the max expansions to be exceeded");
+            }
+        }
+        assert_eq!(search(prefix("word12").set_max_expansions(111)).unwrap().len(), 111);
+        assert!(search(prefix("word12").set_max_expansions(110)).is_err());
+        assert_eq!(prefix("mic").explain(&searcher, &DocAddress(0, 4)).unwrap().val(), 1f32);
+        assert_eq!(prefix("mic").explain(&searcher, &DocAddress(0, 3)).unwrap().val(), 0f32);
+    }
+}
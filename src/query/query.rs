@@ -1,26 +1,136 @@
 use Result;
 use collector::Collector;
 use core::searcher::Searcher;
+use core::SegmentReader;
 use common::TimerTree;
 use DocAddress;
+use ScoredDoc;
+use SegmentLocalId;
 use query::Explanation;
+use query::Scorer;
+use std::fmt;
 
 
 /// Queries represent the query of the user, and are in charge
 /// of the logic defining the set of documents that should be
 /// sent to the collector, as well as the way to score the
 /// documents. 
-pub trait Query {
+///
+/// Queries can be combined into a `BooleanQuery`, which relies
+/// on the `Scorer` each of its clauses returns for a segment.
+pub trait Query: fmt::Debug {
     
     /// Perform the search operation
+    ///
+    /// By default, the documents of the `Scorer` of 
+    /// each segment are pushed to the collector.
     fn search<C: Collector>(
         &self,
         searcher: &Searcher,
-        collector: &mut C) -> Result<TimerTree>;
+        collector: &mut C) -> Result<TimerTree> where Self: Sized {
+        let mut timer_tree = TimerTree::default();
+        {
+            let mut search_timer = timer_tree.open("search");
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                if collector.should_terminate() {
+                    break;
+                }
+                let mut segment_search_timer = search_timer.open("segment_search");
+                {
+                    let _ = segment_search_timer.open("set_segment");
+                    try!(collector.set_segment(segment_ord as SegmentLocalId, &segment_reader));
+                }
+                let mut scorer = {
+                    let _ = segment_search_timer.open("get_scorer");
+                    try!(self.scorer(searcher, segment_reader))
+                };
+                {
+                    let _collection_timer = segment_search_timer.open("collection");
+                    while scorer.advance() {
+                        collector.collect(ScoredDoc(scorer.score(), scorer.doc()));
+                        if collector.should_terminate() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        try!(collector.finish());
+        Ok(timer_tree)
+    }
         
+    /// Returns the `Scorer` iterating over the documents
+    /// of the segment matching the query.
+    ///
+    /// The searcher gives access to the statistics of the
+    /// whole index, like the document frequency of the terms.
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>>;
+
+    /// Returns a `Scorer` iterating over the same documents
+    /// as `scorer`, for when the scores are not used,
+    /// like in a `ConstantScoreQuery`.
+    ///
+    /// The scores of this scorer are meaningless, which lets
+    /// queries skip reading the term frequencies and positions.
+    /// By default, it is simply the `Scorer` of the query.
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        self.scorer(searcher, reader)
+    }
+
     /// Explain the score of a specific document
     fn explain(
         &self,
         searcher: &Searcher,
         doc_address: &DocAddress) -> Result<Explanation>;
+
+    /// Returns the number of documents matching the query.
+    ///
+    /// The documents are iterated through the `filter_scorer`
+    /// of each segment, so that neither the scores nor the
+    /// term frequencies are computed.
+    fn count(&self, searcher: &Searcher) -> Result<usize> {
+        let mut count = 0;
+        for segment_reader in searcher.segment_readers() {
+            let mut docset = try!(self.filter_scorer(searcher, segment_reader));
+            while docset.advance() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+
+impl<TQuery: Query + ?Sized> Query for Box<TQuery> {
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        (**self).scorer(searcher, reader)
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        (**self).filter_scorer(searcher, reader)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        (**self).explain(searcher, doc_address)
+    }
+
+    fn count(&self, searcher: &Searcher) -> Result<usize> {
+        (**self).count(searcher)
+    }
 }
@@ -3,13 +3,20 @@ use combine::*;
 use collector::Collector;
 use core::searcher::Searcher;
 use common::TimerTree;
-use query::{Query, MultiTermQuery};
-use schema::{Schema, FieldType, Term, Field};
-use analyzer::SimpleTokenizer;
-use analyzer::StreamingIterator;
+use query::{Query, MultiTermQuery, RangeQuery, RangeBound, PhraseQuery, BooleanQuery, PrefixQuery, RegexQuery, AllQuery, DisjunctionMaxQuery, BoostQuery, PhrasePrefixQuery};
+use query::Scorer;
+use core::SegmentReader;
+use schema::{Schema, FieldType, Term, Field, Facet, TextOptions};
+use schema::parse_date;
+use schema::{parse_ip, parse_cidr};
+use analyzer::{Tokenizer, TokenizerManager};
+use std::sync::Arc;
+use std::iter;
+use core::Index;
 use DocAddress;
 use query::Explanation;
 use query::Occur;
+use Score;
 
 
 
@@ -25,23 +32,101 @@ pub enum ParsingError {
     /// The query contains a term for a `u32`-field, but the value
     /// is not a u32.
     ExpectedU32(String, String),
+    /// `ExpectedI64(field_name: String, field_value: String)`
+    /// The query contains a term for an `i64`-field, but the value
+    /// is not an i64.
+    ExpectedI64(String, String),
+    /// `ExpectedF64(field_name: String, field_value: String)`
+    /// The query contains a term for an `f64`-field, but the value
+    /// is not an f64.
+    ExpectedF64(String, String),
+    /// `ExpectedDate(field_name: String, field_value: String)`
+    /// The query contains a term for a date field, but the value
+    /// is not an ISO-8601 date.
+    ExpectedDate(String, String),
+    /// `ExpectedBool(field_name: String, field_value: String)`
+    /// The query contains a term for a bool field, but the value
+    /// is neither `true` nor `false`.
+    ExpectedBool(String, String),
+    /// `ExpectedIp(field_name: String, field_value: String)`
+    /// The query contains a term for an IP field, but the value
+    /// is neither an IP address nor a CIDR block.
+    ExpectedIp(String, String),
+    /// `FieldNotIndexed(field_name: String)`
+    /// The query references a field, or has a default field,
+    /// that is not indexed and thus cannot be searched, like a bytes field.
+    FieldNotIndexed(String),
+    /// `UnknownTokenizer(field_name: String, tokenizer_name: String)`
+    /// The tokenizer of a text field is not registered
+    /// in the `TokenizerManager` of the query parser.
+    UnknownTokenizer(String, String),
+    /// `PositionsNotIndexed(field_name: String)`
+    /// The query contains a phrase for a field that was 
+    /// not indexed with its positions.
+    PositionsNotIndexed(String),
+    /// `UnsupportedPrefix(field_name: String)`
+    /// The query contains a prefix for a field that is not a text field.
+    UnsupportedPrefix(String),
+    /// `UnsupportedRegex(field_name: String)`
+    /// The query contains a regular expression for a field that is not a text field.
+    UnsupportedRegex(String),
+    /// `InvalidRegex(pattern: String)`
+    /// The query contains a regular expression that is invalid,
+    /// or too large once compiled.
+    InvalidRegex(String),
+    /// The query has a quote that is never closed.
+    UnclosedQuote,
+    /// The query has a closing parenthesis without any matching
+    /// opening parenthesis, or a parenthesis that is never closed.
+    UnbalancedParentheses,
+    /// `InvalidClause(clause: String)`
+    /// A space separated part of the query cannot be parsed.
+    InvalidClause(String),
 }
 
+/// Operator joining the clauses of a query.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operator {
+    /// The documents must match both of the clauses.
+    And,
+    /// The documents must match at least one of the clauses.
+    Or,
+}
+
+/// Maximum number of nested parenthesized groups in a query.
+const MAX_GROUP_DEPTH: usize = 32;
+
 /// Tantivy's Query parser
 ///
 /// The language covered by the current parser is extremely simple.
 ///
 /// * simple terms: "e.g.: `Barack Obama` are simply analyzed using 
 ///   tantivy's `StandardTokenizer`, hence becoming `["barack", "obama"]`.
-///   The terms are then searched within the default terms of the query parser.
+///   The terms are then searched within the default fields of the query parser,
+///   given when creating the parser.
 ///   
 ///   e.g. If `body` and `title` are default fields, our example terms are
 ///   `["title:barack", "body:barack", "title:obama", "body:obama"]`.
-///   By default, all tokenized and indexed fields are default fields.
+///   A query parser without any default field ignores the terms that do
+///   not specify a field.
+///
+/// * field-qualified terms: `title:barack` only searches `barack` in the `title` field.
+///   Referencing a field that is not in the schema, or that is not indexed, is an error
+///   rather than a query matching no document. The same goes for the default fields,
+///   which must be indexed.
+///
+/// * escaping: the characters with a special meaning, like `:`, `*` or `^`,
+///   can be searched for by escaping them with a `\`, e.g. `time:12\:30`.
 ///   
 ///   Multiple terms are handled as an `OR` : any document containing at least
 ///   one of the term will go through the scoring.
 ///
+///   A document containing a term in several of the default fields thus
+///   gets the sum of the scores of the fields. The parser can be configured
+///   via `set_disjunction_max_tie_breaker` to search each value in the default
+///   fields with a `DisjunctionMaxQuery` instead, so that only the best field
+///   counts fully.
+///
 ///   This behavior is slower, but is not a bad idea if the user is sorting
 ///   by relevance : The user typically just scans through the first few
 ///   documents in order of decreasing relevance and will stop when the documents
@@ -54,26 +139,135 @@ pub enum ParsingError {
 ///   e.g. `apple -fruit` 
 ///
 /// * must terms: By prepending a term by a `+`, a term can be made required for the search.
+///
+/// * boolean operators: `AND`, `OR` and `NOT` join the clauses of a query, and
+///   parentheses group clauses, e.g. `(rust AND async) OR tokio NOT blocking`.
+///   The operators are uppercase, and `NOT a` is the same as `-a`. The clauses
+///   only separated by spaces are joined by the default operator, `OR` unless
+///   configured otherwise via `set_default_operator`.
+///
+///   `AND` takes precedence over `OR`: `a OR b AND c` is `a OR (b AND c)`, and 
+///   with `AND` as the default operator, `a b OR c` is `(a AND b) OR c`.
+///   The `+`, `-` and `NOT` prefixes apply within the group of their clause:
+///   `(rust AND async) OR tokio NOT blocking` excludes the documents containing
+///   `blocking` from the whole query, like `-blocking` would.
+///   A group can be prefixed and boosted like a literal, e.g. `-(java OR go)` or
+///   `(rust tokio)^2`. Groups can be nested up to 32 times.
+///
+///   A query with `+` or `-` clauses, or with ranges among other clauses, 
+///   is a `BooleanQuery` with one clause per literal: the documents must match
+///   all of the `+` clauses, none of the `-` clauses, and at least one of the 
+///   other clauses if there are no `+` clauses. A query made only of `-` clauses
+///   does not match any document.
+///
+/// * phrases: a quoted text of a tokenized field, like `"barack obama"`, matches the
+///   documents containing its tokens as a sequence, via a `PhraseQuery`.
+///   The field must then be indexed with `TextIndexingOptions::TokenizedWithFreqAndPosition`.
+///   A slop can be given after the phrase, e.g. `"barack obama"~1` also
+///   matches `barack h. obama`. Like the other literals, a phrase can be 
+///   prefixed by `+` or `-`. A quoted text producing a single token is
+///   searched as a term.
+///
+/// * IP addresses and CIDR blocks: `src_ip:10.0.0.1` searches for a given address,
+///   while `src_ip:10.0.0.0/8` searches for all of the addresses of the block.
+///   IPv6 addresses need to be quoted, e.g. `src_ip:"2001:db8::/32"`.
+///
+/// * ranges: `price:[100 TO 500]` searches for the values between `100` and `500`,
+///   both included, while `price:{100 TO 500}` excludes both of the bounds.
+///   The brackets can be mixed, e.g. `price:[100 TO 500}`, and `*` leaves
+///   a side of the range unbounded, e.g. `price:[* TO 100]`.
+///   The bounds can be quoted, e.g. `date:["2017-01-01T00:00:00Z" TO *]`.
+///   The bounds of the text fields are compared lexicographically.
+///
+/// * prefixes: a trailing `*` turns a term into a prefix, e.g. `title:micro*`
+///   matches the documents containing a term of `title` starting with `micro`,
+///   via a `PrefixQuery`. Prefixes are only supported for the text fields, and
+///   are analyzed like the bounds of a range.
+///
+///   A trailing `*` after a phrase turns its last token into a prefix, e.g.
+///   `"harry pot"*` matches `harry potter`, via a `PhrasePrefixQuery`. Like phrases,
+///   the field must then be indexed with `TextIndexingOptions::TokenizedWithFreqAndPosition`.
+///
+/// * regular expressions: `path:/api\/v[0-9]+\/users/` matches the documents containing
+///   a term of `path` matching `api/v[0-9]+/users`, via a `RegexQuery`. The
+///   expression must match the whole term, and `/` must be escaped as `\/`.
+///   Regular expressions are only supported for the text fields, and are not
+///   analyzed: they are matched against the terms as they are indexed, e.g.
+///   lowercased for the tokenized fields.
+///
+/// * boosts: a `^` followed by a boost multiplies the score of a literal,
+///   via a `BoostQuery`, e.g. `title:rust^2.5`, `"barack obama"~1^2`
+///   or `+date:[2017-01-01T00:00:00Z TO *]^3`.
+///
+/// * the empty query does not match any document, unless the parser is
+///   configured to match all of the documents via `set_empty_query_matches_all`.
+///
+/// * untokenized fields: values searched in a field indexed with
+///   `TextIndexingOptions::Untokenized` or `TextIndexingOptions::Raw`
+///   are not analyzed, and are looked up as a single term.
+///   e.g. `sku:"AB-123/X"`. The values searched in a field indexed with
+///   `TextIndexingOptions::UntokenizedLowercase` are only lowercased.
+///
+/// * numeric fields: the values searched in a `u32`, `i64` or `f64` field, like
+///   the bounds of a range, are parsed as numbers and looked up as the terms
+///   of the field, e.g. `price:100` or `price:[10 TO 20]`. A value that is
+///   not a number of the type of the field, like `price:ten`, is an error
+///   such as `ParsingError::ExpectedU32`.
+///
+/// The values of the tokenized text fields are tokenized with 
+/// the tokenizer of the field. A parser created with `QueryParser::new` 
+/// only knows about the built-in tokenizers, use `QueryParser::for_index`
+/// for the fields relying on a tokenizer registered on the index.
 ///   
 pub struct QueryParser {
     schema: Schema,
     default_fields: Vec<Field>,
+    tokenizers: TokenizerManager,
+    empty_query_matches_all: bool,
+    disjunction_max_tie_breaker: Option<Score>,
+    default_operator: Operator,
 }
 
 
 /// The `QueryParser` returns a `StandardQuery`. 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(PartialEq, Debug)]
 pub enum StandardQuery {
     MultiTerm(MultiTermQuery),
+    Range(RangeQuery),
+    Boolean(BooleanQuery<StandardQuery>),
+    Prefix(PrefixQuery),
+    Regex(RegexQuery),
+    All(AllQuery),
+    DisjunctionMax(DisjunctionMaxQuery<StandardQuery>),
+    Boost(BoostQuery<Box<StandardQuery>>),
+    PhrasePrefix(PhrasePrefixQuery),
 }
 
 impl StandardQuery {
     /// Number of terms involved in the query.
+    ///
+    /// A range, a prefix, a phrase prefix or a regular expression counts as a single term,
+    /// while the query matching all of the documents counts as none.
     pub fn num_terms(&self,) -> usize {
         match *self {
             StandardQuery::MultiTerm(ref q) => {
                 q.num_terms()
             }
+            StandardQuery::Range(_) | StandardQuery::Prefix(_) | StandardQuery::Regex(_) | StandardQuery::PhrasePrefix(_) => 1,
+            StandardQuery::All(_) => 0,
+            StandardQuery::Boolean(ref q) => {
+                q.clauses()
+                    .iter()
+                    .map(|&(_, ref clause)| clause.num_terms())
+                    .fold(0, |sum, num_terms| sum + num_terms)
+            }
+            StandardQuery::DisjunctionMax(ref q) => {
+                q.queries()
+                    .iter()
+                    .map(|query| query.num_terms())
+                    .fold(0, |sum, num_terms| sum + num_terms)
+            }
+            StandardQuery::Boost(ref q) => q.query().num_terms(),
         }
     }
 }
@@ -89,14 +283,102 @@ impl QueryParser {
         QueryParser {
             schema: schema,
             default_fields: default_fields,
+            tokenizers: TokenizerManager::default(),
+            empty_query_matches_all: false,
+            disjunction_max_tie_breaker: None,
+            default_operator: Operator::Or,
         }
     }   
+
+    /// Creates a `QueryParser` for an index, tokenizing the
+    /// text fields with the tokenizers registered on the index.
+    pub fn for_index(index: &Index,
+                     default_fields: Vec<Field>) -> QueryParser {
+        QueryParser {
+            schema: index.schema(),
+            default_fields: default_fields,
+            tokenizers: index.tokenizers().clone(),
+            empty_query_matches_all: false,
+            disjunction_max_tie_breaker: None,
+            default_operator: Operator::Or,
+        }
+    }
+
+    /// Sets whether the empty query matches all of the documents,
+    /// via an `AllQuery`, rather than none of them.
+    ///
+    /// Defaults to `false`.
+    pub fn set_empty_query_matches_all(&mut self, empty_query_matches_all: bool) {
+        self.empty_query_matches_all = empty_query_matches_all;
+    }
+
+    /// Sets whether the values searched in several default fields
+    /// are searched via a `DisjunctionMaxQuery` over the fields, with the
+    /// given tie breaker, rather than summing the scores of the fields.
+    ///
+    /// Defaults to `None`, summing the scores of the fields.
+    ///
+    /// # Panics
+    /// If the tie breaker is not within `[0, 1]`.
+    pub fn set_disjunction_max_tie_breaker(&mut self, tie_breaker: Option<Score>) {
+        if let Some(tie_breaker) = tie_breaker {
+            assert!(tie_breaker >= 0f32 && tie_breaker <= 1f32, "The tie breaker must be within [0, 1].");
+        }
+        self.disjunction_max_tie_breaker = tie_breaker;
+    }
+    
+    /// Sets the operator joining the clauses of a query
+    /// that are only separated by spaces, like `barack obama`.
+    ///
+    /// Defaults to `Operator::Or`.
+    pub fn set_default_operator(&mut self, default_operator: Operator) {
+        self.default_operator = default_operator;
+    }
+
+    /// Returns the phrase query associated to a quoted value,
+    /// if the field is tokenized and the value has several tokens.
+    fn transform_field_and_phrase(&self, field: Field, val: &str, slop: u32) -> Result<Option<PhraseQuery>, ParsingError> {
+        let field_entry = self.schema.get_field_entry(field);
+        if let FieldType::Str(ref text_options) = *field_entry.field_type() {
+            let indexing_options = text_options.get_indexing_options();
+            if indexing_options.is_tokenized() {
+                let tokenizer = try!(self.tokenizer(field_entry.name(), text_options));
+                let terms = compute_terms_with_offsets(field, val, &*tokenizer);
+                if terms.len() > 1 {
+                    if !indexing_options.is_position_enabled() {
+                        return Err(ParsingError::PositionsNotIndexed(field_entry.name().clone()));
+                    }
+                    return Ok(Some(PhraseQuery::new_with_offsets(terms).set_slop(slop)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the tokenizer of a tokenized text field.
+    fn tokenizer(&self, field_name: &str, text_options: &TextOptions) -> Result<Arc<Tokenizer>, ParsingError> {
+        let tokenizer_name = text_options.get_tokenizer();
+        self.tokenizers
+            .get(tokenizer_name)
+            .ok_or_else(|| {
+                ParsingError::UnknownTokenizer(String::from(field_name), String::from(tokenizer_name))
+            })
+    }
     
     fn transform_field_and_value(&self, field: Field, val: &str) -> Result<Vec<Term>, ParsingError> {
         let field_entry = self.schema.get_field_entry(field);
         Ok(match *field_entry.field_type() {
-            FieldType::Str(_) => {
-                compute_terms(field, val)
+            FieldType::Str(ref text_options) => {
+                let indexing_options = text_options.get_indexing_options();
+                if !indexing_options.is_tokenized() {
+                    // untokenized fields are looked up as they are indexed,
+                    // without any analysis.
+                    vec!(indexing_options.untokenized_term(field, val))
+                }
+                else {
+                    let tokenizer = try!(self.tokenizer(field_entry.name(), text_options));
+                    compute_terms(field, val, &*tokenizer)
+                }
             },
             FieldType::U32(_) => {
                 let u32_parsed: u32 = try!(val
@@ -107,72 +389,567 @@ impl QueryParser {
                 );
                 vec!(Term::from_field_u32(field, u32_parsed))
             }
+            FieldType::I64(_) => {
+                let i64_parsed: i64 = try!(val
+                    .parse::<i64>()
+                    .map_err(|_| {
+                        ParsingError::ExpectedI64(field_entry.name().clone(), String::from(val))
+                    })
+                );
+                vec!(Term::from_field_i64(field, i64_parsed))
+            }
+            FieldType::F64(_) => {
+                let f64_parsed: f64 = try!(val
+                    .parse::<f64>()
+                    .map_err(|_| {
+                        ParsingError::ExpectedF64(field_entry.name().clone(), String::from(val))
+                    })
+                );
+                vec!(Term::from_field_f64(field, f64_parsed))
+            }
+            FieldType::Date(ref date_options) => {
+                let millis: i64 = try!(parse_date(val)
+                    .ok_or_else(|| {
+                        ParsingError::ExpectedDate(field_entry.name().clone(), String::from(val))
+                    })
+                );
+                let truncated_millis = date_options.get_precision().truncate(millis);
+                vec!(Term::from_field_date(field, truncated_millis))
+            }
+            FieldType::Bytes(_) => {
+                return Err(ParsingError::FieldNotIndexed(field_entry.name().clone()));
+            }
+            FieldType::HierarchicalFacet(_) => {
+                vec!(Term::from_facet(field, &Facet::from_text(val)))
+            }
+            FieldType::Bool(_) => {
+                let bool_parsed: bool = try!(val
+                    .parse::<bool>()
+                    .map_err(|_| {
+                        ParsingError::ExpectedBool(field_entry.name().clone(), String::from(val))
+                    })
+                );
+                vec!(Term::from_field_bool(field, bool_parsed))
+            }
+            FieldType::Ip(_) => {
+                let ip_parsed = try!(parse_ip(val)
+                    .ok_or_else(|| {
+                        ParsingError::ExpectedIp(field_entry.name().clone(), String::from(val))
+                    })
+                );
+                vec!(Term::from_field_ip(field, ip_parsed))
+            }
         })
     }    
     
-    fn transform_literal(&self, literal: Literal) -> Result<Vec<Term>, ParsingError> {
-        match literal {
-            Literal::DefaultField(val) => {
-                let mut terms = Vec::new();
-                for &field in &self.default_fields {
-                    let extra_terms = try!(self.transform_field_and_value(field, &val));
-                    terms.extend_from_slice(&extra_terms);
+    /// Returns the single term associated to a value, like the bound
+    /// of a range or a prefix.
+    ///
+    /// The values of the tokenized text fields are analyzed, 
+    /// unless they do not produce exactly one token.
+    fn transform_field_and_single_value(&self, field: Field, val: &str) -> Result<Term, ParsingError> {
+        let mut terms = try!(self.transform_field_and_value(field, val));
+        if terms.len() == 1 {
+            Ok(terms.pop().unwrap())
+        }
+        else {
+            Ok(Term::from_field_text(field, val))
+        }
+    }
+
+    /// Returns the term of a bound of a range.
+    fn transform_range_bound(&self, field: Field, bound: &LiteralBound) -> Result<RangeBound, ParsingError> {
+        Ok(match *bound {
+            LiteralBound::Included(ref val) => RangeBound::Included(try!(self.transform_field_and_single_value(field, val))),
+            LiteralBound::Excluded(ref val) => RangeBound::Excluded(try!(self.transform_field_and_single_value(field, val))),
+            LiteralBound::Unbounded => RangeBound::Unbounded,
+        })
+    }
+
+    /// Returns the prefix query of a text field.
+    fn transform_field_and_prefix(&self, field: Field, val: &str) -> Result<PrefixQuery, ParsingError> {
+        let field_entry = self.schema.get_field_entry(field);
+        if let FieldType::Str(_) = *field_entry.field_type() {
+            let prefix = try!(self.transform_field_and_single_value(field, val));
+            Ok(PrefixQuery::new(prefix))
+        }
+        else {
+            Err(ParsingError::UnsupportedPrefix(field_entry.name().clone()))
+        }
+    }
+
+    /// Returns the query of a quoted value followed by a `*`,
+    /// if the field is tokenized and the value has several tokens.
+    ///
+    /// The last token is the prefix of a phrase. Otherwise, the
+    /// value is searched as a mere prefix.
+    fn transform_field_and_phrase_prefix(&self, field: Field, val: &str) -> Result<StandardQuery, ParsingError> {
+        let field_entry = self.schema.get_field_entry(field);
+        if let FieldType::Str(ref text_options) = *field_entry.field_type() {
+            let indexing_options = text_options.get_indexing_options();
+            if indexing_options.is_tokenized() {
+                let tokenizer = try!(self.tokenizer(field_entry.name(), text_options));
+                let terms = compute_terms_with_offsets(field, val, &*tokenizer);
+                if terms.len() > 1 {
+                    if !indexing_options.is_position_enabled() {
+                        return Err(ParsingError::PositionsNotIndexed(field_entry.name().clone()));
+                    }
+                    return Ok(StandardQuery::PhrasePrefix(PhrasePrefixQuery::new_with_offsets(terms)));
+                }
+            }
+        }
+        Ok(StandardQuery::Prefix(try!(self.transform_field_and_prefix(field, val))))
+    }
+
+    /// Returns the query associated to the literal, if
+    /// the literal is a prefix or a phrase prefix.
+    ///
+    /// A prefix is searched in each of the default fields
+    /// if it does not specify a field.
+    fn transform_prefix_literal(&self, literal: &Literal) -> Result<Option<StandardQuery>, ParsingError> {
+        Ok(match *literal {
+            Literal::PrefixWithField(ref field_name, ref val) => {
+                let field = try!(self.get_field(field_name.clone()));
+                Some(StandardQuery::Prefix(try!(self.transform_field_and_prefix(field, val))))
+            }
+            Literal::PhrasePrefixWithField(ref field_name, ref val) => {
+                let field = try!(self.get_field(field_name.clone()));
+                Some(try!(self.transform_field_and_phrase_prefix(field, val)))
+            }
+            Literal::PrefixDefaultField(ref val) | Literal::PhrasePrefixDefaultField(ref val) => {
+                let mut clauses = Vec::new();
+                for field in try!(self.default_fields()) {
+                    let prefix_query = if let Literal::PrefixDefaultField(_) = *literal {
+                        StandardQuery::Prefix(try!(self.transform_field_and_prefix(field, val)))
+                    }
+                    else {
+                        try!(self.transform_field_and_phrase_prefix(field, val))
+                    };
+                    clauses.push((Occur::Should, prefix_query));
+                }
+                if clauses.len() == 1 {
+                    clauses.pop().map(|(_, query)| query)
+                }
+                else {
+                    Some(StandardQuery::Boolean(BooleanQuery::from(clauses)))
+                }
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the regular expression query associated to the literal,
+    /// if the literal is a regular expression.
+    fn transform_regex_literal(&self, literal: &Literal) -> Result<Option<RegexQuery>, ParsingError> {
+        if let Literal::RegexWithField(ref field_name, ref pattern) = *literal {
+            let field = try!(self.get_field(field_name.clone()));
+            let field_entry = self.schema.get_field_entry(field);
+            if let FieldType::Str(_) = *field_entry.field_type() {
+                let regex_query = try!(RegexQuery::new(field, pattern)
+                    .map_err(|_| ParsingError::InvalidRegex(pattern.clone())));
+                return Ok(Some(regex_query));
+            }
+            return Err(ParsingError::UnsupportedRegex(field_entry.name().clone()));
+        }
+        Ok(None)
+    }
+
+    /// Returns the disjunction max query associated to the literal, if
+    /// the parser is configured to use them and the literal is searched
+    /// in several default fields.
+    ///
+    /// Each of its subqueries searches the value in one of the default fields.
+    fn transform_disjunction_max_literal(&self, literal: &Literal) -> Result<Option<DisjunctionMaxQuery<StandardQuery>>, ParsingError> {
+        let tie_breaker = match self.disjunction_max_tie_breaker {
+            Some(tie_breaker) if self.default_fields.len() > 1 => tie_breaker,
+            _ => { return Ok(None); }
+        };
+        let (val, slop_opt) = match *literal {
+            Literal::DefaultField(ref val) => (val, None),
+            Literal::PhraseDefaultField(ref val, slop) => (val, Some(slop)),
+            _ => { return Ok(None); }
+        };
+        let mut queries = Vec::new();
+        for field in try!(self.default_fields()) {
+            let (terms, phrase_queries) = try!(self.transform_fields_and_value(vec!(field), val, slop_opt));
+            if terms.is_empty() && phrase_queries.is_empty() {
+                continue;
+            }
+            queries.push(StandardQuery::MultiTerm(multi_term_query(terms, phrase_queries)));
+        }
+        if queries.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(DisjunctionMaxQuery::new(queries, tie_breaker)))
+    }
+
+    /// Returns the range query associated to the literal,
+    /// if the literal is a range or a CIDR block for an IP field.
+    fn transform_range_literal(&self, literal: &Literal) -> Result<Option<RangeQuery>, ParsingError> {
+        if let Literal::Range(ref field_name, ref lower, ref upper) = *literal {
+            let field = try!(self.get_field(field_name.clone()));
+            let lower_bound = try!(self.transform_range_bound(field, lower));
+            let upper_bound = try!(self.transform_range_bound(field, upper));
+            return Ok(Some(RangeQuery::new_with_bounds(field, lower_bound, upper_bound)));
+        }
+        if let Literal::WithField(ref field_name, ref val) | Literal::PhraseWithField(ref field_name, ref val, _) = *literal {
+            let field = try!(self.get_field(field_name.clone()));
+            if let FieldType::Ip(_) = *self.schema.get_field_entry(field).field_type() {
+                if val.contains('/') {
+                    let (lower, upper) = try!(parse_cidr(val)
+                        .ok_or_else(|| {
+                            ParsingError::ExpectedIp(field_name.clone(), val.clone())
+                        })
+                    );
+                    let range_query = RangeQuery::new(
+                        Term::from_field_ip(field, lower),
+                        Term::from_field_ip(field, upper));
+                    return Ok(Some(range_query));
+                }
+            }
+        }
+        Ok(None)
+    }
+    
+    /// Returns the field of a field name found in the query.
+    ///
+    /// The field must exist and be indexed.
+    fn get_field(&self, field_name: String) -> Result<Field, ParsingError> {
+        let field = try!(self.schema
+            .get_field(&field_name)
+            .ok_or_else(|| ParsingError::FieldDoesNotExist(field_name)));
+        try!(self.check_indexed(field));
+        Ok(field)
+    }
+
+    /// Returns the default fields, in which the values
+    /// of the literals without a field are searched.
+    ///
+    /// The default fields must be indexed.
+    fn default_fields(&self,) -> Result<Vec<Field>, ParsingError> {
+        for &field in &self.default_fields {
+            try!(self.check_indexed(field));
+        }
+        Ok(self.default_fields.clone())
+    }
+
+    fn check_indexed(&self, field: Field) -> Result<(), ParsingError> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.is_indexed() {
+            return Err(ParsingError::FieldNotIndexed(field_entry.name().clone()));
+        }
+        Ok(())
+    }
+    
+    /// Returns the terms and the phrases of the literal.
+    fn transform_literal(&self, literal: Literal) -> Result<(Vec<Term>, Vec<PhraseQuery>), ParsingError> {
+        let (fields, val, slop_opt) = match literal {
+            Literal::DefaultField(val) => (try!(self.default_fields()), val, None),
+            Literal::PhraseDefaultField(val, slop) => (try!(self.default_fields()), val, Some(slop)),
+            Literal::WithField(field_name, val) => (vec!(try!(self.get_field(field_name))), val, None),
+            Literal::PhraseWithField(field_name, val, slop) => (vec!(try!(self.get_field(field_name))), val, Some(slop)),
+            Literal::Range(..) => unreachable!("ranges are handled by transform_range_literal"),
+            Literal::PrefixWithField(..) | Literal::PrefixDefaultField(..) |
+            Literal::PhrasePrefixWithField(..) | Literal::PhrasePrefixDefaultField(..) => unreachable!("prefixes are handled by transform_prefix_literal"),
+            Literal::RegexWithField(..) => unreachable!("regular expressions are handled by transform_regex_literal"),
+            Literal::Boosted(..) => unreachable!("boosts are handled by transform_clause"),
+            Literal::Group(..) => unreachable!("groups are handled by transform_clause"),
+        };
+        self.transform_fields_and_value(fields, &val, slop_opt)
+    }
+
+    /// Returns the terms and the phrases of a value searched in `fields`,
+    /// `slop_opt` being the slop of the value if it is a phrase.
+    fn transform_fields_and_value(&self, fields: Vec<Field>, val: &str, slop_opt: Option<u32>) -> Result<(Vec<Term>, Vec<PhraseQuery>), ParsingError> {
+        let mut terms = Vec::new();
+        let mut phrase_queries = Vec::new();
+        for field in fields {
+            if let Some(slop) = slop_opt {
+                if let Some(phrase_query) = try!(self.transform_field_and_phrase(field, val, slop)) {
+                    phrase_queries.push(phrase_query);
+                    continue;
                 }
-                Ok(terms)
-            },
-            Literal::WithField(field_name, val) => {
-                match self.schema.get_field(&field_name) {
-                    Some(field) => {
-                        let terms = try!(self.transform_field_and_value(field, &val));
-                        Ok(terms)
-                    },
-                    None => Err(ParsingError::FieldDoesNotExist(field_name))
-                } 
             }
+            let extra_terms = try!(self.transform_field_and_value(field, val));
+            terms.extend_from_slice(&extra_terms);
         }
+        Ok((terms, phrase_queries))
     }
 
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
-    /// is not a valid query.
-    /// 
-    /// There is currently no lenient mode for the query parser
-    /// which makes it a bad choice for a public/broad user search engine.
-    ///
-    /// Implementing a lenient mode for this query parser is tracked 
-    /// in [Issue 5](https://github.com/fulmicoton/tantivy/issues/5)
+    /// is not a valid query. Queries typed by users in a search box
+    /// are better parsed with `parse_query_lenient`.
     pub fn parse_query(&self, query: &str) -> Result<StandardQuery, ParsingError> {
-        match parser(query_language).parse(query.trim()) {
-            Ok(literals) => {
-                let mut terms_result: Vec<(Occur, Term)> = Vec::new();
-                for (occur, literal) in literals.0 {
-                    let literal_terms = try!(self.transform_literal(literal));
-                    terms_result
-                        .extend(literal_terms
-                        .into_iter()
-                        .map(|term| (occur, term) ));
+        if self.empty_query_matches_all && query.trim().is_empty() {
+            return Ok(StandardQuery::All(AllQuery));
+        }
+        let clauses = try!(self.parse_clauses(query.trim()).ok_or(ParsingError::SyntaxError));
+        self.transform_clauses(clauses, None)
+    }
+
+    /// Parse a query, never failing.
+    ///
+    /// The problems found in the query are returned along with
+    /// a best-effort query, e.g. to display a hint to the user:
+    ///
+    /// * an unclosed quote is closed, unmatched closing parentheses are
+    ///   removed and unclosed parentheses are closed.
+    /// * if the query still cannot be parsed, each of its space separated parts
+    ///   is parsed on its own. The parts that cannot be parsed are reported as
+    ///   `InvalidClause` and searched without their special characters, e.g.
+    ///   `title:(foo` becomes `title:foo`. The operators of the query are then ignored.
+    /// * the clauses referencing a missing field, holding an invalid value
+    ///   or otherwise failing to be transformed into a query are skipped.
+    ///
+    /// A query without any valid clause matches no document.
+    pub fn parse_query_lenient(&self, query: &str) -> (StandardQuery, Vec<ParsingError>) {
+        let query = query.trim();
+        if self.empty_query_matches_all && query.is_empty() {
+            return (StandardQuery::All(AllQuery), Vec::new());
+        }
+        let mut errors = Vec::new();
+        let clauses = match self.parse_clauses(query) {
+            Some(clauses) => clauses,
+            None => {
+                let balanced_query = balance_query(query, &mut errors);
+                let clauses = match self.parse_clauses(&balanced_query) {
+                    Some(clauses) => clauses,
+                    None => self.parse_clauses_lenient(&balanced_query, &mut errors),
+                };
+                if errors.is_empty() {
+                    // e.g. a dangling operator, which is ignored.
+                    errors.push(ParsingError::SyntaxError);
+                }
+                clauses
+            }
+        };
+        match self.transform_clauses(clauses, Some(&mut errors)) {
+            Ok(query) => (query, errors),
+            Err(error) => {
+                errors.push(error);
+                (StandardQuery::MultiTerm(multi_term_query(Vec::new(), Vec::new())), errors)
+            }
+        }
+    }
+
+    /// Returns the clauses of the query, or `None` if
+    /// its syntax is invalid.
+    fn parse_clauses(&self, query: &str) -> Option<Vec<(Occur, Literal)>> {
+        let default_operator = self.default_operator;
+        parser(|input| query_language_with_operator(default_operator, input))
+            .parse(query)
+            .ok()
+            .map(|(clauses, _)| clauses)
+    }
+
+    /// Returns the clauses of a query that cannot be parsed as a whole,
+    /// by parsing each of its space separated parts on its own.
+    ///
+    /// The parts that cannot be parsed are pushed to `errors`,
+    /// and parsed again without their special characters.
+    fn parse_clauses_lenient(&self, query: &str, errors: &mut Vec<ParsingError>) -> Vec<(Occur, Literal)> {
+        let mut clauses = Vec::new();
+        for part in query.split_whitespace() {
+            if part == "AND" || part == "OR" || part == "NOT" {
+                continue;
+            }
+            if let Some(part_clauses) = self.parse_clauses(part) {
+                clauses.extend(part_clauses);
+                continue;
+            }
+            errors.push(ParsingError::InvalidClause(String::from(part)));
+            // the occur of the part is kept, so that
+            // a negative part does not become a positive one.
+            let occur_prefix: String = part.chars().take(1).filter(|&c| c == '+' || c == '-').collect();
+            let stripped_part: String = occur_prefix.chars()
+                .chain(part.chars().filter(|&c| c.is_alphanumeric() || c == ':' || c == '_' || c == '.'))
+                .collect();
+            if let Some(part_clauses) = self.parse_clauses(&stripped_part) {
+                clauses.extend(part_clauses);
+                continue;
+            }
+            if let Some(part_clauses) = self.parse_clauses(&stripped_part.replace(':', " ")) {
+                clauses.extend(part_clauses);
+            }
+        }
+        if self.default_operator == Operator::And && clauses.len() > 1 {
+            for clause in &mut clauses {
+                if clause.0 == Occur::Should {
+                    clause.0 = Occur::Must;
+                }
+            }
+        }
+        clauses
+    }
+
+    /// Returns the query of the clauses of a group, like the whole query.
+    ///
+    /// If `errors` is given, the clauses that cannot be transformed
+    /// are pushed to it and skipped, instead of failing.
+    fn transform_clauses(&self, literals: Vec<(Occur, Literal)>, mut errors: Option<&mut Vec<ParsingError>>) -> Result<StandardQuery, ParsingError> {
+        // a group made only of optional terms and phrases
+        // is a single `MultiTermQuery`.
+        let mut multi_term_only = true;
+        let mut terms_result: Vec<Term> = Vec::new();
+        let mut phrases_result: Vec<PhraseQuery> = Vec::new();
+        let mut clauses: Vec<(Occur, StandardQuery)> = Vec::new();
+        for (occur, literal) in literals {
+            let clause_query = match self.transform_clause(literal, errors.as_mut().map(|errors| &mut **errors)) {
+                Ok(clause_query) => clause_query,
+                Err(error) => {
+                    match errors {
+                        Some(ref mut errors) => {
+                            errors.push(error);
+                            continue;
+                        }
+                        None => {
+                            return Err(error);
+                        }
+                    }
+                }
+            };
+            match clause_query {
+                ClauseQuery::Query(query_opt) => {
+                    multi_term_only = false;
+                    if let Some(query) = query_opt {
+                        clauses.push((occur, query));
+                    }
+                }
+                ClauseQuery::Terms(literal_terms, literal_phrases, boost_opt) => {
+                    if occur != Occur::Should || boost_opt.is_some() {
+                        multi_term_only = false;
+                    }
+                    if literal_terms.is_empty() && literal_phrases.is_empty() {
+                        continue;
+                    }
+                    let clause_query = multi_term_query(literal_terms.clone(), literal_phrases.clone());
+                    clauses.push((occur, boost_query(StandardQuery::MultiTerm(clause_query), boost_opt)));
+                    terms_result.extend(literal_terms);
+                    phrases_result.extend(literal_phrases);
+                }
+            }
+        }
+        if multi_term_only {
+            return Ok(StandardQuery::MultiTerm(multi_term_query(terms_result, phrases_result)));
+        }
+        if clauses.len() == 1 && clauses[0].0 == Occur::Should {
+            return Ok(clauses.pop().unwrap().1);
+        }
+        Ok(StandardQuery::Boolean(BooleanQuery::from(clauses)))
+    }
+
+    /// Returns the query of a clause of a group.
+    fn transform_clause(&self, mut literal: Literal, errors: Option<&mut Vec<ParsingError>>) -> Result<ClauseQuery, ParsingError> {
+        // the boosts of the nested groups compose.
+        let mut boost_opt = None;
+        while let Literal::Boosted(boosted_literal, boost) = literal {
+            boost_opt = Some(boost_opt.unwrap_or(1f32) * boost);
+            literal = *boosted_literal;
+        }
+        if let Literal::Group(group_literals) = literal {
+            let group_query = try!(self.transform_clauses(group_literals, errors));
+            if let StandardQuery::MultiTerm(ref multi_term_query) = group_query {
+                if multi_term_query.num_terms() == 0 {
+                    return Ok(ClauseQuery::Query(None));
                 }
-                Ok(
-                    StandardQuery::MultiTerm(
-                        MultiTermQuery::from(terms_result)
-                    )
-                )
-            }  
-            Err(_) => {
-                Err(ParsingError::SyntaxError)
             }
+            return Ok(ClauseQuery::Query(Some(boost_query(group_query, boost_opt))));
+        }
+        if let Some(range_query) = try!(self.transform_range_literal(&literal)) {
+            return Ok(ClauseQuery::Query(Some(boost_query(StandardQuery::Range(range_query), boost_opt))));
         }
+        if let Some(prefix_query) = try!(self.transform_prefix_literal(&literal)) {
+            return Ok(ClauseQuery::Query(Some(boost_query(prefix_query, boost_opt))));
+        }
+        if let Some(regex_query) = try!(self.transform_regex_literal(&literal)) {
+            return Ok(ClauseQuery::Query(Some(boost_query(StandardQuery::Regex(regex_query), boost_opt))));
+        }
+        if let Some(disjunction_max_query) = try!(self.transform_disjunction_max_literal(&literal)) {
+            return Ok(ClauseQuery::Query(Some(boost_query(StandardQuery::DisjunctionMax(disjunction_max_query), boost_opt))));
+        }
+        let (literal_terms, literal_phrases) = try!(self.transform_literal(literal));
+        Ok(ClauseQuery::Terms(literal_terms, literal_phrases, boost_opt))
     }
 }
 
 
+/// Query of a clause of a group, before the clauses
+/// of the group are combined.
+enum ClauseQuery {
+    /// Terms and phrases, along with their boost. The optional
+    /// terms and phrases of a group are merged into a single `MultiTermQuery`.
+    Terms(Vec<Term>, Vec<PhraseQuery>, Option<Score>),
+    /// Any other query, or `None` for a group without anything to search.
+    Query(Option<StandardQuery>),
+}
+
+
 impl Query for StandardQuery {
     fn search<C: Collector>(&self, searcher: &Searcher, collector: &mut C) -> tantivy_Error<TimerTree> {
         match *self {
             StandardQuery::MultiTerm(ref q) => {
                 q.search(searcher, collector)
             }
+            StandardQuery::Range(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::Boolean(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::Prefix(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::Regex(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::All(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::DisjunctionMax(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::Boost(ref q) => {
+                q.search(searcher, collector)
+            }
+            StandardQuery::PhrasePrefix(ref q) => {
+                q.search(searcher, collector)
+            }
+        }
+    }
+
+    fn scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> tantivy_Error<Box<Scorer + 'a>> {
+        match *self {
+            StandardQuery::MultiTerm(ref q) => q.scorer(searcher, reader),
+            StandardQuery::Range(ref q) => q.scorer(searcher, reader),
+            StandardQuery::Boolean(ref q) => q.scorer(searcher, reader),
+            StandardQuery::Prefix(ref q) => q.scorer(searcher, reader),
+            StandardQuery::Regex(ref q) => q.scorer(searcher, reader),
+            StandardQuery::All(ref q) => q.scorer(searcher, reader),
+            StandardQuery::DisjunctionMax(ref q) => q.scorer(searcher, reader),
+            StandardQuery::Boost(ref q) => q.scorer(searcher, reader),
+            StandardQuery::PhrasePrefix(ref q) => q.scorer(searcher, reader),
+        }
+    }
+
+    fn filter_scorer<'a>(
+        &self,
+        searcher: &Searcher,
+        reader: &'a SegmentReader) -> tantivy_Error<Box<Scorer + 'a>> {
+        match *self {
+            StandardQuery::MultiTerm(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::Range(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::Boolean(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::Prefix(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::Regex(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::All(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::DisjunctionMax(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::Boost(ref q) => q.filter_scorer(searcher, reader),
+            StandardQuery::PhrasePrefix(ref q) => q.filter_scorer(searcher, reader),
         }
     }
 
@@ -181,76 +958,405 @@ impl Query for StandardQuery {
         searcher: &Searcher,
         doc_address: &DocAddress) -> tantivy_Error<Explanation> {
         match *self {
-            StandardQuery::MultiTerm(ref q) => q.explain(searcher, doc_address)
+            StandardQuery::MultiTerm(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::Range(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::Boolean(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::Prefix(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::Regex(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::All(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::DisjunctionMax(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::Boost(ref q) => q.explain(searcher, doc_address),
+            StandardQuery::PhrasePrefix(ref q) => q.explain(searcher, doc_address),
+        }
+    }
+}
+
+
+/// Returns the query of the optional terms and phrases.
+fn multi_term_query(terms: Vec<Term>, phrase_queries: Vec<PhraseQuery>) -> MultiTermQuery {
+    let mut multi_term_query = MultiTermQuery::from(terms);
+    for phrase_query in phrase_queries {
+        multi_term_query.add_phrase(Occur::Should, phrase_query);
+    }
+    multi_term_query
+}
+
+/// Returns the query with balanced quotes and parentheses,
+/// pushing the problems found to `errors`.
+///
+/// An unclosed quote is closed, unmatched closing parentheses
+/// are removed, and unclosed parentheses are closed.
+fn balance_query(query: &str, errors: &mut Vec<ParsingError>) -> String {
+    let mut balanced_query = String::with_capacity(query.len() + 1);
+    let mut escaped = false;
+    let mut in_quote = false;
+    let mut depth = 0usize;
+    let mut unbalanced = false;
+    for c in query.chars() {
+        if escaped {
+            escaped = false;
+        }
+        else {
+            match c {
+                '\\' => {
+                    escaped = true;
+                }
+                '"' => {
+                    in_quote = !in_quote;
+                }
+                '(' if !in_quote => {
+                    depth += 1;
+                }
+                ')' if !in_quote => {
+                    if depth == 0 {
+                        unbalanced = true;
+                        continue;
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
         }
+        balanced_query.push(c);
+    }
+    if in_quote {
+        balanced_query.push('"');
+        errors.push(ParsingError::UnclosedQuote);
     }
+    if unbalanced || depth > 0 {
+        balanced_query.extend(iter::repeat(')').take(depth));
+        errors.push(ParsingError::UnbalancedParentheses);
+    }
+    balanced_query
 }
 
+/// Returns the query, boosted if a boost is given.
+fn boost_query(query: StandardQuery, boost_opt: Option<Score>) -> StandardQuery {
+    match boost_opt {
+        Some(boost) => StandardQuery::Boost(BoostQuery::new(Box::new(query), boost)),
+        None => query,
+    }
+}
 
-fn compute_terms(field: Field, text: &str) -> Vec<Term> {
-    let tokenizer = SimpleTokenizer::new();
+fn compute_terms(field: Field, text: &str, tokenizer: &Tokenizer) -> Vec<Term> {
     let mut tokens = Vec::new();
-    let mut token_it = tokenizer.tokenize(text);
-    while let Some(token_str) = token_it.next() {
-        tokens.push(Term::from_field_text(field, token_str));
+    let mut token_stream = tokenizer.token_stream(text);
+    while let Some(token) = token_stream.next() {
+        tokens.push(Term::from_field_text(field, &token.text));
     }
     tokens
 }
 
+/// Returns the terms of the text, along with the
+/// position of their token.
+fn compute_terms_with_offsets(field: Field, text: &str, tokenizer: &Tokenizer) -> Vec<(u32, Term)> {
+    let mut terms = Vec::new();
+    let mut token_stream = tokenizer.token_stream(text);
+    while let Some(token) = token_stream.next() {
+        terms.push((token.position, Term::from_field_text(field, &token.text)));
+    }
+    terms
+}
 
-#[derive(Debug, Eq, PartialEq)]
+
+#[derive(Debug, PartialEq)]
 pub enum Literal {
     WithField(String, String),
     DefaultField(String),
+    /// `PhraseWithField(field_name, phrase, slop)`
+    PhraseWithField(String, String, u32),
+    /// `PhraseDefaultField(phrase, slop)`
+    PhraseDefaultField(String, u32),
+    /// `Range(field_name, lower, upper)`
+    Range(String, LiteralBound, LiteralBound),
+    /// `PrefixWithField(field_name, prefix)`
+    PrefixWithField(String, String),
+    /// `PrefixDefaultField(prefix)`
+    PrefixDefaultField(String),
+    /// `PhrasePrefixWithField(field_name, phrase)`
+    PhrasePrefixWithField(String, String),
+    /// `PhrasePrefixDefaultField(phrase)`
+    PhrasePrefixDefaultField(String),
+    /// `RegexWithField(field_name, pattern)`
+    RegexWithField(String, String),
+    /// `Boosted(literal, boost)`
+    Boosted(Box<Literal>, f32),
+    /// `Group(clauses)`, for a parenthesized group or
+    /// for the clauses joined by `AND`.
+    Group(Vec<(Occur, Literal)>),
 }
 
+impl Eq for Literal {}
 
-pub fn query_language(input: State<&str>) -> ParseResult<Vec<(Occur, Literal)>, &str>
-{
-    let literal = || {
-        let term_val = || {
-            let word = many1(satisfy(|c: char| c.is_alphanumeric()));
-            let phrase =
-                (char('"'), many1(satisfy(|c| c != '"')), char('"'),)
-                .map(|(_, s, _)| s);
-            phrase.or(word)
-        };
-        
-        let field = many1(letter());
-        let term_query = (field, char(':'), term_val())
-            .map(|(field,_, value)| Literal::WithField(field, value));
-        let term_default_field = term_val().map(Literal::DefaultField);
-        
-        let occur = optional(char('-').or(char('+')))
-            .map(|opt_c| {
-                match opt_c {
-                    Some('-') => Occur::MustNot,
-                    Some('+') => Occur::Must,
-                    _ => Occur::Should, 
+/// Bound of a range literal.
+#[derive(Debug, Eq, PartialEq)]
+pub enum LiteralBound {
+    Included(String),
+    Excluded(String),
+    Unbounded,
+}
+
+impl LiteralBound {
+    fn new(val: Option<String>, inclusive: bool) -> LiteralBound {
+        match val {
+            Some(val) => {
+                if inclusive {
+                    LiteralBound::Included(val)
                 }
-            });        
-        (occur, try(term_query).or(term_default_field)) 
-    };
-     
-    (sep_by(literal(), spaces()), eof())
-    .map(|(first, _)| first)
-    .parse_state(input)
+                else {
+                    LiteralBound::Excluded(val)
+                }
+            }
+            None => LiteralBound::Unbounded,
+        }
+    }
 }
 
+/// Value of a literal, either a word, a prefix, a quoted phrase with its slop,
+/// or a quoted phrase followed by a `*`.
+enum LiteralValue {
+    Word(String),
+    Prefix(String),
+    Phrase(String, u32),
+    PhrasePrefix(String),
+}
 
-#[cfg(test)]
-mod test {
-    
-    use combine::*;
-    use schema::*;
-    use query::MultiTermQuery;
-    use query::Occur;
-    use super::*;
+
+fn literal(input: State<&str>) -> ParseResult<Literal, &str> {
+    let term_val = || {
+        // `/` and `.` are accepted so that facets (`/electronics/phones`),
+        // IPv4 addresses and CIDR blocks (`10.0.0.0/8`) do
+        // not need to be quoted. Any other character can be
+        // escaped with a `\`, e.g. `12\:30`.
+        let word_char = satisfy(|c: char| c.is_alphanumeric() || c == '/' || c == '.')
+            .or((char('\\'), any()).map(|(_, c)| c));
+        let word = (many1(word_char), optional(char('*')))
+            .map(|(word, star)| {
+                if star.is_some() {
+                    LiteralValue::Prefix(word)
+                }
+                else {
+                    LiteralValue::Word(word)
+                }
+            });
+        let slop = (char('~'), many1(digit()))
+            .map(|(_, digits): (_, String)| digits.parse::<u32>().unwrap_or(u32::max_value()));
+        // a phrase is followed either by its slop,
+        // or by a `*` if its last token is a prefix.
+        let phrase_end = char('*').map(|_| None).or(slop.map(Some));
+        let phrase =
+            (char('"'), many1(satisfy(|c| c != '"')), char('"'), optional(phrase_end))
+            .map(|(_, s, _, phrase_end)| {
+                match phrase_end {
+                    Some(None) => LiteralValue::PhrasePrefix(s),
+                    Some(Some(slop)) => LiteralValue::Phrase(s, slop),
+                    None => LiteralValue::Phrase(s, 0),
+                }
+            });
+        phrase.or(word)
+    };
     
-    #[test]
-    pub fn test_query_grammar() {
-        let mut grammar_parser = parser(query_language);
-        assert_eq!(grammar_parser.parse("abc:toto").unwrap().0,
+    let field = || many1(satisfy(|c: char| c.is_alphanumeric() || c == '_'));
+    let term_query = (field(), char(':'), term_val())
+        .map(|(field, _, value)| {
+            match value {
+                LiteralValue::Word(word) => Literal::WithField(field, word),
+                LiteralValue::Prefix(prefix) => Literal::PrefixWithField(field, prefix),
+                LiteralValue::Phrase(phrase, slop) => Literal::PhraseWithField(field, phrase, slop),
+                LiteralValue::PhrasePrefix(phrase) => Literal::PhrasePrefixWithField(field, phrase),
+            }
+        });
+    // `*` stands for an unbounded side of the range.
+    let bound_val = || {
+        let quoted = (char('"'), many1(satisfy(|c| c != '"')), char('"'))
+            .map(|(_, s, _)| Some(s));
+        let unquoted = many1(satisfy(|c: char| !c.is_whitespace() && c != ']' && c != '}'))
+            .map(|s: String| if s == "*" { None } else { Some(s) });
+        quoted.or(unquoted)
+    };
+    let lower_inclusive = char('[').map(|_| true).or(char('{').map(|_| false));
+    let upper_inclusive = char(']').map(|_| true).or(char('}').map(|_| false));
+    let range_query = (
+            field(), char(':'),
+            lower_inclusive, bound_val(),
+            skip_many1(space()), string("TO"), skip_many1(space()),
+            bound_val(), upper_inclusive)
+        .map(|(field, _, lower_inclusive, lower, _, _, _, upper, upper_inclusive)| {
+            Literal::Range(field, LiteralBound::new(lower, lower_inclusive), LiteralBound::new(upper, upper_inclusive))
+        });
+    // `\/` stands for a `/` within the expression, the other
+    // escaped characters are left to the regular expression.
+    let regex_char = satisfy(|c| c != '/' && c != '\\')
+        .map(|c: char| c.to_string())
+        .or((char('\\'), any())
+            .map(|(_, c): (_, char)| if c == '/' { String::from("/") } else { format!("\\{}", c) }));
+    let regex_query = (
+            field(), char(':'),
+            char('/'), many1(regex_char), char('/'),
+            not_followed_by(satisfy(|c: char| !c.is_whitespace() && c != '^' && c != ')')))
+        .map(|(field, _, _, pattern, _, _): (_, _, _, Vec<String>, _, _)| {
+            Literal::RegexWithField(field, pattern.concat())
+        });
+    let term_default_field = term_val()
+        .map(|value| {
+            match value {
+                LiteralValue::Word(word) => Literal::DefaultField(word),
+                LiteralValue::Prefix(prefix) => Literal::PrefixDefaultField(prefix),
+                LiteralValue::Phrase(phrase, slop) => Literal::PhraseDefaultField(phrase, slop),
+                LiteralValue::PhrasePrefix(phrase) => Literal::PhrasePrefixDefaultField(phrase),
+            }
+        });
+    try(regex_query).or(try(range_query)).or(try(term_query)).or(term_default_field)
+        .parse_state(input)
+}
+
+/// Returns a clause of a query, with its occur and its boost:
+/// a literal or a parenthesized group of clauses.
+fn clause(default_operator: Operator, depth: usize, input: State<&str>) -> ParseResult<(Occur, Literal), &str> {
+    let not = try((string("NOT"), not_followed_by(satisfy(|c: char| !c.is_whitespace() && c != '(')), spaces()));
+    let occur = optional(not.map(|_| '-').or(char('-')).or(char('+')))
+        .map(|opt_c| {
+            match opt_c {
+                Some('-') => Occur::MustNot,
+                Some('+') => Occur::Must,
+                _ => Occur::Should, 
+            }
+        });
+    let group = (char('('), spaces(), parser(|input| group(default_operator, depth + 1, input)), spaces(), char(')'))
+        .map(|(_, _, clauses, _, _)| group_literal(clauses));
+    let boost = (char('^'), many1(digit()), optional((char('.'), many1(digit()))))
+        .map(|(_, integer, fraction): (_, String, Option<(_, String)>)| {
+            let boost = match fraction {
+                Some((_, fraction)) => format!("{}.{}", integer, fraction),
+                None => integer,
+            };
+            boost.parse::<f32>().unwrap_or(0f32)
+        });
+    let boosted_literal = (group.or(parser(literal)), optional(boost))
+        .map(|(literal, boost)| {
+            match boost {
+                Some(boost) => Literal::Boosted(Box::new(literal), boost),
+                None => literal,
+            }
+        });
+    (occur, boosted_literal).parse_state(input)
+}
+
+/// Returns the clauses of a group, like the whole query.
+///
+/// The groups may not be nested more than `MAX_GROUP_DEPTH` times,
+/// so that parsing a query does not overflow the stack.
+fn group(default_operator: Operator, depth: usize, input: State<&str>) -> ParseResult<Vec<(Occur, Literal)>, &str> {
+    if depth > MAX_GROUP_DEPTH {
+        return unexpected("group nested too deeply")
+            .map(|_| Vec::new())
+            .parse_state(input);
+    }
+    let operator = |name: &'static str, operator: Operator| {
+        try((skip_many1(space()), string(name), not_followed_by(satisfy(|c: char| !c.is_whitespace() && c != '(')), spaces()))
+            .map(move |_| Some(operator))
+    };
+    // clauses separated by spaces only are joined by the default operator.
+    let separator = operator("AND", Operator::And)
+        .or(operator("OR", Operator::Or))
+        .or(try((skip_many1(space()), not_followed_by(char(')')))).map(|_| None));
+    let clauses = many((separator, parser(|input| clause(default_operator, depth, input))));
+    (parser(|input| clause(default_operator, depth, input)), clauses)
+        .map(|(first, others)| resolve_operators(first, others, default_operator))
+        .parse_state(input)
+}
+
+/// Returns the clauses of a group, given its first clause and its
+/// other clauses along with the operator preceding them, `None`
+/// standing for the default operator.
+///
+/// `AND` takes precedence over `OR`: the clauses joined by `AND` form
+/// a nested group of required clauses, unless the whole group is joined by `AND`.
+fn resolve_operators(first: (Occur, Literal), others: Vec<(Option<Operator>, (Occur, Literal))>, default_operator: Operator) -> Vec<(Occur, Literal)> {
+    let mut conjunctions: Vec<Vec<(Occur, Literal)>> = vec!(vec!(first));
+    for (operator, clause) in others {
+        match operator.unwrap_or(default_operator) {
+            Operator::And => conjunctions.last_mut().unwrap().push(clause),
+            Operator::Or => conjunctions.push(vec!(clause)),
+        }
+    }
+    let required = |clauses: Vec<(Occur, Literal)>| -> Vec<(Occur, Literal)> {
+        clauses
+            .into_iter()
+            .map(|(occur, literal)| {
+                if occur == Occur::Should {
+                    (Occur::Must, literal)
+                }
+                else {
+                    (occur, literal)
+                }
+            })
+            .collect()
+    };
+    if conjunctions.len() == 1 {
+        let clauses = conjunctions.pop().unwrap();
+        return if clauses.len() == 1 { clauses } else { required(clauses) };
+    }
+    conjunctions
+        .into_iter()
+        .map(|mut clauses| {
+            if clauses.len() == 1 {
+                clauses.pop().unwrap()
+            }
+            else {
+                (Occur::Should, Literal::Group(required(clauses)))
+            }
+        })
+        .collect()
+}
+
+/// Returns the literal of a parenthesized group.
+///
+/// A group made of a single optional clause is that clause.
+fn group_literal(mut clauses: Vec<(Occur, Literal)>) -> Literal {
+    if clauses.len() == 1 && clauses[0].0 == Occur::Should {
+        clauses.pop().unwrap().1
+    }
+    else {
+        Literal::Group(clauses)
+    }
+}
+
+/// Grammar of the queries, the clauses separated by spaces
+/// only being joined by `default_operator`.
+pub fn query_language_with_operator(default_operator: Operator, input: State<&str>) -> ParseResult<Vec<(Occur, Literal)>, &str> {
+    (optional(parser(|input| group(default_operator, 0, input))), eof())
+        .map(|(clauses, _)| clauses.unwrap_or_else(Vec::new))
+        .parse_state(input)
+}
+
+/// Grammar of the queries, the clauses separated by spaces
+/// only being joined by `OR`.
+pub fn query_language(input: State<&str>) -> ParseResult<Vec<(Occur, Literal)>, &str> {
+    query_language_with_operator(Operator::Or, input)
+}
+
+
+#[cfg(test)]
+mod test {
+    
+    use combine::*;
+    use schema::*;
+    use query::MultiTermQuery;
+    use query::Occur;
+    use super::*;
+    use core::Index;
+    use query::{Query, RangeBound, BooleanQuery, PrefixQuery, RegexQuery, AllQuery, DisjunctionMaxQuery, BoostQuery, PhrasePrefixQuery};
+    use collector::TopCollector;
+    use collector::tests::TestCollector;
+    use {DocId, DocAddress};
+    use std::iter;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    
+    #[test]
+    pub fn test_query_grammar() {
+        let mut grammar_parser = parser(query_language);
+        assert_eq!(grammar_parser.parse("abc:toto").unwrap().0,
             vec!(
                 (Occur::Should, Literal::WithField(String::from("abc"), String::from("toto")))
             )
@@ -263,23 +1369,28 @@ mod test {
         assert_eq!(
             grammar_parser.parse("\"some phrase query\"").unwrap().0,
             vec!(
-                (Occur::Should, Literal::DefaultField(String::from("some phrase query"))),
+                (Occur::Should, Literal::PhraseDefaultField(String::from("some phrase query"), 0)),
             )
         );
         assert_eq!(
             grammar_parser.parse("field:\"some phrase query\"").unwrap().0,
             vec!(
-                (Occur::Should, Literal::WithField(String::from("field"), String::from("some phrase query")))
+                (Occur::Should, Literal::PhraseWithField(String::from("field"), String::from("some phrase query"), 0))
         ));
         assert_eq!(grammar_parser.parse("field:\"some phrase query\" field:toto a").unwrap().0,
             vec!(
-                (Occur::Should, Literal::WithField(String::from("field"), String::from("some phrase query"))),
+                (Occur::Should, Literal::PhraseWithField(String::from("field"), String::from("some phrase query"), 0)),
                 (Occur::Should, Literal::WithField(String::from("field"), String::from("toto"))),
                 (Occur::Should, Literal::DefaultField(String::from("a"))),
             ));
         assert_eq!(grammar_parser.parse("field:\"a ! b\"").unwrap().0,
             vec!(
-                (Occur::Should, Literal::WithField(String::from("field"), String::from("a ! b"))),
+                (Occur::Should, Literal::PhraseWithField(String::from("field"), String::from("a ! b"), 0)),
+            ));
+        assert_eq!(grammar_parser.parse("\"barack obama\"~2 field:\"a b\"~10").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::PhraseDefaultField(String::from("barack obama"), 2)),
+                (Occur::Should, Literal::PhraseWithField(String::from("field"), String::from("a b"), 10)),
             ));
         assert_eq!(grammar_parser.parse("field:a9e3").unwrap().0,
             vec!(
@@ -293,8 +1404,163 @@ mod test {
             vec!(
                 (Occur::Should, Literal::WithField(String::from("field"), String::from("タンタイビーって早い"))),
             ));
+        assert_eq!(grammar_parser.parse("title:micro* +macro*").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::PrefixWithField(String::from("title"), String::from("micro"))),
+                (Occur::Must, Literal::PrefixDefaultField(String::from("macro"))),
+            ));
+        assert!(grammar_parser.parse("title:*").is_err());
+        assert!(grammar_parser.parse("micro**").is_err());
+        assert_eq!(grammar_parser.parse("path:/api\\/v[0-9]+\\/users/ -path:/.*\\.png/").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::RegexWithField(String::from("path"), String::from("api/v[0-9]+/users"))),
+                (Occur::MustNot, Literal::RegexWithField(String::from("path"), String::from(".*\\.png"))),
+            ));
+        assert_eq!(grammar_parser.parse("title:rust^2.5 +micro*^2 \"a b\"~1^3 -price:[1 TO 2]^0.5").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::Boosted(Box::new(Literal::WithField(String::from("title"), String::from("rust"))), 2.5f32)),
+                (Occur::Must, Literal::Boosted(Box::new(Literal::PrefixDefaultField(String::from("micro"))), 2f32)),
+                (Occur::Should, Literal::Boosted(Box::new(Literal::PhraseDefaultField(String::from("a b"), 1)), 3f32)),
+                (Occur::MustNot, Literal::Boosted(Box::new(Literal::Range(String::from("price"), LiteralBound::Included(String::from("1")), LiteralBound::Included(String::from("2")))), 0.5f32)),
+            ));
+        assert!(grammar_parser.parse("rust^").is_err());
+        assert!(grammar_parser.parse("rust^a").is_err());
+        assert!(grammar_parser.parse("rust ^2").is_err());
+        // a facet is not mistaken for a regular expression.
+        assert_eq!(grammar_parser.parse("facet:/electronics/phones").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::WithField(String::from("facet"), String::from("/electronics/phones"))),
+            ));
     }
     
+    #[test]
+    pub fn test_query_grammar_range() {
+        let mut grammar_parser = parser(query_language);
+        let range = |lower: LiteralBound, upper: LiteralBound| {
+            vec!((Occur::Should, Literal::Range(String::from("price"), lower, upper)))
+        };
+        let included = |val: &str| LiteralBound::Included(String::from(val));
+        let excluded = |val: &str| LiteralBound::Excluded(String::from(val));
+        assert_eq!(grammar_parser.parse("price:[100 TO 500]").unwrap().0, range(included("100"), included("500")));
+        assert_eq!(grammar_parser.parse("price:{100 TO 500}").unwrap().0, range(excluded("100"), excluded("500")));
+        assert_eq!(grammar_parser.parse("price:[100  TO 500}").unwrap().0, range(included("100"), excluded("500")));
+        assert_eq!(grammar_parser.parse("price:{100 TO 500]").unwrap().0, range(excluded("100"), included("500")));
+        assert_eq!(grammar_parser.parse("price:[* TO 100]").unwrap().0, range(LiteralBound::Unbounded, included("100")));
+        assert_eq!(grammar_parser.parse("price:{\"a b\" TO *}").unwrap().0, range(excluded("a b"), LiteralBound::Unbounded));
+        assert_eq!(grammar_parser.parse("price:[\"*\" TO 2017-01-01T00:00:00Z]").unwrap().0, range(included("*"), included("2017-01-01T00:00:00Z")));
+        assert_eq!(grammar_parser.parse("-price:[1 TO 2] a").unwrap().0,
+            vec!(
+                (Occur::MustNot, Literal::Range(String::from("price"), included("1"), included("2"))),
+                (Occur::Should, Literal::DefaultField(String::from("a"))),
+            ));
+        assert!(grammar_parser.parse("price:[100 500]").is_err());
+        assert!(grammar_parser.parse("price:[100TO 500]").is_err());
+        assert!(grammar_parser.parse("price:[100 TO 500").is_err());
+        assert!(grammar_parser.parse("price:(100 TO 500)").is_err());
+    }
+
+    #[test]
+    pub fn test_query_grammar_operators() {
+        let mut grammar_parser = parser(query_language);
+        let word = |val: &str| Literal::DefaultField(String::from(val));
+        assert_eq!(grammar_parser.parse("(rust AND async) OR tokio NOT blocking").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::Group(vec!((Occur::Must, word("rust")), (Occur::Must, word("async"))))),
+                (Occur::Should, word("tokio")),
+                (Occur::MustNot, word("blocking")),
+            ));
+        // `AND` takes precedence over `OR`.
+        assert_eq!(grammar_parser.parse("a OR b AND c").unwrap().0,
+            vec!(
+                (Occur::Should, word("a")),
+                (Occur::Should, Literal::Group(vec!((Occur::Must, word("b")), (Occur::Must, word("c"))))),
+            ));
+        assert_eq!(grammar_parser.parse("a AND b c").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::Group(vec!((Occur::Must, word("a")), (Occur::Must, word("b"))))),
+                (Occur::Should, word("c")),
+            ));
+        assert_eq!(grammar_parser.parse("a AND b").unwrap().0,
+            vec!((Occur::Must, word("a")), (Occur::Must, word("b"))));
+        assert_eq!(grammar_parser.parse("a AND NOT b").unwrap().0, grammar_parser.parse("a AND -b").unwrap().0);
+        assert_eq!(grammar_parser.parse("a  OR  b").unwrap().0, grammar_parser.parse("a b").unwrap().0);
+        // the prefixes apply within the group of their clause.
+        assert_eq!(grammar_parser.parse("+a -b OR c").unwrap().0,
+            vec!((Occur::Must, word("a")), (Occur::MustNot, word("b")), (Occur::Should, word("c"))));
+        assert_eq!(grammar_parser.parse("NOT a").unwrap().0, vec!((Occur::MustNot, word("a"))));
+        assert_eq!(grammar_parser.parse("-( a OR b ) c").unwrap().0,
+            vec!(
+                (Occur::MustNot, Literal::Group(vec!((Occur::Should, word("a")), (Occur::Should, word("b"))))),
+                (Occur::Should, word("c")),
+            ));
+        assert_eq!(grammar_parser.parse("a AND(b OR c)").unwrap().0,
+            vec!(
+                (Occur::Must, word("a")),
+                (Occur::Must, Literal::Group(vec!((Occur::Should, word("b")), (Occur::Should, word("c"))))),
+            ));
+        assert_eq!(grammar_parser.parse("(a b)^2").unwrap().0,
+            vec!((Occur::Should, Literal::Boosted(Box::new(Literal::Group(vec!((Occur::Should, word("a")), (Occur::Should, word("b"))))), 2f32))));
+        assert_eq!(grammar_parser.parse("((a))").unwrap().0, vec!((Occur::Should, word("a"))));
+        assert_eq!(grammar_parser.parse("(path:/a.*/) (price:[1 TO 2])").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::RegexWithField(String::from("path"), String::from("a.*"))),
+                (Occur::Should, Literal::Range(String::from("price"), LiteralBound::Included(String::from("1")), LiteralBound::Included(String::from("2")))),
+            ));
+        // the operators are uppercase words.
+        assert_eq!(grammar_parser.parse("ANDROID or NOTE").unwrap().0,
+            vec!((Occur::Should, word("ANDROID")), (Occur::Should, word("or")), (Occur::Should, word("NOTE"))));
+        assert!(grammar_parser.parse("a AND").is_err());
+        assert!(grammar_parser.parse("NOT").is_err());
+        assert!(grammar_parser.parse("(a").is_err());
+        assert!(grammar_parser.parse("a)").is_err());
+        assert!(grammar_parser.parse("()").is_err());
+    }
+
+    #[test]
+    pub fn test_query_grammar_default_operator() {
+        let mut grammar_parser = parser(|input| query_language_with_operator(Operator::And, input));
+        let word = |val: &str| Literal::DefaultField(String::from(val));
+        assert_eq!(grammar_parser.parse("a b").unwrap().0,
+            vec!((Occur::Must, word("a")), (Occur::Must, word("b"))));
+        assert_eq!(grammar_parser.parse("a b OR c -d").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::Group(vec!((Occur::Must, word("a")), (Occur::Must, word("b"))))),
+                (Occur::Should, Literal::Group(vec!((Occur::Must, word("c")), (Occur::MustNot, word("d"))))),
+            ));
+        assert_eq!(grammar_parser.parse("a").unwrap().0, vec!((Occur::Should, word("a"))));
+    }
+
+    #[test]
+    pub fn test_query_grammar_nested_groups() {
+        let nested = |depth: usize| format!("{}a{}", iter::repeat("(b OR ").take(depth).collect::<String>(), iter::repeat(")").take(depth).collect::<String>());
+        for &(depth, valid) in &[(32, true), (33, false), (100_000, false)] {
+            // deeply nested groups are rejected without overflowing the stack.
+            let query = nested(depth);
+            assert_eq!(parser(query_language).parse(query.as_str()).is_ok(), valid);
+        }
+    }
+
+    #[test]
+    pub fn test_query_grammar_escape() {
+        let mut grammar_parser = parser(query_language);
+        assert_eq!(grammar_parser.parse(r"time:12\:30").unwrap().0,
+            vec!((Occur::Should, Literal::WithField(String::from("time"), String::from("12:30")))));
+        assert_eq!(grammar_parser.parse(r"12\:30 a\ b").unwrap().0,
+            vec!(
+                (Occur::Should, Literal::DefaultField(String::from("12:30"))),
+                (Occur::Should, Literal::DefaultField(String::from("a b"))),
+            ));
+        // an escaped `*` is not a prefix.
+        assert_eq!(grammar_parser.parse(r"micro\*").unwrap().0,
+            vec!((Occur::Should, Literal::DefaultField(String::from("micro*")))));
+        assert_eq!(grammar_parser.parse(r"micro\**").unwrap().0,
+            vec!((Occur::Should, Literal::PrefixDefaultField(String::from("micro*")))));
+        assert_eq!(grammar_parser.parse(r"title:a\-b\\c").unwrap().0,
+            vec!((Occur::Should, Literal::WithField(String::from("title"), String::from(r"a-b\c")))));
+        assert!(grammar_parser.parse(r"a\").is_err());
+        assert!(grammar_parser.parse(r"time:12:30").is_err());
+    }
+
     #[test]
     pub fn test_query_grammar_with_occur() {
         let mut query_parser = parser(query_language);
@@ -305,7 +1571,7 @@ mod test {
         );
         assert_eq!(query_parser.parse("+field:\"some phrase query\" -field:toto a").unwrap().0,
             vec!(
-                (Occur::Must, Literal::WithField(String::from("field"), String::from("some phrase query"))),
+                (Occur::Must, Literal::PhraseWithField(String::from("field"), String::from("some phrase query"), 0)),
                 (Occur::MustNot, Literal::WithField(String::from("field"), String::from("toto"))),
                 (Occur::Should, Literal::DefaultField(String::from("a"))),
             ));
@@ -322,6 +1588,8 @@ mod test {
         assert!(query_parser.parse(":field").is_err());
         assert!(query_parser.parse("f:@e!e").is_err());
         assert!(query_parser.parse("f:@e!e").is_err());
+        assert!(query_parser.parse("\"a b\"~").is_err());
+        assert!(query_parser.parse("a~2").is_err());
     }
     
     #[test]
@@ -330,7 +1598,7 @@ mod test {
         let text_field = schema_builder.add_text_field("text", STRING);
         let title_field = schema_builder.add_text_field("title", STRING);
         let author_field = schema_builder.add_text_field("author", STRING);
-        let query_parser = QueryParser::new(schema_builder.build(), vec!(text_field, author_field));
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(text_field, author_field));
         assert!(query_parser.parse_query("a:b").is_err());
         {
             let terms = vec!(Term::from_field_text(title_field, "abctitle"));
@@ -365,4 +1633,834 @@ mod test {
         }
     }
 
+    #[test]
+    pub fn test_query_parser_raw() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let sku_field = schema_builder.add_text_field("sku", TextOptions::default().set_indexing_options(TextIndexingOptions::Raw));
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(text_field));
+        {
+            let terms = vec!(Term::from_field_text(sku_field, "AB-123/X"));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("sku:\"AB-123/X\"").unwrap(), query);
+        }
+        {
+            let terms = vec!(Term::from_field_text(sku_field, "AB123"));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("sku:AB123").unwrap(), query);
+        }
+        {
+            let terms = vec!(Term::from_field_text(text_field, "AB-123"));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("text:\"AB-123\"").unwrap(), query);
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_untokenized_case() {
+        let mut schema_builder = SchemaBuilder::default();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let tag_field = schema_builder.add_text_field("tag", TextOptions::default().set_indexing_options(TextIndexingOptions::UntokenizedLowercase));
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(title_field));
+        {
+            let terms = vec!(Term::from_field_text(id_field, "AbC-12"));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("id:\"AbC-12\"").unwrap(), query);
+        }
+        {
+            let terms = vec!(Term::from_field_text(tag_field, "abc-12"));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("tag:\"AbC-12\"").unwrap(), query);
+        }
+        {
+            let terms = vec!(Term::from_field_text(title_field, "abc"), Term::from_field_text(title_field, "12"));
+            let mut multi_term_query = MultiTermQuery::from(Vec::<Term>::new());
+            multi_term_query.add_phrase(Occur::Should, PhraseQuery::new(terms));
+            let query = StandardQuery::MultiTerm(multi_term_query);
+            assert_eq!(query_parser.parse_query("title:\"AbC-12\"").unwrap(), query);
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_phrase() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let freq_options = TextOptions::default().set_indexing_options(TextIndexingOptions::TokenizedWithFreq);
+        let comment_field = schema_builder.add_text_field("comment", freq_options);
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(title_field, body_field));
+        let phrase = |field: Field, slop: u32| {
+            PhraseQuery::new(vec!(Term::from_field_text(field, "barack"), Term::from_field_text(field, "obama"))).set_slop(slop)
+        };
+        {
+            let mut phrase_clause = MultiTermQuery::from(Vec::<Term>::new());
+            phrase_clause.add_phrase(Occur::Should, phrase(title_field, 2));
+            let term_clause = MultiTermQuery::from(vec!(Term::from_field_text(body_field, "michelle")));
+            let query = StandardQuery::Boolean(BooleanQuery::from(vec!(
+                (Occur::Must, StandardQuery::MultiTerm(phrase_clause)),
+                (Occur::MustNot, StandardQuery::MultiTerm(term_clause)),
+            )));
+            assert_eq!(query_parser.parse_query("+title:\"Barack Obama\"~2 -body:michelle").unwrap(), query);
+        }
+        {
+            // the phrase is searched in each of the default fields.
+            let mut multi_term_query = MultiTermQuery::from(Vec::<Term>::new());
+            multi_term_query.add_phrase(Occur::Should, phrase(title_field, 0));
+            multi_term_query.add_phrase(Occur::Should, phrase(body_field, 0));
+            let query = StandardQuery::MultiTerm(multi_term_query);
+            assert_eq!(query_parser.parse_query("\"barack obama\"").unwrap(), query);
+        }
+        {
+            // a single token is searched as a term.
+            let terms = vec!(Term::from_field_text(comment_field, "obama"));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("comment:\"Obama\"~3").unwrap(), query);
+            assert_eq!(query_parser.parse_query("comment:obama").unwrap(), query);
+        }
+        match query_parser.parse_query("comment:\"barack obama\"") {
+            Err(ParsingError::PositionsNotIndexed(field_name)) => {
+                assert_eq!(field_name, "comment");
+            }
+            _ => {
+                panic!("Expected an error, as positions are not indexed");
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_facet() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let facet_field = schema_builder.add_facet_field("facet_field", FacetOptions::default().set_indexed());
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(text_field));
+        let terms = vec!(Term::from_facet(facet_field, &Facet::from_text("/electronics/phones")));
+        let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms)); 
+        assert_eq!(
+            query_parser.parse_query("facet_field:/electronics/phones").unwrap(), 
+            query
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_bool() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let bool_field = schema_builder.add_bool_field("is_published", BoolOptions::default().set_indexed());
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(text_field));
+        for &val in &[true, false] {
+            let terms = vec!(Term::from_field_bool(bool_field, val));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms)); 
+            assert_eq!(
+                query_parser.parse_query(&format!("is_published:{}", val)).unwrap(), 
+                query
+            );
+        }
+        match query_parser.parse_query("is_published:yes") {
+            Err(ParsingError::ExpectedBool(field_name, field_value)) => {
+                assert_eq!(field_name, "is_published");
+                assert_eq!(field_value, "yes");
+            }
+            _ => {
+                panic!("Expected a bool parsing error");
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_range() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for (i, text) in ["apple", "Banana", "cherry pie"].iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                doc.add_u32(price_field, (i as u32 + 1) * 100);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        {
+            let query = RangeQuery::new_with_bounds(
+                price_field,
+                RangeBound::Excluded(Term::from_field_u32(price_field, 100)),
+                RangeBound::Unbounded);
+            assert_eq!(query_parser.parse_query("price:{100 TO *]").unwrap(), StandardQuery::Range(query));
+        }
+        {
+            // the bounds of the tokenized fields are analyzed.
+            let query = RangeQuery::new(Term::from_field_text(text_field, "b"), Term::from_field_text(text_field, "c"));
+            assert_eq!(query_parser.parse_query("text:[B TO C]").unwrap(), StandardQuery::Range(query));
+        }
+        let searcher = index.searcher();
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("price:[100 TO 300]"), vec!(0, 1, 2));
+        assert_eq!(search("price:{100 TO 300}"), vec!(1));
+        assert_eq!(search("price:[100 TO 300}"), vec!(0, 1));
+        assert_eq!(search("price:{100 TO 300]"), vec!(1, 2));
+        assert_eq!(search("price:[* TO 200]"), vec!(0, 1));
+        assert_eq!(search("price:{200 TO *}"), vec!(2));
+        assert_eq!(search("text:[banana TO cherry]"), vec!(1, 2));
+        assert_eq!(search("text:{apple TO \"cherry pie\"}"), vec!(1, 2));
+        match query_parser.parse_query("price:[a TO 100]") {
+            Err(ParsingError::ExpectedU32(field_name, field_value)) => {
+                assert_eq!(field_name, "price");
+                assert_eq!(field_value, "a");
+            }
+            _ => {
+                panic!("Expected a u32 parsing error");
+            }
+        }
+        // ranges can be combined with the other clauses.
+        assert_eq!(search("+price:[100 TO 200] -text:apple"), vec!(1));
+        assert_eq!(search("apple price:[300 TO *]"), vec!(0, 2));
+        assert_eq!(search("+pie price:[300 TO *] -price:{200 TO 300}"), vec!(2));
+        match query_parser.parse_query("nofield:[1 TO 100]") {
+            Err(ParsingError::FieldDoesNotExist(field_name)) => {
+                assert_eq!(field_name, "nofield");
+            }
+            _ => {
+                panic!("Expected a missing field error");
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_prefix() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let count_field = schema_builder.add_u32_field("count", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(title, body) in &[("Microsoft", "windows"), ("microscope", "lens"), ("macro", "microwave")] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(title_field, body_field));
+        {
+            // prefixes are lowercased, like the terms of the field.
+            let query = StandardQuery::Prefix(PrefixQuery::new(Term::from_field_text(title_field, "micro")));
+            assert_eq!(query_parser.parse_query("title:Micro*").unwrap(), query);
+        }
+        let searcher = index.searcher();
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("title:micro*"), vec!(0, 1));
+        assert_eq!(search("micro*"), vec!(0, 1, 2));
+        assert_eq!(search("micro* -lens"), vec!(0, 2));
+        assert_eq!(search("+title:m* windows"), vec!(0, 1, 2));
+        assert_eq!(search("title:microscope*"), vec!(1));
+        assert!(search("title:microscopes*").is_empty());
+        match query_parser.parse_query("count:1*") {
+            Err(ParsingError::UnsupportedPrefix(field_name)) => {
+                assert_eq!(field_name, "count");
+            }
+            _ => {
+                panic!("Expected an unsupported prefix error");
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_grammar_phrase_prefix() {
+        let parse = |query: &str| parser(query_language).parse(query).ok().map(|(clauses, _)| clauses);
+        assert_eq!(
+            parse("\"harry pot\"*").unwrap(),
+            vec!((Occur::Should, Literal::PhrasePrefixDefaultField(String::from("harry pot")))));
+        assert_eq!(
+            parse("+title:\"harry pot\"*^2 \"harry potter\"~1").unwrap(),
+            vec!(
+                (Occur::Must, Literal::Boosted(Box::new(Literal::PhrasePrefixWithField(String::from("title"), String::from("harry pot"))), 2f32)),
+                (Occur::Should, Literal::PhraseDefaultField(String::from("harry potter"), 1)),
+            ));
+        assert!(parse("\"harry pot\"~1*").is_none());
+        assert!(parse("\"harry pot\"**").is_none());
+    }
+
+    #[test]
+    pub fn test_query_parser_phrase_prefix() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let freq_field = schema_builder.add_text_field("freq_only", TextOptions::default().set_indexing_options(TextIndexingOptions::TokenizedWithFreq));
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for title in &["Harry Potter", "harry pottery", "potter harry", "harry houdini"] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(freq_field, title);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(title_field));
+        {
+            let query = StandardQuery::PhrasePrefix(PhrasePrefixQuery::new(vec!(
+                Term::from_field_text(title_field, "harry"),
+                Term::from_field_text(title_field, "pot"))));
+            assert_eq!(query_parser.parse_query("\"Harry Pot\"*").unwrap(), query);
+            assert_eq!(query_parser.parse_query("title:\"harry pot\"*").unwrap(), query);
+            // a single token is a prefix.
+            let prefix_query = StandardQuery::Prefix(PrefixQuery::new(Term::from_field_text(title_field, "pot")));
+            assert_eq!(query_parser.parse_query("\"Pot\"*").unwrap(), prefix_query);
+        }
+        let searcher = index.searcher();
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("\"harry pot\"*"), vec!(0, 1));
+        assert_eq!(search("\"harry potter\"*"), vec!(0));
+        assert_eq!(search("\"harry pot\"* -pottery"), vec!(0));
+        assert_eq!(search("\"harry pot\"* OR \"harry h\"*"), vec!(0, 1, 3));
+        assert_eq!(search("\"pot\"*"), vec!(0, 1, 2));
+        match query_parser.parse_query("freq_only:\"harry pot\"*") {
+            Err(ParsingError::PositionsNotIndexed(field_name)) => {
+                assert_eq!(field_name, "freq_only");
+            }
+            _ => {
+                panic!("Expected a positions not indexed error");
+            }
+        }
+        // a single token does not need the positions.
+        assert!(query_parser.parse_query("freq_only:\"pot\"*").is_ok());
+    }
+
+    #[test]
+    pub fn test_query_parser_regex() {
+        let mut schema_builder = SchemaBuilder::default();
+        let path_field = schema_builder.add_text_field("path", STRING);
+        let count_field = schema_builder.add_u32_field("count", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for path in &["/api/v1/users", "/api/v2/users", "/api/v12/groups", "/index.html"] {
+                let mut doc = Document::default();
+                doc.add_text(path_field, path);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(path_field));
+        assert_eq!(
+            query_parser.parse_query("path:/\\/api\\/v[0-9]+\\/users/").unwrap(),
+            StandardQuery::Regex(RegexQuery::new(path_field, "/api/v[0-9]+/users").unwrap()));
+        let searcher = index.searcher();
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("path:/\\/api\\/v[0-9]+\\/users/"), vec!(0, 1));
+        assert_eq!(search("path:/\\/api\\/.*/ -path:/.*groups/"), vec!(0, 1));
+        assert_eq!(search("path:/.*\\.html/"), vec!(3));
+        assert!(search("path:/api.*/").is_empty());
+        match query_parser.parse_query("path:/a(b/") {
+            Err(ParsingError::InvalidRegex(pattern)) => {
+                assert_eq!(pattern, "a(b");
+            }
+            _ => {
+                panic!("Expected an invalid regex error");
+            }
+        }
+        match query_parser.parse_query("count:/1.*/") {
+            Err(ParsingError::UnsupportedRegex(field_name)) => {
+                assert_eq!(field_name, "count");
+            }
+            _ => {
+                panic!("Expected an unsupported regex error");
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_empty() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["a", "b", "a b"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let mut query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let searcher = index.searcher();
+        let search = |query_parser: &QueryParser, query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert!(search(&query_parser, "").is_empty());
+        query_parser.set_empty_query_matches_all(true);
+        assert_eq!(query_parser.parse_query("  ").unwrap(), StandardQuery::All(AllQuery));
+        assert_eq!(search(&query_parser, ""), vec!(0, 1, 2));
+        assert_eq!(search(&query_parser, "b"), vec!(1, 2));
+    }
+
+    #[test]
+    pub fn test_query_parser_disjunction_max() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let docs = [
+                ("rust tips and more", "rust notes and other stuff"),
+                ("rust", "nothing here at all"),
+                ("cooking", "food"),
+                ("garden", "plants")];
+            for &(title, body) in &docs {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let mut query_parser = QueryParser::for_index(&index, vec!(title_field, body_field));
+        let searcher = index.searcher();
+        let ranking = |query_parser: &QueryParser, query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut top_collector = TopCollector::with_limit(4);
+            query.search(&searcher, &mut top_collector).unwrap();
+            top_collector
+                .docs()
+                .into_iter()
+                .map(|doc_address| doc_address.doc())
+                .collect::<Vec<DocId>>()
+        };
+        // summing the scores of the fields favors the document
+        // having `rust` in both of its fields.
+        assert_eq!(ranking(&query_parser, "rust"), vec!(0, 1));
+        query_parser.set_disjunction_max_tie_breaker(Some(0f32));
+        assert_eq!(
+            query_parser.parse_query("rust").unwrap(),
+            StandardQuery::DisjunctionMax(DisjunctionMaxQuery::new(vec!(
+                StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(title_field, "rust")))),
+                StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(body_field, "rust")))),
+            ), 0f32)));
+        // the short title of the second document is the best match.
+        assert_eq!(ranking(&query_parser, "rust"), vec!(1, 0));
+        query_parser.set_disjunction_max_tie_breaker(Some(0.5f32));
+        assert_eq!(ranking(&query_parser, "rust"), vec!(1, 0));
+        assert_eq!(ranking(&query_parser, "rust -title:tips"), vec!(1));
+        assert_eq!(ranking(&query_parser, "rust garden"), vec!(3, 1, 0));
+        // a tie breaker of 1 sums the scores of the fields.
+        query_parser.set_disjunction_max_tie_breaker(Some(1f32));
+        assert_eq!(ranking(&query_parser, "rust"), vec!(0, 1));
+        // fields can still be searched explicitly.
+        assert_eq!(query_parser.parse_query("title:rust").unwrap(), StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(title_field, "rust")))));
+    }
+
+    #[test]
+    pub fn test_query_parser_boost() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for (i, text) in ["a", "b", "a b", "c"].iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                doc.add_u32(price_field, i as u32);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(text_field));
+        let term_query = |text: &str| StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(text_field, text))));
+        assert_eq!(
+            query_parser.parse_query("a^2.5").unwrap(),
+            StandardQuery::Boost(BoostQuery::new(Box::new(term_query("a")), 2.5f32)));
+        assert_eq!(
+            query_parser.parse_query("a^2 b").unwrap(),
+            StandardQuery::Boolean(BooleanQuery::from(vec!(
+                (Occur::Should, StandardQuery::Boost(BoostQuery::new(Box::new(term_query("a")), 2f32))),
+                (Occur::Should, term_query("b")),
+            ))));
+        let searcher = index.searcher();
+        let score = |query_str: &str, doc| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            query.explain(&searcher, &DocAddress(0, doc)).unwrap().val()
+        };
+        let ranking = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut top_collector = TopCollector::with_limit(4);
+            query.search(&searcher, &mut top_collector).unwrap();
+            top_collector
+                .docs()
+                .into_iter()
+                .map(|doc_address| doc_address.doc())
+                .collect::<Vec<DocId>>()
+        };
+        assert!((score("a^2.5", 0) - 2.5f32 * score("a", 0)).abs() < 0.0001f32);
+        assert_eq!(ranking("a^10 b"), vec!(0, 2, 1));
+        assert_eq!(ranking("a b^10"), vec!(1, 2, 0));
+        assert_eq!(ranking("+a^10 -b"), vec!(0));
+        // ranges are scored by their boost.
+        assert_eq!(ranking("b price:[2 TO 3]^5"), vec!(2, 3, 1));
+        assert!((score("price:[2 TO 3]^5", 3) - 5f32).abs() < 0.0001f32);
+    }
+
+    #[test]
+    pub fn test_query_parser_boolean() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(title, body) in &[("a", "b"), ("a c", "d"), ("b", "c d")] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(body_field, body);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let query_parser = QueryParser::for_index(&index, vec!(title_field, body_field));
+        let searcher = index.searcher();
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("+a -c"), vec!(0));
+        assert_eq!(search("+a +b"), vec!(0));
+        assert_eq!(search("+d b"), vec!(1, 2));
+        assert_eq!(search("a -title:b -body:b"), vec!(1));
+        assert_eq!(search("+body:d -\"c d\""), vec!(1));
+        assert!(search("-a").is_empty());
+        assert!(search("+z a").is_empty());
+        {
+            // the optional clauses add their score.
+            let query = query_parser.parse_query("+d b").unwrap();
+            let score = |doc: DocId| query.explain(&searcher, &DocAddress(0, doc)).unwrap().val();
+            assert!(score(2) > score(1));
+        }
+        assert_eq!(query_parser.parse_query("+title:a -body:b c").unwrap().num_terms(), 4);
+    }
+
+    #[test]
+    pub fn test_query_parser_ip() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let ip_field = schema_builder.add_ip_field("src_ip", IpOptions::default().set_indexed());
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(text_field));
+        {
+            let terms = vec!(Term::from_field_ip(ip_field, parse_ip("10.0.0.1").unwrap()));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("src_ip:10.0.0.1").unwrap(), query);
+        }
+        {
+            let terms = vec!(Term::from_field_ip(ip_field, parse_ip("fe80::1").unwrap()));
+            let query = StandardQuery::MultiTerm(MultiTermQuery::from(terms));
+            assert_eq!(query_parser.parse_query("src_ip:\"fe80::1\"").unwrap(), query);
+        }
+        for cidr in &["10.0.0.0/8", "2001:db8::/32"] {
+            let (lower, upper) = parse_cidr(cidr).unwrap();
+            let range_query = RangeQuery::new(Term::from_field_ip(ip_field, lower), Term::from_field_ip(ip_field, upper));
+            let query = StandardQuery::Range(range_query);
+            assert_eq!(query_parser.parse_query(&format!("src_ip:\"{}\"", cidr)).unwrap(), query);
+            assert_eq!(query_parser.parse_query(&format!("+src_ip:\"{}\"", cidr)).unwrap().num_terms(), 1);
+        }
+        assert!(query_parser.parse_query("src_ip:10.0.0.0/8").is_ok());
+        match query_parser.parse_query("src_ip:10.0.0.0/33") {
+            Err(ParsingError::ExpectedIp(field_name, field_value)) => {
+                assert_eq!(field_name, "src_ip");
+                assert_eq!(field_value, "10.0.0.0/33");
+            }
+            _ => {
+                panic!("Expected an IP parsing error");
+            }
+        }
+        match query_parser.parse_query("src_ip:localhost") {
+            Err(ParsingError::ExpectedIp(_, _)) => {}
+            _ => {
+                panic!("Expected an IP parsing error");
+            }
+        }
+        {
+            let (lower, upper) = parse_cidr("10.0.0.0/8").unwrap();
+            let range_query = || StandardQuery::Range(RangeQuery::new(Term::from_field_ip(ip_field, lower), Term::from_field_ip(ip_field, upper)));
+            let term_query = StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(text_field, "a"))));
+            let query = StandardQuery::Boolean(BooleanQuery::from(vec!(
+                (Occur::Should, term_query),
+                (Occur::Should, range_query()),
+            )));
+            assert_eq!(query_parser.parse_query("a src_ip:10.0.0.0/8").unwrap(), query);
+            let query = StandardQuery::Boolean(BooleanQuery::from(vec!((Occur::MustNot, range_query()))));
+            assert_eq!(query_parser.parse_query("-src_ip:10.0.0.0/8").unwrap(), query);
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_escape() {
+        let mut schema_builder = SchemaBuilder::default();
+        let time_field = schema_builder.add_text_field("time", STRING);
+        let query_parser = QueryParser::new(schema_builder.build().unwrap(), vec!(time_field));
+        let query = StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(time_field, "12:30"))));
+        assert_eq!(query_parser.parse_query(r"time:12\:30").unwrap(), query);
+        assert_eq!(query_parser.parse_query(r"12\:30").unwrap(), query);
+    }
+
+    #[test]
+    pub fn test_query_parser_errors() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_text_field("freq_only", TextOptions::default().set_indexing_options(TextIndexingOptions::TokenizedWithFreq));
+        schema_builder.add_text_field("custom", TEXT.set_tokenizer("missing"));
+        let stored_field = schema_builder.add_text_field("stored_only", STORED);
+        schema_builder.add_u32_field("count", U32Options::default().set_indexed());
+        schema_builder.add_u32_field("fast_count", U32Options::default().set_fast());
+        schema_builder.add_i64_field("delta", I64Options::default().set_indexed());
+        schema_builder.add_f64_field("ratio", F64Options::default().set_indexed());
+        schema_builder.add_date_field("date", DateOptions::default().set_indexed());
+        schema_builder.add_bool_field("is_published", BoolOptions::default().set_indexed());
+        schema_builder.add_ip_field("src_ip", IpOptions::default().set_indexed());
+        schema_builder.add_bytes_field("payload", BytesOptions::default().set_fast());
+        let schema = schema_builder.build().unwrap();
+        let query_parser = QueryParser::new(schema.clone(), vec!(text_field));
+        let error = |query: &str| format!("{:?}", query_parser.parse_query(query).err().unwrap());
+        assert_eq!(error("title:"), "SyntaxError");
+        assert_eq!(error("\"a b"), "SyntaxError");
+        assert_eq!(error("nofield:a"), r#"FieldDoesNotExist("nofield")"#);
+        assert_eq!(error("a -nofield:\"a b\""), r#"FieldDoesNotExist("nofield")"#);
+        assert_eq!(error("count:abc"), r#"ExpectedU32("count", "abc")"#);
+        assert_eq!(error("delta:abc"), r#"ExpectedI64("delta", "abc")"#);
+        assert_eq!(error("ratio:abc"), r#"ExpectedF64("ratio", "abc")"#);
+        assert_eq!(error("date:tomorrow"), r#"ExpectedDate("date", "tomorrow")"#);
+        assert_eq!(error("is_published:maybe"), r#"ExpectedBool("is_published", "maybe")"#);
+        assert_eq!(error("src_ip:abc"), r#"ExpectedIp("src_ip", "abc")"#);
+        assert_eq!(error("stored_only:abc"), r#"FieldNotIndexed("stored_only")"#);
+        assert_eq!(error("fast_count:3"), r#"FieldNotIndexed("fast_count")"#);
+        assert_eq!(error("fast_count:[1 TO 3]"), r#"FieldNotIndexed("fast_count")"#);
+        assert_eq!(error("payload:abc"), r#"FieldNotIndexed("payload")"#);
+        assert_eq!(error("custom:abc"), r#"UnknownTokenizer("custom", "missing")"#);
+        assert_eq!(error("freq_only:\"a b\""), r#"PositionsNotIndexed("freq_only")"#);
+        assert_eq!(error("count:1*"), r#"UnsupportedPrefix("count")"#);
+        assert_eq!(error("count:/1.*/"), r#"UnsupportedRegex("count")"#);
+        assert_eq!(error("text:/a(/"), r#"InvalidRegex("a(")"#);
+        {
+            // the default fields must be indexed as well.
+            let query_parser = QueryParser::new(schema, vec!(text_field, stored_field));
+            assert_eq!(format!("{:?}", query_parser.parse_query("abc").err().unwrap()), r#"FieldNotIndexed("stored_only")"#);
+            assert_eq!(format!("{:?}", query_parser.parse_query("abc*").err().unwrap()), r#"FieldNotIndexed("stored_only")"#);
+            assert!(query_parser.parse_query("text:abc").is_ok());
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_operators() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for text in &["rust async", "rust blocking", "tokio", "tokio blocking", "async", "rust async blocking"] {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let mut query_parser = QueryParser::new(index.schema(), vec!(text_field));
+        {
+            let term_query = |text: &str| StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_text(text_field, text))));
+            let query = StandardQuery::Boolean(BooleanQuery::from(vec!(
+                (Occur::Should, StandardQuery::Boolean(BooleanQuery::from(vec!(
+                    (Occur::Must, term_query("rust")),
+                    (Occur::Must, term_query("async")),
+                )))),
+                (Occur::Should, term_query("tokio")),
+                (Occur::MustNot, term_query("blocking")),
+            )));
+            assert_eq!(query_parser.parse_query("(rust AND async) OR tokio NOT blocking").unwrap(), query);
+        }
+        {
+            let search = |query_parser: &QueryParser, query_str: &str| {
+                let query = query_parser.parse_query(query_str).unwrap();
+                let mut test_collector = TestCollector::default();
+                query.search(&searcher, &mut test_collector).unwrap();
+                test_collector.docs()
+            };
+            assert_eq!(search(&query_parser, "(rust AND async) OR tokio NOT blocking"), vec!(0, 2));
+            assert_eq!(search(&query_parser, "rust AND async OR tokio"), vec!(0, 2, 3, 5));
+            assert_eq!(search(&query_parser, "rust AND (async OR blocking)"), vec!(0, 1, 5));
+            assert_eq!(search(&query_parser, "rust -(async OR tokio)"), vec!(1));
+            assert_eq!(search(&query_parser, "+rust async"), vec!(0, 1, 5));
+            assert_eq!(search(&query_parser, "(tokio OR async)^2 AND NOT blocking"), vec!(0, 2, 4));
+            query_parser.set_default_operator(Operator::And);
+            assert_eq!(search(&query_parser, "rust async"), vec!(0, 5));
+            assert_eq!(search(&query_parser, "rust async OR tokio"), vec!(0, 2, 3, 5));
+            assert_eq!(search(&query_parser, "rust -blocking"), vec!(0));
+            assert_eq!(search(&query_parser, "tokio"), vec!(2, 3));
+        }
+        {
+            let nested = |depth: usize| format!("{}rust{}", iter::repeat("(").take(depth).collect::<String>(), iter::repeat(")").take(depth).collect::<String>());
+            assert!(query_parser.parse_query(&nested(32)).is_ok());
+            match query_parser.parse_query(&nested(10_000)) {
+                Err(ParsingError::SyntaxError) => {}
+                _ => {
+                    panic!("Expected a syntax error");
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_u32() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for (i, text) in ["apple", "banana", "cherry", "apple pie"].iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_text(text_field, text);
+                doc.add_u32(price_field, (i as u32 + 1) * 100);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(text_field));
+        assert_eq!(
+            query_parser.parse_query("price:200").unwrap(),
+            StandardQuery::MultiTerm(MultiTermQuery::from(vec!(Term::from_field_u32(price_field, 200)))));
+        let search = |query_str: &str| {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search("price:200"), vec!(1));
+        assert_eq!(search("price:\"300\""), vec!(2));
+        assert!(search("price:250").is_empty());
+        assert_eq!(search("price:100 banana"), vec!(0, 1));
+        assert_eq!(search("price:[150 TO 300]"), vec!(1, 2));
+        assert_eq!(search("+apple -price:[* TO 200}"), vec!(3));
+        assert_eq!(search("price:100 OR (apple AND price:{100 TO *})"), vec!(0, 3));
+        for &value in &["ten", "1.5", "4294967296"] {
+            match query_parser.parse_query(&format!("price:{}", value)) {
+                Err(ParsingError::ExpectedU32(field_name, field_value)) => {
+                    assert_eq!(field_name, "price");
+                    assert_eq!(field_value, value);
+                }
+                _ => {
+                    panic!("Expected a u32 parsing error");
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_lenient() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(title, text) in &[("foo", "barack obama"), ("bar", "rust async"), ("obama", "foo")] {
+                let mut doc = Document::default();
+                doc.add_text(title_field, title);
+                doc.add_text(text_field, text);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(text_field));
+        let search = |query_str: &str| {
+            let (query, errors) = query_parser.parse_query_lenient(query_str);
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            let errors: Vec<String> = errors.iter().map(|error| format!("{:?}", error)).collect();
+            (test_collector.docs(), errors)
+        };
+        {
+            let (query, errors) = query_parser.parse_query_lenient("title:foo text:rust");
+            assert_eq!(query, query_parser.parse_query("title:foo text:rust").unwrap());
+            assert!(errors.is_empty());
+        }
+        assert_eq!(search("title:(foo"), (vec!(0), vec!(String::from("UnbalancedParentheses"), String::from(r#"InvalidClause("title:(foo)")"#))));
+        assert_eq!(search("\"barack obama"), (vec!(0), vec!(String::from("UnclosedQuote"))));
+        assert_eq!(search("rust) AND (async"), (vec!(1), vec!(String::from("UnbalancedParentheses"))));
+        assert_eq!(search("(foo \"barack"), (vec!(0, 2), vec!(String::from("UnclosedQuote"), String::from("UnbalancedParentheses"))));
+        assert_eq!(search("obama -(foo"), (vec!(0), vec!(String::from("UnbalancedParentheses"))));
+        assert_eq!(search("rust AND"), (vec!(1), vec!(String::from("SyntaxError"))));
+        assert_eq!(search("\"\"~~"), (vec!(), vec!(String::from(r#"InvalidClause("\"\"~~")"#))));
+        // the clauses that cannot be transformed are skipped.
+        assert_eq!(search("nofield:rust async"), (vec!(1), vec!(String::from(r#"FieldDoesNotExist("nofield")"#))));
+        assert_eq!(search("nofield:rust (title:bar OR text:/a(/)"), (vec!(1), vec!(String::from(r#"FieldDoesNotExist("nofield")"#), String::from(r#"InvalidRegex("a(")"#))));
+        // the strict parser still fails.
+        for query_str in &["title:(foo", "\"barack obama", "rust AND", "\"\"~~", "nofield:rust async"] {
+            assert!(query_parser.parse_query(query_str).is_err());
+        }
+    }
+
+    #[test]
+    pub fn test_query_parser_lenient_random_punctuation() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let count_field = schema_builder.add_u32_field("count", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let mut doc = Document::default();
+            doc.add_text(title_field, "a title");
+            doc.add_u32(count_field, 3);
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let query_parser = QueryParser::new(index.schema(), vec!(title_field));
+        let chars: Vec<char> = "\"():^~*+-\\/[]{}!., _aAORNDTtitle:count:3é".chars().collect();
+        let seed: &[u32; 4] = &[1, 2, 3, 4];
+        let mut rng = XorShiftRng::from_seed(*seed);
+        for _ in 0..10_000 {
+            let len = rng.gen_range(0, 20);
+            let query_str: String = (0..len).map(|_| *rng.choose(&chars).unwrap()).collect();
+            let (query, errors) = query_parser.parse_query_lenient(&query_str);
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            if query_parser.parse_query(&query_str).is_err() {
+                assert!(!errors.is_empty(), "No error reported for {:?}", query_str);
+            }
+        }
+    }
+
 }
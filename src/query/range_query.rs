@@ -0,0 +1,497 @@
+use Result;
+use schema::{Term, Field};
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use common::TimerTree;
+use common::BitSet;
+use core::searcher::Searcher;
+use collector::Collector;
+use SegmentLocalId;
+use core::SegmentReader;
+use query::segment_docs_builder::SegmentDocsBuilder;
+use postings::DocSet;
+use postings::BitSetDocSet;
+use ScoredDoc;
+use DocAddress;
+use query::Explanation;
+use byteorder::{BigEndian, ByteOrder};
+
+
+/// Bound of a `RangeQuery`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum RangeBound {
+    /// The term is part of the range.
+    Included(Term),
+    /// The term is not part of the range.
+    Excluded(Term),
+    /// The range is not bounded on this side.
+    Unbounded,
+}
+
+impl RangeBound {
+    fn term(&self,) -> Option<&Term> {
+        match *self {
+            RangeBound::Included(ref term) | RangeBound::Excluded(ref term) => Some(term),
+            RangeBound::Unbounded => None,
+        }
+    }
+}
+
+
+/// Query matching the documents containing at least one
+/// term within a range of terms of a given field.
+///
+/// The matching terms are streamed out of the term dictionary
+/// of each segment, and their postings are unioned.
+///
+/// The terms are compared in the lexicographical order of their bytes.
+/// The encoding of the `u32`, `i64`, `f64`, date and IP address 
+/// terms preserves the order of the values, so that a range of terms
+/// is a range of values, and a CIDR block is a range of IP addresses.
+/// Text terms are compared byte-wise, which only matches the 
+/// alphabetical order for ASCII texts.
+///
+/// All of the matching documents get the same score of `1`.
+///
+/// For a u32 field that is also a fast field, the segments whose
+/// min and max values do not intersect the range are skipped,
+/// without reading their term dictionary.
+#[derive(Eq, PartialEq, Debug)]
+pub struct RangeQuery {
+    field: Field,
+    lower: RangeBound,
+    upper: RangeBound,
+}
+
+impl RangeQuery {
+
+    /// Creates a query matching the terms between `lower` and `upper`,
+    /// both included.
+    ///
+    /// # Panics
+    /// If the two bounds do not belong to the same field.
+    pub fn new(lower: Term, upper: Term) -> RangeQuery {
+        RangeQuery::new_with_bounds(lower.field(), RangeBound::Included(lower), RangeBound::Included(upper))
+    }
+
+    /// Creates a query matching the terms of `field` between
+    /// `lower` and `upper`.
+    ///
+    /// # Panics
+    /// If the terms of the bounds do not belong to `field`.
+    pub fn new_with_bounds(field: Field, lower: RangeBound, upper: RangeBound) -> RangeQuery {
+        for term in lower.term().into_iter().chain(upper.term()) {
+            assert_eq!(term.field(), field, "The bounds of a range query must belong to the same field.");
+        }
+        RangeQuery {
+            field: field,
+            lower: lower,
+            upper: upper,
+        }
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.field
+    }
+
+    /// Returns the lower bound of the range.
+    pub fn lower(&self,) -> &RangeBound {
+        &self.lower
+    }
+
+    /// Returns the upper bound of the range.
+    pub fn upper(&self,) -> &RangeBound {
+        &self.upper
+    }
+
+    /// Returns the range as u32 values, both included,
+    /// or `None` if the range is empty.
+    ///
+    /// The bounds must be u32 terms.
+    fn u32_range(&self,) -> Option<(u32, u32)> {
+        let term_val = |term: &Term| BigEndian::read_u32(term.value_bytes());
+        let lower = match self.lower {
+            RangeBound::Included(ref term) => term_val(term),
+            RangeBound::Excluded(ref term) => {
+                match term_val(term).checked_add(1) {
+                    Some(lower) => lower,
+                    None => { return None; }
+                }
+            }
+            RangeBound::Unbounded => 0u32,
+        };
+        let upper = match self.upper {
+            RangeBound::Included(ref term) => term_val(term),
+            RangeBound::Excluded(ref term) => {
+                match term_val(term).checked_sub(1) {
+                    Some(upper) => upper,
+                    None => { return None; }
+                }
+            }
+            RangeBound::Unbounded => u32::max_value(),
+        };
+        if lower <= upper {
+            Some((lower, upper))
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns false if none of the documents of the segment
+    /// can be in the range, given the min and max values of
+    /// the fast field, when the field is a u32 fast field.
+    ///
+    /// The errors reading the bounds of the fast field are propagated.
+    fn may_match(&self, reader: &SegmentReader) -> Result<bool> {
+        let field_entry = reader.schema().get_field_entry(self.field);
+        if !field_entry.is_u32_fast() && !field_entry.is_multivalued_u32_fast() {
+            return Ok(true);
+        }
+        let (min_val, max_val) = try!(reader.u32_fast_field_bounds(self.field));
+        Ok(match self.u32_range() {
+            Some((lower, upper)) => lower <= max_val && min_val <= upper,
+            None => false,
+        })
+    }
+
+    /// Returns the set of the documents of the segment
+    /// containing a term of the range.
+    fn segment_docs(&self, reader: &SegmentReader) -> Result<BitSet> {
+        let mut docs_builder = SegmentDocsBuilder::new(reader, self.field);
+        if !try!(self.may_match(reader)) {
+            return Ok(docs_builder.build());
+        }
+        let field_prefix = [self.field.0];
+        let range = reader.term_dict().range();
+        let range = match self.lower {
+            RangeBound::Included(ref term) => range.ge(term.as_slice()),
+            RangeBound::Excluded(ref term) => range.gt(term.as_slice()),
+            RangeBound::Unbounded => range.ge(&field_prefix[..]),
+        };
+        let range = match self.upper {
+            RangeBound::Included(ref term) => range.le(term.as_slice()),
+            RangeBound::Excluded(ref term) => range.lt(term.as_slice()),
+            RangeBound::Unbounded => range,
+        };
+        let mut terms = range.into_stream();
+        while let Some((term_bytes, term_info)) = terms.next() {
+            if term_bytes[0] != self.field.0 {
+                // the terms of the field are all consumed.
+                break;
+            }
+            docs_builder.add_term_info(&term_info);
+        }
+        Ok(docs_builder.build())
+    }
+}
+
+impl Query for RangeQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = BitSetDocSet::from(try!(self.segment_docs(reader)));
+        Ok(Box::new(ConstantScorer::new(docset, 1f32)))
+    }
+
+    fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        collector: &mut C) -> Result<TimerTree> {
+        let mut timer_tree = TimerTree::default();
+        {
+            let mut search_timer = timer_tree.open("search");
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                if collector.should_terminate() {
+                    break;
+                }
+                let mut segment_search_timer = search_timer.open("segment_search");
+                {
+                    let _ = segment_search_timer.open("set_segment");
+                    try!(collector.set_segment(segment_ord as SegmentLocalId, &segment_reader));
+                }
+                if !try!(self.may_match(segment_reader)) {
+                    let _ = segment_search_timer.open("skip_segment");
+                    continue;
+                }
+                let docs = {
+                    let _ = segment_search_timer.open("get_postings");
+                    try!(self.segment_docs(segment_reader))
+                };
+                {
+                    let _collection_timer = segment_search_timer.open("collection");
+                    let mut docset = BitSetDocSet::from(&docs);
+                    while docset.advance() {
+                        collector.collect(ScoredDoc(1f32, docset.doc()));
+                        if collector.should_terminate() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        try!(collector.finish());
+        Ok(timer_tree)
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let docs = try!(self.segment_docs(segment_reader));
+        Ok(if docs.contains(doc_address.doc()) {
+            let mut explanation = Explanation::with_val(1f32);
+            explanation.description(&format!("The document contains a term within ({:?}, {:?})", self.lower, self.upper));
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, IpOptions, U32Options, STRING, parse_cidr, ipv6_to_ip};
+    use collector::TopCollector;
+    use collector::tests::TestCollector;
+    use collector::CountCollector;
+    use rustc_serialize::json;
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_range_query_u32() {
+        let mut schema_builder = SchemaBuilder::default();
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_indexed());
+        let other_field = schema_builder.add_u32_field("other", U32Options::default().set_indexed());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // 256 and 1 only differ by their byte order.
+            for (i, &price) in [99u32, 100, 101, 256, 499, 500, 501, 1, 4_000_000_000].iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_u32(price_field, price);
+                if i % 2 == 0 {
+                    doc.add_u32(other_field, 300);
+                }
+                index_writer.add_document(doc).unwrap();
+            }
+            let mut doc = Document::default();
+            doc.add_u32(other_field, 300);
+            index_writer.add_document(doc).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search = |lower: RangeBound, upper: RangeBound| {
+            let query = RangeQuery::new_with_bounds(price_field, lower, upper);
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        let included = |val: u32| RangeBound::Included(Term::from_field_u32(price_field, val));
+        let excluded = |val: u32| RangeBound::Excluded(Term::from_field_u32(price_field, val));
+        assert_eq!(search(included(100), included(500)), vec!(1, 2, 3, 4, 5));
+        assert_eq!(search(excluded(100), excluded(500)), vec!(2, 3, 4));
+        assert_eq!(search(included(100), excluded(500)), vec!(1, 2, 3, 4));
+        assert_eq!(search(excluded(100), included(500)), vec!(2, 3, 4, 5));
+        assert_eq!(search(included(101), included(101)), vec!(2));
+        assert!(search(excluded(101), included(101)).is_empty());
+        assert!(search(included(102), included(255)).is_empty());
+        assert!(search(included(500), included(100)).is_empty());
+        assert_eq!(search(RangeBound::Unbounded, included(100)), vec!(0, 1, 7));
+        assert_eq!(search(RangeBound::Unbounded, excluded(100)), vec!(0, 7));
+        assert_eq!(search(excluded(500), RangeBound::Unbounded), vec!(6, 8));
+        // the terms of the other field are not part of an unbounded range.
+        assert_eq!(search(RangeBound::Unbounded, RangeBound::Unbounded), (0..9).collect::<Vec<_>>());
+        {
+            let query = RangeQuery::new_with_bounds(other_field, RangeBound::Unbounded, RangeBound::Unbounded);
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            assert_eq!(test_collector.docs(), vec!(0, 2, 4, 6, 8, 9));
+        }
+        assert_eq!(
+            RangeQuery::new(Term::from_field_u32(price_field, 1), Term::from_field_u32(price_field, 99)),
+            RangeQuery::new_with_bounds(price_field, included(1), included(99)));
+    }
+
+    #[test]
+    fn test_range_query_skips_segments() {
+        let mut schema_builder = SchemaBuilder::default();
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_indexed().set_fast());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // the first segment has a document with a second
+            // price, which is indexed but not stored in the fast field.
+            let segments = vec!(
+                vec!(vec!(1u32, 2_000u32), vec!(5u32), vec!(10u32)),
+                vec!(vec!(100u32), vec!(110u32)),
+                vec!(vec!(1_000u32), vec!(1_010u32)),
+            );
+            for segment_prices in segments {
+                for prices in segment_prices {
+                    let mut doc = Document::default();
+                    for price in prices {
+                        doc.add_u32(price_field, price);
+                    }
+                    index_writer.add_document(doc).unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+        }
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let mut bounds: Vec<(u32, u32)> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.u32_fast_field_bounds(price_field).unwrap())
+            .collect();
+        bounds.sort();
+        assert_eq!(bounds, vec!((1, 2_000), (100, 110), (1_000, 1_010)));
+        let included = |val: u32| RangeBound::Included(Term::from_field_u32(price_field, val));
+        let excluded = |val: u32| RangeBound::Excluded(Term::from_field_u32(price_field, val));
+        // returns the number of matching documents, and
+        // the number of segments skipped by the search.
+        let search = |searcher: &Searcher, lower: RangeBound, upper: RangeBound| {
+            let query = RangeQuery::new_with_bounds(price_field, lower, upper);
+            let mut count_collector = CountCollector::default();
+            let timer_tree = query.search(searcher, &mut count_collector).unwrap();
+            let trace = json::encode(&timer_tree).unwrap();
+            (count_collector.count(), trace.matches("\"skip_segment\"").count())
+        };
+        assert_eq!(search(&searcher, included(2_001), included(5_000)), (0, 3));
+        assert_eq!(search(&searcher, included(11), included(99)), (0, 2));
+        // the bounds of the first segment include its second price.
+        assert_eq!(search(&searcher, included(100), included(105)), (1, 1));
+        assert_eq!(search(&searcher, included(1_500), included(3_000)), (1, 2));
+        assert_eq!(search(&searcher, excluded(10), excluded(1_000)), (2, 1));
+        assert_eq!(search(&searcher, RangeBound::Unbounded, RangeBound::Unbounded), (7, 0));
+        assert_eq!(search(&searcher, excluded(u32::max_value()), RangeBound::Unbounded), (0, 3));
+        assert_eq!(search(&searcher, RangeBound::Unbounded, excluded(0)), (0, 3));
+        // the scorer of a skipped segment matches no document.
+        {
+            let query = RangeQuery::new_with_bounds(price_field, included(100), included(105));
+            let counts: Vec<u32> = searcher
+                .segment_readers()
+                .iter()
+                .map(|segment_reader| {
+                    let mut scorer = query.scorer(&searcher, segment_reader).unwrap();
+                    let mut count = 0;
+                    while scorer.advance() {
+                        count += 1;
+                    }
+                    count
+                })
+                .collect();
+            assert_eq!(counts.iter().fold(0, |total, count| total + count), 1);
+        }
+        // the bounds of the merged segment are the union of the bounds.
+        {
+            let segments = index.searchable_segments();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segments).unwrap();
+        }
+        let merged_searcher = index.searcher();
+        assert_eq!(merged_searcher.segment_readers().len(), 1);
+        assert_eq!(merged_searcher.segment_reader(0).u32_fast_field_bounds(price_field).unwrap(), (1, 2_000));
+        assert_eq!(search(&merged_searcher, included(1_500), included(3_000)), (1, 0));
+        assert_eq!(search(&merged_searcher, included(0), excluded(1)), (0, 1));
+    }
+
+    #[test]
+    fn test_range_query_text() {
+        let mut schema_builder = SchemaBuilder::default();
+        let name_field = schema_builder.add_text_field("name", STRING);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for name in &["apple", "apricot", "banana", "b", "cherry", "Banana"] {
+                let mut doc = Document::default();
+                doc.add_text(name_field, name);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search = |lower: RangeBound, upper: RangeBound| {
+            let query = RangeQuery::new_with_bounds(name_field, lower, upper);
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        let included = |val: &str| RangeBound::Included(Term::from_field_text(name_field, val));
+        let excluded = |val: &str| RangeBound::Excluded(Term::from_field_text(name_field, val));
+        assert_eq!(search(included("apricot"), included("banana")), vec!(1, 2, 3));
+        assert_eq!(search(excluded("apricot"), excluded("banana")), vec!(3));
+        assert_eq!(search(included("b"), excluded("c")), vec!(2, 3));
+        assert_eq!(search(included("ap"), included("apq")), vec!(0));
+        // uppercase letters come before the lowercase ones.
+        assert_eq!(search(RangeBound::Unbounded, excluded("a")), vec!(5));
+        assert_eq!(search(excluded("b"), RangeBound::Unbounded), vec!(2, 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_query_bounds_field() {
+        let mut schema_builder = SchemaBuilder::default();
+        let price_field = schema_builder.add_u32_field("price", U32Options::default().set_indexed());
+        let other_field = schema_builder.add_u32_field("other", U32Options::default().set_indexed());
+        RangeQuery::new(Term::from_field_u32(price_field, 1), Term::from_field_u32(other_field, 2));
+    }
+
+    #[test]
+    fn test_range_query_ip() {
+        let mut schema_builder = SchemaBuilder::default();
+        let ip_field = schema_builder.add_ip_field("src_ip", IpOptions::default().set_indexed().set_stored());
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let ips = ["10.0.0.1", "2001:db8::1", "10.255.0.3", "11.0.0.1", "9.255.255.255", "10.0.0.1", "2001:db9::1"];
+            for (i, ip_text) in ips.iter().enumerate() {
+                let mut doc = Document::default();
+                doc.add_ip(ip_field, IpAddr::from_str(ip_text).unwrap());
+                index_writer.add_document(doc).unwrap();
+                if i == 3 {
+                    // spread the documents over two segments
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search_cidr = |cidr: &str| {
+            let (lower, upper) = parse_cidr(cidr).unwrap();
+            let query = RangeQuery::new(Term::from_field_ip(ip_field, lower), Term::from_field_ip(ip_field, upper));
+            let mut top_collector = TopCollector::with_limit(10);
+            query.search(&searcher, &mut top_collector).unwrap();
+            let mut ips: Vec<String> = top_collector
+                .docs()
+                .iter()
+                .map(|doc_address| {
+                    let doc = searcher.doc(doc_address).unwrap();
+                    format!("{}", ipv6_to_ip(doc.get_first(ip_field).unwrap().ip_value()))
+                })
+                .collect();
+            ips.sort();
+            ips
+        };
+        assert_eq!(search_cidr("10.0.0.0/8"), vec!("10.0.0.1", "10.0.0.1", "10.255.0.3"));
+        assert_eq!(search_cidr("10.0.0.1"), vec!("10.0.0.1", "10.0.0.1"));
+        assert_eq!(search_cidr("0.0.0.0/0"), vec!("10.0.0.1", "10.0.0.1", "10.255.0.3", "11.0.0.1", "9.255.255.255"));
+        assert_eq!(search_cidr("2001:db8::/32"), vec!("2001:db8::1"));
+        assert_eq!(search_cidr("2001:db8::/31"), vec!("2001:db8::1", "2001:db9::1"));
+        assert_eq!(search_cidr("::/0").len(), 7);
+        assert!(search_cidr("192.168.0.0/16").is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use Result;
+use Error;
+use schema::Field;
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use query::DEFAULT_MAX_EXPANSIONS;
+use query::field_automaton::FieldAutomaton;
+use common::BitSet;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use query::segment_docs_builder::SegmentDocsBuilder;
+use postings::BitSetDocSet;
+use DocAddress;
+use query::Explanation;
+use fst::Regex;
+use regex::RegexBuilder;
+use std::fmt;
+
+
+/// Default limit, in bytes, of the size of the compiled
+/// regular expression of a `RegexQuery`.
+pub const DEFAULT_REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+
+/// Query matching the documents containing a term
+/// of a field matching a regular expression, like `api/v[0-9]+/users`.
+///
+/// The regular expression is anchored: it must match the
+/// whole term, as if it were written `^(pattern)$`. Searching
+/// for a substring requires surrounding it with `.*`, e.g. `.*users.*`,
+/// which may visit all of the terms of the field.
+///
+/// The regular expression is compiled to a deterministic automaton,
+/// which is intersected with the term dictionary of each segment.
+/// The postings of the matching terms are then unioned.
+/// The syntax is the one of the `regex` crate, without the lazy
+/// repetitions, the word boundaries and the `^` and `$` anchors.
+///
+/// So that a hostile pattern cannot exhaust the resources of the
+/// searcher, the size of the compiled expression is limited,
+/// and so is the number of terms the expression may expand to
+/// within a segment. The search returns an error when the
+/// latter is exceeded.
+///
+/// All of the matching documents get the same score of `1`.
+pub struct RegexQuery {
+    field: Field,
+    pattern: String,
+    regex: Regex,
+    max_expansions: usize,
+}
+
+impl RegexQuery {
+
+    /// Creates a query matching the terms of `field` matching `pattern`.
+    ///
+    /// # Errors
+    /// If the pattern is invalid, or if its compiled expression
+    /// exceeds `DEFAULT_REGEX_SIZE_LIMIT`.
+    pub fn new(field: Field, pattern: &str) -> Result<RegexQuery> {
+        RegexQuery::with_size_limit(field, pattern, DEFAULT_REGEX_SIZE_LIMIT)
+    }
+
+    /// Creates a query matching the terms of `field` matching `pattern`,
+    /// with a limit of `size_limit` bytes for its compiled expression.
+    ///
+    /// # Errors
+    /// If the pattern is invalid, or if its compiled expression
+    /// exceeds `size_limit`.
+    pub fn with_size_limit(field: Field, pattern: &str, size_limit: usize) -> Result<RegexQuery> {
+        // the size of the expression is checked before
+        // the automaton gets built.
+        try!(RegexBuilder::new(pattern)
+            .size_limit(size_limit)
+            .compile()
+            .map_err(|err| Error::InvalidArgument(format!("Invalid regular expression {:?}: {}", pattern, err))));
+        let regex = try!(Regex::new(pattern)
+            .map_err(|err| Error::InvalidArgument(format!("Invalid regular expression {:?}: {}", pattern, err))));
+        Ok(RegexQuery {
+            field: field,
+            pattern: String::from(pattern),
+            regex: regex,
+            max_expansions: DEFAULT_MAX_EXPANSIONS,
+        })
+    }
+
+    /// Sets the maximum number of terms the expression may expand to
+    /// within a segment.
+    ///
+    /// Defaults to `DEFAULT_MAX_EXPANSIONS`.
+    pub fn set_max_expansions(mut self, max_expansions: usize) -> RegexQuery {
+        self.max_expansions = max_expansions;
+        self
+    }
+
+    /// Returns the maximum number of terms the expression may expand to.
+    pub fn max_expansions(&self,) -> usize {
+        self.max_expansions
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.field
+    }
+
+    /// Returns the regular expression.
+    pub fn pattern(&self,) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the set of the documents of the segment
+    /// containing a term matching the expression.
+    ///
+    /// # Errors
+    /// If the expression matches more than `max_expansions` terms.
+    fn segment_docs(&self, reader: &SegmentReader) -> Result<BitSet> {
+        let mut docs_builder = SegmentDocsBuilder::new(reader, self.field);
+        let mut terms = reader.term_dict().search(FieldAutomaton::new(self.field, &self.regex));
+        while let Some((_, term_info)) = terms.next() {
+            if docs_builder.num_terms() == self.max_expansions {
+                return Err(Error::InvalidArgument(format!("The regular expression {:?} matches more than {} terms.", self.pattern, self.max_expansions)));
+            }
+            docs_builder.add_term_info(&term_info);
+        }
+        Ok(docs_builder.build())
+    }
+}
+
+impl fmt::Debug for RegexQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RegexQuery({:?}, {:?}, max_expansions={})", self.field, self.pattern, self.max_expansions)
+    }
+}
+
+impl PartialEq for RegexQuery {
+    fn eq(&self, other: &RegexQuery) -> bool {
+        self.field == other.field &&
+        self.pattern == other.pattern &&
+        self.max_expansions == other.max_expansions
+    }
+}
+
+impl Eq for RegexQuery {}
+
+impl Query for RegexQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = BitSetDocSet::from(try!(self.segment_docs(reader)));
+        Ok(Box::new(ConstantScorer::new(docset, 1f32)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        let docs = try!(self.segment_docs(segment_reader));
+        Ok(if docs.contains(doc_address.doc()) {
+            let mut explanation = Explanation::with_val(1f32);
+            explanation.description(&format!("The document contains a term matching {:?}", self.pattern));
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use core::Index;
+    use schema::{SchemaBuilder, Document, STRING};
+    use collector::tests::TestCollector;
+    use DocId;
+
+    #[test]
+    fn test_regex_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let path_field = schema_builder.add_text_field("path", STRING);
+        let other_field = schema_builder.add_text_field("other", STRING);
+        let index = Index::create_in_ram(schema_builder.build().unwrap());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for path in &["/api/v1/users", "/api/v2/users", "/api/v12/users/3", "/api/vx/users", "/static/users"] {
+                let mut doc = Document::default();
+                doc.add_text(path_field, path);
+                index_writer.add_document(doc).unwrap();
+            }
+            let mut doc = Document::default();
+            doc.add_text(other_field, "/api/v3/users");
+            index_writer.add_document(doc).unwrap();
+            // 3000 distinct paths.
+            for i in 0..3_000 {
+                let mut doc = Document::default();
+                doc.add_text(path_field, &format!("/assets/{}.png", i));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.searcher();
+        let search = |query: RegexQuery| -> Result<Vec<DocId>> {
+            let mut test_collector = TestCollector::default();
+            try!(query.search(&searcher, &mut test_collector));
+            Ok(test_collector.docs())
+        };
+        let regex = |pattern: &str| RegexQuery::new(path_field, pattern).unwrap();
+        assert_eq!(search(regex("/api/v[0-9]+/users")).unwrap(), vec!(0, 1));
+        // the expression must match the whole term.
+        assert!(search(regex("users")).unwrap().is_empty());
+        assert_eq!(search(regex(".*users.*")).unwrap(), vec!(0, 1, 2, 3, 4));
+        assert!(search(regex("/api/v[0-9]+/groups")).unwrap().is_empty());
+        assert_eq!(search(regex("/assets/[0-9]+\\.png")).unwrap().len(), 3_000);
+        assert_eq!(search(regex("/assets/12[0-9]\\.png")).unwrap().len(), 10);
+        match search(regex("/assets/.*").set_max_expansions(2_999)) {
+            Err(Error::InvalidArgument(_)) => {}
+            _ => {
+                panic!("Expected the max expansions to be exceeded");
+            }
+        }
+        assert_eq!(search(regex("/assets/12[0-9]\\.png").set_max_expansions(10)).unwrap().len(), 10);
+        assert_eq!(regex("/static/.*").explain(&searcher, &DocAddress(0, 4)).unwrap().val(), 1f32);
+        assert_eq!(regex("/static/.*").explain(&searcher, &DocAddress(0, 3)).unwrap().val(), 0f32);
+    }
+
+    #[test]
+    fn test_regex_query_invalid() {
+        let field = Field(0);
+        assert!(RegexQuery::new(field, "a(b").is_err());
+        // lazy repetitions are not supported by the automaton.
+        assert!(RegexQuery::new(field, "a+?").is_err());
+        assert!(RegexQuery::with_size_limit(field, "[a-z]{100}", 10_000).is_ok());
+        assert!(RegexQuery::with_size_limit(field, "[a-z]{100}", 100).is_err());
+        assert!(RegexQuery::new(field, "(\\w{100}){100}").is_err());
+        assert_eq!(RegexQuery::new(field, "a.c").unwrap(), RegexQuery::new(field, "a.c").unwrap());
+    }
+}
@@ -1,4 +1,7 @@
 use DocSet;
+use DocId;
+use Score;
+use postings::SkipResult;
 
 
 /// Scored `DocSet`
@@ -11,3 +14,39 @@ pub trait Scorer: DocSet {
 } 
 
 
+/// `Scorer` giving the same score to all of the documents
+/// of a `DocSet`.
+pub struct ConstantScorer<TDocSet: DocSet> {
+    docset: TDocSet,
+    score: Score,
+}
+
+impl<TDocSet: DocSet> ConstantScorer<TDocSet> {
+    /// Creates a scorer giving `score` to all of the documents of `docset`.
+    pub fn new(docset: TDocSet, score: Score) -> ConstantScorer<TDocSet> {
+        ConstantScorer {
+            docset: docset,
+            score: score,
+        }
+    }
+}
+
+impl<TDocSet: DocSet> DocSet for ConstantScorer<TDocSet> {
+    fn advance(&mut self,) -> bool {
+        self.docset.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.docset.skip_next(target)
+    }
+
+    fn doc(&self,) -> DocId {
+        self.docset.doc()
+    }
+}
+
+impl<TDocSet: DocSet> Scorer for ConstantScorer<TDocSet> {
+    fn score(&self,) -> Score {
+        self.score
+    }
+}
@@ -0,0 +1,57 @@
+use common::BitSet;
+use schema::Field;
+use core::SegmentReader;
+use postings::DocSet;
+use postings::TermInfo;
+use postings::SegmentPostingsOption;
+
+
+/// Builds the set of the documents of a segment containing
+/// any of a list of terms of a field.
+///
+/// It is shared by the queries expanding to many terms, like the
+/// `RangeQuery`, the `PrefixQuery`, the `TermSetQuery` or the
+/// `RegexQuery`, which score all of their documents alike, so that
+/// the postings of their terms are read without their frequencies,
+/// and unioned in a `BitSet` rather than in a heap.
+pub struct SegmentDocsBuilder<'a> {
+    reader: &'a SegmentReader,
+    field: Field,
+    docs: BitSet,
+    num_terms: usize,
+}
+
+impl<'a> SegmentDocsBuilder<'a> {
+
+    /// Creates a builder for the documents of `reader`
+    /// containing some terms of `field`.
+    pub fn new(reader: &'a SegmentReader, field: Field) -> SegmentDocsBuilder<'a> {
+        SegmentDocsBuilder {
+            reader: reader,
+            field: field,
+            docs: BitSet::with_max_doc(reader.max_doc()),
+            num_terms: 0,
+        }
+    }
+
+    /// Adds the documents of the term of `term_info`.
+    pub fn add_term_info(&mut self, term_info: &TermInfo) {
+        let mut postings = self.reader.read_postings_from_term_info(self.field, term_info, SegmentPostingsOption::NoFreq);
+        while postings.advance() {
+            self.docs.insert(postings.doc());
+        }
+        self.num_terms += 1;
+    }
+
+    /// Returns the number of terms added so far, so that
+    /// the queries can cap their expansion.
+    pub fn num_terms(&self,) -> usize {
+        self.num_terms
+    }
+
+    /// Returns the set of the documents containing
+    /// any of the terms added.
+    pub fn build(self,) -> BitSet {
+        self.docs
+    }
+}
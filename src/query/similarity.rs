@@ -1,6 +1,7 @@
 use Score;
 use query::Explanation;
 use query::MultiTermAccumulator;
+use query::{DEFAULT_BM25_K1, DEFAULT_BM25_B};
 
 /// Similarity score
 pub trait Similarity: MultiTermAccumulator {
@@ -16,4 +17,61 @@ pub trait Similarity: MultiTermAccumulator {
     /// `vals` is an array of `(term_ord, term_freq, field_norm)`.
     /// Terms that are not present should not appear in the array. 
     fn explain(&self, vals: &[(usize, u32, u32)]) -> Explanation;
+
+    /// Returns an upper bound of the score of the documents
+    /// containing at most the given terms, if the similarity can compute one.
+    ///
+    /// `vals` is an array of `(term_ord, max_term_freq, min_field_norm)`,
+    /// bounding the term frequencies and the field norms of the terms
+    /// in the documents.
+    fn score_upper_bound(&self, _vals: &[(usize, u32, u32)]) -> Option<Score> {
+        None
+    }
+}
+
+impl<TSimilarity: Similarity + ?Sized> Similarity for Box<TSimilarity> {
+
+    fn score(&self, ) -> Score {
+        (**self).score()
+    }
+
+    fn explain(&self, vals: &[(usize, u32, u32)]) -> Explanation {
+        (**self).explain(vals)
+    }
+
+    fn score_upper_bound(&self, vals: &[(usize, u32, u32)]) -> Option<Score> {
+        (**self).score_upper_bound(vals)
+    }
+}
+
+
+/// Similarity used to score the documents matching a `MultiTermQuery`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimilarityType {
+    /// The `TfIdf` score, the default of tantivy.
+    TfIdf,
+    /// The `Bm25` score, with its `k1` and `b` parameters.
+    Bm25 {
+        /// Saturation of the term frequencies.
+        k1: f32,
+        /// Normalization by the field norms.
+        b: f32,
+    },
+}
+
+impl SimilarityType {
+    /// The `Bm25` score, with the default parameters
+    /// `DEFAULT_BM25_K1` and `DEFAULT_BM25_B`.
+    pub fn bm25() -> SimilarityType {
+        SimilarityType::Bm25 {
+            k1: DEFAULT_BM25_K1,
+            b: DEFAULT_BM25_B,
+        }
+    }
+}
+
+impl Default for SimilarityType {
+    fn default() -> SimilarityType {
+        SimilarityType::TfIdf
+    }
 }
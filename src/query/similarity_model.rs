@@ -0,0 +1,307 @@
+use Score;
+use core::FieldStats;
+use super::MultiTermAccumulator;
+use super::Similarity;
+use super::Explanation;
+
+
+/// Statistics of one of the terms of a query, over the whole index,
+/// from which a `SimilarityModel` computes the weight of the term.
+///
+/// For a phrase, the statistics are the smallest ones of its terms,
+/// which bound the statistics of the phrase itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TermStats {
+    /// Number of documents containing the term.
+    pub doc_freq: u32,
+    /// Overall number of occurrences of the term.
+    ///
+    /// `None` if one of the segments containing the term
+    /// was written before it was recorded.
+    pub total_term_freq: Option<u64>,
+    /// Number of documents of the index.
+    pub num_docs: u32,
+    /// Statistics of the field of the term.
+    pub field_stats: FieldStats,
+    /// True if the field of the term records its field norms.
+    /// Otherwise, the field norm of all of the documents is `1`.
+    pub has_fieldnorms: bool,
+    /// Boost of the field of the term.
+    pub boost: f32,
+}
+
+
+/// Model of the score of the documents matching some terms,
+/// which makes it possible to plug a custom scoring function
+/// in the queries of an index, see `Index::set_similarity_model`.
+///
+/// The built-in `TfIdf` and `Bm25` are models as well.
+///
+/// The score of a document is the sum of the scores of the terms
+/// it contains, times a coordination factor.
+/// * `term_weight` is computed once per term, query and segment,
+///   from the statistics of the index.
+/// * `term_score` is computed for each of the terms of each of the
+///   matching documents, from the weight of the term.
+///
+/// The model is used as a type parameter of `ModelSimilarity`,
+/// so that `term_score` can be inlined in the scorer. The models
+/// set on an index are however used through a `BoxedSimilarityModel`.
+pub trait SimilarityModel: Clone {
+
+    /// Weight of a term, computed by `term_weight`.
+    type Weight: Clone;
+
+    /// Computes the weight of a term, given its statistics.
+    fn term_weight(&self, term_stats: &TermStats) -> Self::Weight;
+
+    /// Computes the score of a term within a document, given its weight.
+    fn term_score(&self, weight: &Self::Weight, term_freq: u32, fieldnorm: u32) -> Score;
+
+    /// Factor of the sum of the scores of the terms, given the number
+    /// of terms the document contains, out of the terms of the query.
+    ///
+    /// It is `1` by default.
+    fn coord(&self, _num_matching_terms: usize, _num_terms: usize) -> Score {
+        1f32
+    }
+
+    /// True if the term scores increase with the term frequency and
+    /// decrease with the field norm, so that they can be bounded
+    /// to skip the blocks of postings that cannot score enough,
+    /// see `Similarity::score_upper_bound`.
+    ///
+    /// It is `false` by default.
+    fn is_monotonic(&self,) -> bool {
+        false
+    }
+
+    /// Explains the score of a term within a document.
+    fn explain_term(&self, weight: &Self::Weight, term_freq: u32, fieldnorm: u32) -> Explanation {
+        let mut explanation = Explanation::with_val(self.term_score(weight, term_freq, fieldnorm));
+        explanation.set_formula("term_score(<term_freq>, <field_norm>)");
+        explanation.add_child("term_freq", term_freq as f32);
+        explanation.add_child("field_norm", fieldnorm as f32);
+        explanation
+    }
+}
+
+
+/// Object-safe counterpart of a `SimilarityModel`, for
+/// a model to be set on an `Index` and its `Searcher`s.
+///
+/// It is implemented by all of the models. The similarity it builds
+/// is boxed, so that its term scores are not inlined in the scorers.
+pub trait BoxedSimilarityModel: Send + Sync {
+
+    /// Builds the similarity of the terms of a query, given their
+    /// statistics, along with their names, for `explain`.
+    fn boxed_similarity(&self, term_stats: &[TermStats], term_names: Vec<String>) -> Box<Similarity>;
+}
+
+impl<TModel> BoxedSimilarityModel for TModel
+    where TModel: SimilarityModel + Send + Sync + 'static, TModel::Weight: 'static {
+
+    fn boxed_similarity(&self, term_stats: &[TermStats], term_names: Vec<String>) -> Box<Similarity> {
+        let weights = term_stats
+            .iter()
+            .map(|term_stats| self.term_weight(term_stats))
+            .collect();
+        let mut model_similarity = ModelSimilarity::new(self.clone(), weights);
+        model_similarity.set_term_names(term_names);
+        Box::new(model_similarity)
+    }
+}
+
+
+/// `Similarity` scoring the documents with a `SimilarityModel`.
+#[derive(Clone)]
+pub struct ModelSimilarity<TModel: SimilarityModel> {
+    model: TModel,
+    weights: Vec<TModel::Weight>,
+    num_matching_terms: usize,
+    score: Score,
+    term_names: Option<Vec<String>>, //< only here for explain
+}
+
+impl<TModel: SimilarityModel> ModelSimilarity<TModel> {
+    /// Constructor
+    /// * weights - the weight of each given term, as computed
+    ///   by `SimilarityModel::term_weight`.
+    pub fn new(model: TModel, weights: Vec<TModel::Weight>) -> ModelSimilarity<TModel> {
+        ModelSimilarity {
+            model: model,
+            weights: weights,
+            num_matching_terms: 0,
+            score: 0f32,
+            term_names: None,
+        }
+    }
+
+    /// Set the term names for the explain function
+    pub fn set_term_names(&mut self, term_names: Vec<String>) {
+        self.term_names = Some(term_names);
+    }
+
+    /// Return the name for the ordinal `ord`
+    fn term_name(&self, ord: usize) -> String {
+        match self.term_names {
+            Some(ref term_names_vec) => term_names_vec[ord].clone(),
+            None => format!("Field({})", ord)
+        }
+    }
+}
+
+impl<TModel: SimilarityModel> MultiTermAccumulator for ModelSimilarity<TModel> {
+
+    #[inline]
+    fn update(&mut self, term_ord: usize, term_freq: u32, fieldnorm: u32) {
+        assert!(term_freq != 0u32);
+        self.score += self.model.term_score(&self.weights[term_ord], term_freq, fieldnorm);
+        self.num_matching_terms += 1;
+    }
+
+    #[inline]
+    fn clear(&mut self,) {
+        self.score = 0f32;
+        self.num_matching_terms = 0;
+    }
+}
+
+impl<TModel: SimilarityModel> Similarity for ModelSimilarity<TModel> {
+
+    #[inline]
+    fn score(&self, ) -> Score {
+        self.score * self.model.coord(self.num_matching_terms, self.weights.len())
+    }
+
+    fn explain(&self, vals: &[(usize, u32, u32)]) -> Explanation {
+        let mut explanation = Explanation::with_val(self.score());
+        let formula_components: Vec<String> = vals.iter()
+            .map(|&(ord, _, _)| ord)
+            .map(|ord| format!("<score for ({}>", self.term_name(ord)))
+            .collect();
+        let formula = format!("<coord> * ({})", formula_components.join(" + "));
+        explanation.set_formula(&formula);
+        explanation.add_child("coord", self.model.coord(vals.len(), self.weights.len()));
+        for &(ord, term_freq, field_norm) in vals {
+            let term_explanation = self.model.explain_term(&self.weights[ord], term_freq, field_norm);
+            explanation.add_child_explanation(&self.term_name(ord), term_explanation);
+        }
+        explanation
+    }
+
+    /// The bound is the sum of the scores of the terms at their bounds,
+    /// times the largest possible coord, if the model `is_monotonic`.
+    /// Negative term scores are bounded by `0`.
+    fn score_upper_bound(&self, vals: &[(usize, u32, u32)]) -> Option<Score> {
+        if !self.model.is_monotonic() {
+            return None;
+        }
+        let score = vals
+            .iter()
+            .map(|&(ord, max_term_freq, min_field_norm)| {
+                self.model.term_score(&self.weights[ord], max_term_freq, min_field_norm).max(0f32)
+            })
+            .fold(0f32, |sum, term_score| sum + term_score);
+        let max_coord = (0..vals.len() + 1)
+            .map(|num_matching_terms| self.model.coord(num_matching_terms, self.weights.len()))
+            .fold(0f32, f32::max);
+        Some(score * max_coord)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use query::{MultiTermAccumulator, Similarity};
+    use query::{TfIdf, Bm25};
+    use core::FieldStats;
+
+    fn abs_diff(left: f32, right: f32) -> f32 {
+        (right - left).abs()
+    }
+
+    fn term_stats(doc_freq: u32, average_fieldnorm: u32) -> TermStats {
+        TermStats {
+            doc_freq: doc_freq,
+            total_term_freq: None,
+            num_docs: 100,
+            field_stats: FieldStats {
+                num_docs_with_field: 100,
+                total_num_tokens: 100 * average_fieldnorm as u64,
+                num_unique_terms: 1_000,
+            },
+            has_fieldnorms: true,
+            boost: 1f32,
+        }
+    }
+
+    fn check_scores<TLeft: Similarity, TRight: Similarity>(left: &mut TLeft, right: &mut TRight, vals: &[(usize, u32, u32)]) {
+        left.clear();
+        right.clear();
+        for &(term_ord, term_freq, fieldnorm) in vals {
+            left.update(term_ord, term_freq, fieldnorm);
+            right.update(term_ord, term_freq, fieldnorm);
+        }
+        assert!(abs_diff(left.score(), right.score()) < 0.001f32);
+        assert!(abs_diff(left.score_upper_bound(vals).unwrap(), right.score_upper_bound(vals).unwrap()) < 0.001f32);
+    }
+
+    #[test]
+    pub fn test_model_similarity() {
+        let stats = vec!(term_stats(1, 10), term_stats(50, 10));
+        let vals: Vec<Vec<(usize, u32, u32)>> = vec!(
+            vec!((0, 1, 10)),
+            vec!((1, 3, 5)),
+            vec!((0, 2, 20), (1, 1, 4)),
+        );
+        {
+            let mut tfidf = TfIdf::new(vec!(0f32, 0.5f32, 1f32), stats.iter().map(|stats| TfIdf::idf(stats.doc_freq, stats.num_docs)).collect());
+            let model = TfIdf::default();
+            let weights = stats.iter().map(|stats| model.term_weight(stats)).collect();
+            let mut model_similarity = ModelSimilarity::new(model, weights);
+            for term_vals in &vals {
+                check_scores(&mut tfidf, &mut model_similarity, term_vals);
+            }
+        }
+        {
+            let idfs = stats.iter().map(|stats| Bm25::idf(stats.doc_freq, stats.num_docs)).collect();
+            let mut bm25 = Bm25::new(1.2f32, 0.75f32, idfs, vec!(10f32, 10f32));
+            let model = Bm25::default();
+            let weights = stats.iter().map(|stats| model.term_weight(stats)).collect();
+            let mut model_similarity = ModelSimilarity::new(model, weights);
+            for term_vals in &vals {
+                check_scores(&mut bm25, &mut model_similarity, term_vals);
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct ConstantModel;
+
+    impl SimilarityModel for ConstantModel {
+        type Weight = ();
+
+        fn term_weight(&self, _term_stats: &TermStats) {}
+
+        fn term_score(&self, _weight: &(), _term_freq: u32, _fieldnorm: u32) -> Score {
+            1f32
+        }
+    }
+
+    #[test]
+    pub fn test_model_similarity_default_methods() {
+        let mut model_similarity = ModelSimilarity::new(ConstantModel, vec!((), ()));
+        model_similarity.update(0, 3, 10);
+        model_similarity.update(1, 1, 10);
+        assert_eq!(model_similarity.score(), 2f32);
+        // the blocks of postings are not skipped,
+        // as the model does not bound its scores.
+        assert_eq!(model_similarity.score_upper_bound(&[(0, 3, 10)]), None);
+        let explanation = model_similarity.explain(&[(0, 3, 10), (1, 1, 10)]);
+        assert_eq!(explanation.val(), 2f32);
+    }
+}
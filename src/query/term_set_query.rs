@@ -0,0 +1,200 @@
+use Result;
+use schema::{Term, Field};
+use query::Query;
+use query::{Scorer, ConstantScorer};
+use common::BitSet;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use query::segment_docs_builder::SegmentDocsBuilder;
+use postings::BitSetDocSet;
+use DocAddress;
+use query::Explanation;
+use datastruct::SetAutomaton;
+use fst::Set;
+use std::collections::BTreeSet;
+use std::fmt;
+
+
+/// Query matching the documents containing any
+/// of a set of terms of a field, like a set of ids.
+///
+/// It is equivalent to a `BooleanQuery` with one
+/// `Should` clause per term, but scales to thousands of terms:
+/// the terms are compiled into an automaton, which is intersected
+/// with the term dictionary of each segment in a single pass.
+/// The postings of the terms found are then unioned.
+///
+/// All of the matching documents get the same score of `1`.
+pub struct TermSetQuery {
+    field: Field,
+    terms: BTreeSet<Term>,
+    set: Set,
+}
+
+impl TermSetQuery {
+
+    /// Creates a query matching the documents containing
+    /// any of `terms`.
+    ///
+    /// # Panics
+    /// If one of the terms does not belong to `field`.
+    pub fn new(field: Field, terms: BTreeSet<Term>) -> TermSetQuery {
+        assert!(terms.iter().all(|term| term.field() == field), "The terms must belong to the field of the query.");
+        let set = Set::from_iter(terms.iter().map(|term| term.as_slice()))
+            .expect("The terms of a BTreeSet are sorted");
+        TermSetQuery {
+            field: field,
+            terms: terms,
+            set: set,
+        }
+    }
+
+    /// Returns the field targeted by the query.
+    pub fn field(&self,) -> Field {
+        self.field
+    }
+
+    /// Returns the terms of the query.
+    pub fn terms(&self,) -> &BTreeSet<Term> {
+        &self.terms
+    }
+
+    /// Returns the set of the documents of the segment
+    /// containing any of the terms.
+    fn segment_docs(&self, reader: &SegmentReader) -> BitSet {
+        let mut docs_builder = SegmentDocsBuilder::new(reader, self.field);
+        let mut terms = reader.term_dict().search(SetAutomaton::new(&self.set));
+        while let Some((_, term_info)) = terms.next() {
+            docs_builder.add_term_info(&term_info);
+        }
+        docs_builder.build()
+    }
+}
+
+impl fmt::Debug for TermSetQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TermSetQuery({:?}, {:?})", self.field, self.terms)
+    }
+}
+
+impl PartialEq for TermSetQuery {
+    fn eq(&self, other: &TermSetQuery) -> bool {
+        self.field == other.field && self.terms == other.terms
+    }
+}
+
+impl Eq for TermSetQuery {}
+
+impl Query for TermSetQuery {
+
+    fn scorer<'a>(
+        &self,
+        _searcher: &Searcher,
+        reader: &'a SegmentReader) -> Result<Box<Scorer + 'a>> {
+        let docset = BitSetDocSet::from(self.segment_docs(reader));
+        Ok(Box::new(ConstantScorer::new(docset, 1f32)))
+    }
+
+    fn explain(
+        &self,
+        searcher: &Searcher,
+        doc_address: &DocAddress) -> Result<Explanation> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord() as usize);
+        Ok(if self.segment_docs(segment_reader).contains(doc_address.doc()) {
+            let mut explanation = Explanation::with_val(1f32);
+            explanation.description(&format!("The document contains one of the {} terms of the set", self.terms.len()));
+            explanation
+        }
+        else {
+            let mut explanation = Explanation::with_val(0f32);
+            explanation.description(&format!("Failed to run explain: the document {:?} does not match", doc_address));
+            explanation
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use schema::STRING;
+    use query::tests::create_index;
+    use query::{BooleanQuery, MultiTermQuery, Occur};
+    use collector::tests::TestCollector;
+    use collector::CountCollector;
+    use test::Bencher;
+
+    fn user_texts(num_docs: usize) -> Vec<String> {
+        (0..num_docs)
+            .map(|i| format!("user{}", i % 20_000))
+            .collect()
+    }
+
+    fn user_terms(field: Field, users: &[usize]) -> BTreeSet<Term> {
+        users
+            .iter()
+            .map(|user| Term::from_field_text(field, &format!("user{}", user)))
+            .collect()
+    }
+
+    #[test]
+    fn test_term_set_query() {
+        let (index, user_field) = create_index(STRING, &[user_texts(40_000)]);
+        let searcher = index.searcher();
+        let search = |query: &TermSetQuery| {
+            let mut test_collector = TestCollector::default();
+            query.search(&searcher, &mut test_collector).unwrap();
+            test_collector.docs()
+        };
+        assert_eq!(search(&TermSetQuery::new(user_field, user_terms(user_field, &[3, 1, 42]))), vec!(1, 3, 42, 20_001, 20_003, 20_042));
+        // `user1` is a prefix of `user12`, but the terms are matched exactly.
+        assert_eq!(search(&TermSetQuery::new(user_field, user_terms(user_field, &[1]))), vec!(1, 20_001));
+        let users: Vec<usize> = (0..10_000).map(|i| i * 2).collect();
+        assert_eq!(search(&TermSetQuery::new(user_field, user_terms(user_field, &users))).len(), 20_000);
+        // none of the terms exist.
+        let missing_users: Vec<usize> = (20_000..30_000).collect();
+        assert!(search(&TermSetQuery::new(user_field, user_terms(user_field, &missing_users))).is_empty());
+        assert!(search(&TermSetQuery::new(user_field, BTreeSet::new())).is_empty());
+        let query = TermSetQuery::new(user_field, user_terms(user_field, &[5]));
+        assert_eq!(query.explain(&searcher, &DocAddress(0, 20_005)).unwrap().val(), 1f32);
+        assert_eq!(query.explain(&searcher, &DocAddress(0, 6)).unwrap().val(), 0f32);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_term_set_query_other_field() {
+        let terms = user_terms(Field(1), &[1, 2]);
+        TermSetQuery::new(Field(0), terms);
+    }
+
+    #[bench]
+    fn bench_term_set_query(b: &mut Bencher) {
+        let (index, user_field) = create_index(STRING, &[user_texts(100_000)]);
+        let searcher = index.searcher();
+        let users: Vec<usize> = (0..10_000).map(|i| i * 2).collect();
+        let query = TermSetQuery::new(user_field, user_terms(user_field, &users));
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+
+    #[bench]
+    fn bench_term_set_boolean_query(b: &mut Bencher) {
+        let (index, user_field) = create_index(STRING, &[user_texts(100_000)]);
+        let searcher = index.searcher();
+        let users: Vec<usize> = (0..10_000).map(|i| i * 2).collect();
+        let clauses: Vec<(Occur, MultiTermQuery)> = user_terms(user_field, &users)
+            .into_iter()
+            .map(|term| (Occur::Should, MultiTermQuery::from(vec!(term))))
+            .collect();
+        let query = BooleanQuery::from(clauses);
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            query.search(&searcher, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
+}
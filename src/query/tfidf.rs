@@ -2,15 +2,23 @@ use Score;
 use super::MultiTermAccumulator;
 use super::Explanation;
 use super::Similarity;
+use super::{SimilarityModel, TermStats};
 
 
 /// `TfIdf` is the default pertinence score in tantivy.
 ///
 /// See [Tf-Idf in the global documentation](https://fulmicoton.gitbooks.io/tantivy-doc/content/tfidf.html) 
+///
+/// It is also the `SimilarityModel` of its score, in which case
+/// its idfs, coords and boosts are ignored: the weight of a term
+/// is computed from its `TermStats`, and the coord is the ratio of
+/// the terms of the query contained by the document.
+/// `TfIdf::default()` is meant to be used as a model.
 #[derive(Clone)]
 pub struct TfIdf {
     coords: Vec<f32>,
     idf: Vec<f32>,
+    boosts: Option<Vec<f32>>,
     score: f32,
     num_fields: usize,
     term_names: Option<Vec<String>>, //< only here for explain
@@ -21,7 +29,7 @@ impl MultiTermAccumulator for TfIdf {
     #[inline]
     fn update(&mut self, term_ord: usize, term_freq: u32, fieldnorm: u32) {
         assert!(term_freq != 0u32);
-        self.score += self.term_score(term_ord, term_freq, fieldnorm);
+        self.score += self.term_ord_score(term_ord, term_freq, fieldnorm);
         self.num_fields += 1;
     }
 
@@ -43,12 +51,24 @@ impl TfIdf {
         TfIdf {
             coords: coords,
             idf: idf,
+            boosts: None,
             score: 0f32,
             num_fields: 0,
             term_names: None,
         }
     }
     
+    /// Returns the idf of a term, given the number of documents
+    /// containing it, out of the `num_docs` documents of the index.
+    pub fn idf(doc_freq: u32, num_docs: u32) -> f32 {
+        if doc_freq == 0 {
+            1f32
+        }
+        else {
+            1f32 + (num_docs as f32 / doc_freq as f32).ln()
+        }
+    }
+
     /// Compute the coord term
     fn coord(&self,) -> f32 {
         self.coords[self.num_fields]
@@ -59,6 +79,21 @@ impl TfIdf {
         self.term_names = Some(term_names);
     }
     
+    /// Set the boost of each given term, typically
+    /// the boost of the field of the term.
+    /// `boosts` must have a length of `num_terms`.
+    pub fn set_boosts(&mut self, boosts: Vec<f32>) {
+        self.boosts = Some(boosts);
+    }
+    
+    /// Return the boost for the ordinal `ord`
+    fn boost(&self, ord: usize) -> f32 {
+        match self.boosts {
+            Some(ref boosts_vec) => boosts_vec[ord],
+            None => 1f32,
+        }
+    }
+    
     /// Return the name for the ordinal `ord` 
     fn term_name(&self, ord: usize) -> String {
         match self.term_names {
@@ -68,8 +103,15 @@ impl TfIdf {
     }
     
     #[inline]
-    fn term_score(&self, term_ord: usize, term_freq: u32, field_norm: u32) -> f32 {
-        (term_freq as f32 / field_norm as f32).sqrt() * self.idf[term_ord]
+    fn term_ord_score(&self, term_ord: usize, term_freq: u32, field_norm: u32) -> f32 {
+        let weight = self.idf[term_ord] * self.boost(term_ord);
+        self.term_score(&weight, term_freq, field_norm)
+    }
+}
+
+impl Default for TfIdf {
+    fn default() -> TfIdf {
+        TfIdf::new(Vec::new(), Vec::new())
     }
 }
 
@@ -89,16 +131,69 @@ impl Similarity for TfIdf {
             .collect();
         let formula = format!("<coord> * ({})", formula_components.join(" + "));
         explanation.set_formula(&formula);
+        explanation.add_child("coord", self.coords[vals.len()]);
         for &(ord, term_freq, field_norm) in vals {
-            let term_score = self.term_score(ord, term_freq, field_norm);
+            let term_score = self.term_ord_score(ord, term_freq, field_norm);
             let term_explanation = explanation.add_child(&self.term_name(ord), term_score);
-            term_explanation.set_formula(" sqrt(<term_freq> / <field_norm>) * <idf>");    
+            term_explanation.set_formula(" sqrt(<term_freq> / <field_norm>) * <idf> * <boost>");    
+            term_explanation.add_child("term_freq", term_freq as f32);
+            term_explanation.add_child("field_norm", field_norm as f32);
+            term_explanation.add_child("idf", self.idf[ord]);
+            term_explanation.add_child("boost", self.boost(ord));
         }
         explanation
     }
+
+    /// The term scores increase with the term frequency and decrease
+    /// with the field norm, so that the bound is the sum of the scores
+    /// of the terms at their bounds, times the largest possible coord.
+    /// Negative term scores are bounded by `0`.
+    fn score_upper_bound(&self, vals: &[(usize, u32, u32)]) -> Option<Score> {
+        let score = vals
+            .iter()
+            .map(|&(ord, max_term_freq, min_field_norm)| self.term_ord_score(ord, max_term_freq, min_field_norm).max(0f32))
+            .fold(0f32, |sum, term_score| sum + term_score);
+        let max_coord = self.coords[..vals.len() + 1]
+            .iter()
+            .cloned()
+            .fold(0f32, f32::max);
+        Some(score * max_coord)
+    }
 }
 
 
+/// The weight of a term is its idf times its boost.
+impl SimilarityModel for TfIdf {
+
+    type Weight = f32;
+
+    fn term_weight(&self, term_stats: &TermStats) -> f32 {
+        TfIdf::idf(term_stats.doc_freq, term_stats.num_docs) * term_stats.boost
+    }
+
+    #[inline]
+    fn term_score(&self, weight: &f32, term_freq: u32, fieldnorm: u32) -> Score {
+        (term_freq as f32 / fieldnorm as f32).sqrt() * weight
+    }
+
+    #[inline]
+    fn coord(&self, num_matching_terms: usize, num_terms: usize) -> Score {
+        num_matching_terms as f32 / num_terms as f32
+    }
+
+    fn is_monotonic(&self,) -> bool {
+        true
+    }
+
+    fn explain_term(&self, weight: &f32, term_freq: u32, fieldnorm: u32) -> Explanation {
+        let mut explanation = Explanation::with_val(self.term_score(weight, term_freq, fieldnorm));
+        explanation.set_formula("sqrt(<term_freq> / <field_norm>) * <weight>");
+        explanation.add_child("term_freq", term_freq as f32);
+        explanation.add_child("field_norm", fieldnorm as f32);
+        explanation.add_child("weight", *weight);
+        explanation
+    }
+}
 
 
 #[cfg(test)]
@@ -136,8 +231,42 @@ mod tests {
             assert_eq!(tfidf.score(), 10f32);    
             tfidf.clear();
         }
+        tfidf.set_boosts(vec!(3f32, 1f32));
+        {
+            tfidf.update(0, 1, 1);
+            assert_eq!(tfidf.score(), 3f32);    
+            tfidf.clear();
+        }
+    }
 
+    #[test]
+    pub fn test_tfidf_explain() {
+        let mut tfidf = TfIdf::new(vec!(0f32, 0.5f32, 1f32), vec!(1f32, 4f32));
+        tfidf.set_boosts(vec!(3f32, 1f32));
+        tfidf.update(1, 4, 1);
+        let explanation = tfidf.explain(&[(1, 4, 1)]);
+        assert_eq!(explanation.val(), 4f32);
+        let children = explanation.children();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].0, "coord");
+        assert_eq!(children[0].1.val(), 0.5f32);
+        assert_eq!(children[1].1.val(), 8f32);
+        let term_children: Vec<(&str, f32)> = children[1].1.children()
+            .iter()
+            .map(|&(ref name, ref child)| (name.as_str(), child.val()))
+            .collect();
+        assert_eq!(term_children, vec!(("term_freq", 4f32), ("field_norm", 1f32), ("idf", 4f32), ("boost", 1f32)));
+    }
 
+    #[test]
+    pub fn test_tfidf_score_upper_bound() {
+        let mut tfidf = TfIdf::new(vec!(0f32, 0.5f32, 1f32), vec!(1f32, 4f32));
+        assert_eq!(tfidf.score_upper_bound(&[(1, 4, 1)]), Some(4f32));
+        assert_eq!(tfidf.score_upper_bound(&[(0, 1, 1), (1, 1, 1)]), Some(5f32));
+        // the bound holds for any document within the bounds.
+        tfidf.update(0, 1, 4);
+        tfidf.update(1, 3, 2);
+        assert!(tfidf.score() <= tfidf.score_upper_bound(&[(0, 2, 2), (1, 3, 2)]).unwrap());
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,64 @@
+/// Define how a bool field should be handled by tantivy.
+#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+pub struct BoolOptions {
+    indexed: bool,
+    fast: bool,
+    stored: bool,
+}
+
+impl BoolOptions {
+
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self,) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self,) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self,) -> bool {
+        self.fast
+    }
+
+    /// Set the bool options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self,) -> BoolOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Set the bool options as indexed.
+    ///
+    /// An indexed bool field only generates two posting lists,
+    /// one for `true` and one for `false`.
+    pub fn set_indexed(mut self,) -> BoolOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the bool options as a fast field.
+    ///
+    /// Fast fields are designed for random access.
+    /// Bool fast fields are stored as a u32 fast field,
+    /// using a single bit per document.
+    /// Documents without any value are associated to `false`.
+    pub fn set_fast(mut self,) -> BoolOptions {
+        self.fast = true;
+        self
+    }
+}
+
+impl Default for BoolOptions {
+    fn default() -> BoolOptions {
+        BoolOptions {
+            fast: false,
+            indexed: false,
+            stored: false,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/// Define how a bytes field should be handled by tantivy.
+///
+/// Bytes fields are opaque binary payloads.
+/// They cannot be indexed, but they can be stored, and/or
+/// be a fast field.
+#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+pub struct BytesOptions {
+    fast: bool,
+    stored: bool,
+}
+
+impl BytesOptions {
+   
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self,) -> bool {
+        self.stored
+    }
+    
+    /// Returns true iff the value is a fast field. 
+    pub fn is_fast(&self,) -> bool {
+        self.fast
+    }
+    
+    /// Set the bytes options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self,) -> BytesOptions {
+        self.stored = true;
+        self
+    }
+    
+    /// Set the bytes options as a fast field.
+    ///
+    /// Bytes fast fields are written in a dedicated segment
+    /// component, and give a random access to the bytes 
+    /// associated to each document.
+    /// If more than one value is associated to a fast field, only the first one is
+    /// kept.
+    pub fn set_fast(mut self,) -> BytesOptions {
+        self.fast = true;
+        self
+    }
+}
+
+impl Default for BytesOptions {
+    fn default() -> BytesOptions {
+        BytesOptions {
+            fast: false,
+            stored: false,
+        }
+    }    
+}
@@ -0,0 +1,246 @@
+use rustc_serialize::Decodable;
+use rustc_serialize::Decoder;
+use rustc_serialize::Encodable;
+use rustc_serialize::Encoder;
+use time;
+use time::Timespec;
+
+
+/// Define how a date field should be handled by tantivy.
+///
+/// Dates are represented as a number of milliseconds
+/// since Epoch (UTC), stored on an `i64`.
+#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+pub struct DateOptions {
+    indexed: bool,
+    fast: bool,
+    stored: bool,
+    precision: DatePrecision,
+}
+
+impl DateOptions {
+
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self,) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self,) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self,) -> bool {
+        self.fast
+    }
+
+    /// Returns the precision used when indexing the date.
+    pub fn get_precision(&self,) -> DatePrecision {
+        self.precision
+    }
+
+    /// Set the date options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self,) -> DateOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Set the date options as indexed.
+    ///
+    /// Setting a date as indexed will generate
+    /// a posting list for each value taken by the date,
+    /// once truncated to the precision of the field.
+    pub fn set_indexed(mut self,) -> DateOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the date options as a fast field.
+    ///
+    /// Fast fields are designed for random access.
+    /// Access time are similar to a random lookup in an array.
+    /// If more than one value is associated to a fast field, only the last one is
+    /// kept.
+    pub fn set_fast(mut self,) -> DateOptions {
+        self.fast = true;
+        self
+    }
+
+    /// Set the precision of the indexed terms.
+    ///
+    /// Truncating dates to the second or to the minute
+    /// reduces the number of distinct terms, and
+    /// therefore the size of the term dictionary.
+    /// The stored and fast field values are not truncated.
+    pub fn set_precision(mut self, precision: DatePrecision) -> DateOptions {
+        self.precision = precision;
+        self
+    }
+}
+
+impl Default for DateOptions {
+    fn default() -> DateOptions {
+        DateOptions {
+            fast: false,
+            indexed: false,
+            stored: false,
+            precision: DatePrecision::Milliseconds,
+        }
+    }
+}
+
+
+/// Precision with which a date is indexed.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum DatePrecision {
+    /// Dates are indexed to the millisecond.
+    Milliseconds,
+    /// Dates are truncated to the second.
+    Seconds,
+    /// Dates are truncated to the minute.
+    Minutes,
+}
+
+impl DatePrecision {
+
+    fn num_millis(&self,) -> i64 {
+        match *self {
+            DatePrecision::Milliseconds => 1,
+            DatePrecision::Seconds => 1_000,
+            DatePrecision::Minutes => 60_000,
+        }
+    }
+
+    /// Truncates a date (expressed in milliseconds since Epoch)
+    /// to the precision.
+    ///
+    /// Truncation always rounds toward the past, including for
+    /// dates before Epoch. The dates whose truncation would be
+    /// lower than `i64::MIN` are truncated to `i64::MIN`.
+    pub fn truncate(&self, millis: i64) -> i64 {
+        let unit = self.num_millis();
+        let remainder = millis % unit;
+        if remainder < 0 {
+            (millis - remainder).saturating_sub(unit)
+        }
+        else {
+            millis - remainder
+        }
+    }
+}
+
+impl Encodable for DatePrecision {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        let name = match *self {
+            DatePrecision::Milliseconds => "milliseconds",
+            DatePrecision::Seconds => "seconds",
+            DatePrecision::Minutes => "minutes",
+        };
+        s.emit_str(name)
+    }
+}
+
+impl Decodable for DatePrecision {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Self, D::Error> {
+        let precision_name: String = try!(d.read_str());
+        Ok(match precision_name.as_ref() {
+            "milliseconds" => DatePrecision::Milliseconds,
+            "seconds" => DatePrecision::Seconds,
+            "minutes" => DatePrecision::Minutes,
+            _ => {
+                return Err(d.error(&format!("Date precision {:?} unknown", precision_name)));
+            }
+        })
+    }
+}
+
+
+/// Converts a `Timespec` into a number of milliseconds since Epoch.
+pub fn timespec_to_millis(timespec: &Timespec) -> i64 {
+    timespec.sec * 1_000 + (timespec.nsec as i64) / 1_000_000
+}
+
+/// Parses an ISO-8601 date into a number of milliseconds since Epoch.
+///
+/// The supported formats are
+///
+/// * `2016-07-23`
+/// * `2016-07-23T13:04:11`
+/// * `2016-07-23T13:04:11.123`
+///
+/// with an optional trailing `Z`. Dates are always assumed to be UTC.
+pub fn parse_date(text: &str) -> Option<i64> {
+    let text = text.trim_right_matches('Z');
+    let (datetime_text, millis) = match text.find('.') {
+        Some(pos) => {
+            let fraction_text = &text[pos + 1..];
+            if fraction_text.is_empty() || !fraction_text.chars().all(|c| c.is_digit(10)) {
+                return None;
+            }
+            let mut millis = 0i64;
+            for (i, c) in fraction_text.chars().chain("00".chars()).take(3).enumerate() {
+                let digit = c.to_digit(10).unwrap() as i64;
+                millis += digit * [100, 10, 1][i];
+            }
+            (&text[..pos], millis)
+        }
+        None => {
+            (text, 0i64)
+        }
+    };
+    let tm_res = time::strptime(datetime_text, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| time::strptime(datetime_text, "%Y-%m-%d"));
+    match tm_res {
+        Ok(tm) => {
+            Some(timespec_to_millis(&tm.to_timespec()) + millis)
+        }
+        Err(_) => {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use time::Timespec;
+    use std::i64;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0i64));
+        assert_eq!(parse_date("1970-01-01T00:00:01"), Some(1_000i64));
+        assert_eq!(parse_date("1970-01-01T00:00:01Z"), Some(1_000i64));
+        assert_eq!(parse_date("1970-01-01T00:00:01.5Z"), Some(1_500i64));
+        assert_eq!(parse_date("1970-01-01T00:00:01.123"), Some(1_123i64));
+        assert_eq!(parse_date("1969-12-31T23:59:59"), Some(-1_000i64));
+        assert_eq!(parse_date("2016-07-23T13:04:11"), Some(1_469_279_051_000i64));
+        assert_eq!(parse_date("2016-07-23T13:04:11."), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_timespec_to_millis() {
+        assert_eq!(timespec_to_millis(&Timespec::new(3, 2_500_000)), 3_002i64);
+    }
+
+    #[test]
+    fn test_date_precision_truncate() {
+        assert_eq!(DatePrecision::Milliseconds.truncate(61_234), 61_234);
+        assert_eq!(DatePrecision::Seconds.truncate(61_234), 61_000);
+        assert_eq!(DatePrecision::Minutes.truncate(61_234), 60_000);
+        assert_eq!(DatePrecision::Seconds.truncate(-1), -1_000);
+        assert_eq!(DatePrecision::Seconds.truncate(-1_000), -1_000);
+        assert_eq!(DatePrecision::Minutes.truncate(-61_234), -120_000);
+        assert_eq!(DatePrecision::Milliseconds.truncate(i64::MIN), i64::MIN);
+        assert_eq!(DatePrecision::Seconds.truncate(i64::MIN), i64::MIN);
+        assert_eq!(DatePrecision::Minutes.truncate(i64::MIN + 1), i64::MIN);
+        assert_eq!(DatePrecision::Seconds.truncate(i64::MAX), i64::MAX - 807);
+    }
+}
@@ -1,5 +1,7 @@
 use super::*;
 use itertools::Itertools;    
+use time::Timespec;
+use std::net::IpAddr;
 
 /// Tantivy's Document is the object that can
 /// be indexed and then searched for.  
@@ -47,11 +49,59 @@ impl Document {
         self.add(FieldValue::new(field, value));
     }
 
+    /// Add a pre-tokenized text field.
+    ///
+    /// The tokens bypass the tokenizer, and are indexed at their
+    /// given position. See `TokenStreamPayload`.
+    pub fn add_pretokenized(&mut self, field: Field, payload: TokenStreamPayload) {
+        self.add(FieldValue::new(field, Value::PreTokenized(payload)));
+    }
+
     /// Add a u32 field
     pub fn add_u32(&mut self, field: Field, value: u32) {
         self.add(FieldValue::new(field, Value::U32(value)));
     }
 
+    /// Add an i64 field
+    pub fn add_i64(&mut self, field: Field, value: i64) {
+        self.add(FieldValue::new(field, Value::I64(value)));
+    }
+
+    /// Add an f64 field
+    pub fn add_f64(&mut self, field: Field, value: f64) {
+        self.add(FieldValue::new(field, Value::F64(value)));
+    }
+
+    /// Add a bool field
+    pub fn add_bool(&mut self, field: Field, value: bool) {
+        self.add(FieldValue::new(field, Value::Bool(value)));
+    }
+
+    /// Add an IP address field
+    ///
+    /// IPv4 addresses are converted to IPv4-mapped IPv6 addresses.
+    pub fn add_ip(&mut self, field: Field, ip: IpAddr) {
+        self.add(FieldValue::new(field, Value::from(ip)));
+    }
+
+    /// Add a bytes field
+    pub fn add_bytes(&mut self, field: Field, bytes: Vec<u8>) {
+        self.add(FieldValue::new(field, Value::Bytes(bytes)));
+    }
+
+    /// Add a hierarchical facet field
+    pub fn add_facet(&mut self, field: Field, facet: Facet) {
+        self.add(FieldValue::new(field, Value::Facet(facet)));
+    }
+
+    /// Add a date field
+    ///
+    /// The date is stored with a millisecond precision.
+    pub fn add_date(&mut self, field: Field, timespec: Timespec) {
+        let millis = timespec_to_millis(&timespec);
+        self.add(FieldValue::new(field, Value::Date(millis)));
+    }
+
     /// Add a field value
     pub fn add(&mut self, field_value: FieldValue) {
         self.field_values.push(field_value);
@@ -0,0 +1,195 @@
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::io::Read;
+use common::BinarySerializable;
+
+
+/// Separator used between the segments of the encoded facet path.
+///
+/// It is lower than any other byte of a UTF-8 string, so that
+/// the children of a facet are lexicographically adjacent to it :
+/// `/a`, `/a/b`, `/a/b/c`, `/a/d`, `/aa`.
+pub const FACET_SEP_BYTE: u8 = 0u8;
+
+const FACET_SEP_CHAR: char = '\u{0}';
+
+
+/// A `Facet` is a path within a hierarchy of categories,
+/// for instance `/electronics/phones/android`.
+///
+/// Facets are ordered lexicographically segment by segment,
+/// so that all of the descendants of a facet directly follow it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, RustcEncodable, RustcDecodable)]
+pub struct Facet(String);
+
+impl Facet {
+
+    /// Returns the root facet, `/`.
+    ///
+    /// Every facet is a descendant of the root facet.
+    pub fn root() -> Facet {
+        Facet(String::new())
+    }
+
+    /// Creates a facet from its segments.
+    ///
+    /// e.g. `Facet::from_path(&["electronics", "phones"])`
+    /// is `/electronics/phones`.
+    pub fn from_path<S: AsRef<str>>(segments: &[S]) -> Facet {
+        let encoded_segments: Vec<&str> = segments
+            .iter()
+            .map(|segment| segment.as_ref())
+            .collect();
+        Facet(encoded_segments.join(&FACET_SEP_CHAR.to_string()))
+    }
+
+    /// Parses a facet from its textual representation.
+    ///
+    /// e.g. `/electronics/phones`
+    ///
+    /// Empty segments are ignored, so that `/electronics//phones/`
+    /// is the same as `/electronics/phones`.
+    pub fn from_text(text: &str) -> Facet {
+        let segments: Vec<&str> = text
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        Facet::from_path(&segments)
+    }
+
+    /// Returns the facet from its encoded bytes.
+    pub fn from_encoded(encoded: String) -> Facet {
+        Facet(encoded)
+    }
+
+    /// Returns the encoded representation of the facet,
+    /// as used in the term dictionary.
+    pub fn encoded_bytes(&self,) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns true iff the facet is the root facet.
+    pub fn is_root(&self,) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the segments of the facet.
+    pub fn segments(&self,) -> Vec<&str> {
+        if self.is_root() {
+            Vec::new()
+        }
+        else {
+            self.0.split(FACET_SEP_CHAR).collect()
+        }
+    }
+
+    /// Returns all of the ancestors of the facet, from the
+    /// closest to the root to the facet itself.
+    /// The root facet is not included.
+    ///
+    /// e.g. the prefixes of `/a/b/c` are `/a`, `/a/b`, `/a/b/c`.
+    pub fn prefixes(&self,) -> Vec<Facet> {
+        let segments = self.segments();
+        (1..segments.len() + 1)
+            .map(|num_segments| Facet::from_path(&segments[..num_segments]))
+            .collect()
+    }
+
+    /// Returns true iff `other` is the facet itself or one of its descendants.
+    pub fn is_prefix_of(&self, other: &Facet) -> bool {
+        if self.is_root() {
+            return true;
+        }
+        let self_bytes = self.encoded_bytes();
+        let other_bytes = other.encoded_bytes();
+        other_bytes.starts_with(self_bytes) &&
+            (other_bytes.len() == self_bytes.len() || other_bytes[self_bytes.len()] == FACET_SEP_BYTE)
+    }
+
+    /// Returns the facet obtained by appending a segment to this facet.
+    pub fn child(&self, segment: &str) -> Facet {
+        if self.is_root() {
+            Facet(String::from(segment))
+        }
+        else {
+            let mut encoded = self.0.clone();
+            encoded.push(FACET_SEP_CHAR);
+            encoded.push_str(segment);
+            Facet(encoded)
+        }
+    }
+}
+
+impl fmt::Display for Facet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_root() {
+            return write!(f, "/");
+        }
+        for segment in self.segments() {
+            try!(write!(f, "/{}", segment));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a str> for Facet {
+    fn from(text: &'a str) -> Facet {
+        Facet::from_text(text)
+    }
+}
+
+impl BinarySerializable for Facet {
+    fn serialize(&self, writer: &mut Write) -> io::Result<usize> {
+        self.0.serialize(writer)
+    }
+    fn deserialize(reader: &mut Read) -> io::Result<Facet> {
+        String::deserialize(reader).map(Facet)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_facet_text() {
+        let facet = Facet::from_text("/electronics/phones/android");
+        assert_eq!(facet.segments(), vec!("electronics", "phones", "android"));
+        assert_eq!(format!("{}", facet), "/electronics/phones/android");
+        assert_eq!(Facet::from_text("/electronics//phones/"), Facet::from_path(&["electronics", "phones"]));
+        assert!(Facet::from_text("/").is_root());
+        assert_eq!(format!("{}", Facet::root()), "/");
+    }
+
+    #[test]
+    fn test_facet_prefixes() {
+        let facet = Facet::from_text("/a/b/c");
+        assert_eq!(facet.prefixes(), vec!(Facet::from_text("/a"), Facet::from_text("/a/b"), facet.clone()));
+        assert!(Facet::root().prefixes().is_empty());
+        assert!(Facet::from_text("/a").is_prefix_of(&facet));
+        assert!(facet.is_prefix_of(&facet));
+        assert!(Facet::root().is_prefix_of(&facet));
+        assert!(!Facet::from_text("/a/b/c/d").is_prefix_of(&facet));
+        assert!(!Facet::from_text("/a/bb").is_prefix_of(&Facet::from_text("/a/b")));
+        assert!(!Facet::from_text("/a/b").is_prefix_of(&Facet::from_text("/a/bb")));
+        assert_eq!(Facet::from_text("/a").child("b"), Facet::from_text("/a/b"));
+        assert_eq!(Facet::root().child("a"), Facet::from_text("/a"));
+    }
+
+    #[test]
+    fn test_facet_order() {
+        let mut facets: Vec<Facet> = vec!("/aa", "/a/d", "/a", "/a/b/c", "/a/b")
+            .into_iter()
+            .map(Facet::from_text)
+            .collect();
+        facets.sort();
+        let facet_texts: Vec<String> = facets
+            .iter()
+            .map(|facet| format!("{}", facet))
+            .collect();
+        assert_eq!(facet_texts, vec!("/a", "/a/b", "/a/b/c", "/a/d", "/aa"));
+    }
+}
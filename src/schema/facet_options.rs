@@ -0,0 +1,50 @@
+/// Define how a hierarchical facet field should be handled by tantivy.
+///
+/// When indexed, each of the prefixes of a facet generates a term :
+/// a document with the facet `/electronics/phones` is found
+/// when searching for `/electronics` as well.
+#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+pub struct FacetOptions {
+    indexed: bool,
+    stored: bool,
+}
+
+impl FacetOptions {
+   
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self,) -> bool {
+        self.stored
+    }
+    
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self,) -> bool {
+        self.indexed
+    }
+    
+    /// Set the facet options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self,) -> FacetOptions {
+        self.stored = true;
+        self
+    }
+    
+    /// Set the facet options as indexed.
+    ///
+    /// Indexing is required to search for facets, and
+    /// to count them with the `HierarchicalFacetCollector`.
+    pub fn set_indexed(mut self,) -> FacetOptions {
+        self.indexed = true;
+        self
+    }
+}
+
+impl Default for FacetOptions {
+    fn default() -> FacetOptions {
+        FacetOptions {
+            indexed: false,
+            stored: false,
+        }
+    }    
+}
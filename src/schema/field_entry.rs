@@ -1,25 +1,67 @@
 use schema::TextOptions;
 use schema::U32Options;
+use schema::Cardinality;
+use schema::I64Options;
+use schema::F64Options;
+use schema::DateOptions;
+use schema::BytesOptions;
+use schema::FacetOptions;
+use schema::BoolOptions;
+use schema::IpOptions;
+use schema::Facet;
+use schema::parse_date;
+use schema::parse_ip;
 
 use rustc_serialize::Decodable;
 use rustc_serialize::Decoder;
 use rustc_serialize::Encodable;
 use rustc_serialize::Encoder;
 use rustc_serialize::json::Json;
+use rustc_serialize::base64::FromBase64;
 use schema::Value;
 
 
-/// A `FieldType` describes the type (text, u32) of a field as well as 
+/// A `FieldType` describes the type (text, u32, i64, f64, date, bytes, facet, bool, ip) of a field as well as 
 /// how it should be handled by tantivy.
-#[derive(Clone, Debug, RustcDecodable, RustcEncodable)]
+#[derive(Clone, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
 pub enum FieldType {
     /// String field type configuration
     Str(TextOptions),
     /// U32 field type configuration
     U32(U32Options),
+    /// I64 field type configuration
+    I64(I64Options),
+    /// F64 field type configuration
+    F64(F64Options),
+    /// Date field type configuration
+    Date(DateOptions),
+    /// Bytes field type configuration
+    Bytes(BytesOptions),
+    /// Hierarchical facet field type configuration
+    HierarchicalFacet(FacetOptions),
+    /// Bool field type configuration
+    Bool(BoolOptions),
+    /// IP address field type configuration
+    Ip(IpOptions),
 }
 
 impl FieldType {
+
+    /// Returns the name of the type, as it appears
+    /// in the JSON representation of the schema.
+    pub fn type_name(&self,) -> &'static str {
+        match *self {
+            FieldType::Str(_) => "text",
+            FieldType::U32(_) => "u32",
+            FieldType::I64(_) => "i64",
+            FieldType::F64(_) => "f64",
+            FieldType::Date(_) => "date",
+            FieldType::Bytes(_) => "bytes",
+            FieldType::HierarchicalFacet(_) => "facet",
+            FieldType::Bool(_) => "bool",
+            FieldType::Ip(_) => "ip",
+        }
+    }
     
     /// Parses a field value from json, given the target FieldType.
     ///
@@ -36,6 +78,51 @@ impl FieldType {
                     FieldType::U32(_) => {
                         Err(ValueParsingError::TypeError(format!("Expected a u32 int, got {:?}", json)))
                     }
+                    FieldType::I64(_) => {
+                        Err(ValueParsingError::TypeError(format!("Expected an i64 int, got {:?}", json)))
+                    }
+                    FieldType::F64(_) => {
+                        Err(ValueParsingError::TypeError(format!("Expected a f64, got {:?}", json)))
+                    }
+                    FieldType::Date(_) => {
+                        match parse_date(field_text) {
+                            Some(millis) => Ok(Value::Date(millis)),
+                            None => Err(ValueParsingError::TypeError(format!("Expected an ISO-8601 date, got {:?}", json))),
+                        }
+                    }
+                    FieldType::Bytes(_) => {
+                        field_text
+                            .from_base64()
+                            .map(Value::Bytes)
+                            .map_err(|_| ValueParsingError::TypeError(format!("Expected a base64 string, got {:?}", json)))
+                    }
+                    FieldType::HierarchicalFacet(_) => {
+                        if field_text.starts_with('/') {
+                            Ok(Value::Facet(Facet::from_text(field_text)))
+                        }
+                        else {
+                            Err(ValueParsingError::TypeError(format!("Expected a facet path starting with '/', got {:?}", json)))
+                        }
+                    }
+                    FieldType::Bool(_) => {
+                        Err(ValueParsingError::TypeError(format!("Expected a bool, got {:?}", json)))
+                    }
+                    FieldType::Ip(_) => {
+                        match parse_ip(field_text) {
+                            Some(ip) => Ok(Value::from(ip)),
+                            None => Err(ValueParsingError::TypeError(format!("Expected an IPv4 or IPv6 address, got {:?}", json))),
+                        }
+                    }
+                }
+            }
+            Json::Boolean(field_val_bool) => {
+                match *self {
+                    FieldType::Bool(_) => {
+                        Ok(Value::Bool(field_val_bool))
+                    }
+                    _ => {
+                        Err(ValueParsingError::TypeError(format!("Expected a string or a number, got {:?}", json)))
+                    }
                 }
             }
             Json::U64(ref field_val_u64) => {
@@ -48,13 +135,61 @@ impl FieldType {
                             Ok(Value::U32(*field_val_u64 as u32))
                         }
                     }
+                    FieldType::I64(_) => {
+                        if *field_val_u64 > (i64::max_value() as u64) {
+                            Err(ValueParsingError::OverflowError(format!("Expected i64, but value {:?} overflows.", field_val_u64)))
+                        }
+                        else {
+                            Ok(Value::I64(*field_val_u64 as i64))
+                        }
+                    }
+                    FieldType::F64(_) => {
+                        Ok(Value::F64(*field_val_u64 as f64))
+                    }
+                    FieldType::Date(_) => {
+                        if *field_val_u64 > (i64::max_value() as u64) {
+                            Err(ValueParsingError::OverflowError(format!("Expected a date in milliseconds, but value {:?} overflows.", field_val_u64)))
+                        }
+                        else {
+                            Ok(Value::Date(*field_val_u64 as i64))
+                        }
+                    }
                     _ => {
                         Err(ValueParsingError::TypeError(format!("Expected a string, got {:?}", json)))
                     }
                 }
             },
+            Json::I64(ref field_val_i64) => {
+                match *self {
+                    FieldType::I64(_) => {
+                        Ok(Value::I64(*field_val_i64))
+                    }
+                    FieldType::F64(_) => {
+                        Ok(Value::F64(*field_val_i64 as f64))
+                    }
+                    FieldType::Date(_) => {
+                        Ok(Value::Date(*field_val_i64))
+                    }
+                    FieldType::U32(_) => {
+                        Err(ValueParsingError::TypeError(format!("Expected a u32 int, got the negative value {:?}", field_val_i64)))
+                    }
+                    _ => {
+                        Err(ValueParsingError::TypeError(format!("Expected a string, got {:?}", json)))
+                    }
+                }
+            },
+            Json::F64(ref field_val_f64) => {
+                match *self {
+                    FieldType::F64(_) => {
+                        Ok(Value::F64(*field_val_f64))
+                    }
+                    _ => {
+                        Err(ValueParsingError::TypeError(format!("Expected an integer or a string, got {:?}", json)))
+                    }
+                }
+            },
             _ => {
-                Err(ValueParsingError::TypeError(format!("Expected a string or a u32, got {:?}", json)))
+                Err(ValueParsingError::TypeError(format!("Expected a string or a number, got {:?}", json)))
             }
         }
     }
@@ -68,7 +203,7 @@ impl FieldType {
 /// - a field name 
 /// - a field type, itself wrapping up options describing 
 /// how the field should be indexed.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FieldEntry {
     name: String,
     field_type: FieldType,
@@ -105,6 +240,69 @@ impl FieldEntry {
             field_type: FieldType::U32(field_type),
         }
     }
+
+    /// Creates a new i64 field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_i64(field_name: String, field_type: I64Options) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::I64(field_type),
+        }
+    }
+
+    /// Creates a new f64 field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_f64(field_name: String, field_type: F64Options) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::F64(field_type),
+        }
+    }
+
+    /// Creates a new date field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_date(field_name: String, field_type: DateOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::Date(field_type),
+        }
+    }
+
+    /// Creates a new bytes field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_bytes(field_name: String, field_type: BytesOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::Bytes(field_type),
+        }
+    }
+    
+    /// Creates a new bool field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_bool(field_name: String, field_type: BoolOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::Bool(field_type),
+        }
+    }
+    
+    /// Creates a new IP address field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_ip(field_name: String, field_type: IpOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::Ip(field_type),
+        }
+    }
+    
+    /// Creates a new hierarchical facet field entry in the schema, given
+    /// a name, and some options.
+    pub fn new_facet(field_name: String, field_type: FacetOptions) -> FieldEntry {
+        FieldEntry {
+            name: field_name,
+            field_type: FieldType::HierarchicalFacet(field_type),
+        }
+    }
     
     /// Returns the name of the field
     pub fn name(&self,) -> &String {
@@ -120,17 +318,131 @@ impl FieldEntry {
     pub fn is_indexed(&self,) -> bool {
         match self.field_type {
             FieldType::Str(ref options) => options.get_indexing_options().is_indexed(),
-            _ => false, // TODO handle u32 indexed
+            FieldType::U32(ref options) => options.is_indexed(),
+            FieldType::I64(ref options) => options.is_indexed(),
+            FieldType::F64(ref options) => options.is_indexed(),
+            FieldType::Date(ref options) => options.is_indexed(),
+            FieldType::HierarchicalFacet(ref options) => options.is_indexed(),
+            FieldType::Bool(ref options) => options.is_indexed(),
+            FieldType::Ip(ref options) => options.is_indexed(),
+            FieldType::Bytes(_) => false,
+        }
+    }
+    
+    /// Returns true iff the positions of the terms of the field are recorded.
+    ///
+    /// Only text fields indexed with `TokenizedWithFreqAndPosition`
+    /// or `TokenizedWithFreqPositionAndPayload` have positions.
+    pub fn has_positions(&self,) -> bool {
+        match self.field_type {
+            FieldType::Str(ref options) => options.get_indexing_options().is_position_enabled(),
+            _ => false,
+        }
+    }
+    
+    /// Returns true iff the field norms of the field are recorded.
+    ///
+    /// Only indexed text fields may have field norms.
+    pub fn has_fieldnorms(&self,) -> bool {
+        match self.field_type {
+            FieldType::Str(ref options) => options.has_fieldnorms(),
+            _ => false,
         }
     }
     
-    /// Returns true iff the field is a u32 fast field
+    /// Returns the boost of the field.
+    ///
+    /// Only text fields may be boosted. Other fields
+    /// have a boost of 1.
+    pub fn boost(&self,) -> f32 {
+        match self.field_type {
+            FieldType::Str(ref options) => options.get_boost(),
+            _ => 1f32,
+        }
+    }
+    
+    /// Returns true iff the field is a single-valued u32 fast field
     pub fn is_u32_fast(&self,) -> bool {
         match self.field_type {
-            FieldType::U32(ref options) => options.is_fast(),
+            FieldType::U32(ref options) => {
+                options.is_fast() && options.get_cardinality() == Cardinality::SingleValue
+            }
+            _ => false,
+        }
+    }
+    
+    /// Returns true iff the field is a multivalued u32 fast field
+    pub fn is_multivalued_u32_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::U32(ref options) => {
+                options.is_fast() && options.get_cardinality() == Cardinality::MultiValues
+            }
+            _ => false,
+        }
+    }
+    
+    /// Returns true iff the field is an i64 fast field
+    pub fn is_i64_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::I64(ref options) => options.is_fast(),
+            _ => false,
+        }
+    }
+    
+    /// Returns true iff the field is an f64 fast field
+    pub fn is_f64_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::F64(ref options) => options.is_fast(),
+            _ => false,
+        }
+    }
+    
+    /// Returns true iff the field is a date fast field
+    pub fn is_date_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::Date(ref options) => options.is_fast(),
+            _ => false,
+        }
+    }
+
+    /// Returns true iff the field is a text field whose
+    /// term ordinals are recorded in a fast field
+    pub fn is_str_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::Str(ref options) => options.is_fast(),
+            _ => false,
+        }
+    }
+
+    /// Returns true iff the field is a bytes fast field
+    pub fn is_bytes_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::Bytes(ref options) => options.is_fast(),
+            _ => false,
+        }
+    }
+
+    /// Returns true iff the field is a bool fast field
+    pub fn is_bool_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::Bool(ref options) => options.is_fast(),
+            _ => false,
+        }
+    }
+
+    /// Returns true iff the field is an IP fast field
+    pub fn is_ip_fast(&self,) -> bool {
+        match self.field_type {
+            FieldType::Ip(ref options) => options.is_fast(),
             _ => false,
         }
     }
+
+    /// Returns true iff the field is a fast field
+    /// stored as u64. (i64, f64 or date)
+    pub fn is_u64_fast(&self,) -> bool {
+        self.is_i64_fast() || self.is_f64_fast() || self.is_date_fast()
+    }
     
     /// Returns true iff the field is stored
     pub fn is_stored(&self,) -> bool {
@@ -138,6 +450,27 @@ impl FieldEntry {
             FieldType::U32(ref options) => {
                 options.is_stored()
             }
+            FieldType::I64(ref options) => {
+                options.is_stored()
+            }
+            FieldType::F64(ref options) => {
+                options.is_stored()
+            }
+            FieldType::Date(ref options) => {
+                options.is_stored()
+            }
+            FieldType::Bytes(ref options) => {
+                options.is_stored()
+            }
+            FieldType::HierarchicalFacet(ref options) => {
+                options.is_stored()
+            }
+            FieldType::Bool(ref options) => {
+                options.is_stored()
+            }
+            FieldType::Ip(ref options) => {
+                options.is_stored()
+            }
             FieldType::Str(ref options) => {
                 options.is_stored()
             }
@@ -170,6 +503,62 @@ impl Encodable for FieldEntry {
                         options.encode(s)
                     }));
                 }
+                FieldType::I64(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("i64")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
+                FieldType::F64(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("f64")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
+                FieldType::Date(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("date")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
+                FieldType::Bytes(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("bytes")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
+                FieldType::HierarchicalFacet(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("facet")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
+                FieldType::Bool(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("bool")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
+                FieldType::Ip(ref options) => {
+                    try!(s.emit_struct_field("type", 1, |s| {
+                        s.emit_str("ip")
+                    }));
+                    try!(s.emit_struct_field("options", 2, |s| {
+                        options.encode(s)
+                    }));
+                }
             }
             
             Ok(())
@@ -192,6 +581,34 @@ impl Decodable for FieldEntry {
                         let u32_options = try!(U32Options::decode(d));
                         Ok(FieldEntry::new_u32(name, u32_options))
                     }
+                    "i64" => {
+                        let i64_options = try!(I64Options::decode(d));
+                        Ok(FieldEntry::new_i64(name, i64_options))
+                    }
+                    "f64" => {
+                        let f64_options = try!(F64Options::decode(d));
+                        Ok(FieldEntry::new_f64(name, f64_options))
+                    }
+                    "date" => {
+                        let date_options = try!(DateOptions::decode(d));
+                        Ok(FieldEntry::new_date(name, date_options))
+                    }
+                    "bytes" => {
+                        let bytes_options = try!(BytesOptions::decode(d));
+                        Ok(FieldEntry::new_bytes(name, bytes_options))
+                    }
+                    "facet" => {
+                        let facet_options = try!(FacetOptions::decode(d));
+                        Ok(FieldEntry::new_facet(name, facet_options))
+                    }
+                    "bool" => {
+                        let bool_options = try!(BoolOptions::decode(d));
+                        Ok(FieldEntry::new_bool(name, bool_options))
+                    }
+                    "ip" => {
+                        let ip_options = try!(IpOptions::decode(d));
+                        Ok(FieldEntry::new_ip(name, ip_options))
+                    }
                     "text" => {
                         let text_options = try!(TextOptions::decode(d));
                         Ok(FieldEntry::new_text(name, text_options))
@@ -211,6 +628,15 @@ mod tests {
 
     use super::*;
     use schema::TEXT;
+    use schema::I64Options;
+    use schema::F64Options;
+    use schema::{DateOptions, DatePrecision};
+    use schema::BytesOptions;
+    use schema::{FacetOptions, Facet};
+    use schema::{BoolOptions, U32Options};
+    use schema::IpOptions;
+    use std::net::IpAddr;
+    use std::str::FromStr;
     use rustc_serialize::json;
     
     #[test]
@@ -225,4 +651,159 @@ mod tests {
   }
 }"#);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_i64_json_serialization() {
+        let i64_options = I64Options::default().set_indexed().set_fast();
+        let field_entry = FieldEntry::new_i64(String::from("timestamp"), i64_options);
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"timestamp","type":"i64","options":{"indexed":true,"fast":true,"stored":false}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        assert_eq!(field_entry_deser.name(), "timestamp");
+        assert!(field_entry_deser.is_i64_fast());
+    }
+
+    #[test]
+    fn test_i64_value_from_json() {
+        let field_type = FieldType::I64(I64Options::default());
+        assert_eq!(field_type.value_from_json(&Json::I64(-3)).unwrap(), Value::I64(-3));
+        assert_eq!(field_type.value_from_json(&Json::U64(3)).unwrap(), Value::I64(3));
+        assert!(field_type.value_from_json(&Json::U64(u64::max_value())).is_err());
+        assert!(field_type.value_from_json(&Json::String(String::from("3"))).is_err());
+    }
+
+    #[test]
+    fn test_f64_json_serialization() {
+        let f64_options = F64Options::default().set_stored().set_fast();
+        let field_entry = FieldEntry::new_f64(String::from("price"), f64_options);
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"price","type":"f64","options":{"indexed":false,"fast":true,"stored":true}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        assert_eq!(field_entry_deser.name(), "price");
+        assert!(field_entry_deser.is_f64_fast());
+        assert!(field_entry_deser.is_stored());
+    }
+
+    #[test]
+    fn test_f64_value_from_json() {
+        let field_type = FieldType::F64(F64Options::default());
+        assert_eq!(field_type.value_from_json(&Json::F64(-3.5)).unwrap(), Value::F64(-3.5));
+        assert_eq!(field_type.value_from_json(&Json::U64(3)).unwrap(), Value::F64(3.0));
+        assert_eq!(field_type.value_from_json(&Json::I64(-3)).unwrap(), Value::F64(-3.0));
+        assert!(field_type.value_from_json(&Json::String(String::from("3"))).is_err());
+    }
+
+    #[test]
+    fn test_date_json_serialization() {
+        let date_options = DateOptions::default()
+            .set_indexed()
+            .set_fast()
+            .set_precision(DatePrecision::Seconds);
+        let field_entry = FieldEntry::new_date(String::from("timestamp"), date_options.clone());
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"timestamp","type":"date","options":{"indexed":true,"fast":true,"stored":false,"precision":"seconds"}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        match *field_entry_deser.field_type() {
+            FieldType::Date(ref options) => {
+                assert_eq!(options, &date_options);
+            }
+            _ => {
+                panic!("Expected a date field");
+            }
+        }
+    }
+
+    #[test]
+    fn test_date_value_from_json() {
+        let field_type = FieldType::Date(DateOptions::default());
+        assert_eq!(field_type.value_from_json(&Json::String(String::from("1970-01-01T00:00:02Z"))).unwrap(), Value::Date(2_000));
+        assert_eq!(field_type.value_from_json(&Json::I64(-3)).unwrap(), Value::Date(-3));
+        assert!(field_type.value_from_json(&Json::String(String::from("tomorrow"))).is_err());
+    }
+
+    #[test]
+    fn test_bytes_json_serialization() {
+        let field_entry = FieldEntry::new_bytes(String::from("payload"), BytesOptions::default().set_fast());
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"payload","type":"bytes","options":{"fast":true,"stored":false}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        assert!(field_entry_deser.is_bytes_fast());
+        assert!(!field_entry_deser.is_indexed());
+    }
+
+    #[test]
+    fn test_bytes_value_from_json() {
+        let field_type = FieldType::Bytes(BytesOptions::default());
+        assert_eq!(field_type.value_from_json(&Json::String(String::from("AQID"))).unwrap(), Value::Bytes(vec!(1u8, 2u8, 3u8)));
+        assert!(field_type.value_from_json(&Json::String(String::from("!!"))).is_err());
+        assert!(field_type.value_from_json(&Json::U64(3)).is_err());
+    }
+
+    #[test]
+    fn test_facet_json_serialization() {
+        let field_entry = FieldEntry::new_facet(String::from("category"), FacetOptions::default().set_indexed());
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"category","type":"facet","options":{"indexed":true,"stored":false}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        match *field_entry_deser.field_type() {
+            FieldType::HierarchicalFacet(ref options) => {
+                assert!(options.is_indexed());
+            }
+            _ => {
+                panic!("Expected a facet field");
+            }
+        }
+    }
+
+    #[test]
+    fn test_facet_value_from_json() {
+        let field_type = FieldType::HierarchicalFacet(FacetOptions::default());
+        assert_eq!(field_type.value_from_json(&Json::String(String::from("/electronics/phones"))).unwrap(),
+                   Value::Facet(Facet::from_path(&["electronics", "phones"])));
+        assert!(field_type.value_from_json(&Json::String(String::from("electronics"))).is_err());
+        assert!(field_type.value_from_json(&Json::U64(3)).is_err());
+    }
+
+    #[test]
+    fn test_bool_json_serialization() {
+        let field_entry = FieldEntry::new_bool(String::from("is_published"), BoolOptions::default().set_indexed().set_fast());
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"is_published","type":"bool","options":{"indexed":true,"fast":true,"stored":false}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        assert!(field_entry_deser.is_bool_fast());
+        assert!(!field_entry_deser.is_u32_fast());
+    }
+
+    #[test]
+    fn test_bool_value_from_json() {
+        let field_type = FieldType::Bool(BoolOptions::default());
+        assert_eq!(field_type.value_from_json(&Json::Boolean(true)).unwrap(), Value::Bool(true));
+        assert_eq!(field_type.value_from_json(&Json::Boolean(false)).unwrap(), Value::Bool(false));
+        assert!(field_type.value_from_json(&Json::U64(1)).is_err());
+        assert!(field_type.value_from_json(&Json::String(String::from("true"))).is_err());
+        assert!(FieldType::U32(U32Options::default()).value_from_json(&Json::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_ip_json_serialization() {
+        let field_entry = FieldEntry::new_ip(String::from("src_ip"), IpOptions::default().set_indexed().set_fast());
+        let json_str = format!("{}", json::as_json(&field_entry));
+        assert_eq!(json_str, r#"{"name":"src_ip","type":"ip","options":{"indexed":true,"fast":true,"stored":false}}"#);
+        let field_entry_deser: FieldEntry = json::decode(&json_str).unwrap();
+        assert!(field_entry_deser.is_ip_fast());
+        assert!(!field_entry_deser.is_u64_fast());
+    }
+
+    #[test]
+    fn test_ip_value_from_json() {
+        let field_type = FieldType::Ip(IpOptions::default());
+        assert_eq!(
+            field_type.value_from_json(&Json::String(String::from("10.0.0.1"))).unwrap(),
+            Value::from(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert_eq!(
+            field_type.value_from_json(&Json::String(String::from("fe80::1"))).unwrap(),
+            Value::from(IpAddr::from_str("fe80::1").unwrap()));
+        assert!(field_type.value_from_json(&Json::String(String::from("10.0.0.0/8"))).is_err());
+        assert!(field_type.value_from_json(&Json::U64(167772161)).is_err());
+    }
+}
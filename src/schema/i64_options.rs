@@ -0,0 +1,65 @@
+/// Define how an I64 field should be handled by tantivy.
+#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+pub struct I64Options {
+    indexed: bool,
+    fast: bool,
+    stored: bool,
+}
+
+impl I64Options {
+   
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self,) -> bool {
+        self.stored
+    }
+    
+    
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self,) -> bool {
+        self.indexed
+    }
+    
+    /// Returns true iff the value is a fast field. 
+    pub fn is_fast(&self,) -> bool {
+        self.fast
+    }
+    
+    /// Set the i64 options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self,) -> I64Options {
+        self.stored = true;
+        self
+    }
+    
+    /// Set the i64 options as indexed.
+    ///
+    /// Setting an integer as indexed will generate
+    /// a posting list for each value taken by the integer.
+    pub fn set_indexed(mut self,) -> I64Options {
+        self.indexed = true;
+        self
+    }
+    
+    /// Set the i64 options as a fast field.
+    ///
+    /// Fast fields are designed for random access.
+    /// Access time are similar to a random lookup in an array. 
+    /// If more than one value is associated to a fast field, only the last one is
+    /// kept.
+    pub fn set_fast(mut self,) -> I64Options {
+        self.fast = true;
+        self
+    }
+}
+
+impl Default for I64Options {
+    fn default() -> I64Options {
+        I64Options {
+            fast: false,
+            indexed: false,
+            stored: false,
+        }
+    }    
+}
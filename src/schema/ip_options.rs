@@ -0,0 +1,187 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+
+/// Define how an IP address field should be handled by tantivy.
+///
+/// Both IPv4 and IPv6 addresses are accepted.
+/// IPv4 addresses are handled as IPv4-mapped IPv6 addresses
+/// (`::ffff:a.b.c.d`), so that all addresses are 128-bits wide.
+#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+pub struct IpOptions {
+    indexed: bool,
+    fast: bool,
+    stored: bool,
+}
+
+impl IpOptions {
+
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self,) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self,) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self,) -> bool {
+        self.fast
+    }
+
+    /// Set the IP options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self,) -> IpOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Set the IP options as indexed.
+    ///
+    /// Setting an IP field as indexed will generate
+    /// a posting list for each address, and makes it
+    /// possible to search for CIDR ranges.
+    pub fn set_indexed(mut self,) -> IpOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the IP options as a fast field.
+    ///
+    /// IP fast fields are stored as two u64 fast fields,
+    /// the high and the low 64 bits of the address.
+    /// Documents without any value are associated to `::`.
+    pub fn set_fast(mut self,) -> IpOptions {
+        self.fast = true;
+        self
+    }
+}
+
+impl Default for IpOptions {
+    fn default() -> IpOptions {
+        IpOptions {
+            fast: false,
+            indexed: false,
+            stored: false,
+        }
+    }
+}
+
+
+/// Converts an IP address to its IPv6 form.
+///
+/// IPv4 addresses are mapped to `::ffff:a.b.c.d`.
+pub fn ip_to_ipv6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ipv4) => ipv4.to_ipv6_mapped(),
+        IpAddr::V6(ipv6) => ipv6,
+    }
+}
+
+/// Converts back an IPv6 address to an IP address,
+/// IPv4-mapped addresses being returned as IPv4 addresses.
+pub fn ipv6_to_ip(ipv6: Ipv6Addr) -> IpAddr {
+    let segments = ipv6.segments();
+    if segments[..5].iter().all(|&segment| segment == 0u16) && segments[5] == 0xffff {
+        let octets = ipv6.octets();
+        IpAddr::V4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]))
+    }
+    else {
+        IpAddr::V6(ipv6)
+    }
+}
+
+/// Parses an IPv4 or an IPv6 address.
+pub fn parse_ip(text: &str) -> Option<Ipv6Addr> {
+    IpAddr::from_str(text).ok().map(ip_to_ipv6)
+}
+
+/// Parses an IP address or a CIDR block, and returns the
+/// first and the last address of the block.
+///
+/// e.g. `10.0.0.0/8` returns `(::ffff:10.0.0.0, ::ffff:10.255.255.255)`.
+///
+/// The prefix length of an IPv4 block applies to the IPv4 address,
+/// and therefore needs to be lower or equal to 32.
+/// A plain address is a block containing only this address.
+pub fn parse_cidr(text: &str) -> Option<(Ipv6Addr, Ipv6Addr)> {
+    let (addr_text, prefix_len) = match text.find('/') {
+        Some(pos) => {
+            match text[pos + 1..].parse::<u32>() {
+                Ok(prefix_len) => (&text[..pos], Some(prefix_len)),
+                Err(_) => { return None; }
+            }
+        }
+        None => (text, None)
+    };
+    let ip = match IpAddr::from_str(addr_text) {
+        Ok(ip) => ip,
+        Err(_) => { return None; }
+    };
+    let prefix_len = match (ip, prefix_len) {
+        (IpAddr::V4(_), Some(prefix_len)) if prefix_len <= 32 => 96 + prefix_len,
+        (IpAddr::V6(_), Some(prefix_len)) if prefix_len <= 128 => prefix_len,
+        (_, Some(_)) => { return None; }
+        (_, None) => 128,
+    };
+    let mut lower = ip_to_ipv6(ip).octets();
+    let mut upper = lower;
+    for bit in prefix_len..128 {
+        let byte = (bit / 8) as usize;
+        let mask = 1u8 << (7 - bit % 8);
+        lower[byte] &= !mask;
+        upper[byte] |= mask;
+    }
+    Some((Ipv6Addr::from(lower), Ipv6Addr::from(upper)))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::str::FromStr;
+
+    fn ipv4(text: &str) -> Ipv6Addr {
+        Ipv4Addr::from_str(text).unwrap().to_ipv6_mapped()
+    }
+
+    #[test]
+    fn test_parse_ip() {
+        assert_eq!(parse_ip("10.0.0.1"), Some(ipv4("10.0.0.1")));
+        assert_eq!(parse_ip("fe80::1"), Some(Ipv6Addr::from_str("fe80::1").unwrap()));
+        assert_eq!(parse_ip("10.0.0"), None);
+        assert_eq!(parse_ip("10.0.0.0/8"), None);
+    }
+
+    #[test]
+    fn test_ipv6_to_ip() {
+        assert_eq!(ipv6_to_ip(ipv4("10.0.0.1")), IpAddr::from_str("10.0.0.1").unwrap());
+        assert_eq!(ipv6_to_ip(Ipv6Addr::from_str("fe80::1").unwrap()), IpAddr::from_str("fe80::1").unwrap());
+        assert_eq!(ipv6_to_ip(Ipv6Addr::from_str("::1").unwrap()), IpAddr::from_str("::1").unwrap());
+    }
+
+    #[test]
+    fn test_parse_cidr() {
+        assert_eq!(parse_cidr("10.0.0.0/8"), Some((ipv4("10.0.0.0"), ipv4("10.255.255.255"))));
+        assert_eq!(parse_cidr("10.1.2.3/16"), Some((ipv4("10.1.0.0"), ipv4("10.1.255.255"))));
+        assert_eq!(parse_cidr("10.1.2.3"), Some((ipv4("10.1.2.3"), ipv4("10.1.2.3"))));
+        assert_eq!(parse_cidr("10.1.2.3/32"), Some((ipv4("10.1.2.3"), ipv4("10.1.2.3"))));
+        assert_eq!(parse_cidr("0.0.0.0/0"), Some((ipv4("0.0.0.0"), ipv4("255.255.255.255"))));
+        assert_eq!(
+            parse_cidr("2001:db8::/32"),
+            Some((
+                Ipv6Addr::from_str("2001:db8::").unwrap(),
+                Ipv6Addr::from_str("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff").unwrap()
+            )));
+        assert_eq!(parse_cidr("10.0.0.0/33"), None);
+        assert_eq!(parse_cidr("::/129"), None);
+        assert_eq!(parse_cidr("10.0.0.0/"), None);
+        assert_eq!(parse_cidr("hello/8"), None);
+    }
+}
@@ -32,7 +32,7 @@ let title_options = TextOptions::default()
     .set_stored()
     .set_indexing_options(TextIndexingOptions::TokenizedWithFreqAndPosition);
 schema_builder.add_text_field("title_options", title_options);
-let schema = schema_builder.build();
+let schema = schema_builder.build().unwrap();
 ```
 
 We can split the problem of generating a search result page into two phases :
@@ -59,7 +59,7 @@ The example can be rewritten :
 use tantivy::schema::*;
 let mut schema_builder = SchemaBuilder::default();
 schema_builder.add_text_field("title_options", TEXT | STORED);
-let schema = schema_builder.build();
+let schema = schema_builder.build().unwrap();
 ``` 
 
 
@@ -75,7 +75,7 @@ let num_stars_options = U32Options::default()
     .set_stored()
     .set_indexed();
 schema_builder.add_u32_field("num_stars", num_stars_options);
-let schema = schema_builder.build();
+let schema = schema_builder.build().unwrap();
 ```
 
 Just like for Text fields (see above),
@@ -93,6 +93,9 @@ u32 that are indexed as fast will be stored in a special data structure that wil
 make it possible to access the u32 value given the doc id rapidly. This is useful if the value of
 the field is required during scoring or collection for instance.
 
+The `indexed`, `fast` and `stored` options are independent,
+and can be freely combined.
+
 */ 
 
 mod schema;
@@ -104,19 +107,29 @@ mod field_value;
 
 mod text_options;
 mod u32_options;
+mod i64_options;
+mod f64_options;
+mod date_options;
+mod bytes_options;
+mod bool_options;
+mod ip_options;
+mod facet_options;
+mod facet;
+mod token_stream_payload;
 mod field;
 mod value;
 mod named_field_document;
 
 
 pub use self::named_field_document::NamedFieldDocument;
-pub use self::schema::{Schema, SchemaBuilder};
+pub use self::schema::{Schema, SchemaBuilder, FieldEntriesIter};
 pub use self::value::Value;
 pub use self::schema::DocParsingError;
+pub use self::schema::SchemaError;
 
 pub use self::document::Document;
 pub use self::field::Field;
-pub use self::term::Term;
+pub use self::term::{Term, TERMS_FORMAT_VERSION};
 
 pub use self::field_entry::FieldEntry;
 pub use self::field_value::FieldValue;
@@ -126,11 +139,29 @@ pub use self::text_options::TextOptions;
 pub use self::text_options::TEXT;
 pub use self::text_options::STRING;
 pub use self::text_options::STORED;
+pub use self::text_options::TEXT_STORED_ONLY;
 pub use self::text_options::TextIndexingOptions;
 
 pub use self::u32_options::U32Options;
+pub use self::u32_options::Cardinality;
 pub use self::u32_options::FAST;
 
+pub use self::i64_options::I64Options;
+pub use self::f64_options::F64Options;
+
+pub use self::date_options::{DateOptions, DatePrecision};
+pub use self::date_options::{parse_date, timespec_to_millis};
+
+pub use self::bytes_options::BytesOptions;
+pub use self::bool_options::BoolOptions;
+
+pub use self::ip_options::IpOptions;
+pub use self::ip_options::{parse_ip, parse_cidr, ip_to_ipv6, ipv6_to_ip};
+
+pub use self::facet_options::FacetOptions;
+pub use self::facet::{Facet, FACET_SEP_BYTE};
+pub use self::token_stream_payload::TokenStreamPayload;
+
 use regex::Regex;
 
 
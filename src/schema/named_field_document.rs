@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
 use schema::Value;
+use schema::ipv6_to_ip;
 use rustc_serialize::Encodable;
 use rustc_serialize::Encoder;
+use rustc_serialize::base64::{ToBase64, STANDARD};
 
 
 
@@ -19,9 +21,9 @@ impl Encodable for NamedFieldDocument {
         s.emit_struct("named_field_document", self.0.len(), |s| {
             for (i, (name, vals)) in self.0.iter().enumerate() {
                 try!(s.emit_struct_field(name, i, |s| {
-                    for (j, val) in vals.iter().enumerate() {
-                        try!(s.emit_seq(vals.len(), |s| {
-                            s.emit_seq_elt(j, |s| {
+                    s.emit_seq(vals.len(), |s| {
+                        for (j, val) in vals.iter().enumerate() {
+                            try!(s.emit_seq_elt(j, |s| {
                                 match *val {
                                     Value::Str(ref text) => {
                                         s.emit_str(text)
@@ -29,12 +31,35 @@ impl Encodable for NamedFieldDocument {
                                     Value::U32(ref val) => {
                                         s.emit_u32(*val)
                                     }
+                                    Value::I64(ref val) => {
+                                        s.emit_i64(*val)
+                                    }
+                                    Value::F64(ref val) => {
+                                        s.emit_f64(*val)
+                                    }
+                                    Value::Date(ref val) => {
+                                        s.emit_i64(*val)
+                                    }
+                                    Value::Bytes(ref bytes) => {
+                                        s.emit_str(&bytes.to_base64(STANDARD))
+                                    }
+                                    Value::Facet(ref facet) => {
+                                        s.emit_str(&format!("{}", facet))
+                                    }
+                                    Value::Bool(val) => {
+                                        s.emit_bool(val)
+                                    }
+                                    Value::Ip(_) => {
+                                        s.emit_str(&format!("{}", ipv6_to_ip(val.ip_value())))
+                                    }
+                                    Value::PreTokenized(ref payload) => {
+                                        s.emit_str(&payload.text())
+                                    }
                                 }
-                            })
-                        }));
-                    }
-                    Ok(())
-                    
+                            }));
+                        }
+                        Ok(())
+                    })
                 }));
             }
             Ok(())
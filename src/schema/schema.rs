@@ -11,6 +11,8 @@ use schema::field_entry::ValueParsingError;
 use std::sync::Arc;
 use super::*;
 use std::fmt;
+use std::iter::Enumerate;
+use std::slice;
 
 
 
@@ -31,12 +33,11 @@ use std::fmt;
 /// let id_field = schema_builder.add_text_field("id", STRING);
 /// let title_field = schema_builder.add_text_field("title", TEXT);
 /// let body_field = schema_builder.add_text_field("body", TEXT);
-/// let schema = schema_builder.build();
+/// let schema = schema_builder.build().unwrap();
 ///
 /// ```
 pub struct SchemaBuilder {
     fields: Vec<FieldEntry>,
-    fields_map: HashMap<String, Field>,
 }
 
 
@@ -48,10 +49,8 @@ impl SchemaBuilder {
     /// # Caution
     ///
     /// Appending two fields with the same name 
-    /// will result in the shadowing of the first 
-    /// by the second one.
-    /// The first field will get a field id 
-    /// but only the second one will be indexed  
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
     pub fn add_u32_field(
             &mut self,
             field_name_str: &str, 
@@ -61,16 +60,133 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new i64 field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_i64_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: I64Options) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_i64(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new f64 field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_f64_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: F64Options) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_f64(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new date field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_date_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: DateOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_date(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new bytes field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_bytes_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: BytesOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_bytes(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new bool field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_bool_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: BoolOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_bool(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new IP address field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_ip_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: IpOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_ip(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
+    /// Adds a new hierarchical facet field.
+    /// Returns the associated field handle
+    ///
+    /// # Caution
+    ///
+    /// Appending two fields with the same name 
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
+    pub fn add_facet_field(
+            &mut self,
+            field_name_str: &str, 
+            field_options: FacetOptions) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_facet(field_name, field_options);
+        self.add_field(field_entry)
+    }
+
     /// Adds a new text field.
     /// Returns the associated field handle
     ///
     /// # Caution
     ///
     /// Appending two fields with the same name 
-    /// will result in the shadowing of the first 
-    /// by the second one.
-    /// The first field will get a field id 
-    /// but only the second one will be indexed  
+    /// will make `build()` fail with a
+    /// `SchemaError::DuplicateField` error.
     pub fn add_text_field(
             &mut self,
             field_name_str: &str, 
@@ -84,20 +200,109 @@ impl SchemaBuilder {
     /// Adds a field entry to the schema in build.
     fn add_field(&mut self, field_entry: FieldEntry) -> Field {
         let field = Field(self.fields.len() as u8);
-        let field_name = field_entry.name().clone();
         self.fields.push(field_entry);
-        self.fields_map.insert(field_name, field);
         field
     }
     
     
     /// Finalize the creation of a `Schema`
     /// This will consume your `SchemaBuilder`
-    pub fn build(self,) -> Schema {
-        Schema(Arc::new(InnerSchema {
+    ///
+    /// # Errors
+    ///
+    /// The field names are validated at this point.
+    /// Building the schema fails if two fields share the same name, or
+    /// if a field name is empty or contains a `:`, as it would not
+    /// be usable in the query parser.
+    pub fn build(self,) -> Result<Schema, SchemaError> {
+        let mut fields_map: HashMap<String, Field> = HashMap::new();
+        for (field_id, field_entry) in self.fields.iter().enumerate() {
+            let field_name = field_entry.name();
+            try!(validate_field_name(field_name));
+            if fields_map.contains_key(field_name) {
+                return Err(SchemaError::DuplicateField(field_name.clone()));
+            }
+            fields_map.insert(field_name.clone(), Field(field_id as u8));
+        }
+        for (field_id, field_entry) in self.fields.iter().enumerate() {
+            try!(validate_copy_to(field_entry, &self.fields[..field_id]));
+            try!(validate_fast_text(field_entry));
+        }
+        Ok(Schema(Arc::new(InnerSchema {
             fields: self.fields,
-            fields_map: self.fields_map,
-        })) 
+            fields_map: fields_map,
+        })))
+    }
+}
+
+
+fn validate_field_name(field_name: &str) -> Result<(), SchemaError> {
+    if field_name.is_empty() {
+        Err(SchemaError::EmptyFieldName)
+    }
+    else if field_name.contains(':') {
+        Err(SchemaError::InvalidFieldName(String::from(field_name)))
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// The values of a field may only be copied to an indexed text field
+/// declared before it, which is not copied itself.
+///
+/// `previous_fields` are the fields declared before `field_entry`.
+fn validate_copy_to(field_entry: &FieldEntry, previous_fields: &[FieldEntry]) -> Result<(), SchemaError> {
+    let copy_to = match *field_entry.field_type() {
+        FieldType::Str(ref text_options) => text_options.get_copy_to(),
+        _ => None,
+    };
+    if let Some(Field(dest_field_id)) = copy_to {
+        let is_valid_dest = previous_fields
+            .get(dest_field_id as usize)
+            .map(|dest_field_entry| {
+                match *dest_field_entry.field_type() {
+                    FieldType::Str(ref dest_options) => {
+                        dest_options.get_indexing_options().is_indexed() && dest_options.get_copy_to().is_none()
+                    }
+                    _ => false,
+                }
+            })
+            .unwrap_or(false);
+        if !is_valid_dest {
+            return Err(SchemaError::InvalidCopyTo(field_entry.name().clone()));
+        }
+    }
+    Ok(())
+}
+
+/// The term ordinals of a text field are only recorded
+/// for untokenized fields, whose values are a single term.
+fn validate_fast_text(field_entry: &FieldEntry) -> Result<(), SchemaError> {
+    match *field_entry.field_type() {
+        FieldType::Str(ref text_options) if text_options.is_fast() => {
+            let indexing_options = text_options.get_indexing_options();
+            if !indexing_options.is_indexed() || indexing_options.is_tokenized() {
+                return Err(SchemaError::InvalidFastField(field_entry.name().clone()));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_same_field_type(left: &FieldType, right: &FieldType) -> bool {
+    match (left, right) {
+        (&FieldType::Str(_), &FieldType::Str(_)) |
+        (&FieldType::U32(_), &FieldType::U32(_)) |
+        (&FieldType::I64(_), &FieldType::I64(_)) |
+        (&FieldType::F64(_), &FieldType::F64(_)) |
+        (&FieldType::Date(_), &FieldType::Date(_)) |
+        (&FieldType::Bytes(_), &FieldType::Bytes(_)) |
+        (&FieldType::HierarchicalFacet(_), &FieldType::HierarchicalFacet(_)) |
+        (&FieldType::Bool(_), &FieldType::Bool(_)) |
+        (&FieldType::Ip(_), &FieldType::Ip(_)) => true,
+        _ => false,
     }
 }
 
@@ -106,7 +311,6 @@ impl Default for SchemaBuilder {
     fn default() -> SchemaBuilder {
         SchemaBuilder {
             fields: Vec::new(),
-            fields_map: HashMap::new(),
         }
     }
 }
@@ -136,7 +340,7 @@ struct InnerSchema {
 /// let id_field = schema_builder.add_text_field("id", STRING);
 /// let title_field = schema_builder.add_text_field("title", TEXT);
 /// let body_field = schema_builder.add_text_field("body", TEXT);
-/// let schema = schema_builder.build();
+/// let schema = schema_builder.build().unwrap();
 ///
 /// ```
 #[derive(Clone)]
@@ -159,19 +363,58 @@ impl Schema {
         &self.0.fields
     }
     
-    /// Returns the field options associated with a given name.
+    /// Returns an iterator over the `(Field, &FieldEntry)` pairs
+    /// of the schema, in the order in which the fields were added.
+    pub fn field_entries(&self,) -> FieldEntriesIter {
+        FieldEntriesIter {
+            field_entries: self.0.fields.iter().enumerate(),
+        }
+    }
+    
+    /// Returns the field associated with a given name.
     ///
-    /// # Panics
-    /// Panics if the field name does not exist.
-    /// It is meant as an helper for user who created
-    /// and control the content of their schema.
+    /// Returns `None` if the field name does not exist.
     ///
-    /// If panicking is not an option for you,
-    /// you may use `get(&self, field_name: &str)`.
+    /// The lookup relies on a map built when the schema is
+    /// created, and does not depend on the number of fields.
     pub fn get_field(&self, field_name: &str) -> Option<Field> {
         self.0.fields_map.get(field_name).cloned()
     }
     
+    /// Checks that the schema is an extension of the `previous` schema.
+    ///
+    /// A schema extends another one if it starts with exactly
+    /// the same fields, in the same order. New fields may only
+    /// be appended.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first field of the `previous`
+    /// schema that was removed, moved, or whose type or options changed.
+    pub fn check_extends(&self, previous: &Schema) -> Result<(), SchemaError> {
+        for (field_id, previous_entry) in previous.fields().iter().enumerate() {
+            let field_name = previous_entry.name();
+            let field_entry = match self.get_field(field_name) {
+                Some(Field(new_field_id)) if new_field_id as usize == field_id => {
+                    self.get_field_entry(Field(new_field_id))
+                }
+                Some(_) => {
+                    return Err(SchemaError::FieldMoved(field_name.clone()));
+                }
+                None => {
+                    return Err(SchemaError::FieldRemoved(field_name.clone()));
+                }
+            };
+            if !is_same_field_type(previous_entry.field_type(), field_entry.field_type()) {
+                return Err(SchemaError::FieldTypeChanged(field_name.clone()));
+            }
+            if previous_entry != field_entry {
+                return Err(SchemaError::FieldOptionsChanged(field_name.clone()));
+            }
+        }
+        Ok(())
+    }
+    
     /// Create a named document off the doc.
     pub fn to_named_doc(&self, doc: &Document) -> NamedFieldDocument {
         let mut field_map = BTreeMap::new();
@@ -188,14 +431,63 @@ impl Schema {
     }
     
     
-    /// Encode the schema in JSON. 
+    /// Encode the document in JSON. 
     ///
     /// Encoding a document cannot fail.
     pub fn to_json(&self, doc: &Document) -> String {
+        self.to_named_doc_json(doc)
+    }
+
+    /// Encodes a document as a JSON object, mapping
+    /// the field names to the array of their values.
+    ///
+    /// The result can be parsed back with `.parse_document(...)`.
+    /// Bytes are encoded in base64, dates as a number of milliseconds
+    /// since the epoch, and IPv4-mapped addresses as IPv4 addresses.
+    pub fn to_named_doc_json(&self, doc: &Document) -> String {
         json::encode(&self.to_named_doc(doc)).unwrap()
     }
 
+    /// Encode the schema itself in JSON.
+    ///
+    /// This is the representation stored in the `meta.json` file,
+    /// and it can be read back using `Schema::from_schema_json`.
+    pub fn to_schema_json(&self,) -> String {
+        json::encode(self).expect("Encoding a schema cannot fail")
+    }
+
+    /// Decode a schema from its JSON representation.
+    ///
+    /// Keys that are unknown to this version of tantivy, typically 
+    /// options introduced by a newer version, are ignored
+    /// rather than failing the deserialization. 
+    /// Their paths (e.g. `title.options.future_option`) are returned 
+    /// alongside the schema.
+    pub fn from_schema_json(schema_json: &str) -> json::DecodeResult<(Schema, Vec<String>)> {
+        let json_node = try!(Json::from_str(schema_json).map_err(json::DecoderError::ParseError));
+        let schema: Schema = try!(Decodable::decode(&mut json::Decoder::new(json_node.clone())));
+        let known_json_node = Json::from_str(&schema.to_schema_json())
+            .expect("The schema JSON should always be valid");
+        let mut unknown_keys = Vec::new();
+        if let (Some(field_nodes), Some(known_field_nodes)) = (json_node.as_array(), known_json_node.as_array()) {
+            for (field_node, known_field_node) in field_nodes.iter().zip(known_field_nodes) {
+                let field_name = field_node
+                    .find("name")
+                    .and_then(Json::as_string)
+                    .unwrap_or("");
+                collect_unknown_keys(field_name, field_node, known_field_node, &mut unknown_keys);
+            }
+        }
+        Ok((schema, unknown_keys))
+    }
+
     /// Build a document object from a json-object. 
+    ///
+    /// Each key of the object is the name of a field, and
+    /// multivalued fields are given as an array of values.
+    /// Values are cast to the type of their field when possible
+    /// (e.g. a JSON number for a f64 field), and a
+    /// `DocParsingError::TypeMismatch` is returned otherwise.
     pub fn parse_document(&self, doc_json: &str) -> Result<Document, DocParsingError> {
         let json_node = try!(Json::from_str(doc_json));
         let some_json_obj = json_node.as_object();
@@ -214,28 +506,34 @@ impl Schema {
         for (field_name, json_value) in json_obj.iter() {
             match self.get_field(field_name) {
                 Some(field) => {
-                    let field_entry = self.get_field_entry(field);
-                    let field_type = field_entry.field_type();
+                    let field_type = self.get_field_entry(field).field_type();
+                    let parse_value = |json_item: &Json| {
+                        field_type
+                            .value_from_json(json_item)
+                            .map_err(|e| {
+                                match e {
+                                    ValueParsingError::TypeError(_) => {
+                                        DocParsingError::TypeMismatch {
+                                            field: field_name.clone(),
+                                            expected: field_type.type_name(),
+                                            got: json_item.to_string(),
+                                        }
+                                    }
+                                    _ => DocParsingError::ValueError(field_name.clone(), e),
+                                }
+                            })
+                    };
                     match *json_value {
                         Json::Array(ref json_items) => {
                             for json_item in json_items {
-                                let value = try!(
-                                    field_type
-                                     .value_from_json(&json_item)
-                                     .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))
-                                );
+                                let value = try!(parse_value(json_item));
                                 doc.add(FieldValue::new(field, value));
                             }
                         }
                         _ => {
-                            let value = try!(
-                                field_type
-                                 .value_from_json(&json_value)
-                                 .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))
-                            );
+                            let value = try!(parse_value(json_value));
                             doc.add(FieldValue::new(field, value));
                         }
-                        
                     }
                 }
                 None => {
@@ -247,12 +545,51 @@ impl Schema {
     }
 }
 
+/// Iterator over the `(Field, &FieldEntry)` pairs of a `Schema`.
+///
+/// See `Schema::field_entries`.
+pub struct FieldEntriesIter<'a> {
+    field_entries: Enumerate<slice::Iter<'a, FieldEntry>>,
+}
+
+impl<'a> Iterator for FieldEntriesIter<'a> {
+    type Item = (Field, &'a FieldEntry);
+
+    fn next(&mut self,) -> Option<(Field, &'a FieldEntry)> {
+        self.field_entries
+            .next()
+            .map(|(field_id, field_entry)| (Field(field_id as u8), field_entry))
+    }
+
+    fn size_hint(&self,) -> (usize, Option<usize>) {
+        self.field_entries.size_hint()
+    }
+}
+
 impl fmt::Debug for Schema {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         self.0.fmt(f)
     }
 }
 
+/// Appends to `unknown_keys` the path of the keys of `json_node`
+/// that are not in `known_json_node`.
+fn collect_unknown_keys(path: &str, json_node: &Json, known_json_node: &Json, unknown_keys: &mut Vec<String>) {
+    if let (Some(json_obj), Some(known_json_obj)) = (json_node.as_object(), known_json_node.as_object()) {
+        for (key, child_node) in json_obj.iter() {
+            let child_path = format!("{}.{}", path, key);
+            match known_json_obj.get(key) {
+                Some(known_child_node) => {
+                    collect_unknown_keys(&child_path, child_node, known_child_node, unknown_keys);
+                }
+                None => {
+                    unknown_keys.push(child_path);
+                }
+            }
+        }
+    }
+}
+
 impl Decodable for Schema {
     fn decode<D: Decoder>(d: &mut D) -> Result  <Self, D::Error> {
         let mut schema_builder = SchemaBuilder::default();
@@ -263,7 +600,9 @@ impl Decodable for Schema {
             }
             Ok(())
         }));
-        Ok(schema_builder.build())
+        schema_builder
+            .build()
+            .map_err(|schema_error| d.error(&format!("Invalid schema: {:?}", schema_error)))
     }
 }
 
@@ -281,16 +620,35 @@ impl Encodable for Schema {
 }
 
 
-impl From<SchemaBuilder> for Schema {
-    fn from(schema_builder: SchemaBuilder) -> Schema {
-        schema_builder.build()
-    }
+/// Error that may happen when building a `Schema`,
+/// or when checking that a `Schema` extends another one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    /// Two fields share the same name.
+    DuplicateField(String),
+    /// A field name is empty.
+    EmptyFieldName,
+    /// The field name contains a character that is not allowed (`:`).
+    InvalidFieldName(String),
+    /// The values of the field are copied to a field that is not
+    /// an indexed text field declared before it, or that is itself copied.
+    InvalidCopyTo(String),
+    /// The text field is fast, but it is not untokenized.
+    InvalidFastField(String),
+    /// A field of the existing schema is missing from the new schema.
+    FieldRemoved(String),
+    /// A field of the existing schema does not have the same
+    /// position in the new schema.
+    FieldMoved(String),
+    /// A field of the existing schema has a different type
+    /// in the new schema.
+    FieldTypeChanged(String),
+    /// A field of the existing schema has different options
+    /// in the new schema.
+    FieldOptionsChanged(String),
 }
 
 
-
-
-
 /// Error that may happen when deserializing 
 /// a document from JSON.
 #[derive(Debug)]
@@ -301,6 +659,16 @@ pub enum DocParsingError {
     NotJSONObject(String),
     /// One of the value node could not be parsed.
     ValueError(String, ValueParsingError),
+    /// One of the value node does not match the type of its field.
+    /// (e.g. `"abc"` for a u32 field)
+    TypeMismatch {
+        /// Name of the field
+        field: String,
+        /// Type of the field, as it appears in the schema.
+        expected: &'static str,
+        /// The JSON value that was received.
+        got: String,
+    },
     /// The json-document contains a field that is not declared in the schema. 
     NoSuchFieldInSchema(String),
 }
@@ -327,7 +695,7 @@ mod tests {
         schema_builder.add_text_field("title", TEXT);
         schema_builder.add_text_field("author", STRING);
         schema_builder.add_u32_field("count", count_options);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let schema_json: String = format!("{}", json::as_pretty_json(&schema));
         println!("{}", schema_json);
         let expected = r#"[
@@ -363,6 +731,59 @@ mod tests {
 
 
     
+    #[test]
+    pub fn test_schema_json_roundtrip() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("title", TEXT.set_fieldnorms(false));
+        schema_builder.add_text_field("author", STRING | STORED);
+        schema_builder.add_u32_field("count", U32Options::default().set_stored().set_fast());
+        schema_builder.add_u32_field("tags", U32Options::default().set_fast().set_cardinality(Cardinality::MultiValues));
+        schema_builder.add_i64_field("balance", I64Options::default().set_indexed());
+        schema_builder.add_f64_field("price", F64Options::default().set_fast());
+        schema_builder.add_date_field("timestamp", DateOptions::default().set_stored().set_precision(DatePrecision::Seconds));
+        schema_builder.add_bytes_field("thumbnail", BytesOptions::default().set_fast());
+        schema_builder.add_facet_field("category", FacetOptions::default().set_indexed());
+        schema_builder.add_bool_field("is_published", BoolOptions::default().set_indexed().set_fast());
+        schema_builder.add_ip_field("src_ip", IpOptions::default().set_indexed().set_stored());
+        let schema = schema_builder.build().unwrap();
+        let schema_json = schema.to_schema_json();
+        let (deserialized_schema, unknown_keys) = Schema::from_schema_json(&schema_json).unwrap();
+        assert!(unknown_keys.is_empty());
+        assert_eq!(deserialized_schema.fields(), schema.fields());
+        assert_eq!(deserialized_schema.to_schema_json(), schema_json);
+    }
+
+    #[test]
+    pub fn test_schema_json_unknown_options() {
+        let schema_json = r#"[
+            {
+                "name": "title",
+                "type": "text",
+                "options": {
+                    "indexing": "position",
+                    "stored": false,
+                    "future_option": true
+                }
+            },
+            {
+                "name": "count",
+                "type": "u32",
+                "future_field_option": 3,
+                "options": {
+                    "indexed": false,
+                    "fast": true,
+                    "stored": true
+                }
+            }
+        ]"#;
+        let (schema, unknown_keys) = Schema::from_schema_json(schema_json).unwrap();
+        assert_eq!(unknown_keys, vec!(String::from("title.options.future_option"), String::from("count.future_field_option")));
+        let title_field = schema.get_field("title").unwrap();
+        assert_eq!(schema.get_field_entry(title_field).field_type(), &FieldType::Str(TEXT));
+        assert!(Schema::from_schema_json("[{\"name\": \"title\"}]").is_err());
+        assert!(Schema::from_schema_json("not json").is_err());
+    }
+
     #[test]
     pub fn test_document_to_json() {
         let mut schema_builder = SchemaBuilder::default();
@@ -370,7 +791,7 @@ mod tests {
         schema_builder.add_text_field("title", TEXT);
         schema_builder.add_text_field("author", STRING);
         schema_builder.add_u32_field("count", count_options);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         let doc_json = r#"{
                 "title": "my title",
                 "author": "fulmicoton",
@@ -381,6 +802,60 @@ mod tests {
         assert_eq!(doc, doc_serdeser);
     }
     
+    #[test]
+    pub fn test_document_json_roundtrip_all_types() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u32_field("count", U32Options::default().set_stored());
+        schema_builder.add_i64_field("delta", I64Options::default().set_stored());
+        schema_builder.add_f64_field("price", F64Options::default().set_stored());
+        schema_builder.add_date_field("timestamp", DateOptions::default().set_stored());
+        schema_builder.add_bytes_field("payload", BytesOptions::default().set_stored());
+        schema_builder.add_facet_field("category", FacetOptions::default().set_stored());
+        schema_builder.add_bool_field("is_published", BoolOptions::default().set_stored());
+        schema_builder.add_ip_field("src_ip", IpOptions::default().set_stored());
+        let schema = schema_builder.build().unwrap();
+        let doc_json = r#"{
+            "title": ["my title", "my subtitle"],
+            "count": 4,
+            "delta": -3,
+            "price": 3.5,
+            "timestamp": 1500000000000,
+            "payload": "AQID",
+            "category": "/electronics/phones",
+            "is_published": true,
+            "src_ip": ["10.0.0.1", "2001:db8::1"]
+        }"#;
+        let doc = schema.parse_document(doc_json).unwrap();
+        assert_eq!(doc.field_values().len(), 11);
+        let named_doc_json = schema.to_named_doc_json(&doc);
+        assert_eq!(
+            named_doc_json,
+            concat!(
+                r#"{"category":["/electronics/phones"],"count":[4],"delta":[-3],"is_published":[true],"#,
+                r#""payload":["AQID"],"price":[3.5],"src_ip":["10.0.0.1","2001:db8::1"],"#,
+                r#""timestamp":[1500000000000],"title":["my title","my subtitle"]}"#));
+        let doc_serdeser = schema.parse_document(&named_doc_json).unwrap();
+        assert_eq!(doc, doc_serdeser);
+        {
+            let date_doc = schema.parse_document(r#"{"timestamp": "2017-07-14T02:40:00Z"}"#).unwrap();
+            assert_eq!(schema.to_named_doc_json(&date_doc), r#"{"timestamp":[1500000000000]}"#);
+        }
+        {
+            let json_err = schema.parse_document(r#"{"is_published": [true, 1]}"#);
+            match json_err {
+                Err(DocParsingError::TypeMismatch { field, expected, got }) => {
+                    assert_eq!(field, "is_published");
+                    assert_eq!(expected, "bool");
+                    assert_eq!(got, "1");
+                }
+                _ => {
+                    assert!(false);
+                }
+            }
+        }
+    }
+
     #[test]
     pub fn test_parse_document() {
         let mut schema_builder = SchemaBuilder::default();
@@ -388,7 +863,7 @@ mod tests {
         let title_field = schema_builder.add_text_field("title", TEXT);
         let author_field = schema_builder.add_text_field("author", STRING);
         let count_field = schema_builder.add_u32_field("count", count_options);
-        let schema = schema_builder.build();
+        let schema = schema_builder.build().unwrap();
         {
             let doc = schema.parse_document("{}").unwrap();
             assert!(doc.field_values().is_empty());
@@ -442,8 +917,10 @@ mod tests {
                 "jambon": "bayonne" 
             }"#);
             match json_err {
-                Err(DocParsingError::ValueError(_, ValueParsingError::TypeError(_))) => {
-                    assert!(true);
+                Err(DocParsingError::TypeMismatch { field, expected, got }) => {
+                    assert_eq!(field, "count");
+                    assert_eq!(expected, "u32");
+                    assert_eq!(got, r#""5""#);
                 }
                 _ => {
                     assert!(false);
@@ -483,4 +960,154 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    pub fn test_schema_field_entries() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let count_field = schema_builder.add_u32_field("count", FAST);
+        let schema = schema_builder.build().unwrap();
+        let fields: Vec<(Field, &str)> = schema
+            .field_entries()
+            .map(|(field, field_entry)| (field, field_entry.name().as_str()))
+            .collect();
+        assert_eq!(fields, vec!((title_field, "title"), (count_field, "count")));
+        assert_eq!(schema.get_field("count"), Some(count_field));
+        assert_eq!(schema.get_field("body"), None);
+    }
+
+    #[test]
+    pub fn test_schema_duplicate_field() {
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT);
+            schema_builder.add_u32_field("title", FAST);
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::DuplicateField(String::from("title")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            let title_field = schema_builder.add_text_field("title", TEXT);
+            let count_field = schema_builder.add_u32_field("count", FAST);
+            let schema = schema_builder.build().unwrap();
+            assert_eq!(schema.get_field("title"), Some(title_field));
+            assert_eq!(schema.get_field("count"), Some(count_field));
+        }
+    }
+
+    #[test]
+    pub fn test_schema_invalid_field_name() {
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("", TEXT);
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::EmptyFieldName);
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title:en", TEXT);
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidFieldName(String::from("title:en")));
+        }
+    }
+
+    #[test]
+    pub fn test_schema_check_extends() {
+        let previous_schema = {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT);
+            schema_builder.add_u32_field("count", FAST);
+            schema_builder.build().unwrap()
+        };
+        assert!(previous_schema.check_extends(&previous_schema).is_ok());
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT);
+            schema_builder.add_u32_field("count", FAST);
+            schema_builder.add_text_field("body", TEXT);
+            let schema = schema_builder.build().unwrap();
+            assert!(schema.check_extends(&previous_schema).is_ok());
+            assert_eq!(previous_schema.check_extends(&schema).unwrap_err(), SchemaError::FieldRemoved(String::from("body")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_u32_field("count", FAST);
+            schema_builder.add_text_field("title", TEXT);
+            let schema = schema_builder.build().unwrap();
+            assert_eq!(schema.check_extends(&previous_schema).unwrap_err(), SchemaError::FieldMoved(String::from("title")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT);
+            schema_builder.add_i64_field("count", I64Options::default().set_fast());
+            let schema = schema_builder.build().unwrap();
+            assert_eq!(schema.check_extends(&previous_schema).unwrap_err(), SchemaError::FieldTypeChanged(String::from("count")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", STRING);
+            schema_builder.add_u32_field("count", FAST);
+            let schema = schema_builder.build().unwrap();
+            assert_eq!(schema.check_extends(&previous_schema).unwrap_err(), SchemaError::FieldOptionsChanged(String::from("title")));
+        }
+    }
+
+    #[test]
+    pub fn test_schema_invalid_copy_to() {
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            let all_field = schema_builder.add_text_field("all", TEXT);
+            schema_builder.add_text_field("title", TEXT.set_copy_to(all_field));
+            assert!(schema_builder.build().is_ok());
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            let all_field = schema_builder.add_text_field("all", STORED);
+            schema_builder.add_text_field("title", TEXT.set_copy_to(all_field));
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidCopyTo(String::from("title")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT.set_copy_to(Field(1)));
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidCopyTo(String::from("title")));
+        }
+        {
+            // the destination is declared after the field.
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT.set_copy_to(Field(1)));
+            schema_builder.add_text_field("all", TEXT);
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidCopyTo(String::from("title")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT.set_copy_to(Field(0)));
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidCopyTo(String::from("title")));
+        }
+    }
+
+    #[test]
+    pub fn test_schema_invalid_fast_text_field() {
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("country", STRING.set_fast());
+            schema_builder.add_text_field("tag", STRING.set_fast().set_indexing_options(TextIndexingOptions::Raw));
+            assert!(schema_builder.build().is_ok());
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", TEXT.set_fast());
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidFastField(String::from("title")));
+        }
+        {
+            let mut schema_builder = SchemaBuilder::default();
+            schema_builder.add_text_field("title", STORED.set_fast());
+            assert_eq!(schema_builder.build().unwrap_err(), SchemaError::InvalidFastField(String::from("title")));
+        }
+    }
+
+    #[test]
+    pub fn test_schema_deserialization_duplicate_field() {
+        let schema_json = r#"[
+            {"name": "title", "type": "text", "options": {"indexing": "position", "stored": false}},
+            {"name": "title", "type": "text", "options": {"indexing": "untokenized", "stored": false}}
+        ]"#;
+        assert!(json::decode::<Schema>(schema_json).is_err());
+    }
 }
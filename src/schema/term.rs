@@ -1,9 +1,21 @@
 use std::fmt;
+use std::net::Ipv6Addr;
 
 use common::BinarySerializable;
+use common::i64_to_u64;
+use common::f64_to_u64;
+use byteorder::{BigEndian, WriteBytesExt};
 use super::Field;
+use super::Facet;
 
 
+/// Version of the encoding of the terms written by
+/// the `SegmentWriter` and the `IndexMerger`.
+///
+/// Version 1 encodes the u32 values in big-endian, see
+/// `Term::from_field_u32`, rather than in little-endian.
+pub const TERMS_FORMAT_VERSION: u32 = 1;
+
 
 /// Term represents the value that the token can take.
 ///
@@ -40,12 +52,81 @@ impl Term {
     ///
     /// Assuming the term has a field id of 1, and a u32 value of 3234,
     /// the Term will have 5 bytes.
-    /// The first byte is `1`, and the 4 following bytes are that of the u32.
+    /// The first byte is `1`, and the 4 following bytes are that of the u32,
+    /// in big-endian, so that the lexicographical order of the terms
+    /// is the order of the values.
     pub fn from_field_u32(field: Field, val: u32) -> Term {
         let mut buffer = Vec::with_capacity(1 + 4);
         buffer.clear();
         field.serialize(&mut buffer).unwrap();
-        val.serialize(&mut buffer).unwrap();
+        buffer.write_u32::<BigEndian>(val).unwrap();
+        Term(buffer)
+    }
+    
+    /// Builds a term given a field, and an i64-value
+    ///
+    /// The value is mapped to a `u64` by flipping its sign bit,
+    /// and written in big-endian, so that the lexicographical order of
+    /// the terms matches the numerical order of the values.
+    ///
+    /// The Term will have 9 bytes : the field id and the 8 bytes of the
+    /// mapped value.
+    pub fn from_field_i64(field: Field, val: i64) -> Term {
+        let mut buffer = Vec::with_capacity(1 + 8);
+        field.serialize(&mut buffer).unwrap();
+        buffer.write_u64::<BigEndian>(i64_to_u64(val)).unwrap();
+        Term(buffer)
+    }
+    
+    /// Builds a term given a field, and a date expressed in
+    /// milliseconds since Epoch.
+    ///
+    /// Date terms are encoded exactly like i64 terms.
+    /// Truncation to the precision of the field is the responsability
+    /// of the caller.
+    pub fn from_field_date(field: Field, millis: i64) -> Term {
+        Term::from_field_i64(field, millis)
+    }
+
+    /// Builds a term given a field, and a bool-value
+    ///
+    /// The Term will have 2 bytes : the field id and
+    /// `0` for `false` or `1` for `true`. A bool field therefore 
+    /// has at most two terms.
+    pub fn from_field_bool(field: Field, val: bool) -> Term {
+        let mut buffer = Vec::with_capacity(1 + 1);
+        field.serialize(&mut buffer).unwrap();
+        buffer.push(val as u8);
+        Term(buffer)
+    }
+
+    /// Builds a term given a field, and an IP address
+    ///
+    /// The Term will have 17 bytes : the field id, followed by
+    /// the 16 bytes of the IPv6 form of the address (see `schema::ip_to_ipv6`),
+    /// in network order.
+    /// The lexicographical order of the terms is therefore
+    /// the order of the addresses, and a CIDR block is a range of terms.
+    pub fn from_field_ip(field: Field, ip: Ipv6Addr) -> Term {
+        let mut buffer = Vec::with_capacity(1 + 16);
+        field.serialize(&mut buffer).unwrap();
+        buffer.extend(&ip.octets());
+        Term(buffer)
+    }
+
+    /// Builds a term given a field, and an f64-value
+    ///
+    /// The value is mapped to a monotonic `u64` (see `common::f64_to_u64`),
+    /// and written in big-endian, so that the lexicographical order of
+    /// the terms matches the numerical order of the values.
+    /// `-0.0` and `0.0` give the same term.
+    ///
+    /// The Term will have 9 bytes : the field id and the 8 bytes of the
+    /// mapped value.
+    pub fn from_field_f64(field: Field, val: f64) -> Term {
+        let mut buffer = Vec::with_capacity(1 + 8);
+        field.serialize(&mut buffer).unwrap();
+        buffer.write_u64::<BigEndian>(f64_to_u64(val)).unwrap();
         Term(buffer)
     }
     
@@ -55,6 +136,11 @@ impl Term {
     /// the Term will have 4 bytes.
     /// The first byte is 2, and the three following bytes are the utf-8 
     /// representation of "abc".
+    ///
+    /// The text is used byte-for-byte, without any normalization,
+    /// which matches the terms of the `Untokenized` fields.
+    /// The terms of the `UntokenizedLowercase` fields are built with
+    /// `TextIndexingOptions::untokenized_term`.
     pub fn from_field_text(field: Field, text: &str) -> Term {
         let mut buffer = Vec::with_capacity(1 + text.len());
         buffer.clear();
@@ -63,6 +149,20 @@ impl Term {
         Term(buffer)
     }
     
+    /// Builds a term given a field, and a facet.
+    ///
+    /// The segments of the facet are separated by a `\0` byte, which
+    /// sorts before any other byte. The terms of the children of a facet
+    /// therefore directly follow the term of the facet in the term dictionary,
+    /// and all of the descendants of `/a` are in the range `[/a\0, /a\1)`.
+    pub fn from_facet(field: Field, facet: &Facet) -> Term {
+        let facet_bytes = facet.encoded_bytes();
+        let mut buffer = Vec::with_capacity(1 + facet_bytes.len());
+        field.serialize(&mut buffer).unwrap();
+        buffer.extend(facet_bytes);
+        Term(buffer)
+    }
+    
     /// Set the texts only, keeping the field untouched. 
     pub fn set_text(&mut self, text: &str) {
         self.0.resize(1, 0u8);
@@ -73,6 +173,12 @@ impl Term {
     pub fn as_slice(&self,)->&[u8] {
         &self.0
     }
+
+    /// Returns the bytes of the value of the term,
+    /// that is the underlying bytes without the field.
+    pub fn value_bytes(&self,) -> &[u8] {
+        &self.0[1..]
+    }
 }
 
 impl<'a> From<&'a [u8]> for Term {
@@ -117,8 +223,87 @@ mod tests {
             assert_eq!(term.field(), count_field);
             assert_eq!(term.as_slice()[0], 2u8);
             assert_eq!(term.as_slice().len(), 5);
-            assert_eq!(term.as_slice()[1], (983u32 % 256u32) as u8);            
+            // big-endian since `TERMS_FORMAT_VERSION` 1.
+            assert_eq!(&term.as_slice()[1..], &[0u8, 0u8, (983u32 / 256u32) as u8, (983u32 % 256u32) as u8]);
         }
                 
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn test_term_u32_order() {
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_u32_field("val", U32Options::default());
+        let vals = [0u32, 1u32, 255u32, 256u32, 1_000_000u32, u32::max_value()];
+        let terms: Vec<Term> = vals.iter()
+            .map(|&val| Term::from_field_u32(field, val))
+            .collect();
+        for (left, right) in terms.iter().zip(terms.iter().skip(1)) {
+            assert!(left < right);
+        }
+    }
+
+    #[test]
+    pub fn test_term_i64_order() {
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_i64_field("val", I64Options::default());
+        let vals = [i64::min_value(), -1_000_000i64, -1i64, 0i64, 1i64, 256i64, i64::max_value()];
+        let terms: Vec<Term> = vals.iter()
+            .map(|&val| Term::from_field_i64(field, val))
+            .collect();
+        for term in &terms {
+            assert_eq!(term.field(), field);
+            assert_eq!(term.as_slice().len(), 9);
+        }
+        for (left, right) in terms.iter().zip(terms.iter().skip(1)) {
+            assert!(left < right);
+        }
+    }
+
+    #[test]
+    pub fn test_term_f64_order() {
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_f64_field("val", F64Options::default());
+        let vals = [-1e30f64, -2.5f64, -0.5f64, 0f64, 1e-10f64, 0.5f64, 2.5f64, 1e30f64];
+        let terms: Vec<Term> = vals.iter()
+            .map(|&val| Term::from_field_f64(field, val))
+            .collect();
+        for (left, right) in terms.iter().zip(terms.iter().skip(1)) {
+            assert!(left < right);
+        }
+        assert_eq!(Term::from_field_f64(field, -0.0f64), Term::from_field_f64(field, 0f64));
+    }
+
+    #[test]
+    pub fn test_term_facet_order() {
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_facet_field("category", FacetOptions::default());
+        let mut terms: Vec<Term> = vec!("/electronics/phones", "/electronicsx", "/electronics", "/electronics/phones/android", "/electronics/tv")
+            .into_iter()
+            .map(|facet_text| Term::from_facet(field, &Facet::from_text(facet_text)))
+            .collect();
+        terms.sort();
+        let expected_terms: Vec<Term> = vec!("/electronics", "/electronics/phones", "/electronics/phones/android", "/electronics/tv", "/electronicsx")
+            .into_iter()
+            .map(|facet_text| Term::from_facet(field, &Facet::from_text(facet_text)))
+            .collect();
+        assert_eq!(terms, expected_terms);
+        assert_eq!(&terms[1].as_slice()[1..], b"electronics\0phones");
+    }
+
+    #[test]
+    pub fn test_term_ip_order() {
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_ip_field("src_ip", IpOptions::default());
+        let ips = ["::", "::1", "9.255.255.255", "10.0.0.0", "10.0.0.1", "10.1.0.0", "255.255.255.255", "fe80::1"];
+        let terms: Vec<Term> = ips.iter()
+            .map(|ip_text| Term::from_field_ip(field, parse_ip(ip_text).unwrap()))
+            .collect();
+        for term in &terms {
+            assert_eq!(term.field(), field);
+            assert_eq!(term.as_slice().len(), 17);
+        }
+        for (left, right) in terms.iter().zip(terms.iter().skip(1)) {
+            assert!(left < right);
+        }
+    }
+}
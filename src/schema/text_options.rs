@@ -1,15 +1,27 @@
 use std::ops::BitOr;
+use std::cmp::{max, min};
+use std::f32;
 use rustc_serialize::Decodable;
 use rustc_serialize::Decoder;
 use rustc_serialize::Encodable;
 use rustc_serialize::Encoder;
+use schema::Field;
+use schema::Term;
+use analyzer::DEFAULT_TOKENIZER;
 
 
 /// Define how a text field should be handled by tantivy.
-#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+#[derive(Clone,Debug)]
 pub struct TextOptions {
     indexing: TextIndexingOptions,
     stored: bool,
+    fieldnorms: bool,
+    boost: f32,
+    copy_to: Option<Field>,
+    position_gap: u32,
+    tokenizer: Option<String>,
+    max_positions_per_doc: Option<u32>,
+    fast: bool,
 }
 
 impl TextOptions {
@@ -36,6 +48,155 @@ impl TextOptions {
         self
     }
 
+    /// Returns true iff the field norms (the number of tokens
+    /// in the field) are recorded for this field.
+    ///
+    /// Field norms only make sense for indexed fields.
+    pub fn has_fieldnorms(&self,) -> bool {
+        self.fieldnorms && self.indexing.is_indexed()
+    }
+
+    /// Enables or disables the field norms. They are enabled by default.
+    ///
+    /// For short identifier-like fields, length normalization
+    /// does not carry any information. Disabling the field norms
+    /// saves their space, and the scorer then uses a field norm of 1.
+    pub fn set_fieldnorms(mut self, fieldnorms: bool) -> TextOptions {
+        self.fieldnorms = fieldnorms;
+        self
+    }
+
+    /// Returns the boost of the field.
+    pub fn get_boost(&self,) -> f32 {
+        self.boost
+    }
+
+    /// Sets the boost of the field. The default boost is 1.
+    ///
+    /// The score contribution of the terms of this field
+    /// is multiplied by the boost, so that a match on a
+    /// field with a boost of 3 counts roughly 3 times more
+    /// than a match on a field with the default boost.
+    ///
+    /// # Panics
+    /// Panics if the boost is negative, infinite or NaN.
+    pub fn set_boost(mut self, boost: f32) -> TextOptions {
+        if !is_valid_boost(boost) {
+            panic!(format!("The boost of a field must be finite and positive, got {}", boost));
+        }
+        // -0 and 0 have different bit patterns.
+        self.boost = if boost == 0f32 { 0f32 } else { boost };
+        self
+    }
+
+    /// Returns the field the values of this field are copied to, if any.
+    pub fn get_copy_to(&self,) -> Option<Field> {
+        self.copy_to
+    }
+
+    /// Copies the values of this field into another text field,
+    /// typically a catch-all field used as the default search field.
+    ///
+    /// The values are tokenized and indexed in the destination field,
+    /// after its own values, as if they had been added to the document 
+    /// for this field. They are not stored a second time.
+    ///
+    /// Term frequencies and field norms of the destination field
+    /// account for all of the copied tokens, so that the destination
+    /// field scores like a single field concatenating all of the values.
+    ///
+    /// The destination field must be declared before this field,
+    /// and must be an indexed text field that is not itself 
+    /// copied to another field.
+    pub fn set_copy_to(mut self, field: Field) -> TextOptions {
+        self.copy_to = Some(field);
+        self
+    }
+
+    /// Returns the position gap inserted between two values of the field.
+    pub fn get_position_gap(&self,) -> u32 {
+        self.position_gap
+    }
+
+    /// Sets the position gap inserted between two consecutive values 
+    /// of the field, including the ones copied from other fields.
+    ///
+    /// The default gap is 1, so that a phrase query
+    /// cannot match across two values. Larger gaps keep
+    /// proximity queries from matching across values as well.
+    /// Positions saturate at `u32::max_value() - 1`.
+    pub fn set_position_gap(mut self, position_gap: u32) -> TextOptions {
+        self.position_gap = position_gap;
+        self
+    }
+
+    /// Returns the name of the tokenizer of the field.
+    ///
+    /// Fields that do not define a tokenizer use
+    /// the `default` tokenizer.
+    pub fn get_tokenizer(&self,) -> &str {
+        self.tokenizer
+            .as_ref()
+            .map(|tokenizer_name| tokenizer_name.as_str())
+            .unwrap_or(DEFAULT_TOKENIZER)
+    }
+
+    /// Sets the name of the tokenizer used to tokenize the field.
+    ///
+    /// The tokenizer is looked up in the `TokenizerManager` of
+    /// the index when a segment gets written, and when the
+    /// `QueryParser` is created with `QueryParser::for_index`.
+    /// It only applies to tokenized fields.
+    pub fn set_tokenizer(mut self, tokenizer_name: &str) -> TextOptions {
+        self.tokenizer = Some(String::from(tokenizer_name));
+        self
+    }
+
+    /// Returns the maximum number of tokens indexed per document
+    /// for this field, if any.
+    pub fn get_max_positions_per_doc(&self,) -> Option<u32> {
+        self.max_positions_per_doc
+    }
+
+    /// Caps the number of tokens indexed per document for this field,
+    /// over all of its values, including the copied ones.
+    ///
+    /// The tokens past the cap are dropped: they are not indexed
+    /// at all, and do not count in the field norm. Indexing them 
+    /// without their positions would leave term frequencies 
+    /// inconsistent with the positions, so this is not an option.
+    ///
+    /// The cap bounds the memory a single pathological document
+    /// can take in the indexing heap. The number of documents that 
+    /// got truncated is reported by `IndexWriter::commit`.
+    /// By default, the number of tokens is not capped.
+    pub fn set_max_positions_per_doc(mut self, max_positions_per_doc: u32) -> TextOptions {
+        self.max_positions_per_doc = Some(max_positions_per_doc);
+        self
+    }
+
+    /// Returns true iff the term ordinals of the values
+    /// are recorded in a fast field.
+    pub fn is_fast(&self,) -> bool {
+        self.fast
+    }
+
+    /// Records the ordinals of the terms of each document in a
+    /// multivalued fast field, so that the documents can be grouped,
+    /// or their values counted, without an external string mapping.
+    ///
+    /// The ordinal of a term is its rank among the terms of the field
+    /// in the term dictionary of the segment, so that it is only valid
+    /// within a segment, and the ordinals of a document are sorted
+    /// like its terms. See `SegmentReader::term_ords_fast_field`, and
+    /// `SegmentReader::str_fast_field` to sort the documents by their term.
+    ///
+    /// Only untokenized fields may be fast.
+    pub fn set_fast(mut self,) -> TextOptions {
+        self.fast = true;
+        self
+    }
+
 }
 
 impl Default for TextOptions {
@@ -43,10 +204,129 @@ impl Default for TextOptions {
         TextOptions {
             indexing: TextIndexingOptions::Unindexed,
             stored: false,
+            fieldnorms: true,
+            boost: 1f32,
+            copy_to: None,
+            position_gap: 1u32,
+            tokenizer: None,
+            max_positions_per_doc: None,
+            fast: false,
         }
     }
 }
 
+// `fieldnorms`, `boost`, `copy_to`, `position_gap`, `tokenizer`, `max_positions_per_doc`
+// and `fast` are only serialized when they differ from their default value, so that 
+// the schema of existing indexes stays unchanged.
+impl Encodable for TextOptions {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        let has_boost = self.boost != 1f32;
+        let has_position_gap = self.position_gap != 1u32;
+        let mut num_fields = 2;
+        if !self.fieldnorms {
+            num_fields += 1;
+        }
+        if has_boost {
+            num_fields += 1;
+        }
+        if self.copy_to.is_some() {
+            num_fields += 1;
+        }
+        if has_position_gap {
+            num_fields += 1;
+        }
+        if self.tokenizer.is_some() {
+            num_fields += 1;
+        }
+        if self.max_positions_per_doc.is_some() {
+            num_fields += 1;
+        }
+        if self.fast {
+            num_fields += 1;
+        }
+        s.emit_struct("TextOptions", num_fields, |s| {
+            try!(s.emit_struct_field("indexing", 0, |s| self.indexing.encode(s)));
+            try!(s.emit_struct_field("stored", 1, |s| self.stored.encode(s)));
+            if !self.fieldnorms {
+                try!(s.emit_struct_field("fieldnorms", 2, |s| self.fieldnorms.encode(s)));
+            }
+            if has_boost {
+                try!(s.emit_struct_field("boost", 3, |s| self.boost.encode(s)));
+            }
+            if let Some(Field(field_id)) = self.copy_to {
+                try!(s.emit_struct_field("copy_to", 4, |s| field_id.encode(s)));
+            }
+            if has_position_gap {
+                try!(s.emit_struct_field("position_gap", 5, |s| self.position_gap.encode(s)));
+            }
+            if let Some(ref tokenizer_name) = self.tokenizer {
+                try!(s.emit_struct_field("tokenizer", 6, |s| tokenizer_name.encode(s)));
+            }
+            if let Some(max_positions_per_doc) = self.max_positions_per_doc {
+                try!(s.emit_struct_field("max_positions_per_doc", 7, |s| max_positions_per_doc.encode(s)));
+            }
+            if self.fast {
+                try!(s.emit_struct_field("fast", 8, |s| self.fast.encode(s)));
+            }
+            Ok(())
+        })
+    }
+}
+
+// the boosts are compared on their bit pattern, so that
+// the comparison is an equivalence, as required by `Eq`.
+impl PartialEq for TextOptions {
+    fn eq(&self, other: &TextOptions) -> bool {
+        self.indexing == other.indexing &&
+        self.stored == other.stored &&
+        self.fieldnorms == other.fieldnorms &&
+        self.boost.to_bits() == other.boost.to_bits() &&
+        self.copy_to == other.copy_to &&
+        self.position_gap == other.position_gap &&
+        self.tokenizer == other.tokenizer &&
+        self.max_positions_per_doc == other.max_positions_per_doc &&
+        self.fast == other.fast
+    }
+}
+
+impl Eq for TextOptions {}
+
+fn is_valid_boost(boost: f32) -> bool {
+    boost.is_finite() && boost >= 0f32
+}
+
+impl Decodable for TextOptions {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Self, D::Error> {
+        d.read_struct("TextOptions", 9, |d| {
+            let indexing = try!(d.read_struct_field("indexing", 0, Decodable::decode));
+            let stored = try!(d.read_struct_field("stored", 1, Decodable::decode));
+            let fieldnorms: Option<bool> = try!(d.read_struct_field("fieldnorms", 2, Decodable::decode));
+            let boost: Option<f32> = try!(d.read_struct_field("boost", 3, Decodable::decode));
+            let copy_to: Option<u8> = try!(d.read_struct_field("copy_to", 4, Decodable::decode));
+            let position_gap: Option<u32> = try!(d.read_struct_field("position_gap", 5, Decodable::decode));
+            let tokenizer: Option<String> = try!(d.read_struct_field("tokenizer", 6, Decodable::decode));
+            let max_positions_per_doc: Option<u32> = try!(d.read_struct_field("max_positions_per_doc", 7, Decodable::decode));
+            let fast: Option<bool> = try!(d.read_struct_field("fast", 8, Decodable::decode));
+            if let Some(boost) = boost {
+                if !is_valid_boost(boost) {
+                    return Err(d.error(&format!("The boost of a field must be finite and positive, got {}", boost)));
+                }
+            }
+            Ok(TextOptions {
+                indexing: indexing,
+                stored: stored,
+                fieldnorms: fieldnorms.unwrap_or(true),
+                boost: boost.unwrap_or(1f32),
+                copy_to: copy_to.map(Field),
+                position_gap: position_gap.unwrap_or(1u32),
+                tokenizer: tokenizer,
+                max_positions_per_doc: max_positions_per_doc,
+                fast: fast.unwrap_or(false),
+            })
+        })
+    }
+}
+
 
 
 
@@ -61,7 +341,23 @@ pub enum TextIndexingOptions {
     ///
     /// It will **not** be searchable if the user enter "hello" for instance.
     /// This can be useful for tags, or ids for instance.   
+    ///
+    /// The value is indexed byte-for-byte, without any normalization:
+    /// the lookups are case-sensitive, and the query parser looks up
+    /// the values searched in the field as is.
     Untokenized,
+    /// Like `Untokenized`, but the value is lowercased before
+    /// being indexed, and so are the values searched in the field
+    /// by the query parser, so that the lookups are case-insensitive.
+    UntokenizedLowercase,
+    /// Raw fields are indexed exactly like untokenized fields : the whole
+    /// value is indexed as one single term, without any normalization.
+    ///
+    /// The query parser looks up the values searched in a raw field
+    /// as is, so that the case and the punctuation of identifiers
+    /// like `AB-123/X` are preserved.
+    /// Values containing punctuation need to be quoted in the query.
+    Raw,
     /// TokenizedNoFreq will tokenize the field value, and append the document doc id 
     /// to the posting lists associated to all of the tokens.
     /// The frequence of appearance of the term in the document however will be lost.
@@ -69,12 +365,24 @@ pub enum TextIndexingOptions {
     TokenizedNoFreq,
     /// TokenizedWithFreq will tokenize the field value, and encode
     /// both the docid and the term frequency in the posting lists associated to all
-    // of the tokens.
+    /// of the tokens.
+    ///
+    /// The positions of the terms are not recorded : the field can be
+    /// scored as accurately as with `TokenizedWithFreqAndPosition`, but
+    /// phrase queries will fail on it, and it does not take any
+    /// room in the positions file.
     TokenizedWithFreq,
     /// Like TokenizedWithFreq, but also encodes the positions of the 
     /// terms in a separate file. This option is required for phrase queries.
     /// Don't use this if you are certain you won't need it, the term positions file can be very big.
     TokenizedWithFreqAndPosition,
+    /// Like TokenizedWithFreqAndPosition, but also records a `u32` payload
+    /// along with each position of the terms, in a separate file.
+    ///
+    /// The payload of a position is the `payload` of the token, as set by
+    /// the tokenizer, e.g. to record that the term appeared in bold.
+    /// The tokens of pre-tokenized values have a payload of 0.
+    TokenizedWithFreqPositionAndPayload,
 }
 
 impl Encodable for TextIndexingOptions {
@@ -86,6 +394,12 @@ impl Encodable for TextIndexingOptions {
           TextIndexingOptions::Untokenized => {
               "untokenized"
           }
+          TextIndexingOptions::UntokenizedLowercase => {
+              "untokenized_lowercase"
+          }
+          TextIndexingOptions::Raw => {
+              "raw"
+          }
           TextIndexingOptions::TokenizedNoFreq => {
               "tokenize"
           }
@@ -95,6 +409,9 @@ impl Encodable for TextIndexingOptions {
           TextIndexingOptions::TokenizedWithFreqAndPosition => {
               "position"
           }
+          TextIndexingOptions::TokenizedWithFreqPositionAndPayload => {
+              "payload"
+          }
         };
         s.emit_str(name)
     }
@@ -107,9 +424,12 @@ impl Decodable for TextIndexingOptions {
         Ok(match option_name.as_ref() {
             "unindexed" => Unindexed,
             "untokenized" => Untokenized,
+            "untokenized_lowercase" => UntokenizedLowercase,
+            "raw" => Raw,
             "tokenize" => TokenizedNoFreq,
             "freq" => TokenizedWithFreq,
             "position" => TokenizedWithFreqAndPosition,
+            "payload" => TokenizedWithFreqPositionAndPayload,
             _ => {
                 return Err(d.error(&format!("Encoding option {:?} unknown", option_name)));
             }
@@ -123,7 +443,8 @@ impl TextIndexingOptions {
     pub fn is_termfreq_enabled(&self) -> bool {
         match *self {
             TextIndexingOptions::TokenizedWithFreq
-            | TextIndexingOptions::TokenizedWithFreqAndPosition => true,
+            | TextIndexingOptions::TokenizedWithFreqAndPosition
+            | TextIndexingOptions::TokenizedWithFreqPositionAndPayload => true,
             _ => false,
         }
     }
@@ -133,7 +454,8 @@ impl TextIndexingOptions {
         match *self {
             TextIndexingOptions::TokenizedNoFreq 
             | TextIndexingOptions::TokenizedWithFreq 
-            | TextIndexingOptions::TokenizedWithFreqAndPosition=> true,
+            | TextIndexingOptions::TokenizedWithFreqAndPosition
+            | TextIndexingOptions::TokenizedWithFreqPositionAndPayload => true,
             _ => false,
         }
     }
@@ -150,10 +472,30 @@ impl TextIndexingOptions {
     /// Returns true iff the term positions within the document are stored as well. 
     pub fn is_position_enabled(&self,) -> bool {
         match *self {
-            TextIndexingOptions::TokenizedWithFreqAndPosition => true,
+            TextIndexingOptions::TokenizedWithFreqAndPosition
+            | TextIndexingOptions::TokenizedWithFreqPositionAndPayload => true,
             _ => false,
         }
     }
+
+    /// Returns true iff a payload is stored along with each term position.
+    pub fn is_payload_enabled(&self,) -> bool {
+        *self == TextIndexingOptions::TokenizedWithFreqPositionAndPayload
+    }
+
+    /// Returns the term a value of an untokenized field is indexed as.
+    ///
+    /// The value is lowercased for `UntokenizedLowercase` fields,
+    /// and kept byte-for-byte for the other options. The indexer
+    /// and the query parser both build their terms with this method.
+    pub fn untokenized_term(&self, field: Field, text: &str) -> Term {
+        match *self {
+            TextIndexingOptions::UntokenizedLowercase => {
+                Term::from_field_text(field, &text.to_lowercase())
+            }
+            _ => Term::from_field_text(field, text),
+        }
+    }
 }
 
 
@@ -176,10 +518,19 @@ impl BitOr for TextIndexingOptions {
 }
 
 
-/// The field will be untokenized and indexed
+/// The field will be untokenized and indexed.
+///
+/// The value is indexed as is, so the lookups are case-sensitive.
 pub const STRING: TextOptions = TextOptions {
     indexing: TextIndexingOptions::Untokenized,
     stored: false,
+    fieldnorms: true,
+    boost: 1f32,
+    copy_to: None,
+    position_gap: 1u32,
+    tokenizer: None,
+    max_positions_per_doc: None,
+    fast: false,
 };
 
 
@@ -187,6 +538,13 @@ pub const STRING: TextOptions = TextOptions {
 pub const TEXT: TextOptions = TextOptions {
     indexing: TextIndexingOptions::TokenizedWithFreqAndPosition,
     stored: false,
+    fieldnorms: true,
+    boost: 1f32,
+    copy_to: None,
+    position_gap: 1u32,
+    tokenizer: None,
+    max_positions_per_doc: None,
+    fast: false,
 };
 
 /// A stored fields of a document can be retrieved given its `DocId`.
@@ -196,8 +554,23 @@ pub const TEXT: TextOptions = TextOptions {
 pub const STORED: TextOptions = TextOptions {
     indexing: TextIndexingOptions::Unindexed,
     stored: true,
+    fieldnorms: true,
+    boost: 1f32,
+    copy_to: None,
+    position_gap: 1u32,
+    tokenizer: None,
+    max_positions_per_doc: None,
+    fast: false,
 };
 
+/// The field will only be stored.
+///
+/// It does not go through tokenization and does not generate
+/// any posting list, nor any field norm.
+/// This is the same as `STORED`, for readability when
+/// declaring a text field.
+pub const TEXT_STORED_ONLY: TextOptions = STORED;
+
 
 impl BitOr for TextOptions {
 
@@ -207,6 +580,17 @@ impl BitOr for TextOptions {
         let mut res = TextOptions::default();
         res.indexing = self.indexing | other.indexing;
         res.stored = self.stored || other.stored;
+        res.fieldnorms = self.fieldnorms && other.fieldnorms;
+        // the product of two large boosts saturates.
+        res.boost = (self.boost * other.boost).min(f32::MAX);
+        res.copy_to = self.copy_to.or(other.copy_to);
+        res.position_gap = max(self.position_gap, other.position_gap);
+        res.tokenizer = self.tokenizer.or(other.tokenizer);
+        res.max_positions_per_doc = match (self.max_positions_per_doc, other.max_positions_per_doc) {
+            (Some(left), Some(right)) => Some(min(left, right)),
+            (left, right) => left.or(right),
+        };
+        res.fast = self.fast || other.fast;
         res
     }
 }
@@ -215,7 +599,146 @@ impl BitOr for TextOptions {
 #[cfg(test)]
 mod tests {
     use schema::*;
+    use rustc_serialize::json;
+    use std::f32;
     
+    #[test]
+    fn test_text_options_raw_json() {
+        let raw_options = TextOptions::default().set_indexing_options(TextIndexingOptions::Raw);
+        assert!(raw_options.get_indexing_options().is_indexed());
+        assert!(!raw_options.get_indexing_options().is_tokenized());
+        let raw_json = json::encode(&raw_options).unwrap();
+        assert_eq!(raw_json, r#"{"indexing":"raw","stored":false}"#);
+        let decoded: TextOptions = json::decode(&raw_json).unwrap();
+        assert_eq!(decoded, raw_options);
+    }
+
+    #[test]
+    fn test_text_options_untokenized_lowercase() {
+        let options = TextOptions::default().set_indexing_options(TextIndexingOptions::UntokenizedLowercase);
+        assert!(options.get_indexing_options().is_indexed());
+        assert!(!options.get_indexing_options().is_tokenized());
+        let options_json = json::encode(&options).unwrap();
+        assert_eq!(options_json, r#"{"indexing":"untokenized_lowercase","stored":false}"#);
+        let decoded: TextOptions = json::decode(&options_json).unwrap();
+        assert_eq!(decoded, options);
+        assert_eq!(
+            TextIndexingOptions::UntokenizedLowercase.untokenized_term(Field(1), "AbC-Ünï"),
+            Term::from_field_text(Field(1), "abc-ünï"));
+        assert_eq!(
+            TextIndexingOptions::Untokenized.untokenized_term(Field(1), "AbC-Ünï"),
+            Term::from_field_text(Field(1), "AbC-Ünï"));
+    }
+
+    #[test]
+    fn test_text_options_fieldnorms_json() {
+        let with_fieldnorms = TEXT;
+        assert!(with_fieldnorms.has_fieldnorms());
+        assert_eq!(json::encode(&with_fieldnorms).unwrap(), r#"{"indexing":"position","stored":false}"#);
+        let without_fieldnorms = TEXT.set_fieldnorms(false);
+        assert!(!without_fieldnorms.has_fieldnorms());
+        let without_fieldnorms_json = json::encode(&without_fieldnorms).unwrap();
+        assert_eq!(without_fieldnorms_json, r#"{"indexing":"position","stored":false,"fieldnorms":false}"#);
+        let decoded: TextOptions = json::decode(&without_fieldnorms_json).unwrap();
+        assert_eq!(decoded, without_fieldnorms);
+        let decoded_legacy: TextOptions = json::decode(r#"{"indexing":"position","stored":false}"#).unwrap();
+        assert!(decoded_legacy.has_fieldnorms());
+        assert!(!STORED.has_fieldnorms());
+    }
+
+    #[test]
+    fn test_text_options_boost_json() {
+        assert_eq!(TEXT.get_boost(), 1f32);
+        let boosted = TEXT.set_boost(3f32);
+        let boosted_json = json::encode(&boosted).unwrap();
+        assert_eq!(boosted_json, r#"{"indexing":"position","stored":false,"boost":3.0}"#);
+        let decoded: TextOptions = json::decode(&boosted_json).unwrap();
+        assert_eq!(decoded.get_boost(), 3f32);
+        assert_eq!(decoded, boosted);
+        assert_eq!((boosted | STORED).get_boost(), 3f32);
+        assert_eq!(TEXT.set_boost(-0f32), TEXT.set_boost(0f32));
+        assert!(json::decode::<TextOptions>(r#"{"indexing":"position","stored":false,"boost":-2.0}"#).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_text_options_nan_boost() {
+        TEXT.set_boost(f32::NAN);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_text_options_negative_boost() {
+        TEXT.set_boost(-1f32);
+    }
+
+    #[test]
+    fn test_text_options_copy_to_json() {
+        let options = TEXT.set_copy_to(Field(2)).set_position_gap(10);
+        let options_json = json::encode(&options).unwrap();
+        assert_eq!(options_json, r#"{"indexing":"position","stored":false,"copy_to":2,"position_gap":10}"#);
+        let decoded: TextOptions = json::decode(&options_json).unwrap();
+        assert_eq!(decoded.get_copy_to(), Some(Field(2)));
+        assert_eq!(decoded.get_position_gap(), 10);
+        assert_eq!(TEXT.get_copy_to(), None);
+        assert_eq!(TEXT.get_position_gap(), 1);
+    }
+
+    #[test]
+    fn test_text_options_tokenizer_json() {
+        assert_eq!(TEXT.get_tokenizer(), "default");
+        let options = TEXT.set_tokenizer("en_stem");
+        let options_json = json::encode(&options).unwrap();
+        assert_eq!(options_json, r#"{"indexing":"position","stored":false,"tokenizer":"en_stem"}"#);
+        let decoded: TextOptions = json::decode(&options_json).unwrap();
+        assert_eq!(decoded.get_tokenizer(), "en_stem");
+        assert_eq!((STORED | options).get_tokenizer(), "en_stem");
+    }
+
+    #[test]
+    fn test_text_options_max_positions_per_doc_json() {
+        assert_eq!(TEXT.get_max_positions_per_doc(), None);
+        let options = TEXT.set_max_positions_per_doc(10_000);
+        let options_json = json::encode(&options).unwrap();
+        assert_eq!(options_json, r#"{"indexing":"position","stored":false,"max_positions_per_doc":10000}"#);
+        let decoded: TextOptions = json::decode(&options_json).unwrap();
+        assert_eq!(decoded.get_max_positions_per_doc(), Some(10_000));
+        assert_eq!((STORED | options.clone()).get_max_positions_per_doc(), Some(10_000));
+        assert_eq!((options | TEXT.set_max_positions_per_doc(100)).get_max_positions_per_doc(), Some(100));
+    }
+
+    #[test]
+    fn test_text_options_fast_json() {
+        assert!(!STRING.is_fast());
+        let options = STRING.set_fast();
+        let options_json = json::encode(&options).unwrap();
+        assert_eq!(options_json, r#"{"indexing":"untokenized","stored":false,"fast":true}"#);
+        let decoded: TextOptions = json::decode(&options_json).unwrap();
+        assert_eq!(decoded, options);
+        assert!((STORED | options).is_fast());
+    }
+
+    #[test]
+    fn test_text_options_payload_json() {
+        let options = TEXT.set_indexing_options(TextIndexingOptions::TokenizedWithFreqPositionAndPayload);
+        let indexing_options = options.get_indexing_options();
+        assert!(indexing_options.is_tokenized());
+        assert!(indexing_options.is_termfreq_enabled());
+        assert!(indexing_options.is_position_enabled());
+        assert!(indexing_options.is_payload_enabled());
+        assert!(!TextIndexingOptions::TokenizedWithFreqAndPosition.is_payload_enabled());
+        let options_json = json::encode(&options).unwrap();
+        assert_eq!(options_json, r#"{"indexing":"payload","stored":false}"#);
+        let decoded: TextOptions = json::decode(&options_json).unwrap();
+        assert_eq!(decoded, options);
+    }
+
+    #[test]
+    fn test_text_stored_only() {
+        assert!(TEXT_STORED_ONLY.is_stored());
+        assert!(!TEXT_STORED_ONLY.get_indexing_options().is_indexed());
+    }
+
     #[test]
     fn test_field_options() {
         {
@@ -226,7 +749,7 @@ mod tests {
         {
             let mut schema_builder = SchemaBuilder::default();
             schema_builder.add_text_field("body", TEXT);
-            let schema = schema_builder.build();
+            let schema = schema_builder.build().unwrap();
             let field = schema.get_field("body").unwrap();
             let field_entry = schema.get_field_entry(field);
             match field_entry.field_type() {
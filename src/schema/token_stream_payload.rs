@@ -0,0 +1,76 @@
+/// A list of tokens produced by an external tokenizer,
+/// each associated to its position.
+///
+/// Pre-tokenized values bypass tantivy's tokenizer : the tokens
+/// are indexed as is, at their given position.
+/// Several tokens may share the same position (e.g. synonyms), and
+/// positions do not need to be contiguous.
+///
+/// The tokens are sorted by position when the payload is created,
+/// so that the positions of a term within a document are increasing.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, RustcEncodable, RustcDecodable)]
+pub struct TokenStreamPayload {
+    tokens: Vec<(String, u32)>,
+}
+
+impl TokenStreamPayload {
+
+    /// Returns the `(text, position)` pairs, sorted by position.
+    pub fn tokens(&self,) -> &[(String, u32)] {
+        &self.tokens
+    }
+
+    /// Returns the text of the tokens, joined by a space.
+    ///
+    /// This is the text that gets stored for pre-tokenized values.
+    pub fn text(&self,) -> String {
+        let texts: Vec<&str> = self.tokens
+            .iter()
+            .map(|&(ref text, _)| text.as_str())
+            .collect();
+        texts.join(" ")
+    }
+
+    /// Returns the number of positions spanned by the tokens,
+    /// that is the last position + 1.
+    pub fn num_positions(&self,) -> u32 {
+        self.tokens
+            .last()
+            .map(|&(_, position)| position + 1)
+            .unwrap_or(0u32)
+    }
+}
+
+impl From<Vec<(String, u32)>> for TokenStreamPayload {
+    fn from(mut tokens: Vec<(String, u32)>) -> TokenStreamPayload {
+        // the sort is stable, so that tokens sharing
+        // the same position keep their order.
+        tokens.sort_by_key(|&(_, position)| position);
+        TokenStreamPayload {
+            tokens: tokens,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_token_stream_payload() {
+        let payload = TokenStreamPayload::from(vec!(
+            (String::from("york"), 2),
+            (String::from("new"), 1),
+            (String::from("nyc"), 1),
+            (String::from("in"), 0),
+        ));
+        assert_eq!(payload.tokens()[0], (String::from("in"), 0));
+        assert_eq!(payload.tokens()[1], (String::from("new"), 1));
+        assert_eq!(payload.tokens()[2], (String::from("nyc"), 1));
+        assert_eq!(payload.text(), "in new nyc york");
+        assert_eq!(payload.num_positions(), 3);
+        assert_eq!(TokenStreamPayload::from(Vec::new()).num_positions(), 0);
+    }
+}
@@ -1,9 +1,17 @@
+use rustc_serialize::Decodable;
+use rustc_serialize::Decoder;
+use rustc_serialize::Encodable;
+use rustc_serialize::Encoder;
+
+
 /// Define how a U32 field should be handled by tantivy.
-#[derive(Clone,Debug,PartialEq,Eq, RustcDecodable, RustcEncodable)]
+#[derive(Clone,Debug,PartialEq,Eq)]
 pub struct U32Options {
     indexed: bool,
     fast: bool,
     stored: bool,
+    cardinality: Cardinality,
+    default_value: u32,
 }
 
 impl U32Options {
@@ -24,6 +32,18 @@ impl U32Options {
         self.fast
     }
     
+    /// Returns the number of values a document
+    /// may have in the fast field.
+    pub fn get_cardinality(&self,) -> Cardinality {
+        self.cardinality
+    }
+
+    /// Returns the value recorded in the fast field
+    /// for the documents that do not have any value.
+    pub fn get_default_value(&self,) -> u32 {
+        self.default_value
+    }
+    
     /// Set the u32 options as stored.
     ///
     /// Only the fields that are set as *stored* are
@@ -52,6 +72,26 @@ impl U32Options {
         self.fast = true;
         self
     }
+    
+    /// Set the cardinality of the fast field.
+    ///
+    /// With `Cardinality::MultiValues`, all of the values
+    /// associated to a document are kept in the fast field.
+    pub fn set_cardinality(mut self, cardinality: Cardinality) -> U32Options {
+        self.cardinality = cardinality;
+        self
+    }
+
+    /// Set the value recorded in the fast field for the
+    /// documents that do not have any value for the field.
+    ///
+    /// Defaults to `0`.
+    /// Multivalued fast fields are not affected, as documents
+    /// without any value are simply associated to no value.
+    pub fn set_default_value(mut self, default_value: u32) -> U32Options {
+        self.default_value = default_value;
+        self
+    }
 }
 
 impl Default for U32Options {
@@ -60,14 +100,122 @@ impl Default for U32Options {
             fast: false,
             indexed: false,
             stored: false,
+            cardinality: Cardinality::SingleValue,
+            default_value: 0u32,
         }
     }    
 }
 
+// The cardinality is only serialized for multivalued fields,
+// and the default value only if it is not 0,
+// so that the schema of existing indexes stays unchanged.
+impl Encodable for U32Options {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        let has_cardinality = self.cardinality != Cardinality::SingleValue;
+        let has_default_value = self.default_value != 0u32;
+        let num_fields = 3 + (has_cardinality as usize) + (has_default_value as usize);
+        s.emit_struct("U32Options", num_fields, |s| {
+            try!(s.emit_struct_field("indexed", 0, |s| self.indexed.encode(s)));
+            try!(s.emit_struct_field("fast", 1, |s| self.fast.encode(s)));
+            try!(s.emit_struct_field("stored", 2, |s| self.stored.encode(s)));
+            if has_cardinality {
+                try!(s.emit_struct_field("cardinality", 3, |s| self.cardinality.encode(s)));
+            }
+            if has_default_value {
+                try!(s.emit_struct_field("default_value", 4, |s| self.default_value.encode(s)));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Decodable for U32Options {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Self, D::Error> {
+        d.read_struct("U32Options", 5, |d| {
+            let indexed = try!(d.read_struct_field("indexed", 0, Decodable::decode));
+            let fast = try!(d.read_struct_field("fast", 1, Decodable::decode));
+            let stored = try!(d.read_struct_field("stored", 2, Decodable::decode));
+            let cardinality: Option<Cardinality> = try!(d.read_struct_field("cardinality", 3, Decodable::decode));
+            let default_value: Option<u32> = try!(d.read_struct_field("default_value", 4, Decodable::decode));
+            Ok(U32Options {
+                indexed: indexed,
+                fast: fast,
+                stored: stored,
+                cardinality: cardinality.unwrap_or(Cardinality::SingleValue), 
+                default_value: default_value.unwrap_or(0u32),
+            })
+        })
+    }
+}
+
+
+/// Number of values a document may have in a fast field.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum Cardinality {
+    /// A document has at most one value.
+    /// If more than one value is given, only the first one is kept.
+    SingleValue,
+    /// A document may have any number of values,
+    /// including none.
+    MultiValues,
+}
+
+impl Encodable for Cardinality {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        let name = match *self {
+            Cardinality::SingleValue => "single",
+            Cardinality::MultiValues => "multi",
+        };
+        s.emit_str(name)
+    }
+}
+
+impl Decodable for Cardinality {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Self, D::Error> {
+        let cardinality_name: String = try!(d.read_str());
+        Ok(match cardinality_name.as_ref() {
+            "single" => Cardinality::SingleValue,
+            "multi" => Cardinality::MultiValues,
+            _ => {
+                return Err(d.error(&format!("Cardinality {:?} unknown", cardinality_name)));
+            }
+        })
+    }
+}
+
 
 /// Shortcut for   
 pub const FAST: U32Options = U32Options {
     indexed: false,
     stored: false,
     fast: true,
+    cardinality: Cardinality::SingleValue,
+    default_value: 0u32,
 };
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rustc_serialize::json;
+
+    #[test]
+    fn test_u32_options_json() {
+        let single_valued = U32Options::default().set_fast();
+        assert_eq!(json::encode(&single_valued).unwrap(), r#"{"indexed":false,"fast":true,"stored":false}"#);
+        let multi_valued = U32Options::default().set_fast().set_cardinality(Cardinality::MultiValues);
+        let multi_valued_json = json::encode(&multi_valued).unwrap();
+        assert_eq!(multi_valued_json, r#"{"indexed":false,"fast":true,"stored":false,"cardinality":"multi"}"#);
+        let single_valued_decoded: U32Options = json::decode(r#"{"indexed":false,"fast":true,"stored":false}"#).unwrap();
+        assert_eq!(single_valued_decoded, single_valued);
+        let multi_valued_decoded: U32Options = json::decode(&multi_valued_json).unwrap();
+        assert_eq!(multi_valued_decoded, multi_valued);
+        let with_default = U32Options::default().set_fast().set_default_value(7u32);
+        let with_default_json = json::encode(&with_default).unwrap();
+        assert_eq!(with_default_json, r#"{"indexed":false,"fast":true,"stored":false,"default_value":7}"#);
+        let with_default_decoded: U32Options = json::decode(&with_default_json).unwrap();
+        assert_eq!(with_default_decoded.get_default_value(), 7u32);
+        assert_eq!(with_default_decoded, with_default);
+    }
+}
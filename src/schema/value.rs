@@ -3,17 +3,96 @@ use common::BinarySerializable;
 use std::io;
 use std::io::Write;
 use std::io::Read;
+use std::cmp::Ordering;
+use common::f64_to_u64;
+use schema::Facet;
+use schema::TokenStreamPayload;
+use schema::ip_to_ipv6;
+use std::net::{IpAddr, Ipv6Addr};
 
 /// Value represents the value of a any field.
 /// It is an enum over all over all of the possible field type.
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, RustcEncodable, RustcDecodable)]
+#[derive(Debug, Clone, RustcEncodable, RustcDecodable)]
 pub enum Value {
     /// The str type is used for any text information.
     Str(String),
     /// Unsigned 32-bits Integer `u32`
     U32(u32),
+    /// Signed 64-bits Integer `i64`
+    I64(i64),
+    /// 64-bits Float `f64`
+    F64(f64),
+    /// Date, expressed as a number of milliseconds since Epoch (UTC)
+    Date(i64),
+    /// Opaque binary payload
+    Bytes(Vec<u8>),
+    /// Path within a hierarchy of categories
+    Facet(Facet),
+    /// Boolean
+    Bool(bool),
+    /// IP address, as the 16 bytes of its IPv6 form
+    /// (see `Value::ip_value`)
+    Ip([u8; 16]),
+    /// Text that has already been tokenized.
+    ///
+    /// Pre-tokenized values are stored as the joined text of
+    /// their tokens, and are therefore retrieved as `Str` values.
+    PreTokenized(TokenStreamPayload),
 }
 
+impl Value {
+    fn type_code(&self) -> u8 {
+        match *self {
+            Value::Str(_) => TEXT_CODE,
+            Value::U32(_) => U32_CODE,
+            Value::I64(_) => I64_CODE,
+            Value::F64(_) => F64_CODE,
+            Value::Date(_) => DATE_CODE,
+            Value::Bytes(_) => BYTES_CODE,
+            Value::Facet(_) => FACET_CODE,
+            Value::Bool(_) => BOOL_CODE,
+            Value::Ip(_) => IP_CODE,
+            Value::PreTokenized(_) => PRE_TOKENIZED_CODE,
+        }
+    }
+}
+
+/// Values are ordered by type first, and then by value.
+///
+/// `f64` are compared according to their order preserving
+/// `u64` representation, so that the order is total.
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (&Value::Str(ref left), &Value::Str(ref right)) => left.cmp(right),
+            (&Value::U32(left), &Value::U32(right)) => left.cmp(&right),
+            (&Value::I64(left), &Value::I64(right)) => left.cmp(&right),
+            (&Value::F64(left), &Value::F64(right)) => f64_to_u64(left).cmp(&f64_to_u64(right)),
+            (&Value::Date(left), &Value::Date(right)) => left.cmp(&right),
+            (&Value::Bytes(ref left), &Value::Bytes(ref right)) => left.cmp(right),
+            (&Value::Facet(ref left), &Value::Facet(ref right)) => left.cmp(right),
+            (&Value::Bool(left), &Value::Bool(right)) => left.cmp(&right),
+            (&Value::Ip(ref left), &Value::Ip(ref right)) => left.cmp(right),
+            (&Value::PreTokenized(ref left), &Value::PreTokenized(ref right)) => left.cmp(right),
+            _ => self.type_code().cmp(&other.type_code()),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
 impl Value {
     /// Returns the text value, provided the value is of the `Str` type.
     ///
@@ -44,6 +123,129 @@ impl Value {
             }
         }
     }
+
+    /// Returns the i64-value, provided the value is of the `I64` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `I64` 
+    pub fn i64_value(&self) -> i64 {
+        match *self {
+            Value::I64(ref value) => {
+               *value
+            }
+            _ => {
+                panic!("This is not an i64 field.")
+            }
+        }
+    }
+
+    /// Returns the f64-value, provided the value is of the `F64` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `F64` 
+    pub fn f64_value(&self) -> f64 {
+        match *self {
+            Value::F64(ref value) => {
+               *value
+            }
+            _ => {
+                panic!("This is not an f64 field.")
+            }
+        }
+    }
+
+    /// Returns the date, expressed in milliseconds since Epoch,
+    /// provided the value is of the `Date` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `Date` 
+    pub fn date_value(&self) -> i64 {
+        match *self {
+            Value::Date(ref value) => {
+               *value
+            }
+            _ => {
+                panic!("This is not a date field.")
+            }
+        }
+    }
+
+    /// Returns the tokens, provided the value is of the `PreTokenized` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `PreTokenized`
+    pub fn pretokenized_value(&self) -> &TokenStreamPayload {
+        match *self {
+            Value::PreTokenized(ref payload) => {
+               payload
+            }
+            _ => {
+                panic!("This is not a pre-tokenized value.")
+            }
+        }
+    }
+
+    /// Returns the bytes, provided the value is of the `Bytes` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `Bytes` 
+    pub fn bytes_value(&self) -> &[u8] {
+        match *self {
+            Value::Bytes(ref value) => {
+               value
+            }
+            _ => {
+                panic!("This is not a bytes field.")
+            }
+        }
+    }
+
+    /// Returns the facet, provided the value is of the `Facet` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `Facet` 
+    pub fn facet_value(&self) -> &Facet {
+        match *self {
+            Value::Facet(ref facet) => {
+               facet
+            }
+            _ => {
+                panic!("This is not a facet field.")
+            }
+        }
+    }
+
+    /// Returns the bool-value, provided the value is of the `Bool` type.
+    ///
+    /// # Panics
+    /// If the value is not of type `Bool` 
+    pub fn bool_value(&self) -> bool {
+        match *self {
+            Value::Bool(value) => {
+               value
+            }
+            _ => {
+                panic!("This is not a bool field.")
+            }
+        }
+    }
+
+    /// Returns the IP address, provided the value is of the `Ip` type.
+    ///
+    /// IPv4 addresses are returned as IPv4-mapped IPv6 addresses.
+    ///
+    /// # Panics
+    /// If the value is not of type `Ip` 
+    pub fn ip_value(&self) -> Ipv6Addr {
+        match *self {
+            Value::Ip(octets) => {
+               Ipv6Addr::from(octets)
+            }
+            _ => {
+                panic!("This is not an IP field.")
+            }
+        }
+    }
 }
 
 impl From<String> for Value {
@@ -59,6 +261,54 @@ impl From<u32> for Value {
     }
 }
 
+impl From<i64> for Value {
+    fn from(v: i64) -> Value {
+        Value::I64(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Value {
+        Value::F64(v)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(bytes: Vec<u8>) -> Value {
+        Value::Bytes(bytes)
+    }
+}
+
+impl From<Facet> for Value {
+    fn from(facet: Facet) -> Value {
+        Value::Facet(facet)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::Bool(v)
+    }
+}
+
+impl From<IpAddr> for Value {
+    fn from(ip: IpAddr) -> Value {
+        Value::Ip(ip_to_ipv6(ip).octets())
+    }
+}
+
+impl From<Ipv6Addr> for Value {
+    fn from(ip: Ipv6Addr) -> Value {
+        Value::Ip(ip.octets())
+    }
+}
+
+impl From<TokenStreamPayload> for Value {
+    fn from(payload: TokenStreamPayload) -> Value {
+        Value::PreTokenized(payload)
+    }
+}
+
 impl<'a> From<&'a str> for Value {
     fn from(s: &'a str) -> Value {
         Value::Str(s.to_string())
@@ -67,6 +317,16 @@ impl<'a> From<&'a str> for Value {
 
 const TEXT_CODE: u8 = 0;
 const U32_CODE: u8 = 1;
+const I64_CODE: u8 = 2;
+const F64_CODE: u8 = 3;
+const DATE_CODE: u8 = 4;
+const BYTES_CODE: u8 = 5;
+const FACET_CODE: u8 = 6;
+const BOOL_CODE: u8 = 7;
+const IP_CODE: u8 = 8;
+// pre-tokenized values are serialized as text.
+// This code is only used to order values.
+const PRE_TOKENIZED_CODE: u8 = 9;
 
 
 impl BinarySerializable for Value {
@@ -77,10 +337,45 @@ impl BinarySerializable for Value {
                 written_size += try!(TEXT_CODE.serialize(writer));
                 written_size += try!(text.serialize(writer));
             },
+            Value::PreTokenized(ref payload) => {
+                written_size += try!(TEXT_CODE.serialize(writer));
+                written_size += try!(payload.text().serialize(writer));
+            },
             Value::U32(ref val) => {
                 written_size += try!(U32_CODE.serialize(writer));
                 written_size += try!(val.serialize(writer));
-            },            
+            },
+            Value::I64(ref val) => {
+                written_size += try!(I64_CODE.serialize(writer));
+                written_size += try!(val.serialize(writer));
+            },
+            Value::F64(ref val) => {
+                written_size += try!(F64_CODE.serialize(writer));
+                written_size += try!(val.to_bits().serialize(writer));
+            },
+            Value::Date(ref val) => {
+                written_size += try!(DATE_CODE.serialize(writer));
+                written_size += try!(val.serialize(writer));
+            },
+            Value::Bytes(ref bytes) => {
+                // bytes are length-prefixed.
+                written_size += try!(BYTES_CODE.serialize(writer));
+                written_size += try!(bytes.serialize(writer));
+            },
+            Value::Facet(ref facet) => {
+                written_size += try!(FACET_CODE.serialize(writer));
+                written_size += try!(facet.serialize(writer));
+            },
+            Value::Bool(val) => {
+                written_size += try!(BOOL_CODE.serialize(writer));
+                written_size += try!((val as u8).serialize(writer));
+            },
+            Value::Ip(ref octets) => {
+                // IP addresses have a fixed size of 16 bytes.
+                written_size += try!(IP_CODE.serialize(writer));
+                try!(writer.write_all(octets));
+                written_size += octets.len();
+            },
         }
         Ok(written_size)
     }
@@ -95,9 +390,93 @@ impl BinarySerializable for Value {
                 let value = try!(u32::deserialize(reader));
                 Ok(Value::U32(value))
             }
+            I64_CODE => {
+                let value = try!(i64::deserialize(reader));
+                Ok(Value::I64(value))
+            }
+            F64_CODE => {
+                let bits = try!(u64::deserialize(reader));
+                Ok(Value::F64(f64::from_bits(bits)))
+            }
+            DATE_CODE => {
+                let value = try!(i64::deserialize(reader));
+                Ok(Value::Date(value))
+            }
+            BYTES_CODE => {
+                let bytes = try!(Vec::<u8>::deserialize(reader));
+                Ok(Value::Bytes(bytes))
+            }
+            FACET_CODE => {
+                let facet = try!(Facet::deserialize(reader));
+                Ok(Value::Facet(facet))
+            }
+            BOOL_CODE => {
+                let value = try!(u8::deserialize(reader));
+                Ok(Value::Bool(value != 0u8))
+            }
+            IP_CODE => {
+                let mut octets = [0u8; 16];
+                try!(reader.read_exact(&mut octets));
+                Ok(Value::Ip(octets))
+            }
             _ => {
                 Err(io::Error::new(io::ErrorKind::InvalidData, format!("No field type is associated with code {:?}", type_code)))
             }
         }      
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use common::BinarySerializable;
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    fn test_value_serialization_helper(value: Value) {
+        let mut buffer: Vec<u8> = Vec::new();
+        value.serialize(&mut buffer).unwrap();
+        let mut cursor = &buffer[..];
+        assert_eq!(Value::deserialize(&mut cursor).unwrap(), value);
+    }
+
+    #[test]
+    fn test_value_serialization() {
+        test_value_serialization_helper(Value::from("abc"));
+        test_value_serialization_helper(Value::U32(3u32));
+        test_value_serialization_helper(Value::I64(-3i64));
+        test_value_serialization_helper(Value::F64(-3.25f64));
+        test_value_serialization_helper(Value::F64(1e300f64));
+        test_value_serialization_helper(Value::Date(-1_000i64));
+        test_value_serialization_helper(Value::Bytes(Vec::new()));
+        test_value_serialization_helper(Value::Bytes(vec!(0u8, 17u8, 255u8)));
+        test_value_serialization_helper(Value::Facet(Facet::from_text("/electronics/phones")));
+        test_value_serialization_helper(Value::Bool(true));
+        test_value_serialization_helper(Value::Bool(false));
+        test_value_serialization_helper(Value::from(IpAddr::from_str("10.0.0.1").unwrap()));
+        test_value_serialization_helper(Value::from(IpAddr::from_str("fe80::1").unwrap()));
+    }
+
+    #[test]
+    fn test_pretokenized_value_serialization() {
+        let payload = TokenStreamPayload::from(vec!((String::from("new"), 0), (String::from("york"), 1)));
+        let mut buffer: Vec<u8> = Vec::new();
+        Value::from(payload).serialize(&mut buffer).unwrap();
+        let mut cursor = &buffer[..];
+        assert_eq!(Value::deserialize(&mut cursor).unwrap(), Value::from("new york"));
+    }
+
+    #[test]
+    fn test_value_order() {
+        assert!(Value::F64(-1.5f64) < Value::F64(0.5f64));
+        assert!(Value::F64(2f64) > Value::F64(0.5f64));
+        assert!(Value::I64(-2i64) < Value::I64(1i64));
+        assert!(Value::U32(100u32) < Value::I64(-1i64));
+        assert!(Value::Bool(false) < Value::Bool(true));
+        assert!(Value::from(IpAddr::from_str("10.0.0.1").unwrap()) < Value::from(IpAddr::from_str("10.0.0.2").unwrap()));
+        assert!(Value::from(IpAddr::from_str("255.0.0.1").unwrap()) < Value::from(IpAddr::from_str("fe80::1").unwrap()));
+        assert_eq!(Value::F64(0.5f64), Value::F64(0.5f64));
+    }
+}
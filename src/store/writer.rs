@@ -1,4 +1,4 @@
-use directory::WritePtr;
+use directory::{self, WritePtr};
 use DocId;
 use schema::FieldValue;
 use common::BinarySerializable;
@@ -84,6 +84,6 @@ impl StoreWriter {
         try!(self.offset_index_writer.write::<Box<Write>>(&mut self.writer));
         try!(header_offset.serialize(&mut self.writer));
         try!(self.doc.serialize(&mut self.writer));
-        self.writer.flush()
+        directory::terminate(&mut self.writer)
     }
 }